@@ -0,0 +1,22 @@
+-- Track token usage, cost and files changed per Claude Code session
+ALTER TABLE claude_code_sessions
+ADD COLUMN input_tokens INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE claude_code_sessions
+ADD COLUMN output_tokens INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE claude_code_sessions
+ADD COLUMN cost_usd REAL NOT NULL DEFAULT 0;
+
+ALTER TABLE claude_code_sessions
+ADD COLUMN files_changed INTEGER NOT NULL DEFAULT 0;
+
+-- Per-session budget limits (NULL = unlimited)
+ALTER TABLE claude_code_config
+ADD COLUMN max_tokens_per_session INTEGER;
+
+ALTER TABLE claude_code_config
+ADD COLUMN max_cost_per_session_usd REAL;
+
+ALTER TABLE claude_code_config
+ADD COLUMN max_files_changed INTEGER;
@@ -0,0 +1,7 @@
+-- Verification commands run in the worktree before a session's branch is pushed
+ALTER TABLE claude_code_config
+ADD COLUMN verification_pipeline TEXT;
+
+-- JSON report of the last verification run for a session
+ALTER TABLE claude_code_sessions
+ADD COLUMN verification_report TEXT;
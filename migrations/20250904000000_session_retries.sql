@@ -0,0 +1,6 @@
+-- Retries run as new sessions linked to the session they follow up on
+ALTER TABLE claude_code_sessions
+ADD COLUMN retry_of_session_id TEXT;
+
+ALTER TABLE claude_code_sessions
+ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
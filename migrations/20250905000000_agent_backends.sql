@@ -0,0 +1,6 @@
+-- JSON-encoded coding agent backend selection, for all sessions and per task
+ALTER TABLE claude_code_config
+ADD COLUMN agent_backend TEXT;
+
+ALTER TABLE claude_code_config
+ADD COLUMN task_agent_backends TEXT;
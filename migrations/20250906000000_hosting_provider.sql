@@ -0,0 +1,3 @@
+-- JSON-encoded Git hosting service (GitHub, GitHub Enterprise, Gitea/Forgejo)
+ALTER TABLE claude_code_config
+ADD COLUMN hosting_provider TEXT;
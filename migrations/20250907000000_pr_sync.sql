@@ -0,0 +1,3 @@
+-- JSON-encoded record of what PR monitoring has mirrored into the task
+ALTER TABLE claude_code_sessions
+ADD COLUMN pr_sync TEXT;
@@ -0,0 +1,11 @@
+-- Diff of each Claude Code session's branch against the base branch, captured
+-- when the session ends. Kept apart from the session row because patches can be large.
+CREATE TABLE IF NOT EXISTS claude_code_session_diffs (
+    session_id TEXT PRIMARY KEY NOT NULL,
+    base_branch TEXT NOT NULL,
+    files TEXT NOT NULL, -- JSON array of per-file addition/deletion counts
+    patch TEXT NOT NULL,
+    truncated BOOLEAN NOT NULL DEFAULT false,
+    captured_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES claude_code_sessions(id) ON DELETE CASCADE
+);
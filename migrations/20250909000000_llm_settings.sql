@@ -0,0 +1,7 @@
+-- Language model provider settings
+ALTER TABLE app_settings ADD COLUMN llm_provider TEXT NOT NULL DEFAULT '"Ollama"';
+ALTER TABLE app_settings ADD COLUMN llm_endpoint TEXT NOT NULL DEFAULT 'http://localhost:11434/api/generate';
+ALTER TABLE app_settings ADD COLUMN llm_model TEXT NOT NULL DEFAULT 'llama3.2:1b';
+ALTER TABLE app_settings ADD COLUMN llm_temperature REAL NOT NULL DEFAULT 0.3;
+ALTER TABLE app_settings ADD COLUMN llm_max_tokens INTEGER NOT NULL DEFAULT 2048;
+ALTER TABLE app_settings ADD COLUMN llm_fixture_directory TEXT;
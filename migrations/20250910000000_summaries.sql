@@ -0,0 +1,10 @@
+-- Generated summaries of tasks, goals and clusters, so they survive restarts.
+-- A summary is only valid for the text it was made from (content_hash).
+CREATE TABLE IF NOT EXISTS summaries (
+    entity_id TEXT NOT NULL,
+    level TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (entity_id, level, content_hash)
+);
@@ -0,0 +1,11 @@
+-- Embedding model for topic clustering; empty uses local TF-IDF vectors
+ALTER TABLE app_settings ADD COLUMN llm_embedding_model TEXT NOT NULL DEFAULT '';
+
+-- Embedding vectors per task, keyed by the model and the text they came from
+CREATE TABLE IF NOT EXISTS task_embeddings (
+    task_id TEXT PRIMARY KEY NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    vector TEXT NOT NULL,  -- JSON array of floats
+    created_at TEXT NOT NULL
+);
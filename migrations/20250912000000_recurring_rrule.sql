@@ -0,0 +1,2 @@
+-- RFC 5545 recurrence rules for recurring templates, with DTSTART and EXDATE lines
+ALTER TABLE recurring_templates ADD COLUMN rrule TEXT;
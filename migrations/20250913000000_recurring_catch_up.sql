@@ -0,0 +1,13 @@
+-- What recurring generation does with missed occurrences, and the occurrences
+-- already generated so none is generated twice
+ALTER TABLE recurring_templates ADD COLUMN catch_up TEXT NOT NULL DEFAULT 'All';
+
+CREATE TABLE IF NOT EXISTS recurring_occurrences (
+    occurrence_key TEXT PRIMARY KEY,
+    template_id TEXT NOT NULL,
+    occurrence_at TEXT NOT NULL,
+    task_id TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_recurring_occurrences_template ON recurring_occurrences(template_id);
@@ -0,0 +1,3 @@
+-- Task blueprints for recurring templates: tags, subtasks, goal, configuration,
+-- due offset and chained child tasks, as JSON
+ALTER TABLE recurring_templates ADD COLUMN blueprint TEXT NOT NULL DEFAULT '{}';
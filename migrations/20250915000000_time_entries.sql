@@ -0,0 +1,17 @@
+-- Tracked and manually logged time, one row per segment of work
+CREATE TABLE IF NOT EXISTS time_entries (
+    id TEXT PRIMARY KEY NOT NULL,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    resource_id TEXT,
+    start_time TEXT NOT NULL,
+    end_time TEXT,
+    description TEXT NOT NULL DEFAULT '',
+    manual INTEGER NOT NULL DEFAULT 0,
+    -- Set once the entry's hours are added to the task's actual hours
+    rolled_up INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_time_entries_task ON time_entries(task_id);
+CREATE INDEX IF NOT EXISTS idx_time_entries_start ON time_entries(start_time);
@@ -0,0 +1,14 @@
+-- Workflow state of tasks with a configuration, and notifications raised
+-- by their state machines
+ALTER TABLE tasks ADD COLUMN workflow_state TEXT;
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id TEXT PRIMARY KEY NOT NULL,
+    resource_id TEXT,
+    task_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    read_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_resource ON notifications(resource_id, read_at);
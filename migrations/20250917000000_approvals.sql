@@ -0,0 +1,29 @@
+-- Approval requests for workflow transitions that need sign-off, and their
+-- audit trail
+CREATE TABLE IF NOT EXISTS approval_requests (
+    id TEXT PRIMARY KEY NOT NULL,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    from_state TEXT NOT NULL,
+    to_state TEXT NOT NULL,
+    role TEXT NOT NULL,
+    quorum INTEGER NOT NULL DEFAULT 1,
+    approvers TEXT NOT NULL DEFAULT '[]',
+    requested_by TEXT,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    resolved_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_requests_task ON approval_requests(task_id);
+CREATE INDEX IF NOT EXISTS idx_approval_requests_status ON approval_requests(status);
+
+CREATE TABLE IF NOT EXISTS approval_events (
+    id TEXT PRIMARY KEY NOT NULL,
+    request_id TEXT NOT NULL REFERENCES approval_requests(id) ON DELETE CASCADE,
+    resource_id TEXT,
+    action TEXT NOT NULL,
+    comment TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_events_request ON approval_events(request_id);
@@ -0,0 +1,25 @@
+-- Typed, indexed copies of task metadata, so numbers and dates can be
+-- sorted and range-filtered and unique fields enforced. tasks.metadata
+-- keeps the text as entered. List values take one row per item. Existing
+-- metadata is indexed from Rust after migrating, typed by its fields
+-- (see task_metadata_repository::index_unindexed_tasks).
+CREATE TABLE IF NOT EXISTS task_metadata (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    field TEXT NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    configuration_id TEXT,
+    value_type TEXT NOT NULL,
+    value_text TEXT NOT NULL,
+    value_number REAL,
+    value_date TEXT,
+    -- Set for fields with a UniqueValue rule
+    unique_key TEXT,
+    PRIMARY KEY (task_id, field, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_metadata_text ON task_metadata(field, value_text);
+CREATE INDEX IF NOT EXISTS idx_task_metadata_number ON task_metadata(field, value_number);
+CREATE INDEX IF NOT EXISTS idx_task_metadata_date ON task_metadata(field, value_date);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_task_metadata_unique
+    ON task_metadata(configuration_id, field, unique_key)
+    WHERE unique_key IS NOT NULL;
@@ -0,0 +1,6 @@
+-- One-off data backfills run from Rust after migrating, recorded here so
+-- they run once per database.
+CREATE TABLE IF NOT EXISTS data_backfills (
+    name TEXT PRIMARY KEY,
+    completed_at TEXT NOT NULL
+);
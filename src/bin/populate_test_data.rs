@@ -79,6 +79,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 None
             },
             configuration_id: None,
+            workflow_state: None,
             sort_order: i as i32,
         };
         
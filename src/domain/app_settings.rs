@@ -49,6 +49,15 @@ pub struct AppSettings {
     pub enable_calendar_sync: bool,
     pub calendar_provider: Option<String>,
     
+    // Language Model Settings (API keys come from the environment)
+    pub llm_provider: LlmProviderKind,
+    pub llm_endpoint: String,
+    pub llm_model: String,
+    pub llm_temperature: f32,
+    pub llm_max_tokens: i32,  // Upper limit per response
+    pub llm_fixture_directory: Option<String>,  // Recorded responses for the Replay provider
+    pub llm_embedding_model: String,  // Empty uses local TF-IDF vectors
+    
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -81,6 +90,53 @@ pub enum SidebarPosition {
     Right,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LlmProviderKind {
+    Ollama,
+    OpenAiCompatible,
+    Anthropic,
+    /// Answers from recorded fixture files, for offline use and tests
+    Replay,
+}
+
+impl LlmProviderKind {
+    pub fn all() -> [LlmProviderKind; 4] {
+        [
+            LlmProviderKind::Ollama,
+            LlmProviderKind::OpenAiCompatible,
+            LlmProviderKind::Anthropic,
+            LlmProviderKind::Replay,
+        ]
+    }
+
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Ollama => "http://localhost:11434/api/generate",
+            LlmProviderKind::OpenAiCompatible => "https://api.openai.com/v1/chat/completions",
+            LlmProviderKind::Anthropic => "https://api.anthropic.com/v1/messages",
+            LlmProviderKind::Replay => "",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Ollama => "llama3.2:1b",
+            LlmProviderKind::OpenAiCompatible => "gpt-4o-mini",
+            LlmProviderKind::Anthropic => "claude-3-5-haiku-latest",
+            LlmProviderKind::Replay => "",
+        }
+    }
+
+    /// Suggested embedding model, for providers that serve embeddings.
+    pub fn default_embedding_model(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Ollama => "nomic-embed-text",
+            LlmProviderKind::OpenAiCompatible => "text-embedding-3-small",
+            LlmProviderKind::Anthropic | LlmProviderKind::Replay => "",
+        }
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         let now = Utc::now();
@@ -129,6 +185,15 @@ impl Default for AppSettings {
             enable_calendar_sync: false,
             calendar_provider: None,
             
+            // Language Model Settings
+            llm_provider: LlmProviderKind::Ollama,
+            llm_endpoint: LlmProviderKind::Ollama.default_endpoint().to_string(),
+            llm_model: LlmProviderKind::Ollama.default_model().to_string(),
+            llm_temperature: 0.3,
+            llm_max_tokens: 2048,
+            llm_fixture_directory: None,
+            llm_embedding_model: String::new(),
+            
             created_at: now,
             updated_at: now,
         }
@@ -174,4 +239,15 @@ impl std::fmt::Display for UiDensity {
             UiDensity::Spacious => write!(f, "Spacious"),
         }
     }
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for LlmProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LlmProviderKind::Ollama => write!(f, "Ollama"),
+            LlmProviderKind::OpenAiCompatible => write!(f, "OpenAI-compatible"),
+            LlmProviderKind::Anthropic => write!(f, "Anthropic"),
+            LlmProviderKind::Replay => write!(f, "Replay (recorded responses)"),
+        }
+    }
+}
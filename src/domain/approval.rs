@@ -0,0 +1,279 @@
+//! Sign-off on workflow transitions guarded by
+//! `TransitionCondition::RequireApproval`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ApprovalStatus {
+    /// Waiting for approvers to decide
+    Pending,
+    /// Quorum met; the transition has not been carried out yet
+    Approved,
+    Rejected,
+    /// The approved transition was carried out
+    Completed,
+    /// The task left the state the transition starts from
+    Cancelled,
+}
+
+/// An entry in a request's audit trail.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ApprovalAction {
+    Requested,
+    Approved,
+    Rejected,
+    Completed,
+    Cancelled,
+}
+
+/// A request for one role to approve moving a task between two states.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRequest {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub from_state: String,
+    pub to_state: String,
+    pub role: String,
+    /// Approvals needed before the transition goes ahead
+    pub quorum: usize,
+    /// Resources holding `role` when the approval was requested
+    pub approvers: Vec<Uuid>,
+    pub requested_by: Option<Uuid>,
+    pub status: ApprovalStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalEvent {
+    pub id: Uuid,
+    pub request_id: Uuid,
+    /// Who acted; `None` for the system
+    pub resource_id: Option<Uuid>,
+    pub action: ApprovalAction,
+    pub comment: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApprovalError {
+    /// Nobody holds the role that must approve
+    NoApprovers(String),
+    NotPending(ApprovalStatus),
+    NotAnApprover,
+    AlreadyDecided,
+}
+
+impl std::fmt::Display for ApprovalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalError::NoApprovers(role) => {
+                write!(f, "No resource has the role '{}' to approve this", role)
+            }
+            ApprovalError::NotPending(status) => {
+                write!(f, "The approval request is no longer pending ({:?})", status)
+            }
+            ApprovalError::NotAnApprover => write!(f, "You are not an approver for this request"),
+            ApprovalError::AlreadyDecided => write!(f, "You have already decided on this request"),
+        }
+    }
+}
+
+impl std::error::Error for ApprovalError {}
+
+impl ApprovalEvent {
+    pub fn new(
+        request_id: Uuid,
+        resource_id: Option<Uuid>,
+        action: ApprovalAction,
+        comment: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            request_id,
+            resource_id,
+            action,
+            comment,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl ApprovalRequest {
+    /// Open a request and its first audit entry. The quorum is capped at
+    /// the number of approvers so it can always be met.
+    pub fn new(
+        task_id: Uuid,
+        from_state: String,
+        to_state: String,
+        role: String,
+        quorum: usize,
+        approvers: Vec<Uuid>,
+        requested_by: Option<Uuid>,
+    ) -> Result<(Self, ApprovalEvent), ApprovalError> {
+        if approvers.is_empty() {
+            return Err(ApprovalError::NoApprovers(role));
+        }
+        let request = Self {
+            id: Uuid::new_v4(),
+            task_id,
+            from_state,
+            to_state,
+            role,
+            quorum: quorum.clamp(1, approvers.len()),
+            approvers,
+            requested_by,
+            status: ApprovalStatus::Pending,
+            created_at: Utc::now(),
+            resolved_at: None,
+        };
+        let event = ApprovalEvent::new(
+            request.id,
+            requested_by,
+            ApprovalAction::Requested,
+            String::new(),
+        );
+        Ok((request, event))
+    }
+
+    pub fn approvals(&self, history: &[ApprovalEvent]) -> usize {
+        history
+            .iter()
+            .filter(|e| e.request_id == self.id && e.action == ApprovalAction::Approved)
+            .count()
+    }
+
+    fn has_decided(&self, resource_id: Uuid, history: &[ApprovalEvent]) -> bool {
+        history.iter().any(|e| {
+            e.request_id == self.id
+                && e.resource_id == Some(resource_id)
+                && matches!(e.action, ApprovalAction::Approved | ApprovalAction::Rejected)
+        })
+    }
+
+    /// Whether the request is waiting on `resource_id`'s decision.
+    pub fn awaits(&self, resource_id: Uuid, history: &[ApprovalEvent]) -> bool {
+        self.status == ApprovalStatus::Pending
+            && self.approvers.contains(&resource_id)
+            && !self.has_decided(resource_id, history)
+    }
+
+    /// Record an approver's decision. One rejection rejects the request;
+    /// it is approved once `quorum` approvers agree.
+    pub fn decide(
+        &mut self,
+        resource_id: Uuid,
+        approve: bool,
+        comment: String,
+        history: &[ApprovalEvent],
+    ) -> Result<ApprovalEvent, ApprovalError> {
+        if self.status != ApprovalStatus::Pending {
+            return Err(ApprovalError::NotPending(self.status));
+        }
+        if !self.approvers.contains(&resource_id) {
+            return Err(ApprovalError::NotAnApprover);
+        }
+        if self.has_decided(resource_id, history) {
+            return Err(ApprovalError::AlreadyDecided);
+        }
+
+        let action = if approve {
+            ApprovalAction::Approved
+        } else {
+            ApprovalAction::Rejected
+        };
+        let event = ApprovalEvent::new(self.id, Some(resource_id), action, comment);
+        if !approve {
+            self.resolve(ApprovalStatus::Rejected);
+        } else if self.approvals(history) + 1 >= self.quorum {
+            self.resolve(ApprovalStatus::Approved);
+        }
+        Ok(event)
+    }
+
+    /// Close the request once its transition has been carried out.
+    pub fn complete(&mut self) -> ApprovalEvent {
+        self.resolve(ApprovalStatus::Completed);
+        ApprovalEvent::new(self.id, None, ApprovalAction::Completed, String::new())
+    }
+
+    /// Withdraw the request; `reason` goes in the audit trail.
+    pub fn cancel(&mut self, reason: String) -> ApprovalEvent {
+        self.resolve(ApprovalStatus::Cancelled);
+        ApprovalEvent::new(self.id, None, ApprovalAction::Cancelled, reason)
+    }
+
+    fn resolve(&mut self, status: ApprovalStatus) {
+        self.status = status;
+        self.resolved_at = Some(Utc::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_and_rejection() {
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (mut request, requested) = ApprovalRequest::new(
+            Uuid::new_v4(),
+            "review".to_string(),
+            "done".to_string(),
+            "Lead".to_string(),
+            2,
+            vec![alice, bob],
+            Some(carol),
+        )
+        .unwrap();
+        let mut history = vec![requested];
+
+        assert!(request.awaits(alice, &history));
+        assert!(!request.awaits(carol, &history));
+        assert_eq!(
+            request.decide(carol, true, String::new(), &history),
+            Err(ApprovalError::NotAnApprover)
+        );
+
+        history.push(request.decide(alice, true, "LGTM".to_string(), &history).unwrap());
+        assert_eq!(request.status, ApprovalStatus::Pending);
+        assert_eq!(
+            request.decide(alice, true, String::new(), &history),
+            Err(ApprovalError::AlreadyDecided)
+        );
+        assert!(!request.awaits(alice, &history));
+
+        history.push(request.decide(bob, true, String::new(), &history).unwrap());
+        assert_eq!(request.status, ApprovalStatus::Approved);
+        assert_eq!(request.approvals(&history), 2);
+
+        let (mut rejected, requested) = ApprovalRequest::new(
+            Uuid::new_v4(),
+            "review".to_string(),
+            "done".to_string(),
+            "Lead".to_string(),
+            2,
+            vec![alice, bob],
+            None,
+        )
+        .unwrap();
+        rejected.decide(bob, false, "Missing tests".to_string(), &[requested]).unwrap();
+        assert_eq!(rejected.status, ApprovalStatus::Rejected);
+
+        assert_eq!(
+            ApprovalRequest::new(
+                Uuid::new_v4(),
+                String::new(),
+                String::new(),
+                "QA".to_string(),
+                1,
+                vec![],
+                None
+            )
+            .unwrap_err(),
+            ApprovalError::NoApprovers("QA".to_string())
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 use uuid::Uuid;
 
+use crate::domain::prompt_template::{CompiledTemplate, TemplateContext, TemplateError};
+use crate::domain::verification::{VerificationPipeline, VerificationReport};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ClaudeCodeSession {
     pub id: Uuid,
@@ -14,12 +18,130 @@ pub struct ClaudeCodeSession {
     pub pr_number: Option<i32>,
     pub session_log: String,
     pub error_message: Option<String>,
+    pub usage: AgentUsage,
+    pub verification: Option<VerificationReport>,
+    /// The failed session this one retries, if any
+    pub retry_of: Option<Uuid>,
+    /// 1 for the first run of a task, incremented on each retry
+    pub attempt: i32,
+    pub pr_sync: PrSyncState,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// What PR monitoring has already mirrored from the hosting service, so each
+/// review, comment and check result reaches the task's comment thread once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PrSyncState {
+    /// Set once the PR was merged or closed and the task moved accordingly
+    pub closed: bool,
+    pub review_ids: Vec<u64>,
+    pub comment_ids: Vec<u64>,
+    /// Last mirrored result per check name, `true` for success
+    pub checks: HashMap<String, bool>,
+    /// Set once the task's workflow refused the move and that was reported
+    pub move_refused: bool,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Which coding agent runs a session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentBackendConfig {
+    /// The `claude` CLI, using the configured model and API key
+    #[default]
+    ClaudeCli,
+    /// Any command run through `sh -c` in the worktree, with the prompt on stdin
+    Shell { command: String },
+}
+
+impl AgentBackendConfig {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ClaudeCli => "Claude Code CLI",
+            Self::Shell { .. } => "Shell command",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Shell { command } if command.trim().is_empty() => {
+                Err("Shell agent backend needs a command".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Git hosting service that pull requests are opened on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostingProvider {
+    /// github.com, or GitHub Enterprise when `api_url` is set (e.g. `https://ghe.example.com/api/v3`)
+    GitHub { api_url: Option<String> },
+    /// A Gitea or Forgejo instance, e.g. `https://codeberg.org`
+    Gitea { base_url: String },
+}
+
+impl Default for HostingProvider {
+    fn default() -> Self {
+        Self::GitHub { api_url: None }
+    }
+}
+
+impl HostingProvider {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::GitHub { .. } => "GitHub",
+            Self::Gitea { .. } => "Gitea / Forgejo",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let url = match self {
+            Self::GitHub { api_url: None } => return Ok(()),
+            Self::GitHub { api_url: Some(url) } | Self::Gitea { base_url: url } => url,
+        };
+        if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(())
+        } else {
+            Err(format!("{} URL must start with http:// or https://", self.label()))
+        }
+    }
+}
+
+/// Prefix of `error_message` for sessions stopped by a budget limit.
+pub const BUDGET_EXCEEDED_PREFIX: &str = "Budget exceeded";
+
+/// Resources consumed by an agent run.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct AgentUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub files_changed: i32,
+}
+
+/// Limits applied to a single session or a whole auto-run. `None` means unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AgentBudget {
+    pub max_duration_minutes: Option<i64>,
+    pub max_tokens: Option<i64>,
+    pub max_cost_usd: Option<f64>,
+    pub max_files_changed: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetExceeded {
+    WallClock { limit_minutes: i64, elapsed_minutes: i64 },
+    Tokens { limit: i64, used: i64 },
+    Cost { limit: f64, spent: f64 },
+    FilesChanged { limit: i32, changed: i32 },
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SessionStatus {
     Pending,
@@ -83,6 +205,15 @@ pub struct ClaudeCodeConfig {
     pub working_directory: Option<String>,
     pub claude_model: String,
     pub max_session_duration_minutes: i32,
+    pub max_tokens_per_session: Option<i64>,
+    pub max_cost_per_session_usd: Option<f64>,
+    pub max_files_changed: Option<i32>,
+    pub verification_pipeline: VerificationPipeline,
+    /// Coding agent used for sessions unless the task has its own entry below
+    pub agent_backend: AgentBackendConfig,
+    /// Per-task backend overrides
+    pub task_agent_backends: HashMap<Uuid, AgentBackendConfig>,
+    pub hosting_provider: HostingProvider,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -111,6 +242,11 @@ impl ClaudeCodeSession {
             pr_number: None,
             session_log: String::new(),
             error_message: None,
+            usage: AgentUsage::default(),
+            verification: None,
+            retry_of: None,
+            attempt: 1,
+            pr_sync: PrSyncState::default(),
             started_at: now,
             completed_at: None,
             created_at: now,
@@ -118,6 +254,41 @@ impl ClaudeCodeSession {
         }
     }
 
+    /// A new session for the same task that continues on `previous`'s branch.
+    pub fn retry_of(previous: &ClaudeCodeSession) -> Self {
+        let mut session = Self::new(previous.task_id);
+        session.branch_name = previous.branch_name.clone();
+        session.retry_of = Some(previous.id);
+        session.attempt = previous.attempt + 1;
+        session
+    }
+
+    /// Markdown appended to the prompt of a retry, describing how this session
+    /// failed: its error, any failing verification output and the changes it
+    /// left behind (`diff_summary`, e.g. from `git diff --stat`).
+    pub fn retry_feedback(&self, diff_summary: &str) -> String {
+        let mut feedback = format!(
+            "## Previous attempt failed\n\n\
+             This is attempt {} at this task. The previous attempt stopped with an error; \
+             its changes are still on the branch. Continue from them and fix the problems below.\n\n",
+            self.attempt + 1
+        );
+        if let Some(error) = &self.error_message {
+            feedback.push_str(&format!("### Error\n\n```\n{}\n```\n\n", error.trim_end()));
+        }
+        if let Some(report) = self.verification.as_ref().filter(|r| !r.passed()) {
+            feedback.push_str("### Failing checks\n\n");
+            feedback.push_str(&report.failure_feedback());
+        }
+        if !diff_summary.trim().is_empty() {
+            feedback.push_str(&format!(
+                "### Changes so far\n\n```\n{}\n```\n",
+                diff_summary.trim_end()
+            ));
+        }
+        feedback
+    }
+
     pub fn update_status(&mut self, status: SessionStatus) {
         self.status = status;
         self.updated_at = Utc::now();
@@ -156,6 +327,181 @@ impl ClaudeCodeSession {
         let elapsed = Utc::now() - self.started_at;
         elapsed.num_minutes() > max_duration_minutes as i64
     }
+
+    /// Time spent so far, or in total once the session has finished.
+    pub fn elapsed(&self) -> chrono::Duration {
+        self.completed_at.unwrap_or_else(Utc::now) - self.started_at
+    }
+
+    pub fn check_budget(&self, budget: &AgentBudget) -> Option<BudgetExceeded> {
+        budget.check(self.elapsed(), &self.usage)
+    }
+
+    pub fn fail_over_budget(&mut self, exceeded: &BudgetExceeded) {
+        self.set_error(format!("{}: {}", BUDGET_EXCEEDED_PREFIX, exceeded));
+    }
+
+    pub fn is_budget_failure(&self) -> bool {
+        self.status == SessionStatus::Failed
+            && self
+                .error_message
+                .as_deref()
+                .is_some_and(|e| e.starts_with(BUDGET_EXCEEDED_PREFIX))
+    }
+}
+
+impl AgentUsage {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    pub fn add(&mut self, other: &AgentUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+        self.files_changed += other.files_changed;
+    }
+
+    /// Extract token and cost figures from agent output.
+    ///
+    /// Understands the JSON result objects printed by `claude --output-format json`
+    /// (`total_cost_usd`, `usage.input_tokens`, ...) as well as plain-text summary
+    /// lines such as `Total cost: $0.42` or `Input tokens: 1,234`. Figures are
+    /// cumulative, so the last value seen wins. `files_changed` is left at zero.
+    pub fn parse_output(output: &str) -> Self {
+        let mut usage = Self::default();
+        for line in output.lines() {
+            usage.update_from_line(line);
+        }
+        usage
+    }
+
+    /// Take in the figures one line of agent output reports, for output
+    /// read as it streams.
+    pub fn update_from_line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.starts_with('{') {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
+                if let Some(cost) = json
+                    .get("total_cost_usd")
+                    .or_else(|| json.get("cost_usd"))
+                    .and_then(|v| v.as_f64())
+                {
+                    self.cost_usd = cost;
+                }
+                if let Some(tokens) = json.get("usage") {
+                    let field = |name: &str| tokens.get(name).and_then(|v| v.as_i64());
+                    if let Some(input) = field("input_tokens") {
+                        self.input_tokens = input
+                            + field("cache_creation_input_tokens").unwrap_or(0)
+                            + field("cache_read_input_tokens").unwrap_or(0);
+                    }
+                    if let Some(output) = field("output_tokens") {
+                        self.output_tokens = output;
+                    }
+                }
+            }
+            return;
+        }
+
+        let lower = line.to_lowercase();
+        if let Some(value) = lower.strip_prefix("total cost:") {
+            if let Ok(cost) = value.trim().trim_start_matches('$').parse::<f64>() {
+                self.cost_usd = cost;
+            }
+        } else if let Some(value) = lower.strip_prefix("input tokens:")
+            && let Some(n) = parse_count(value)
+        {
+            self.input_tokens = n;
+        } else if let Some(value) = lower.strip_prefix("output tokens:")
+            && let Some(n) = parse_count(value)
+        {
+            self.output_tokens = n;
+        }
+    }
+}
+
+fn parse_count(value: &str) -> Option<i64> {
+    value.trim().replace(',', "").parse().ok()
+}
+
+impl AgentBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_duration_minutes.is_none()
+            && self.max_tokens.is_none()
+            && self.max_cost_usd.is_none()
+            && self.max_files_changed.is_none()
+    }
+
+    /// What is left of the token and cost limits after `usage`.
+    pub fn remaining(&self, usage: &AgentUsage) -> AgentBudget {
+        AgentBudget {
+            max_tokens: self.max_tokens.map(|limit| limit - usage.total_tokens()),
+            max_cost_usd: self.max_cost_usd.map(|limit| limit - usage.cost_usd),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the first limit that `elapsed` and `usage` exceed, if any.
+    pub fn check(&self, elapsed: chrono::Duration, usage: &AgentUsage) -> Option<BudgetExceeded> {
+        if let Some(limit) = self.max_duration_minutes
+            && elapsed > chrono::Duration::minutes(limit)
+        {
+            return Some(BudgetExceeded::WallClock {
+                limit_minutes: limit,
+                elapsed_minutes: elapsed.num_minutes(),
+            });
+        }
+        if let Some(limit) = self.max_tokens
+            && usage.total_tokens() > limit
+        {
+            return Some(BudgetExceeded::Tokens {
+                limit,
+                used: usage.total_tokens(),
+            });
+        }
+        if let Some(limit) = self.max_cost_usd
+            && usage.cost_usd > limit
+        {
+            return Some(BudgetExceeded::Cost {
+                limit,
+                spent: usage.cost_usd,
+            });
+        }
+        if let Some(limit) = self.max_files_changed
+            && usage.files_changed > limit
+        {
+            return Some(BudgetExceeded::FilesChanged {
+                limit,
+                changed: usage.files_changed,
+            });
+        }
+        None
+    }
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WallClock {
+                limit_minutes,
+                elapsed_minutes,
+            } => write!(
+                f,
+                "ran for {} minutes (limit {} minutes)",
+                elapsed_minutes, limit_minutes
+            ),
+            Self::Tokens { limit, used } => {
+                write!(f, "used {} tokens (limit {})", used, limit)
+            }
+            Self::Cost { limit, spent } => {
+                write!(f, "spent ${:.2} (limit ${:.2})", spent, limit)
+            }
+            Self::FilesChanged { limit, changed } => {
+                write!(f, "changed {} files (limit {})", changed, limit)
+            }
+        }
+    }
 }
 
 impl ClaudeCodeConfig {
@@ -172,6 +518,13 @@ impl ClaudeCodeConfig {
             working_directory: None,
             claude_model: "claude-3-opus-20240229".to_string(),
             max_session_duration_minutes: 60,
+            max_tokens_per_session: None,
+            max_cost_per_session_usd: None,
+            max_files_changed: None,
+            verification_pipeline: VerificationPipeline::default(),
+            agent_backend: AgentBackendConfig::default(),
+            task_agent_backends: HashMap::new(),
+            hosting_provider: HostingProvider::default(),
             created_at: now,
             updated_at: now,
         }
@@ -190,8 +543,58 @@ impl ClaudeCodeConfig {
         if self.max_session_duration_minutes > 240 {
             return Err("Session duration cannot exceed 4 hours".to_string());
         }
+        if self.max_tokens_per_session.is_some_and(|t| t <= 0) {
+            return Err("Token budget must be positive".to_string());
+        }
+        if self.max_cost_per_session_usd.is_some_and(|c| c <= 0.0) {
+            return Err("Cost budget must be positive".to_string());
+        }
+        if self.max_files_changed.is_some_and(|n| n <= 0) {
+            return Err("Files changed limit must be positive".to_string());
+        }
+        if self.verification_pipeline.steps.iter().any(|s| s.timeout_secs == 0) {
+            return Err("Verification step timeouts must be positive".to_string());
+        }
+        for backend in std::iter::once(&self.agent_backend).chain(self.task_agent_backends.values()) {
+            backend.validate()?;
+        }
+        self.hosting_provider.validate()?;
         Ok(())
     }
+
+    pub fn backend_for_task(&self, task_id: Uuid) -> &AgentBackendConfig {
+        self.task_agent_backends
+            .get(&task_id)
+            .unwrap_or(&self.agent_backend)
+    }
+
+    /// Tighten the per-session limits to `budget` where it is stricter.
+    pub fn limit_sessions_to(&mut self, budget: &AgentBudget) {
+        fn stricter<T: PartialOrd + Copy>(current: Option<T>, limit: Option<T>) -> Option<T> {
+            match (current, limit) {
+                (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+                (a, b) => a.or(b),
+            }
+        }
+        if let Some(minutes) = budget.max_duration_minutes {
+            self.max_session_duration_minutes =
+                self.max_session_duration_minutes.min(minutes.clamp(1, i32::MAX as i64) as i32);
+        }
+        self.max_tokens_per_session = stricter(self.max_tokens_per_session, budget.max_tokens);
+        self.max_cost_per_session_usd =
+            stricter(self.max_cost_per_session_usd, budget.max_cost_usd);
+        self.max_files_changed = stricter(self.max_files_changed, budget.max_files_changed);
+    }
+
+    /// Per-session budget derived from this configuration.
+    pub fn session_budget(&self) -> AgentBudget {
+        AgentBudget {
+            max_duration_minutes: Some(self.max_session_duration_minutes as i64),
+            max_tokens: self.max_tokens_per_session,
+            max_cost_usd: self.max_cost_per_session_usd,
+            max_files_changed: self.max_files_changed,
+        }
+    }
 }
 
 impl ClaudePromptTemplate {
@@ -211,26 +614,37 @@ impl ClaudePromptTemplate {
     }
 
     fn extract_variables(template: &str) -> Vec<String> {
-        let re = regex::Regex::new(r"\{\{(\w+)\}\}").unwrap();
-        let mut vars = Vec::new();
-        for cap in re.captures_iter(template) {
-            if let Some(var) = cap.get(1) {
-                let var_name = var.as_str().to_string();
-                if !vars.contains(&var_name) {
-                    vars.push(var_name);
-                }
-            }
-        }
-        vars
+        CompiledTemplate::parse(template)
+            .map(|compiled| compiled.variables())
+            .unwrap_or_default()
     }
 
-    pub fn render(&self, context: &HashMap<String, String>) -> String {
-        let mut result = self.template.clone();
-        for (key, value) in context.iter() {
-            let placeholder = format!("{{{{{}}}}}", key);
-            result = result.replace(&placeholder, value);
-        }
-        result
+    /// Replace the template body, refreshing the extracted variable list.
+    pub fn set_template(&mut self, template: String) {
+        self.variables = Self::extract_variables(&template);
+        self.template = template;
+        self.updated_at = Utc::now();
+    }
+
+    /// Parse the template, surfacing syntax errors before it is used.
+    pub fn compile(&self) -> Result<CompiledTemplate, TemplateError> {
+        CompiledTemplate::parse(&self.template)
+    }
+
+    /// Lenient render of flat string variables. Unknown variables render
+    /// empty; syntax errors are returned.
+    pub fn render(&self, context: &HashMap<String, String>) -> Result<String, TemplateError> {
+        self.render_with(&TemplateContext::from(context), false)
+    }
+
+    /// Render against a structured context. In strict mode any variable that
+    /// is referenced but not supplied is reported as an error.
+    pub fn render_with(
+        &self,
+        context: &TemplateContext,
+        strict: bool,
+    ) -> Result<String, TemplateError> {
+        self.compile()?.render(context, strict)
     }
 }
 
@@ -297,6 +711,26 @@ mod tests {
         assert!(session.completed_at.is_some());
     }
 
+    #[test]
+    fn test_retry_session_feedback() {
+        let mut first = ClaudeCodeSession::new(Uuid::new_v4());
+        first.branch_name = Some("claude/1234-fix".to_string());
+        first.set_error("Verification failed: test".to_string());
+
+        let retry = ClaudeCodeSession::retry_of(&first);
+        assert_eq!(retry.task_id, first.task_id);
+        assert_eq!(retry.branch_name, first.branch_name);
+        assert_eq!(retry.retry_of, Some(first.id));
+        assert_eq!(retry.attempt, 2);
+        assert_eq!(ClaudeCodeSession::retry_of(&retry).attempt, 3);
+
+        let feedback = first.retry_feedback(" src/main.rs | 3 ++-");
+        assert!(feedback.contains("attempt 2"));
+        assert!(feedback.contains("Verification failed: test"));
+        assert!(feedback.contains("src/main.rs | 3 ++-"));
+        assert!(!first.retry_feedback("").contains("Changes so far"));
+    }
+
     #[test]
     fn test_prompt_template() {
         let template = ClaudePromptTemplate::new(
@@ -313,8 +747,32 @@ mod tests {
         context.insert("task_title".to_string(), "Fix bug".to_string());
         context.insert("priority".to_string(), "High".to_string());
 
-        let rendered = template.render(&context);
+        let rendered = template.render(&context).unwrap();
         assert_eq!(rendered, "Task: Fix bug\nPriority: High");
+
+        let broken = ClaudePromptTemplate::new("broken".to_string(), "{{#if x}}open".to_string());
+        assert!(broken.render(&context).is_err());
+    }
+
+    #[test]
+    fn test_prompt_template_strict_render() {
+        let template = ClaudePromptTemplate::new(
+            "strict".to_string(),
+            "{{task.title | upper}}{{#if goal}} for {{goal.title}}{{/if}}: {{missing}}".to_string(),
+        );
+        assert_eq!(template.variables, vec!["task", "goal", "missing"]);
+
+        let mut context = TemplateContext::new();
+        context.insert(
+            "task",
+            std::collections::BTreeMap::from([("title".to_string(), "fix bug".into())]),
+        );
+
+        assert_eq!(
+            template.render_with(&context, true),
+            Err(TemplateError::MissingVariable("missing".to_string()))
+        );
+        assert_eq!(template.render_with(&context, false).unwrap(), "FIX BUG: ");
     }
 
     #[test]
@@ -331,5 +789,67 @@ mod tests {
 
         config.max_session_duration_minutes = 300;
         assert!(config.validate().is_err());
+
+        config.max_session_duration_minutes = 60;
+        config.max_cost_per_session_usd = Some(0.0);
+        assert!(config.validate().is_err());
+
+        config.max_cost_per_session_usd = None;
+        config.hosting_provider = HostingProvider::Gitea {
+            base_url: "codeberg.org".to_string(),
+        };
+        assert!(config.validate().is_err());
+
+        config.hosting_provider = HostingProvider::Gitea {
+            base_url: "https://codeberg.org".to_string(),
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_agent_usage() {
+        let json_output = r#"Working...
+{"type":"result","total_cost_usd":0.4213,"usage":{"input_tokens":1200,"cache_read_input_tokens":300,"output_tokens":450}}"#;
+        let usage = AgentUsage::parse_output(json_output);
+        assert_eq!(usage.input_tokens, 1500);
+        assert_eq!(usage.output_tokens, 450);
+        assert!((usage.cost_usd - 0.4213).abs() < f64::EPSILON);
+
+        let text_output = "Done\nInput tokens: 12,000\nOutput tokens: 3,400\nTotal cost: $1.25";
+        let usage = AgentUsage::parse_output(text_output);
+        assert_eq!(usage.total_tokens(), 15_400);
+        assert!((usage.cost_usd - 1.25).abs() < f64::EPSILON);
+
+        assert_eq!(AgentUsage::parse_output("no usage here"), AgentUsage::default());
+    }
+
+    #[test]
+    fn test_budget_enforcement() {
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.max_tokens_per_session = Some(10_000);
+        config.max_files_changed = Some(5);
+        let budget = config.session_budget();
+
+        let mut session = ClaudeCodeSession::new(Uuid::new_v4());
+        assert_eq!(session.check_budget(&budget), None);
+
+        session.usage.files_changed = 8;
+        assert_eq!(
+            session.check_budget(&budget),
+            Some(BudgetExceeded::FilesChanged {
+                limit: 5,
+                changed: 8
+            })
+        );
+
+        session.usage.files_changed = 0;
+        session.started_at = Utc::now() - chrono::Duration::minutes(61);
+        let exceeded = session.check_budget(&budget).unwrap();
+        assert!(matches!(exceeded, BudgetExceeded::WallClock { limit_minutes: 60, .. }));
+
+        session.fail_over_budget(&exceeded);
+        assert_eq!(session.status, SessionStatus::Failed);
+        assert!(session.is_budget_failure());
+        assert!(AgentBudget::default().is_unlimited());
     }
 }
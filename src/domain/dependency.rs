@@ -9,7 +9,10 @@ use uuid::Uuid;
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Dependency {
     pub id: Uuid,
+    /// The task that waits. Auto-run, the merge queue and agent prompts all
+    /// read dependencies this way (see `DependencyService::get_dependencies`)
     pub from_task_id: Uuid,
+    /// The task it waits on
     pub to_task_id: Uuid,
     pub dependency_type: DependencyType,
     pub created_at: DateTime<Utc>,
@@ -0,0 +1,234 @@
+//! Formula and relation metadata fields.
+//!
+//! A `Relation` field stores the ids of the tasks or goals it links to,
+//! separated by commas. A `Formula` field is a script expression (see
+//! `domain::script`) recomputed whenever its task changes. Formulas can
+//! read other metadata fields, formulas included, and see the items each
+//! relation field links to as `relations.<field>`: arrays of maps with
+//! `id`, `title`, `status`, `done`, `estimated_hours` and `due_date`.
+//!
+//! Besides Rhai's own functions, formulas can call:
+//!
+//! - `today()`: the current date as `YYYY-MM-DD`.
+//! - `days_between(from, to)`: whole days from one date to another.
+//! - `sum_estimates(items)`, `count_done(items)` and `max_due_date(items)`:
+//!   rollups over a relation's items. `max_due_date` returns `()` when no
+//!   item has a due date.
+
+use crate::domain::goal::{Goal, GoalStatus};
+use crate::domain::task::{Task, TaskStatus};
+use chrono::{DateTime, NaiveDate, Utc};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+/// A task or goal a relation field links to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedItem {
+    pub id: Uuid,
+    pub title: String,
+    pub status: String,
+    pub done: bool,
+    pub estimated_hours: Option<f32>,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+impl From<&Task> for RelatedItem {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id,
+            title: task.title.clone(),
+            status: format!("{:?}", task.status),
+            done: task.status == TaskStatus::Done,
+            estimated_hours: task.estimated_hours,
+            due_date: task.due_date,
+        }
+    }
+}
+
+impl From<&Goal> for RelatedItem {
+    fn from(goal: &Goal) -> Self {
+        Self {
+            id: goal.id,
+            title: goal.title.clone(),
+            status: format!("{:?}", goal.status),
+            done: goal.status == GoalStatus::Completed,
+            estimated_hours: goal.estimated_hours,
+            due_date: goal.target_date,
+        }
+    }
+}
+
+impl RelatedItem {
+    pub(crate) fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".into(), self.id.to_string().into());
+        map.insert("title".into(), self.title.clone().into());
+        map.insert("status".into(), self.status.clone().into());
+        map.insert("done".into(), self.done.into());
+        map.insert(
+            "estimated_hours".into(),
+            self.estimated_hours.map_or(Dynamic::UNIT, |h| f64::from(h).into()),
+        );
+        map.insert(
+            "due_date".into(),
+            self.due_date.map_or(Dynamic::UNIT, |d| d.to_rfc3339().into()),
+        );
+        map
+    }
+}
+
+/// The ids stored in a relation field.
+pub fn relation_ids(value: &str) -> Result<Vec<Uuid>, uuid::Error> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(Uuid::parse_str)
+        .collect()
+}
+
+/// The metadata fields a formula reads, as `metadata.name` or
+/// `metadata["name"]`.
+pub fn references(formula: &str) -> HashSet<String> {
+    let pattern = regex::Regex::new(
+        r#"\bmetadata\s*(?:\.\s*([A-Za-z_][A-Za-z0-9_]*)|\[\s*"([^"]*)"\s*\])"#,
+    )
+    .expect("valid regex");
+    pattern
+        .captures_iter(formula)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Formulas that read each other in a loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaCycle(pub Vec<String>);
+
+impl std::fmt::Display for FormulaCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Formulas depend on each other: {}", self.0.join(", "))
+    }
+}
+
+impl std::error::Error for FormulaCycle {}
+
+/// Order formula fields so each comes after the formulas it reads. Ties
+/// go by name, so the order is stable.
+pub fn evaluation_order(formulas: &HashMap<String, String>) -> Result<Vec<String>, FormulaCycle> {
+    let mut waiting_on: HashMap<&str, HashSet<String>> = formulas
+        .iter()
+        .map(|(name, formula)| {
+            let inputs = references(formula)
+                .into_iter()
+                .filter(|input| formulas.contains_key(input))
+                .collect();
+            (name.as_str(), inputs)
+        })
+        .collect();
+
+    let mut order = Vec::new();
+    let mut ready: BTreeSet<&str> = waiting_on
+        .iter()
+        .filter(|(_, inputs)| inputs.is_empty())
+        .map(|(name, _)| *name)
+        .collect();
+    while let Some(name) = ready.pop_first() {
+        waiting_on.remove(name);
+        for (other, inputs) in waiting_on.iter_mut() {
+            if inputs.remove(name) && inputs.is_empty() {
+                ready.insert(*other);
+            }
+        }
+        order.push(name.to_string());
+    }
+
+    if waiting_on.is_empty() {
+        Ok(order)
+    } else {
+        let mut stuck: Vec<String> = waiting_on.keys().map(|name| name.to_string()).collect();
+        stuck.sort();
+        Err(FormulaCycle(stuck))
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc).date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
+
+fn items(items: Array) -> impl Iterator<Item = Map> {
+    items.into_iter().filter_map(|item| item.try_cast::<Map>())
+}
+
+/// Add the date and rollup functions formulas can call.
+pub(crate) fn register_functions(engine: &mut Engine) {
+    engine.register_fn("today", || Utc::now().date_naive().to_string());
+    engine.register_fn(
+        "days_between",
+        |from: &str, to: &str| -> Result<i64, Box<EvalAltResult>> {
+            match (parse_date(from), parse_date(to)) {
+                (Some(from), Some(to)) => Ok((to - from).num_days()),
+                _ => Err(format!("'{}' or '{}' is not a date", from, to).into()),
+            }
+        },
+    );
+    engine.register_fn("sum_estimates", |related: Array| {
+        items(related)
+            .filter_map(|item| item.get("estimated_hours")?.as_float().ok())
+            .fold(0.0, |total, hours| total + hours)
+    });
+    engine.register_fn("count_done", |related: Array| {
+        items(related)
+            .filter(|item| item.get("done").is_some_and(|d| d.as_bool() == Ok(true)))
+            .count() as i64
+    });
+    engine.register_fn("max_due_date", |related: Array| {
+        items(related)
+            .filter_map(|item| item.get("due_date")?.clone().into_string().ok())
+            .filter_map(|due| DateTime::parse_from_rfc3339(&due).ok())
+            .max()
+            .map_or(Dynamic::UNIT, |due| due.with_timezone(&Utc).to_rfc3339().into())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formulas_are_ordered_by_what_they_read() {
+        assert_eq!(
+            references(r#"metadata.points * 2 + parse_int(metadata["risk level"]) + task.x"#),
+            HashSet::from(["points".to_string(), "risk level".to_string()])
+        );
+
+        let formulas = HashMap::from([
+            ("total".to_string(), "metadata.cost + metadata.tax".to_string()),
+            ("tax".to_string(), "metadata.cost * 0.2".to_string()),
+            ("cost".to_string(), "metadata.hours * metadata.rate".to_string()),
+            ("label".to_string(), "\"fixed\"".to_string()),
+        ]);
+        assert_eq!(
+            evaluation_order(&formulas).unwrap(),
+            ["cost", "label", "tax", "total"]
+        );
+
+        let cyclic = HashMap::from([
+            ("a".to_string(), "metadata.b".to_string()),
+            ("b".to_string(), "metadata.a".to_string()),
+            ("c".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(
+            evaluation_order(&cyclic),
+            Err(FormulaCycle(vec!["a".to_string(), "b".to_string()]))
+        );
+
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(relation_ids(&format!("{}, {},", a, b)).unwrap(), [a, b]);
+        assert!(relation_ids("not-an-id").is_err());
+    }
+}
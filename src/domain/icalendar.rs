@@ -0,0 +1,375 @@
+//! Reading and writing RFC 5545 iCalendar content: components such as
+//! `VCALENDAR`, `VTODO` and `VEVENT` holding properties with parameters.
+
+use std::fmt;
+
+/// Longest content line before folding, in octets
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ICalError {
+    /// A line that isn't `NAME[;PARAM=VALUE]:VALUE`
+    MalformedLine { line: usize },
+    /// `END` without a matching `BEGIN`, or the wrong one
+    UnexpectedEnd { line: usize, name: String },
+    /// A component still open at the end of the input
+    Unclosed(String),
+}
+
+impl fmt::Display for ICalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ICalError::MalformedLine { line } => write!(f, "Malformed iCalendar line {}", line),
+            ICalError::UnexpectedEnd { line, name } => {
+                write!(f, "Unexpected END:{} on iCalendar line {}", name, line)
+            }
+            ICalError::Unclosed(name) => write!(f, "iCalendar component {} is never closed", name),
+        }
+    }
+}
+
+impl std::error::Error for ICalError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    /// The raw value; text values are escaped
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value read as TEXT, with escapes undone.
+    pub fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// The property as it appears in a file, before folding.
+    pub fn content_line(&self) -> String {
+        let params: String = self
+            .params
+            .iter()
+            .map(|(key, value)| format!(";{}={}", key, value))
+            .collect();
+        format!("{}{}:{}", self.name, params, self.value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_ascii_uppercase(),
+            properties: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    /// Add a property with a raw value.
+    pub fn push(&mut self, name: &str, value: impl Into<String>) -> &mut Self {
+        self.push_with_params(name, &[], value)
+    }
+
+    pub fn push_with_params(
+        &mut self,
+        name: &str,
+        params: &[(&str, &str)],
+        value: impl Into<String>,
+    ) -> &mut Self {
+        self.properties.push(Property {
+            name: name.to_ascii_uppercase(),
+            params: params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Add a TEXT property, escaping `text`.
+    pub fn push_text(&mut self, name: &str, text: &str) -> &mut Self {
+        self.push(name, escape_text(text))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> + 'a {
+        self.properties
+            .iter()
+            .filter(move |p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Nested components named `name`, at any depth.
+    pub fn find_all<'a>(&'a self, name: &str) -> Vec<&'a Component> {
+        let mut found = Vec::new();
+        for component in &self.components {
+            if component.name.eq_ignore_ascii_case(name) {
+                found.push(component);
+            }
+            found.extend(component.find_all(name));
+        }
+        found
+    }
+}
+
+/// CRLF-terminated, folded content lines.
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BEGIN:{}\r\n", self.name)?;
+        for property in &self.properties {
+            write!(f, "{}\r\n", fold(&property.content_line()))?;
+        }
+        for component in &self.components {
+            write!(f, "{}", component)?;
+        }
+        write!(f, "END:{}\r\n", self.name)
+    }
+}
+
+/// The top-level components of `text`, usually one `VCALENDAR`.
+pub fn parse(text: &str) -> Result<Vec<Component>, ICalError> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut top = Vec::new();
+
+    for (index, line) in unfold(text).into_iter().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = parse_line(&line).ok_or(ICalError::MalformedLine { line: line_number })?;
+        if property.name == "BEGIN" {
+            stack.push(Component::new(&property.value));
+        } else if property.name == "END" {
+            let component = match stack.pop() {
+                Some(c) if c.name.eq_ignore_ascii_case(&property.value) => c,
+                _ => {
+                    return Err(ICalError::UnexpectedEnd {
+                        line: line_number,
+                        name: property.value,
+                    });
+                }
+            };
+            match stack.last_mut() {
+                Some(parent) => parent.components.push(component),
+                None => top.push(component),
+            }
+        } else if let Some(current) = stack.last_mut() {
+            current.properties.push(property);
+        }
+    }
+
+    match stack.pop() {
+        Some(open) => Err(ICalError::Unclosed(open.name)),
+        None => Ok(top),
+    }
+}
+
+/// Join folded lines: a line starting with a space or tab continues the previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    // The value starts at the first colon outside a quoted parameter
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .map(|p| {
+            let (key, value) = p.split_once('=')?;
+            Some((
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let value = if name == "BEGIN" || name == "END" {
+        value.trim().to_ascii_uppercase()
+    } else {
+        value.to_string()
+    };
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+/// Split `line` into pieces of at most 75 octets, without breaking characters.
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
+
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Split a comma-separated TEXT list such as `CATEGORIES`, honouring escaped commas.
+pub fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            items.push(unescape_text(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    items.push(unescape_text(&current));
+    items.into_iter().filter(|i| !i.trim().is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_parse_round_trip() {
+        let mut calendar = Component::new("VCALENDAR");
+        calendar.push("VERSION", "2.0");
+        let mut todo = Component::new("VTODO");
+        let description = "Columns: id, title; status\nSee \\docs ".repeat(4);
+        todo.push_text("SUMMARY", "Export tasks, CSV; PDF")
+            .push_text("DESCRIPTION", &description)
+            .push_with_params("DUE", &[("VALUE", "DATE")], "20250301");
+        calendar.components.push(todo);
+
+        let text = calendar.to_string();
+        assert!(text.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\n"));
+        assert!(
+            text.lines()
+                .all(|l| l.trim_end_matches('\r').len() <= MAX_LINE_OCTETS)
+        );
+        assert!(text.contains("SUMMARY:Export tasks\\, CSV\\; PDF"));
+
+        let parsed = parse(&text).unwrap();
+        assert_eq!(parsed, vec![calendar]);
+        let todo = &parsed[0].find_all("vtodo")[0];
+        assert_eq!(
+            todo.get("summary").unwrap().text(),
+            "Export tasks, CSV; PDF"
+        );
+        assert_eq!(todo.get("DESCRIPTION").unwrap().text(), description);
+        assert_eq!(todo.get("DUE").unwrap().param("value"), Some("DATE"));
+    }
+
+    #[test]
+    fn test_parse_quirks_and_errors() {
+        let text = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;TZID=\"Europe/Paris:1\":20250301T090000\n\
+                    CATEGORIES:ops,on\\,call\nSUMMARY:Rota\n  tion\nEND:VEVENT\nEND:VCALENDAR\n";
+        let calendar = &parse(text).unwrap()[0];
+        let event = &calendar.components[0];
+        assert_eq!(event.get("DTSTART").unwrap().value, "20250301T090000");
+        assert_eq!(
+            event.get("DTSTART").unwrap().param("TZID"),
+            Some("Europe/Paris:1")
+        );
+        assert_eq!(event.get("SUMMARY").unwrap().text(), "Rota tion");
+        assert_eq!(
+            split_text_list(&event.get("CATEGORIES").unwrap().value),
+            ["ops", "on,call"]
+        );
+
+        assert_eq!(
+            parse("BEGIN:VCALENDAR\nEND:VTODO"),
+            Err(ICalError::UnexpectedEnd {
+                line: 2,
+                name: "VTODO".to_string()
+            })
+        );
+        assert_eq!(
+            parse("BEGIN:VCALENDAR\n"),
+            Err(ICalError::Unclosed("VCALENDAR".to_string()))
+        );
+        assert_eq!(
+            parse("BEGIN:VCALENDAR\nnot a property\n"),
+            Err(ICalError::MalformedLine { line: 2 })
+        );
+    }
+}
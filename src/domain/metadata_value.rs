@@ -0,0 +1,286 @@
+//! Typed metadata values.
+//!
+//! Tasks keep their metadata as text, the way it's entered and shown. A
+//! configured field's `FieldType` says how to read that text, and the typed
+//! values are what the `task_metadata` table indexes, so queries can sort
+//! and range-filter numbers and dates and enforce unique values.
+
+use crate::domain::task_config::FieldType;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Text(String),
+    /// Numbers, decimals, percentages (without the `%`) and durations (in
+    /// hours)
+    Number(f64),
+    /// An amount of money; the currency symbol or code isn't kept
+    Currency(f64),
+    Date(NaiveDate),
+    DateTime(DateTime<Utc>),
+    Boolean(bool),
+    /// Multi-select options or relation ids
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidMetadataValue {
+    /// What the field takes, e.g. "a number"
+    pub expected: &'static str,
+    pub value: String,
+}
+
+impl std::fmt::Display for InvalidMetadataValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not {}", self.value, self.expected)
+    }
+}
+
+impl std::error::Error for InvalidMetadataValue {}
+
+impl std::fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataValue::Text(text) => write!(f, "{}", text),
+            MetadataValue::Number(number) | MetadataValue::Currency(number) => {
+                write!(f, "{}", number)
+            }
+            MetadataValue::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            MetadataValue::DateTime(time) => write!(f, "{}", time.to_rfc3339()),
+            MetadataValue::Boolean(value) => write!(f, "{}", value),
+            MetadataValue::List(items) => write!(f, "{}", items.join(", ")),
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+fn parse_date_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            // What a datetime-local input produces
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+                .ok()
+                .map(|d| d.and_utc())
+        })
+        .or_else(|| parse_date(value)?.and_hms_opt(0, 0, 0).map(|d| d.and_utc()))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|d| d.date_naive()))
+}
+
+/// "$1,200.50", "1200.50 EUR" or "€1200" as 1200.5.
+fn parse_currency(value: &str) -> Option<f64> {
+    let symbols = |c: char| c.is_ascii_uppercase() || "$€£¥₹".contains(c) || c.is_whitespace();
+    parse_number(&value.trim_matches(symbols).replace(',', ""))
+}
+
+/// "1.5", "90m", "1h 30m" or "2d" as hours; a day is eight hours.
+fn parse_duration(value: &str) -> Option<f64> {
+    if let Some(hours) = parse_number(value) {
+        return Some(hours);
+    }
+    let mut hours = 0.0;
+    for part in value.split_whitespace() {
+        let (amount, unit) = part.split_at(part.char_indices().last()?.0);
+        let amount = parse_number(amount)?;
+        hours += match unit {
+            "d" => amount * 8.0,
+            "h" => amount,
+            "m" => amount / 60.0,
+            _ => return None,
+        };
+    }
+    (!value.trim().is_empty()).then_some(hours)
+}
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl MetadataValue {
+    /// Read a field's text as its type. Formulas are numbers or dates when
+    /// they look like one and text otherwise.
+    pub fn parse(field_type: FieldType, value: &str) -> Result<Self, InvalidMetadataValue> {
+        let invalid = |expected| InvalidMetadataValue {
+            expected,
+            value: value.to_string(),
+        };
+        match field_type {
+            FieldType::Text
+            | FieldType::LongText
+            | FieldType::Select
+            | FieldType::Url
+            | FieldType::Email
+            | FieldType::Phone
+            | FieldType::User
+            | FieldType::Attachment => Ok(MetadataValue::Text(value.to_string())),
+            FieldType::Number | FieldType::Decimal => parse_number(value)
+                .map(MetadataValue::Number)
+                .ok_or_else(|| invalid("a number")),
+            FieldType::Percentage => parse_number(value.trim().trim_end_matches('%'))
+                .map(MetadataValue::Number)
+                .ok_or_else(|| invalid("a percentage")),
+            FieldType::Duration => parse_duration(value)
+                .map(MetadataValue::Number)
+                .ok_or_else(|| invalid("a duration such as 1h 30m")),
+            FieldType::Currency => parse_currency(value)
+                .map(MetadataValue::Currency)
+                .ok_or_else(|| invalid("an amount")),
+            FieldType::Date => parse_date(value)
+                .map(MetadataValue::Date)
+                .ok_or_else(|| invalid("a date")),
+            FieldType::DateTime => parse_date_time(value)
+                .map(MetadataValue::DateTime)
+                .ok_or_else(|| invalid("a date and time")),
+            FieldType::Boolean => parse_boolean(value)
+                .map(MetadataValue::Boolean)
+                .ok_or_else(|| invalid("yes or no")),
+            FieldType::MultiSelect => Ok(MetadataValue::List(list(value))),
+            FieldType::Relation => {
+                let ids = list(value);
+                if ids.iter().all(|id| Uuid::parse_str(id).is_ok()) {
+                    Ok(MetadataValue::List(ids))
+                } else {
+                    Err(invalid("a list of task or goal ids"))
+                }
+            }
+            FieldType::Formula => Ok(parse_number(value)
+                .map(MetadataValue::Number)
+                .or_else(|| parse_date_time(value).map(MetadataValue::DateTime))
+                .unwrap_or_else(|| MetadataValue::Text(value.to_string()))),
+        }
+    }
+
+    /// What kind of value this is, as stored in `task_metadata`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MetadataValue::Text(_) => "Text",
+            MetadataValue::Number(_) => "Number",
+            MetadataValue::Currency(_) => "Currency",
+            MetadataValue::Date(_) => "Date",
+            MetadataValue::DateTime(_) => "DateTime",
+            MetadataValue::Boolean(_) => "Boolean",
+            MetadataValue::List(_) => "List",
+        }
+    }
+
+    /// Read back a value stored as `kind`.
+    pub fn from_kind(kind: &str, value: &str) -> Result<Self, InvalidMetadataValue> {
+        let field_type = match kind {
+            "Number" => FieldType::Decimal,
+            "Currency" => FieldType::Currency,
+            "Date" => FieldType::Date,
+            "DateTime" => FieldType::DateTime,
+            "Boolean" => FieldType::Boolean,
+            "List" => FieldType::MultiSelect,
+            _ => FieldType::Text,
+        };
+        Self::parse(field_type, value)
+    }
+
+    /// The value as a number, for sorting and range filters. Yes is 1.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            MetadataValue::Number(number) | MetadataValue::Currency(number) => Some(*number),
+            MetadataValue::Boolean(value) => Some(if *value { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// Dates and times as fixed-width UTC text, which sorts in time order.
+    pub fn as_sortable_date(&self) -> Option<String> {
+        let time = match self {
+            MetadataValue::Date(date) => date.and_hms_opt(0, 0, 0)?.and_utc(),
+            MetadataValue::DateTime(time) => *time,
+            _ => return None,
+        };
+        Some(time.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+    }
+
+    /// The items a list holds, or the value itself.
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            MetadataValue::List(items) => items.clone(),
+            other => vec![other.to_string()],
+        }
+    }
+
+    /// What two values must not share in a unique field: text ignoring case
+    /// and surrounding space, anything else as written by `Display`.
+    pub fn unique_key(&self) -> String {
+        match self {
+            MetadataValue::Text(text) => text.trim().to_lowercase(),
+            other => other.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_read_by_field_type() {
+        let parse = MetadataValue::parse;
+        assert_eq!(parse(FieldType::Number, " 8 "), Ok(MetadataValue::Number(8.0)));
+        assert_eq!(parse(FieldType::Percentage, "75%"), Ok(MetadataValue::Number(75.0)));
+        assert_eq!(parse(FieldType::Duration, "1h 30m"), Ok(MetadataValue::Number(1.5)));
+        assert_eq!(parse(FieldType::Currency, "$1,200.50"), Ok(MetadataValue::Currency(1200.5)));
+        assert_eq!(parse(FieldType::Currency, "99 EUR"), Ok(MetadataValue::Currency(99.0)));
+        assert_eq!(
+            parse(FieldType::Date, "2025-03-01"),
+            Ok(MetadataValue::Date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()))
+        );
+        assert_eq!(
+            parse(FieldType::DateTime, "2025-03-01T09:30").unwrap().as_sortable_date(),
+            Some("2025-03-01T09:30:00Z".to_string())
+        );
+        assert_eq!(parse(FieldType::Boolean, "Yes"), Ok(MetadataValue::Boolean(true)));
+        assert_eq!(
+            parse(FieldType::MultiSelect, "ios, web,"),
+            Ok(MetadataValue::List(vec!["ios".to_string(), "web".to_string()]))
+        );
+        assert_eq!(parse(FieldType::Formula, "12.5"), Ok(MetadataValue::Number(12.5)));
+        assert_eq!(
+            parse(FieldType::Formula, "1/2"),
+            Ok(MetadataValue::Text("1/2".to_string()))
+        );
+
+        assert_eq!(
+            parse(FieldType::Number, "eight").unwrap_err().to_string(),
+            "'eight' is not a number"
+        );
+        assert!(parse(FieldType::Currency, "lots").is_err());
+        assert!(parse(FieldType::Date, "next week").is_err());
+        assert!(parse(FieldType::Duration, "2 weeks").is_err());
+        assert!(parse(FieldType::Relation, "not-an-id").is_err());
+
+        let stored = parse(FieldType::Currency, "€1200").unwrap();
+        assert_eq!(MetadataValue::from_kind(stored.kind(), &stored.to_string()), Ok(stored));
+        assert_eq!(MetadataValue::Text(" PAY-1 ".to_string()).unique_key(), "pay-1");
+    }
+}
@@ -1,13 +1,25 @@
 pub mod app_settings;
+pub mod approval;
 pub mod claude_code;
 pub mod comment;
 pub mod dependency;
+pub mod formula;
 pub mod goal;
+pub mod icalendar;
 pub mod metadata;
+pub mod metadata_value;
+pub mod notification;
+pub mod plan;
+pub mod prompt_template;
 pub mod recurring;
 pub mod resource;
+pub mod rrule;
+pub mod script;
+pub mod session_diff;
 pub mod task;
 pub mod task_config;
+pub mod time_entry;
+pub mod verification;
 
 #[cfg(test)]
 mod goal_tests;
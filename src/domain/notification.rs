@@ -0,0 +1,33 @@
+//! Messages for resources raised by task workflows.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Notification {
+    pub id: Uuid,
+    /// Who it is for; `None` for everyone
+    pub resource_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    pub fn new(resource_id: Option<Uuid>, task_id: Option<Uuid>, message: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            resource_id,
+            task_id,
+            message,
+            created_at: Utc::now(),
+            read_at: None,
+        }
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+}
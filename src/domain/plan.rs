@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A proposed breakdown of one task, as returned by the planner and edited in
+/// the preview before anything is saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TaskPlan {
+    /// Checklist items added to the task itself
+    #[serde(default)]
+    pub subtasks: Vec<String>,
+    /// New child tasks of the planned task
+    #[serde(default)]
+    pub tasks: Vec<PlannedTask>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PlannedTask {
+    /// Short identifier other planned tasks use in `depends_on`
+    #[serde(default)]
+    pub key: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub estimated_hours: Option<f32>,
+    /// Keys of planned tasks that must finish before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanError {
+    /// The planner's output contained no JSON object
+    NoPlan,
+    InvalidJson(String),
+    Empty,
+    MissingTitle(String),
+    DuplicateKey(String),
+    UnknownDependency { task: String, depends_on: String },
+    Cycle(Vec<String>),
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::NoPlan => write!(f, "The planner did not return a JSON plan"),
+            PlanError::InvalidJson(message) => write!(f, "The plan is not valid: {}", message),
+            PlanError::Empty => write!(f, "The plan has no subtasks or tasks"),
+            PlanError::MissingTitle(key) => write!(f, "Planned task '{}' has no title", key),
+            PlanError::DuplicateKey(key) => {
+                write!(f, "More than one planned task uses the key '{}'", key)
+            }
+            PlanError::UnknownDependency { task, depends_on } => write!(
+                f,
+                "Planned task '{}' depends on '{}', which is not in the plan",
+                task, depends_on
+            ),
+            PlanError::Cycle(keys) => {
+                write!(f, "Planned tasks depend on each other in a cycle: {}", keys.join(" → "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl TaskPlan {
+    /// Read a plan from model output. Models often wrap JSON in prose or a
+    /// code fence, so everything outside the outermost braces is ignored.
+    pub fn parse(output: &str) -> Result<Self, PlanError> {
+        let start = output.find('{').ok_or(PlanError::NoPlan)?;
+        let end = output.rfind('}').ok_or(PlanError::NoPlan)?;
+        if end < start {
+            return Err(PlanError::NoPlan);
+        }
+        let mut plan: TaskPlan = serde_json::from_str(&output[start..=end])
+            .map_err(|e| PlanError::InvalidJson(e.to_string()))?;
+        plan.normalize();
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    /// Trim text, drop blank subtasks and give unkeyed tasks a key.
+    pub fn normalize(&mut self) {
+        self.subtasks = self
+            .subtasks
+            .iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        for (i, task) in self.tasks.iter_mut().enumerate() {
+            task.key = task.key.trim().to_string();
+            if task.key.is_empty() {
+                task.key = format!("task-{}", i + 1);
+            }
+            task.title = task.title.trim().to_string();
+            task.depends_on = task
+                .depends_on
+                .iter()
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty())
+                .collect();
+            task.estimated_hours = task.estimated_hours.filter(|h| *h > 0.0);
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PlanError> {
+        if self.subtasks.is_empty() && self.tasks.is_empty() {
+            return Err(PlanError::Empty);
+        }
+
+        let mut keys = HashSet::new();
+        for task in &self.tasks {
+            if task.title.trim().is_empty() {
+                return Err(PlanError::MissingTitle(task.key.clone()));
+            }
+            if !keys.insert(task.key.as_str()) {
+                return Err(PlanError::DuplicateKey(task.key.clone()));
+            }
+        }
+        for task in &self.tasks {
+            if let Some(unknown) = task.depends_on.iter().find(|d| !keys.contains(d.as_str())) {
+                return Err(PlanError::UnknownDependency {
+                    task: task.key.clone(),
+                    depends_on: unknown.clone(),
+                });
+            }
+        }
+
+        self.creation_order().map(|_| ())
+    }
+
+    /// Indices of `tasks` with every task after the tasks it depends on.
+    pub fn creation_order(&self) -> Result<Vec<usize>, PlanError> {
+        let index: HashMap<&str, usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.key.as_str(), i))
+            .collect();
+
+        // 0 = unvisited, 1 = on the current path, 2 = done
+        let mut state = vec![0u8; self.tasks.len()];
+        let mut order = Vec::with_capacity(self.tasks.len());
+        fn visit(
+            plan: &TaskPlan,
+            index: &HashMap<&str, usize>,
+            i: usize,
+            state: &mut [u8],
+            path: &mut Vec<String>,
+            order: &mut Vec<usize>,
+        ) -> Result<(), PlanError> {
+            match state[i] {
+                2 => return Ok(()),
+                1 => {
+                    let key = &plan.tasks[i].key;
+                    let start = path.iter().position(|k| k == key).unwrap_or(0);
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(key.clone());
+                    return Err(PlanError::Cycle(cycle));
+                }
+                _ => {}
+            }
+            state[i] = 1;
+            path.push(plan.tasks[i].key.clone());
+            for dependency in &plan.tasks[i].depends_on {
+                if let Some(&j) = index.get(dependency.as_str()) {
+                    visit(plan, index, j, state, path, order)?;
+                }
+            }
+            path.pop();
+            state[i] = 2;
+            order.push(i);
+            Ok(())
+        }
+
+        for i in 0..self.tasks.len() {
+            visit(self, &index, i, &mut state, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
+    }
+
+    pub fn total_estimated_hours(&self) -> f32 {
+        self.tasks.iter().filter_map(|t| t.estimated_hours).sum()
+    }
+
+    /// Remove a planned task and any dependencies on it.
+    pub fn remove_task(&mut self, key: &str) {
+        self.tasks.retain(|t| t.key != key);
+        for task in &mut self.tasks {
+            task.depends_on.retain(|d| d != key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"Here is the plan:
+
+```json
+{
+  "subtasks": ["Read the existing exporter", "  "],
+  "tasks": [
+    {"key": "schema", "title": "Design the schema", "estimated_hours": 2},
+    {"key": "api", "title": "Build the API", "description": "REST endpoints", "estimated_hours": 5, "depends_on": ["schema"]},
+    {"title": "Write docs", "estimated_hours": 0, "depends_on": ["api", "schema"]}
+  ]
+}
+```
+"#;
+
+    #[test]
+    fn test_parse_plan_from_model_output() {
+        let plan = TaskPlan::parse(OUTPUT).unwrap();
+        assert_eq!(plan.subtasks, vec!["Read the existing exporter"]);
+        assert_eq!(plan.tasks.len(), 3);
+        assert_eq!(plan.tasks[1].description, "REST endpoints");
+        assert_eq!(plan.tasks[2].key, "task-3");
+        assert_eq!(plan.tasks[2].estimated_hours, None);
+        assert_eq!(plan.total_estimated_hours(), 7.0);
+        assert_eq!(plan.creation_order().unwrap(), vec![0, 1, 2]);
+
+        assert_eq!(TaskPlan::parse("I can't plan this"), Err(PlanError::NoPlan));
+        assert!(matches!(TaskPlan::parse("{\"tasks\": 3}"), Err(PlanError::InvalidJson(_))));
+        assert_eq!(TaskPlan::parse("{}"), Err(PlanError::Empty));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_dependencies() {
+        let task = |key: &str, depends_on: &[&str]| PlannedTask {
+            key: key.to_string(),
+            title: key.to_uppercase(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let mut plan = TaskPlan {
+            subtasks: Vec::new(),
+            tasks: vec![task("a", &["c"]), task("b", &["a"]), task("c", &["b"])],
+        };
+        assert_eq!(
+            plan.validate(),
+            Err(PlanError::Cycle(vec![
+                "a".to_string(),
+                "c".to_string(),
+                "b".to_string(),
+                "a".to_string()
+            ]))
+        );
+
+        plan.remove_task("c");
+        assert_eq!(plan.validate(), Ok(()));
+        assert_eq!(plan.tasks[0].depends_on, Vec::<String>::new());
+
+        plan.tasks.push(task("d", &["missing"]));
+        assert_eq!(
+            plan.validate(),
+            Err(PlanError::UnknownDependency {
+                task: "d".to_string(),
+                depends_on: "missing".to_string()
+            })
+        );
+
+        plan.tasks[2] = task("a", &[]);
+        assert_eq!(plan.validate(), Err(PlanError::DuplicateKey("a".to_string())));
+    }
+}
@@ -0,0 +1,934 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// A value that can be referenced from a prompt template.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TemplateValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<TemplateValue>),
+    Object(BTreeMap<String, TemplateValue>),
+}
+
+/// Root variables available to a template render.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TemplateContext {
+    values: BTreeMap<String, TemplateValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    Syntax { message: String, position: usize },
+    MissingVariable(String),
+    UnknownFilter(String),
+    InvalidFilterArgument { filter: String, argument: String },
+    NotRenderable(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateError::Syntax { message, position } => {
+                write!(
+                    f,
+                    "Template syntax error at offset {}: {}",
+                    position, message
+                )
+            }
+            TemplateError::MissingVariable(name) => {
+                write!(f, "Missing template variable: {}", name)
+            }
+            TemplateError::UnknownFilter(name) => write!(f, "Unknown template filter: {}", name),
+            TemplateError::InvalidFilterArgument { filter, argument } => {
+                write!(f, "Invalid argument '{}' for filter '{}'", argument, filter)
+            }
+            TemplateError::NotRenderable(name) => {
+                write!(
+                    f,
+                    "Variable '{}' is an object and cannot be rendered directly",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl TemplateValue {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            TemplateValue::Null => false,
+            TemplateValue::Bool(b) => *b,
+            TemplateValue::Number(n) => *n != 0.0,
+            TemplateValue::String(s) => !s.is_empty(),
+            TemplateValue::List(items) => !items.is_empty(),
+            TemplateValue::Object(map) => !map.is_empty(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TemplateValue> {
+        match self {
+            TemplateValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    fn to_text(&self, name: &str) -> Result<String, TemplateError> {
+        match self {
+            TemplateValue::Null => Ok(String::new()),
+            TemplateValue::Bool(b) => Ok(b.to_string()),
+            TemplateValue::Number(n) => Ok(format_number(*n)),
+            TemplateValue::String(s) => Ok(s.clone()),
+            TemplateValue::List(items) => Ok(items
+                .iter()
+                .map(|item| item.to_text(name))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", ")),
+            TemplateValue::Object(_) => Err(TemplateError::NotRenderable(name.to_string())),
+        }
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        TemplateValue::String(value.to_string())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        TemplateValue::String(value)
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(value: bool) -> Self {
+        TemplateValue::Bool(value)
+    }
+}
+
+impl From<f64> for TemplateValue {
+    fn from(value: f64) -> Self {
+        TemplateValue::Number(value)
+    }
+}
+
+impl From<f32> for TemplateValue {
+    fn from(value: f32) -> Self {
+        TemplateValue::Number(value as f64)
+    }
+}
+
+impl From<i64> for TemplateValue {
+    fn from(value: i64) -> Self {
+        TemplateValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for TemplateValue {
+    fn from(value: usize) -> Self {
+        TemplateValue::Number(value as f64)
+    }
+}
+
+impl From<DateTime<Utc>> for TemplateValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        TemplateValue::String(value.to_rfc3339())
+    }
+}
+
+impl<T: Into<TemplateValue>> From<Option<T>> for TemplateValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(TemplateValue::Null)
+    }
+}
+
+impl<T: Into<TemplateValue>> From<Vec<T>> for TemplateValue {
+    fn from(value: Vec<T>) -> Self {
+        TemplateValue::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<BTreeMap<String, TemplateValue>> for TemplateValue {
+    fn from(value: BTreeMap<String, TemplateValue>) -> Self {
+        TemplateValue::Object(value)
+    }
+}
+
+impl From<&HashMap<String, String>> for TemplateValue {
+    fn from(value: &HashMap<String, String>) -> Self {
+        TemplateValue::Object(
+            value
+                .iter()
+                .map(|(k, v)| (k.clone(), TemplateValue::String(v.clone())))
+                .collect(),
+        )
+    }
+}
+
+impl TemplateContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<TemplateValue>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TemplateValue> {
+        self.values.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.values.keys()
+    }
+}
+
+impl From<&HashMap<String, String>> for TemplateContext {
+    fn from(map: &HashMap<String, String>) -> Self {
+        let mut context = TemplateContext::new();
+        for (key, value) in map {
+            context.insert(key.clone(), value.clone());
+        }
+        context
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    name: String,
+    argument: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Expression {
+    path: String,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Expr(Expression),
+    If {
+        path: String,
+        negate: bool,
+        then_branch: Vec<Node>,
+        else_branch: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+        else_branch: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Tag { content: String, position: usize },
+}
+
+impl Token {
+    fn is_block_tag(&self) -> bool {
+        match self {
+            Token::Tag { content, .. } => {
+                content.starts_with('#')
+                    || content.starts_with('/')
+                    || content.starts_with('!')
+                    || content == "else"
+            }
+            Token::Text(_) => false,
+        }
+    }
+}
+
+enum BlockEnd {
+    Eof,
+    Else,
+    Close(String),
+}
+
+/// A parsed prompt template supporting `{{var}}`, `{{#if}}`, `{{#unless}}`,
+/// `{{#each}}` blocks and `{{value | filter:arg}}` filters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledTemplate {
+    nodes: Vec<Node>,
+}
+
+struct Frame<'a> {
+    this: &'a TemplateValue,
+    index: Option<usize>,
+    len: usize,
+}
+
+impl CompiledTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let tokens = strip_standalone_lines(tokenize(source)?);
+        let mut parser = Parser { tokens, pos: 0 };
+        let (nodes, end) = parser.parse_block()?;
+        match end {
+            BlockEnd::Eof => Ok(Self { nodes }),
+            BlockEnd::Else => Err(parser.syntax_error("unexpected {{else}} outside a block")),
+            BlockEnd::Close(name) => {
+                Err(parser.syntax_error(&format!("unexpected {{{{/{}}}}}", name)))
+            }
+        }
+    }
+
+    /// Render against `context`. In strict mode a reference to a variable that
+    /// is not present in the context is an error; otherwise it renders empty.
+    pub fn render(&self, context: &TemplateContext, strict: bool) -> Result<String, TemplateError> {
+        let root = TemplateValue::Object(context.values.clone());
+        let mut frames = vec![Frame {
+            this: &root,
+            index: None,
+            len: 0,
+        }];
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut frames, strict, &mut out)?;
+        Ok(out)
+    }
+
+    /// Root-level variable names referenced by the template, in order of first use.
+    /// Names only referenced inside `{{#each}}` bodies are item fields and are skipped.
+    pub fn variables(&self) -> Vec<String> {
+        let mut vars = Vec::new();
+        collect_variables(&self.nodes, &mut vars);
+        vars
+    }
+}
+
+fn collect_variables(nodes: &[Node], vars: &mut Vec<String>) {
+    let push = |path: &str, vars: &mut Vec<String>| {
+        let root = path.split('.').next().unwrap_or_default();
+        if root.is_empty() || root == "this" || root.starts_with('@') {
+            return;
+        }
+        if !vars.iter().any(|v| v == root) {
+            vars.push(root.to_string());
+        }
+    };
+
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Expr(expr) => push(&expr.path, vars),
+            Node::If {
+                path,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                push(path, vars);
+                collect_variables(then_branch, vars);
+                collect_variables(else_branch, vars);
+            }
+            Node::Each {
+                path, else_branch, ..
+            } => {
+                push(path, vars);
+                collect_variables(else_branch, vars);
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut offset = 0;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after_open = &rest[start + 2..];
+        let end = after_open.find("}}").ok_or_else(|| TemplateError::Syntax {
+            message: "unclosed '{{'".to_string(),
+            position: offset + start,
+        })?;
+        tokens.push(Token::Tag {
+            content: after_open[..end].trim().to_string(),
+            position: offset + start,
+        });
+        let consumed = start + 2 + end + 2;
+        offset += consumed;
+        rest = &rest[consumed..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+/// Block tags that sit alone on a line should not leave a blank line behind.
+fn strip_standalone_lines(mut tokens: Vec<Token>) -> Vec<Token> {
+    let standalone: Vec<bool> = (0..tokens.len())
+        .map(|i| is_standalone(&tokens, i))
+        .collect();
+
+    for (i, _) in standalone.iter().enumerate().filter(|(_, s)| **s) {
+        if i > 0
+            && let Token::Text(text) = &mut tokens[i - 1]
+        {
+            let keep = text.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            text.truncate(keep);
+        }
+        if let Some(Token::Text(text)) = tokens.get_mut(i + 1) {
+            let drop = text.find('\n').map(|idx| idx + 1).unwrap_or(text.len());
+            text.drain(..drop);
+        }
+    }
+
+    tokens
+        .into_iter()
+        .filter(|t| !matches!(t, Token::Text(text) if text.is_empty()))
+        .collect()
+}
+
+fn is_standalone(tokens: &[Token], i: usize) -> bool {
+    if !tokens[i].is_block_tag() {
+        return false;
+    }
+
+    let before_ok = match i.checked_sub(1).map(|j| &tokens[j]) {
+        None => true,
+        Some(Token::Text(text)) => {
+            let tail = text.rsplit('\n').next().unwrap_or_default();
+            tail.trim().is_empty() && (text.contains('\n') || i == 1)
+        }
+        Some(Token::Tag { .. }) => false,
+    };
+    let after_ok = match tokens.get(i + 1) {
+        None => true,
+        Some(Token::Text(text)) => {
+            let head = text.split('\n').next().unwrap_or_default();
+            head.trim().is_empty() && (text.contains('\n') || i + 2 == tokens.len())
+        }
+        Some(Token::Tag { .. }) => false,
+    };
+
+    before_ok && after_ok
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn syntax_error(&self, message: &str) -> TemplateError {
+        let position = self
+            .tokens
+            .get(self.pos.saturating_sub(1))
+            .map(|t| match t {
+                Token::Tag { position, .. } => *position,
+                Token::Text(_) => 0,
+            })
+            .unwrap_or(0);
+        TemplateError::Syntax {
+            message: message.to_string(),
+            position,
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<(Vec<Node>, BlockEnd), TemplateError> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            let content = match token {
+                Token::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Token::Tag { content, .. } => content,
+            };
+
+            if content.starts_with('!') {
+                continue;
+            }
+            if content == "else" {
+                return Ok((nodes, BlockEnd::Else));
+            }
+            if let Some(name) = content.strip_prefix('/') {
+                return Ok((nodes, BlockEnd::Close(name.trim().to_string())));
+            }
+            if let Some(block) = content.strip_prefix('#') {
+                nodes.push(self.parse_section(block)?);
+                continue;
+            }
+
+            nodes.push(Node::Expr(self.parse_expression(&content)?));
+        }
+
+        Ok((nodes, BlockEnd::Eof))
+    }
+
+    fn parse_section(&mut self, block: &str) -> Result<Node, TemplateError> {
+        let mut parts = block.splitn(2, char::is_whitespace);
+        let keyword = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().trim().to_string();
+
+        if !matches!(keyword.as_str(), "if" | "unless" | "each") {
+            return Err(self.syntax_error(&format!("unknown block '#{}'", keyword)));
+        }
+        if path.is_empty() {
+            return Err(self.syntax_error(&format!("'#{}' requires a variable", keyword)));
+        }
+
+        let (body, end) = self.parse_block()?;
+        let else_branch = match end {
+            BlockEnd::Else => {
+                let (else_nodes, else_end) = self.parse_block()?;
+                self.expect_close(&keyword, else_end)?;
+                else_nodes
+            }
+            other => {
+                self.expect_close(&keyword, other)?;
+                Vec::new()
+            }
+        };
+
+        Ok(match keyword.as_str() {
+            "each" => Node::Each {
+                path,
+                body,
+                else_branch,
+            },
+            _ => Node::If {
+                path,
+                negate: keyword == "unless",
+                then_branch: body,
+                else_branch,
+            },
+        })
+    }
+
+    fn expect_close(&self, keyword: &str, end: BlockEnd) -> Result<(), TemplateError> {
+        match end {
+            BlockEnd::Close(name) if name == keyword => Ok(()),
+            BlockEnd::Close(name) => Err(self.syntax_error(&format!(
+                "expected {{{{/{}}}}} but found {{{{/{}}}}}",
+                keyword, name
+            ))),
+            BlockEnd::Else => Err(self.syntax_error("duplicate {{else}}")),
+            BlockEnd::Eof => Err(self.syntax_error(&format!("unclosed '#{}' block", keyword))),
+        }
+    }
+
+    fn parse_expression(&self, content: &str) -> Result<Expression, TemplateError> {
+        let mut segments = split_unquoted(content, '|').into_iter();
+        let path = segments.next().unwrap_or_default().trim().to_string();
+        if path.is_empty() || path.contains(char::is_whitespace) {
+            return Err(self.syntax_error(&format!("invalid expression '{}'", content)));
+        }
+
+        let mut filters = Vec::new();
+        for segment in segments {
+            let segment = segment.trim();
+            let (name, argument) = match segment.split_once(':') {
+                Some((name, arg)) => (name.trim(), Some(unquote(arg.trim()))),
+                None => (segment, None),
+            };
+            if name.is_empty() {
+                return Err(self.syntax_error(&format!("empty filter in '{}'", content)));
+            }
+            filters.push(Filter {
+                name: name.to_string(),
+                argument,
+            });
+        }
+
+        Ok(Expression { path, filters })
+    }
+}
+
+fn split_unquoted(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (idx, ch) in input.char_indices() {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        } else if ch == separator && !in_quotes {
+            parts.push(&input[start..idx]);
+            start = idx + ch.len_utf8();
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn unquote(arg: &str) -> String {
+    arg.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(arg)
+        .to_string()
+}
+
+fn lookup<'a>(frames: &[Frame<'a>], path: &str) -> Option<Cow<'a, TemplateValue>> {
+    let innermost = frames.last()?;
+    match path {
+        "@index" => return innermost.index.map(|i| Cow::Owned(TemplateValue::from(i))),
+        "@first" => {
+            return innermost
+                .index
+                .map(|i| Cow::Owned(TemplateValue::Bool(i == 0)));
+        }
+        "@last" => {
+            return innermost
+                .index
+                .map(|i| Cow::Owned(TemplateValue::Bool(i + 1 == innermost.len)));
+        }
+        _ => {}
+    }
+
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut current: &'a TemplateValue = if first == "this" {
+        innermost.this
+    } else {
+        frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.this.get(first))?
+    };
+
+    for segment in segments {
+        current = current.get(segment)?;
+    }
+
+    Some(Cow::Borrowed(current))
+}
+
+fn render_nodes<'a>(
+    nodes: &'a [Node],
+    frames: &mut Vec<Frame<'a>>,
+    strict: bool,
+    out: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Expr(expr) => {
+                let value = lookup(frames, &expr.path);
+                let has_default = expr.filters.iter().any(|f| f.name == "default");
+                if value.is_none() && strict && !has_default {
+                    return Err(TemplateError::MissingVariable(expr.path.clone()));
+                }
+                let value = value.map(Cow::into_owned).unwrap_or(TemplateValue::Null);
+                let value = apply_filters(value, &expr.filters)?;
+                out.push_str(&value.to_text(&expr.path)?);
+            }
+            Node::If {
+                path,
+                negate,
+                then_branch,
+                else_branch,
+            } => {
+                let truthy = lookup(frames, path).is_some_and(|v| v.is_truthy());
+                let branch = if truthy != *negate {
+                    then_branch
+                } else {
+                    else_branch
+                };
+                render_nodes(branch, frames, strict, out)?;
+            }
+            Node::Each {
+                path,
+                body,
+                else_branch,
+            } => {
+                let value = match lookup(frames, path) {
+                    Some(value) => value,
+                    None if strict => return Err(TemplateError::MissingVariable(path.clone())),
+                    None => Cow::Owned(TemplateValue::Null),
+                };
+                // Items must outlive the frames pushed for them, so only borrowed
+                // lists can be iterated; computed values (@index etc.) are never lists.
+                let items: &'a [TemplateValue] = match value {
+                    Cow::Borrowed(TemplateValue::List(items)) => items,
+                    _ => &[],
+                };
+                if items.is_empty() {
+                    render_nodes(else_branch, frames, strict, out)?;
+                    continue;
+                }
+                for (index, item) in items.iter().enumerate() {
+                    frames.push(Frame {
+                        this: item,
+                        index: Some(index),
+                        len: items.len(),
+                    });
+                    let result = render_nodes(body, frames, strict, out);
+                    frames.pop();
+                    result?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_filters(
+    mut value: TemplateValue,
+    filters: &[Filter],
+) -> Result<TemplateValue, TemplateError> {
+    for filter in filters {
+        value = apply_filter(value, filter)?;
+    }
+    Ok(value)
+}
+
+fn apply_filter(value: TemplateValue, filter: &Filter) -> Result<TemplateValue, TemplateError> {
+    let invalid = |argument: &str| TemplateError::InvalidFilterArgument {
+        filter: filter.name.clone(),
+        argument: argument.to_string(),
+    };
+    let text = |value: &TemplateValue| value.to_text(&filter.name);
+
+    match filter.name.as_str() {
+        "upper" => Ok(text(&value)?.to_uppercase().into()),
+        "lower" => Ok(text(&value)?.to_lowercase().into()),
+        "trim" => Ok(text(&value)?.trim().to_string().into()),
+        "slug" => Ok(slugify(&text(&value)?).into()),
+        "truncate" => {
+            let arg = filter.argument.as_deref().unwrap_or("80");
+            let max: usize = arg.parse().map_err(|_| invalid(arg))?;
+            let s = text(&value)?;
+            if s.chars().count() <= max {
+                Ok(s.into())
+            } else {
+                let truncated: String = s.chars().take(max).collect();
+                Ok(format!("{}...", truncated.trim_end()).into())
+            }
+        }
+        "default" => {
+            if value.is_truthy() {
+                Ok(value)
+            } else {
+                Ok(filter.argument.clone().unwrap_or_default().into())
+            }
+        }
+        "join" => match value {
+            TemplateValue::List(items) => {
+                let separator = filter.argument.as_deref().unwrap_or(", ");
+                Ok(items
+                    .iter()
+                    .map(text)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(separator)
+                    .into())
+            }
+            other => Ok(other),
+        },
+        "length" => Ok(match &value {
+            TemplateValue::List(items) => items.len().into(),
+            TemplateValue::Object(map) => map.len().into(),
+            TemplateValue::Null => 0usize.into(),
+            other => text(other)?.chars().count().into(),
+        }),
+        "date" => {
+            if value == TemplateValue::Null {
+                return Ok(value);
+            }
+            let raw = text(&value)?;
+            let format = match filter.argument.as_deref().unwrap_or("short") {
+                "short" => "%Y-%m-%d",
+                "long" => "%B %-d, %Y",
+                "datetime" => "%Y-%m-%d %H:%M",
+                "iso" => "%Y-%m-%dT%H:%M:%SZ",
+                custom => custom,
+            };
+            let date = DateTime::parse_from_rfc3339(&raw)
+                .map(|d| d.with_timezone(&Utc))
+                .or_else(|_| {
+                    NaiveDate::parse_from_str(&raw, "%Y-%m-%d")
+                        .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+                })
+                .map_err(|_| invalid(&raw))?;
+            let mut formatted = String::new();
+            write!(formatted, "{}", date.format(format)).map_err(|_| invalid(format))?;
+            Ok(formatted.into())
+        }
+        other => Err(TemplateError::UnknownFilter(other.to_string())),
+    }
+}
+
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(
+        template: &str,
+        context: &TemplateContext,
+        strict: bool,
+    ) -> Result<String, TemplateError> {
+        CompiledTemplate::parse(template)?.render(context, strict)
+    }
+
+    fn task_context() -> TemplateContext {
+        let mut task = BTreeMap::new();
+        task.insert("title".to_string(), TemplateValue::from("Fix login bug"));
+        task.insert(
+            "due_date".to_string(),
+            TemplateValue::from("2025-03-04T10:00:00Z"),
+        );
+
+        let mut context = TemplateContext::new();
+        context.insert("task", TemplateValue::Object(task));
+        context.insert(
+            "subtasks",
+            vec![
+                TemplateValue::Object(BTreeMap::from([
+                    ("title".to_string(), "Reproduce".into()),
+                    ("completed".to_string(), true.into()),
+                ])),
+                TemplateValue::Object(BTreeMap::from([
+                    ("title".to_string(), "Write test".into()),
+                    ("completed".to_string(), false.into()),
+                ])),
+            ],
+        );
+        context.insert("goal", TemplateValue::Null);
+        context
+    }
+
+    #[test]
+    fn test_variables_and_paths() {
+        let context = task_context();
+        assert_eq!(
+            render("Task: {{ task.title }}", &context, true).unwrap(),
+            "Task: Fix login bug"
+        );
+    }
+
+    #[test]
+    fn test_if_else_and_unless() {
+        let context = task_context();
+        let template = "{{#if goal}}Goal set{{else}}No goal{{/if}}";
+        assert_eq!(render(template, &context, true).unwrap(), "No goal");
+
+        let template = "{{#unless goal}}Unassigned{{/unless}}";
+        assert_eq!(render(template, &context, true).unwrap(), "Unassigned");
+
+        // Missing variables are simply falsy in conditionals, even in strict mode
+        let template = "{{#if nothing}}yes{{else}}no{{/if}}";
+        assert_eq!(render(template, &context, true).unwrap(), "no");
+    }
+
+    #[test]
+    fn test_each_loop() {
+        let context = task_context();
+        let template = "{{#each subtasks}}\n- [{{#if completed}}x{{else}} {{/if}}] {{@index}} {{title}} ({{task.title}})\n{{/each}}\n";
+        assert_eq!(
+            render(template, &context, true).unwrap(),
+            "- [x] 0 Reproduce (Fix login bug)\n- [ ] 1 Write test (Fix login bug)\n"
+        );
+
+        let template = "{{#each missing_list}}x{{else}}none{{/each}}";
+        assert_eq!(render(template, &context, false).unwrap(), "none");
+    }
+
+    #[test]
+    fn test_filters() {
+        let context = task_context();
+        assert_eq!(
+            render("{{task.title | upper}}", &context, true).unwrap(),
+            "FIX LOGIN BUG"
+        );
+        assert_eq!(
+            render("{{task.title | truncate:3}}", &context, true).unwrap(),
+            "Fix..."
+        );
+        assert_eq!(
+            render("{{task.due_date | date:\"%d/%m/%Y\"}}", &context, true).unwrap(),
+            "04/03/2025"
+        );
+        assert_eq!(
+            render("{{task.due_date | date:long}}", &context, true).unwrap(),
+            "March 4, 2025"
+        );
+        assert_eq!(
+            render("{{goal.title | default:\"N/A\"}}", &context, true).unwrap(),
+            "N/A"
+        );
+        assert_eq!(
+            render("{{task.title | slug | upper}}", &context, true).unwrap(),
+            "FIX-LOGIN-BUG"
+        );
+        assert_eq!(
+            render("{{subtasks | length}}", &context, true).unwrap(),
+            "2"
+        );
+        assert_eq!(
+            render("{{task.title | shout}}", &context, true),
+            Err(TemplateError::UnknownFilter("shout".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_strict_mode_missing_variable() {
+        let context = task_context();
+        assert_eq!(
+            render("Hello {{ name }}", &context, true),
+            Err(TemplateError::MissingVariable("name".to_string()))
+        );
+        assert_eq!(
+            render("Hello {{ name }}!", &context, false).unwrap(),
+            "Hello !"
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert!(matches!(
+            CompiledTemplate::parse("{{#if x}}unclosed"),
+            Err(TemplateError::Syntax { .. })
+        ));
+        assert!(matches!(
+            CompiledTemplate::parse("{{#if x}}a{{/each}}"),
+            Err(TemplateError::Syntax { .. })
+        ));
+        assert!(matches!(
+            CompiledTemplate::parse("{{ task.title"),
+            Err(TemplateError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_variables_listing() {
+        let template = CompiledTemplate::parse(
+            "{{a}} {{#if b.c}}{{d | upper}}{{/if}}{{#each items}}{{name}}{{/each}}{{a}}",
+        )
+        .unwrap();
+        assert_eq!(template.variables(), vec!["a", "b", "d", "items"]);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::domain::dependency::{Dependency, DependencyType};
+use crate::domain::rrule::{ByDay, Frequency, RRule};
 use crate::domain::task::{Priority, Task};
-use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,11 +16,66 @@ pub struct RecurringTaskTemplate {
     pub assigned_resource_id: Option<Uuid>,
     pub estimated_hours: Option<f32>,
     pub recurrence_rule: RecurrenceRule,
+    /// RFC 5545 rule that schedules the template instead of `recurrence_rule`,
+    /// which then only mirrors what it can express
+    #[serde(default)]
+    pub rrule: Option<RRule>,
     pub active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_generated: Option<DateTime<Utc>>,
     pub next_occurrence: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub catch_up: CatchUpPolicy,
+    /// What generated tasks carry beyond the fields above
+    #[serde(default)]
+    pub blueprint: TaskBlueprint,
+}
+
+/// The rest of a generated task, and the child tasks generated with it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TaskBlueprint {
+    pub tags: HashSet<String>,
+    /// Checklist items, added as subtasks
+    pub subtasks: Vec<String>,
+    pub goal_id: Option<Uuid>,
+    pub configuration_id: Option<Uuid>,
+    /// Hours from the occurrence to the task's due date
+    pub due_offset_hours: Option<i64>,
+    pub children: Vec<ChildTaskBlueprint>,
+}
+
+/// A child task generated under the main one, e.g. one step of a release.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChildTaskBlueprint {
+    pub title: String,
+    pub description: String,
+    pub priority: Option<Priority>,
+    pub estimated_hours: Option<f32>,
+    pub tags: HashSet<String>,
+    pub subtasks: Vec<String>,
+    /// Hours from the occurrence to the child's due date
+    pub due_offset_hours: Option<i64>,
+    /// Earlier children, by index, that must finish before this one starts
+    pub depends_on: Vec<usize>,
+}
+
+impl ChildTaskBlueprint {
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            ..Default::default()
+        }
+    }
+}
+
+/// Child tasks generated for one occurrence, with the dependencies between them.
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedChildren {
+    pub tasks: Vec<Task>,
+    pub dependencies: Vec<Dependency>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -34,6 +91,26 @@ pub struct RecurrenceRule {
     pub occurrences_count: u32,
 }
 
+/// What generation does with occurrences that came and went while nothing
+/// was generating, e.g. while the app was closed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    /// A task for every missed occurrence
+    #[default]
+    All,
+    /// A task for the most recent missed occurrence only
+    Latest,
+    /// No tasks for missed occurrences, only for ones just due
+    Skip,
+}
+
+/// Most occurrences one generation run catches up on
+const MAX_CATCH_UP: usize = 50;
+
+/// How late an occurrence can be and still count as on time for
+/// `CatchUpPolicy::Skip`
+const ON_TIME_MINUTES: i64 = 60;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum RecurrencePattern {
     Daily,
@@ -63,11 +140,14 @@ impl RecurringTaskTemplate {
             assigned_resource_id: None,
             estimated_hours: None,
             recurrence_rule,
+            rrule: None,
             active,
             created_at: now,
             updated_at: now,
             last_generated: None,
             next_occurrence: None,
+            catch_up: CatchUpPolicy::default(),
+            blueprint: TaskBlueprint::default(),
         };
         // For new templates, set next occurrence to now to allow immediate generation
         // but only if the template is active
@@ -77,96 +157,258 @@ impl RecurringTaskTemplate {
         template
     }
 
-    pub fn generate_task(&mut self) -> Option<Task> {
-        if !self.active {
-            return None;
+    /// A template scheduled by `rrule`; its first task is due at the rule's
+    /// first occurrence from now on.
+    pub fn from_rrule(title: String, description: String, rrule: RRule) -> Self {
+        let time_of_day = rrule
+            .dtstart
+            .map(|start| start.time().with_nanosecond(0).unwrap_or_default())
+            .unwrap_or_else(|| NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        let recurrence_rule = RecurrenceRule::from_rrule(&rrule, time_of_day);
+        let mut template = Self::new(title, description, recurrence_rule);
+        template.rrule = Some(rrule);
+        template.active = true;
+        template.next_occurrence = template.following_occurrence(None);
+        template.active = template.next_occurrence.is_some();
+        template
+    }
+
+    /// Where an RRULE without `DTSTART` starts: the day the template was
+    /// created, at its time of day.
+    pub fn rrule_start(&self) -> DateTime<Utc> {
+        DateTime::from_naive_utc_and_offset(
+            self.created_at
+                .date_naive()
+                .and_time(self.recurrence_rule.time_of_day),
+            Utc,
+        )
+    }
+
+    /// The rule that places occurrences: `rrule`, or the legacy pattern as
+    /// one. Occurrence limits are left to the template.
+    pub fn schedule(&self) -> RRule {
+        if let Some(rrule) = &self.rrule {
+            return rrule.clone();
+        }
+        let mut rule = self.recurrence_rule.clone();
+        // Without a day, months and years keep the start's day, clamped to
+        // the end of shorter months
+        let start = self.rrule_start();
+        if matches!(
+            rule.pattern,
+            RecurrencePattern::Monthly | RecurrencePattern::Yearly
+        ) {
+            rule.day_of_month = rule.day_of_month.or(Some(start.day()));
+        }
+        if rule.pattern == RecurrencePattern::Yearly {
+            rule.month_of_year = rule.month_of_year.or(Some(start.month()));
         }
+        let mut rrule = rule.to_rrule();
+        rrule.count = None;
+        rrule
+    }
 
-        if let Some(max) = self.recurrence_rule.max_occurrences
-            && self.recurrence_rule.occurrences_count >= max
-        {
-            self.active = false;
-            return None;
+    /// The scheduled occurrence strictly after `after`.
+    pub fn occurrence_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule().next_after(self.rrule_start(), after)
+    }
+
+    /// The RRULE occurrence after `previous`, or after now without one.
+    fn following_occurrence(&self, previous: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        self.occurrence_after(previous.unwrap_or_else(|| Utc::now() - Duration::nanoseconds(1)))
+    }
+
+    /// How many more tasks `max_occurrences` allows.
+    fn remaining_occurrences(&self) -> Option<usize> {
+        self.recurrence_rule
+            .max_occurrences
+            .map(|max| max.saturating_sub(self.recurrence_rule.occurrences_count) as usize)
+    }
+
+    fn past_end_date(&self, at: DateTime<Utc>) -> bool {
+        self.recurrence_rule
+            .end_date
+            .is_some_and(|end| at.date_naive() > end)
+    }
+
+    /// The next `count` dates tasks will be generated for, starting with the
+    /// pending one.
+    pub fn preview_occurrences(&self, count: usize) -> Vec<DateTime<Utc>> {
+        if !self.active || count == 0 {
+            return Vec::new();
         }
+        let count = self
+            .remaining_occurrences()
+            .map_or(count, |left| count.min(left));
+        let first = self.next_occurrence.unwrap_or_else(Utc::now);
+        let mut dates = vec![first];
+        dates.extend(self.schedule().occurrences_after(
+            self.rrule_start(),
+            Some(first),
+            count.saturating_sub(1),
+        ));
+        dates.retain(|date| !self.past_end_date(*date));
+        dates.truncate(count);
+        dates
+    }
 
-        if let Some(end_date) = self.recurrence_rule.end_date
-            && Utc::now().date_naive() > end_date
-        {
+    /// Key identifying the task generated for the occurrence at `at`, so the
+    /// same occurrence is never generated twice.
+    pub fn occurrence_key(&self, at: DateTime<Utc>) -> String {
+        format!("{}@{}", self.id, at.to_rfc3339())
+    }
+
+    /// The occurrences due by `now` that the catch-up policy wants tasks for,
+    /// oldest first. Occurrences it drops are skipped for good: the pending
+    /// occurrence moves past all of them.
+    pub fn take_due_occurrences(&mut self, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        if !self.should_generate_at(now) {
+            return Vec::new();
+        }
+        let first = self.next_occurrence.unwrap_or(now);
+        let schedule = self.schedule();
+        let start = self.rrule_start();
+        let mut due = vec![first];
+        let mut following = None;
+        loop {
+            let batch = schedule.occurrences_after(start, due.last().copied(), MAX_CATCH_UP);
+            let exhausted = batch.len() < MAX_CATCH_UP;
+            for at in batch {
+                if at > now {
+                    following = Some(at);
+                    break;
+                }
+                due.push(at);
+            }
+            // Far behind, the oldest occurrences go rather than flooding the board
+            due.drain(..due.len().saturating_sub(MAX_CATCH_UP));
+            if following.is_some() || exhausted {
+                break;
+            }
+        }
+
+        due.retain(|at| !self.past_end_date(*at));
+        match self.catch_up {
+            CatchUpPolicy::All => {}
+            CatchUpPolicy::Latest => {
+                due.drain(..due.len().saturating_sub(1));
+            }
+            CatchUpPolicy::Skip => due.retain(|at| now - *at <= Duration::minutes(ON_TIME_MINUTES)),
+        }
+        if let Some(left) = self.remaining_occurrences() {
+            due.truncate(left);
+        }
+
+        self.next_occurrence = following;
+        if following.is_none_or(|at| self.past_end_date(at)) {
             self.active = false;
-            return None;
         }
+        self.updated_at = Utc::now();
+        due
+    }
 
+    /// A task for the occurrence at `at`, counted against the template's limits.
+    pub fn task_for(&mut self, at: DateTime<Utc>) -> Task {
         let mut task = Task::new(self.title.clone(), self.description.clone());
 
         task.priority = self.priority;
         task.metadata = self.metadata.clone();
         task.assigned_resource_id = self.assigned_resource_id;
         task.estimated_hours = self.estimated_hours;
-        task.configuration_id = None; // Recurring tasks don't have a specific configuration
-
-        if let Some(next) = self.next_occurrence {
-            task.scheduled_date = Some(next);
+        task.configuration_id = self.blueprint.configuration_id;
+        task.goal_id = self.blueprint.goal_id;
+        task.tags = self.blueprint.tags.clone();
+        for item in &self.blueprint.subtasks {
+            task.add_subtask(item.clone());
         }
+        task.scheduled_date = Some(at);
+        task.due_date = self
+            .blueprint
+            .due_offset_hours
+            .map(|hours| at + Duration::hours(hours));
 
         self.last_generated = Some(Utc::now());
         self.recurrence_rule.occurrences_count += 1;
-        self.next_occurrence = Some(self.calculate_next_occurrence());
+        if self.remaining_occurrences() == Some(0) {
+            self.active = false;
+        }
         self.updated_at = Utc::now();
-
-        Some(task)
+        task
     }
 
-    pub fn calculate_next_occurrence(&self) -> DateTime<Utc> {
-        let base = self.last_generated.unwrap_or_else(Utc::now);
-
-        match self.recurrence_rule.pattern {
-            RecurrencePattern::Daily => base + Duration::days(self.recurrence_rule.interval as i64),
-            RecurrencePattern::Weekly => {
-                let mut next = base + Duration::weeks(self.recurrence_rule.interval as i64);
-
-                // Find next matching day of week
-                if !self.recurrence_rule.days_of_week.is_empty() {
-                    while !self.recurrence_rule.days_of_week.contains(&next.weekday()) {
-                        next += Duration::days(1);
-                    }
-                }
-                next
+    /// The blueprint's child tasks for `parent`, generated for the occurrence
+    /// at `at`. Dependencies on children that don't come earlier are ignored,
+    /// which keeps the chain free of cycles.
+    pub fn child_tasks(&self, parent: &Task, at: DateTime<Utc>) -> GeneratedChildren {
+        let mut generated = GeneratedChildren::default();
+        for (index, child) in self.blueprint.children.iter().enumerate() {
+            let mut task = Task::new(child.title.clone(), child.description.clone());
+            task.priority = child.priority.unwrap_or(self.priority);
+            task.estimated_hours = child.estimated_hours;
+            task.assigned_resource_id = self.assigned_resource_id;
+            task.configuration_id = parent.configuration_id;
+            task.goal_id = parent.goal_id;
+            task.parent_task_id = Some(parent.id);
+            task.tags = child.tags.clone();
+            for item in &child.subtasks {
+                task.add_subtask(item.clone());
             }
-            RecurrencePattern::Monthly => {
-                let mut next = base;
-                for _ in 0..self.recurrence_rule.interval {
-                    next = add_months(next, 1);
-                }
-
-                // Adjust to specific day of month if set
-                if let Some(day) = self.recurrence_rule.day_of_month {
-                    next = set_day_of_month(next, day);
-                }
-                next
+            task.scheduled_date = Some(at);
+            task.due_date = child
+                .due_offset_hours
+                .map(|hours| at + Duration::hours(hours));
+
+            for &before in child.depends_on.iter().filter(|&&before| before < index) {
+                generated.dependencies.push(Dependency::new(
+                    task.id,
+                    generated.tasks[before].id,
+                    DependencyType::FinishToStart,
+                ));
             }
-            RecurrencePattern::Yearly => {
-                let mut next = base;
-                for _ in 0..self.recurrence_rule.interval {
-                    next = add_years(next, 1);
-                }
+            generated.tasks.push(task);
+        }
+        generated
+    }
 
-                // Adjust to specific month and day if set
-                if let Some(month) = self.recurrence_rule.month_of_year {
-                    next = set_month(next, month);
-                }
-                if let Some(day) = self.recurrence_rule.day_of_month {
-                    next = set_day_of_month(next, day);
-                }
-                next
-            }
-            RecurrencePattern::Custom => {
-                // For custom patterns, use more complex logic
-                base + Duration::days(self.recurrence_rule.interval as i64)
-            }
+    /// The task for the pending occurrence, whether or not it is due yet.
+    pub fn generate_task(&mut self) -> Option<Task> {
+        if !self.active {
+            return None;
+        }
+
+        if self.remaining_occurrences() == Some(0) {
+            self.active = false;
+            return None;
         }
+
+        let slot = self.next_occurrence.unwrap_or_else(Utc::now);
+        if self.past_end_date(slot) {
+            self.active = false;
+            return None;
+        }
+
+        let task = self.task_for(slot);
+        self.next_occurrence = self.occurrence_after(slot);
+        if self.next_occurrence.is_none() {
+            // The rule has no occurrences left
+            self.active = false;
+        }
+
+        Some(task)
+    }
+
+    /// The first scheduled occurrence after now.
+    pub fn calculate_next_occurrence(&self) -> DateTime<Utc> {
+        self.occurrence_after(Utc::now())
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
     }
 
     pub fn should_generate_now(&self) -> bool {
+        self.should_generate_at(Utc::now())
+    }
+
+    /// Whether the pending occurrence is due at `now`.
+    pub fn should_generate_at(&self, now: DateTime<Utc>) -> bool {
         if !self.active {
             return false;
         }
@@ -179,7 +421,7 @@ impl RecurringTaskTemplate {
         }
 
         if let Some(next) = self.next_occurrence {
-            next <= Utc::now()
+            next <= now
         } else {
             true
         }
@@ -191,88 +433,78 @@ impl RecurringTaskTemplate {
     }
 
     pub fn reactivate(&mut self) {
-        self.active = true;
-        self.next_occurrence = Some(self.calculate_next_occurrence());
+        self.next_occurrence = self.following_occurrence(None);
+        self.active = self.next_occurrence.is_some();
         self.updated_at = Utc::now();
     }
 }
 
-// Helper functions for date manipulation
-fn add_months(dt: DateTime<Utc>, months: u32) -> DateTime<Utc> {
-    let naive = dt.naive_utc();
-    let year = naive.year() + (naive.month() + months - 1) as i32 / 12;
-    let month = ((naive.month() + months - 1) % 12) + 1;
-    let day = naive.day().min(days_in_month(year, month));
-
-    DateTime::from_naive_utc_and_offset(
-        NaiveDate::from_ymd_opt(year, month, day)
-            .unwrap()
-            .and_time(naive.time()),
-        Utc,
-    )
-}
-
-fn add_years(dt: DateTime<Utc>, years: u32) -> DateTime<Utc> {
-    let naive = dt.naive_utc();
-    let year = naive.year() + years as i32;
-    let day = if naive.month() == 2 && naive.day() == 29 && !is_leap_year(year) {
-        28
-    } else {
-        naive.day()
-    };
-
-    DateTime::from_naive_utc_and_offset(
-        NaiveDate::from_ymd_opt(year, naive.month(), day)
-            .unwrap()
-            .and_time(naive.time()),
-        Utc,
-    )
-}
-
-fn set_day_of_month(dt: DateTime<Utc>, day: u32) -> DateTime<Utc> {
-    let naive = dt.naive_utc();
-    let max_day = days_in_month(naive.year(), naive.month());
-    let actual_day = day.min(max_day);
-
-    DateTime::from_naive_utc_and_offset(
-        NaiveDate::from_ymd_opt(naive.year(), naive.month(), actual_day)
-            .unwrap()
-            .and_time(naive.time()),
-        Utc,
-    )
-}
-
-fn set_month(dt: DateTime<Utc>, month: u32) -> DateTime<Utc> {
-    let naive = dt.naive_utc();
-    let day = naive.day().min(days_in_month(naive.year(), month));
-
-    DateTime::from_naive_utc_and_offset(
-        NaiveDate::from_ymd_opt(naive.year(), month, day)
-            .unwrap()
-            .and_time(naive.time()),
-        Utc,
-    )
-}
+impl RecurrenceRule {
+    /// The closest legacy rule to `rrule`, kept alongside it for older readers.
+    pub fn from_rrule(rrule: &RRule, time_of_day: NaiveTime) -> Self {
+        let pattern = match rrule.freq {
+            Frequency::Daily => RecurrencePattern::Daily,
+            Frequency::Weekly => RecurrencePattern::Weekly,
+            Frequency::Monthly => RecurrencePattern::Monthly,
+            Frequency::Yearly => RecurrencePattern::Yearly,
+        };
+        Self {
+            pattern,
+            interval: rrule.interval,
+            days_of_week: rrule.by_day.iter().map(|b| b.weekday).collect(),
+            day_of_month: rrule
+                .by_month_day
+                .iter()
+                .find(|d| **d > 0)
+                .map(|d| *d as u32),
+            month_of_year: rrule.by_month.first().copied(),
+            time_of_day,
+            end_date: rrule.until.map(|until| until.date_naive()),
+            max_occurrences: rrule.count,
+            occurrences_count: 0,
+        }
+    }
 
-fn days_in_month(year: i32, month: u32) -> u32 {
-    match month {
-        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
-        4 | 6 | 9 | 11 => 30,
-        2 => {
-            if is_leap_year(year) {
-                29
+    /// This rule as an RRULE. Days of month past the 28th fall back to the
+    /// month's last day, as the legacy patterns do.
+    pub fn to_rrule(&self) -> RRule {
+        let freq = match self.pattern {
+            RecurrencePattern::Daily | RecurrencePattern::Custom => Frequency::Daily,
+            RecurrencePattern::Weekly => Frequency::Weekly,
+            RecurrencePattern::Monthly => Frequency::Monthly,
+            RecurrencePattern::Yearly => Frequency::Yearly,
+        };
+        let mut rrule = RRule::new(freq);
+        rrule.interval = self.interval.max(1);
+        rrule.count = self.max_occurrences;
+        rrule.until = self.end_date.map(|end| {
+            DateTime::from_naive_utc_and_offset(end.and_hms_opt(23, 59, 59).unwrap(), Utc)
+        });
+        if freq == Frequency::Weekly {
+            rrule.by_day = self
+                .days_of_week
+                .iter()
+                .copied()
+                .map(ByDay::every)
+                .collect();
+        }
+        if freq == Frequency::Yearly {
+            rrule.by_month = self.month_of_year.into_iter().collect();
+        }
+        if matches!(freq, Frequency::Monthly | Frequency::Yearly)
+            && let Some(day) = self.day_of_month
+        {
+            if day > 28 {
+                rrule.by_month_day = (28..=day.min(31) as i32).collect();
+                rrule.by_set_pos = vec![-1];
             } else {
-                28
+                rrule.by_month_day = vec![day as i32];
             }
         }
-        _ => panic!("Invalid month"),
+        rrule
     }
 }
 
-fn is_leap_year(year: i32) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -456,25 +688,250 @@ mod tests {
     }
 
     #[test]
-    fn test_date_helpers() {
-        let dt = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+    fn test_legacy_rules_as_rrule() {
+        let mut rule = RecurrenceRule {
+            pattern: RecurrencePattern::Monthly,
+            interval: 1,
+            days_of_week: vec![],
+            day_of_month: Some(31),
+            month_of_year: None,
+            time_of_day: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end_date: None,
+            max_occurrences: Some(3),
+            occurrences_count: 0,
+        };
+        let rrule = rule.to_rrule();
+        assert_eq!(
+            rrule.to_string(),
+            "RRULE:FREQ=MONTHLY;COUNT=3;BYMONTHDAY=28,29,30,31;BYSETPOS=-1"
+        );
+        let start = Utc.with_ymd_and_hms(2025, 1, 31, 9, 0, 0).unwrap();
+        let days: Vec<u32> = rrule
+            .occurrences_after(start, None, 5)
+            .iter()
+            .map(|d| d.day())
+            .collect();
+        assert_eq!(days, vec![31, 28, 31]);
+
+        rule.pattern = RecurrencePattern::Weekly;
+        rule.interval = 2;
+        rule.days_of_week = vec![Weekday::Mon, Weekday::Thu];
+        rule.max_occurrences = None;
+        assert_eq!(
+            rule.to_rrule().to_string(),
+            "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH"
+        );
+    }
+
+    #[test]
+    fn test_rrule_template_preview_and_generation() {
+        let rrule = RRule::parse("FREQ=WEEKLY;BYDAY=TU;COUNT=2").unwrap();
+        let mut template =
+            RecurringTaskTemplate::from_rrule("Standup".to_string(), String::new(), rrule);
+        assert_eq!(template.recurrence_rule.pattern, RecurrencePattern::Weekly);
+
+        let preview = template.preview_occurrences(5);
+        assert!(!preview.is_empty() && preview.len() <= 2);
+        assert!(preview.iter().all(|d| d.weekday() == Weekday::Tue));
 
-        // Test add_months with day overflow
-        let next = add_months(dt, 1);
-        assert_eq!(next.month(), 2);
-        assert_eq!(next.day(), 29); // February has fewer days
+        for date in &preview {
+            let task = template.generate_task().unwrap();
+            assert_eq!(task.scheduled_date, Some(*date));
+        }
+        assert!(!template.active);
+        assert!(template.generate_task().is_none());
 
-        // Test add_years
-        let next_year = add_years(dt, 1);
-        assert_eq!(next_year.year(), 2025);
-        assert_eq!(next_year.month(), 1);
-        assert_eq!(next_year.day(), 31);
+        let legacy = RecurringTaskTemplate::new(
+            "Daily".to_string(),
+            String::new(),
+            RecurrenceRule {
+                pattern: RecurrencePattern::Daily,
+                interval: 2,
+                days_of_week: vec![],
+                day_of_month: None,
+                month_of_year: None,
+                time_of_day: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                end_date: None,
+                max_occurrences: Some(3),
+                occurrences_count: 1,
+            },
+        );
+        let preview = legacy.preview_occurrences(5);
+        assert_eq!(preview.len(), 2);
+        // After the immediate first task, every other day at the time of day
+        assert!(preview[1] > preview[0] && preview[1] - preview[0] <= Duration::days(2));
+        assert_eq!(preview[1].time(), NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_legacy_schedule_clamps_to_month_end() {
+        let mut template = RecurringTaskTemplate::new(
+            "Invoice".to_string(),
+            String::new(),
+            RecurrenceRule {
+                pattern: RecurrencePattern::Monthly,
+                interval: 1,
+                days_of_week: vec![],
+                day_of_month: None,
+                month_of_year: None,
+                time_of_day: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+                end_date: None,
+                max_occurrences: None,
+                occurrences_count: 0,
+            },
+        );
+        let dt = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+        template.created_at = dt;
+
+        // Months without the start's day fall back to their last day
+        let next = template.occurrence_after(dt).unwrap();
+        assert_eq!((next.month(), next.day()), (2, 29));
+        let next = template.occurrence_after(next).unwrap();
+        assert_eq!((next.month(), next.day()), (3, 31));
+
+        template.recurrence_rule.pattern = RecurrencePattern::Yearly;
+        let next_year = template.occurrence_after(dt).unwrap();
+        assert_eq!(
+            (next_year.year(), next_year.month(), next_year.day()),
+            (2025, 1, 31)
+        );
 
-        // Test leap year handling
+        // Leap days fall back to the 28th
         let leap_day = Utc.with_ymd_and_hms(2024, 2, 29, 12, 0, 0).unwrap();
-        let non_leap = add_years(leap_day, 1);
-        assert_eq!(non_leap.year(), 2025);
-        assert_eq!(non_leap.month(), 2);
-        assert_eq!(non_leap.day(), 28);
+        template.created_at = leap_day;
+        let non_leap = template.occurrence_after(leap_day).unwrap();
+        assert_eq!(
+            (non_leap.year(), non_leap.month(), non_leap.day()),
+            (2025, 2, 28)
+        );
+    }
+
+    #[test]
+    fn test_catch_up_policies() {
+        let rule = RecurrenceRule {
+            pattern: RecurrencePattern::Daily,
+            interval: 1,
+            days_of_week: vec![],
+            day_of_month: None,
+            month_of_year: None,
+            time_of_day: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end_date: None,
+            max_occurrences: None,
+            occurrences_count: 0,
+        };
+        let mut template = RecurringTaskTemplate::new("Backup".to_string(), String::new(), rule);
+        template.created_at = Utc.with_ymd_and_hms(2025, 3, 1, 8, 0, 0).unwrap();
+        let missed = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+        // Three days behind, checked half an hour after the latest occurrence
+        let now = Utc.with_ymd_and_hms(2025, 3, 4, 9, 30, 0).unwrap();
+
+        let mut all = template.clone();
+        all.next_occurrence = Some(missed);
+        let due = all.take_due_occurrences(now);
+        assert_eq!(due.len(), 4);
+        assert_eq!(due[0], missed);
+        assert_eq!(due[3] - due[0], Duration::days(3));
+        // Anchored to the schedule, not to when generation ran
+        assert_eq!(all.next_occurrence, Some(missed + Duration::days(4)));
+        assert!(all.take_due_occurrences(now).is_empty());
+
+        let mut latest = template.clone();
+        latest.catch_up = CatchUpPolicy::Latest;
+        latest.next_occurrence = Some(missed);
+        assert_eq!(
+            latest.take_due_occurrences(now),
+            vec![missed + Duration::days(3)]
+        );
+        assert_eq!(latest.next_occurrence, Some(missed + Duration::days(4)));
+
+        let mut skip = template.clone();
+        skip.catch_up = CatchUpPolicy::Skip;
+        skip.next_occurrence = Some(missed);
+        assert_eq!(
+            skip.take_due_occurrences(now),
+            vec![missed + Duration::days(3)]
+        );
+        skip.next_occurrence = Some(missed);
+        assert!(
+            skip.take_due_occurrences(now + Duration::hours(2))
+                .is_empty()
+        );
+        assert_eq!(skip.next_occurrence, Some(missed + Duration::days(4)));
+
+        let mut limited = template.clone();
+        limited.recurrence_rule.max_occurrences = Some(2);
+        limited.next_occurrence = Some(missed);
+        let due = limited.take_due_occurrences(now);
+        assert_eq!(due.len(), 2);
+        for at in due {
+            limited.task_for(at);
+        }
+        assert!(!limited.active);
+        assert_ne!(
+            template.occurrence_key(missed),
+            template.occurrence_key(missed + Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn test_blueprint_generates_release_project() {
+        let rrule = RRule::parse("DTSTART:20250106T090000Z\nRRULE:FREQ=MONTHLY;BYDAY=1MO").unwrap();
+        let mut template =
+            RecurringTaskTemplate::from_rrule("Release".to_string(), String::new(), rrule);
+        let goal_id = Uuid::new_v4();
+        template.blueprint = TaskBlueprint {
+            tags: HashSet::from(["release".to_string()]),
+            subtasks: vec!["Write changelog".to_string(), "Tag version".to_string()],
+            goal_id: Some(goal_id),
+            configuration_id: None,
+            due_offset_hours: Some(72),
+            children: vec![
+                ChildTaskBlueprint::new("Freeze branch".to_string()),
+                ChildTaskBlueprint {
+                    priority: Some(Priority::High),
+                    due_offset_hours: Some(48),
+                    depends_on: vec![0],
+                    ..ChildTaskBlueprint::new("Run regression suite".to_string())
+                },
+                ChildTaskBlueprint {
+                    // A later child can't be waited on
+                    depends_on: vec![0, 1, 3],
+                    ..ChildTaskBlueprint::new("Publish".to_string())
+                },
+            ],
+        };
+
+        let at = template.next_occurrence.unwrap();
+        let task = template.generate_task().unwrap();
+        assert_eq!(task.tags, template.blueprint.tags);
+        assert_eq!(task.subtasks.len(), 2);
+        assert_eq!(task.goal_id, Some(goal_id));
+        assert_eq!(task.due_date, Some(at + Duration::hours(72)));
+
+        let children = template.child_tasks(&task, at);
+        assert_eq!(children.tasks.len(), 3);
+        assert!(
+            children
+                .tasks
+                .iter()
+                .all(|c| c.parent_task_id == Some(task.id))
+        );
+        assert!(children.tasks.iter().all(|c| c.goal_id == Some(goal_id)));
+        assert_eq!(children.tasks[1].priority, Priority::High);
+        assert_eq!(children.tasks[1].due_date, Some(at + Duration::hours(48)));
+        let edges: Vec<(Uuid, Uuid)> = children
+            .dependencies
+            .iter()
+            .map(|d| (d.from_task_id, d.to_task_id))
+            .collect();
+        let ids: Vec<Uuid> = children.tasks.iter().map(|c| c.id).collect();
+        assert_eq!(
+            edges,
+            vec![(ids[1], ids[0]), (ids[2], ids[0]), (ids[2], ids[1])]
+        );
+
+        // Blueprints written before children existed still load
+        let blueprint: TaskBlueprint = serde_json::from_str("{}").unwrap();
+        assert_eq!(blueprint, TaskBlueprint::default());
     }
 }
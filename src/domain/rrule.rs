@@ -0,0 +1,803 @@
+//! RFC 5545 recurrence rules: `RRULE` with the `DTSTART` and `EXDATE`
+//! properties that make up a recurrence set.
+//!
+//! Supported: `FREQ` (daily to yearly), `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY`
+//! with ordinals, `BYMONTHDAY` (negative counts from the month's end),
+//! `BYMONTH`, `BYSETPOS` and `WKST`. All times are UTC; `TZID` parameters
+//! are accepted and ignored.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Periods without a single match before evaluation gives up, so rules
+/// such as `BYMONTH=2;BYMONTHDAY=30` end instead of looping forever.
+const MAX_EMPTY_PERIODS: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RRuleError {
+    MissingFrequency,
+    InvalidPart { name: String, value: String },
+    Unsupported(String),
+    InvalidDate(String),
+    CountAndUntil,
+}
+
+impl fmt::Display for RRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RRuleError::MissingFrequency => write!(f, "Recurrence rule has no FREQ"),
+            RRuleError::InvalidPart { name, value } => {
+                write!(f, "Invalid value '{}' for {}", value, name)
+            }
+            RRuleError::Unsupported(what) => {
+                write!(f, "Unsupported recurrence rule part: {}", what)
+            }
+            RRuleError::InvalidDate(value) => write!(f, "Invalid recurrence date: {}", value),
+            RRuleError::CountAndUntil => write!(f, "COUNT and UNTIL cannot both be set"),
+        }
+    }
+}
+
+impl std::error::Error for RRuleError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A `BYDAY` entry: a weekday, optionally the nth (or nth from last) one
+/// of the month or year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+impl ByDay {
+    pub fn every(weekday: Weekday) -> Self {
+        Self {
+            ordinal: None,
+            weekday,
+        }
+    }
+
+    pub fn nth(ordinal: i32, weekday: Weekday) -> Self {
+        Self {
+            ordinal: Some(ordinal),
+            weekday,
+        }
+    }
+}
+
+/// A date left out of the recurrence set; a plain date excludes the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExDate {
+    Date(NaiveDate),
+    DateTime(DateTime<Utc>),
+}
+
+impl ExDate {
+    fn excludes(&self, at: DateTime<Utc>) -> bool {
+        match self {
+            ExDate::Date(date) => at.date_naive() == *date,
+            ExDate::DateTime(time) => at == *time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    /// Last possible occurrence, inclusive
+    pub until: Option<DateTime<Utc>>,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+    /// First occurrence and time of day; callers supply one when unset
+    pub dtstart: Option<DateTime<Utc>>,
+    pub exdates: Vec<ExDate>,
+}
+
+impl RRule {
+    pub fn new(freq: Frequency) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+            dtstart: None,
+            exdates: Vec::new(),
+        }
+    }
+
+    /// Parse either a bare rule (`FREQ=MONTHLY;BYDAY=-1FR`) or content lines
+    /// with `DTSTART`, `RRULE` and `EXDATE` properties.
+    pub fn parse(text: &str) -> Result<Self, RRuleError> {
+        let mut rule: Option<RRule> = None;
+        let mut dtstart = None;
+        let mut exdates = Vec::new();
+
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            // Dates in rules have no colons, so a line without one is a bare rule
+            let (name, params, value) = match line.split_once(':') {
+                Some((head, value)) => {
+                    let mut parts = head.split(';');
+                    let name = parts.next().unwrap_or_default().to_ascii_uppercase();
+                    (
+                        name,
+                        parts.map(str::to_ascii_uppercase).collect::<Vec<_>>(),
+                        value,
+                    )
+                }
+                None => ("RRULE".to_string(), Vec::new(), line),
+            };
+            match name.as_str() {
+                "RRULE" => rule = Some(Self::parse_rule(value)?),
+                "DTSTART" => dtstart = Some(parse_date_time(value)?),
+                "EXDATE" => {
+                    let date_only = params.iter().any(|p| p == "VALUE=DATE");
+                    for value in value.split(',').map(str::trim) {
+                        exdates.push(if date_only || value.len() == 8 {
+                            ExDate::Date(parse_date(value)?)
+                        } else {
+                            ExDate::DateTime(parse_date_time(value)?)
+                        });
+                    }
+                }
+                other => return Err(RRuleError::Unsupported(other.to_string())),
+            }
+        }
+
+        let mut rule = rule.ok_or(RRuleError::MissingFrequency)?;
+        rule.dtstart = dtstart;
+        rule.exdates = exdates;
+        Ok(rule)
+    }
+
+    fn parse_rule(value: &str) -> Result<Self, RRuleError> {
+        let mut freq = None;
+        let mut rule = RRule::new(Frequency::Daily);
+
+        for part in value.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| RRuleError::InvalidPart {
+                    name: part.to_string(),
+                    value: String::new(),
+                })?;
+            let name = name.to_ascii_uppercase();
+            let invalid = || RRuleError::InvalidPart {
+                name: name.clone(),
+                value: value.to_string(),
+            };
+            match name.as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        "SECONDLY" | "MINUTELY" | "HOURLY" => {
+                            return Err(RRuleError::Unsupported(format!("FREQ={}", value)));
+                        }
+                        _ => return Err(invalid()),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value.parse().ok().filter(|n| *n >= 1).ok_or_else(invalid)?
+                }
+                "COUNT" => {
+                    rule.count = Some(value.parse().ok().filter(|n| *n >= 1).ok_or_else(invalid)?)
+                }
+                "UNTIL" => {
+                    rule.until = Some(if value.len() == 8 {
+                        let date = parse_date(value)?;
+                        date_time(date.and_hms_opt(23, 59, 59).unwrap())
+                    } else {
+                        parse_date_time(value)?
+                    })
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|d| parse_by_day(d.trim()).ok_or_else(invalid))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => rule.by_month_day = parse_list(value, 31).ok_or_else(invalid)?,
+                "BYSETPOS" => rule.by_set_pos = parse_list(value, 366).ok_or_else(invalid)?,
+                "BYMONTH" => {
+                    rule.by_month = parse_list(value, 12)
+                        .filter(|months| months.iter().all(|m| *m > 0))
+                        .ok_or_else(invalid)?
+                        .into_iter()
+                        .map(|m| m as u32)
+                        .collect()
+                }
+                "WKST" => rule.week_start = parse_weekday(value).ok_or_else(invalid)?,
+                other => return Err(RRuleError::Unsupported(other.to_string())),
+            }
+        }
+
+        rule.freq = freq.ok_or(RRuleError::MissingFrequency)?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(RRuleError::CountAndUntil);
+        }
+        Ok(rule)
+    }
+
+    /// Up to `limit` occurrences strictly after `after`, or from the start
+    /// when `after` is `None`. `dtstart` is used when the rule has none.
+    pub fn occurrences_after(
+        &self,
+        dtstart: DateTime<Utc>,
+        after: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Vec<DateTime<Utc>> {
+        let start = self.dtstart.unwrap_or(dtstart);
+        let mut occurrences = Vec::new();
+        let mut counted = 0u32;
+        let mut empty_periods = 0;
+
+        for period in 0.. {
+            if occurrences.len() >= limit || empty_periods > MAX_EMPTY_PERIODS {
+                break;
+            }
+            let Some(days) = self.period_dates(start.date_naive(), period) else {
+                break;
+            };
+            if days.is_empty() {
+                empty_periods += 1;
+                continue;
+            }
+            empty_periods = 0;
+
+            for day in days {
+                let at = date_time(day.and_time(start.time()));
+                if at < start {
+                    continue;
+                }
+                if self.until.is_some_and(|until| at > until)
+                    || self.count.is_some_and(|count| counted >= count)
+                {
+                    return occurrences;
+                }
+                // Excluded dates still use up COUNT
+                counted += 1;
+                if self.exdates.iter().any(|ex| ex.excludes(at)) {
+                    continue;
+                }
+                if after.is_none_or(|after| at > after) {
+                    occurrences.push(at);
+                    if occurrences.len() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+        occurrences
+    }
+
+    /// The first occurrence strictly after `after`.
+    pub fn next_after(
+        &self,
+        dtstart: DateTime<Utc>,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.occurrences_after(dtstart, Some(after), 1)
+            .into_iter()
+            .next()
+    }
+
+    /// Candidate dates in the `index`th period from `start`, sorted, with
+    /// `BYSETPOS` applied. `None` once dates run past what chrono can hold.
+    fn period_dates(&self, start: NaiveDate, index: i64) -> Option<Vec<NaiveDate>> {
+        let step = index.checked_mul(self.interval as i64)?;
+        let mut dates = match self.freq {
+            Frequency::Daily => {
+                let day = start.checked_add_signed(Duration::try_days(step)?)?;
+                let matches = self.month_matches(day.month())
+                    && (self.by_month_day.is_empty()
+                        || self.by_month_day.iter().any(|d| month_day_matches(day, *d)))
+                    && (self.by_day.is_empty()
+                        || self.by_day.iter().any(|b| b.weekday == day.weekday()));
+                if matches { vec![day] } else { Vec::new() }
+            }
+            Frequency::Weekly => {
+                let offset = (7 + start.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let week = start
+                    .checked_sub_signed(Duration::days(offset as i64))?
+                    .checked_add_signed(Duration::try_weeks(step)?)?;
+                week.iter_days()
+                    .take(7)
+                    .filter(|day| {
+                        let weekday_matches = if self.by_day.is_empty() {
+                            day.weekday() == start.weekday()
+                        } else {
+                            self.by_day.iter().any(|b| b.weekday == day.weekday())
+                        };
+                        weekday_matches && self.month_matches(day.month())
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months =
+                    (start.year() as i64 * 12 + start.month0() as i64).checked_add(step)?;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = months.rem_euclid(12) as u32 + 1;
+                NaiveDate::from_ymd_opt(year, month, 1)?;
+                if self.month_matches(month) {
+                    self.month_dates(year, month, start)
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(start.year() as i64 + step).ok()?;
+                NaiveDate::from_ymd_opt(year, 1, 1)?;
+                self.year_dates(year, start)
+            }
+        };
+        dates.sort();
+        dates.dedup();
+
+        if self.by_set_pos.is_empty() {
+            return Some(dates);
+        }
+        let mut picked: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|pos| {
+                let index = if *pos > 0 {
+                    *pos as usize - 1
+                } else {
+                    dates.len().checked_sub(pos.unsigned_abs() as usize)?
+                };
+                dates.get(index).copied()
+            })
+            .collect();
+        picked.sort();
+        picked.dedup();
+        Some(picked)
+    }
+
+    fn month_matches(&self, month: u32) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&month)
+    }
+
+    /// Dates in one month: the `BYMONTHDAY`s limited by `BYDAY`, else the
+    /// `BYDAY`s counted within the month, else the start's day of month.
+    fn month_dates(&self, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        let last = last_of_month(year, month);
+        if !self.by_month_day.is_empty() {
+            first
+                .iter_days()
+                .take_while(|d| *d <= last)
+                .filter(|d| self.by_month_day.iter().any(|n| month_day_matches(*d, *n)))
+                .filter(|d| {
+                    self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|b| by_day_matches(*b, *d, first, last))
+                })
+                .collect()
+        } else if !self.by_day.is_empty() {
+            self.by_day
+                .iter()
+                .flat_map(|b| by_day_dates(*b, first, last))
+                .collect()
+        } else {
+            NaiveDate::from_ymd_opt(year, month, start.day())
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn year_dates(&self, year: i32, start: NaiveDate) -> Vec<NaiveDate> {
+        if !self.by_month.is_empty() {
+            self.by_month
+                .iter()
+                .flat_map(|month| self.month_dates(year, *month, start))
+                .collect()
+        } else if !self.by_month_day.is_empty() {
+            (1..=12)
+                .flat_map(|month| self.month_dates(year, month, start))
+                .collect()
+        } else if !self.by_day.is_empty() {
+            let first = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            let last = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
+            self.by_day
+                .iter()
+                .flat_map(|b| by_day_dates(*b, first, last))
+                .collect()
+        } else {
+            NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                .into_iter()
+                .collect()
+        }
+    }
+
+    /// The `RRULE` property value, without `DTSTART` or `EXDATE`.
+    pub fn rule_text(&self) -> String {
+        let mut parts = vec![format!("FREQ={}", self.freq.as_str())];
+        if self.interval != 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", format_date_time(until)));
+        }
+        if !self.by_month.is_empty() {
+            parts.push(format!("BYMONTH={}", join(&self.by_month)));
+        }
+        if !self.by_month_day.is_empty() {
+            parts.push(format!("BYMONTHDAY={}", join(&self.by_month_day)));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|b| {
+                    let ordinal = b.ordinal.map(|n| n.to_string()).unwrap_or_default();
+                    format!("{}{}", ordinal, weekday_code(b.weekday))
+                })
+                .collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if !self.by_set_pos.is_empty() {
+            parts.push(format!("BYSETPOS={}", join(&self.by_set_pos)));
+        }
+        if self.week_start != Weekday::Mon {
+            parts.push(format!("WKST={}", weekday_code(self.week_start)));
+        }
+        parts.join(";")
+    }
+}
+
+/// Content lines: `DTSTART` when set, the `RRULE` and any `EXDATE`s.
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(dtstart) = self.dtstart {
+            writeln!(f, "DTSTART:{}", format_date_time(dtstart))?;
+        }
+        write!(f, "RRULE:{}", self.rule_text())?;
+
+        let dates: Vec<String> = self
+            .exdates
+            .iter()
+            .filter_map(|ex| match ex {
+                ExDate::Date(date) => Some(date.format("%Y%m%d").to_string()),
+                ExDate::DateTime(_) => None,
+            })
+            .collect();
+        if !dates.is_empty() {
+            write!(f, "\nEXDATE;VALUE=DATE:{}", dates.join(","))?;
+        }
+        let times: Vec<String> = self
+            .exdates
+            .iter()
+            .filter_map(|ex| match ex {
+                ExDate::DateTime(time) => Some(format_date_time(*time)),
+                ExDate::Date(_) => None,
+            })
+            .collect();
+        if !times.is_empty() {
+            write!(f, "\nEXDATE:{}", times.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for RRule {
+    type Err = RRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for RRule {
+    type Error = RRuleError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<RRule> for String {
+    fn from(rule: RRule) -> Self {
+        rule.to_string()
+    }
+}
+
+fn date_time(naive: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::from_naive_utc_and_offset(naive, Utc)
+}
+
+pub(crate) fn parse_date(value: &str) -> Result<NaiveDate, RRuleError> {
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .map_err(|_| RRuleError::InvalidDate(value.to_string()))
+}
+
+/// `20250131T090000Z`, or a floating `20250131T090000` taken as UTC.
+pub(crate) fn parse_date_time(value: &str) -> Result<DateTime<Utc>, RRuleError> {
+    if value.len() == 8 {
+        return Ok(date_time(parse_date(value)?.and_hms_opt(0, 0, 0).unwrap()));
+    }
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map(date_time)
+        .map_err(|_| RRuleError::InvalidDate(value.to_string()))
+}
+
+pub(crate) fn format_date_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(T::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Comma-separated non-zero numbers within `-max..=max`.
+fn parse_list(value: &str, max: i32) -> Option<Vec<i32>> {
+    value
+        .split(',')
+        .map(|n| {
+            n.trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= max)
+        })
+        .collect()
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+pub fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// `MO`, `2TU`, `-1FR` or `+1MO`.
+fn parse_by_day(value: &str) -> Option<ByDay> {
+    let split = value.len().checked_sub(2)?;
+    let weekday = parse_weekday(value.get(split..)?)?;
+    let ordinal = value.get(..split)?;
+    if ordinal.is_empty() {
+        return Some(ByDay::every(weekday));
+    }
+    let n: i32 = ordinal.trim_start_matches('+').parse().ok()?;
+    (n != 0 && n.abs() <= 53).then_some(ByDay::nth(n, weekday))
+}
+
+fn last_of_month(year: i32, month: u32) -> NaiveDate {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .unwrap()
+        .pred_opt()
+        .unwrap()
+}
+
+fn month_day_matches(date: NaiveDate, n: i32) -> bool {
+    if n > 0 {
+        date.day() == n as u32
+    } else {
+        let last = last_of_month(date.year(), date.month()).day() as i32;
+        date.day() as i32 == last + 1 + n
+    }
+}
+
+/// Every date in `first..=last` that `by_day` picks.
+fn by_day_dates(by_day: ByDay, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+    let matching: Vec<NaiveDate> = first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .filter(|d| d.weekday() == by_day.weekday)
+        .collect();
+    match by_day.ordinal {
+        None => matching,
+        Some(n) if n > 0 => matching.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) => matching
+            .len()
+            .checked_sub(n.unsigned_abs() as usize)
+            .and_then(|i| matching.get(i).copied())
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn by_day_matches(by_day: ByDay, date: NaiveDate, first: NaiveDate, last: NaiveDate) -> bool {
+    match by_day.ordinal {
+        None => date.weekday() == by_day.weekday,
+        Some(_) => by_day_dates(by_day, first, last).contains(&date),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 9, 0, 0).unwrap()
+    }
+
+    fn dates(rule: &str, start: DateTime<Utc>, limit: usize) -> Vec<String> {
+        RRule::parse(rule)
+            .unwrap()
+            .occurrences_after(start, None, limit)
+            .iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let text = "DTSTART:20250106T090000Z\n\
+                    RRULE:FREQ=MONTHLY;INTERVAL=2;COUNT=6;BYDAY=-1FR,2TU;BYSETPOS=1;WKST=SU\n\
+                    EXDATE;VALUE=DATE:20251225\n\
+                    EXDATE:20250301T090000Z";
+        let rule = RRule::parse(text).unwrap();
+        assert_eq!(rule.freq, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(
+            rule.by_day,
+            vec![ByDay::nth(-1, Weekday::Fri), ByDay::nth(2, Weekday::Tue)]
+        );
+        assert_eq!(rule.week_start, Weekday::Sun);
+        assert_eq!(rule.exdates.len(), 2);
+        assert_eq!(RRule::parse(&rule.to_string()).unwrap(), rule);
+
+        let bare = RRule::parse("FREQ=WEEKLY;BYDAY=MO,WE").unwrap();
+        assert_eq!(bare.to_string(), "RRULE:FREQ=WEEKLY;BYDAY=MO,WE");
+
+        assert_eq!(
+            RRule::parse("INTERVAL=2"),
+            Err(RRuleError::MissingFrequency)
+        );
+        assert_eq!(
+            RRule::parse("FREQ=DAILY;COUNT=2;UNTIL=20250101"),
+            Err(RRuleError::CountAndUntil)
+        );
+        assert!(matches!(
+            RRule::parse("FREQ=DAILY;BYDAY=0MO"),
+            Err(RRuleError::InvalidPart { .. })
+        ));
+        assert!(matches!(
+            RRule::parse("FREQ=HOURLY"),
+            Err(RRuleError::Unsupported(_))
+        ));
+        assert!(matches!(
+            RRule::parse("FREQ=DAILY;BYWEEKNO=20"),
+            Err(RRuleError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_monthly_ordinals_and_negative_days() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR", at(2025, 1, 1), 3),
+            ["2025-01-31", "2025-02-28", "2025-03-28"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=2TU", at(2025, 1, 1), 3),
+            ["2025-01-14", "2025-02-11", "2025-03-11"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1", at(2024, 1, 15), 3),
+            ["2024-01-31", "2024-02-29", "2024-03-31"]
+        );
+        // Last weekday of the month
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+                at(2025, 5, 1),
+                2
+            ),
+            ["2025-05-30", "2025-06-30"]
+        );
+        // Friday the 13th
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13", at(2025, 1, 1), 2),
+            ["2025-06-13", "2026-02-13"]
+        );
+        // Months without the start's day are skipped
+        assert_eq!(
+            dates("FREQ=MONTHLY", at(2025, 1, 31), 3),
+            ["2025-01-31", "2025-03-31", "2025-05-31"]
+        );
+        assert!(dates("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30", at(2025, 1, 1), 1).is_empty());
+    }
+
+    #[test]
+    fn test_weekly_yearly_count_until_and_exdates() {
+        // Every other week on Monday and Thursday
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH", at(2025, 1, 6), 4),
+            ["2025-01-06", "2025-01-09", "2025-01-20", "2025-01-23"]
+        );
+        assert_eq!(
+            dates("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", at(2025, 1, 1), 2),
+            ["2025-11-27", "2026-11-26"]
+        );
+        assert_eq!(dates("FREQ=DAILY;COUNT=3", at(2025, 1, 1), 10).len(), 3);
+        assert_eq!(
+            dates("FREQ=DAILY;UNTIL=20250103T090000Z", at(2025, 1, 1), 10),
+            ["2025-01-01", "2025-01-02", "2025-01-03"]
+        );
+
+        // Weekdays except holidays; excluded dates still count towards COUNT
+        let rule =
+            "RRULE:FREQ=DAILY;COUNT=5;BYDAY=MO,TU,WE,TH,FR\nEXDATE;VALUE=DATE:20251225,20251226";
+        assert_eq!(
+            dates(rule, at(2025, 12, 22), 10),
+            ["2025-12-22", "2025-12-23", "2025-12-24"]
+        );
+
+        let rule = RRule::parse("FREQ=DAILY").unwrap();
+        assert_eq!(
+            rule.next_after(at(2025, 1, 1), at(2025, 3, 1)),
+            Some(at(2025, 3, 2))
+        );
+        let preview = rule.occurrences_after(at(2025, 1, 1), Some(at(2025, 3, 1)), 3);
+        assert_eq!(
+            preview,
+            vec![at(2025, 3, 2), at(2025, 3, 3), at(2025, 3, 4)]
+        );
+    }
+}
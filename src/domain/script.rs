@@ -0,0 +1,369 @@
+//! Sandboxed [Rhai](https://rhai.rs) scripts for custom validation rules,
+//! custom transition conditions and computed default values.
+//!
+//! Scripts see these variables:
+//!
+//! - `task`: a map of `id`, `title`, `description`, `status`, `priority`,
+//!   `state` (the workflow state), `assignee`, `tags` (an array),
+//!   `estimated_hours` and `actual_hours` (numbers), and `due_date`,
+//!   `scheduled_date` and `created_at` (RFC 3339 strings). Unset values
+//!   are `()`.
+//! - `metadata`: the task's metadata fields, as strings. Use `parse_float`
+//!   or `parse_int` for numbers.
+//! - `subtasks`: an array of maps with `title` and `completed`.
+//! - `dependencies`: the tasks this one depends on, as maps with `id`,
+//!   `title`, `status` and `type`.
+//! - `relations`: the tasks or goals each relation field links to, in
+//!   formulas; see `domain::formula`.
+//! - `field` and `value`: the name and value of the field being checked,
+//!   in custom validation rules only.
+//!
+//! A validation script passes by returning `true` or nothing, and fails by
+//! returning `false`, returning a message, or `throw`ing one. A default
+//! value script returns the value to store.
+//!
+//! Scripts can't import modules, `eval` code or print, and run within the
+//! operation, time, size and call depth bounds of `ScriptLimits`.
+
+use crate::domain::dependency::DependencyType;
+use crate::domain::formula::{self, RelatedItem};
+use crate::domain::task::Task;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_duration: Duration,
+    pub max_string_size: usize,
+    /// Largest array or map a script may build
+    pub max_collection_size: usize,
+    pub max_call_depth: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            max_duration: Duration::from_millis(250),
+            max_string_size: 64 * 1024,
+            max_collection_size: 10_000,
+            max_call_depth: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    Syntax { message: String, line: Option<usize> },
+    Runtime { message: String, line: Option<usize> },
+    Timeout(Duration),
+    /// The script outgrew one of its `ScriptLimits`
+    LimitExceeded(String),
+    /// The script ran and rejected the task, with its message
+    Failed(String),
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = |line: &Option<usize>| line.map(|l| format!(" on line {}", l)).unwrap_or_default();
+        match self {
+            ScriptError::Syntax { message, line } => {
+                write!(f, "Script syntax error{}: {}", at(line), message)
+            }
+            ScriptError::Runtime { message, line } => {
+                write!(f, "Script error{}: {}", at(line), message)
+            }
+            ScriptError::Timeout(limit) => {
+                write!(f, "Script ran longer than {} ms", limit.as_millis())
+            }
+            ScriptError::LimitExceeded(limit) => write!(f, "Script exceeded its {} limit", limit),
+            ScriptError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// What a script can see of a task.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    task: Map,
+    metadata: Map,
+    subtasks: Array,
+    dependencies: Array,
+    relations: Map,
+}
+
+fn optional<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+impl ScriptContext {
+    /// `task` and the tasks it depends on, with how it depends on each.
+    pub fn for_task(task: &Task, dependencies: &[(DependencyType, &Task)]) -> Self {
+        let mut map = Map::new();
+        let mut set = |key: &str, value: Dynamic| {
+            map.insert(key.into(), value);
+        };
+        set("id", task.id.to_string().into());
+        set("title", task.title.clone().into());
+        set("description", task.description.clone().into());
+        set("status", format!("{:?}", task.status).into());
+        set("priority", format!("{:?}", task.priority).into());
+        set("state", optional(task.workflow_state.clone()));
+        set("assignee", optional(task.assignee.clone()));
+        set(
+            "tags",
+            task.tags.iter().cloned().map(Dynamic::from).collect::<Array>().into(),
+        );
+        set("estimated_hours", optional(task.estimated_hours.map(f64::from)));
+        set("actual_hours", optional(task.actual_hours.map(f64::from)));
+        set("due_date", optional(task.due_date.map(|d| d.to_rfc3339())));
+        set("scheduled_date", optional(task.scheduled_date.map(|d| d.to_rfc3339())));
+        set("created_at", task.created_at.to_rfc3339().into());
+
+        let subtasks = task
+            .subtasks
+            .iter()
+            .map(|s| {
+                let mut subtask = Map::new();
+                subtask.insert("title".into(), s.title.clone().into());
+                subtask.insert("completed".into(), s.completed.into());
+                subtask.into()
+            })
+            .collect();
+        let dependencies = dependencies
+            .iter()
+            .map(|(kind, other)| {
+                let mut dependency = Map::new();
+                dependency.insert("id".into(), other.id.to_string().into());
+                dependency.insert("title".into(), other.title.clone().into());
+                dependency.insert("status".into(), format!("{:?}", other.status).into());
+                dependency.insert("type".into(), format!("{:?}", kind).into());
+                dependency.into()
+            })
+            .collect();
+
+        Self {
+            task: map,
+            metadata: Self::metadata_map(&task.metadata),
+            subtasks,
+            dependencies,
+            relations: Map::new(),
+        }
+    }
+
+    /// Add the items each relation field links to.
+    pub fn with_relations(mut self, relations: &HashMap<String, Vec<RelatedItem>>) -> Self {
+        self.relations = relations
+            .iter()
+            .map(|(field, items)| {
+                let items: Array = items.iter().map(|item| item.to_map().into()).collect();
+                (field.as_str().into(), items.into())
+            })
+            .collect();
+        self
+    }
+
+    /// Update a metadata field, so later formulas see a computed value.
+    pub fn set_metadata(&mut self, name: &str, value: Option<&str>) {
+        match value {
+            Some(value) => self.metadata.insert(name.into(), value.to_string().into()),
+            None => self.metadata.remove(name),
+        };
+    }
+
+    /// Metadata alone, for validating fields without a task.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        Self {
+            metadata: Self::metadata_map(metadata),
+            ..Self::default()
+        }
+    }
+
+    fn metadata_map(metadata: &HashMap<String, String>) -> Map {
+        metadata
+            .iter()
+            .map(|(k, v)| (k.as_str().into(), v.clone().into()))
+            .collect()
+    }
+
+    fn scope(&self) -> Scope<'static> {
+        let mut scope = Scope::new();
+        scope.push_constant("task", self.task.clone());
+        scope.push_constant("metadata", self.metadata.clone());
+        scope.push_constant("subtasks", self.subtasks.clone());
+        scope.push_constant("dependencies", self.dependencies.clone());
+        scope.push_constant("relations", self.relations.clone());
+        scope
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptEngine {
+    limits: ScriptLimits,
+}
+
+impl ScriptEngine {
+    pub fn new(limits: ScriptLimits) -> Self {
+        Self { limits }
+    }
+
+    /// Run a validation script; `Ok` if it passes.
+    pub fn check(&self, script: &str, context: &ScriptContext) -> Result<(), ScriptError> {
+        let result = self.run(script, context.scope())?;
+        Self::verdict(result)
+    }
+
+    /// Run a custom validation rule against one field's value.
+    pub fn check_value(
+        &self,
+        script: &str,
+        context: &ScriptContext,
+        field: &str,
+        value: &str,
+    ) -> Result<(), ScriptError> {
+        let mut scope = context.scope();
+        scope.push_constant("field", field.to_string());
+        scope.push_constant("value", value.to_string());
+        Self::verdict(self.run(script, scope)?)
+    }
+
+    /// Run a default value script, returning what it produces as text.
+    pub fn evaluate(&self, script: &str, context: &ScriptContext) -> Result<String, ScriptError> {
+        let result = self.run(script, context.scope())?;
+        Ok(if result.is_unit() { String::new() } else { result.to_string() })
+    }
+
+    fn verdict(result: Dynamic) -> Result<(), ScriptError> {
+        if result.is_unit() {
+            return Ok(());
+        }
+        if let Ok(passed) = result.as_bool() {
+            return if passed {
+                Ok(())
+            } else {
+                Err(ScriptError::Failed("Custom validation failed".to_string()))
+            };
+        }
+        match result.into_string() {
+            Ok(message) if message.is_empty() => Ok(()),
+            Ok(message) => Err(ScriptError::Failed(message)),
+            Err(kind) => Err(ScriptError::Runtime {
+                message: format!(
+                    "validation scripts return true, false or a message, not {}",
+                    kind
+                ),
+                line: None,
+            }),
+        }
+    }
+
+    fn run(&self, script: &str, mut scope: Scope<'static>) -> Result<Dynamic, ScriptError> {
+        let engine = self.engine();
+        let ast = engine.compile(script).map_err(|e| ScriptError::Syntax {
+            message: e.0.to_string(),
+            line: e.1.line(),
+        })?;
+        engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+            .map_err(|e| self.error(*e))
+    }
+
+    fn engine(&self) -> Engine {
+        let limits = self.limits;
+        let started = Instant::now();
+        let mut engine = Engine::new();
+        engine
+            .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+            .set_max_modules(0)
+            .set_max_operations(limits.max_operations)
+            .set_max_call_levels(limits.max_call_depth)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(limits.max_string_size)
+            .set_max_array_size(limits.max_collection_size)
+            .set_max_map_size(limits.max_collection_size)
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {})
+            .on_progress(move |_| {
+                (started.elapsed() > limits.max_duration).then_some(Dynamic::UNIT)
+            });
+        engine.disable_symbol("eval");
+        formula::register_functions(&mut engine);
+        engine
+    }
+
+    fn error(&self, mut error: EvalAltResult) -> ScriptError {
+        let line = error.take_position().line();
+        match error {
+            EvalAltResult::ErrorRuntime(value, _) => ScriptError::Failed(value.to_string()),
+            EvalAltResult::ErrorTerminated(..) => ScriptError::Timeout(self.limits.max_duration),
+            EvalAltResult::ErrorTooManyOperations(_) => {
+                ScriptError::LimitExceeded("operation".to_string())
+            }
+            EvalAltResult::ErrorStackOverflow(_) => {
+                ScriptError::LimitExceeded("call depth".to_string())
+            }
+            EvalAltResult::ErrorDataTooLarge(what, _) => ScriptError::LimitExceeded(what),
+            EvalAltResult::ErrorTooManyModules(_) | EvalAltResult::ErrorModuleNotFound(..) => {
+                ScriptError::Runtime {
+                    message: "scripts cannot import modules".to_string(),
+                    line,
+                }
+            }
+            other => ScriptError::Runtime {
+                message: other.to_string(),
+                line,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_see_the_task_and_stay_sandboxed() {
+        let mut task = Task::new("Release 2.0".to_string(), String::new());
+        task.estimated_hours = Some(6.0);
+        task.metadata.insert("story_points".to_string(), "5".to_string());
+        task.add_subtask("Changelog".to_string());
+        let blocker = Task::new("Fix crash".to_string(), String::new());
+        let context = ScriptContext::for_task(&task, &[(DependencyType::FinishToStart, &blocker)]);
+        let engine = ScriptEngine::default();
+
+        assert!(engine.check("parse_int(metadata.story_points) <= 8", &context).is_ok());
+        assert!(engine.check("task.estimated_hours > 4.0 && subtasks.len() == 1", &context).is_ok());
+        assert_eq!(
+            engine.check(
+                r#"if dependencies.some(|d| d.status != "Done") { "Blocked by " + dependencies[0].title }"#,
+                &context
+            ),
+            Err(ScriptError::Failed("Blocked by Fix crash".to_string()))
+        );
+        assert_eq!(
+            engine.check_value(r#"if value.len() < 3 { throw field + " is too short" }"#, &context, "sprint", "S1"),
+            Err(ScriptError::Failed("sprint is too short".to_string()))
+        );
+        assert_eq!(engine.evaluate("task.estimated_hours * 2.0", &context).unwrap(), "12.0");
+        assert_eq!(
+            engine.evaluate(r#"task.title.sub_string(0, 7)"#, &context).unwrap(),
+            "Release"
+        );
+
+        assert!(matches!(
+            engine.check("let x = ", &context),
+            Err(ScriptError::Syntax { line: Some(1), .. })
+        ));
+        assert!(matches!(
+            engine.check("loop { }", &context),
+            Err(ScriptError::LimitExceeded(_) | ScriptError::Timeout(_))
+        ));
+        assert!(engine.check(r#"eval("true")"#, &context).is_err());
+        assert!(engine.check(r#"import "std" as s; true"#, &context).is_err());
+        assert!(engine.check("let s = \"x\"; loop { s += s; }", &context).is_err());
+    }
+}
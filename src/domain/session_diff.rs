@@ -0,0 +1,424 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Patches larger than this are cut at a line boundary before being stored.
+pub const MAX_PATCH_BYTES: usize = 1024 * 1024;
+
+/// Line counts for one file, from `git diff --numstat`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileDiffStat {
+    pub path: String,
+    pub additions: u32,
+    pub deletions: u32,
+    pub binary: bool,
+}
+
+/// What a Claude Code session changed relative to the base branch, captured
+/// when the session ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionDiff {
+    pub session_id: Uuid,
+    pub base_branch: String,
+    pub files: Vec<FileDiffStat>,
+    /// Unified diff of the whole branch
+    pub patch: String,
+    /// The patch was longer than `MAX_PATCH_BYTES` and has been cut short
+    pub truncated: bool,
+    pub captured_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@ ...` line
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// One row of a side-by-side view: old file on the left, new file on the right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SideBySideRow {
+    pub left: Option<DiffLine>,
+    pub right: Option<DiffLine>,
+}
+
+/// The part of the unified diff that belongs to one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    pub path: String,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A row of the changed-files tree, flattened in display order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileTreeEntry {
+    pub depth: usize,
+    pub name: String,
+    /// Set for files, `None` for directories
+    pub file: Option<FileDiffStat>,
+}
+
+impl SessionDiff {
+    /// Build from the output of `git diff --numstat <base>` and `git diff <base>`.
+    pub fn from_git(session_id: Uuid, base_branch: &str, numstat: &str, patch: &str) -> Self {
+        let (patch, truncated) = truncate_patch(patch);
+        Self {
+            session_id,
+            base_branch: base_branch.to_string(),
+            files: parse_numstat(numstat),
+            patch,
+            truncated,
+            captured_at: Utc::now(),
+        }
+    }
+
+    pub fn total_additions(&self) -> u32 {
+        self.files.iter().map(|f| f.additions).sum()
+    }
+
+    pub fn total_deletions(&self) -> u32 {
+        self.files.iter().map(|f| f.deletions).sum()
+    }
+
+    pub fn file_patches(&self) -> Vec<FilePatch> {
+        parse_patch(&self.patch)
+    }
+
+    pub fn file_patch(&self, path: &str) -> Option<FilePatch> {
+        self.file_patches().into_iter().find(|p| p.path == path)
+    }
+
+    /// Changed files grouped under their directories, sorted by path.
+    pub fn file_tree(&self) -> Vec<FileTreeEntry> {
+        let mut files = self.files.clone();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut entries = Vec::new();
+        let mut open_dirs: Vec<&str> = Vec::new();
+        for file in &files {
+            let parts: Vec<&str> = file.path.split('/').collect();
+            let (name, dirs) = parts.split_last().expect("split yields at least one part");
+            let shared = open_dirs
+                .iter()
+                .zip(dirs.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            open_dirs.truncate(shared);
+            for dir in &dirs[shared..] {
+                entries.push(FileTreeEntry {
+                    depth: open_dirs.len(),
+                    name: dir.to_string(),
+                    file: None,
+                });
+                open_dirs.push(dir);
+            }
+            entries.push(FileTreeEntry {
+                depth: dirs.len(),
+                name: name.to_string(),
+                file: Some(file.clone()),
+            });
+        }
+        entries
+    }
+}
+
+impl DiffHunk {
+    /// Pair removed lines with the added lines that replace them; context lines
+    /// appear on both sides.
+    pub fn side_by_side(&self) -> Vec<SideBySideRow> {
+        let mut rows = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+
+        fn flush(
+            rows: &mut Vec<SideBySideRow>,
+            removed: &mut Vec<DiffLine>,
+            added: &mut Vec<DiffLine>,
+        ) {
+            let len = removed.len().max(added.len());
+            let mut left = removed.drain(..);
+            let mut right = added.drain(..);
+            for _ in 0..len {
+                rows.push(SideBySideRow {
+                    left: left.next(),
+                    right: right.next(),
+                });
+            }
+        }
+
+        for line in &self.lines {
+            match line.kind {
+                DiffLineKind::Removed => {
+                    if !added.is_empty() {
+                        flush(&mut rows, &mut removed, &mut added);
+                    }
+                    removed.push(line.clone());
+                }
+                DiffLineKind::Added => added.push(line.clone()),
+                DiffLineKind::Context => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push(SideBySideRow {
+                        left: Some(line.clone()),
+                        right: Some(line.clone()),
+                    });
+                }
+            }
+        }
+        flush(&mut rows, &mut removed, &mut added);
+        rows
+    }
+}
+
+fn truncate_patch(patch: &str) -> (String, bool) {
+    if patch.len() <= MAX_PATCH_BYTES {
+        return (patch.to_string(), false);
+    }
+    let mut end = MAX_PATCH_BYTES;
+    while !patch.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = patch[..end].rfind('\n').map(|i| i + 1).unwrap_or(end);
+    (patch[..end].to_string(), true)
+}
+
+/// Parse `git diff --numstat` output. Binary files report `-` for both counts.
+pub fn parse_numstat(numstat: &str) -> Vec<FileDiffStat> {
+    numstat
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let additions = fields.next()?;
+            let deletions = fields.next()?;
+            let path = fields.next()?.trim();
+            if path.is_empty() {
+                return None;
+            }
+            let binary = additions == "-" && deletions == "-";
+            Some(FileDiffStat {
+                path: rename_target(path),
+                additions: if binary { 0 } else { additions.parse().ok()? },
+                deletions: if binary { 0 } else { deletions.parse().ok()? },
+                binary,
+            })
+        })
+        .collect()
+}
+
+/// Numstat shows renames as `old => new` or `dir/{old => new}/file`.
+fn rename_target(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.find('}'))
+        && let Some((_, new)) = path[open + 1..close].split_once(" => ")
+    {
+        let joined = format!("{}{}{}", &path[..open], new, &path[close + 1..]);
+        return joined.replace("//", "/");
+    }
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Split a unified diff into per-file hunks.
+pub fn parse_patch(patch: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut old_line = 0;
+    let mut new_line = 0;
+
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header
+                .rsplit_once(" b/")
+                .map(|(_, new)| new)
+                .unwrap_or(header);
+            files.push(FilePatch {
+                path: path.to_string(),
+                binary: false,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@") {
+            let (old_start, new_start) = hunk_starts(line);
+            old_line = old_start;
+            new_line = new_start;
+            file.hunks.push(DiffHunk {
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(hunk) = file.hunks.last_mut() else {
+            // File header lines: index, mode changes, ---/+++ and binary markers
+            if line.starts_with("Binary files ") {
+                file.binary = true;
+            }
+            continue;
+        };
+
+        let (kind, text) = match line.chars().next() {
+            Some('+') => (DiffLineKind::Added, &line[1..]),
+            Some('-') => (DiffLineKind::Removed, &line[1..]),
+            Some(' ') => (DiffLineKind::Context, &line[1..]),
+            Some('\\') => continue, // "\ No newline at end of file"
+            None => (DiffLineKind::Context, ""),
+            _ => continue,
+        };
+        let (old, new) = match kind {
+            DiffLineKind::Added => (None, Some(new_line)),
+            DiffLineKind::Removed => (Some(old_line), None),
+            DiffLineKind::Context => (Some(old_line), Some(new_line)),
+        };
+        if old.is_some() {
+            old_line += 1;
+        }
+        if new.is_some() {
+            new_line += 1;
+        }
+        hunk.lines.push(DiffLine {
+            kind,
+            old_line: old,
+            new_line: new,
+            text: text.to_string(),
+        });
+    }
+
+    files
+}
+
+/// Start lines from `@@ -12,5 +14,7 @@`.
+fn hunk_starts(header: &str) -> (u32, u32) {
+    let start = |prefix: char| {
+        header
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix(prefix))
+            .and_then(|range| range.split(',').next())
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(1)
+    };
+    (start('-'), start('+'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUMSTAT: &str = "3\t1\tsrc/lib.rs\n\
+                           10\t0\tsrc/services/new.rs\n\
+                           -\t-\tassets/logo.png\n\
+                           0\t0\tsrc/{old => renamed}/mod.rs\n\
+                           2\t2\tREADME.md\n";
+
+    const PATCH: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,6 @@
+ pub mod domain;
+-pub mod old;
++pub mod new;
++pub mod services;
++pub mod ui;
+ pub mod repository;
+@@ -20,2 +22,2 @@ fn main() {
+-    old::run();
++    new::run();
+diff --git a/assets/logo.png b/assets/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/assets/logo.png differ
+";
+
+    #[test]
+    fn test_parse_numstat() {
+        let diff = SessionDiff::from_git(Uuid::new_v4(), "main", NUMSTAT, PATCH);
+        assert_eq!(diff.files.len(), 5);
+        assert_eq!(diff.total_additions(), 15);
+        assert_eq!(diff.total_deletions(), 3);
+        assert!(diff.files[2].binary);
+        assert_eq!(diff.files[3].path, "src/renamed/mod.rs");
+        assert_eq!(rename_target("a.txt => b.txt"), "b.txt");
+        assert_eq!(rename_target("src/{ => nested}/x.rs"), "src/nested/x.rs");
+        assert!(!diff.truncated);
+    }
+
+    #[test]
+    fn test_file_tree_groups_by_directory() {
+        let diff = SessionDiff::from_git(Uuid::new_v4(), "main", NUMSTAT, "");
+        let entries = diff.file_tree();
+        let tree: Vec<(usize, &str, bool)> = entries
+            .iter()
+            .map(|e| (e.depth, e.name.as_str(), e.file.is_some()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (0, "README.md", true),
+                (0, "assets", false),
+                (1, "logo.png", true),
+                (0, "src", false),
+                (1, "lib.rs", true),
+                (1, "renamed", false),
+                (2, "mod.rs", true),
+                (1, "services", false),
+                (2, "new.rs", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_patch_and_side_by_side() {
+        let diff = SessionDiff::from_git(Uuid::new_v4(), "main", NUMSTAT, PATCH);
+        let patches = diff.file_patches();
+        assert_eq!(patches.len(), 2);
+        assert!(diff.file_patch("assets/logo.png").unwrap().binary);
+
+        let lib = diff.file_patch("src/lib.rs").unwrap();
+        assert_eq!(lib.hunks.len(), 2);
+        let first = &lib.hunks[0];
+        assert_eq!(first.lines.len(), 6);
+        assert_eq!(first.lines[1].old_line, Some(2));
+        assert_eq!(first.lines[2].new_line, Some(2));
+        assert_eq!(first.lines[5].old_line, Some(3));
+        assert_eq!(first.lines[5].new_line, Some(5));
+        assert_eq!(lib.hunks[1].lines[1].new_line, Some(22));
+
+        let rows = first.side_by_side();
+        assert_eq!(rows.len(), 5);
+        // The removed line sits beside its replacement, extra additions stand alone
+        assert_eq!(rows[1].left.as_ref().unwrap().text, "pub mod old;");
+        assert_eq!(rows[1].right.as_ref().unwrap().text, "pub mod new;");
+        assert!(rows[2].left.is_none());
+        assert!(rows[3].left.is_none());
+        assert_eq!(rows[4].left, rows[4].right);
+    }
+
+    #[test]
+    fn test_large_patch_is_truncated_at_line_boundary() {
+        let line = "+".repeat(99) + "\n";
+        let patch = line.repeat(MAX_PATCH_BYTES / 100 + 10);
+        let diff = SessionDiff::from_git(Uuid::new_v4(), "main", "", &patch);
+        assert!(diff.truncated);
+        assert!(diff.patch.len() <= MAX_PATCH_BYTES);
+        assert!(diff.patch.ends_with('\n'));
+    }
+}
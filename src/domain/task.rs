@@ -27,6 +27,10 @@ pub struct Task {
     pub is_archived: bool,
     pub assignee: Option<String>,
     pub configuration_id: Option<Uuid>, // Link to task configuration
+    /// State in the configuration's state machine; authoritative over
+    /// `status` for configured tasks
+    #[serde(default)]
+    pub workflow_state: Option<String>,
     pub sort_order: i32, // For ordering within Kanban columns
 }
 
@@ -96,6 +100,7 @@ impl Task {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,
         }
     }
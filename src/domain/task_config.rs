@@ -1,5 +1,9 @@
+use crate::domain::formula;
+use crate::domain::metadata_value::MetadataValue;
+use crate::domain::script::{ScriptContext, ScriptEngine};
+use crate::domain::task::{Task, TaskStatus};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +51,9 @@ pub enum TransitionCondition {
     },
     RequireApproval {
         role: String,
+        /// How many resources with `role` must approve
+        #[serde(default = "default_quorum")]
+        quorum: usize,
     },
     RequireAllSubtasksComplete,
     CustomValidation {
@@ -54,6 +61,10 @@ pub enum TransitionCondition {
     },
 }
 
+fn default_quorum() -> usize {
+    1
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TransitionEffect {
     SetMetadataField { field: String, value: String },
@@ -85,6 +96,16 @@ pub struct MetadataFieldConfig {
     pub required: bool,
     pub options: Vec<FieldOption>,
     pub default_value: Option<String>,
+    /// Script computing the default in place of `default_value`; see
+    /// `domain::script` for what it can use
+    #[serde(default)]
+    pub default_script: Option<String>,
+    /// Expression a `Formula` field is computed from; see `domain::formula`
+    #[serde(default)]
+    pub formula: Option<String>,
+    /// What a `Relation` field links to
+    #[serde(default)]
+    pub relation: Option<RelationTarget>,
     pub validation_rules: Vec<ValidationRule>,
     pub help_text: String,
     pub show_in_list: bool,
@@ -124,6 +145,13 @@ pub enum FieldType {
     Relation,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RelationTarget {
+    #[default]
+    Tasks,
+    Goals,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ValidationRule {
     MinLength(usize),
@@ -185,9 +213,41 @@ impl TaskConfiguration {
     }
 
     pub fn validate_metadata(&self, metadata: &HashMap<String, String>) -> Result<(), Vec<String>> {
+        self.validate_with(metadata, &ScriptContext::from_metadata(metadata), |_| true)
+    }
+
+    /// Validate a task's metadata, giving custom validation scripts the
+    /// whole task to look at.
+    pub fn validate_task(&self, task: &Task, context: &ScriptContext) -> Result<(), Vec<String>> {
+        self.validate_with(&task.metadata, context, |_| true)
+    }
+
+    /// Validate only the fields an edit changed from `before`, so values
+    /// stored earlier (say, before a field became required) don't block
+    /// unrelated edits.
+    pub fn validate_changes(
+        &self,
+        before: &HashMap<String, String>,
+        task: &Task,
+        context: &ScriptContext,
+    ) -> Result<(), Vec<String>> {
+        self.validate_with(&task.metadata, context, |name| {
+            task.metadata.get(name) != before.get(name)
+        })
+    }
+
+    fn validate_with(
+        &self,
+        metadata: &HashMap<String, String>,
+        context: &ScriptContext,
+        changed: impl Fn(&str) -> bool,
+    ) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
         for (name, field) in &self.metadata_schema.fields {
+            if !changed(name) {
+                continue;
+            }
             if field.required && !metadata.contains_key(name) {
                 errors.push(format!(
                     "Required field '{}' is missing",
@@ -196,8 +256,12 @@ impl TaskConfiguration {
             }
 
             if let Some(value) = metadata.get(name) {
+                if let Err(e) = field.typed_value(value) {
+                    errors.push(e);
+                    continue;
+                }
                 for rule in &field.validation_rules {
-                    if let Err(e) = self.validate_field_rule(field, value, rule) {
+                    if let Err(e) = self.validate_field_rule(field, value, rule, context) {
                         errors.push(e);
                     }
                 }
@@ -216,6 +280,7 @@ impl TaskConfiguration {
         field: &MetadataFieldConfig,
         value: &str,
         rule: &ValidationRule,
+        context: &ScriptContext,
     ) -> Result<(), String> {
         match rule {
             ValidationRule::MinLength(min) => {
@@ -260,13 +325,97 @@ impl TaskConfiguration {
                 }
             }
             ValidationRule::UniqueValue => {
-                // This would need to be checked against the database
+                // Enforced by the unique index on task_metadata
+            }
+            ValidationRule::CustomValidation(script) => {
+                ScriptEngine::default()
+                    .check_value(script, context, &field.name, value)
+                    .map_err(|e| format!("{}: {}", field.display_name, e))?;
             }
             _ => {}
         }
         Ok(())
     }
 
+    /// Fill in metadata fields the task lacks from their defaults, running
+    /// default scripts against the task. Returns the scripts' errors.
+    pub fn apply_defaults(&self, task: &mut Task, context: &ScriptContext) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut names: Vec<&String> = self.metadata_schema.fields.keys().collect();
+        names.sort();
+        for name in names {
+            let field = &self.metadata_schema.fields[name];
+            if task.metadata.contains_key(name) {
+                continue;
+            }
+            let value = match (&field.default_script, &field.default_value) {
+                (Some(script), _) => match ScriptEngine::default().evaluate(script, context) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        errors.push(format!("Default for {}: {}", field.display_name, e));
+                        continue;
+                    }
+                },
+                (None, Some(value)) => value.clone(),
+                (None, None) => continue,
+            };
+            task.metadata.insert(name.clone(), value);
+        }
+        errors
+    }
+
+    /// Each relation field, with what it links to and the ids `task` holds
+    /// in it. Unset or invalid fields link to nothing.
+    pub fn relations(&self, task: &Task) -> Vec<(String, RelationTarget, Vec<Uuid>)> {
+        self.metadata_schema
+            .fields
+            .values()
+            .filter(|field| field.field_type == FieldType::Relation)
+            .map(|field| {
+                let ids = task
+                    .metadata
+                    .get(&field.name)
+                    .and_then(|value| formula::relation_ids(value).ok())
+                    .unwrap_or_default();
+                (field.name.clone(), field.relation.unwrap_or_default(), ids)
+            })
+            .collect()
+    }
+
+    /// Recompute `task`'s formula fields, each after the formulas it reads.
+    /// A formula producing nothing clears its field. Returns the formulas'
+    /// errors.
+    pub fn compute_formulas(&self, task: &mut Task, context: &mut ScriptContext) -> Vec<String> {
+        let formulas: HashMap<String, String> = self
+            .metadata_schema
+            .fields
+            .values()
+            .filter(|field| field.field_type == FieldType::Formula)
+            .filter_map(|field| Some((field.name.clone(), field.formula.clone()?)))
+            .collect();
+        let order = match formula::evaluation_order(&formulas) {
+            Ok(order) => order,
+            Err(cycle) => return vec![cycle.to_string()],
+        };
+
+        let mut errors = Vec::new();
+        for name in order {
+            let field = &self.metadata_schema.fields[&name];
+            match ScriptEngine::default().evaluate(&formulas[&name], context) {
+                Ok(value) if value.is_empty() => {
+                    task.metadata.remove(&name);
+                    context.set_metadata(&name, None);
+                }
+                Ok(value) => {
+                    context.set_metadata(&name, Some(&value));
+                    task.metadata.insert(name, value);
+                }
+                Err(e) => errors.push(format!("Formula for {}: {}", field.display_name, e)),
+            }
+        }
+        errors
+    }
+
     pub fn get_available_transitions(&self, current_state: &str) -> Vec<&StateTransition> {
         self.state_machine
             .transitions
@@ -281,6 +430,16 @@ impl TaskConfiguration {
         to_state: &str,
         context: &TransitionContext,
     ) -> Result<(), String> {
+        self.checked_transition(from_state, to_state, context)
+            .map(|_| ())
+    }
+
+    fn checked_transition(
+        &self,
+        from_state: &str,
+        to_state: &str,
+        context: &TransitionContext,
+    ) -> Result<&StateTransition, String> {
         let transition = self
             .state_machine
             .transitions
@@ -292,7 +451,137 @@ impl TaskConfiguration {
             self.check_condition(condition, context)?;
         }
 
-        Ok(())
+        Ok(transition)
+    }
+
+    /// Roles that must approve moving from `from_state` to `to_state`,
+    /// with how many of each.
+    pub fn required_approvals(&self, from_state: &str, to_state: &str) -> Vec<(String, usize)> {
+        self.state_machine
+            .transitions
+            .iter()
+            .filter(|t| t.from_state == from_state && t.to_state == to_state)
+            .flat_map(|t| &t.conditions)
+            .filter_map(|c| match c {
+                TransitionCondition::RequireApproval { role, quorum } => {
+                    Some((role.clone(), *quorum))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The state `task` is in. Tasks saved before they had a state are
+    /// placed by their status.
+    pub fn current_state(&self, task: &Task) -> String {
+        if let Some(state) = &task.workflow_state
+            && self.state_machine.states.contains_key(state)
+        {
+            return state.clone();
+        }
+        self.state_machine
+            .ordered_states()
+            .into_iter()
+            .find(|s| self.state_machine.status_for(&s.name) == task.status)
+            .map(|s| s.name.clone())
+            .unwrap_or_else(|| self.state_machine.initial_state.clone())
+    }
+
+    /// Put a new task into the initial state and run its auto actions.
+    pub fn enter_initial_state(&self, task: &mut Task) -> StateEntry {
+        let mut entry = StateEntry::default();
+        let initial = self.state_machine.initial_state.clone();
+        self.enter_state(task, &initial, &mut entry);
+        entry
+    }
+
+    /// Move `task` from its current state into `to_state`. The transition's
+    /// conditions are checked against `context`; its effects and the new
+    /// state's auto actions are applied to the task, and whatever needs
+    /// other records comes back in the `StateEntry`.
+    pub fn apply_transition(
+        &self,
+        task: &mut Task,
+        to_state: &str,
+        context: &TransitionContext,
+    ) -> Result<StateEntry, String> {
+        let from_state = self.current_state(task);
+        let transition = self.checked_transition(&from_state, to_state, context)?;
+
+        let mut entry = StateEntry::default();
+        self.enter_state(task, to_state, &mut entry);
+        for effect in &transition.effects {
+            match effect {
+                TransitionEffect::SetMetadataField { field, value } => {
+                    task.metadata.insert(field.clone(), value.clone());
+                }
+                TransitionEffect::NotifyResource { message_template } => {
+                    entry
+                        .notifications
+                        .push(self.render(message_template, task));
+                }
+                TransitionEffect::CreateSubtask { template } => {
+                    let mut child = Task::new(
+                        self.render(&template.title, task),
+                        self.render(&template.description, task),
+                    );
+                    child.parent_task_id = Some(task.id);
+                    child.goal_id = task.goal_id;
+                    child.assignee = template.assigned_to.clone();
+                    entry.subtasks.push(child);
+                }
+                TransitionEffect::TriggerWebhook { url } => {
+                    entry.webhooks.push(url.clone());
+                }
+            }
+        }
+        Ok(entry)
+    }
+
+    fn enter_state(&self, task: &mut Task, state: &str, entry: &mut StateEntry) {
+        let now = chrono::Utc::now();
+        task.workflow_state = Some(state.to_string());
+        task.status = self.state_machine.status_for(state);
+        if task.status == TaskStatus::Done {
+            task.completed_at.get_or_insert(now);
+        } else {
+            task.completed_at = None;
+        }
+        task.updated_at = now;
+
+        let Some(definition) = self.state_machine.states.get(state) else {
+            return;
+        };
+        for action in &definition.auto_actions {
+            match action {
+                AutoAction::SetDueDate { days_from_now } => {
+                    task.due_date = Some(now + chrono::Duration::days(*days_from_now as i64));
+                }
+                AutoAction::AssignToResource { resource_role } => {
+                    entry.assign_role = Some(resource_role.clone());
+                }
+                AutoAction::AddTag { tag } => {
+                    task.tags.insert(tag.clone());
+                }
+                AutoAction::SendNotification { template } => {
+                    entry.notifications.push(self.render(template, task));
+                }
+            }
+        }
+    }
+
+    /// Fill `{title}`, `{state}` and `{assignee}` in a message template.
+    fn render(&self, template: &str, task: &Task) -> String {
+        let state = task
+            .workflow_state
+            .as_ref()
+            .and_then(|s| self.state_machine.states.get(s))
+            .map(|s| s.display_name.as_str())
+            .unwrap_or_default();
+        template
+            .replace("{title}", &task.title)
+            .replace("{state}", state)
+            .replace("{assignee}", task.assignee.as_deref().unwrap_or_default())
     }
 
     fn check_condition(
@@ -318,17 +607,209 @@ impl TaskConfiguration {
                     return Err("All subtasks must be completed".to_string());
                 }
             }
+            TransitionCondition::RequireApproval { role, .. }
+                if !context.approved_roles.contains(role) =>
+            {
+                return Err(format!("Needs approval from {}", role));
+            }
+            TransitionCondition::CustomValidation { script } => {
+                ScriptEngine::default()
+                    .check(script, &context.script)
+                    .map_err(|e| e.to_string())?;
+            }
             _ => {}
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+/// What entering a state asks for beyond changes to the task itself.
+#[derive(Debug, Clone, Default)]
+pub struct StateEntry {
+    /// Child tasks from `CreateSubtask` effects, not yet saved
+    pub subtasks: Vec<Task>,
+    /// Messages for the task's assigned resource
+    pub notifications: Vec<String>,
+    /// Role from an `AssignToResource` action, for the caller to resolve
+    pub assign_role: Option<String>,
+    /// URLs from `TriggerWebhook` effects, to be told about the transition
+    pub webhooks: Vec<String>,
+}
+
+impl MetadataSchema {
+    /// A metadata value typed by its field; text if the field isn't
+    /// configured or the value doesn't fit it.
+    pub fn typed_value(&self, name: &str, value: &str) -> MetadataValue {
+        self.fields
+            .get(name)
+            .and_then(|field| field.typed_value(value).ok())
+            .unwrap_or_else(|| MetadataValue::Text(value.to_string()))
+    }
+
+    pub fn is_unique(&self, name: &str) -> bool {
+        self.fields.get(name).is_some_and(|field| {
+            field
+                .validation_rules
+                .contains(&ValidationRule::UniqueValue)
+        })
+    }
+}
+
+impl MetadataFieldConfig {
+    /// Read `value` as this field's type. Select fields with options only
+    /// take those options.
+    pub fn typed_value(&self, value: &str) -> Result<MetadataValue, String> {
+        let typed = MetadataValue::parse(self.field_type, value)
+            .map_err(|e| format!("{} must be {}", self.display_name, e.expected))?;
+        if matches!(self.field_type, FieldType::Select | FieldType::MultiSelect)
+            && !self.options.is_empty()
+        {
+            for item in typed.items().iter().filter(|item| !item.is_empty()) {
+                if !self.options.iter().any(|option| option.value == *item) {
+                    let options: Vec<&str> = self
+                        .options
+                        .iter()
+                        .map(|option| option.value.as_str())
+                        .collect();
+                    return Err(format!(
+                        "{} must be one of: {}",
+                        self.display_name,
+                        options.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(typed)
+    }
+
+    /// Order two metadata values. Numbers come first, in numeric order,
+    /// then dates in time order, then everything else as text ignoring
+    /// case, so mixed fields still sort consistently.
+    pub fn compare_values(a: &str, b: &str) -> std::cmp::Ordering {
+        let (a, b) = (Self::sort_key(a), Self::sort_key(b));
+        a.0.cmp(&b.0)
+            .then(a.1.total_cmp(&b.1))
+            .then_with(|| a.2.cmp(&b.2))
+    }
+
+    /// A value's group (number, date or text) and its place in the group.
+    fn sort_key(value: &str) -> (u8, f64, String) {
+        if let Ok(number) = value.trim().parse::<f64>() {
+            return (0, number, String::new());
+        }
+        let date = chrono::DateTime::parse_from_rfc3339(value)
+            .map(|d| d.with_timezone(&chrono::Utc))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)
+                    .map(|d| d.and_utc())
+            });
+        match date {
+            Some(date) => (1, date.timestamp_millis() as f64, String::new()),
+            None => (2, 0.0, value.to_lowercase()),
+        }
+    }
+
+    /// Whether a value passes a list filter. `>`, `>=`, `<`, `<=`, `=` and
+    /// `!=` compare with `compare_values`; anything else matches values
+    /// containing it. Missing values only pass an empty filter or `!=`.
+    pub fn matches_filter(value: Option<&str>, filter: &str) -> bool {
+        use std::cmp::Ordering::{Equal, Greater, Less};
+
+        let filter = filter.trim();
+        if filter.is_empty() {
+            return true;
+        }
+        let operators: [(&str, &[std::cmp::Ordering]); 6] = [
+            (">=", &[Greater, Equal]),
+            ("<=", &[Less, Equal]),
+            ("!=", &[Less, Greater]),
+            (">", &[Greater]),
+            ("<", &[Less]),
+            ("=", &[Equal]),
+        ];
+        for (operator, accepted) in operators {
+            if let Some(operand) = filter.strip_prefix(operator) {
+                return match value {
+                    Some(value) => accepted.contains(&Self::compare_values(value, operand.trim())),
+                    None => operator == "!=",
+                };
+            }
+        }
+        value.is_some_and(|value| value.to_lowercase().contains(&filter.to_lowercase()))
+    }
+}
+
+impl StateMachine {
+    /// The fixed status a state shows up as outside the workflow: by name
+    /// where it matches one, else `Done` for final states, `Todo` for the
+    /// initial state and `InProgress` for the rest.
+    pub fn status_for(&self, state: &str) -> TaskStatus {
+        match state.to_lowercase().replace([' ', '-'], "_").as_str() {
+            "todo" | "to_do" => TaskStatus::Todo,
+            "in_progress" => TaskStatus::InProgress,
+            "blocked" => TaskStatus::Blocked,
+            "review" | "in_review" => TaskStatus::Review,
+            "done" => TaskStatus::Done,
+            "cancelled" | "canceled" => TaskStatus::Cancelled,
+            _ if self.states.get(state).is_some_and(|s| s.is_final) => TaskStatus::Done,
+            _ if state == self.initial_state => TaskStatus::Todo,
+            _ => TaskStatus::InProgress,
+        }
+    }
+
+    /// The first state reachable from `from_state` that shows up as `status`.
+    pub fn state_for_status(&self, from_state: &str, status: TaskStatus) -> Option<&str> {
+        self.transitions
+            .iter()
+            .filter(|t| t.from_state == from_state)
+            .map(|t| t.to_state.as_str())
+            .find(|to| self.status_for(to) == status)
+    }
+
+    /// States in board order: breadth-first from the initial state along
+    /// the transitions, final states last, unreachable ones by name.
+    pub fn ordered_states(&self) -> Vec<&StateDefinition> {
+        let mut order: Vec<&str> = Vec::new();
+        let mut queue = VecDeque::from([self.initial_state.as_str()]);
+        while let Some(state) = queue.pop_front() {
+            if order.contains(&state) || !self.states.contains_key(state) {
+                continue;
+            }
+            order.push(state);
+            for transition in self.transitions.iter().filter(|t| t.from_state == state) {
+                queue.push_back(&transition.to_state);
+            }
+        }
+        let mut unreachable: Vec<&str> = self
+            .states
+            .keys()
+            .map(String::as_str)
+            .filter(|s| !order.contains(s))
+            .collect();
+        unreachable.sort();
+        order.extend(unreachable);
+
+        let (mut states, finals): (Vec<_>, Vec<_>) = order
+            .into_iter()
+            .filter_map(|s| self.states.get(s))
+            .partition(|s| !s.is_final);
+        states.extend(finals);
+        states
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TransitionContext {
     pub metadata: HashMap<String, String>,
     pub all_subtasks_complete: bool,
     pub user_role: Option<String>,
+    /// Roles whose approval of this transition has been granted
+    pub approved_roles: HashSet<String>,
+    /// The task as custom condition scripts see it
+    pub script: ScriptContext,
 }
 
 impl Default for StateMachine {
@@ -445,6 +926,9 @@ pub fn create_software_development_config() -> TaskConfiguration {
             },
         ],
         default_value: Some("3".to_string()),
+        default_script: None,
+        formula: None,
+        relation: None,
         validation_rules: vec![],
         help_text: "Estimate the complexity of this task".to_string(),
         show_in_list: true,
@@ -460,6 +944,9 @@ pub fn create_software_development_config() -> TaskConfiguration {
         required: false,
         options: vec![],
         default_value: None,
+        default_script: None,
+        formula: None,
+        relation: None,
         validation_rules: vec![ValidationRule::MaxLength(50)],
         help_text: "Sprint or iteration this task belongs to".to_string(),
         show_in_list: true,
@@ -475,6 +962,9 @@ pub fn create_software_development_config() -> TaskConfiguration {
         required: false,
         options: vec![],
         default_value: None,
+        default_script: None,
+        formula: None,
+        relation: None,
         validation_rules: vec![],
         help_text: "Link to the pull request".to_string(),
         show_in_list: false,
@@ -559,6 +1049,9 @@ mod tests {
                 },
             ],
             default_value: Some("low".to_string()),
+            default_script: None,
+            formula: None,
+            relation: None,
             validation_rules: vec![],
             help_text: "Task priority".to_string(),
             show_in_list: true,
@@ -590,6 +1083,9 @@ mod tests {
             required: true,
             options: vec![],
             default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
             validation_rules: vec![ValidationRule::MinLength(5)],
             help_text: String::new(),
             show_in_list: false,
@@ -610,4 +1106,193 @@ mod tests {
         let result = config.validate_metadata(&metadata);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_apply_transition() {
+        let config = create_software_development_config();
+        let names: Vec<&str> = config
+            .state_machine
+            .ordered_states()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["todo", "in_progress", "review", "done"]);
+
+        let mut task = Task::new("Fix login".to_string(), String::new());
+        config.enter_initial_state(&mut task);
+        assert_eq!(config.current_state(&task), "todo");
+
+        let context = TransitionContext::default();
+        assert!(
+            config
+                .apply_transition(&mut task, "done", &context)
+                .is_err()
+        );
+        config
+            .apply_transition(&mut task, "in_progress", &context)
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert!(
+            config
+                .apply_transition(&mut task, "review", &context)
+                .is_err()
+        );
+
+        let context = TransitionContext {
+            metadata: HashMap::from([("pr_url".to_string(), "https://example.com".to_string())]),
+            ..TransitionContext::default()
+        };
+        let entry = config
+            .apply_transition(&mut task, "review", &context)
+            .unwrap();
+        assert_eq!(task.workflow_state.as_deref(), Some("review"));
+        assert_eq!(task.status, TaskStatus::Review);
+        assert!(task.tags.contains("needs-review"));
+        assert_eq!(
+            entry.notifications,
+            ["Task 'Fix login' is ready for review"]
+        );
+    }
+
+    #[test]
+    fn test_compute_formulas() {
+        use crate::domain::formula::RelatedItem;
+
+        let field =
+            |name: &str, field_type: FieldType, formula: Option<&str>| MetadataFieldConfig {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                field_type,
+                required: false,
+                options: vec![],
+                default_value: None,
+                default_script: None,
+                formula: formula.map(str::to_string),
+                relation: Some(RelationTarget::Tasks),
+                validation_rules: vec![],
+                help_text: String::new(),
+                show_in_list: true,
+                show_in_card: false,
+                sortable: true,
+                searchable: false,
+            };
+        let mut config = TaskConfiguration::new("Rollups".to_string());
+        config.add_metadata_field(field("parts", FieldType::Relation, None));
+        config.add_metadata_field(field(
+            "remaining",
+            FieldType::Formula,
+            Some("parse_float(metadata.total) - parse_float(metadata.done_hours)"),
+        ));
+        config.add_metadata_field(field(
+            "total",
+            FieldType::Formula,
+            Some("sum_estimates(relations.parts)"),
+        ));
+        config.add_metadata_field(field(
+            "done_hours",
+            FieldType::Formula,
+            Some("sum_estimates(relations.parts.filter(|p| p.done))"),
+        ));
+        config.add_metadata_field(field(
+            "progress",
+            FieldType::Formula,
+            Some(r#"`${count_done(relations.parts)}/${relations.parts.len()}`"#),
+        ));
+        config.add_metadata_field(field(
+            "last_due",
+            FieldType::Formula,
+            Some("max_due_date(relations.parts)"),
+        ));
+
+        let mut first = Task::new("Design".to_string(), String::new());
+        first.estimated_hours = Some(3.0);
+        first.status = TaskStatus::Done;
+        let mut second = Task::new("Build".to_string(), String::new());
+        second.estimated_hours = Some(5.0);
+        second.due_date = Some(chrono::Utc::now() + chrono::Duration::days(3));
+
+        let mut task = Task::new("Feature".to_string(), String::new());
+        task.metadata
+            .insert("parts".to_string(), format!("{}, {}", first.id, second.id));
+        task.metadata
+            .insert("last_due".to_string(), "stale".to_string());
+        let relations = config
+            .relations(&task)
+            .into_iter()
+            .map(|(field, _, _)| {
+                (
+                    field,
+                    vec![RelatedItem::from(&first), RelatedItem::from(&second)],
+                )
+            })
+            .collect();
+        let mut context = ScriptContext::for_task(&task, &[]).with_relations(&relations);
+
+        assert!(config.compute_formulas(&mut task, &mut context).is_empty());
+        assert_eq!(task.metadata["total"], "8.0");
+        assert_eq!(task.metadata["remaining"], "5.0");
+        assert_eq!(task.metadata["progress"], "1/2");
+        assert_eq!(
+            task.metadata["last_due"],
+            second.due_date.unwrap().to_rfc3339()
+        );
+
+        task.metadata
+            .insert("parts".to_string(), "not an id".to_string());
+        assert!(config.validate_metadata(&task.metadata).is_err());
+
+        config.add_metadata_field(field("a", FieldType::Formula, Some("metadata.b")));
+        config.add_metadata_field(field("b", FieldType::Formula, Some("metadata.a")));
+        let errors = config.compute_formulas(&mut task, &mut context);
+        assert_eq!(errors, ["Formulas depend on each other: a, b"]);
+    }
+
+    #[test]
+    fn test_metadata_values_sort_and_filter() {
+        use std::cmp::Ordering;
+
+        assert_eq!(
+            MetadataFieldConfig::compare_values("9", "10.5"),
+            Ordering::Less
+        );
+        assert_eq!(
+            MetadataFieldConfig::compare_values("2025-03-01", "2025-02-28T23:00:00+00:00"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            MetadataFieldConfig::compare_values("beta", "Alpha"),
+            Ordering::Greater
+        );
+        let mut mixed = vec!["1a", "10", "b", "2025-01-01", "9"];
+        mixed.sort_by(|a, b| MetadataFieldConfig::compare_values(a, b));
+        assert_eq!(mixed, ["9", "10", "2025-01-01", "1a", "b"]);
+
+        assert!(MetadataFieldConfig::matches_filter(Some("12.0"), ">= 12"));
+        assert!(!MetadataFieldConfig::matches_filter(Some("9"), ">10"));
+        assert!(MetadataFieldConfig::matches_filter(
+            Some("High risk"),
+            "risk"
+        ));
+        assert!(MetadataFieldConfig::matches_filter(None, ""));
+        assert!(MetadataFieldConfig::matches_filter(None, "!= 3"));
+        assert!(!MetadataFieldConfig::matches_filter(None, "risk"));
+    }
+
+    #[test]
+    fn test_metadata_is_validated_by_field_type() {
+        let config = create_software_development_config();
+        let mut metadata = HashMap::from([
+            ("story_points".to_string(), "5".to_string()),
+            ("sprint".to_string(), "Sprint 12".to_string()),
+        ]);
+        assert!(config.validate_metadata(&metadata).is_ok());
+
+        metadata.insert("story_points".to_string(), "huge".to_string());
+        let errors = config.validate_metadata(&metadata).unwrap_err();
+        assert!(
+            errors.iter().any(|e| e.contains("must be one of")),
+            "{:?}",
+            errors
+        );
+    }
 }
@@ -41,6 +41,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         // Create dependent task
@@ -67,6 +68,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         repo.tasks.create(&parent_task).await.unwrap();
@@ -107,6 +109,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         // Create dependent task
@@ -133,6 +136,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         repo.tasks.create(&parent_task).await.unwrap();
@@ -183,6 +187,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         // Create dependent task
@@ -209,6 +214,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         repo.tasks.create(&parent_task).await.unwrap();
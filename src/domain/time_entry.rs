@@ -0,0 +1,244 @@
+//! Time tracked against tasks, as segments of work, and the weekly
+//! timesheets built from them.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// One uninterrupted segment of work on a task. Pausing ends a segment and
+/// resuming starts the next one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimeEntry {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Who did the work; the task's assignee when tracked with the timer
+    pub resource_id: Option<Uuid>,
+    pub start_time: DateTime<Utc>,
+    /// `None` while the timer is running
+    pub end_time: Option<DateTime<Utc>>,
+    pub description: String,
+    /// Logged by hand rather than with the timer
+    pub manual: bool,
+    /// Whether its hours are in the task's actual hours. Paused segments
+    /// are added when the timer stops.
+    pub rolled_up: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TimeEntry {
+    /// A running segment starting now.
+    pub fn start(task_id: Uuid, resource_id: Option<Uuid>, description: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            resource_id,
+            start_time: now,
+            end_time: None,
+            description,
+            manual: false,
+            rolled_up: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A finished segment logged by hand.
+    pub fn manual(
+        task_id: Uuid,
+        resource_id: Option<Uuid>,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        description: String,
+    ) -> Self {
+        let mut entry = Self::start(task_id, resource_id, description);
+        entry.start_time = start_time;
+        entry.end_time = Some(end_time);
+        entry.manual = true;
+        entry
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.end_time.is_none()
+    }
+
+    /// Time worked, up to now for a running segment.
+    pub fn duration(&self) -> Duration {
+        self.end_time.unwrap_or_else(Utc::now) - self.start_time
+    }
+
+    pub fn hours(&self) -> f64 {
+        hours(self.duration())
+    }
+
+    /// The part of the segment that falls within `[start, end)`.
+    pub fn overlap(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Duration {
+        let from = self.start_time.max(start);
+        let to = self.end_time.unwrap_or_else(Utc::now).min(end);
+        (to - from).max(Duration::zero())
+    }
+}
+
+/// A task's actual hours: the total of its rolled-up entries, or `None`
+/// when none are.
+pub fn actual_hours(entries: &[TimeEntry]) -> Option<f32> {
+    let counted: Vec<&TimeEntry> = entries.iter().filter(|e| e.rolled_up).collect();
+    if counted.is_empty() {
+        None
+    } else {
+        Some(counted.iter().fold(0.0, |sum, e| sum + e.hours()) as f32)
+    }
+}
+
+/// `duration` in hours, to the millisecond so short segments still count.
+pub fn hours(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 3_600_000.0
+}
+
+/// Hours one task took on each day of a timesheet's week.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimesheetRow {
+    pub task_id: Uuid,
+    pub task_title: String,
+    /// Monday first
+    pub hours: [f64; 7],
+}
+
+impl TimesheetRow {
+    pub fn total(&self) -> f64 {
+        self.hours.iter().fold(0.0, |sum, h| sum + h)
+    }
+}
+
+/// A resource's hours for one week, by task and day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timesheet {
+    /// `None` for everyone's hours
+    pub resource_id: Option<Uuid>,
+    /// The Monday the week starts on
+    pub week_start: NaiveDate,
+    pub rows: Vec<TimesheetRow>,
+}
+
+impl Timesheet {
+    /// The Monday of the week `date` falls in.
+    pub fn week_of(date: NaiveDate) -> NaiveDate {
+        date - Duration::days(date.weekday().num_days_from_monday() as i64)
+    }
+
+    /// When the week containing `date` starts and ends, in UTC.
+    pub fn week_range(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = Self::week_of(date).and_hms_opt(0, 0, 0).unwrap().and_utc();
+        (start, start + Duration::days(7))
+    }
+
+    /// Lay `entries` out over the week containing `date`. Segments running
+    /// over midnight count towards each day they cover; tasks missing from
+    /// `titles` are shown by id.
+    pub fn build(
+        resource_id: Option<Uuid>,
+        date: NaiveDate,
+        entries: &[TimeEntry],
+        titles: &HashMap<Uuid, String>,
+    ) -> Self {
+        let week_start = Self::week_of(date);
+        let (start, _) = Self::week_range(date);
+        let mut rows: Vec<TimesheetRow> = Vec::new();
+
+        for entry in entries {
+            if resource_id.is_some() && entry.resource_id != resource_id {
+                continue;
+            }
+            let mut day_hours = [0.0; 7];
+            for (day, slot) in day_hours.iter_mut().enumerate() {
+                let day_start = start + Duration::days(day as i64);
+                *slot = hours(entry.overlap(day_start, day_start + Duration::days(1)));
+            }
+            if day_hours.iter().all(|h| *h == 0.0) {
+                continue;
+            }
+
+            let row = match rows.iter_mut().position(|r| r.task_id == entry.task_id) {
+                Some(index) => &mut rows[index],
+                None => {
+                    rows.push(TimesheetRow {
+                        task_id: entry.task_id,
+                        task_title: titles
+                            .get(&entry.task_id)
+                            .cloned()
+                            .unwrap_or_else(|| entry.task_id.to_string()),
+                        hours: [0.0; 7],
+                    });
+                    rows.last_mut().unwrap()
+                }
+            };
+            for (total, h) in row.hours.iter_mut().zip(day_hours) {
+                *total += h;
+            }
+        }
+        rows.sort_by(|a, b| a.task_title.cmp(&b.task_title));
+
+        Self {
+            resource_id,
+            week_start,
+            rows,
+        }
+    }
+
+    pub fn days(&self) -> [NaiveDate; 7] {
+        std::array::from_fn(|day| self.week_start + Duration::days(day as i64))
+    }
+
+    /// Hours across all tasks on each day.
+    pub fn day_totals(&self) -> [f64; 7] {
+        let mut totals = [0.0; 7];
+        for row in &self.rows {
+            for (total, h) in totals.iter_mut().zip(row.hours) {
+                *total += h;
+            }
+        }
+        totals
+    }
+
+    pub fn total(&self) -> f64 {
+        self.rows.iter().fold(0.0, |sum, row| sum + row.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_timesheet_splits_days_and_filters_resource() {
+        let alice = Uuid::new_v4();
+        let deploy = Uuid::new_v4();
+        let review = Uuid::new_v4();
+        let at = |d, h, m| Utc.with_ymd_and_hms(2025, 3, d, h, m, 0).unwrap();
+        let entries = vec![
+            // Tuesday evening into Wednesday
+            TimeEntry::manual(deploy, Some(alice), at(4, 22, 0), at(5, 1, 30), String::new()),
+            TimeEntry::manual(review, Some(alice), at(7, 9, 0), at(7, 10, 15), String::new()),
+            TimeEntry::manual(review, None, at(7, 11, 0), at(7, 12, 0), String::new()),
+            // The week before
+            TimeEntry::manual(review, Some(alice), at(2, 9, 0), at(2, 17, 0), String::new()),
+        ];
+        let titles = HashMap::from([(deploy, "Deploy".to_string()), (review, "Review".to_string())]);
+
+        let sheet = Timesheet::build(Some(alice), NaiveDate::from_ymd_opt(2025, 3, 6).unwrap(), &entries, &titles);
+        assert_eq!(sheet.week_start, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        assert_eq!(sheet.rows.len(), 2);
+        assert_eq!(sheet.rows[0].task_title, "Deploy");
+        assert_eq!(sheet.rows[0].hours, [0.0, 2.0, 1.5, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(sheet.rows[1].hours[4], 1.25);
+        assert_eq!(sheet.day_totals()[4], 1.25);
+        assert_eq!(sheet.total(), 4.75);
+
+        let everyone = Timesheet::build(None, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(), &entries, &titles);
+        assert_eq!(everyone.total(), 5.75);
+        assert_eq!(everyone.days()[6], NaiveDate::from_ymd_opt(2025, 3, 9).unwrap());
+    }
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+
+/// Default per-step timeout when none is given.
+pub const DEFAULT_STEP_TIMEOUT_SECS: u64 = 600;
+
+/// Captured output is truncated to its last this-many bytes per stream.
+pub const MAX_CAPTURED_OUTPUT: usize = 16 * 1024;
+
+/// One command in the verification pipeline, run through `sh -c` in the worktree.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerificationStep {
+    pub name: String,
+    pub command: String,
+    pub timeout_secs: u64,
+}
+
+/// What to do when a verification step fails.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum VerificationFailurePolicy {
+    /// Mark the session failed without pushing
+    #[default]
+    FailSession,
+    /// Run the agent again with the failing output, up to `max_attempts` times
+    FeedBackToAgent { max_attempts: usize },
+}
+
+/// Ordered commands run after the agent finishes and before anything is pushed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct VerificationPipeline {
+    pub steps: Vec<VerificationStep>,
+    pub on_failure: VerificationFailurePolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerificationStepResult {
+    pub name: String,
+    pub command: String,
+    pub success: bool,
+    pub timed_out: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct VerificationReport {
+    pub steps: Vec<VerificationStepResult>,
+}
+
+impl VerificationStep {
+    pub fn new(name: &str, command: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            timeout_secs: DEFAULT_STEP_TIMEOUT_SECS,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Parse a `name [timeout]: command` line, e.g. `test [900]: cargo test`.
+    /// The timeout is optional; a line without a name uses the command as its name.
+    /// Only a single word before the first `:` is a name, so commands such as
+    /// `sh -c 'echo a:b'` are kept whole.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let parsed = line
+            .split_once(':')
+            .and_then(|(head, command)| Some((parse_head(head.trim())?, command.trim())));
+        let Some(((name, timeout), command)) = parsed else {
+            return Some(Self::new(line, line));
+        };
+        if command.is_empty() {
+            return None;
+        }
+
+        let step = Self::new(name, command);
+        Some(match timeout {
+            Some(secs) => step.with_timeout(secs),
+            None => step,
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        if self.timeout_secs == DEFAULT_STEP_TIMEOUT_SECS {
+            format!("{}: {}", self.name, self.command)
+        } else {
+            format!("{} [{}]: {}", self.name, self.timeout_secs, self.command)
+        }
+    }
+}
+
+/// The name and timeout in a `name [timeout]` line prefix, or `None` when
+/// the prefix is part of a command instead.
+fn parse_head(head: &str) -> Option<(&str, Option<u64>)> {
+    let (name, timeout) = match head.split_once('[') {
+        Some((name, rest)) => {
+            let secs = rest.strip_suffix(']')?.trim();
+            let secs = secs.strip_suffix('s').unwrap_or(secs).parse().ok()?;
+            (name.trim_end(), Some(secs))
+        }
+        None => (head, None),
+    };
+    let is_name = !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '/');
+    is_name.then_some((name, timeout))
+}
+
+impl VerificationPipeline {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Parse one step per line, skipping blanks and `#` comments.
+    pub fn parse_steps(text: &str) -> Vec<VerificationStep> {
+        text.lines()
+            .filter_map(VerificationStep::parse_line)
+            .collect()
+    }
+
+    pub fn steps_text(&self) -> String {
+        self.steps
+            .iter()
+            .map(VerificationStep::to_line)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl VerificationReport {
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|s| s.success)
+    }
+
+    pub fn failed_steps(&self) -> impl Iterator<Item = &VerificationStepResult> {
+        self.steps.iter().filter(|s| !s.success)
+    }
+
+    /// One line per step, suitable for the session log.
+    pub fn summary(&self) -> String {
+        self.steps
+            .iter()
+            .map(|s| {
+                let outcome = if s.success {
+                    "passed".to_string()
+                } else if s.timed_out {
+                    "timed out".to_string()
+                } else {
+                    format!("failed (exit {})", s.exit_code.unwrap_or(-1))
+                };
+                format!("{}: {} in {}ms", s.name, outcome, s.duration_ms)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Markdown describing the failing steps and their output, for feeding
+    /// back into a follow-up agent run.
+    pub fn failure_feedback(&self) -> String {
+        let mut feedback = String::new();
+        for step in self.failed_steps() {
+            feedback.push_str(&format!("### `{}` ({})\n\n", step.command, step.name));
+            if step.timed_out {
+                feedback.push_str("The command timed out.\n\n");
+            }
+            for output in [&step.stdout, &step.stderr] {
+                if !output.trim().is_empty() {
+                    feedback.push_str(&format!("```\n{}\n```\n\n", output.trim_end()));
+                }
+            }
+        }
+        feedback
+    }
+}
+
+/// Keep the tail of `output`, where compilers and test runners put the errors.
+pub fn truncate_output(output: &str) -> String {
+    if output.len() <= MAX_CAPTURED_OUTPUT {
+        return output.to_string();
+    }
+    let mut start = output.len() - MAX_CAPTURED_OUTPUT;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("[... truncated ...]\n{}", &output[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_step_lines() {
+        let steps = VerificationPipeline::parse_steps(
+            "# checks\nbuild: cargo build\ntest [900]: cargo test -- --nocapture\n\n./scripts/lint.sh",
+        );
+
+        assert_eq!(
+            steps,
+            vec![
+                VerificationStep::new("build", "cargo build"),
+                VerificationStep::new("test", "cargo test -- --nocapture").with_timeout(900),
+                VerificationStep::new("./scripts/lint.sh", "./scripts/lint.sh"),
+            ]
+        );
+        assert_eq!(steps[1].to_line(), "test [900]: cargo test -- --nocapture");
+
+        // A colon inside a command doesn't make its start a name
+        for command in [
+            "sh -c 'echo a:b'",
+            "curl -f http://localhost:8080/health",
+            "./scripts/check.sh:strict",
+            "lint [soon]: make lint",
+        ] {
+            assert_eq!(
+                VerificationStep::parse_line(command),
+                Some(VerificationStep::new(command, command))
+            );
+        }
+        assert_eq!(
+            VerificationStep::parse_line("e2e [120s]: npm run e2e"),
+            Some(VerificationStep::new("e2e", "npm run e2e").with_timeout(120))
+        );
+    }
+
+    #[test]
+    fn test_report_feedback_lists_failures_only() {
+        let report = VerificationReport {
+            steps: vec![
+                VerificationStepResult {
+                    name: "build".to_string(),
+                    command: "cargo build".to_string(),
+                    success: true,
+                    timed_out: false,
+                    exit_code: Some(0),
+                    stdout: "Finished".to_string(),
+                    stderr: String::new(),
+                    duration_ms: 10,
+                },
+                VerificationStepResult {
+                    name: "test".to_string(),
+                    command: "cargo test".to_string(),
+                    success: false,
+                    timed_out: false,
+                    exit_code: Some(101),
+                    stdout: "test foo ... FAILED".to_string(),
+                    stderr: String::new(),
+                    duration_ms: 20,
+                },
+            ],
+        };
+
+        assert!(!report.passed());
+        assert_eq!(
+            report.summary(),
+            "build: passed in 10ms\ntest: failed (exit 101) in 20ms"
+        );
+        let feedback = report.failure_feedback();
+        assert!(feedback.contains("cargo test"));
+        assert!(feedback.contains("test foo ... FAILED"));
+        assert!(!feedback.contains("Finished"));
+    }
+
+    #[test]
+    fn test_truncate_output_keeps_tail() {
+        let long = format!("{}END", "x".repeat(MAX_CAPTURED_OUTPUT * 2));
+        let truncated = truncate_output(&long);
+        assert!(truncated.ends_with("END"));
+        assert!(truncated.len() < long.len());
+    }
+}
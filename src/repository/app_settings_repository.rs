@@ -1,7 +1,7 @@
 use anyhow::Result;
 use sqlx::{SqlitePool, Row};
 use uuid::Uuid;
-use crate::domain::app_settings::{AppSettings, Theme, FontSize, UiDensity, SidebarPosition};
+use crate::domain::app_settings::{AppSettings, Theme, FontSize, UiDensity, SidebarPosition, LlmProviderKind};
 use crate::domain::task::TaskStatus;
 use chrono::{DateTime, Utc};
 
@@ -72,6 +72,15 @@ impl AppSettingsRepository {
                 enable_calendar_sync: row.get::<i32, _>("enable_calendar_sync") != 0,
                 calendar_provider: row.get("calendar_provider"),
                 
+                // Language Model Settings
+                llm_provider: serde_json::from_str(row.get("llm_provider")).unwrap_or(LlmProviderKind::Ollama),
+                llm_endpoint: row.get("llm_endpoint"),
+                llm_model: row.get("llm_model"),
+                llm_temperature: row.get::<f64, _>("llm_temperature") as f32,
+                llm_max_tokens: row.get("llm_max_tokens"),
+                llm_fixture_directory: row.get("llm_fixture_directory"),
+                llm_embedding_model: row.get("llm_embedding_model"),
+                
                 created_at: DateTime::parse_from_rfc3339(row.get("created_at"))?.with_timezone(&Utc),
                 updated_at: DateTime::parse_from_rfc3339(row.get("updated_at"))?.with_timezone(&Utc),
             }))
@@ -96,8 +105,10 @@ impl AppSettingsRepository {
                 enable_github_integration, enable_slack_integration, slack_webhook_url,
                 enable_discord_integration, discord_webhook_url,
                 enable_calendar_sync, calendar_provider,
+                llm_provider, llm_endpoint, llm_model, llm_temperature,
+                llm_max_tokens, llm_fixture_directory, llm_embedding_model,
                 created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(settings.id.to_string())
@@ -135,6 +146,13 @@ impl AppSettingsRepository {
         .bind(&settings.discord_webhook_url)
         .bind(settings.enable_calendar_sync as i32)
         .bind(&settings.calendar_provider)
+        .bind(serde_json::to_string(&settings.llm_provider)?)
+        .bind(&settings.llm_endpoint)
+        .bind(&settings.llm_model)
+        .bind(settings.llm_temperature as f64)
+        .bind(settings.llm_max_tokens)
+        .bind(&settings.llm_fixture_directory)
+        .bind(&settings.llm_embedding_model)
         .bind(settings.created_at.to_rfc3339())
         .bind(settings.updated_at.to_rfc3339())
         .execute(&self.pool)
@@ -158,6 +176,8 @@ impl AppSettingsRepository {
                 enable_github_integration = ?, enable_slack_integration = ?, slack_webhook_url = ?,
                 enable_discord_integration = ?, discord_webhook_url = ?,
                 enable_calendar_sync = ?, calendar_provider = ?,
+                llm_provider = ?, llm_endpoint = ?, llm_model = ?, llm_temperature = ?,
+                llm_max_tokens = ?, llm_fixture_directory = ?, llm_embedding_model = ?,
                 updated_at = ?
             WHERE id = ?
             "#
@@ -196,6 +216,13 @@ impl AppSettingsRepository {
         .bind(&settings.discord_webhook_url)
         .bind(settings.enable_calendar_sync as i32)
         .bind(&settings.calendar_provider)
+        .bind(serde_json::to_string(&settings.llm_provider)?)
+        .bind(&settings.llm_endpoint)
+        .bind(&settings.llm_model)
+        .bind(settings.llm_temperature as f64)
+        .bind(settings.llm_max_tokens)
+        .bind(&settings.llm_fixture_directory)
+        .bind(&settings.llm_embedding_model)
         .bind(settings.updated_at.to_rfc3339())
         .bind(settings.id.to_string())
         .execute(&self.pool)
@@ -262,6 +289,13 @@ mod tests {
                 discord_webhook_url TEXT,
                 enable_calendar_sync INTEGER NOT NULL DEFAULT 0,
                 calendar_provider TEXT,
+                llm_provider TEXT NOT NULL DEFAULT '"Ollama"',
+                llm_endpoint TEXT NOT NULL DEFAULT 'http://localhost:11434/api/generate',
+                llm_model TEXT NOT NULL DEFAULT 'llama3.2:1b',
+                llm_temperature REAL NOT NULL DEFAULT 0.3,
+                llm_max_tokens INTEGER NOT NULL DEFAULT 2048,
+                llm_fixture_directory TEXT,
+                llm_embedding_model TEXT NOT NULL DEFAULT '',
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )
@@ -299,6 +333,10 @@ mod tests {
         settings.theme = Theme::Dark;
         settings.accent_color = "#ff0000".to_string();
         settings.enable_animations = false;
+        settings.llm_provider = LlmProviderKind::Anthropic;
+        settings.llm_model = "claude-3-5-haiku-latest".to_string();
+        settings.llm_temperature = 0.7;
+        settings.llm_embedding_model = "nomic-embed-text".to_string();
         settings.update_timestamp();
         
         repo.update(&settings).await.unwrap();
@@ -307,6 +345,10 @@ mod tests {
         assert_eq!(loaded.theme, Theme::Dark);
         assert_eq!(loaded.accent_color, "#ff0000");
         assert_eq!(loaded.enable_animations, false);
+        assert_eq!(loaded.llm_provider, LlmProviderKind::Anthropic);
+        assert_eq!(loaded.llm_model, "claude-3-5-haiku-latest");
+        assert!((loaded.llm_temperature - 0.7).abs() < 1e-6);
+        assert_eq!(loaded.llm_embedding_model, "nomic-embed-text");
     }
     
     #[tokio::test]
@@ -0,0 +1,216 @@
+use crate::domain::approval::{ApprovalAction, ApprovalEvent, ApprovalRequest, ApprovalStatus};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REQUEST_COLUMNS: &str = "id, task_id, from_state, to_state, role, quorum, approvers, \
+     requested_by, status, created_at, resolved_at";
+const EVENT_COLUMNS: &str = "id, request_id, resource_id, action, comment, created_at";
+
+#[derive(Clone)]
+pub struct ApprovalRepository {
+    pool: Arc<SqlitePool>,
+}
+
+/// Fixed-width UTC timestamps, so the audit trail sorts in order.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+impl ApprovalRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, request: &ApprovalRequest) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO approval_requests ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            REQUEST_COLUMNS
+        ))
+        .bind(request.id.to_string())
+        .bind(request.task_id.to_string())
+        .bind(&request.from_state)
+        .bind(&request.to_state)
+        .bind(&request.role)
+        .bind(request.quorum as i64)
+        .bind(serde_json::to_string(&request.approvers)?)
+        .bind(request.requested_by.map(|id| id.to_string()))
+        .bind(status_to_string(request.status))
+        .bind(timestamp(request.created_at))
+        .bind(request.resolved_at.map(timestamp))
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Save a request's status; everything else is fixed when it's opened.
+    pub async fn update(&self, request: &ApprovalRequest) -> Result<()> {
+        sqlx::query("UPDATE approval_requests SET status = ?, resolved_at = ? WHERE id = ?")
+            .bind(status_to_string(request.status))
+            .bind(request.resolved_at.map(timestamp))
+            .bind(request.id.to_string())
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Move every request on one task, with its audit trail, to another.
+    /// Returns how many moved.
+    pub async fn reassign(&self, from_task_id: Uuid, to_task_id: Uuid) -> Result<u64> {
+        let result = sqlx::query("UPDATE approval_requests SET task_id = ? WHERE task_id = ?")
+            .bind(to_task_id.to_string())
+            .bind(from_task_id.to_string())
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<ApprovalRequest>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM approval_requests WHERE id = ?",
+            REQUEST_COLUMNS
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&*self.pool)
+        .await?;
+        row.map(row_to_request).transpose()
+    }
+
+    /// A task's requests, oldest first.
+    pub async fn list_for_task(&self, task_id: Uuid) -> Result<Vec<ApprovalRequest>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM approval_requests WHERE task_id = ? ORDER BY created_at",
+            REQUEST_COLUMNS
+        ))
+        .bind(task_id.to_string())
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_request).collect()
+    }
+
+    pub async fn list_pending(&self) -> Result<Vec<ApprovalRequest>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM approval_requests WHERE status = ? ORDER BY created_at",
+            REQUEST_COLUMNS
+        ))
+        .bind(status_to_string(ApprovalStatus::Pending))
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_request).collect()
+    }
+
+    pub async fn add_event(&self, event: &ApprovalEvent) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO approval_events ({}) VALUES (?, ?, ?, ?, ?, ?)",
+            EVENT_COLUMNS
+        ))
+        .bind(event.id.to_string())
+        .bind(event.request_id.to_string())
+        .bind(event.resource_id.map(|id| id.to_string()))
+        .bind(action_to_string(event.action))
+        .bind(&event.comment)
+        .bind(timestamp(event.created_at))
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A request's audit trail, oldest first.
+    pub async fn list_events(&self, request_id: Uuid) -> Result<Vec<ApprovalEvent>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM approval_events WHERE request_id = ? ORDER BY created_at",
+            EVENT_COLUMNS
+        ))
+        .bind(request_id.to_string())
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_event).collect()
+    }
+}
+
+fn status_to_string(status: ApprovalStatus) -> &'static str {
+    match status {
+        ApprovalStatus::Pending => "Pending",
+        ApprovalStatus::Approved => "Approved",
+        ApprovalStatus::Rejected => "Rejected",
+        ApprovalStatus::Completed => "Completed",
+        ApprovalStatus::Cancelled => "Cancelled",
+    }
+}
+
+fn string_to_status(s: &str) -> Result<ApprovalStatus> {
+    match s {
+        "Pending" => Ok(ApprovalStatus::Pending),
+        "Approved" => Ok(ApprovalStatus::Approved),
+        "Rejected" => Ok(ApprovalStatus::Rejected),
+        "Completed" => Ok(ApprovalStatus::Completed),
+        "Cancelled" => Ok(ApprovalStatus::Cancelled),
+        _ => Err(anyhow!("Invalid approval status: {}", s)),
+    }
+}
+
+fn action_to_string(action: ApprovalAction) -> &'static str {
+    match action {
+        ApprovalAction::Requested => "Requested",
+        ApprovalAction::Approved => "Approved",
+        ApprovalAction::Rejected => "Rejected",
+        ApprovalAction::Completed => "Completed",
+        ApprovalAction::Cancelled => "Cancelled",
+    }
+}
+
+fn string_to_action(s: &str) -> Result<ApprovalAction> {
+    match s {
+        "Requested" => Ok(ApprovalAction::Requested),
+        "Approved" => Ok(ApprovalAction::Approved),
+        "Rejected" => Ok(ApprovalAction::Rejected),
+        "Completed" => Ok(ApprovalAction::Completed),
+        "Cancelled" => Ok(ApprovalAction::Cancelled),
+        _ => Err(anyhow!("Invalid approval action: {}", s)),
+    }
+}
+
+fn parse_time(s: String) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc))
+}
+
+fn row_to_request(row: sqlx::sqlite::SqliteRow) -> Result<ApprovalRequest> {
+    let id: String = row.get("id");
+    let task_id: String = row.get("task_id");
+    let approvers: String = row.get("approvers");
+    let requested_by: Option<String> = row.get("requested_by");
+    let status: String = row.get("status");
+    let resolved_at: Option<String> = row.get("resolved_at");
+
+    Ok(ApprovalRequest {
+        id: Uuid::parse_str(&id)?,
+        task_id: Uuid::parse_str(&task_id)?,
+        from_state: row.get("from_state"),
+        to_state: row.get("to_state"),
+        role: row.get("role"),
+        quorum: row.get::<i64, _>("quorum") as usize,
+        approvers: serde_json::from_str(&approvers)?,
+        requested_by: requested_by.map(|s| Uuid::parse_str(&s)).transpose()?,
+        status: string_to_status(&status)?,
+        created_at: parse_time(row.get("created_at"))?,
+        resolved_at: resolved_at.map(parse_time).transpose()?,
+    })
+}
+
+fn row_to_event(row: sqlx::sqlite::SqliteRow) -> Result<ApprovalEvent> {
+    let id: String = row.get("id");
+    let request_id: String = row.get("request_id");
+    let resource_id: Option<String> = row.get("resource_id");
+    let action: String = row.get("action");
+
+    Ok(ApprovalEvent {
+        id: Uuid::parse_str(&id)?,
+        request_id: Uuid::parse_str(&request_id)?,
+        resource_id: resource_id.map(|s| Uuid::parse_str(&s)).transpose()?,
+        action: string_to_action(&action)?,
+        comment: row.get("comment"),
+        created_at: parse_time(row.get("created_at"))?,
+    })
+}
@@ -1,9 +1,13 @@
 use crate::domain::claude_code::{
-    ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate, SessionStatus,
+    AgentUsage, ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate, SessionStatus,
 };
+use crate::domain::comment::Comment;
+use crate::domain::session_diff::SessionDiff;
+use crate::repository::comment_repository::insert_comment;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Row, Sqlite};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::collections::HashMap;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -17,14 +21,20 @@ impl ClaudeCodeRepository {
         Self { pool }
     }
 
+    /// The database the sessions live in, shared with the other repositories.
+    pub fn pool(&self) -> &Pool<Sqlite> {
+        &self.pool
+    }
+
     pub async fn create_session(&self, session: &ClaudeCodeSession) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO claude_code_sessions (
                 id, task_id, status, branch_name, pr_url, pr_number,
-                session_log, error_message, started_at, completed_at,
-                created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                session_log, error_message, input_tokens, output_tokens,
+                cost_usd, files_changed, verification_report, retry_of_session_id, attempt,
+                pr_sync, started_at, completed_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(session.id.to_string())
@@ -35,6 +45,14 @@ impl ClaudeCodeRepository {
         .bind(session.pr_number)
         .bind(&session.session_log)
         .bind(&session.error_message)
+        .bind(session.usage.input_tokens)
+        .bind(session.usage.output_tokens)
+        .bind(session.usage.cost_usd)
+        .bind(session.usage.files_changed)
+        .bind(verification_json(session)?)
+        .bind(session.retry_of.map(|id| id.to_string()))
+        .bind(session.attempt)
+        .bind(serde_json::to_string(&session.pr_sync)?)
         .bind(session.started_at)
         .bind(session.completed_at)
         .bind(session.created_at)
@@ -46,26 +64,24 @@ impl ClaudeCodeRepository {
     }
 
     pub async fn update_session(&self, session: &ClaudeCodeSession) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE claude_code_sessions
-            SET status = ?, branch_name = ?, pr_url = ?, pr_number = ?,
-                session_log = ?, error_message = ?, completed_at = ?, updated_at = ?
-            WHERE id = ?
-            "#,
-        )
-        .bind(session.status.as_str())
-        .bind(&session.branch_name)
-        .bind(&session.pr_url)
-        .bind(session.pr_number)
-        .bind(&session.session_log)
-        .bind(&session.error_message)
-        .bind(session.completed_at)
-        .bind(session.updated_at)
-        .bind(session.id.to_string())
-        .execute(&self.pool)
-        .await?;
+        let mut conn = self.pool.acquire().await?;
+        update_session_with(&mut conn, session).await
+    }
 
+    /// Save a session's PR sync state together with the comments it mirrored,
+    /// so a failed write can't record comments as mirrored without posting
+    /// them, or post them twice.
+    pub async fn save_pr_sync(
+        &self,
+        session: &ClaudeCodeSession,
+        comments: &[Comment],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for comment in comments {
+            insert_comment(&mut tx, comment).await?;
+        }
+        update_session_with(&mut tx, session).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -73,8 +89,9 @@ impl ClaudeCodeRepository {
         let row = sqlx::query(
             r#"
             SELECT id, task_id, status, branch_name, pr_url, pr_number,
-                   session_log, error_message, started_at, completed_at,
-                   created_at, updated_at
+                   session_log, error_message, input_tokens, output_tokens,
+                   cost_usd, files_changed, verification_report, retry_of_session_id,
+                   attempt, pr_sync, started_at, completed_at, created_at, updated_at
             FROM claude_code_sessions
             WHERE id = ?
             "#,
@@ -94,8 +111,9 @@ impl ClaudeCodeRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, task_id, status, branch_name, pr_url, pr_number,
-                   session_log, error_message, started_at, completed_at,
-                   created_at, updated_at
+                   session_log, error_message, input_tokens, output_tokens,
+                   cost_usd, files_changed, verification_report, retry_of_session_id,
+                   attempt, pr_sync, started_at, completed_at, created_at, updated_at
             FROM claude_code_sessions
             WHERE task_id = ?
             ORDER BY created_at DESC
@@ -117,8 +135,9 @@ impl ClaudeCodeRepository {
         let rows = sqlx::query(
             r#"
             SELECT id, task_id, status, branch_name, pr_url, pr_number,
-                   session_log, error_message, started_at, completed_at,
-                   created_at, updated_at
+                   session_log, error_message, input_tokens, output_tokens,
+                   cost_usd, files_changed, verification_report, retry_of_session_id,
+                   attempt, pr_sync, started_at, completed_at, created_at, updated_at
             FROM claude_code_sessions
             WHERE status IN ('initializing', 'working', 'creating_pr')
             ORDER BY started_at DESC
@@ -135,6 +154,89 @@ impl ClaudeCodeRepository {
         Ok(sessions)
     }
 
+    /// Sessions whose pull request has not yet been merged or closed.
+    pub async fn get_sessions_with_open_prs(&self) -> Result<Vec<ClaudeCodeSession>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, task_id, status, branch_name, pr_url, pr_number,
+                   session_log, error_message, input_tokens, output_tokens,
+                   cost_usd, files_changed, verification_report, retry_of_session_id,
+                   attempt, pr_sync, started_at, completed_at, created_at, updated_at
+            FROM claude_code_sessions
+            WHERE pr_url IS NOT NULL
+            ORDER BY started_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            let session = self.row_to_session(row)?;
+            if !session.pr_sync.closed {
+                sessions.push(session);
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    /// Sessions that started or changed at or after `since`, oldest first.
+    pub async fn get_sessions_updated_since(&self, since: DateTime<Utc>) -> Result<Vec<ClaudeCodeSession>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, task_id, status, branch_name, pr_url, pr_number,
+                   session_log, error_message, input_tokens, output_tokens,
+                   cost_usd, files_changed, verification_report, retry_of_session_id,
+                   attempt, pr_sync, started_at, completed_at, created_at, updated_at
+            FROM claude_code_sessions
+            WHERE updated_at >= ?
+            ORDER BY started_at
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(self.row_to_session(row)?);
+        }
+        Ok(sessions)
+    }
+
+    /// Total agent spend per task across all of its sessions.
+    pub async fn get_usage_by_task(&self) -> Result<HashMap<Uuid, AgentUsage>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT task_id,
+                   SUM(input_tokens) AS input_tokens,
+                   SUM(output_tokens) AS output_tokens,
+                   SUM(cost_usd) AS cost_usd,
+                   SUM(files_changed) AS files_changed
+            FROM claude_code_sessions
+            GROUP BY task_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut usage = HashMap::new();
+        for row in rows {
+            usage.insert(
+                Uuid::parse_str(row.get("task_id"))?,
+                AgentUsage {
+                    input_tokens: row.get("input_tokens"),
+                    output_tokens: row.get("output_tokens"),
+                    cost_usd: row.get("cost_usd"),
+                    files_changed: row.get::<i64, _>("files_changed") as i32,
+                },
+            );
+        }
+
+        Ok(usage)
+    }
+
     pub async fn cleanup_old_sessions(&self, before: DateTime<Utc>) -> Result<()> {
         sqlx::query(
             r#"
@@ -149,14 +251,65 @@ impl ClaudeCodeRepository {
         Ok(())
     }
 
+    /// Store a session's diff, replacing any earlier capture.
+    pub async fn save_session_diff(&self, diff: &SessionDiff) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO claude_code_session_diffs (
+                session_id, base_branch, files, patch, truncated, captured_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(diff.session_id.to_string())
+        .bind(&diff.base_branch)
+        .bind(serde_json::to_string(&diff.files)?)
+        .bind(&diff.patch)
+        .bind(diff.truncated)
+        .bind(diff.captured_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_session_diff(&self, session_id: Uuid) -> Result<Option<SessionDiff>> {
+        let row = sqlx::query(
+            r#"
+            SELECT session_id, base_branch, files, patch, truncated, captured_at
+            FROM claude_code_session_diffs
+            WHERE session_id = ?
+            "#,
+        )
+        .bind(session_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let files_json: String = row.get("files");
+                Ok(Some(SessionDiff {
+                    session_id: Uuid::parse_str(row.get("session_id"))?,
+                    base_branch: row.get("base_branch"),
+                    files: serde_json::from_str(&files_json)?,
+                    patch: row.get("patch"),
+                    truncated: row.get("truncated"),
+                    captured_at: row.get("captured_at"),
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub async fn create_config(&self, config: &ClaudeCodeConfig) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO claude_code_config (
                 id, github_repo, github_owner, github_token, claude_api_key,
                 default_base_branch, auto_create_pr, working_directory,
-                claude_model, max_session_duration_minutes, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                claude_model, max_session_duration_minutes, max_tokens_per_session,
+                max_cost_per_session_usd, max_files_changed, verification_pipeline,
+                agent_backend, task_agent_backends, hosting_provider, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(config.id.to_string())
@@ -169,6 +322,13 @@ impl ClaudeCodeRepository {
         .bind(&config.working_directory)
         .bind(&config.claude_model)
         .bind(config.max_session_duration_minutes)
+        .bind(config.max_tokens_per_session)
+        .bind(config.max_cost_per_session_usd)
+        .bind(config.max_files_changed)
+        .bind(serde_json::to_string(&config.verification_pipeline)?)
+        .bind(serde_json::to_string(&config.agent_backend)?)
+        .bind(serde_json::to_string(&config.task_agent_backends)?)
+        .bind(serde_json::to_string(&config.hosting_provider)?)
         .bind(config.created_at)
         .bind(config.updated_at)
         .execute(&self.pool)
@@ -183,7 +343,9 @@ impl ClaudeCodeRepository {
             UPDATE claude_code_config
             SET github_repo = ?, github_owner = ?, github_token = ?, claude_api_key = ?,
                 default_base_branch = ?, auto_create_pr = ?, working_directory = ?,
-                claude_model = ?, max_session_duration_minutes = ?, updated_at = ?
+                claude_model = ?, max_session_duration_minutes = ?, max_tokens_per_session = ?,
+                max_cost_per_session_usd = ?, max_files_changed = ?, verification_pipeline = ?,
+                agent_backend = ?, task_agent_backends = ?, hosting_provider = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
@@ -196,6 +358,13 @@ impl ClaudeCodeRepository {
         .bind(&config.working_directory)
         .bind(&config.claude_model)
         .bind(config.max_session_duration_minutes)
+        .bind(config.max_tokens_per_session)
+        .bind(config.max_cost_per_session_usd)
+        .bind(config.max_files_changed)
+        .bind(serde_json::to_string(&config.verification_pipeline)?)
+        .bind(serde_json::to_string(&config.agent_backend)?)
+        .bind(serde_json::to_string(&config.task_agent_backends)?)
+        .bind(serde_json::to_string(&config.hosting_provider)?)
         .bind(config.updated_at)
         .bind(config.id.to_string())
         .execute(&self.pool)
@@ -209,7 +378,9 @@ impl ClaudeCodeRepository {
             r#"
             SELECT id, github_repo, github_owner, github_token, claude_api_key,
                    default_base_branch, auto_create_pr, working_directory,
-                   claude_model, max_session_duration_minutes, created_at, updated_at
+                   claude_model, max_session_duration_minutes, max_tokens_per_session,
+                   max_cost_per_session_usd, max_files_changed, verification_pipeline,
+                   agent_backend, task_agent_backends, hosting_provider, created_at, updated_at
             FROM claude_code_config
             LIMIT 1
             "#,
@@ -218,6 +389,10 @@ impl ClaudeCodeRepository {
         .await?;
 
         if let Some(row) = row {
+            let pipeline_json: Option<String> = row.get("verification_pipeline");
+            let backend_json: Option<String> = row.get("agent_backend");
+            let task_backends_json: Option<String> = row.get("task_agent_backends");
+            let hosting_json: Option<String> = row.get("hosting_provider");
             Ok(Some(ClaudeCodeConfig {
                 id: Uuid::parse_str(row.get("id"))?,
                 github_repo: row.get("github_repo"),
@@ -229,6 +404,25 @@ impl ClaudeCodeRepository {
                 working_directory: row.get("working_directory"),
                 claude_model: row.get("claude_model"),
                 max_session_duration_minutes: row.get("max_session_duration_minutes"),
+                max_tokens_per_session: row.get("max_tokens_per_session"),
+                max_cost_per_session_usd: row.get("max_cost_per_session_usd"),
+                max_files_changed: row.get("max_files_changed"),
+                verification_pipeline: pipeline_json
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()?
+                    .unwrap_or_default(),
+                agent_backend: backend_json
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()?
+                    .unwrap_or_default(),
+                task_agent_backends: task_backends_json
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()?
+                    .unwrap_or_default(),
+                hosting_provider: hosting_json
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()?
+                    .unwrap_or_default(),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             }))
@@ -260,6 +454,28 @@ impl ClaudeCodeRepository {
         Ok(())
     }
 
+    pub async fn update_template(&self, template: &ClaudePromptTemplate) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE claude_prompt_templates
+            SET name = ?, template = ?, description = ?, variables = ?, is_default = ?,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&template.name)
+        .bind(&template.template)
+        .bind(&template.description)
+        .bind(serde_json::to_string(&template.variables)?)
+        .bind(template.is_default)
+        .bind(template.updated_at)
+        .bind(template.id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_template(&self, name: &str) -> Result<Option<ClaudePromptTemplate>> {
         let row = sqlx::query(
             r#"
@@ -324,6 +540,9 @@ impl ClaudeCodeRepository {
         let status_str: String = row.get("status");
         let status = SessionStatus::from_str(&status_str)
             .map_err(|e| anyhow::anyhow!("Invalid session status: {}", e))?;
+        let verification_json: Option<String> = row.get("verification_report");
+        let retry_of: Option<String> = row.get("retry_of_session_id");
+        let pr_sync_json: Option<String> = row.get("pr_sync");
 
         Ok(ClaudeCodeSession {
             id: Uuid::parse_str(row.get("id"))?,
@@ -334,6 +553,21 @@ impl ClaudeCodeRepository {
             pr_number: row.get("pr_number"),
             session_log: row.get("session_log"),
             error_message: row.get("error_message"),
+            usage: AgentUsage {
+                input_tokens: row.get("input_tokens"),
+                output_tokens: row.get("output_tokens"),
+                cost_usd: row.get("cost_usd"),
+                files_changed: row.get("files_changed"),
+            },
+            verification: verification_json
+                .map(|json| serde_json::from_str(&json))
+                .transpose()?,
+            retry_of: retry_of.map(|id| Uuid::parse_str(&id)).transpose()?,
+            attempt: row.get("attempt"),
+            pr_sync: pr_sync_json
+                .map(|json| serde_json::from_str(&json))
+                .transpose()?
+                .unwrap_or_default(),
             started_at: row.get("started_at"),
             completed_at: row.get("completed_at"),
             created_at: row.get("created_at"),
@@ -341,3 +575,46 @@ impl ClaudeCodeRepository {
         })
     }
 }
+
+async fn update_session_with(
+    conn: &mut SqliteConnection,
+    session: &ClaudeCodeSession,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE claude_code_sessions
+        SET status = ?, branch_name = ?, pr_url = ?, pr_number = ?,
+            session_log = ?, error_message = ?, input_tokens = ?, output_tokens = ?,
+            cost_usd = ?, files_changed = ?, verification_report = ?, pr_sync = ?,
+            completed_at = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(session.status.as_str())
+    .bind(&session.branch_name)
+    .bind(&session.pr_url)
+    .bind(session.pr_number)
+    .bind(&session.session_log)
+    .bind(&session.error_message)
+    .bind(session.usage.input_tokens)
+    .bind(session.usage.output_tokens)
+    .bind(session.usage.cost_usd)
+    .bind(session.usage.files_changed)
+    .bind(verification_json(session)?)
+    .bind(serde_json::to_string(&session.pr_sync)?)
+    .bind(session.completed_at)
+    .bind(session.updated_at)
+    .bind(session.id.to_string())
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+fn verification_json(session: &ClaudeCodeSession) -> Result<Option<String>> {
+    Ok(session
+        .verification
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
@@ -1,7 +1,7 @@
 use crate::domain::comment::{Comment, EntityType};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,35 +16,8 @@ impl CommentRepository {
     }
 
     pub async fn create(&self, comment: &Comment) -> Result<()> {
-        let id_str = comment.id.to_string();
-        let entity_id_str = comment.entity_id.to_string();
-        let entity_type_str = format!("{:?}", comment.entity_type);
-        let author_id_str = comment.author_id.map(|id| id.to_string());
-        let created_at_str = comment.created_at.to_rfc3339();
-        let updated_at_str = comment.updated_at.to_rfc3339();
-        let edited_int = comment.edited as i32;
-
-        sqlx::query(
-            r#"
-            INSERT INTO comments (
-                id, entity_id, entity_type, author_id, author_name, 
-                content, created_at, updated_at, edited
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id_str)
-        .bind(entity_id_str)
-        .bind(entity_type_str)
-        .bind(author_id_str)
-        .bind(&comment.author_name)
-        .bind(&comment.content)
-        .bind(created_at_str)
-        .bind(updated_at_str)
-        .bind(edited_int)
-        .execute(&*self.pool)
-        .await?;
-
-        Ok(())
+        let mut conn = self.pool.acquire().await?;
+        insert_comment(&mut conn, comment).await
     }
 
     pub async fn update(&self, comment: &Comment) -> Result<()> {
@@ -122,6 +95,17 @@ impl CommentRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Move every comment on one entity to another. Returns how many moved.
+    pub async fn reassign(&self, from_entity_id: Uuid, to_entity_id: Uuid) -> Result<u64> {
+        let result = sqlx::query("UPDATE comments SET entity_id = ? WHERE entity_id = ?")
+            .bind(to_entity_id.to_string())
+            .bind(from_entity_id.to_string())
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn list_for_entity(&self, entity_id: Uuid) -> Result<Vec<Comment>> {
         let entity_id_str = entity_id.to_string();
 
@@ -166,3 +150,36 @@ impl CommentRepository {
         Ok(comments)
     }
 }
+
+/// Insert `comment` on `conn`, so it can share a transaction with other writes.
+pub(crate) async fn insert_comment(conn: &mut SqliteConnection, comment: &Comment) -> Result<()> {
+    let id_str = comment.id.to_string();
+    let entity_id_str = comment.entity_id.to_string();
+    let entity_type_str = format!("{:?}", comment.entity_type);
+    let author_id_str = comment.author_id.map(|id| id.to_string());
+    let created_at_str = comment.created_at.to_rfc3339();
+    let updated_at_str = comment.updated_at.to_rfc3339();
+    let edited_int = comment.edited as i32;
+
+    sqlx::query(
+        r#"
+        INSERT INTO comments (
+            id, entity_id, entity_type, author_id, author_name, 
+            content, created_at, updated_at, edited
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id_str)
+    .bind(entity_id_str)
+    .bind(entity_type_str)
+    .bind(author_id_str)
+    .bind(&comment.author_name)
+    .bind(&comment.content)
+    .bind(created_at_str)
+    .bind(updated_at_str)
+    .bind(edited_int)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
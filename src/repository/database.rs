@@ -1,3 +1,4 @@
+use crate::repository::task_metadata_repository::backfill_task_metadata;
 use anyhow::Result;
 use sqlx::{Sqlite, SqlitePool, migrate::MigrateDatabase, sqlite::SqlitePoolOptions};
 use std::path::Path;
@@ -26,6 +27,7 @@ pub async fn init_database(db_path: &str) -> Result<SqlitePool> {
     // Run SQLx migrations
     println!("Running database migrations...");
     sqlx::migrate!("./migrations").run(&pool).await?;
+    backfill_task_metadata(&pool).await?;
     println!("Migrations completed successfully");
 
     Ok(pool)
@@ -40,6 +42,7 @@ pub async fn init_test_database() -> Result<SqlitePool> {
 
     // Run SQLx migrations for tests
     sqlx::migrate!("./migrations").run(&pool).await?;
+    backfill_task_metadata(&pool).await?;
 
     Ok(pool)
 }
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Embedding vectors per task. Each is tagged with the model that made it
+/// and a hash of the task text, so vectors from another model or for older
+/// text are never returned.
+#[derive(Clone)]
+pub struct EmbeddingRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl EmbeddingRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    /// Stored vectors for the given tasks, keyed by task. `tasks` pairs
+    /// each id with the hash of its current text.
+    pub async fn get_many(
+        &self,
+        model: &str,
+        tasks: &[(Uuid, String)],
+    ) -> Result<HashMap<Uuid, Vec<f32>>> {
+        let wanted: HashMap<String, &str> = tasks
+            .iter()
+            .map(|(id, hash)| (id.to_string(), hash.as_str()))
+            .collect();
+        let rows = sqlx::query("SELECT task_id, content_hash, vector FROM task_embeddings WHERE model = ?")
+            .bind(model)
+            .fetch_all(&*self.pool)
+            .await?;
+
+        let mut vectors = HashMap::new();
+        for row in rows {
+            let task_id: String = row.get("task_id");
+            let hash: String = row.get("content_hash");
+            if wanted.get(&task_id) == Some(&hash.as_str()) {
+                let vector: Vec<f32> = serde_json::from_str(row.get("vector"))?;
+                vectors.insert(Uuid::parse_str(&task_id)?, vector);
+            }
+        }
+        Ok(vectors)
+    }
+
+    /// Store a task's vector, replacing whatever was stored for it before.
+    pub async fn save(&self, task_id: Uuid, model: &str, content_hash: &str, vector: &[f32]) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO task_embeddings (task_id, model, content_hash, vector, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(task_id.to_string())
+        .bind(model)
+        .bind(content_hash)
+        .bind(serde_json::to_string(vector)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn count(&self) -> Result<i64> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM task_embeddings")
+            .fetch_one(&*self.pool)
+            .await?;
+        Ok(row.get("count"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::task::Task;
+    use crate::repository::Repository;
+    use crate::repository::database::init_test_database;
+
+    #[tokio::test]
+    async fn test_vectors_are_keyed_by_model_and_text() {
+        let pool = init_test_database().await.unwrap();
+        let repository = Repository::new(pool);
+        let task = Task::new("Export".to_string(), "CSV".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let embeddings = &repository.embeddings;
+        embeddings.save(task.id, "nomic", "h1", &[1.0, 0.0]).await.unwrap();
+        embeddings.save(task.id, "nomic", "h2", &[0.0, 1.0]).await.unwrap();
+        assert_eq!(embeddings.count().await.unwrap(), 1);
+
+        let current = embeddings
+            .get_many("nomic", &[(task.id, "h2".to_string())])
+            .await
+            .unwrap();
+        assert_eq!(current[&task.id], vec![0.0, 1.0]);
+        let stale = embeddings
+            .get_many("nomic", &[(task.id, "h1".to_string())])
+            .await
+            .unwrap();
+        assert!(stale.is_empty());
+        let other_model = embeddings
+            .get_many("minilm", &[(task.id, "h2".to_string())])
+            .await
+            .unwrap();
+        assert!(other_model.is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::domain::goal::Goal;
+use crate::repository::summary_repository::invalidate_summaries;
 use anyhow::Result;
 use sqlx::{Row, SqlitePool};
 use std::sync::Arc;
@@ -56,6 +57,19 @@ impl GoalRepository {
     }
 
     pub async fn update(&self, goal: &Goal) -> Result<()> {
+        // Summaries of the old text are stale once the title or description changes
+        let previous = sqlx::query("SELECT title, description FROM goals WHERE id = ?")
+            .bind(goal.id.to_string())
+            .fetch_optional(self.pool.as_ref())
+            .await?;
+        if let Some(previous) = previous
+            && (previous.get::<String, _>("title") != goal.title
+                || previous.get::<String, _>("description") != goal.description)
+        {
+            let mut conn = self.pool.acquire().await?;
+            invalidate_summaries(&mut conn, &[goal.id]).await?;
+        }
+
         sqlx::query(
             r#"
             UPDATE goals SET
@@ -1,13 +1,19 @@
 pub mod app_settings_repository;
+pub mod approval_repository;
 pub mod claude_code_repository;
 pub mod comment_repository;
 pub mod database;
 pub mod dependency_repository;
+pub mod embedding_repository;
 pub mod goal_repository;
+pub mod notification_repository;
 pub mod recurring_repository;
 pub mod resource_repository;
+pub mod summary_repository;
 pub mod task_config_repository;
+pub mod task_metadata_repository;
 pub mod task_repository;
+pub mod time_entry_repository;
 
 use sqlx::SqlitePool;
 use std::sync::Arc;
@@ -24,6 +30,12 @@ pub struct Repository {
     pub task_configs: task_config_repository::TaskConfigRepository,
     pub claude_code: claude_code_repository::ClaudeCodeRepository,
     pub app_settings: app_settings_repository::AppSettingsRepository,
+    pub summaries: summary_repository::SummaryRepository,
+    pub embeddings: embedding_repository::EmbeddingRepository,
+    pub time_entries: time_entry_repository::TimeEntryRepository,
+    pub notifications: notification_repository::NotificationRepository,
+    pub approvals: approval_repository::ApprovalRepository,
+    pub task_metadata: task_metadata_repository::TaskMetadataRepository,
 }
 
 impl Repository {
@@ -39,6 +51,12 @@ impl Repository {
             task_configs: task_config_repository::TaskConfigRepository::new(pool.clone()),
             claude_code: claude_code_repository::ClaudeCodeRepository::new((*pool).clone()),
             app_settings: app_settings_repository::AppSettingsRepository::new((*pool).clone()),
+            summaries: summary_repository::SummaryRepository::new(pool.clone()),
+            embeddings: embedding_repository::EmbeddingRepository::new(pool.clone()),
+            time_entries: time_entry_repository::TimeEntryRepository::new(pool.clone()),
+            notifications: notification_repository::NotificationRepository::new(pool.clone()),
+            approvals: approval_repository::ApprovalRepository::new(pool.clone()),
+            task_metadata: task_metadata_repository::TaskMetadataRepository::new(pool.clone()),
             pool,
         }
     }
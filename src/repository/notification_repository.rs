@@ -0,0 +1,97 @@
+use crate::domain::notification::Notification;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const COLUMNS: &str = "id, resource_id, task_id, message, created_at, read_at";
+
+#[derive(Clone)]
+pub struct NotificationRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl NotificationRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, notification: &Notification) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO notifications ({}) VALUES (?, ?, ?, ?, ?, ?)",
+            COLUMNS
+        ))
+        .bind(notification.id.to_string())
+        .bind(notification.resource_id.map(|id| id.to_string()))
+        .bind(notification.task_id.map(|id| id.to_string()))
+        .bind(&notification.message)
+        .bind(notification.created_at.to_rfc3339())
+        .bind(notification.read_at.map(|t| t.to_rfc3339()))
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Notifications for a resource, including those for everyone, newest
+    /// first.
+    pub async fn list_for_resource(
+        &self,
+        resource_id: Uuid,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM notifications
+             WHERE (resource_id = ? OR resource_id IS NULL)
+               AND (? = 0 OR read_at IS NULL)
+             ORDER BY created_at DESC",
+            COLUMNS
+        ))
+        .bind(resource_id.to_string())
+        .bind(unread_only as i32)
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_notification).collect()
+    }
+
+    pub async fn list_for_task(&self, task_id: Uuid) -> Result<Vec<Notification>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM notifications WHERE task_id = ? ORDER BY created_at",
+            COLUMNS
+        ))
+        .bind(task_id.to_string())
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_notification).collect()
+    }
+
+    pub async fn mark_read(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = ? WHERE id = ? AND read_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_notification(row: sqlx::sqlite::SqliteRow) -> Result<Notification> {
+    let parse = |s: String| -> Result<DateTime<Utc>> {
+        Ok(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc))
+    };
+    let id: String = row.get("id");
+    let resource_id: Option<String> = row.get("resource_id");
+    let task_id: Option<String> = row.get("task_id");
+    let read_at: Option<String> = row.get("read_at");
+
+    Ok(Notification {
+        id: Uuid::parse_str(&id)?,
+        resource_id: resource_id.map(|s| Uuid::parse_str(&s)).transpose()?,
+        task_id: task_id.map(|s| Uuid::parse_str(&s)).transpose()?,
+        message: row.get("message"),
+        created_at: parse(row.get("created_at"))?,
+        read_at: read_at.map(parse).transpose()?,
+    })
+}
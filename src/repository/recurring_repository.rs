@@ -1,4 +1,7 @@
-use crate::domain::recurring::{RecurrencePattern, RecurrenceRule, RecurringTaskTemplate};
+use crate::domain::recurring::{
+    CatchUpPolicy, RecurrencePattern, RecurrenceRule, RecurringTaskTemplate,
+};
+use crate::domain::rrule::RRule;
 use crate::domain::task::Priority;
 use anyhow::Result;
 use chrono::{DateTime, NaiveTime, Utc, Weekday};
@@ -35,6 +38,9 @@ impl RecurringRepository {
         let end_date = template.recurrence_rule.end_date.map(|d| d.to_string());
         let last_generated = template.last_generated.map(|dt| dt.to_rfc3339());
         let next_occurrence = template.next_occurrence.map(|dt| dt.to_rfc3339());
+        let rrule = template.rrule.as_ref().map(|r| r.to_string());
+        let catch_up = self.catch_up_to_string(&template.catch_up);
+        let blueprint_json = serde_json::to_string(&template.blueprint)?;
 
         sqlx::query(
             "INSERT INTO recurring_templates (
@@ -44,8 +50,8 @@ impl RecurringRepository {
                 day_of_month, month_of_year, time_of_day,
                 end_date, max_occurrences, occurrences_count,
                 active, created_at, updated_at,
-                last_generated, next_occurrence
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                last_generated, next_occurrence, rrule, catch_up, blueprint
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&id)
         .bind(&template.title)
@@ -68,6 +74,9 @@ impl RecurringRepository {
         .bind(template.updated_at.to_rfc3339())
         .bind(last_generated)
         .bind(next_occurrence)
+        .bind(rrule)
+        .bind(&catch_up)
+        .bind(&blueprint_json)
         .execute(&*self.pool)
         .await?;
 
@@ -91,6 +100,9 @@ impl RecurringRepository {
         let end_date = template.recurrence_rule.end_date.map(|d| d.to_string());
         let last_generated = template.last_generated.map(|dt| dt.to_rfc3339());
         let next_occurrence = template.next_occurrence.map(|dt| dt.to_rfc3339());
+        let rrule = template.rrule.as_ref().map(|r| r.to_string());
+        let catch_up = self.catch_up_to_string(&template.catch_up);
+        let blueprint_json = serde_json::to_string(&template.blueprint)?;
 
         sqlx::query(
             "UPDATE recurring_templates SET
@@ -100,7 +112,8 @@ impl RecurringRepository {
                 day_of_month = ?, month_of_year = ?, time_of_day = ?,
                 end_date = ?, max_occurrences = ?, occurrences_count = ?,
                 active = ?, updated_at = ?,
-                last_generated = ?, next_occurrence = ?
+                last_generated = ?, next_occurrence = ?, rrule = ?, catch_up = ?,
+                blueprint = ?
             WHERE id = ?",
         )
         .bind(&template.title)
@@ -122,6 +135,9 @@ impl RecurringRepository {
         .bind(template.updated_at.to_rfc3339())
         .bind(last_generated)
         .bind(next_occurrence)
+        .bind(rrule)
+        .bind(&catch_up)
+        .bind(&blueprint_json)
         .bind(&id)
         .execute(&*self.pool)
         .await?;
@@ -191,6 +207,9 @@ impl RecurringRepository {
         let updated_at_str: String = row.get("updated_at");
         let last_generated_str: Option<String> = row.get("last_generated");
         let next_occurrence_str: Option<String> = row.get("next_occurrence");
+        let rrule_str: Option<String> = row.get("rrule");
+        let catch_up_str: String = row.get("catch_up");
+        let blueprint_json: String = row.get("blueprint");
 
         let metadata: HashMap<String, String> = serde_json::from_str(&metadata_json)?;
         let days_of_week = if let Some(json) = days_of_week_json {
@@ -224,6 +243,7 @@ impl RecurringRepository {
             assigned_resource_id: assigned_resource_id.and_then(|s| Uuid::parse_str(&s).ok()),
             estimated_hours,
             recurrence_rule,
+            rrule: rrule_str.map(|s| RRule::parse(&s)).transpose()?,
             active: active == 1,
             created_at: DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&Utc),
             updated_at: DateTime::parse_from_rfc3339(&updated_at_str)?.with_timezone(&Utc),
@@ -233,9 +253,63 @@ impl RecurringRepository {
             next_occurrence: next_occurrence_str
                 .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
                 .map(|dt| dt.with_timezone(&Utc)),
+            catch_up: self.string_to_catch_up(&catch_up_str)?,
+            blueprint: serde_json::from_str(&blueprint_json)?,
         })
     }
 
+    /// Record that the occurrence under `key` is being generated. `false`
+    /// when it already was, in which case nothing should be generated.
+    pub async fn claim_occurrence(
+        &self,
+        key: &str,
+        template_id: Uuid,
+        occurrence_at: DateTime<Utc>,
+        task_id: Uuid,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO recurring_occurrences (
+                occurrence_key, template_id, occurrence_at, task_id, created_at
+            ) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(key)
+        .bind(template_id.to_string())
+        .bind(occurrence_at.to_rfc3339())
+        .bind(task_id.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Give back a claim whose task could not be created.
+    pub async fn release_occurrence(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM recurring_occurrences WHERE occurrence_key = ?")
+            .bind(key)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    fn catch_up_to_string(&self, policy: &CatchUpPolicy) -> String {
+        match policy {
+            CatchUpPolicy::All => "All".to_string(),
+            CatchUpPolicy::Latest => "Latest".to_string(),
+            CatchUpPolicy::Skip => "Skip".to_string(),
+        }
+    }
+
+    fn string_to_catch_up(&self, s: &str) -> Result<CatchUpPolicy> {
+        match s {
+            "All" => Ok(CatchUpPolicy::All),
+            "Latest" => Ok(CatchUpPolicy::Latest),
+            "Skip" => Ok(CatchUpPolicy::Skip),
+            _ => Err(anyhow::anyhow!("Invalid catch-up policy: {}", s)),
+        }
+    }
+
     fn pattern_to_string(&self, pattern: &RecurrencePattern) -> String {
         match pattern {
             RecurrencePattern::Daily => "Daily".to_string(),
@@ -0,0 +1,198 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Persisted summaries, keyed by the entity they describe, the detail level
+/// and a hash of the text they were made from.
+#[derive(Clone)]
+pub struct SummaryRepository {
+    pool: Arc<SqlitePool>,
+}
+
+/// Hash of a summary's source text, stable across runs and Rust versions.
+pub fn content_hash(content: &str) -> String {
+    // FNV-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+impl SummaryRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get(&self, entity_id: Uuid, level: &str, content_hash: &str) -> Result<Option<String>> {
+        let row = sqlx::query(
+            "SELECT summary FROM summaries WHERE entity_id = ? AND level = ? AND content_hash = ?",
+        )
+        .bind(entity_id.to_string())
+        .bind(level)
+        .bind(content_hash)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(|r| r.get("summary")))
+    }
+
+    /// Summaries at `level` for many entities at once, keyed by entity.
+    /// `entities` pairs each id with the hash of its current text; stale
+    /// summaries are left out.
+    pub async fn get_many(
+        &self,
+        entities: &[(Uuid, String)],
+        level: &str,
+    ) -> Result<HashMap<Uuid, String>> {
+        let wanted: HashMap<String, &str> = entities
+            .iter()
+            .map(|(id, hash)| (id.to_string(), hash.as_str()))
+            .collect();
+        let rows = sqlx::query("SELECT entity_id, content_hash, summary FROM summaries WHERE level = ?")
+            .bind(level)
+            .fetch_all(&*self.pool)
+            .await?;
+
+        let mut summaries = HashMap::new();
+        for row in rows {
+            let entity_id: String = row.get("entity_id");
+            let hash: String = row.get("content_hash");
+            if wanted.get(&entity_id) == Some(&hash.as_str()) {
+                summaries.insert(Uuid::parse_str(&entity_id)?, row.get("summary"));
+            }
+        }
+        Ok(summaries)
+    }
+
+    /// Store a summary, replacing any older one for the entity and level.
+    pub async fn save(&self, entity_id: Uuid, level: &str, content_hash: &str, summary: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM summaries WHERE entity_id = ? AND level = ?")
+            .bind(entity_id.to_string())
+            .bind(level)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO summaries (entity_id, level, content_hash, summary, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(entity_id.to_string())
+        .bind(level)
+        .bind(content_hash)
+        .bind(summary)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop every summary of the given entities. Returns how many were removed.
+    pub async fn invalidate(&self, entity_ids: &[Uuid]) -> Result<u64> {
+        let mut removed = 0;
+        for entity_id in entity_ids {
+            removed += sqlx::query("DELETE FROM summaries WHERE entity_id = ?")
+                .bind(entity_id.to_string())
+                .execute(&*self.pool)
+                .await?
+                .rows_affected();
+        }
+        Ok(removed)
+    }
+
+    pub async fn count(&self) -> Result<i64> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM summaries")
+            .fetch_one(&*self.pool)
+            .await?;
+        Ok(row.get("count"))
+    }
+}
+
+/// Delete summaries of entities on a connection the caller holds, so task
+/// and goal updates can invalidate within their own transaction.
+pub(crate) async fn invalidate_summaries(
+    conn: &mut sqlx::SqliteConnection,
+    entity_ids: &[Uuid],
+) -> Result<()> {
+    for entity_id in entity_ids {
+        sqlx::query("DELETE FROM summaries WHERE entity_id = ?")
+            .bind(entity_id.to_string())
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::goal::Goal;
+    use crate::domain::task::Task;
+    use crate::repository::Repository;
+    use crate::repository::database::init_test_database;
+
+    #[tokio::test]
+    async fn test_save_get_and_invalidate() {
+        let pool = init_test_database().await.unwrap();
+        let repo = SummaryRepository::new(Arc::new(pool));
+        let id = Uuid::new_v4();
+        let hash = content_hash("Export: CSV");
+
+        repo.save(id, "HighLevel", &hash, "Old").await.unwrap();
+        repo.save(id, "HighLevel", &hash, "CSV export").await.unwrap();
+        repo.save(id, "MidLevel", &hash, "CSV export for lists").await.unwrap();
+        assert_eq!(repo.count().await.unwrap(), 2);
+        assert_eq!(repo.get(id, "HighLevel", &hash).await.unwrap().as_deref(), Some("CSV export"));
+        assert_eq!(repo.get(id, "HighLevel", &content_hash("Export: PDF")).await.unwrap(), None);
+
+        let other = Uuid::new_v4();
+        let found = repo
+            .get_many(&[(id, hash.clone()), (other, hash.clone())], "MidLevel")
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[&id], "CSV export for lists");
+
+        assert_eq!(repo.invalidate(&[id]).await.unwrap(), 2);
+        assert_eq!(repo.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_editing_text_invalidates_summaries() {
+        let pool = init_test_database().await.unwrap();
+        let repository = Repository::new(pool);
+
+        let mut goal = Goal::new("Reporting".to_string(), "Better reports".to_string());
+        repository.goals.create(&goal).await.unwrap();
+        let mut task = Task::new("Export".to_string(), "CSV".to_string());
+        task.goal_id = Some(goal.id);
+        repository.tasks.create(&task).await.unwrap();
+        for id in [task.id, goal.id] {
+            repository.summaries.save(id, "HighLevel", "h", "s").await.unwrap();
+        }
+
+        // Moving the task keeps its summaries
+        task.position.x += 50.0;
+        repository.tasks.update(&task).await.unwrap();
+        assert_eq!(repository.summaries.count().await.unwrap(), 2);
+
+        // Changing its text drops its summaries and its goal's
+        task.description = "CSV and PDF".to_string();
+        repository.tasks.update(&task).await.unwrap();
+        assert_eq!(repository.summaries.count().await.unwrap(), 0);
+
+        repository.summaries.save(goal.id, "HighLevel", "h", "s").await.unwrap();
+        goal.color = "#ff0000".to_string();
+        repository.goals.update(&goal).await.unwrap();
+        assert_eq!(repository.summaries.count().await.unwrap(), 1);
+        goal.title = "Reports".to_string();
+        repository.goals.update(&goal).await.unwrap();
+        assert_eq!(repository.summaries.count().await.unwrap(), 0);
+    }
+}
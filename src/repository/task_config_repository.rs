@@ -1,4 +1,5 @@
 use crate::domain::task_config::TaskConfiguration;
+use crate::repository::task_metadata_repository::reindex_configuration;
 use anyhow::Result;
 use sqlx::{Row, SqlitePool};
 use std::sync::Arc;
@@ -59,7 +60,17 @@ impl TaskConfigRepository {
         Ok(())
     }
 
+    /// Save `config`. If its fields changed, its tasks' metadata is typed
+    /// again, which fails if a unique field now has duplicate values.
     pub async fn update(&self, config: &TaskConfiguration) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let schema = serde_json::to_string(&config.metadata_schema)?;
+        let previous: Option<String> =
+            sqlx::query_scalar("SELECT metadata_schema FROM task_configurations WHERE id = ?1")
+                .bind(config.id.to_string())
+                .fetch_optional(&mut *tx)
+                .await?;
+
         sqlx::query(
             "UPDATE task_configurations SET 
                 name = ?2,
@@ -72,12 +83,16 @@ impl TaskConfigRepository {
         .bind(config.id.to_string())
         .bind(&config.name)
         .bind(&config.description)
-        .bind(serde_json::to_string(&config.metadata_schema)?)
+        .bind(&schema)
         .bind(serde_json::to_string(&config.state_machine)?)
         .bind(config.updated_at.to_rfc3339())
-        .execute(self.pool.as_ref())
+        .execute(&mut *tx)
         .await?;
 
+        if previous.is_some_and(|previous| previous != schema) {
+            reindex_configuration(&mut tx, config.id).await?;
+        }
+        tx.commit().await?;
         Ok(())
     }
 
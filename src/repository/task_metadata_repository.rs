@@ -0,0 +1,576 @@
+use crate::domain::metadata_value::MetadataValue;
+use crate::domain::task_config::MetadataSchema;
+use anyhow::{Result, bail};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Tasks whose `field` lies between `min` and `max`, both inclusive.
+/// Numbers and currencies compare as numbers, dates and times in time
+/// order, anything else as text.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataQuery {
+    pub field: String,
+    pub min: Option<MetadataValue>,
+    pub max: Option<MetadataValue>,
+    pub descending: bool,
+}
+
+/// Typed task metadata. Rows are written by `TaskRepository` with the task,
+/// so they can't drift from `Task::metadata`.
+#[derive(Clone)]
+pub struct TaskMetadataRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl TaskMetadataRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    /// A task's metadata, typed by its configuration's fields.
+    pub async fn get(&self, task_id: Uuid) -> Result<HashMap<String, MetadataValue>> {
+        let mut metadata = self.get_for_tasks(&[task_id]).await?;
+        Ok(metadata.remove(&task_id).unwrap_or_default())
+    }
+
+    /// The typed metadata of each of `task_ids` that has any, for views
+    /// and filters showing many tasks at once.
+    pub async fn get_for_tasks(
+        &self,
+        task_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, HashMap<String, MetadataValue>>> {
+        let mut values: HashMap<(String, String), (String, Vec<String>)> = HashMap::new();
+        // Stay well under SQLite's limit on bound parameters
+        for ids in task_ids.chunks(500) {
+            let sql = format!(
+                "SELECT task_id, field, value_type, value_text FROM task_metadata
+                 WHERE task_id IN ({}) ORDER BY task_id, field, position",
+                vec!["?"; ids.len()].join(", ")
+            );
+            let mut query = sqlx::query(&sql);
+            for id in ids {
+                query = query.bind(id.to_string());
+            }
+            for row in query.fetch_all(&*self.pool).await? {
+                values
+                    .entry((row.get("task_id"), row.get("field")))
+                    .or_insert_with(|| (row.get("value_type"), Vec::new()))
+                    .1
+                    .push(row.get("value_text"));
+            }
+        }
+
+        let mut metadata: HashMap<Uuid, HashMap<String, MetadataValue>> = HashMap::new();
+        for ((task_id, field), (kind, items)) in values {
+            let value = match kind.as_str() {
+                "List" => MetadataValue::List(items),
+                _ => MetadataValue::from_kind(&kind, &items.concat())?,
+            };
+            metadata
+                .entry(Uuid::parse_str(&task_id)?)
+                .or_default()
+                .insert(field, value);
+        }
+        Ok(metadata)
+    }
+
+    /// Ids of the tasks matching `query`, ordered by the field's value.
+    /// Tasks without the field are left out.
+    pub async fn find_tasks(&self, query: &MetadataQuery) -> Result<Vec<Uuid>> {
+        let bounds: Vec<(&str, &MetadataValue)> = [(">=", &query.min), ("<=", &query.max)]
+            .into_iter()
+            .filter_map(|(operator, bound)| Some((operator, bound.as_ref()?)))
+            .collect();
+        let column = match bounds.first().map(|(_, value)| *value) {
+            Some(value) if value.as_number().is_some() => "value_number",
+            Some(value) if value.as_sortable_date().is_some() => "value_date",
+            Some(_) => "lower(value_text)",
+            // Numbers sort before text in SQLite, so mixed fields stay grouped
+            None => "COALESCE(value_number, value_date, lower(value_text))",
+        };
+
+        let mut sql = String::from("SELECT task_id FROM task_metadata WHERE field = ?");
+        for (operator, value) in &bounds {
+            let comparable = match column {
+                "value_number" => value.as_number().is_some(),
+                "value_date" => value.as_sortable_date().is_some(),
+                _ => true,
+            };
+            if !comparable {
+                bail!(
+                    "Can't compare {} with a {} value",
+                    query.field,
+                    value.kind()
+                );
+            }
+            sql.push_str(&format!(" AND {} {} ?", column, operator));
+        }
+        let (aggregate, direction) = if query.descending {
+            ("MAX", "DESC")
+        } else {
+            ("MIN", "ASC")
+        };
+        sql.push_str(&format!(
+            " GROUP BY task_id ORDER BY {agg}({col}) IS NULL, {agg}({col}) {dir}, task_id",
+            agg = aggregate,
+            col = column,
+            dir = direction
+        ));
+
+        let mut statement = sqlx::query(&sql).bind(&query.field);
+        for (_, value) in &bounds {
+            statement = match column {
+                "value_number" => statement.bind(value.as_number()),
+                "value_date" => statement.bind(value.as_sortable_date()),
+                _ => statement.bind(value.to_string().to_lowercase()),
+            };
+        }
+        let rows = statement.fetch_all(&*self.pool).await?;
+        rows.iter()
+            .map(|row| Ok(Uuid::parse_str(row.get("task_id"))?))
+            .collect()
+    }
+
+    /// Ids of the tasks with a list field, such as a relation, that holds
+    /// `item`.
+    pub async fn tasks_listing(&self, item: &str) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT task_id FROM task_metadata
+             WHERE value_type = 'List' AND value_text = ?",
+        )
+        .bind(item)
+        .fetch_all(&*self.pool)
+        .await?;
+        ids.iter().map(|id| Ok(Uuid::parse_str(id)?)).collect()
+    }
+}
+
+/// Replace a task's rows in task_metadata, typing its values by its
+/// configuration's fields. Fails, naming the field, if a value a unique
+/// field needs is taken by another task of the same configuration.
+pub(crate) async fn index_task_metadata(
+    conn: &mut SqliteConnection,
+    task_id: Uuid,
+    configuration_id: Option<Uuid>,
+    metadata: &HashMap<String, String>,
+) -> Result<()> {
+    index_with(conn, task_id, configuration_id, metadata, true).await
+}
+
+/// Name of the task_metadata backfill in `data_backfills`.
+const METADATA_BACKFILL: &str = "task_metadata";
+
+/// Index the metadata tasks had before the task_metadata table existed.
+/// Runs once per database; later calls return `None`, since saves keep
+/// the index up to date.
+pub async fn backfill_task_metadata(pool: &SqlitePool) -> Result<Option<usize>> {
+    let done: Option<String> =
+        sqlx::query_scalar("SELECT completed_at FROM data_backfills WHERE name = ?")
+            .bind(METADATA_BACKFILL)
+            .fetch_optional(pool)
+            .await?;
+    if done.is_some() {
+        return Ok(None);
+    }
+    let indexed = index_unindexed_tasks(pool).await?;
+    sqlx::query("INSERT OR IGNORE INTO data_backfills (name, completed_at) VALUES (?, ?)")
+        .bind(METADATA_BACKFILL)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(Some(indexed))
+}
+
+/// Index the tasks that have metadata but no task_metadata rows. Values
+/// are typed like a save would type them; unique values another task
+/// already holds are kept without their key, so existing duplicates are
+/// indexed but can't be saved again until changed.
+async fn index_unindexed_tasks(pool: &SqlitePool) -> Result<usize> {
+    let rows = sqlx::query(
+        "SELECT id, configuration_id, metadata FROM tasks
+         WHERE metadata NOT IN ('', '{}')
+           AND id NOT IN (SELECT task_id FROM task_metadata)
+         ORDER BY created_at, id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut indexed = 0;
+    for row in rows {
+        let id = Uuid::parse_str(row.get("id"))?;
+        let configuration_id = row
+            .get::<Option<String>, _>("configuration_id")
+            .map(|id| Uuid::parse_str(&id))
+            .transpose()?;
+        let metadata: HashMap<String, String> = match serde_json::from_str(row.get("metadata")) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tracing::warn!("Skipping metadata of task {}: {}", id, e);
+                continue;
+            }
+        };
+        index_with(&mut tx, id, configuration_id, &metadata, false).await?;
+        indexed += 1;
+    }
+    tx.commit().await?;
+    Ok(indexed)
+}
+
+/// Index a task's metadata. With `enforce_unique` off, a unique value
+/// another task holds is stored without its key instead of failing.
+async fn index_with(
+    conn: &mut SqliteConnection,
+    task_id: Uuid,
+    configuration_id: Option<Uuid>,
+    metadata: &HashMap<String, String>,
+    enforce_unique: bool,
+) -> Result<()> {
+    let schema = match configuration_id {
+        Some(id) => {
+            let schema: Option<String> =
+                sqlx::query_scalar("SELECT metadata_schema FROM task_configurations WHERE id = ?")
+                    .bind(id.to_string())
+                    .fetch_optional(&mut *conn)
+                    .await?;
+            schema
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?
+                .unwrap_or_default()
+        }
+        None => MetadataSchema::default(),
+    };
+
+    sqlx::query("DELETE FROM task_metadata WHERE task_id = ?")
+        .bind(task_id.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    for (field, raw) in metadata {
+        let value = schema.typed_value(field, raw);
+        let unique_key =
+            (schema.is_unique(field) && !raw.trim().is_empty()).then(|| value.unique_key());
+        let items = value.items();
+        for (position, item) in items.iter().enumerate() {
+            let first = position == 0;
+            let (number, date) = match &value {
+                MetadataValue::List(_) => (None, None),
+                _ => (value.as_number(), value.as_sortable_date()),
+            };
+            let insert = |unique_key: Option<&String>| {
+                sqlx::query(
+                    "INSERT INTO task_metadata (
+                        task_id, field, position, configuration_id, value_type, value_text,
+                        value_number, value_date, unique_key
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(task_id.to_string())
+                .bind(field)
+                .bind(position as i64)
+                .bind(configuration_id.map(|id| id.to_string()))
+                .bind(value.kind())
+                .bind(item)
+                .bind(number)
+                .bind(date.clone())
+                .bind(unique_key.cloned())
+            };
+            let key = unique_key.as_ref().filter(|_| first);
+            match insert(key).execute(&mut *conn).await {
+                Ok(_) => {}
+                Err(sqlx::Error::Database(db)) if db.is_unique_violation() && !enforce_unique => {
+                    tracing::warn!(
+                        "Task {} shares its unique {} '{}' with another task",
+                        task_id,
+                        field,
+                        raw
+                    );
+                    insert(None).execute(&mut *conn).await?;
+                }
+                Err(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+                    let name = schema.fields.get(field).map_or(field, |f| &f.display_name);
+                    bail!(
+                        "{} must be unique; '{}' is already used by another task",
+                        name,
+                        raw
+                    );
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Re-type the metadata of a configuration's tasks after its fields
+/// change.
+pub(crate) async fn reindex_configuration(
+    conn: &mut SqliteConnection,
+    configuration_id: Uuid,
+) -> Result<()> {
+    // Databases set up with `TaskConfigRepository::create_tables` alone
+    // have no tasks to re-type
+    let indexed: Option<i64> = sqlx::query_scalar(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'task_metadata'",
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if indexed.is_none() {
+        return Ok(());
+    }
+
+    let rows = sqlx::query("SELECT id, metadata FROM tasks WHERE configuration_id = ?")
+        .bind(configuration_id.to_string())
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in rows {
+        let id = Uuid::parse_str(row.get("id"))?;
+        let metadata: HashMap<String, String> = serde_json::from_str(row.get("metadata"))?;
+        index_task_metadata(conn, id, Some(configuration_id), &metadata).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::Task;
+    use crate::domain::task_config::{
+        FieldType, MetadataFieldConfig, TaskConfiguration, ValidationRule,
+    };
+    use crate::repository::Repository;
+    use crate::repository::database::init_test_database;
+    use chrono::NaiveDate;
+
+    fn field(name: &str, field_type: FieldType) -> MetadataFieldConfig {
+        MetadataFieldConfig {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            field_type,
+            required: false,
+            options: vec![],
+            default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
+            validation_rules: vec![],
+            help_text: String::new(),
+            show_in_list: true,
+            show_in_card: false,
+            sortable: true,
+            searchable: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_values_sort_filter_and_stay_unique() {
+        let repository = Repository::new(init_test_database().await.unwrap());
+        let mut config = TaskConfiguration::new("Invoices".to_string());
+        config.add_metadata_field(field("amount", FieldType::Currency));
+        config.add_metadata_field(field("due", FieldType::Date));
+        config.add_metadata_field(MetadataFieldConfig {
+            validation_rules: vec![ValidationRule::UniqueValue],
+            ..field("number", FieldType::Text)
+        });
+        repository.task_configs.create(&config).await.unwrap();
+
+        let invoice = |title: &str, amount: &str, due: &str, number: &str| {
+            let mut task = Task::new(title.to_string(), String::new());
+            task.configuration_id = Some(config.id);
+            for (name, value) in [("amount", amount), ("due", due), ("number", number)] {
+                task.metadata.insert(name.to_string(), value.to_string());
+            }
+            task
+        };
+        let small = invoice("Small", "$90", "2025-03-10", "INV-1");
+        let large = invoice("Large", "$1,200.00", "2025-02-01", "INV-2");
+        let medium = invoice("Medium", "450 EUR", "2025-04-01", "INV-3");
+        for task in [&small, &large, &medium] {
+            repository.tasks.create(task).await.unwrap();
+        }
+
+        let typed = repository.task_metadata.get(large.id).await.unwrap();
+        assert_eq!(typed["amount"], MetadataValue::Currency(1200.0));
+        assert_eq!(
+            typed["due"],
+            MetadataValue::Date(NaiveDate::from_ymd_opt(2025, 2, 1).unwrap())
+        );
+        let plain = Task::new("Plain".to_string(), String::new());
+        let all = repository
+            .task_metadata
+            .get_for_tasks(&[small.id, large.id, plain.id])
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[&small.id]["amount"], MetadataValue::Currency(90.0));
+        assert_eq!(all[&large.id], typed);
+
+        // As text, "$90" would sort after "$1,200.00"
+        let by_amount = repository
+            .task_metadata
+            .find_tasks(&MetadataQuery {
+                field: "amount".to_string(),
+                descending: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_amount, [large.id, medium.id, small.id]);
+
+        let in_range = repository
+            .task_metadata
+            .find_tasks(&MetadataQuery {
+                field: "amount".to_string(),
+                min: Some(MetadataValue::Number(100.0)),
+                max: Some(MetadataValue::Number(1000.0)),
+                descending: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(in_range, [medium.id]);
+
+        let due_by_march = repository
+            .task_metadata
+            .find_tasks(&MetadataQuery {
+                field: "due".to_string(),
+                max: MetadataValue::parse(FieldType::Date, "2025-03-31").ok(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(due_by_march, [large.id, small.id]);
+
+        // Unique values are enforced by the database, ignoring case
+        let duplicate = invoice("Copy", "$10", "2025-05-01", "inv-1");
+        let error = repository.tasks.create(&duplicate).await.unwrap_err();
+        assert!(
+            error.to_string().contains("number must be unique"),
+            "{}",
+            error
+        );
+        assert!(repository.tasks.get(duplicate.id).await.unwrap().is_none());
+
+        let mut renumbered = medium.clone();
+        renumbered
+            .metadata
+            .insert("number".to_string(), "INV-2".to_string());
+        assert!(repository.tasks.update(&renumbered).await.is_err());
+        renumbered
+            .metadata
+            .insert("number".to_string(), "INV-4".to_string());
+        repository.tasks.update(&renumbered).await.unwrap();
+
+        // Making a field unique re-types the existing tasks, and fails while
+        // they share values
+        let mut other = invoice("Other", "$5", "2025-03-10", "INV-5");
+        other.metadata.remove("amount");
+        repository.tasks.create(&other).await.unwrap();
+        config
+            .metadata_schema
+            .fields
+            .get_mut("due")
+            .unwrap()
+            .validation_rules
+            .push(ValidationRule::UniqueValue);
+        assert!(repository.task_configs.update(&config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_existing_metadata_is_indexed_by_its_fields() {
+        let repository = Repository::new(init_test_database().await.unwrap());
+        let mut config = TaskConfiguration::new("Legacy".to_string());
+        config.add_metadata_field(field("points", FieldType::Number));
+        config.add_metadata_field(field("amount", FieldType::Currency));
+        config.add_metadata_field(field("label", FieldType::Text));
+        config.add_metadata_field(MetadataFieldConfig {
+            validation_rules: vec![ValidationRule::UniqueValue],
+            ..field("code", FieldType::Text)
+        });
+        repository.task_configs.create(&config).await.unwrap();
+
+        let legacy = |title: &str, amount: &str, code: &str| {
+            let mut task = Task::new(title.to_string(), String::new());
+            task.configuration_id = Some(config.id);
+            for (name, value) in [
+                ("points", "8"),
+                ("amount", amount),
+                ("label", "8"),
+                ("code", code),
+            ] {
+                task.metadata.insert(name.to_string(), value.to_string());
+            }
+            task
+        };
+        let first = legacy("First", "$90", "A-1");
+        let second = legacy("Second", "$1,200.00", "A-2");
+        repository.tasks.create(&first).await.unwrap();
+        repository.tasks.create(&second).await.unwrap();
+
+        // As the tasks were before the table existed, one of them with a
+        // code the other already uses
+        let mut metadata = second.metadata.clone();
+        metadata.insert("code".to_string(), "a-1".to_string());
+        sqlx::query("UPDATE tasks SET metadata = ? WHERE id = ?")
+            .bind(serde_json::to_string(&metadata).unwrap())
+            .bind(second.id.to_string())
+            .execute(&*repository.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM task_metadata")
+            .execute(&*repository.pool)
+            .await
+            .unwrap();
+        // Opening the database ran the backfill already, so it is skipped
+        assert_eq!(
+            backfill_task_metadata(&repository.pool).await.unwrap(),
+            None
+        );
+        sqlx::query("DELETE FROM data_backfills")
+            .execute(&*repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            backfill_task_metadata(&repository.pool).await.unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            backfill_task_metadata(&repository.pool).await.unwrap(),
+            None
+        );
+
+        let typed = repository.task_metadata.get(first.id).await.unwrap();
+        assert_eq!(typed["points"], MetadataValue::Number(8.0));
+        assert_eq!(typed["amount"], MetadataValue::Currency(90.0));
+        assert_eq!(typed["label"], MetadataValue::Text("8".to_string()));
+
+        let by_amount = repository
+            .task_metadata
+            .find_tasks(&MetadataQuery {
+                field: "amount".to_string(),
+                min: Some(MetadataValue::Number(100.0)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_amount, [second.id]);
+
+        let keyed: Vec<Option<String>> = sqlx::query_scalar(
+            "SELECT unique_key FROM task_metadata WHERE field = 'code' ORDER BY task_id = ?",
+        )
+        .bind(second.id.to_string())
+        .fetch_all(&*repository.pool)
+        .await
+        .unwrap();
+        assert!(keyed[0].is_some());
+        assert!(keyed[1].is_none());
+
+        // The duplicate can't be saved again until its code changes
+        let mut duplicate = repository.tasks.get(second.id).await.unwrap().unwrap();
+        assert!(repository.tasks.update(&duplicate).await.is_err());
+        duplicate
+            .metadata
+            .insert("code".to_string(), "A-3".to_string());
+        repository.tasks.update(&duplicate).await.unwrap();
+    }
+}
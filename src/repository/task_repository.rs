@@ -1,11 +1,13 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde_json;
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::domain::task::{Position, Priority, SubTask, Task, TaskStatus};
+use crate::repository::summary_repository::invalidate_summaries;
+use crate::repository::task_metadata_repository::index_task_metadata;
 
 #[derive(Clone)]
 pub struct TaskRepository {
@@ -27,8 +29,9 @@ impl TaskRepository {
                 id, title, description, status, priority, metadata, tags,
                 created_at, updated_at, due_date, scheduled_date, completed_at,
                 estimated_hours, actual_hours, assigned_resource_id,
-                goal_id, parent_task_id, position_x, position_y, is_archived, assignee, configuration_id, sort_order
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                goal_id, parent_task_id, position_x, position_y, is_archived, assignee, configuration_id, sort_order,
+                workflow_state
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(task.id.to_string())
@@ -54,9 +57,12 @@ impl TaskRepository {
         .bind(task.assignee.as_ref())
         .bind(task.configuration_id.map(|id| id.to_string()))
         .bind(task.sort_order)
+        .bind(task.workflow_state.as_ref())
         .execute(&mut *tx)
         .await?;
 
+        index_task_metadata(&mut tx, task.id, task.configuration_id, &task.metadata).await?;
+
         // Insert subtasks
         for subtask in &task.subtasks {
             sqlx::query(
@@ -75,8 +81,10 @@ impl TaskRepository {
             .await?;
         }
 
-        // Update spatial index (using rowid from the inserted task)
-        let rowid: i64 = sqlx::query_scalar("SELECT last_insert_rowid()")
+        // Update spatial index (using rowid from the inserted task; the
+        // last inserted row may be a subtask)
+        let rowid: i64 = sqlx::query_scalar("SELECT rowid FROM tasks WHERE id = ?")
+            .bind(task.id.to_string())
             .fetch_one(&mut *tx)
             .await?;
 
@@ -100,94 +108,7 @@ impl TaskRepository {
 
     pub async fn update(&self, task: &Task) -> Result<()> {
         let mut tx = self.pool.begin().await?;
-
-        // Update main task
-        sqlx::query(
-            r#"
-            UPDATE tasks SET
-                title = ?, description = ?, status = ?, priority = ?,
-                metadata = ?, tags = ?, updated_at = ?, due_date = ?,
-                scheduled_date = ?, completed_at = ?, estimated_hours = ?,
-                actual_hours = ?, assigned_resource_id = ?, goal_id = ?,
-                parent_task_id = ?, position_x = ?, position_y = ?, is_archived = ?, assignee = ?, configuration_id = ?, sort_order = ?
-            WHERE id = ?
-            "#,
-        )
-        .bind(&task.title)
-        .bind(&task.description)
-        .bind(status_to_string(&task.status))
-        .bind(priority_to_string(&task.priority))
-        .bind(serde_json::to_string(&task.metadata)?)
-        .bind(serde_json::to_string(&task.tags)?)
-        .bind(task.updated_at.to_rfc3339())
-        .bind(task.due_date.map(|d| d.to_rfc3339()))
-        .bind(task.scheduled_date.map(|d| d.to_rfc3339()))
-        .bind(task.completed_at.map(|d| d.to_rfc3339()))
-        .bind(task.estimated_hours)
-        .bind(task.actual_hours)
-        .bind(task.assigned_resource_id.map(|id| id.to_string()))
-        .bind(task.goal_id.map(|id| id.to_string()))
-        .bind(task.parent_task_id.map(|id| id.to_string()))
-        .bind(task.position.x)
-        .bind(task.position.y)
-        .bind(if task.is_archived { 1 } else { 0 })
-        .bind(task.assignee.as_ref())
-        .bind(task.configuration_id.map(|id| id.to_string()))
-        .bind(task.sort_order)
-        .bind(task.id.to_string())
-        .execute(&mut *tx)
-        .await?;
-
-        // Delete existing subtasks and insert new ones
-        sqlx::query("DELETE FROM subtasks WHERE task_id = ?")
-            .bind(task.id.to_string())
-            .execute(&mut *tx)
-            .await?;
-
-        for subtask in &task.subtasks {
-            sqlx::query(
-                r#"
-                INSERT INTO subtasks (id, task_id, description, completed, created_at, completed_at)
-                VALUES (?, ?, ?, ?, ?, ?)
-                "#,
-            )
-            .bind(subtask.id.to_string())
-            .bind(task.id.to_string())
-            .bind(&subtask.description)
-            .bind(subtask.completed as i32)
-            .bind(subtask.created_at.to_rfc3339())
-            .bind(subtask.completed_at.map(|d| d.to_rfc3339()))
-            .execute(&mut *tx)
-            .await?;
-        }
-
-        // Update spatial index
-        let rowid: Option<i64> = sqlx::query_scalar("SELECT rowid FROM tasks WHERE id = ?")
-            .bind(task.id.to_string())
-            .fetch_optional(&mut *tx)
-            .await?;
-
-        if let Some(rowid) = rowid {
-            sqlx::query("DELETE FROM tasks_spatial WHERE id = ?")
-                .bind(rowid)
-                .execute(&mut *tx)
-                .await?;
-
-            sqlx::query(
-                r#"
-                INSERT INTO tasks_spatial (id, min_x, max_x, min_y, max_y)
-                VALUES (?, ?, ?, ?, ?)
-                "#,
-            )
-            .bind(rowid)
-            .bind(task.position.x)
-            .bind(task.position.x)
-            .bind(task.position.y)
-            .bind(task.position.y)
-            .execute(&mut *tx)
-            .await?;
-        }
-
+        update_with(&mut tx, task).await?;
         tx.commit().await?;
         Ok(())
     }
@@ -198,7 +119,8 @@ impl TaskRepository {
             SELECT id, title, description, status, priority, metadata, tags,
                    created_at, updated_at, due_date, scheduled_date, completed_at,
                    estimated_hours, actual_hours, assigned_resource_id,
-                   goal_id, parent_task_id, position_x, position_y, is_archived, assignee, configuration_id, sort_order
+                   goal_id, parent_task_id, position_x, position_y, is_archived, assignee, configuration_id, sort_order,
+                   workflow_state
             FROM tasks WHERE id = ?
             "#,
         )
@@ -246,28 +168,19 @@ impl TaskRepository {
 
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let mut tx = self.pool.begin().await?;
+        let deleted = delete_with(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
 
-        // Delete from spatial index
-        let rowid: Option<i64> = sqlx::query_scalar("SELECT rowid FROM tasks WHERE id = ?")
-            .bind(id.to_string())
-            .fetch_optional(&mut *tx)
-            .await?;
-
-        if let Some(rowid) = rowid {
-            sqlx::query("DELETE FROM tasks_spatial WHERE id = ?")
-                .bind(rowid)
-                .execute(&mut *tx)
-                .await?;
-        }
-
-        // Delete task (subtasks will cascade)
-        let result = sqlx::query("DELETE FROM tasks WHERE id = ?")
-            .bind(id.to_string())
-            .execute(&mut *tx)
-            .await?;
-
+    /// Delete `duplicate_id` and save `keep` in one transaction, so a merge
+    /// that fails leaves the duplicate in place.
+    pub async fn replace(&self, keep: &Task, duplicate_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        delete_with(&mut tx, duplicate_id).await?;
+        update_with(&mut tx, keep).await?;
         tx.commit().await?;
-        Ok(result.rows_affected() > 0)
+        Ok(())
     }
 
     pub async fn list(&self, filters: TaskFilters) -> Result<Vec<Task>> {
@@ -277,7 +190,8 @@ impl TaskRepository {
                    t.metadata, t.tags, t.created_at, t.updated_at, t.due_date,
                    t.scheduled_date, t.completed_at, t.estimated_hours, t.actual_hours,
                    t.assigned_resource_id, t.goal_id, t.parent_task_id,
-                   t.position_x, t.position_y, t.is_archived, t.assignee, t.configuration_id, t.sort_order
+                   t.position_x, t.position_y, t.is_archived, t.assignee, t.configuration_id, t.sort_order,
+                   t.workflow_state
             FROM tasks t
             WHERE 1=1
             "#,
@@ -435,10 +349,152 @@ impl TaskRepository {
                 .get::<Option<String>, _>("configuration_id")
                 .and_then(|s| Uuid::parse_str(&s).ok()),
             sort_order: row.get::<Option<i32>, _>("sort_order").unwrap_or(0),
+            workflow_state: row.try_get("workflow_state").unwrap_or_default(),
         })
     }
 }
 
+async fn update_with(conn: &mut SqliteConnection, task: &Task) -> Result<()> {
+
+    // Summaries of the old text are stale once the title or description
+    // changes, including the summary of the goal it belongs to
+    let previous = sqlx::query("SELECT title, description, goal_id FROM tasks WHERE id = ?")
+        .bind(task.id.to_string())
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(previous) = previous {
+        let title: String = previous.get("title");
+        let description: String = previous.get("description");
+        if title != task.title || description != task.description {
+            let mut stale = vec![task.id];
+            stale.extend(task.goal_id);
+            stale.extend(
+                previous
+                    .get::<Option<String>, _>("goal_id")
+                    .and_then(|id| Uuid::parse_str(&id).ok()),
+            );
+            invalidate_summaries(&mut *conn, &stale).await?;
+        }
+    }
+
+    // Update main task
+    sqlx::query(
+        r#"
+        UPDATE tasks SET
+            title = ?, description = ?, status = ?, priority = ?,
+            metadata = ?, tags = ?, updated_at = ?, due_date = ?,
+            scheduled_date = ?, completed_at = ?, estimated_hours = ?,
+            actual_hours = ?, assigned_resource_id = ?, goal_id = ?,
+            parent_task_id = ?, position_x = ?, position_y = ?, is_archived = ?, assignee = ?, configuration_id = ?, sort_order = ?,
+            workflow_state = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&task.title)
+    .bind(&task.description)
+    .bind(status_to_string(&task.status))
+    .bind(priority_to_string(&task.priority))
+    .bind(serde_json::to_string(&task.metadata)?)
+    .bind(serde_json::to_string(&task.tags)?)
+    .bind(task.updated_at.to_rfc3339())
+    .bind(task.due_date.map(|d| d.to_rfc3339()))
+    .bind(task.scheduled_date.map(|d| d.to_rfc3339()))
+    .bind(task.completed_at.map(|d| d.to_rfc3339()))
+    .bind(task.estimated_hours)
+    .bind(task.actual_hours)
+    .bind(task.assigned_resource_id.map(|id| id.to_string()))
+    .bind(task.goal_id.map(|id| id.to_string()))
+    .bind(task.parent_task_id.map(|id| id.to_string()))
+    .bind(task.position.x)
+    .bind(task.position.y)
+    .bind(if task.is_archived { 1 } else { 0 })
+    .bind(task.assignee.as_ref())
+    .bind(task.configuration_id.map(|id| id.to_string()))
+    .bind(task.sort_order)
+    .bind(task.workflow_state.as_ref())
+    .bind(task.id.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    index_task_metadata(&mut *conn, task.id, task.configuration_id, &task.metadata).await?;
+
+    // Delete existing subtasks and insert new ones
+    sqlx::query("DELETE FROM subtasks WHERE task_id = ?")
+        .bind(task.id.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    for subtask in &task.subtasks {
+        sqlx::query(
+            r#"
+            INSERT INTO subtasks (id, task_id, description, completed, created_at, completed_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(subtask.id.to_string())
+        .bind(task.id.to_string())
+        .bind(&subtask.description)
+        .bind(subtask.completed as i32)
+        .bind(subtask.created_at.to_rfc3339())
+        .bind(subtask.completed_at.map(|d| d.to_rfc3339()))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    // Update spatial index
+    let rowid: Option<i64> = sqlx::query_scalar("SELECT rowid FROM tasks WHERE id = ?")
+        .bind(task.id.to_string())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    if let Some(rowid) = rowid {
+        sqlx::query("DELETE FROM tasks_spatial WHERE id = ?")
+            .bind(rowid)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tasks_spatial (id, min_x, max_x, min_y, max_y)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(rowid)
+        .bind(task.position.x)
+        .bind(task.position.x)
+        .bind(task.position.y)
+        .bind(task.position.y)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn delete_with(conn: &mut SqliteConnection, id: Uuid) -> Result<bool> {
+
+    // Delete from spatial index
+    let rowid: Option<i64> = sqlx::query_scalar("SELECT rowid FROM tasks WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    if let Some(rowid) = rowid {
+        sqlx::query("DELETE FROM tasks_spatial WHERE id = ?")
+            .bind(rowid)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    // Delete task (subtasks will cascade)
+    let result = sqlx::query("DELETE FROM tasks WHERE id = ?")
+        .bind(id.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 #[derive(Default)]
 pub struct TaskFilters {
     pub status: Option<TaskStatus>,
@@ -0,0 +1,216 @@
+use crate::domain::time_entry::TimeEntry;
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const COLUMNS: &str =
+    "id, task_id, resource_id, start_time, end_time, description, manual, rolled_up, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct TimeEntryRepository {
+    pool: Arc<SqlitePool>,
+}
+
+/// Fixed-width UTC timestamps, so they sort and compare as text.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+impl TimeEntryRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, entry: &TimeEntry) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO time_entries ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            COLUMNS
+        ))
+        .bind(entry.id.to_string())
+        .bind(entry.task_id.to_string())
+        .bind(entry.resource_id.map(|id| id.to_string()))
+        .bind(timestamp(entry.start_time))
+        .bind(entry.end_time.map(timestamp))
+        .bind(&entry.description)
+        .bind(entry.manual as i32)
+        .bind(entry.rolled_up as i32)
+        .bind(timestamp(entry.created_at))
+        .bind(timestamp(entry.updated_at))
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update(&self, entry: &TimeEntry) -> Result<()> {
+        sqlx::query(
+            "UPDATE time_entries SET
+                task_id = ?, resource_id = ?, start_time = ?, end_time = ?,
+                description = ?, manual = ?, rolled_up = ?, updated_at = ?
+            WHERE id = ?",
+        )
+        .bind(entry.task_id.to_string())
+        .bind(entry.resource_id.map(|id| id.to_string()))
+        .bind(timestamp(entry.start_time))
+        .bind(entry.end_time.map(timestamp))
+        .bind(&entry.description)
+        .bind(entry.manual as i32)
+        .bind(entry.rolled_up as i32)
+        .bind(timestamp(entry.updated_at))
+        .bind(entry.id.to_string())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM time_entries WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move every entry on one task to another. Returns how many moved.
+    pub async fn reassign(&self, from_task_id: Uuid, to_task_id: Uuid) -> Result<u64> {
+        let result = sqlx::query("UPDATE time_entries SET task_id = ? WHERE task_id = ?")
+            .bind(to_task_id.to_string())
+            .bind(from_task_id.to_string())
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<TimeEntry>> {
+        let row = sqlx::query(&format!("SELECT {} FROM time_entries WHERE id = ?", COLUMNS))
+            .bind(id.to_string())
+            .fetch_optional(&*self.pool)
+            .await?;
+        row.map(row_to_entry).transpose()
+    }
+
+    /// A task's segments, oldest first.
+    pub async fn list_for_task(&self, task_id: Uuid) -> Result<Vec<TimeEntry>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM time_entries WHERE task_id = ? ORDER BY start_time",
+            COLUMNS
+        ))
+        .bind(task_id.to_string())
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_entry).collect()
+    }
+
+    /// The running segment for a task, if its timer is on.
+    pub async fn get_running(&self, task_id: Uuid) -> Result<Option<TimeEntry>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM time_entries WHERE task_id = ? AND end_time IS NULL",
+            COLUMNS
+        ))
+        .bind(task_id.to_string())
+        .fetch_optional(&*self.pool)
+        .await?;
+        row.map(row_to_entry).transpose()
+    }
+
+    pub async fn list_running(&self) -> Result<Vec<TimeEntry>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM time_entries WHERE end_time IS NULL",
+            COLUMNS
+        ))
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_entry).collect()
+    }
+
+    /// Segments overlapping `[start, end)`, including running ones.
+    pub async fn list_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeEntry>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM time_entries
+             WHERE start_time < ? AND (end_time IS NULL OR end_time > ?)
+             ORDER BY start_time",
+            COLUMNS
+        ))
+        .bind(timestamp(end))
+        .bind(timestamp(start))
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(row_to_entry).collect()
+    }
+}
+
+fn row_to_entry(row: sqlx::sqlite::SqliteRow) -> Result<TimeEntry> {
+    let parse = |s: String| -> Result<DateTime<Utc>> {
+        Ok(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc))
+    };
+    let id: String = row.get("id");
+    let task_id: String = row.get("task_id");
+    let resource_id: Option<String> = row.get("resource_id");
+    let end_time: Option<String> = row.get("end_time");
+
+    Ok(TimeEntry {
+        id: Uuid::parse_str(&id)?,
+        task_id: Uuid::parse_str(&task_id)?,
+        resource_id: resource_id.map(|s| Uuid::parse_str(&s)).transpose()?,
+        start_time: parse(row.get("start_time"))?,
+        end_time: end_time.map(parse).transpose()?,
+        description: row.get("description"),
+        manual: row.get::<i32, _>("manual") != 0,
+        rolled_up: row.get::<i32, _>("rolled_up") != 0,
+        created_at: parse(row.get("created_at"))?,
+        updated_at: parse(row.get("updated_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::Task;
+    use crate::repository::Repository;
+    use crate::repository::database::init_test_database;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_time_entries_round_trip_and_range() {
+        let repository = Repository::new(init_test_database().await.unwrap());
+        let task = Task::new("Invoice".to_string(), String::new());
+        repository.tasks.create(&task).await.unwrap();
+
+        let now = Utc::now();
+        let logged = TimeEntry::manual(
+            task.id,
+            Some(Uuid::new_v4()),
+            now - Duration::hours(30),
+            now - Duration::hours(28),
+            "Drafting".to_string(),
+        );
+        let running = TimeEntry::start(task.id, None, String::new());
+        repository.time_entries.create(&logged).await.unwrap();
+        repository.time_entries.create(&running).await.unwrap();
+
+        let stored = repository.time_entries.get(logged.id).await.unwrap().unwrap();
+        assert_eq!(stored.end_time, logged.end_time);
+        assert!(stored.manual);
+        assert_eq!(
+            repository.time_entries.get_running(task.id).await.unwrap().map(|e| e.id),
+            Some(running.id)
+        );
+
+        let today = repository
+            .time_entries
+            .list_between(now - Duration::hours(1), now + Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(today.len(), 1);
+        assert_eq!(repository.time_entries.list_for_task(task.id).await.unwrap().len(), 2);
+
+        // Entries go with their task
+        repository.tasks.delete(task.id).await.unwrap();
+        assert!(repository.time_entries.get(logged.id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,480 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::domain::claude_code::{AgentBackendConfig, AgentBudget, AgentUsage};
+use crate::services::command_executor::{CommandExecutor, CommandOutput};
+
+/// What a backend can do beyond editing files in the worktree.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AgentCapabilities {
+    /// Reads a separate instructions file next to the prompt
+    pub instructions_file: bool,
+    /// Honours the configured model
+    pub model_selection: bool,
+    /// Prints token and cost figures that `AgentUsage::parse_output` understands
+    pub reports_usage: bool,
+    /// Opens its own pull request instead of leaving that to the service
+    pub creates_pull_requests: bool,
+}
+
+/// Everything a backend needs for one run in a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentInvocation {
+    pub work_dir: PathBuf,
+    pub prompt: String,
+    pub prompt_file: PathBuf,
+    pub instructions_file: Option<PathBuf>,
+    pub branch: String,
+    pub model: Option<String>,
+    pub env_vars: HashMap<String, String>,
+    /// Token and cost limits left for this run; backends reading output as
+    /// it streams stop the agent once its reported usage exceeds them
+    pub budget: AgentBudget,
+}
+
+/// Result of one agent run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentOutcome {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub usage: AgentUsage,
+    pub pr_url: Option<String>,
+}
+
+/// A coding agent that works on a task inside a worktree.
+///
+/// A session calls `start` once, `run` one or more times (verification
+/// feedback re-runs the agent), and `finish` once it is over, whatever the
+/// outcome.
+#[async_trait]
+pub trait AgentBackend: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn capabilities(&self) -> AgentCapabilities;
+
+    async fn start(&self, _invocation: &AgentInvocation) -> Result<()> {
+        Ok(())
+    }
+
+    async fn run(&self, invocation: &AgentInvocation) -> Result<AgentOutcome>;
+
+    async fn finish(&self, _invocation: &AgentInvocation) -> Result<()> {
+        Ok(())
+    }
+
+    fn parse_output(&self, output: CommandOutput) -> AgentOutcome {
+        AgentOutcome {
+            usage: AgentUsage::parse_output(&output.stdout),
+            pr_url: find_pr_url(&output.stdout),
+            success: output.success,
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// Build the backend selected in the configuration.
+pub fn backend_from_config(
+    config: &AgentBackendConfig,
+    executor: Arc<dyn CommandExecutor>,
+) -> Arc<dyn AgentBackend> {
+    match config {
+        AgentBackendConfig::ClaudeCli => Arc::new(ClaudeCliBackend::new(executor)),
+        AgentBackendConfig::Shell { command } => {
+            Arc::new(ShellAgentBackend::new(executor, command.clone()))
+        }
+    }
+}
+
+/// Stops an agent as soon as the usage it prints goes over `budget`.
+fn budget_watch(budget: &AgentBudget) -> impl Fn(&str) -> ControlFlow<()> + Send + Sync + use<> {
+    let budget = AgentBudget {
+        max_duration_minutes: None,
+        max_files_changed: None,
+        ..budget.clone()
+    };
+    let usage = Mutex::new(AgentUsage::default());
+    move |line| {
+        let mut usage = usage.lock().unwrap();
+        usage.update_from_line(line);
+        match budget.check(chrono::Duration::zero(), &usage) {
+            Some(_) => ControlFlow::Break(()),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+/// Last pull request URL printed by the agent, if any.
+fn find_pr_url(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .filter(|word| word.starts_with("https://") && word.contains("/pull/"))
+        .last()
+        .map(|url| url.trim_end_matches(['.', ',', ')']).to_string())
+}
+
+/// The `claude` CLI.
+pub struct ClaudeCliBackend {
+    executor: Arc<dyn CommandExecutor>,
+}
+
+impl ClaudeCliBackend {
+    pub fn new(executor: Arc<dyn CommandExecutor>) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl AgentBackend for ClaudeCliBackend {
+    fn name(&self) -> &str {
+        "claude"
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            instructions_file: true,
+            model_selection: true,
+            reports_usage: true,
+            creates_pull_requests: false,
+        }
+    }
+
+    async fn run(&self, invocation: &AgentInvocation) -> Result<AgentOutcome> {
+        let prompt_path = invocation.prompt_file.to_string_lossy().to_string();
+        let instructions_path = invocation
+            .instructions_file
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string());
+
+        let mut args = vec!["code", "--file", prompt_path.as_str()];
+        if let Some(path) = &instructions_path {
+            args.extend(["--instructions", path.as_str()]);
+        }
+        if let Some(model) = &invocation.model {
+            args.extend(["--model", model.as_str()]);
+        }
+
+        let output = self
+            .executor
+            .execute_streaming(
+                "claude",
+                &args,
+                Some(&invocation.work_dir),
+                if invocation.env_vars.is_empty() {
+                    None
+                } else {
+                    Some(invocation.env_vars.clone())
+                },
+                None,
+                &budget_watch(&invocation.budget),
+            )
+            .await
+            .context("Failed to execute Claude Code")?;
+        Ok(self.parse_output(output))
+    }
+}
+
+/// Any command run through `sh -c` in the worktree.
+///
+/// The prompt is written to the command's stdin. The prompt file, instructions
+/// file, branch and model are passed as `PLON_PROMPT_FILE`,
+/// `PLON_INSTRUCTIONS_FILE`, `PLON_BRANCH` and `PLON_MODEL`.
+pub struct ShellAgentBackend {
+    executor: Arc<dyn CommandExecutor>,
+    command: String,
+}
+
+impl ShellAgentBackend {
+    pub fn new(executor: Arc<dyn CommandExecutor>, command: String) -> Self {
+        Self { executor, command }
+    }
+}
+
+#[async_trait]
+impl AgentBackend for ShellAgentBackend {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            instructions_file: true,
+            reports_usage: true,
+            ..Default::default()
+        }
+    }
+
+    async fn run(&self, invocation: &AgentInvocation) -> Result<AgentOutcome> {
+        let mut env_vars = invocation.env_vars.clone();
+        env_vars.insert(
+            "PLON_PROMPT_FILE".to_string(),
+            invocation.prompt_file.to_string_lossy().to_string(),
+        );
+        if let Some(path) = &invocation.instructions_file {
+            env_vars.insert(
+                "PLON_INSTRUCTIONS_FILE".to_string(),
+                path.to_string_lossy().to_string(),
+            );
+        }
+        env_vars.insert("PLON_BRANCH".to_string(), invocation.branch.clone());
+        if let Some(model) = &invocation.model {
+            env_vars.insert("PLON_MODEL".to_string(), model.clone());
+        }
+
+        let output = self
+            .executor
+            .execute_streaming(
+                "sh",
+                &["-c", &self.command],
+                Some(&invocation.work_dir),
+                Some(env_vars),
+                Some(&invocation.prompt),
+                &budget_watch(&invocation.budget),
+            )
+            .await
+            .with_context(|| format!("Failed to run agent command: {}", self.command))?;
+        Ok(self.parse_output(output))
+    }
+}
+
+/// One scripted response of a `ScriptedAgentBackend`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptedRun {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// Files written into the worktree, relative path and contents
+    pub files: Vec<(String, String)>,
+}
+
+impl ScriptedRun {
+    pub fn success(stdout: &str) -> Self {
+        Self {
+            success: true,
+            stdout: stdout.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn failure(stderr: &str) -> Self {
+        Self {
+            success: false,
+            stderr: stderr.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_file(mut self, path: &str, contents: &str) -> Self {
+        self.files.push((path.to_string(), contents.to_string()));
+        self
+    }
+}
+
+/// Deterministic backend for tests: replays scripted runs in order and
+/// records every invocation. Once the script is exhausted it keeps
+/// succeeding with no output.
+#[derive(Clone, Default)]
+pub struct ScriptedAgentBackend {
+    script: Arc<Mutex<VecDeque<ScriptedRun>>>,
+    invocations: Arc<Mutex<Vec<AgentInvocation>>>,
+    finished: Arc<Mutex<usize>>,
+}
+
+impl ScriptedAgentBackend {
+    pub fn new(script: Vec<ScriptedRun>) -> Self {
+        Self {
+            script: Arc::new(Mutex::new(script.into())),
+            ..Default::default()
+        }
+    }
+
+    pub fn invocations(&self) -> Vec<AgentInvocation> {
+        self.invocations.lock().unwrap().clone()
+    }
+
+    /// How many sessions have called `finish`.
+    pub fn finished_sessions(&self) -> usize {
+        *self.finished.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl AgentBackend for ScriptedAgentBackend {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            reports_usage: true,
+            ..Default::default()
+        }
+    }
+
+    async fn run(&self, invocation: &AgentInvocation) -> Result<AgentOutcome> {
+        self.invocations.lock().unwrap().push(invocation.clone());
+        let run = self
+            .script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| ScriptedRun::success(""));
+
+        for (path, contents) in &run.files {
+            let path = invocation.work_dir.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+
+        Ok(self.parse_output(CommandOutput {
+            stdout: run.stdout,
+            stderr: run.stderr,
+            success: run.success,
+            exit_code: Some(if run.success { 0 } else { 1 }),
+        }))
+    }
+
+    async fn finish(&self, _invocation: &AgentInvocation) -> Result<()> {
+        *self.finished.lock().unwrap() += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::command_executor::mock::MockCommandExecutor;
+    use tempfile::TempDir;
+
+    fn invocation(work_dir: PathBuf) -> AgentInvocation {
+        AgentInvocation {
+            prompt_file: work_dir.join("claude_task.md"),
+            instructions_file: Some(work_dir.join("claude_instructions.md")),
+            work_dir,
+            prompt: "Fix the bug".to_string(),
+            branch: "claude/fix".to_string(),
+            model: Some("claude-sonnet".to_string()),
+            env_vars: HashMap::new(),
+            budget: AgentBudget::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claude_cli_backend_parses_output() {
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.add_response(
+            "claude",
+            vec!["code", "--model", "claude-sonnet"],
+            "Done, opened https://github.com/o/r/pull/7.\n{\"total_cost_usd\": 0.25}",
+            "",
+            true,
+        );
+
+        let backend = ClaudeCliBackend::new(Arc::new(mock.clone()));
+        let outcome = backend.run(&invocation(PathBuf::from("/tmp"))).await.unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.pr_url.as_deref(), Some("https://github.com/o/r/pull/7"));
+        assert!((outcome.usage.cost_usd - 0.25).abs() < f64::EPSILON);
+        assert!(mock.assert_called_with("claude", &["--instructions", "claude_instructions.md"]));
+    }
+
+    #[tokio::test]
+    async fn test_shell_backend_sends_prompt_on_stdin() {
+        let mock = MockCommandExecutor::new().with_delay(0);
+        let backend = ShellAgentBackend::new(Arc::new(mock.clone()), "aider --yes".to_string());
+        backend.run(&invocation(PathBuf::from("/tmp"))).await.unwrap();
+
+        let call = &mock.get_call_history()[0];
+        assert_eq!(call.program, "sh");
+        assert_eq!(call.args, vec!["-c", "aider --yes"]);
+        assert_eq!(call.stdin.as_deref(), Some("Fix the bug"));
+        let env = call.env_vars.as_ref().unwrap();
+        assert_eq!(env["PLON_BRANCH"], "claude/fix");
+        assert!(env["PLON_PROMPT_FILE"].ends_with("claude_task.md"));
+    }
+
+    #[tokio::test]
+    async fn test_system_shell_backend_reads_stdin() {
+        let temp = TempDir::new().unwrap();
+        let backend = ShellAgentBackend::new(
+            Arc::new(crate::services::command_executor::SystemCommandExecutor),
+            "cat > received.md && echo \"$PLON_BRANCH\"".to_string(),
+        );
+        let outcome = backend
+            .run(&invocation(temp.path().to_path_buf()))
+            .await
+            .unwrap();
+
+        assert!(outcome.success, "{}", outcome.stderr);
+        assert_eq!(outcome.stdout.trim(), "claude/fix");
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("received.md")).unwrap(),
+            "Fix the bug"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_backend_is_stopped_once_over_budget() {
+        let temp = TempDir::new().unwrap();
+        let backend = ShellAgentBackend::new(
+            Arc::new(crate::services::command_executor::SystemCommandExecutor),
+            "echo 'Total cost: $2.50'; sleep 10; touch finished".to_string(),
+        );
+        let mut invocation = invocation(temp.path().to_path_buf());
+        invocation.budget.max_cost_usd = Some(1.0);
+
+        let started = std::time::Instant::now();
+        let outcome = backend.run(&invocation).await.unwrap();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(!outcome.success);
+        assert!((outcome.usage.cost_usd - 2.5).abs() < f64::EPSILON);
+        assert!(!temp.path().join("finished").exists());
+    }
+
+    #[tokio::test]
+    async fn test_scripted_backend_replays_in_order() {
+        let temp = TempDir::new().unwrap();
+        let backend = ScriptedAgentBackend::new(vec![
+            ScriptedRun::failure("compile error"),
+            ScriptedRun::success("fixed").with_file("src/lib.rs", "pub fn fixed() {}"),
+        ]);
+        let invocation = invocation(temp.path().to_path_buf());
+
+        let first = backend.run(&invocation).await.unwrap();
+        let second = backend.run(&invocation).await.unwrap();
+        let third = backend.run(&invocation).await.unwrap();
+
+        assert!(!first.success);
+        assert_eq!(first.stderr, "compile error");
+        assert!(second.success);
+        assert!(temp.path().join("src/lib.rs").exists());
+        assert!(third.success);
+        assert_eq!(backend.invocations().len(), 3);
+    }
+
+    #[test]
+    fn test_backend_from_config() {
+        let executor: Arc<dyn CommandExecutor> = Arc::new(MockCommandExecutor::new());
+        assert_eq!(
+            backend_from_config(&AgentBackendConfig::ClaudeCli, executor.clone()).name(),
+            "claude"
+        );
+        let shell = backend_from_config(
+            &AgentBackendConfig::Shell {
+                command: "codex exec -".to_string(),
+            },
+            executor,
+        );
+        assert_eq!(shell.name(), "codex exec -");
+        assert!(!shell.capabilities().model_selection);
+    }
+}
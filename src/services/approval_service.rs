@@ -0,0 +1,202 @@
+use crate::domain::approval::{ApprovalEvent, ApprovalRequest, ApprovalStatus};
+use crate::domain::notification::Notification;
+use crate::repository::Repository;
+use crate::services::task_service::TaskService;
+use anyhow::{Context, Result, anyhow};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Decisions on workflow transitions awaiting approval. Requests are opened
+/// by `TaskService::transition`.
+#[derive(Clone)]
+pub struct ApprovalService {
+    repository: Arc<Repository>,
+    tasks: TaskService,
+}
+
+impl ApprovalService {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self {
+            tasks: TaskService::new(repository.clone()),
+            repository,
+        }
+    }
+
+    /// Pending requests waiting on `resource_id`'s decision, oldest first.
+    pub async fn inbox(&self, resource_id: Uuid) -> Result<Vec<ApprovalRequest>> {
+        let mut waiting = Vec::new();
+        for request in self.repository.approvals.list_pending().await? {
+            let history = self.repository.approvals.list_events(request.id).await?;
+            if request.awaits(resource_id, &history) {
+                waiting.push(request);
+            }
+        }
+        Ok(waiting)
+    }
+
+    pub async fn requests_for_task(&self, task_id: Uuid) -> Result<Vec<ApprovalRequest>> {
+        self.repository.approvals.list_for_task(task_id).await
+    }
+
+    /// Who requested, approved, rejected or closed a request, and when.
+    pub async fn history(&self, request_id: Uuid) -> Result<Vec<ApprovalEvent>> {
+        self.repository.approvals.list_events(request_id).await
+    }
+
+    /// Record `resource_id`'s decision. Once the quorum is met the task is
+    /// moved on behalf of whoever asked; if something else still holds it
+    /// back, the approval stands for the next attempt.
+    pub async fn decide(
+        &self,
+        request_id: Uuid,
+        resource_id: Uuid,
+        approve: bool,
+        comment: String,
+    ) -> Result<ApprovalRequest> {
+        let mut request = self
+            .repository
+            .approvals
+            .get(request_id)
+            .await?
+            .ok_or_else(|| anyhow!("Approval request not found"))?;
+        let history = self.repository.approvals.list_events(request_id).await?;
+        let event = request.decide(resource_id, approve, comment.clone(), &history)?;
+        self.repository.approvals.add_event(&event).await?;
+        self.repository.approvals.update(&request).await?;
+
+        let title = match self.repository.tasks.get(request.task_id).await? {
+            Some(task) => task.title,
+            None => request.task_id.to_string(),
+        };
+        match request.status {
+            ApprovalStatus::Rejected => {
+                let mut message = format!("Moving '{}' to {} was rejected", title, request.to_state);
+                if !comment.is_empty() {
+                    message.push_str(&format!(": {}", comment));
+                }
+                self.notify_requester(&request, message).await?;
+            }
+            ApprovalStatus::Approved => {
+                let message = format!("Moving '{}' to {} was approved", title, request.to_state);
+                self.notify_requester(&request, message).await?;
+                self.tasks
+                    .transition(request.task_id, &request.to_state, request.requested_by)
+                    .await
+                    .context("Approved, but the task could not move yet")?;
+            }
+            _ => {}
+        }
+
+        self.repository
+            .approvals
+            .get(request_id)
+            .await?
+            .ok_or_else(|| anyhow!("Approval request not found"))
+    }
+
+    async fn notify_requester(&self, request: &ApprovalRequest, message: String) -> Result<()> {
+        if request.requested_by.is_none() {
+            return Ok(());
+        }
+        let notification = Notification::new(request.requested_by, Some(request.task_id), message);
+        self.repository.notifications.create(&notification).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::approval::ApprovalAction;
+    use crate::domain::resource::Resource;
+    use crate::domain::task::{Task, TaskStatus};
+    use crate::domain::task_config::{TaskConfiguration, TransitionCondition};
+    use crate::repository::database::init_test_database;
+    use crate::services::task_service::TransitionOutcome;
+
+    #[tokio::test]
+    async fn test_transition_waits_for_quorum() {
+        let repository = Arc::new(Repository::new(init_test_database().await.unwrap()));
+        let tasks = TaskService::new(repository.clone());
+        let service = ApprovalService::new(repository.clone());
+
+        let dev = Resource::new("Dev".to_string(), "Developer".to_string(), 40.0);
+        let lead = Resource::new("Lead".to_string(), "Lead".to_string(), 40.0);
+        let architect = Resource::new("Architect".to_string(), "lead".to_string(), 40.0);
+        for resource in [&dev, &lead, &architect] {
+            repository.resources.create(resource).await.unwrap();
+        }
+
+        let mut config = TaskConfiguration::new("Gated".to_string());
+        let complete = config
+            .state_machine
+            .transitions
+            .iter_mut()
+            .find(|t| t.to_state == "done")
+            .unwrap();
+        complete.conditions.push(TransitionCondition::RequireApproval {
+            role: "Lead".to_string(),
+            quorum: 2,
+        });
+        repository.task_configs.create(&config).await.unwrap();
+
+        let mut task = Task::new("Ship it".to_string(), String::new());
+        task.configuration_id = Some(config.id);
+        let task = tasks.create(task).await.unwrap().task;
+        tasks.transition(task.id, "in_progress", Some(dev.id)).await.unwrap();
+
+        let requests = match tasks.transition(task.id, "done", Some(dev.id)).await.unwrap() {
+            TransitionOutcome::AwaitingApproval(requests) => requests,
+            TransitionOutcome::Moved(_) => panic!("moved without approval"),
+        };
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.approvers.len(), 2);
+        // Asking again doesn't open a second request
+        assert!(tasks.transition(task.id, "done", Some(dev.id)).await.unwrap().moved().is_none());
+        assert_eq!(service.requests_for_task(task.id).await.unwrap().len(), 1);
+
+        assert_eq!(service.inbox(lead.id).await.unwrap().len(), 1);
+        assert!(service.inbox(dev.id).await.unwrap().is_empty());
+        assert!(service.decide(request.id, dev.id, true, String::new()).await.is_err());
+
+        let decided = service
+            .decide(request.id, lead.id, true, "Looks good".to_string())
+            .await
+            .unwrap();
+        assert_eq!(decided.status, ApprovalStatus::Pending);
+        assert!(service.inbox(lead.id).await.unwrap().is_empty());
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.workflow_state.as_deref(), Some("in_progress"));
+
+        let decided = service
+            .decide(request.id, architect.id, true, String::new())
+            .await
+            .unwrap();
+        assert_eq!(decided.status, ApprovalStatus::Completed);
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::Done);
+
+        let trail: Vec<ApprovalAction> = service
+            .history(request.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        assert_eq!(
+            trail,
+            [
+                ApprovalAction::Requested,
+                ApprovalAction::Approved,
+                ApprovalAction::Approved,
+                ApprovalAction::Completed
+            ]
+        );
+        let inbox = repository
+            .notifications
+            .list_for_resource(dev.id, false)
+            .await
+            .unwrap();
+        assert!(inbox.iter().any(|n| n.message.contains("was approved")));
+    }
+}
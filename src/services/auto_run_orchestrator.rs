@@ -3,12 +3,19 @@ use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
-use crate::domain::claude_code::{ClaudeCodeSession, SessionStatus};
+use crate::domain::claude_code::{
+    AgentBudget, AgentUsage, BUDGET_EXCEEDED_PREFIX, BudgetExceeded, ClaudeCodeSession,
+    SessionStatus,
+};
 use crate::repository::Repository;
 use crate::services::claude_code_service::ClaudeCodeService;
 use crate::services::dependency_service::DependencyService;
+use crate::services::merge_queue_service::{
+    MergeOutcome, MergeQueueEntry, MergeQueueService, MergeResult,
+};
 use crate::services::task_service::TaskService;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +35,14 @@ pub struct AutoRunConfig {
     pub require_tests_pass: bool,
     pub retry_on_failure: bool,
     pub max_retries: usize,
+    /// Delay before the first retry of a failed task; doubles on each further retry
+    pub retry_backoff_secs: u64,
+    /// Upper bound on the delay between retries
+    pub max_retry_backoff_secs: u64,
+    /// Limits applied to each agent session
+    pub session_budget: AgentBudget,
+    /// Limits applied to the whole run, summed over all sessions
+    pub run_budget: AgentBudget,
 }
 
 impl Default for AutoRunConfig {
@@ -38,10 +53,26 @@ impl Default for AutoRunConfig {
             require_tests_pass: true,
             retry_on_failure: true,
             max_retries: 2,
+            retry_backoff_secs: 30,
+            max_retry_backoff_secs: 600,
+            session_budget: AgentBudget::default(),
+            run_budget: AgentBudget::default(),
         }
     }
 }
 
+impl AutoRunConfig {
+    /// How long to wait before retry number `retry` (starting at 1).
+    pub fn retry_delay(&self, retry: usize) -> chrono::Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1) as u32);
+        let secs = self
+            .retry_backoff_secs
+            .saturating_mul(factor)
+            .min(self.max_retry_backoff_secs);
+        chrono::Duration::seconds(secs as i64)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TaskExecution {
     pub task_id: Uuid,
@@ -76,7 +107,6 @@ pub struct AutoRunProgress {
 
 pub struct AutoRunOrchestrator {
     repository: Arc<Repository>,
-    #[allow(dead_code)]
     claude_service: Arc<ClaudeCodeService>,
     dependency_service: Arc<DependencyService>,
     task_service: Arc<TaskService>,
@@ -86,6 +116,15 @@ pub struct AutoRunOrchestrator {
     pub executions: Arc<RwLock<HashMap<Uuid, TaskExecution>>>,
     execution_queue: Arc<Mutex<Vec<Uuid>>>,
     active_sessions: Arc<RwLock<HashSet<Uuid>>>,
+    session_handles: Arc<Mutex<HashMap<Uuid, JoinHandle<()>>>>,
+    /// Sessions simulated for lack of a Claude Code configuration; they
+    /// have no worktree to verify
+    simulated_sessions: Arc<RwLock<HashSet<Uuid>>>,
+    run_started_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// Queued retries wait here until their backoff has elapsed
+    retry_not_before: Arc<RwLock<HashMap<Uuid, DateTime<Utc>>>>,
+    /// PRs of finished sessions waiting for the merge queue
+    merge_queue: Arc<Mutex<Vec<MergeQueueEntry>>>,
 }
 
 impl AutoRunOrchestrator {
@@ -105,12 +144,18 @@ impl AutoRunOrchestrator {
             executions: Arc::new(RwLock::new(HashMap::new())),
             execution_queue: Arc::new(Mutex::new(Vec::new())),
             active_sessions: Arc::new(RwLock::new(HashSet::new())),
+            session_handles: Arc::new(Mutex::new(HashMap::new())),
+            simulated_sessions: Arc::new(RwLock::new(HashSet::new())),
+            run_started_at: Arc::new(RwLock::new(None)),
+            retry_not_before: Arc::new(RwLock::new(HashMap::new())),
+            merge_queue: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
     pub async fn start_auto_run(&self, task_ids: Vec<Uuid>) -> Result<()> {
         // Update status
         *self.status.write().await = AutoRunStatus::Planning;
+        *self.run_started_at.write().await = Some(Utc::now());
 
         // Build execution plan
         let execution_plan = self.build_execution_plan(task_ids).await?;
@@ -246,9 +291,15 @@ impl AutoRunOrchestrator {
                     // Process queue
                     self.process_queue().await?;
 
+                    // Cancel anything that has run over budget
+                    self.enforce_budgets().await?;
+
                     // Check for completed sessions
                     self.check_completed_sessions().await?;
 
+                    // Land the PRs they opened
+                    self.process_merge_queue().await?;
+
                     // Check if all tasks are done
                     if self.all_tasks_complete().await? {
                         *self.status.write().await = AutoRunStatus::Completed;
@@ -284,10 +335,23 @@ impl AutoRunOrchestrator {
         let slots_available = config.max_parallel_instances - active_count;
         drop(config);
 
+        // Retries still backing off stay in the queue
+        let now = Utc::now();
+        let mut retry_not_before = self.retry_not_before.write().await;
         let mut queue = self.execution_queue.lock().await;
-        let drain_end = slots_available.min(queue.len());
-        let tasks_to_start: Vec<Uuid> = queue.drain(..drain_end).collect();
+        let mut tasks_to_start = Vec::new();
+        queue.retain(|task_id| {
+            let ready = retry_not_before.get(task_id).is_none_or(|at| *at <= now);
+            if ready && tasks_to_start.len() < slots_available {
+                retry_not_before.remove(task_id);
+                tasks_to_start.push(*task_id);
+                false
+            } else {
+                true
+            }
+        });
         drop(queue);
+        drop(retry_not_before);
 
         for task_id in tasks_to_start {
             self.start_task_execution(task_id).await?;
@@ -304,11 +368,63 @@ impl AutoRunOrchestrator {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
 
-        // Create Claude Code session
-        let session = ClaudeCodeSession::new(task.id);
+        // A retry continues the previous session's branch and carries its failure forward
+        let previous_session_id = self
+            .executions
+            .read()
+            .await
+            .get(&task_id)
+            .filter(|exec| exec.retry_count > 0)
+            .and_then(|exec| exec.session_id);
+        let previous = match previous_session_id {
+            Some(id) => self.repository.claude_code.get_session(id).await?,
+            None => None,
+        };
 
-        // Save session to repository
-        self.repository.claude_code.create_session(&session).await?;
+        // Once Claude Code is configured, sessions run the agent within the
+        // run's session budget; without a configuration the run is simulated
+        let claude_config = self.repository.claude_code.get_config().await?;
+        let simulated = claude_config.is_none();
+        let session = match claude_config {
+            Some(mut config) => {
+                config.limit_sessions_to(&self.config.read().await.session_budget);
+                let template = self
+                    .repository
+                    .claude_code
+                    .get_default_template()
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("No default prompt template"))?;
+                match &previous {
+                    Some(previous) => {
+                        self.claude_service
+                            .retry_session(previous, &task, &config, &template)
+                            .await?
+                    }
+                    None => {
+                        self.claude_service
+                            .launch_claude_code(&task, &config, &template)
+                            .await?
+                    }
+                }
+            }
+            None => {
+                let session = match &previous {
+                    Some(previous) => {
+                        let mut session = ClaudeCodeSession::retry_of(previous);
+                        session.append_log(&format!(
+                            "Retrying session {} (attempt {})\n{}",
+                            previous.id,
+                            session.attempt,
+                            previous.retry_feedback("")
+                        ));
+                        session
+                    }
+                    None => ClaudeCodeSession::new(task.id),
+                };
+                self.repository.claude_code.create_session(&session).await?;
+                session
+            }
+        };
 
         // Update execution
         let mut executions = self.executions.write().await;
@@ -321,10 +437,14 @@ impl AutoRunOrchestrator {
 
         // Add to active sessions
         self.active_sessions.write().await.insert(session.id);
+        if !simulated {
+            return Ok(());
+        }
+        self.simulated_sessions.write().await.insert(session.id);
 
         // Simulate Claude Code starting the task
         // In real implementation, this would launch actual Claude Code instance
-        tokio::spawn({
+        let handle = tokio::spawn({
             let repository = self.repository.clone();
             let session_id = session.id;
             async move {
@@ -344,10 +464,110 @@ impl AutoRunOrchestrator {
                 }
             }
         });
+        self.session_handles.lock().await.insert(session.id, handle);
+
+        Ok(())
+    }
+
+    /// Cancel sessions that exceed the per-session budget, and stop the whole
+    /// run once the run budget is exhausted.
+    async fn enforce_budgets(&self) -> Result<()> {
+        let config = self.config.read().await.clone();
+        let active_sessions = self.active_sessions.read().await.clone();
+
+        for session_id in active_sessions {
+            if let Some(session) = self.repository.claude_code.get_session(session_id).await?
+                && !session.status.is_terminal()
+                && let Some(exceeded) = session.check_budget(&config.session_budget)
+            {
+                self.cancel_over_budget(session, &exceeded).await?;
+            }
+        }
+
+        if config.run_budget.is_unlimited() {
+            return Ok(());
+        }
+        let Some(started_at) = *self.run_started_at.read().await else {
+            return Ok(());
+        };
+
+        let usage = self.get_run_usage().await?;
+        if let Some(exceeded) = config.run_budget.check(Utc::now() - started_at, &usage) {
+            let active_sessions = self.active_sessions.read().await.clone();
+            for session_id in active_sessions {
+                if let Some(session) = self.repository.claude_code.get_session(session_id).await? {
+                    self.cancel_over_budget(session, &exceeded).await?;
+                }
+            }
+
+            let reason = format!("{}: {}", BUDGET_EXCEEDED_PREFIX, exceeded);
+            let mut executions = self.executions.write().await;
+            for exec in executions.values_mut() {
+                if exec.status == TaskExecutionStatus::Queued {
+                    exec.status = TaskExecutionStatus::Failed;
+                    exec.error_message = Some(reason.clone());
+                    exec.completed_at = Some(Utc::now());
+                }
+            }
+            drop(executions);
+            self.execution_queue.lock().await.clear();
+
+            *self.status.write().await = AutoRunStatus::Failed(reason);
+        }
+
+        Ok(())
+    }
+
+    async fn cancel_over_budget(
+        &self,
+        mut session: ClaudeCodeSession,
+        exceeded: &BudgetExceeded,
+    ) -> Result<()> {
+        // Stops the agent process of a real session as well as a simulated one
+        self.claude_service.abort_session(session.id);
+        if let Some(handle) = self.session_handles.lock().await.remove(&session.id) {
+            handle.abort();
+        }
+        self.active_sessions.write().await.remove(&session.id);
+
+        session.fail_over_budget(exceeded);
+        self.repository.claude_code.update_session(&session).await?;
+
+        // Retrying would only spend more, so budget failures are final
+        let mut executions = self.executions.write().await;
+        if let Some(exec) = executions.get_mut(&session.task_id) {
+            exec.status = TaskExecutionStatus::Failed;
+            exec.error_message = session.error_message.clone();
+            exec.completed_at = Some(Utc::now());
+        }
 
         Ok(())
     }
 
+    /// Spend across every session started since the current run began.
+    pub async fn get_run_usage(&self) -> Result<AgentUsage> {
+        let mut usage = AgentUsage::default();
+        let Some(started_at) = *self.run_started_at.read().await else {
+            return Ok(usage);
+        };
+
+        let task_ids: Vec<Uuid> = self.executions.read().await.keys().copied().collect();
+        for task_id in task_ids {
+            for session in self
+                .repository
+                .claude_code
+                .get_sessions_by_task(task_id)
+                .await?
+            {
+                if session.started_at >= started_at {
+                    usage.add(&session.usage);
+                }
+            }
+        }
+
+        Ok(usage)
+    }
+
     async fn check_completed_sessions(&self) -> Result<()> {
         let active_sessions = self.active_sessions.read().await.clone();
 
@@ -370,20 +590,49 @@ impl AutoRunOrchestrator {
         Ok(())
     }
 
-    async fn handle_completed_session(&self, session: ClaudeCodeSession) -> Result<()> {
+    async fn handle_completed_session(&self, mut session: ClaudeCodeSession) -> Result<()> {
+        // With require_tests_pass, a session only counts once the configured
+        // verification pipeline has passed in its worktree
+        let simulated = self.simulated_sessions.write().await.remove(&session.id);
+        if self.config.read().await.require_tests_pass && !simulated {
+            let pipeline_configured = self
+                .repository
+                .claude_code
+                .get_config()
+                .await?
+                .is_some_and(|c| !c.verification_pipeline.is_empty());
+            let verified = session.verification.as_ref().is_some_and(|r| r.passed());
+            if pipeline_configured && !verified {
+                session.set_error("Verification did not pass".to_string());
+                self.repository.claude_code.update_session(&session).await?;
+                return self.handle_failed_session(session).await;
+            }
+        }
+
         // Remove from active sessions
         self.active_sessions.write().await.remove(&session.id);
+        self.session_handles.lock().await.remove(&session.id);
+
+        // With auto-merge, a real session's PR goes through the merge queue
+        let auto_merge = self.config.read().await.auto_merge_enabled;
+        let merge_entry =
+            MergeQueueEntry::from_session(&session).filter(|_| auto_merge && !simulated);
 
         // Update execution status
         let mut executions = self.executions.write().await;
         if let Some(exec) = executions.get_mut(&session.task_id) {
-            exec.status = TaskExecutionStatus::PendingReview;
+            exec.status = if merge_entry.is_some() {
+                TaskExecutionStatus::Merging
+            } else {
+                TaskExecutionStatus::PendingReview
+            };
             exec.pr_url = session.pr_url.clone();
         }
         drop(executions);
 
-        // Start PR review process
-        if let Some(pr_url) = session.pr_url {
+        if let Some(entry) = merge_entry {
+            self.merge_queue.lock().await.push(entry);
+        } else if let Some(pr_url) = session.pr_url {
             self.start_pr_review(session.task_id, pr_url).await?;
         }
 
@@ -393,22 +642,26 @@ impl AutoRunOrchestrator {
     async fn handle_failed_session(&self, session: ClaudeCodeSession) -> Result<()> {
         // Remove from active sessions
         self.active_sessions.write().await.remove(&session.id);
+        self.session_handles.lock().await.remove(&session.id);
 
-        let config = self.config.read().await;
-        let should_retry = config.retry_on_failure;
-        let max_retries = config.max_retries;
-        drop(config);
+        let config = self.config.read().await.clone();
+        let should_retry = config.retry_on_failure && !session.is_budget_failure();
 
         let mut executions = self.executions.write().await;
         if let Some(exec) = executions.get_mut(&session.task_id) {
             exec.error_message = session.error_message.clone();
 
-            if should_retry && exec.retry_count < max_retries {
-                // Retry the task
+            if should_retry && exec.retry_count < config.max_retries {
+                // Retry the task once the backoff has elapsed
                 exec.retry_count += 1;
                 exec.status = TaskExecutionStatus::Queued;
+                let retry_at = Utc::now() + config.retry_delay(exec.retry_count);
                 drop(executions);
 
+                self.retry_not_before
+                    .write()
+                    .await
+                    .insert(session.task_id, retry_at);
                 let mut queue = self.execution_queue.lock().await;
                 queue.push(session.task_id);
             } else {
@@ -421,10 +674,10 @@ impl AutoRunOrchestrator {
         Ok(())
     }
 
+    /// Simulate reviewing and merging the PR of a session that doesn't go
+    /// through the merge queue: simulated sessions, and runs without
+    /// auto-merge.
     async fn start_pr_review(&self, task_id: Uuid, _pr_url: String) -> Result<()> {
-        // This will be implemented with the PR review service
-        // For now, simulate auto-merge after review
-
         tokio::spawn({
             let executions = self.executions.clone();
             let queue = self.execution_queue.clone();
@@ -433,55 +686,179 @@ impl AutoRunOrchestrator {
             async move {
                 // Simulate PR review time (shorter for tests)
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                Self::complete_execution(&executions, &queue, &dependency_service, task_id).await;
+            }
+        });
 
-                // Update execution status
-                let mut execs = executions.write().await;
-                if let Some(exec) = execs.get_mut(&task_id) {
-                    exec.status = TaskExecutionStatus::Completed;
-                    exec.completed_at = Some(Utc::now());
-                }
-                drop(execs);
-
-                // Find newly unblocked tasks
-                if let Ok(dependents) = dependency_service.get_dependents(task_id).await {
-                    let execs = executions.read().await;
-                    let mut newly_unblocked = Vec::new();
-
-                    for dependent_id in dependents {
-                        if let Some(exec) = execs.get(&dependent_id)
-                            && exec.status == TaskExecutionStatus::Queued
-                        {
-                            // Check if all other dependencies are complete
-                            if let Ok(deps) =
-                                dependency_service.get_dependencies(dependent_id).await
-                            {
-                                let all_complete = deps.iter().all(|dep_id| {
-                                    execs
-                                        .get(dep_id)
-                                        .map(|e| e.status == TaskExecutionStatus::Completed)
-                                        .unwrap_or(true)
-                                });
-
-                                if all_complete {
-                                    newly_unblocked.push(dependent_id);
-                                }
-                            }
+        Ok(())
+    }
+
+    /// Mark a task's execution completed and queue the dependents it was
+    /// the last prerequisite of.
+    async fn complete_execution(
+        executions: &RwLock<HashMap<Uuid, TaskExecution>>,
+        queue: &Mutex<Vec<Uuid>>,
+        dependency_service: &DependencyService,
+        task_id: Uuid,
+    ) {
+        // Update execution status
+        let mut execs = executions.write().await;
+        if let Some(exec) = execs.get_mut(&task_id) {
+            exec.status = TaskExecutionStatus::Completed;
+            exec.completed_at = Some(Utc::now());
+        }
+        drop(execs);
+
+        // Find newly unblocked tasks
+        if let Ok(dependents) = dependency_service.get_dependents(task_id).await {
+            let execs = executions.read().await;
+            let mut newly_unblocked = Vec::new();
+
+            for dependent_id in dependents {
+                if let Some(exec) = execs.get(&dependent_id)
+                    && exec.status == TaskExecutionStatus::Queued
+                {
+                    // Check if all other dependencies are complete
+                    if let Ok(deps) = dependency_service.get_dependencies(dependent_id).await {
+                        let all_complete = deps.iter().all(|dep_id| {
+                            execs
+                                .get(dep_id)
+                                .map(|e| e.status == TaskExecutionStatus::Completed)
+                                .unwrap_or(true)
+                        });
+
+                        if all_complete {
+                            newly_unblocked.push(dependent_id);
                         }
                     }
-                    drop(execs);
+                }
+            }
+            drop(execs);
 
-                    // Add newly unblocked tasks to queue
-                    if !newly_unblocked.is_empty() {
-                        let mut q = queue.lock().await;
-                        q.extend(newly_unblocked);
-                    }
+            // Add newly unblocked tasks to queue
+            if !newly_unblocked.is_empty() {
+                let mut q = queue.lock().await;
+                q.extend(newly_unblocked);
+            }
+        }
+    }
+
+    /// Land the PRs waiting in the merge queue. Merged tasks complete and
+    /// unblock their dependents; PRs the queue hands back to an agent run
+    /// again as a retry of their task, and the rest fail.
+    async fn process_merge_queue(&self) -> Result<()> {
+        let entries = std::mem::take(&mut *self.merge_queue.lock().await);
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let results = match self.run_merge_queue(entries.clone()).await {
+            Ok(results) => results,
+            // Only fails before any PR is landed
+            Err(e) => {
+                let error = format!("Merge queue failed: {}", e);
+                for entry in entries {
+                    self.fail_execution(entry.task_id, error.clone()).await;
                 }
+                return Ok(());
             }
-        });
+        };
+
+        for result in results {
+            let task_id = result.entry.task_id;
+            let error = match result.outcome {
+                MergeOutcome::Merged => {
+                    Self::complete_execution(
+                        &self.executions,
+                        &self.execution_queue,
+                        &self.dependency_service,
+                        task_id,
+                    )
+                    .await;
+                    continue;
+                }
+                MergeOutcome::Conflicted {
+                    retry_session_id: Some(retry_id),
+                    ..
+                }
+                | MergeOutcome::VerificationFailed {
+                    retry_session_id: Some(retry_id),
+                    ..
+                } => {
+                    let mut executions = self.executions.write().await;
+                    if let Some(exec) = executions.get_mut(&task_id) {
+                        exec.session_id = Some(retry_id);
+                        exec.status = TaskExecutionStatus::Running;
+                        exec.retry_count += 1;
+                    }
+                    drop(executions);
+                    self.active_sessions.write().await.insert(retry_id);
+                    continue;
+                }
+                MergeOutcome::Conflicted { files, .. } => {
+                    format!("Merge conflict in {}", files.join(", "))
+                }
+                MergeOutcome::VerificationFailed { report, .. } => {
+                    format!("Verification failed after rebasing:\n{}", report.summary())
+                }
+                MergeOutcome::Failed(error) => error,
+            };
+            self.fail_execution(task_id, error).await;
+        }
 
         Ok(())
     }
 
+    async fn run_merge_queue(&self, entries: Vec<MergeQueueEntry>) -> Result<Vec<MergeResult>> {
+        let mut config = self
+            .repository
+            .claude_code
+            .get_config()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Claude Code is not configured"))?;
+        config.limit_sessions_to(&self.config.read().await.session_budget);
+        let template = self
+            .repository
+            .claude_code
+            .get_default_template()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No default prompt template"))?;
+        let queue = MergeQueueService::new(
+            self.repository.clone(),
+            self.claude_service.command_executor(),
+            self.claude_service.hosting_client(&config),
+        );
+
+        // PRs go back to an agent within the run's retry limits, like failed
+        // sessions do
+        let run_config = self.config.read().await.clone();
+        let retries: HashMap<Uuid, usize> = self
+            .executions
+            .read()
+            .await
+            .iter()
+            .map(|(task_id, exec)| (*task_id, exec.retry_count))
+            .collect();
+        let can_retry = |entry: &MergeQueueEntry| {
+            run_config.retry_on_failure
+                && retries
+                    .get(&entry.task_id)
+                    .is_some_and(|count| *count < run_config.max_retries)
+        };
+        queue
+            .process(entries, &self.claude_service, &config, &template, can_retry)
+            .await
+    }
+
+    async fn fail_execution(&self, task_id: Uuid, error: String) {
+        let mut executions = self.executions.write().await;
+        if let Some(exec) = executions.get_mut(&task_id) {
+            exec.status = TaskExecutionStatus::Failed;
+            exec.error_message = Some(error);
+            exec.completed_at = Some(Utc::now());
+        }
+    }
+
     async fn all_tasks_complete(&self) -> Result<bool> {
         let executions = self.executions.read().await;
         Ok(executions.values().all(|exec| {
@@ -504,9 +881,22 @@ impl AutoRunOrchestrator {
         *self.status.write().await = AutoRunStatus::Idle;
 
         // Clear all state
+        for (_, handle) in self.session_handles.lock().await.drain() {
+            handle.abort();
+        }
+        let active_sessions = self.active_sessions.read().await.clone();
+        for session_id in active_sessions {
+            if self.claude_service.abort_session(session_id) {
+                self.claude_service.cancel_session(session_id).await?;
+            }
+        }
         self.executions.write().await.clear();
         self.execution_queue.lock().await.clear();
         self.active_sessions.write().await.clear();
+        self.simulated_sessions.write().await.clear();
+        self.retry_not_before.write().await.clear();
+        self.merge_queue.lock().await.clear();
+        *self.run_started_at.write().await = None;
 
         Ok(())
     }
@@ -562,7 +952,7 @@ impl AutoRunOrchestrator {
     pub async fn get_execution_details(&self) -> Vec<TaskExecution> {
         self.executions.read().await.values().cloned().collect()
     }
-    
+
     #[cfg(test)]
     pub async fn get_active_sessions_count(&self) -> usize {
         self.active_sessions.read().await.len()
@@ -754,6 +1144,7 @@ mod tests {
             require_tests_pass: false,
             retry_on_failure: false,
             max_retries: 0,
+            ..Default::default()
         };
 
         orchestrator
@@ -829,4 +1220,667 @@ mod tests {
         assert_eq!(progress.running_tasks, 1);
         assert_eq!(progress.queued_tasks, 1);
     }
+
+    #[tokio::test]
+    async fn test_budget_enforcement_cancels_sessions() {
+        let (orchestrator, repository) = setup().await;
+
+        let task1 = Task::new("Task 1".to_string(), "".to_string());
+        let task2 = Task::new("Task 2".to_string(), "".to_string());
+        repository.tasks.create(&task1).await.unwrap();
+        repository.tasks.create(&task2).await.unwrap();
+
+        orchestrator
+            .update_config(AutoRunConfig {
+                retry_on_failure: true,
+                session_budget: AgentBudget {
+                    max_tokens: Some(1_000),
+                    ..Default::default()
+                },
+                run_budget: AgentBudget {
+                    max_cost_usd: Some(1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        *orchestrator.run_started_at.write().await =
+            Some(Utc::now() - chrono::Duration::seconds(1));
+
+        // Session 1 blows the token budget
+        let mut session1 = ClaudeCodeSession::new(task1.id);
+        session1.update_status(SessionStatus::Working);
+        session1.usage.output_tokens = 5_000;
+        repository
+            .claude_code
+            .create_session(&session1)
+            .await
+            .unwrap();
+
+        // Session 2 is within its own budget but pushes the run over its cost limit
+        let mut session2 = ClaudeCodeSession::new(task2.id);
+        session2.update_status(SessionStatus::Working);
+        session2.usage.cost_usd = 2.5;
+        repository
+            .claude_code
+            .create_session(&session2)
+            .await
+            .unwrap();
+
+        let mut executions = orchestrator.executions.write().await;
+        for (task_id, session_id) in [(task1.id, session1.id), (task2.id, session2.id)] {
+            executions.insert(
+                task_id,
+                TaskExecution {
+                    task_id,
+                    session_id: Some(session_id),
+                    status: TaskExecutionStatus::Running,
+                    started_at: Some(Utc::now()),
+                    completed_at: None,
+                    pr_url: None,
+                    retry_count: 0,
+                    error_message: None,
+                },
+            );
+        }
+        drop(executions);
+        orchestrator
+            .active_sessions
+            .write()
+            .await
+            .extend([session1.id, session2.id]);
+
+        orchestrator.enforce_budgets().await.unwrap();
+
+        let session1 = repository
+            .claude_code
+            .get_session(session1.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session1.is_budget_failure());
+        assert!(session1.error_message.unwrap().contains("tokens"));
+
+        let session2 = repository
+            .claude_code
+            .get_session(session2.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session2.is_budget_failure());
+
+        assert_eq!(orchestrator.get_active_sessions_count().await, 0);
+        assert!(matches!(
+            orchestrator.get_status().await,
+            AutoRunStatus::Failed(_)
+        ));
+        let progress = orchestrator.get_progress().await;
+        assert_eq!(progress.failed_tasks, 2);
+        assert!((orchestrator.get_run_usage().await.unwrap().cost_usd - 2.5).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_configured_sessions_run_the_agent_within_the_session_budget() {
+        use crate::domain::claude_code::ClaudeCodeConfig;
+        use crate::services::agent_backend::{ScriptedAgentBackend, ScriptedRun};
+        use crate::services::command_executor::mock::MockCommandExecutor;
+
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let backend = ScriptedAgentBackend::new(vec![ScriptedRun::success("Total cost: $3.00")]);
+        let claude_service = Arc::new(ClaudeCodeService::with_backend(
+            repository.claude_code.clone(),
+            Arc::new(MockCommandExecutor::new().with_delay(0)),
+            Arc::new(backend.clone()),
+        ));
+        let orchestrator = AutoRunOrchestrator::new(
+            repository.clone(),
+            claude_service,
+            Arc::new(DependencyService::new(repository.clone())),
+            Arc::new(TaskService::new(repository.clone())),
+        );
+
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.max_cost_per_session_usd = Some(10.0);
+        repository.claude_code.create_config(&config).await.unwrap();
+        orchestrator
+            .update_config(AutoRunConfig {
+                session_budget: AgentBudget {
+                    max_cost_usd: Some(1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        orchestrator.start_task_execution(task.id).await.unwrap();
+
+        let session_id = repository
+            .claude_code
+            .get_sessions_by_task(task.id)
+            .await
+            .unwrap()[0]
+            .id;
+        let mut session = None;
+        for _ in 0..100 {
+            let current = repository
+                .claude_code
+                .get_session(session_id)
+                .await
+                .unwrap()
+                .unwrap();
+            if current.status.is_terminal() {
+                session = Some(current);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        // The agent ran, and the run's tighter budget applied to it
+        assert_eq!(backend.invocations().len(), 1);
+        assert_eq!(backend.invocations()[0].budget.max_cost_usd, Some(1.0));
+        let session = session.expect("session did not finish");
+        assert!(session.is_budget_failure());
+        assert!(session.error_message.unwrap().contains("limit $1.00"));
+    }
+
+    #[tokio::test]
+    async fn test_configured_retry_continues_with_verification_output() {
+        use crate::domain::claude_code::ClaudeCodeConfig;
+        use crate::domain::verification::{VerificationReport, VerificationStepResult};
+        use crate::services::agent_backend::ScriptedAgentBackend;
+        use crate::services::command_executor::mock::MockCommandExecutor;
+
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let backend = ScriptedAgentBackend::default();
+        let orchestrator = AutoRunOrchestrator::new(
+            repository.clone(),
+            Arc::new(ClaudeCodeService::with_backend(
+                repository.claude_code.clone(),
+                Arc::new(MockCommandExecutor::new().with_delay(0)),
+                Arc::new(backend.clone()),
+            )),
+            Arc::new(DependencyService::new(repository.clone())),
+            Arc::new(TaskService::new(repository.clone())),
+        );
+
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        repository.claude_code.create_config(&config).await.unwrap();
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        let mut previous = ClaudeCodeSession::new(task.id);
+        previous.branch_name = Some("claude/task".to_string());
+        previous.verification = Some(VerificationReport {
+            steps: vec![VerificationStepResult {
+                name: "test".to_string(),
+                command: "cargo test".to_string(),
+                success: false,
+                timed_out: false,
+                exit_code: Some(101),
+                stdout: "test parser::it_works ... FAILED".to_string(),
+                stderr: String::new(),
+                duration_ms: 10,
+            }],
+        });
+        previous.set_error("Verification did not pass".to_string());
+        repository
+            .claude_code
+            .create_session(&previous)
+            .await
+            .unwrap();
+        orchestrator.executions.write().await.insert(
+            task.id,
+            TaskExecution {
+                task_id: task.id,
+                session_id: Some(previous.id),
+                status: TaskExecutionStatus::Queued,
+                started_at: None,
+                completed_at: None,
+                pr_url: None,
+                retry_count: 1,
+                error_message: previous.error_message.clone(),
+            },
+        );
+
+        orchestrator.start_task_execution(task.id).await.unwrap();
+
+        let retry_id = orchestrator.executions.read().await[&task.id]
+            .session_id
+            .unwrap();
+        let retry = repository
+            .claude_code
+            .get_session(retry_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retry.retry_of, Some(previous.id));
+        assert_eq!(retry.branch_name.as_deref(), Some("claude/task"));
+        for _ in 0..100 {
+            if !backend.invocations().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let prompt = &backend.invocations()[0].prompt;
+        assert!(prompt.contains("Verification did not pass"), "{}", prompt);
+        assert!(
+            prompt.contains("test parser::it_works ... FAILED"),
+            "{}",
+            prompt
+        );
+    }
+
+    #[tokio::test]
+    async fn test_require_tests_pass_rejects_unverified_sessions() {
+        let (orchestrator, repository) = setup().await;
+
+        let mut config = crate::domain::claude_code::ClaudeCodeConfig::new(
+            "repo".to_string(),
+            "owner".to_string(),
+        );
+        config.verification_pipeline.steps =
+            vec![crate::domain::verification::VerificationStep::new(
+                "test",
+                "cargo test",
+            )];
+        repository.claude_code.create_config(&config).await.unwrap();
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let mut session = ClaudeCodeSession::new(task.id);
+        session.update_status(SessionStatus::Completed);
+        repository
+            .claude_code
+            .create_session(&session)
+            .await
+            .unwrap();
+
+        orchestrator
+            .update_config(AutoRunConfig {
+                require_tests_pass: true,
+                retry_on_failure: false,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        orchestrator.executions.write().await.insert(
+            task.id,
+            TaskExecution {
+                task_id: task.id,
+                session_id: Some(session.id),
+                status: TaskExecutionStatus::Running,
+                started_at: Some(Utc::now()),
+                completed_at: None,
+                pr_url: None,
+                retry_count: 0,
+                error_message: None,
+            },
+        );
+
+        orchestrator
+            .handle_completed_session(session.clone())
+            .await
+            .unwrap();
+
+        let executions = orchestrator.get_execution_details().await;
+        assert_eq!(executions[0].status, TaskExecutionStatus::Failed);
+        assert_eq!(
+            executions[0].error_message.as_deref(),
+            Some("Verification did not pass")
+        );
+        let stored = repository
+            .claude_code
+            .get_session(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, SessionStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_sessions_skip_verification() {
+        let (orchestrator, repository) = setup().await;
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        orchestrator.executions.write().await.insert(
+            task.id,
+            TaskExecution {
+                task_id: task.id,
+                session_id: None,
+                status: TaskExecutionStatus::Queued,
+                started_at: None,
+                completed_at: None,
+                pr_url: None,
+                retry_count: 0,
+                error_message: None,
+            },
+        );
+        orchestrator.start_task_execution(task.id).await.unwrap();
+
+        // A verification pipeline configured mid-run doesn't apply to the
+        // simulated session, which has nothing to verify
+        let mut config = crate::domain::claude_code::ClaudeCodeConfig::new(
+            "repo".to_string(),
+            "owner".to_string(),
+        );
+        config.verification_pipeline.steps =
+            vec![crate::domain::verification::VerificationStep::new(
+                "test",
+                "cargo test",
+            )];
+        repository.claude_code.create_config(&config).await.unwrap();
+
+        let session_id = orchestrator.executions.read().await[&task.id]
+            .session_id
+            .unwrap();
+        let mut session = repository
+            .claude_code
+            .get_session(session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        session.update_status(SessionStatus::Completed);
+        orchestrator
+            .handle_completed_session(session)
+            .await
+            .unwrap();
+
+        let executions = orchestrator.get_execution_details().await;
+        assert_eq!(executions[0].status, TaskExecutionStatus::PendingReview);
+        assert_eq!(executions[0].error_message, None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_waits_for_backoff_and_links_sessions() {
+        let (orchestrator, repository) = setup().await;
+
+        let config = AutoRunConfig {
+            retry_on_failure: true,
+            max_retries: 3,
+            retry_backoff_secs: 10,
+            max_retry_backoff_secs: 25,
+            ..Default::default()
+        };
+        assert_eq!(config.retry_delay(1), chrono::Duration::seconds(10));
+        assert_eq!(config.retry_delay(2), chrono::Duration::seconds(20));
+        assert_eq!(config.retry_delay(3), chrono::Duration::seconds(25));
+        orchestrator.update_config(config).await.unwrap();
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let mut failed = ClaudeCodeSession::new(task.id);
+        failed.branch_name = Some("claude/task".to_string());
+        failed.set_error("Claude Code failed: tests did not compile".to_string());
+        repository
+            .claude_code
+            .create_session(&failed)
+            .await
+            .unwrap();
+
+        orchestrator.executions.write().await.insert(
+            task.id,
+            TaskExecution {
+                task_id: task.id,
+                session_id: Some(failed.id),
+                status: TaskExecutionStatus::Running,
+                started_at: Some(Utc::now()),
+                completed_at: None,
+                pr_url: None,
+                retry_count: 0,
+                error_message: None,
+            },
+        );
+        orchestrator.active_sessions.write().await.insert(failed.id);
+
+        orchestrator
+            .handle_failed_session(failed.clone())
+            .await
+            .unwrap();
+
+        // Still backing off: nothing starts
+        orchestrator.process_queue().await.unwrap();
+        assert_eq!(orchestrator.get_active_sessions_count().await, 0);
+        assert_eq!(orchestrator.execution_queue.lock().await.len(), 1);
+
+        // Once the backoff has elapsed the retry starts on the same branch
+        orchestrator
+            .retry_not_before
+            .write()
+            .await
+            .insert(task.id, Utc::now() - chrono::Duration::seconds(1));
+        orchestrator.process_queue().await.unwrap();
+        assert_eq!(orchestrator.get_active_sessions_count().await, 1);
+
+        let sessions = repository
+            .claude_code
+            .get_sessions_by_task(task.id)
+            .await
+            .unwrap();
+        let retry = sessions.iter().find(|s| s.id != failed.id).unwrap();
+        assert_eq!(retry.retry_of, Some(failed.id));
+        assert_eq!(retry.attempt, 2);
+        assert_eq!(retry.branch_name.as_deref(), Some("claude/task"));
+        assert!(retry.session_log.contains("tests did not compile"));
+
+        orchestrator.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_auto_merge_lands_prs_through_the_merge_queue() {
+        use crate::services::command_executor::mock::MockCommandExecutor;
+        use crate::services::hosting::{
+            FakeHostingServer, NewPullRequest, PullRequestState, RepoRef,
+        };
+
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let server = FakeHostingServer::start().await.unwrap();
+        let mock = MockCommandExecutor::new().with_delay(0);
+        let claude_service = Arc::new(
+            ClaudeCodeService::with_executor(
+                repository.claude_code.clone(),
+                Arc::new(mock.clone()),
+            )
+            .with_hosting(Arc::new(server.github_client())),
+        );
+        let orchestrator = AutoRunOrchestrator::new(
+            repository.clone(),
+            claude_service,
+            Arc::new(DependencyService::new(repository.clone())),
+            Arc::new(TaskService::new(repository.clone())),
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = crate::domain::claude_code::ClaudeCodeConfig::new(
+            "repo".to_string(),
+            "owner".to_string(),
+        );
+        config.working_directory = Some(temp.path().to_string_lossy().to_string());
+        repository.claude_code.create_config(&config).await.unwrap();
+
+        let task = Task::new("Task".to_string(), "".to_string());
+        let dependent = Task::new("Dependent".to_string(), "".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        repository.tasks.create(&dependent).await.unwrap();
+        orchestrator
+            .dependency_service
+            .add_dependency(dependent.id, task.id)
+            .await
+            .unwrap();
+
+        let repo = RepoRef::new("owner", "repo");
+        let pr = server.open_pull_request(
+            &repo,
+            &NewPullRequest {
+                title: "Task".to_string(),
+                body: String::new(),
+                head: "claude/task".to_string(),
+                base: "main".to_string(),
+            },
+            "claude",
+        );
+        let mut session = ClaudeCodeSession::new(task.id);
+        session.branch_name = Some("claude/task".to_string());
+        session.pr_number = Some(pr.number as i32);
+        session.pr_url = Some(pr.url.clone());
+        session.update_status(SessionStatus::Completed);
+        repository
+            .claude_code
+            .create_session(&session)
+            .await
+            .unwrap();
+
+        let mut executions = orchestrator.executions.write().await;
+        for (task_id, session_id, status) in [
+            (task.id, Some(session.id), TaskExecutionStatus::Running),
+            (dependent.id, None, TaskExecutionStatus::Queued),
+        ] {
+            executions.insert(
+                task_id,
+                TaskExecution {
+                    task_id,
+                    session_id,
+                    status,
+                    started_at: None,
+                    completed_at: None,
+                    pr_url: None,
+                    retry_count: 0,
+                    error_message: None,
+                },
+            );
+        }
+        drop(executions);
+        orchestrator
+            .active_sessions
+            .write()
+            .await
+            .insert(session.id);
+
+        orchestrator
+            .handle_completed_session(session)
+            .await
+            .unwrap();
+        assert_eq!(
+            orchestrator.executions.read().await[&task.id].status,
+            TaskExecutionStatus::Merging
+        );
+
+        orchestrator.process_merge_queue().await.unwrap();
+        assert_eq!(
+            orchestrator.executions.read().await[&task.id].status,
+            TaskExecutionStatus::Completed
+        );
+        let merged = server.pull_request(&repo.pull_request(pr.number)).unwrap();
+        assert_eq!(merged.state, PullRequestState::Merged);
+        assert!(mock.assert_called_with("git", &["rebase", "origin/main"]));
+        assert_eq!(
+            *orchestrator.execution_queue.lock().await,
+            vec![dependent.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_queue_sends_prs_back_only_within_the_retry_limit() {
+        use crate::services::command_executor::mock::MockCommandExecutor;
+
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_claude_success();
+        // Every branch conflicts with the base
+        mock.add_response("git", vec!["rebase", "origin/main"], "", "CONFLICT", false);
+        let claude_service = Arc::new(ClaudeCodeService::with_executor(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+        ));
+        let orchestrator = AutoRunOrchestrator::new(
+            repository.clone(),
+            claude_service,
+            Arc::new(DependencyService::new(repository.clone())),
+            Arc::new(TaskService::new(repository.clone())),
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = crate::domain::claude_code::ClaudeCodeConfig::new(
+            "repo".to_string(),
+            "owner".to_string(),
+        );
+        config.working_directory = Some(temp.path().to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        repository.claude_code.create_config(&config).await.unwrap();
+        let max_retries = orchestrator.config.read().await.max_retries;
+
+        let mut tasks = Vec::new();
+        for (title, retry_count) in [("Fresh", 0), ("Spent", max_retries)] {
+            let task = Task::new(title.to_string(), "".to_string());
+            repository.tasks.create(&task).await.unwrap();
+            let mut session = ClaudeCodeSession::new(task.id);
+            session.branch_name = Some(format!("claude/{}", title.to_lowercase()));
+            session.pr_number = Some(tasks.len() as i32 + 1);
+            repository
+                .claude_code
+                .create_session(&session)
+                .await
+                .unwrap();
+            orchestrator.executions.write().await.insert(
+                task.id,
+                TaskExecution {
+                    task_id: task.id,
+                    session_id: Some(session.id),
+                    status: TaskExecutionStatus::Merging,
+                    started_at: None,
+                    completed_at: None,
+                    pr_url: None,
+                    retry_count,
+                    error_message: None,
+                },
+            );
+            orchestrator
+                .merge_queue
+                .lock()
+                .await
+                .push(MergeQueueEntry::from_session(&session).unwrap());
+            tasks.push(task);
+        }
+
+        orchestrator.process_merge_queue().await.unwrap();
+        let executions = orchestrator.executions.read().await;
+        let fresh = &executions[&tasks[0].id];
+        assert_eq!(fresh.status, TaskExecutionStatus::Running);
+        assert_eq!(fresh.retry_count, 1);
+        let spent = &executions[&tasks[1].id];
+        assert_eq!(spent.status, TaskExecutionStatus::Failed);
+        assert!(
+            spent
+                .error_message
+                .as_ref()
+                .unwrap()
+                .contains("Merge conflict")
+        );
+        assert_eq!(
+            repository
+                .claude_code
+                .get_sessions_by_task(tasks[1].id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        drop(executions);
+        orchestrator.stop().await.unwrap();
+    }
 }
@@ -0,0 +1,580 @@
+use crate::domain::app_settings::AppSettings;
+use crate::domain::icalendar::{self, Component, Property, split_text_list};
+use crate::domain::recurring::RecurringTaskTemplate;
+use crate::domain::rrule::{ExDate, RRule, format_date_time, parse_date, parse_date_time};
+use crate::domain::task::{Priority, Task, TaskStatus};
+use crate::repository::Repository;
+use crate::repository::task_repository::TaskFilters;
+use crate::services::task_service::{CreatedTask, TaskService};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const PRODID: &str = "-//Plon//Plon Tasks//EN";
+/// Path of the subscribable feed
+pub const FEED_PATH: &str = "/calendar.ics";
+pub const DEFAULT_FEED_ADDRESS: &str = "127.0.0.1:8765";
+/// Provider name in the app settings that serves the local feed
+pub const ICS_FEED_PROVIDER: &str = "ics";
+
+/// What an .ics import created.
+#[derive(Debug)]
+pub struct IcsImport {
+    pub tasks: Vec<CreatedTask>,
+    /// One per entry with an RRULE
+    pub templates: Vec<RecurringTaskTemplate>,
+}
+
+/// Exports tasks and recurring templates as iCalendar and imports .ics files.
+pub struct CalendarService {
+    repository: Arc<Repository>,
+}
+
+impl CalendarService {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self { repository }
+    }
+
+    /// A calendar of the dated tasks matching `filters` and every active
+    /// recurring template.
+    pub async fn export_ics(&self, filters: TaskFilters) -> Result<String> {
+        let tasks = self.repository.tasks.list(filters).await?;
+        let templates = self.repository.recurring.list_active().await?;
+        Ok(calendar(&tasks, &templates).to_string())
+    }
+
+    /// Create a task per `VTODO` and `VEVENT`, or a recurring template when
+    /// the entry has an RRULE. Imported tasks get fresh ids and are checked
+    /// for duplicates.
+    pub async fn import_ics(&self, text: &str) -> Result<IcsImport> {
+        let mut tasks = Vec::new();
+        let mut templates = Vec::new();
+        for calendar in icalendar::parse(text)? {
+            for entry in calendar
+                .components
+                .iter()
+                .filter(|c| c.name == "VTODO" || c.name == "VEVENT")
+            {
+                if entry.get("RRULE").is_some() {
+                    templates.push(entry_template(entry)?);
+                } else {
+                    tasks.push(entry_task(entry)?);
+                }
+            }
+        }
+
+        for template in &templates {
+            self.repository.recurring.create(template).await?;
+        }
+        let tasks = TaskService::new(self.repository.clone())
+            .import(tasks)
+            .await?;
+        Ok(IcsImport { tasks, templates })
+    }
+}
+
+/// A `VCALENDAR` with a `VTODO` per task due at some point, a `VEVENT` per
+/// task only scheduled, and a recurring `VEVENT` per template.
+pub fn calendar(tasks: &[Task], templates: &[RecurringTaskTemplate]) -> Component {
+    let mut calendar = Component::new("VCALENDAR");
+    calendar
+        .push("VERSION", "2.0")
+        .push("PRODID", PRODID)
+        .push("CALSCALE", "GREGORIAN")
+        .push_text("X-WR-CALNAME", "Plon");
+    calendar.components.extend(
+        tasks
+            .iter()
+            .filter(|t| !t.is_archived)
+            .filter_map(task_entry),
+    );
+    calendar
+        .components
+        .extend(templates.iter().filter(|t| t.active).map(template_entry));
+    calendar
+}
+
+fn task_entry(task: &Task) -> Option<Component> {
+    let kind = match (task.due_date, task.scheduled_date) {
+        (Some(_), _) => "VTODO",
+        (None, Some(_)) => "VEVENT",
+        (None, None) => return None,
+    };
+    let mut entry = Component::new(kind);
+    entry
+        .push("UID", uid(task.id))
+        .push("DTSTAMP", format_date_time(task.updated_at))
+        .push_text("SUMMARY", &task.title);
+    if !task.description.is_empty() {
+        entry.push_text("DESCRIPTION", &task.description);
+    }
+
+    if let Some(due) = task.due_date {
+        if let Some(start) = task.scheduled_date.filter(|start| *start <= due) {
+            entry.push("DTSTART", format_date_time(start));
+        }
+        entry
+            .push("DUE", format_date_time(due))
+            .push("STATUS", todo_status(task.status));
+        if let Some(completed) = task.completed_at {
+            entry.push("COMPLETED", format_date_time(completed));
+        }
+    } else if let Some(start) = task.scheduled_date {
+        let status = if task.status == TaskStatus::Cancelled {
+            "CANCELLED"
+        } else {
+            "CONFIRMED"
+        };
+        entry
+            .push("DTSTART", format_date_time(start))
+            .push(
+                "DTEND",
+                format_date_time(start + length(task.estimated_hours)),
+            )
+            .push("STATUS", status);
+    }
+
+    entry.push("PRIORITY", priority_number(task.priority).to_string());
+    if !task.tags.is_empty() {
+        let mut tags: Vec<String> = task
+            .tags
+            .iter()
+            .map(|t| icalendar::escape_text(t))
+            .collect();
+        tags.sort();
+        entry.push("CATEGORIES", tags.join(","));
+    }
+    Some(entry)
+}
+
+fn template_entry(template: &RecurringTaskTemplate) -> Component {
+    let (start, rrule) = match &template.rrule {
+        Some(rrule) => (
+            rrule.dtstart.unwrap_or_else(|| template.rrule_start()),
+            rrule.clone(),
+        ),
+        None => {
+            // Legacy patterns count from the next task to generate
+            let next = template.next_occurrence.unwrap_or_else(Utc::now);
+            let start = DateTime::from_naive_utc_and_offset(
+                next.date_naive()
+                    .and_time(template.recurrence_rule.time_of_day),
+                Utc,
+            );
+            let mut rrule = template.recurrence_rule.to_rrule();
+            rrule.count = template.recurrence_rule.max_occurrences.map(|max| {
+                max.saturating_sub(template.recurrence_rule.occurrences_count)
+                    .max(1)
+            });
+            (start, rrule)
+        }
+    };
+
+    let mut event = Component::new("VEVENT");
+    event
+        .push("UID", uid(template.id))
+        .push("DTSTAMP", format_date_time(template.updated_at))
+        .push_text("SUMMARY", &template.title);
+    if !template.description.is_empty() {
+        event.push_text("DESCRIPTION", &template.description);
+    }
+    event
+        .push("DTSTART", format_date_time(start))
+        .push(
+            "DTEND",
+            format_date_time(start + length(template.estimated_hours)),
+        )
+        .push("RRULE", rrule.rule_text())
+        .push("PRIORITY", priority_number(template.priority).to_string());
+    for exdate in &rrule.exdates {
+        match exdate {
+            ExDate::Date(date) => event.push_with_params(
+                "EXDATE",
+                &[("VALUE", "DATE")],
+                date.format("%Y%m%d").to_string(),
+            ),
+            ExDate::DateTime(time) => event.push("EXDATE", format_date_time(*time)),
+        };
+    }
+    event
+}
+
+fn entry_task(entry: &Component) -> Result<Task> {
+    let title = entry
+        .get("SUMMARY")
+        .map(Property::text)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| "Untitled".to_string());
+    let description = entry
+        .get("DESCRIPTION")
+        .map(Property::text)
+        .unwrap_or_default();
+    let mut task = Task::new(title, description);
+
+    let start = entry.get("DTSTART").map(property_time).transpose()?;
+    task.scheduled_date = start;
+    if entry.name == "VTODO" {
+        task.due_date = entry.get("DUE").map(property_time).transpose()?;
+    } else {
+        let end = entry.get("DTEND").map(property_time).transpose()?;
+        if let (Some(start), Some(end)) = (start, end)
+            && end > start
+        {
+            task.estimated_hours = Some((end - start).num_minutes() as f32 / 60.0);
+        }
+    }
+
+    task.status = match entry
+        .get("STATUS")
+        .map(|s| s.value.to_ascii_uppercase())
+        .as_deref()
+    {
+        Some("COMPLETED") => TaskStatus::Done,
+        Some("IN-PROCESS") => TaskStatus::InProgress,
+        Some("CANCELLED") => TaskStatus::Cancelled,
+        _ => TaskStatus::Todo,
+    };
+    if task.status == TaskStatus::Done {
+        task.completed_at = Some(
+            entry
+                .get("COMPLETED")
+                .map(property_time)
+                .transpose()?
+                .unwrap_or_else(Utc::now),
+        );
+    }
+    if let Some(priority) = entry
+        .get("PRIORITY")
+        .and_then(|p| p.value.trim().parse().ok())
+    {
+        task.priority = priority_from_number(priority);
+    }
+    for categories in entry.get_all("CATEGORIES") {
+        task.tags.extend(split_text_list(&categories.value));
+    }
+    Ok(task)
+}
+
+fn entry_template(entry: &Component) -> Result<RecurringTaskTemplate> {
+    let mut rrule = RRule::parse(
+        &entry
+            .get("RRULE")
+            .map(|r| r.value.clone())
+            .unwrap_or_default(),
+    )?;
+    rrule.dtstart = entry.get("DTSTART").map(property_time).transpose()?;
+    for exdates in entry.get_all("EXDATE") {
+        let date_only = exdates
+            .param("VALUE")
+            .is_some_and(|v| v.eq_ignore_ascii_case("DATE"));
+        for value in exdates.value.split(',').map(str::trim) {
+            rrule.exdates.push(if date_only || value.len() == 8 {
+                ExDate::Date(parse_date(value)?)
+            } else {
+                ExDate::DateTime(parse_date_time(value)?)
+            });
+        }
+    }
+
+    let task = entry_task(entry)?;
+    let mut template = RecurringTaskTemplate::from_rrule(task.title, task.description, rrule);
+    template.priority = task.priority;
+    template.estimated_hours = task.estimated_hours;
+    Ok(template)
+}
+
+/// A date or date-time value; dates are midnight and `TZID`s are read as UTC.
+fn property_time(property: &Property) -> Result<DateTime<Utc>> {
+    Ok(parse_date_time(property.value.trim())?)
+}
+
+fn uid(id: Uuid) -> String {
+    format!("{}@plon", id)
+}
+
+/// How long a scheduled task or template blocks out, an hour without an estimate.
+fn length(estimated_hours: Option<f32>) -> Duration {
+    estimated_hours
+        .filter(|h| *h > 0.0)
+        .map(|h| Duration::minutes((h * 60.0).round() as i64))
+        .unwrap_or_else(|| Duration::hours(1))
+}
+
+fn todo_status(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "NEEDS-ACTION",
+        TaskStatus::InProgress | TaskStatus::Blocked | TaskStatus::Review => "IN-PROCESS",
+        TaskStatus::Done => "COMPLETED",
+        TaskStatus::Cancelled => "CANCELLED",
+    }
+}
+
+/// iCalendar priorities run from 1 (highest) to 9; 0 is undefined.
+fn priority_number(priority: Priority) -> u8 {
+    match priority {
+        Priority::Critical => 1,
+        Priority::High => 3,
+        Priority::Medium => 5,
+        Priority::Low => 7,
+    }
+}
+
+fn priority_from_number(number: u8) -> Priority {
+    match number {
+        1..=2 => Priority::Critical,
+        3..=4 => Priority::High,
+        6..=9 => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// A local HTTP feed that calendar apps can subscribe to.
+pub struct CalendarFeed {
+    pub url: String,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl CalendarFeed {
+    /// The feed URL with the `webcal` scheme, which calendar apps open as a subscription.
+    pub fn webcal_url(&self) -> String {
+        self.url.replacen("http://", "webcal://", 1)
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+    }
+}
+
+/// Serve every dated task and recurring template at `FEED_PATH` on `address`.
+pub async fn serve_calendar_feed(
+    repository: Arc<Repository>,
+    address: &str,
+) -> Result<CalendarFeed> {
+    let listener = TcpListener::bind(address).await?;
+    let url = format!("http://{}{}", listener.local_addr()?, FEED_PATH);
+    info!("Serving calendar feed at {}", url);
+
+    let handle = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let repository = repository.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream, repository).await {
+                            warn!("Calendar feed request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Calendar feed connection failed: {}", e),
+            }
+        }
+    });
+    Ok(CalendarFeed { url, handle })
+}
+
+/// Start the feed when calendar sync is enabled with the local feed provider.
+pub async fn start_calendar_feed_from_settings(
+    repository: Arc<Repository>,
+    settings: &AppSettings,
+) -> Result<Option<CalendarFeed>> {
+    if !settings.enable_calendar_sync {
+        return Ok(None);
+    }
+    match settings.calendar_provider.as_deref() {
+        None | Some(ICS_FEED_PROVIDER) => Ok(Some(
+            serve_calendar_feed(repository, DEFAULT_FEED_ADDRESS).await?,
+        )),
+        Some(other) => anyhow::bail!(
+            "Calendar provider '{}' is not supported; use '{}' for the local feed",
+            other,
+            ICS_FEED_PROVIDER
+        ),
+    }
+}
+
+async fn respond(mut stream: TcpStream, repository: Arc<Repository>) -> Result<()> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 16 * 1024 {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = if !matches!(method, "GET" | "HEAD") {
+        (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed".to_string(),
+        )
+    } else if path == FEED_PATH {
+        let ics = CalendarService::new(repository)
+            .export_ics(TaskFilters::default())
+            .await?;
+        ("200 OK", "text/calendar; charset=utf-8", ics)
+    } else {
+        ("404 Not Found", "text/plain", "Not found".to_string())
+    };
+
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes()).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::database::init_test_database;
+    use chrono::TimeZone;
+
+    async fn setup() -> Arc<Repository> {
+        let pool = init_test_database().await.unwrap();
+        Arc::new(Repository::new(pool))
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_round_trip() {
+        let repository = setup().await;
+        let due = Utc.with_ymd_and_hms(2025, 3, 7, 17, 0, 0).unwrap();
+
+        let mut release = Task::new("Release 1.2".to_string(), "Tag, build; publish".to_string());
+        release.due_date = Some(due);
+        release.priority = Priority::High;
+        release.tags.insert("release".to_string());
+        repository.tasks.create(&release).await.unwrap();
+        let mut demo = Task::new("Team demo".to_string(), String::new());
+        demo.scheduled_date = Some(due - Duration::days(1));
+        demo.estimated_hours = Some(1.5);
+        repository.tasks.create(&demo).await.unwrap();
+        repository
+            .tasks
+            .create(&Task::new("Someday".to_string(), String::new()))
+            .await
+            .unwrap();
+        let template = RecurringTaskTemplate::from_rrule(
+            "Retro".to_string(),
+            String::new(),
+            RRule::parse("DTSTART:20250103T150000Z\nRRULE:FREQ=MONTHLY;BYDAY=-1FR\nEXDATE;VALUE=DATE:20251226")
+                .unwrap(),
+        );
+        repository.recurring.create(&template).await.unwrap();
+
+        let service = CalendarService::new(repository.clone());
+        let ics = service.export_ics(TaskFilters::default()).await.unwrap();
+        assert!(ics.contains("DUE:20250307T170000Z\r\nSTATUS:NEEDS-ACTION\r\n"));
+        assert!(ics.contains("SUMMARY:Release 1.2\r\n"));
+        assert!(ics.contains("DESCRIPTION:Tag\\, build\\; publish\r\n"));
+        assert!(ics.contains(
+            "DTSTART:20250306T170000Z\r\nDTEND:20250306T183000Z\r\nSTATUS:CONFIRMED\r\n"
+        ));
+        assert!(ics.contains("RRULE:FREQ=MONTHLY;BYDAY=-1FR\r\n"));
+        assert!(ics.contains("EXDATE;VALUE=DATE:20251226\r\n"));
+        assert!(!ics.contains("Someday"));
+
+        let imported = CalendarService::new(setup().await)
+            .import_ics(&ics)
+            .await
+            .unwrap();
+        assert_eq!(imported.tasks.len(), 2);
+        let copy = |title: &str| {
+            imported
+                .tasks
+                .iter()
+                .map(|c| &c.task)
+                .find(|t| t.title == title)
+                .unwrap()
+        };
+        let release_copy = copy("Release 1.2");
+        assert_ne!(release_copy.id, release.id);
+        assert_eq!(release_copy.description, "Tag, build; publish");
+        assert_eq!(release_copy.due_date, Some(due));
+        assert_eq!(release_copy.priority, Priority::High);
+        assert!(release_copy.tags.contains("release"));
+        assert_eq!(copy("Team demo").estimated_hours, Some(1.5));
+
+        assert_eq!(imported.templates.len(), 1);
+        assert_eq!(imported.templates[0].rrule, template.rrule);
+    }
+
+    #[tokio::test]
+    async fn test_import_other_calendars() {
+        let repository = setup().await;
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\nUID:1\r\nSUMMARY:File taxes\r\n\
+                   DUE;VALUE=DATE:20250415\r\nSTATUS:COMPLETED\r\nPRIORITY:1\r\n\
+                   CATEGORIES:home,money\r\nEND:VTODO\r\nBEGIN:VTIMEZONE\r\nTZID:UTC\r\nEND:VTIMEZONE\r\n\
+                   END:VCALENDAR\r\n";
+        let imported = CalendarService::new(repository.clone())
+            .import_ics(ics)
+            .await
+            .unwrap();
+        let task = &imported.tasks[0].task;
+        assert_eq!(
+            task.due_date,
+            Some(Utc.with_ymd_and_hms(2025, 4, 15, 0, 0, 0).unwrap())
+        );
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.completed_at.is_some());
+        assert_eq!(task.priority, Priority::Critical);
+        assert_eq!(task.tags.len(), 2);
+        assert!(repository.tasks.get(task.id).await.unwrap().is_some());
+
+        assert!(
+            CalendarService::new(repository)
+                .import_ics("BEGIN:VCALENDAR\r\n")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feed_serves_calendar() {
+        let repository = setup().await;
+        let mut task = Task::new("Launch".to_string(), String::new());
+        task.due_date = Some(Utc::now() + Duration::days(3));
+        repository.tasks.create(&task).await.unwrap();
+
+        let feed = serve_calendar_feed(repository, "127.0.0.1:0")
+            .await
+            .unwrap();
+        assert!(feed.webcal_url().starts_with("webcal://127.0.0.1:"));
+
+        let response = reqwest::get(&feed.url).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/calendar; charset=utf-8"
+        );
+        let body = response.text().await.unwrap();
+        assert!(body.contains("SUMMARY:Launch"));
+
+        let missing = reqwest::get(feed.url.replace(FEED_PATH, "/other"))
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), 404);
+        feed.stop();
+    }
+}
@@ -1,16 +1,65 @@
+use std::collections::HashMap;
 use std::process::Command;
 use std::path::PathBuf;
+use std::sync::Arc;
 use uuid::Uuid;
+use crate::domain::claude_code::ClaudeCodeConfig;
 use crate::domain::task::{Task, TaskStatus};
-use anyhow::Result;
+use crate::services::agent_backend::{AgentBackend, AgentInvocation, backend_from_config};
+use crate::services::command_executor::{CommandExecutor, SystemCommandExecutor};
+use crate::services::hosting::{HostingClient, NewPullRequest, RepoRef, client_from_config};
+use anyhow::{Context, Result};
 
 pub struct ClaudeAutomation {
     workspace_dir: PathBuf,
+    config: ClaudeCodeConfig,
+    command_executor: Arc<dyn CommandExecutor>,
+    /// Overrides the backend selected in the configuration
+    backend: Option<Arc<dyn AgentBackend>>,
+    /// Overrides the hosting client built from the configuration
+    hosting: Option<Arc<dyn HostingClient>>,
 }
 
 impl ClaudeAutomation {
-    pub fn new(workspace_dir: PathBuf) -> Self {
-        Self { workspace_dir }
+    /// Run each task on the agent backend `config` selects for it.
+    pub fn new(workspace_dir: PathBuf, config: ClaudeCodeConfig) -> Self {
+        Self {
+            workspace_dir,
+            config,
+            command_executor: Arc::new(SystemCommandExecutor),
+            backend: None,
+            hosting: None,
+        }
+    }
+
+    pub fn with_executor(mut self, command_executor: Arc<dyn CommandExecutor>) -> Self {
+        self.command_executor = command_executor;
+        self
+    }
+
+    pub fn with_hosting(mut self, hosting: Arc<dyn HostingClient>) -> Self {
+        self.hosting = Some(hosting);
+        self
+    }
+
+    /// Run every task on `backend`, whatever the configuration selects.
+    pub fn with_backend(
+        workspace_dir: PathBuf,
+        config: ClaudeCodeConfig,
+        backend: Arc<dyn AgentBackend>,
+    ) -> Self {
+        let mut automation = Self::new(workspace_dir, config);
+        automation.backend = Some(backend);
+        automation
+    }
+
+    fn backend_for(&self, task: &Task) -> Arc<dyn AgentBackend> {
+        self.backend.clone().unwrap_or_else(|| {
+            backend_from_config(
+                self.config.backend_for_task(task.id),
+                self.command_executor.clone(),
+            )
+        })
     }
     
     /// Launch Claude Code to work on a specific task
@@ -31,9 +80,10 @@ Task: {}
 Description: {}
 Status: {:?}
 Priority: {:?}
+Branch: {}
 
 Please complete this task following these steps:
-1. Review the existing codebase to understand the context
+1. Check out the branch above (create it if needed) and review the existing codebase to understand the context
 2. Implement the required changes for this task
 3. Write appropriate tests if applicable
 4. Ensure all tests pass
@@ -44,38 +94,58 @@ The task should be implemented following best practices and existing code patter
             task.title,
             task.description,
             task.status,
-            task.priority
+            task.priority,
+            branch_name
         );
         
         // Save prompt to a file that Claude Code can read
         let prompt_file = self.workspace_dir.join(format!(".claude_task_{}.md", task.id));
         std::fs::write(&prompt_file, &prompt)?;
         
-        // Launch Claude Code with the task
-        let output = Command::new("claude")
-            .current_dir(&self.workspace_dir)
-            .args(&[
-                "code",
-                "--task-file", prompt_file.to_str().unwrap(),
-                "--branch", &branch_name,
-                "--auto-pr",
-                "--pr-title", &format!("Complete task: {}", task.title),
-            ])
-            .output();
+        // Launch the agent with the task
+        let mut env_vars = HashMap::new();
+        if let Some(api_key) = &self.config.claude_api_key {
+            env_vars.insert("ANTHROPIC_API_KEY".to_string(), api_key.clone());
+        }
+        let invocation = AgentInvocation {
+            work_dir: self.workspace_dir.clone(),
+            prompt,
+            prompt_file: prompt_file.clone(),
+            instructions_file: None,
+            branch: branch_name,
+            model: Some(self.config.claude_model.clone()),
+            env_vars,
+            budget: self.config.session_budget(),
+        };
+        let backend = self.backend_for(task);
+        backend.start(&invocation).await?;
+        let outcome = backend.run(&invocation).await;
+        let _ = backend.finish(&invocation).await;
         
-        match output {
+        match outcome {
             Ok(result) => {
-                if result.status.success() {
+                if result.success {
                     println!("✅ Claude Code launched successfully");
-                    println!("Output: {}", String::from_utf8_lossy(&result.stdout));
+                    println!("Output: {}", result.stdout);
+                    // Backends that don't open their own PR get one here
+                    let agent_opened_pr =
+                        backend.capabilities().creates_pull_requests && result.pr_url.is_some();
+                    if self.config.auto_create_pr && !agent_opened_pr {
+                        let opened = self.open_pull_request(task, &invocation.branch).await;
+                        if let Err(e) = opened {
+                            let _ = std::fs::remove_file(prompt_file);
+                            return Err(e);
+                        }
+                    }
                 } else {
-                    eprintln!("❌ Claude Code failed: {}", String::from_utf8_lossy(&result.stderr));
+                    eprintln!("❌ Claude Code failed: {}", result.stderr);
                 }
             }
             Err(e) => {
                 eprintln!("❌ Failed to launch Claude Code: {}", e);
                 eprintln!("💡 Please ensure Claude Code CLI is installed and in your PATH");
                 eprintln!("   You can install it from: https://claude.ai/download");
+                let _ = std::fs::remove_file(prompt_file);
                 return Err(anyhow::anyhow!("Claude Code CLI not available"));
             }
         }
@@ -86,6 +156,41 @@ The task should be implemented following best practices and existing code patter
         Ok(())
     }
     
+    /// Push the task's branch and open a pull request for it on the
+    /// configured host.
+    async fn open_pull_request(&self, task: &Task, branch: &str) -> Result<()> {
+        let push = self
+            .command_executor
+            .execute(
+                "git",
+                &["push", "-u", "origin", branch],
+                Some(&self.workspace_dir),
+                None,
+            )
+            .await
+            .context("Failed to push branch")?;
+        if !push.success {
+            anyhow::bail!("Failed to push {}: {}", branch, push.stderr);
+        }
+
+        let hosting = self
+            .hosting
+            .clone()
+            .unwrap_or_else(|| client_from_config(&self.config));
+        let new_pr = NewPullRequest {
+            title: format!("Complete task: {}", task.title),
+            body: task.description.clone(),
+            head: branch.to_string(),
+            base: self.config.default_base_branch.clone(),
+        };
+        let pr = hosting
+            .create_pull_request(&RepoRef::from_config(&self.config), &new_pr)
+            .await
+            .context("Failed to create pull request")?;
+        println!("🔀 Pull request opened: {}", pr.url);
+        Ok(())
+    }
+    
     /// Check the status of a Claude Code task
     pub async fn check_task_status(&self, task_id: Uuid) -> Result<TaskStatus> {
         // Check if there's an open PR for this task
@@ -2,6 +2,7 @@
 mod tests {
     use super::super::claude_automation::ClaudeAutomation;
     use super::super::workspace_service::WorkspaceService;
+    use crate::domain::claude_code::{AgentBackendConfig, ClaudeCodeConfig};
     use crate::domain::task::{Task, TaskStatus, Priority, Position};
     use crate::repository::Repository;
     use tempfile::TempDir;
@@ -71,7 +72,10 @@ mod tests {
                 .output()?;
             
             // Create Claude automation instance
-            let claude_automation = ClaudeAutomation::new(project_path.clone());
+            // The project has no remote to open pull requests on
+            let mut config = ClaudeCodeConfig::new("test-project".to_string(), "test".to_string());
+            config.auto_create_pr = false;
+            let claude_automation = ClaudeAutomation::new(project_path.clone(), config);
             
             // Set up test database
             let db_path = temp_dir.path().join("test.db");
@@ -126,6 +130,7 @@ mod tests {
                 is_archived: false,
                 assignee: Some("test_user".to_string()),
                 configuration_id: None,
+                workflow_state: None,
                 sort_order: 0,
             }
         }
@@ -293,6 +298,62 @@ mod tests {
         }
     }
     
+    #[tokio::test]
+    async fn test_task_runs_on_its_configured_backend() {
+        let context = TestContext::new().await.unwrap();
+        let task = context.create_test_task("Shell task", "Run by a script");
+        
+        let mut config = ClaudeCodeConfig::new("test-project".to_string(), "test".to_string());
+        config.auto_create_pr = false;
+        config.task_agent_backends.insert(
+            task.id,
+            AgentBackendConfig::Shell { command: "touch ran-by-shell".to_string() },
+        );
+        let automation = ClaudeAutomation::new(context.project_path.clone(), config);
+        
+        automation.execute_task(&task, "https://github.com/test/repo").await.unwrap();
+        assert!(context.project_path.join("ran-by-shell").exists());
+    }
+    
+    #[tokio::test]
+    async fn test_agent_gets_the_configured_model_and_budget_and_a_pr_is_opened() {
+        use crate::services::agent_backend::ScriptedAgentBackend;
+        use crate::services::command_executor::mock::MockCommandExecutor;
+        use crate::services::hosting::{FakeHostingServer, RepoRef};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let task = Task::new("Add login".to_string(), "Users can sign in".to_string());
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.claude_api_key = Some("sk-test".to_string());
+        config.max_tokens_per_session = Some(5_000);
+        
+        let backend = ScriptedAgentBackend::default();
+        let mock = MockCommandExecutor::new().with_delay(0);
+        let server = FakeHostingServer::start().await.unwrap();
+        let automation = ClaudeAutomation::with_backend(
+            temp_dir.path().to_path_buf(),
+            config.clone(),
+            Arc::new(backend.clone()),
+        )
+        .with_executor(Arc::new(mock.clone()))
+        .with_hosting(Arc::new(server.github_client()));
+        automation.execute_task(&task, "https://github.com/owner/repo").await.unwrap();
+        
+        let invocation = &backend.invocations()[0];
+        assert_eq!(invocation.model.as_deref(), Some(config.claude_model.as_str()));
+        assert_eq!(invocation.env_vars["ANTHROPIC_API_KEY"], "sk-test");
+        assert_eq!(invocation.budget, config.session_budget());
+        
+        // The scripted backend doesn't open PRs, so the branch is pushed and
+        // one is opened on the host
+        assert!(mock.assert_called_with("git", &["push", "-u", "origin", &invocation.branch]));
+        let pr = server
+            .pull_request(&RepoRef::new("owner", "repo").pull_request(1))
+            .unwrap();
+        assert_eq!(pr.title, "Complete task: Add login");
+        assert_eq!(pr.head, invocation.branch);
+    }
+    
     #[tokio::test]
     async fn test_multiple_tasks_sequential() {
         let context = TestContext::new().await.unwrap();
@@ -1,25 +1,40 @@
 use crate::domain::claude_code::{
-    ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate, SessionStatus,
+    AgentUsage, BudgetExceeded, ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate,
+    SessionStatus,
 };
+use crate::domain::session_diff::SessionDiff;
 use crate::domain::task::Task;
+use crate::domain::verification::VerificationFailurePolicy;
+use crate::repository::Repository;
 use crate::repository::claude_code_repository::ClaudeCodeRepository;
+use crate::services::agent_backend::{AgentBackend, AgentInvocation, backend_from_config};
 use crate::services::command_executor::{CommandExecutor, SystemCommandExecutor};
+use crate::services::hosting::{
+    HostingClient, NewPullRequest, PullRequestRef, RepoRef, client_from_config,
+};
+use crate::services::prompt_context::PromptContextBuilder;
+use crate::services::verification_service::VerificationService;
 use anyhow::{Context, Result};
 use chrono::Utc;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 pub struct ClaudeCodeService {
     repository: ClaudeCodeRepository,
-    active_sessions: HashMap<Uuid, JoinHandle<()>>,
+    active_sessions: Mutex<HashMap<Uuid, JoinHandle<()>>>,
     log_sender: mpsc::Sender<(Uuid, String)>,
     log_receiver: Option<mpsc::Receiver<(Uuid, String)>>,
     command_executor: Arc<dyn CommandExecutor>,
+    /// Overrides the backend selected in the configuration
+    backend: Option<Arc<dyn AgentBackend>>,
+    /// Overrides the hosting service selected in the configuration
+    hosting: Option<Arc<dyn HostingClient>>,
+    prompt_context: PromptContextBuilder,
 }
 
 impl ClaudeCodeService {
@@ -32,17 +47,51 @@ impl ClaudeCodeService {
         executor: Arc<dyn CommandExecutor>,
     ) -> Self {
         let (tx, rx) = mpsc::channel(100);
+        let prompt_context =
+            PromptContextBuilder::new(Arc::new(Repository::new(repository.pool().clone())));
         Self {
             repository,
-            active_sessions: HashMap::new(),
+            active_sessions: Mutex::new(HashMap::new()),
             log_sender: tx,
             log_receiver: Some(rx),
             command_executor: executor,
+            backend: None,
+            hosting: None,
+            prompt_context,
         }
     }
 
+    /// Run every session on `backend`, whatever the configuration selects.
+    pub fn with_backend(
+        repository: ClaudeCodeRepository,
+        executor: Arc<dyn CommandExecutor>,
+        backend: Arc<dyn AgentBackend>,
+    ) -> Self {
+        let mut service = Self::with_executor(repository, executor);
+        service.backend = Some(backend);
+        service
+    }
+
+    /// Open pull requests through `hosting` instead of the configured host.
+    pub fn with_hosting(mut self, hosting: Arc<dyn HostingClient>) -> Self {
+        self.hosting = Some(hosting);
+        self
+    }
+
+    /// The executor sessions run git and the agent through.
+    pub fn command_executor(&self) -> Arc<dyn CommandExecutor> {
+        self.command_executor.clone()
+    }
+
+    /// The hosting service pull requests go through under `config`.
+    pub fn hosting_client(&self, config: &ClaudeCodeConfig) -> Arc<dyn HostingClient> {
+        self.hosting
+            .clone()
+            .unwrap_or_else(|| client_from_config(config))
+    }
+
     pub async fn launch_claude_code(
-        &mut self,
+        &self,
         task: &Task,
         config: &ClaudeCodeConfig,
         template: &ClaudePromptTemplate,
@@ -50,9 +99,55 @@ impl ClaudeCodeService {
         // Validate configuration
         config.validate().map_err(|e| anyhow::anyhow!(e))?;
 
-        // Create new session
         let mut session = ClaudeCodeSession::new(task.id);
-        session.append_log("Initializing Claude Code session");
+        session.branch_name = Some(self.generate_branch_name(task));
+        let prompt = self.render_prompt(task, template).await?;
+
+        self.start_session(task, config, session, prompt, None)
+            .await
+    }
+
+    /// Start a new session that continues on `previous`'s branch and worktree.
+    ///
+    /// The prompt is the task's rendered template followed by what went wrong
+    /// last time: the error, failing verification output and a summary of the
+    /// changes already on the branch, taken once the branch is checked out.
+    pub async fn retry_session(
+        &self,
+        previous: &ClaudeCodeSession,
+        task: &Task,
+        config: &ClaudeCodeConfig,
+        template: &ClaudePromptTemplate,
+    ) -> Result<ClaudeCodeSession> {
+        config.validate().map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut session = ClaudeCodeSession::retry_of(previous);
+        if session.branch_name.is_none() {
+            session.branch_name = Some(self.generate_branch_name(task));
+        }
+
+        let prompt = self.render_prompt(task, template).await?;
+
+        self.start_session(task, config, session, prompt, Some(previous.clone()))
+            .await
+    }
+
+    async fn start_session(
+        &self,
+        task: &Task,
+        config: &ClaudeCodeConfig,
+        mut session: ClaudeCodeSession,
+        prompt: String,
+        previous: Option<ClaudeCodeSession>,
+    ) -> Result<ClaudeCodeSession> {
+        if let Some(previous_id) = session.retry_of {
+            session.append_log(&format!(
+                "Retrying session {} (attempt {})",
+                previous_id, session.attempt
+            ));
+        } else {
+            session.append_log("Initializing Claude Code session");
+        }
         session.update_status(SessionStatus::Initializing);
 
         // Save initial session to database
@@ -62,13 +157,11 @@ impl ClaudeCodeService {
         let work_dir = self.prepare_working_directory(task, config)?;
         session.append_log(&format!("Working directory: {}", work_dir.display()));
 
-        // Generate branch name
-        let branch_name = self.generate_branch_name(task);
-        session.branch_name = Some(branch_name.clone());
+        let branch_name = session
+            .branch_name
+            .clone()
+            .unwrap_or_else(|| self.generate_branch_name(task));
         session.append_log(&format!("Branch name: {}", branch_name));
-
-        // Render prompt from template
-        let prompt = self.render_prompt(task, template)?;
         session.append_log("Prompt generated from template");
 
         // Create prompt file
@@ -80,11 +173,35 @@ impl ClaudeCodeService {
         let instructions_file = work_dir.join("claude_instructions.md");
         fs::write(&instructions_file, &instructions)?;
 
+        let backend = self.backend.clone().unwrap_or_else(|| {
+            backend_from_config(
+                config.backend_for_task(task.id),
+                self.command_executor.clone(),
+            )
+        });
+        session.append_log(&format!("Agent backend: {}", backend.name()));
+        let hosting = self.hosting_client(config);
+
+        let mut env_vars = HashMap::new();
+        if let Some(api_key) = &config.claude_api_key {
+            env_vars.insert("ANTHROPIC_API_KEY".to_string(), api_key.clone());
+        }
+        let invocation = AgentInvocation {
+            work_dir,
+            prompt,
+            prompt_file,
+            instructions_file: Some(instructions_file),
+            branch: branch_name,
+            model: Some(config.claude_model.clone()),
+            env_vars,
+            budget: config.session_budget(),
+        };
+
         // Update session status
         session.update_status(SessionStatus::Working);
         self.repository.update_session(&session).await?;
 
-        // Launch Claude Code process
+        // Launch the agent
         let session_id = session.id;
         let log_sender = self.log_sender.clone();
         let repo_clone = self.repository.clone();
@@ -92,18 +209,42 @@ impl ClaudeCodeService {
         let executor_clone = self.command_executor.clone();
 
         let handle = tokio::spawn(async move {
-            let result = Self::run_claude_code_process(
-                session_id,
-                work_dir,
-                prompt_file,
-                instructions_file,
-                branch_name,
-                config_clone,
-                log_sender.clone(),
-                repo_clone,
-                executor_clone,
-            )
-            .await;
+            let base_branch = config_clone.default_base_branch.clone();
+            let mut result = backend.start(&invocation).await;
+            if result.is_ok() {
+                result = Self::run_claude_code_process(
+                    session_id,
+                    invocation.clone(),
+                    previous,
+                    backend.clone(),
+                    hosting,
+                    config_clone,
+                    log_sender.clone(),
+                    repo_clone.clone(),
+                    executor_clone.clone(),
+                )
+                .await;
+
+                // Capture what the branch changed before the backend cleans up
+                if let Err(e) = Self::capture_diff(
+                    &executor_clone,
+                    &repo_clone,
+                    session_id,
+                    &invocation.work_dir,
+                    &base_branch,
+                )
+                .await
+                {
+                    let _ = log_sender
+                        .send((session_id, format!("Failed to capture diff: {}", e)))
+                        .await;
+                }
+            }
+            if let Err(e) = backend.finish(&invocation).await {
+                let _ = log_sender
+                    .send((session_id, format!("Agent cleanup error: {}", e)))
+                    .await;
+            }
 
             if let Err(e) = result {
                 let _ = log_sender
@@ -112,87 +253,204 @@ impl ClaudeCodeService {
             }
         });
 
-        self.active_sessions.insert(session.id, handle);
+        self.active_sessions
+            .lock()
+            .unwrap()
+            .insert(session.id, handle);
 
         Ok(session)
     }
 
     async fn run_claude_code_process(
         session_id: Uuid,
-        work_dir: PathBuf,
-        prompt_file: PathBuf,
-        instructions_file: PathBuf,
-        branch_name: String,
+        mut invocation: AgentInvocation,
+        previous: Option<ClaudeCodeSession>,
+        backend: Arc<dyn AgentBackend>,
+        hosting: Arc<dyn HostingClient>,
         config: ClaudeCodeConfig,
         log_sender: mpsc::Sender<(Uuid, String)>,
         repository: ClaudeCodeRepository,
         executor: Arc<dyn CommandExecutor>,
     ) -> Result<()> {
+        let work_dir = invocation.work_dir.clone();
+        let branch_name = invocation.branch.clone();
+
         // Setup git branch
         let _ = log_sender
             .send((session_id, "Setting up git branch".to_string()))
             .await;
 
+        // Retries continue on the branch the previous attempt left behind
+        let checkout_args: &[&str] = if previous.is_none() {
+            &["checkout", "-b", &branch_name]
+        } else {
+            &["checkout", &branch_name]
+        };
         executor
-            .execute(
-                "git",
-                &["checkout", "-b", &branch_name],
-                Some(&work_dir),
-                None,
-            )
+            .execute("git", checkout_args, Some(&work_dir), None)
             .await
-            .context("Failed to create git branch")?;
-
-        // Build Claude Code command
-        let mut env_vars = HashMap::new();
-        if let Some(api_key) = &config.claude_api_key {
-            env_vars.insert("ANTHROPIC_API_KEY".to_string(), api_key.clone());
+            .context("Failed to check out git branch")?;
+
+        // Tell a retry how the last attempt failed and what it left on the branch
+        if let Some(previous) = &previous {
+            let diff_summary =
+                Self::diff_summary(&executor, &work_dir, &config.default_base_branch).await;
+            invocation.prompt = format!(
+                "{}\n\n{}",
+                invocation.prompt,
+                previous.retry_feedback(&diff_summary)
+            );
+            fs::write(&invocation.prompt_file, &invocation.prompt)?;
         }
 
-        // Execute Claude Code
-        let _ = log_sender
-            .send((session_id, "Launching Claude Code".to_string()))
-            .await;
-        let output = executor
-            .execute(
-                "claude",
-                &[
-                    "code",
-                    "--file",
-                    &prompt_file.to_string_lossy(),
-                    "--instructions",
-                    &instructions_file.to_string_lossy(),
-                ],
-                Some(&work_dir),
-                if env_vars.is_empty() {
-                    None
-                } else {
-                    Some(env_vars)
-                },
-            )
-            .await
-            .context("Failed to execute Claude Code")?;
+        let budget = config.session_budget();
+        let pipeline = &config.verification_pipeline;
+        let verifier = VerificationService::new(executor.clone());
+        let mut agent_invocation = invocation.clone();
+        let mut usage = AgentUsage::default();
+        let mut feedback_rounds = 0;
+        let mut agent_pr_url;
 
-        // Process output
-        if !output.stdout.is_empty() {
+        loop {
+            // Run the agent
             let _ = log_sender
-                .send((session_id, format!("Output: {}", output.stdout)))
+                .send((session_id, format!("Launching {}", backend.name())))
                 .await;
-        }
+            let started_at = repository
+                .get_session(session_id)
+                .await?
+                .map(|s| s.started_at)
+                .unwrap_or_else(Utc::now);
+            let remaining = chrono::Duration::minutes(config.max_session_duration_minutes as i64)
+                - (Utc::now() - started_at);
+            agent_invocation.budget = budget.remaining(&usage);
+            let run = backend.run(&agent_invocation);
+
+            // Timing out drops the command future, which kills the agent process
+            let output =
+                match tokio::time::timeout(remaining.to_std().unwrap_or_default(), run).await {
+                    Ok(result) => result?,
+                    Err(_) => {
+                        let mut session = repository
+                            .get_session(session_id)
+                            .await?
+                            .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+                        session.fail_over_budget(&BudgetExceeded::WallClock {
+                            limit_minutes: config.max_session_duration_minutes as i64,
+                            elapsed_minutes: session.elapsed().num_minutes(),
+                        });
+                        repository.update_session(&session).await?;
+                        return Ok(());
+                    }
+                };
+
+            // Process output
+            if !output.stdout.is_empty() {
+                let _ = log_sender
+                    .send((session_id, format!("Output: {}", output.stdout)))
+                    .await;
+            }
+
+            if !output.stderr.is_empty() {
+                let _ = log_sender
+                    .send((session_id, format!("Error: {}", output.stderr)))
+                    .await;
+            }
 
-        if !output.stderr.is_empty() {
+            // Record spend and enforce the remaining budget limits before anything is pushed
+            let run_usage = output.usage;
+            usage.input_tokens += run_usage.input_tokens;
+            usage.output_tokens += run_usage.output_tokens;
+            usage.cost_usd += run_usage.cost_usd;
+            usage.files_changed =
+                Self::count_changed_files(&executor, &work_dir, &config.default_base_branch).await;
+            let mut session = repository
+                .get_session(session_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+            session.usage = usage;
+            if let Some(exceeded) = session.check_budget(&budget) {
+                session.fail_over_budget(&exceeded);
+                repository.update_session(&session).await?;
+                return Ok(());
+            }
+            repository.update_session(&session).await?;
+
+            // Check if successful
+            agent_pr_url = output.pr_url.clone();
+            if !output.success {
+                session.set_error(format!("{} failed: {}", backend.name(), output.stderr));
+                repository.update_session(&session).await?;
+                return Ok(());
+            }
+
+            if pipeline.is_empty() {
+                break;
+            }
+
+            // Verify the worktree before anything leaves the machine
             let _ = log_sender
-                .send((session_id, format!("Error: {}", output.stderr)))
+                .send((session_id, "Running verification pipeline".to_string()))
                 .await;
+            let report = verifier.run(pipeline, &work_dir).await;
+            let passed = report.passed();
+            let feedback = report.failure_feedback();
+            session.append_log(&format!("Verification:\n{}", report.summary()));
+            session.verification = Some(report);
+
+            if passed {
+                repository.update_session(&session).await?;
+                break;
+            }
+
+            match pipeline.on_failure {
+                VerificationFailurePolicy::FeedBackToAgent { max_attempts }
+                    if feedback_rounds < max_attempts =>
+                {
+                    feedback_rounds += 1;
+                    session.append_log(&format!(
+                        "Verification failed, asking Claude Code to fix it (attempt {}/{})",
+                        feedback_rounds, max_attempts
+                    ));
+                    repository.update_session(&session).await?;
+
+                    agent_invocation.prompt = format!(
+                        "{}\n\n## Verification failed\n\n\
+                         Your changes did not pass the project's verification checks. \
+                         Fix the problems below without undoing the work already done.\n\n{}",
+                        invocation.prompt, feedback
+                    );
+                    // Kept out of the worktree so it can't be committed with the changes
+                    agent_invocation.prompt_file = std::env::temp_dir()
+                        .join(format!("plon-verification-feedback-{}.md", session_id));
+                    fs::write(&agent_invocation.prompt_file, &agent_invocation.prompt)?;
+                }
+                _ => {
+                    let failed: Vec<String> = session
+                        .verification
+                        .iter()
+                        .flat_map(|r| r.failed_steps().map(|s| s.name.clone()))
+                        .collect();
+                    session.set_error(format!("Verification failed: {}", failed.join(", ")));
+                    repository.update_session(&session).await?;
+                    return Ok(());
+                }
+            }
         }
 
-        // Check if successful
-        if !output.success {
+        // Backends that open their own pull request only need it recorded
+        if let Some(pr_url) = agent_pr_url.filter(|_| backend.capabilities().creates_pull_requests)
+        {
+            let pr_number = PullRequestRef::parse_url(&pr_url)
+                .map(|pr| pr.number as i32)
+                .unwrap_or(0);
             let mut session = repository
                 .get_session(session_id)
                 .await?
                 .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
-            session.set_error(format!("Claude Code failed: {}", output.stderr));
+            session.set_pr_info(pr_url.clone(), pr_number);
+            session.update_status(SessionStatus::Completed);
+            session.append_log(&format!("PR created by agent: {}", pr_url));
             repository.update_session(&session).await?;
             return Ok(());
         }
@@ -221,65 +479,47 @@ impl ClaudeCodeService {
                 .await
                 .context("Failed to push branch")?;
 
-            // Create PR using gh CLI
-            let pr_output = executor
-                .execute(
-                    "gh",
-                    &[
-                        "pr",
-                        "create",
-                        "--title",
-                        &format!("Claude Code: {}", branch_name),
-                        "--body",
-                        &format!(
-                            "Automated PR created by Claude Code for session {}",
-                            session_id
-                        ),
-                        "--base",
-                        &config.default_base_branch,
-                        "--head",
-                        &branch_name,
-                    ],
-                    Some(&work_dir),
-                    None,
-                )
-                .await
-                .context("Failed to create PR")?;
-
-            if pr_output.success {
-                let pr_url = pr_output.stdout.trim().to_string();
-
-                // Extract PR number from URL
-                let pr_number = pr_url
-                    .split('/')
-                    .next_back()
-                    .and_then(|s| s.parse::<i32>().ok())
-                    .unwrap_or(0);
-
-                let mut session = repository
-                    .get_session(session_id)
-                    .await?
-                    .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
-                session.set_pr_info(pr_url.clone(), pr_number);
-                session.update_status(SessionStatus::Completed);
-                session.append_log(&format!("PR created: {}", pr_url));
-                repository.update_session(&session).await?;
-
-                let _ = log_sender
-                    .send((session_id, format!("PR created: {}", pr_url)))
-                    .await;
-            } else {
-                let error = &pr_output.stderr;
-                let _ = log_sender
-                    .send((session_id, format!("Failed to create PR: {}", error)))
-                    .await;
+            let new_pr = NewPullRequest {
+                title: format!("Claude Code: {}", branch_name),
+                body: format!(
+                    "Automated PR created by Claude Code for session {}",
+                    session_id
+                ),
+                head: branch_name.clone(),
+                base: config.default_base_branch.clone(),
+            };
+            let created = hosting
+                .create_pull_request(&RepoRef::from_config(&config), &new_pr)
+                .await;
 
-                let mut session = repository
-                    .get_session(session_id)
-                    .await?
-                    .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
-                session.set_error(format!("Failed to create PR: {}", error));
-                repository.update_session(&session).await?;
+            match created {
+                Ok(pr) => {
+                    let pr_url = pr.url;
+                    let mut session = repository
+                        .get_session(session_id)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+                    session.set_pr_info(pr_url.clone(), pr.number as i32);
+                    session.update_status(SessionStatus::Completed);
+                    session.append_log(&format!("PR created: {}", pr_url));
+                    repository.update_session(&session).await?;
+
+                    let _ = log_sender
+                        .send((session_id, format!("PR created: {}", pr_url)))
+                        .await;
+                }
+                Err(error) => {
+                    let _ = log_sender
+                        .send((session_id, format!("Failed to create PR: {}", error)))
+                        .await;
+
+                    let mut session = repository
+                        .get_session(session_id)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+                    session.set_error(format!("Failed to create PR: {}", error));
+                    repository.update_session(&session).await?;
+                }
             }
         } else {
             // Mark as completed without PR
@@ -295,11 +535,82 @@ impl ClaudeCodeService {
         Ok(())
     }
 
-    pub async fn cancel_session(&mut self, session_id: Uuid) -> Result<()> {
-        // Cancel the running task if it exists
-        if let Some(handle) = self.active_sessions.remove(&session_id) {
-            handle.abort();
+    /// Number of files that differ from the base branch, committed or not.
+    async fn count_changed_files(
+        executor: &Arc<dyn CommandExecutor>,
+        work_dir: &PathBuf,
+        base_branch: &str,
+    ) -> i32 {
+        match executor
+            .execute(
+                "git",
+                &["diff", "--name-only", base_branch],
+                Some(work_dir),
+                None,
+            )
+            .await
+        {
+            Ok(output) if output.success => output
+                .stdout
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .count() as i32,
+            _ => 0,
+        }
+    }
+
+    /// Store the diff stats and unified diff of the worktree against the base branch.
+    async fn capture_diff(
+        executor: &Arc<dyn CommandExecutor>,
+        repository: &ClaudeCodeRepository,
+        session_id: Uuid,
+        work_dir: &PathBuf,
+        base_branch: &str,
+    ) -> Result<()> {
+        let numstat = executor
+            .execute(
+                "git",
+                &["diff", "--numstat", base_branch],
+                Some(work_dir),
+                None,
+            )
+            .await?;
+        let patch = executor
+            .execute("git", &["diff", base_branch], Some(work_dir), None)
+            .await?;
+        for output in [&numstat, &patch] {
+            if !output.success {
+                anyhow::bail!("git diff failed: {}", output.stderr.trim());
+            }
+        }
+
+        let diff = SessionDiff::from_git(session_id, base_branch, &numstat.stdout, &patch.stdout);
+        repository.save_session_diff(&diff).await
+    }
+
+    /// `git diff --stat` against the base branch, or empty if git fails.
+    async fn diff_summary(
+        executor: &Arc<dyn CommandExecutor>,
+        work_dir: &PathBuf,
+        base_branch: &str,
+    ) -> String {
+        match executor
+            .execute(
+                "git",
+                &["diff", "--stat", base_branch],
+                Some(work_dir),
+                None,
+            )
+            .await
+        {
+            Ok(output) if output.success => output.stdout,
+            _ => String::new(),
         }
+    }
+
+    pub async fn cancel_session(&self, session_id: Uuid) -> Result<()> {
+        // Aborting the task drops the agent's command, which kills its process
+        self.abort_session(session_id);
 
         // Update session status
         if let Some(mut session) = self.repository.get_session(session_id).await? {
@@ -311,6 +622,18 @@ impl ClaudeCodeService {
         Ok(())
     }
 
+    /// Stop a session's agent without recording anything, for callers that
+    /// record why themselves. Returns whether it was still running.
+    pub fn abort_session(&self, session_id: Uuid) -> bool {
+        match self.active_sessions.lock().unwrap().remove(&session_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
     pub async fn get_session_status(&self, session_id: Uuid) -> Result<Option<ClaudeCodeSession>> {
         self.repository.get_session(session_id).await
     }
@@ -332,7 +655,11 @@ impl ClaudeCodeService {
         Ok(())
     }
 
-    fn prepare_working_directory(&self, _task: &Task, config: &ClaudeCodeConfig) -> Result<PathBuf> {
+    fn prepare_working_directory(
+        &self,
+        _task: &Task,
+        config: &ClaudeCodeConfig,
+    ) -> Result<PathBuf> {
         let base_dir = if let Some(dir) = &config.working_directory {
             PathBuf::from(dir)
         } else {
@@ -363,48 +690,12 @@ impl ClaudeCodeService {
         format!("claude/{}-{}", task_id_short, title_slug)
     }
 
-    fn render_prompt(&self, task: &Task, template: &ClaudePromptTemplate) -> Result<String> {
-        let mut context = HashMap::new();
-
-        // Basic task information
-        context.insert("task_title".to_string(), task.title.clone());
-        context.insert("task_description".to_string(), task.description.clone());
-        context.insert("task_id".to_string(), task.id.to_string());
-        context.insert(
-            "task_id_short".to_string(),
-            task.id.to_string().chars().take(8).collect(),
-        );
-
-        // Task metadata
-        context.insert("priority".to_string(), format!("{:?}", task.priority));
-        context.insert("status".to_string(), format!("{:?}", task.status));
-        context.insert(
-            "tags".to_string(),
-            task.tags.iter().cloned().collect::<Vec<_>>().join(", "),
-        );
-
-        // Optional fields
-        if let Some(hours) = task.estimated_hours {
-            context.insert("estimated_hours".to_string(), hours.to_string());
-        } else {
-            context.insert("estimated_hours".to_string(), "Not estimated".to_string());
-        }
-
-        // Slugified title for branch naming
-        let title_slug = task
-            .title
-            .to_lowercase()
-            .chars()
-            .map(|c| if c.is_alphanumeric() { c } else { '-' })
-            .collect::<String>()
-            .trim_matches('-')
-            .to_string();
-        context.insert("task_title_slug".to_string(), title_slug);
-
-        // Goal information (would need to be passed in or fetched)
-        context.insert("goal_title".to_string(), "N/A".to_string());
-
-        Ok(template.render(&context))
+    /// Render `template` with the task's goal, dependencies and comments.
+    async fn render_prompt(&self, task: &Task, template: &ClaudePromptTemplate) -> Result<String> {
+        let context = self.prompt_context.build(task).await?;
+        template
+            .render_with(&context, false)
+            .map_err(|e| anyhow::anyhow!(e))
     }
 
     fn create_claude_instructions(
@@ -5,8 +5,12 @@ mod tests {
         ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate, SessionStatus,
     };
     use crate::domain::task::Task;
+    use crate::domain::verification::{
+        VerificationFailurePolicy, VerificationPipeline, VerificationStep,
+    };
     use crate::repository::Repository;
     use crate::services::command_executor::mock::MockCommandExecutor;
+    use crate::services::hosting::{FakeHostingServer, RepoRef};
     use sqlx::SqlitePool;
     use std::sync::Arc;
     use tempfile::TempDir;
@@ -35,15 +39,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_launch_claude_code() {
-        let (_service, repository, _temp_dir, mut mock) = setup_test_env().await;
+        let (_service, repository, _temp_dir, mock) = setup_test_env().await;
 
         // Setup mock responses
         mock.mock_claude_success();
-        mock.mock_gh_pr_create();
+        let server = FakeHostingServer::start().await.unwrap();
 
         // Recreate service with updated mock
-        let mut service =
-            ClaudeCodeService::with_executor(repository.claude_code.clone(), Arc::new(mock));
+        let service =
+            ClaudeCodeService::with_executor(repository.claude_code.clone(), Arc::new(mock))
+                .with_hosting(Arc::new(server.github_client()));
 
         // Create config and template
         let config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
@@ -73,7 +78,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_cancel_session() {
-        let (mut service, repository, _temp_dir, _mock) = setup_test_env().await;
+        let (service, repository, _temp_dir, _mock) = setup_test_env().await;
 
         // Create task first
         let task = Task::new("Test Task".to_string(), "Description".to_string());
@@ -157,7 +162,7 @@ mod tests {
         let mut task = Task::new("Test Task".to_string(), "Test Description".to_string());
         task.add_tag("test".to_string());
 
-        let prompt = service.render_prompt(&task, &template).unwrap();
+        let prompt = service.render_prompt(&task, &template).await.unwrap();
         assert!(prompt.contains("Test Task"));
         assert!(prompt.contains("Test Description"));
     }
@@ -266,4 +271,363 @@ mod tests {
         assert_eq!(remaining.len(), 1);
         assert_eq!(remaining[0].id, recent_session.id);
     }
+
+    async fn wait_for_terminal(
+        repository: &Repository,
+        session_id: uuid::Uuid,
+    ) -> ClaudeCodeSession {
+        for _ in 0..100 {
+            let session = repository
+                .claude_code
+                .get_session(session_id)
+                .await
+                .unwrap()
+                .unwrap();
+            if session.status.is_terminal() {
+                return session;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("session did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_verification_failure_feeds_back_then_fails() {
+        let (_service, repository, temp_dir, _mock) = setup_test_env().await;
+
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_git_operations();
+        mock.mock_claude_success();
+        mock.add_response("sh", vec!["cargo build"], "Finished", "", true);
+        mock.add_response(
+            "sh",
+            vec!["cargo test"],
+            "",
+            "test it_works ... FAILED",
+            false,
+        );
+        let service = ClaudeCodeService::with_executor(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+        );
+
+        let mut config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.verification_pipeline = VerificationPipeline {
+            steps: vec![
+                VerificationStep::new("build", "cargo build"),
+                VerificationStep::new("test", "cargo test"),
+            ],
+            on_failure: VerificationFailurePolicy::FeedBackToAgent { max_attempts: 1 },
+        };
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+
+        let task = Task::new("Test Task".to_string(), "Description".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let session = service
+            .launch_claude_code(&task, &config, &template)
+            .await
+            .unwrap();
+        let session = wait_for_terminal(&repository, session.id).await;
+
+        assert_eq!(session.status, SessionStatus::Failed);
+        assert_eq!(
+            session.error_message.as_deref(),
+            Some("Verification failed: test")
+        );
+        let report = session.verification.unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.steps.len(), 2);
+
+        // One initial run plus one feedback run, and nothing pushed
+        let claude_runs = mock
+            .get_call_history()
+            .iter()
+            .filter(|c| c.program == "claude")
+            .count();
+        assert_eq!(claude_runs, 2);
+        assert!(!mock.assert_called_with("git", &["push"]));
+
+        // The feedback prompt is written outside the worktree, so it can't
+        // end up in the agent's commits
+        let feedback_file =
+            std::env::temp_dir().join(format!("plon-verification-feedback-{}.md", session.id));
+        assert!(mock.assert_called_with("claude", &[feedback_file.to_str().unwrap()]));
+        assert!(
+            !temp_dir
+                .path()
+                .join("claude_verification_feedback.md")
+                .exists()
+        );
+        let feedback = std::fs::read_to_string(&feedback_file).unwrap();
+        assert!(feedback.contains("test it_works ... FAILED"));
+    }
+
+    #[tokio::test]
+    async fn test_verification_pass_allows_push() {
+        let (_service, repository, temp_dir, _mock) = setup_test_env().await;
+
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_git_operations();
+        mock.mock_claude_success();
+        mock.add_response("sh", vec!["cargo test"], "test result: ok", "", true);
+        let server = FakeHostingServer::start().await.unwrap();
+        let service = ClaudeCodeService::with_executor(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+        )
+        .with_hosting(Arc::new(server.github_client()));
+
+        let mut config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.verification_pipeline.steps = vec![VerificationStep::new("test", "cargo test")];
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+
+        let task = Task::new("Test Task".to_string(), "Description".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let session = service
+            .launch_claude_code(&task, &config, &template)
+            .await
+            .unwrap();
+        let session = wait_for_terminal(&repository, session.id).await;
+
+        assert_eq!(session.status, SessionStatus::Completed);
+        assert!(session.verification.unwrap().passed());
+        assert!(mock.assert_called_with("git", &["push"]));
+
+        // The PR was opened through the hosting API, not by parsing CLI output
+        let repo = RepoRef::new("test-owner", "test-repo");
+        let prs = server.pull_requests(&repo);
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].head, session.branch_name.clone().unwrap());
+        assert_eq!(prs[0].base, "main");
+        assert_eq!(session.pr_number, Some(prs[0].number as i32));
+        assert_eq!(session.pr_url.as_deref(), Some(prs[0].url.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_session_continues_branch_with_feedback() {
+        let (_service, repository, temp_dir, _mock) = setup_test_env().await;
+
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_claude_success();
+        mock.add_response(
+            "git",
+            vec!["diff", "--stat"],
+            " src/lib.rs | 12 ++++++------\n 1 file changed",
+            "",
+            true,
+        );
+        let service = ClaudeCodeService::with_executor(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+        );
+
+        let mut config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+
+        let task = Task::new("Test Task".to_string(), "Description".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let mut previous = ClaudeCodeSession::new(task.id);
+        previous.branch_name = Some("claude/retry-me".to_string());
+        previous.set_error("Claude Code failed: could not compile".to_string());
+        repository
+            .claude_code
+            .create_session(&previous)
+            .await
+            .unwrap();
+
+        let session = service
+            .retry_session(&previous, &task, &config, &template)
+            .await
+            .unwrap();
+        assert_eq!(session.retry_of, Some(previous.id));
+        assert_eq!(session.attempt, 2);
+        let session = wait_for_terminal(&repository, session.id).await;
+        assert_eq!(session.status, SessionStatus::Completed);
+        assert_eq!(session.branch_name.as_deref(), Some("claude/retry-me"));
+
+        // Same branch, checked out rather than created
+        let checkouts: Vec<_> = mock
+            .get_call_history()
+            .into_iter()
+            .filter(|c| {
+                c.program == "git" && c.args.first().map(String::as_str) == Some("checkout")
+            })
+            .collect();
+        assert_eq!(checkouts.len(), 1);
+        assert_eq!(checkouts[0].args, vec!["checkout", "claude/retry-me"]);
+
+        // The changes so far are read from the checked-out branch
+        let git_calls: Vec<String> = mock
+            .get_call_history()
+            .into_iter()
+            .filter(|c| c.program == "git")
+            .map(|c| c.args.join(" "))
+            .collect();
+        let checkout = git_calls
+            .iter()
+            .position(|c| c.starts_with("checkout"))
+            .unwrap();
+        let diff_stat = git_calls
+            .iter()
+            .position(|c| c.starts_with("diff --stat"))
+            .unwrap();
+        assert!(checkout < diff_stat, "{:?}", git_calls);
+
+        let prompt = std::fs::read_to_string(temp_dir.path().join("claude_task.md")).unwrap();
+        assert!(prompt.starts_with("Task: Test Task"));
+        assert!(prompt.contains("could not compile"));
+        assert!(prompt.contains("src/lib.rs | 12"));
+
+        let history = repository
+            .claude_code
+            .get_sessions_by_task(task.id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_session_diff_captured_when_session_ends() {
+        let (_service, repository, temp_dir, _mock) = setup_test_env().await;
+
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_claude_success();
+        mock.add_response(
+            "git",
+            vec!["diff", "--numstat"],
+            "2\t1\tsrc/lib.rs\n",
+            "",
+            true,
+        );
+        mock.add_response(
+            "git",
+            vec!["diff", "develop"],
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+             @@ -1,2 +1,3 @@\n pub mod a;\n-pub mod b;\n+pub mod c;\n+pub mod d;\n",
+            "",
+            true,
+        );
+        mock.mock_git_operations();
+        let service = ClaudeCodeService::with_executor(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+        );
+
+        let mut config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.default_base_branch = "develop".to_string();
+        config.auto_create_pr = false;
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+
+        let task = Task::new("Diff Task".to_string(), "Description".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        let session = service
+            .launch_claude_code(&task, &config, &template)
+            .await
+            .unwrap();
+        wait_for_terminal(&repository, session.id).await;
+
+        // The capture runs right after the session is recorded as terminal
+        let mut diff = None;
+        for _ in 0..50 {
+            diff = repository
+                .claude_code
+                .get_session_diff(session.id)
+                .await
+                .unwrap();
+            if diff.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let diff = diff.expect("diff captured");
+        assert_eq!(diff.base_branch, "develop");
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "src/lib.rs");
+        assert_eq!((diff.total_additions(), diff.total_deletions()), (2, 1));
+        let patch = diff.file_patch("src/lib.rs").unwrap();
+        assert_eq!(patch.hunks[0].side_by_side().len(), 3);
+        assert!(mock.assert_called_with("git", &["diff", "develop"]));
+    }
+
+    #[tokio::test]
+    async fn test_scripted_backend_session_lifecycle() {
+        use crate::domain::claude_code::AgentBackendConfig;
+        use crate::services::agent_backend::{ScriptedAgentBackend, ScriptedRun};
+
+        let (_service, repository, temp_dir, mock) = setup_test_env().await;
+        let backend = ScriptedAgentBackend::new(vec![
+            ScriptedRun::success("{\"total_cost_usd\": 0.5}")
+                .with_file("src/new.rs", "fn main() {}"),
+        ]);
+        let service = ClaudeCodeService::with_backend(
+            repository.claude_code.clone(),
+            Arc::new(mock.clone()),
+            Arc::new(backend.clone()),
+        );
+
+        let goal = crate::domain::goal::Goal::new("Launch".to_string(), String::new());
+        repository.goals.create(&goal).await.unwrap();
+        let mut task = Task::new("Scripted Task".to_string(), "Description".to_string());
+        task.goal_id = Some(goal.id);
+        repository.tasks.create(&task).await.unwrap();
+
+        // Backend selection is stored with the configuration
+        let mut config = ClaudeCodeConfig::new("test-repo".to_string(), "test-owner".to_string());
+        config.working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        config.agent_backend = AgentBackendConfig::Shell {
+            command: "aider --yes".to_string(),
+        };
+        config
+            .task_agent_backends
+            .insert(task.id, AgentBackendConfig::ClaudeCli);
+        repository.claude_code.create_config(&config).await.unwrap();
+        let stored = repository.claude_code.get_config().await.unwrap().unwrap();
+        assert_eq!(stored.agent_backend, config.agent_backend);
+        assert_eq!(
+            stored.backend_for_task(task.id),
+            &AgentBackendConfig::ClaudeCli
+        );
+
+        let template = ClaudePromptTemplate::new(
+            "test".to_string(),
+            "Task: {{task_title}} for {{goal.title}}".to_string(),
+        );
+        let session = service
+            .launch_claude_code(&task, &config, &template)
+            .await
+            .unwrap();
+        let session = wait_for_terminal(&repository, session.id).await;
+
+        assert_eq!(session.status, SessionStatus::Completed);
+        assert!((session.usage.cost_usd - 0.5).abs() < f64::EPSILON);
+        assert!(temp_dir.path().join("src/new.rs").exists());
+
+        let invocations = backend.invocations();
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].prompt, "Task: Scripted Task for Launch");
+        assert_eq!(invocations[0].branch, session.branch_name.unwrap());
+        assert!(!mock.assert_called_with("claude", &["code"]));
+
+        // finish runs after the session is recorded as terminal
+        for _ in 0..50 {
+            if backend.finished_sessions() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(backend.finished_sessions(), 1);
+    }
 }
@@ -236,6 +236,7 @@ mod tests {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,
         };
         let task_id = task.id;
@@ -286,6 +287,7 @@ mod tests {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,
         };
         repo.tasks.create(&task).await.unwrap();
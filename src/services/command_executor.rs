@@ -1,10 +1,15 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::ops::ControlFlow;
 use std::path::Path;
 use std::process::{Output, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
+/// Called with each line of a command's output as it is printed.
+pub type LineHandler = dyn Fn(&str) -> ControlFlow<()> + Send + Sync;
+
 /// Trait for executing system commands - allows for mocking in tests
 #[async_trait]
 pub trait CommandExecutor: Send + Sync {
@@ -15,6 +20,58 @@ pub trait CommandExecutor: Send + Sync {
         working_dir: Option<&Path>,
         env_vars: Option<HashMap<String, String>>,
     ) -> Result<CommandOutput>;
+
+    /// Like `execute`, with `stdin` written to the process's standard input.
+    async fn execute_with_stdin(
+        &self,
+        program: &str,
+        _args: &[&str],
+        _working_dir: Option<&Path>,
+        _env_vars: Option<HashMap<String, String>>,
+        _stdin: &str,
+    ) -> Result<CommandOutput> {
+        Err(anyhow::anyhow!(
+            "This executor cannot pass standard input to {}",
+            program
+        ))
+    }
+
+    /// Like `execute`, passing `stdin` if given and each line of standard
+    /// output to `on_line` as it is printed. `on_line` returning
+    /// `ControlFlow::Break` kills the process; the output read until then is
+    /// returned as a failure.
+    ///
+    /// By default the command runs to completion and its output is replayed.
+    async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        env_vars: Option<HashMap<String, String>>,
+        stdin: Option<&str>,
+        on_line: &LineHandler,
+    ) -> Result<CommandOutput> {
+        let mut output = match stdin {
+            Some(stdin) => {
+                self.execute_with_stdin(program, args, working_dir, env_vars, stdin)
+                    .await?
+            }
+            None => self.execute(program, args, working_dir, env_vars).await?,
+        };
+        let mut read = 0;
+        let stopped_at = output.stdout.split_inclusive('\n').find_map(|line| {
+            read += line.len();
+            on_line(line.trim_end_matches(['\r', '\n']))
+                .is_break()
+                .then_some(read)
+        });
+        if let Some(end) = stopped_at {
+            output.stdout.truncate(end);
+            output.success = false;
+            output.exit_code = None;
+        }
+        Ok(output)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +107,8 @@ impl CommandExecutor for SystemCommandExecutor {
     ) -> Result<CommandOutput> {
         let mut cmd = Command::new(program);
         cmd.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+        // Dropping the future (e.g. on timeout or abort) must not leave the process running
+        cmd.kill_on_drop(true);
 
         if let Some(dir) = working_dir {
             cmd.current_dir(dir);
@@ -64,6 +123,132 @@ impl CommandExecutor for SystemCommandExecutor {
         let output = cmd.output().await?;
         Ok(CommandOutput::from(output))
     }
+
+    async fn execute_with_stdin(
+        &self,
+        program: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        env_vars: Option<HashMap<String, String>>,
+        stdin: &str,
+    ) -> Result<CommandOutput> {
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        if let Some(dir) = working_dir {
+            cmd.current_dir(dir);
+        }
+
+        if let Some(vars) = env_vars {
+            for (key, value) in vars {
+                cmd.env(key, value);
+            }
+        }
+
+        let mut child = cmd.spawn()?;
+        if let Some(mut pipe) = child.stdin.take() {
+            pipe.write_all(stdin.as_bytes()).await?;
+            // Closing the pipe signals end of input
+            drop(pipe);
+        }
+        let output = child.wait_with_output().await?;
+        Ok(CommandOutput::from(output))
+    }
+
+    async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        env_vars: Option<HashMap<String, String>>,
+        stdin: Option<&str>,
+        on_line: &LineHandler,
+    ) -> Result<CommandOutput> {
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        if let Some(dir) = working_dir {
+            cmd.current_dir(dir);
+        }
+
+        if let Some(vars) = env_vars {
+            for (key, value) in vars {
+                cmd.env(key, value);
+            }
+        }
+
+        let mut child = cmd.spawn()?;
+        // Input is written and stderr drained alongside reading stdout, so a
+        // process blocked on a full pipe can't stall the other end
+        let writer = match (child.stdin.take(), stdin) {
+            (Some(mut pipe), Some(input)) => {
+                let input = input.to_string();
+                Some(tokio::spawn(async move {
+                    // A process that exits without reading its input is not an error
+                    let _ = pipe.write_all(input.as_bytes()).await;
+                }))
+            }
+            _ => None,
+        };
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = tokio::spawn(async move {
+            let mut stderr = Vec::new();
+            if let Some(pipe) = stderr_pipe.as_mut() {
+                let _ = pipe.read_to_end(&mut stderr).await;
+            }
+            stderr
+        });
+
+        let mut stdout = String::new();
+        let mut stopped = false;
+        if let Some(pipe) = child.stdout.take() {
+            let mut lines = BufReader::new(pipe).lines();
+            while let Some(line) = lines.next_line().await? {
+                stdout.push_str(&line);
+                stdout.push('\n');
+                if on_line(&line).is_break() {
+                    stopped = true;
+                    break;
+                }
+            }
+        }
+
+        if stopped {
+            child.kill().await?;
+        }
+        let status = child.wait().await?;
+        if let Some(writer) = writer {
+            writer.abort();
+        }
+        // Children of a killed process may hold stderr open
+        let stderr = if stopped {
+            tokio::time::timeout(std::time::Duration::from_secs(1), stderr_reader)
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .unwrap_or_default()
+        } else {
+            stderr_reader.await.unwrap_or_default()
+        };
+        Ok(CommandOutput {
+            stdout,
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            success: !stopped && status.success(),
+            exit_code: if stopped { None } else { status.code() },
+        })
+    }
 }
 
 pub mod mock {
@@ -96,6 +281,7 @@ pub mod mock {
         pub args: Vec<String>,
         pub working_dir: Option<String>,
         pub env_vars: Option<HashMap<String, String>>,
+        pub stdin: Option<String>,
     }
 
     impl MockCommandExecutor {
@@ -233,22 +419,14 @@ pub mod mock {
         }
     }
 
-    #[async_trait]
-    impl CommandExecutor for MockCommandExecutor {
-        async fn execute(
+    impl MockCommandExecutor {
+        async fn respond(
             &self,
+            call: MockCall,
             program: &str,
             args: &[&str],
-            working_dir: Option<&Path>,
-            env_vars: Option<HashMap<String, String>>,
         ) -> Result<CommandOutput> {
             // Record the call
-            let call = MockCall {
-                program: program.to_string(),
-                args: args.iter().map(|s| s.to_string()).collect(),
-                working_dir: working_dir.map(|p| p.to_string_lossy().to_string()),
-                env_vars: env_vars.clone(),
-            };
             self.call_history.lock().unwrap().push(call);
 
             // Find matching response
@@ -292,6 +470,44 @@ pub mod mock {
         }
     }
 
+    #[async_trait]
+    impl CommandExecutor for MockCommandExecutor {
+        async fn execute(
+            &self,
+            program: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+            env_vars: Option<HashMap<String, String>>,
+        ) -> Result<CommandOutput> {
+            let call = MockCall {
+                program: program.to_string(),
+                args: args.iter().map(|s| s.to_string()).collect(),
+                working_dir: working_dir.map(|p| p.to_string_lossy().to_string()),
+                env_vars,
+                stdin: None,
+            };
+            self.respond(call, program, args).await
+        }
+
+        async fn execute_with_stdin(
+            &self,
+            program: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+            env_vars: Option<HashMap<String, String>>,
+            stdin: &str,
+        ) -> Result<CommandOutput> {
+            let call = MockCall {
+                program: program.to_string(),
+                args: args.iter().map(|s| s.to_string()).collect(),
+                working_dir: working_dir.map(|p| p.to_string_lossy().to_string()),
+                env_vars,
+                stdin: Some(stdin.to_string()),
+            };
+            self.respond(call, program, args).await
+        }
+    }
+
     impl Default for MockCommandExecutor {
         fn default() -> Self {
             Self::new()
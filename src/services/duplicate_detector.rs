@@ -0,0 +1,390 @@
+use crate::domain::task::Task;
+use crate::repository::embedding_repository::EmbeddingRepository;
+use crate::repository::summary_repository::content_hash;
+use crate::services::embeddings::{EmbeddingProvider, cosine_similarity};
+use crate::services::summarization::task_summary_source;
+use anyhow::{Result, bail};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const TITLE_WEIGHT: f32 = 0.6;
+const DESCRIPTION_WEIGHT: f32 = 0.25;
+const TAG_WEIGHT: f32 = 0.15;
+/// Longer descriptions are compared on their start only
+const DESCRIPTION_CHARS: usize = 2000;
+
+/// An existing task that may be the same as the one being checked.
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+    pub task: Task,
+    /// 0.0 (nothing alike) to 1.0 (identical)
+    pub similarity: f32,
+}
+
+/// Finds tasks that are probably the same piece of work, by fuzzy matching
+/// of normalized titles, descriptions and tags, optionally blended with
+/// embedding similarity.
+#[derive(Clone)]
+pub struct DuplicateDetector {
+    threshold: f32,
+    max_candidates: usize,
+    embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    store: Option<EmbeddingRepository>,
+}
+
+impl Default for DuplicateDetector {
+    fn default() -> Self {
+        Self {
+            threshold: 0.75,
+            max_candidates: 5,
+            embeddings: None,
+            store: None,
+        }
+    }
+}
+
+impl DuplicateDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lowest similarity reported as a duplicate.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Average the fuzzy score with embedding similarity, so rewordings of
+    /// the same task are caught too.
+    pub fn with_embeddings(mut self, embeddings: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Reuse and keep the task vectors stored in `store`, so checking a
+    /// task embeds only that task and tasks whose text changed.
+    pub fn with_store(mut self, store: EmbeddingRepository) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Fuzzy similarity of two tasks. Descriptions and tags only count
+    /// when both tasks have them.
+    pub fn score(&self, a: &Task, b: &Task) -> f32 {
+        let mut total = TITLE_WEIGHT * title_similarity(&a.title, &b.title);
+        let mut weight = TITLE_WEIGHT;
+
+        let description_a: String = normalize(&a.description)
+            .chars()
+            .take(DESCRIPTION_CHARS)
+            .collect();
+        let description_b: String = normalize(&b.description)
+            .chars()
+            .take(DESCRIPTION_CHARS)
+            .collect();
+        if !description_a.is_empty() && !description_b.is_empty() {
+            total += DESCRIPTION_WEIGHT * dice_similarity(&description_a, &description_b);
+            weight += DESCRIPTION_WEIGHT;
+        }
+
+        if !a.tags.is_empty() && !b.tags.is_empty() {
+            let tags_a: HashSet<String> = a.tags.iter().map(|t| normalize(t)).collect();
+            let tags_b: HashSet<String> = b.tags.iter().map(|t| normalize(t)).collect();
+            let shared = tags_a.intersection(&tags_b).count() as f32;
+            total += TAG_WEIGHT * shared / tags_a.union(&tags_b).count() as f32;
+            weight += TAG_WEIGHT;
+        }
+
+        total / weight
+    }
+
+    /// Tasks in `existing` that `task` likely duplicates, most similar
+    /// first. Archived tasks and `task` itself are skipped.
+    pub async fn find_duplicates(
+        &self,
+        task: &Task,
+        existing: &[Task],
+    ) -> Result<Vec<DuplicateCandidate>> {
+        let others: Vec<&Task> = existing
+            .iter()
+            .filter(|t| t.id != task.id && !t.is_archived)
+            .collect();
+        let mut scores: Vec<f32> = others.iter().map(|other| self.score(task, other)).collect();
+
+        if let Some(embeddings) = &self.embeddings
+            && !others.is_empty()
+        {
+            let (vector, other_vectors) = self.vectors(embeddings.as_ref(), task, &others).await?;
+            for (score, other) in scores.iter_mut().zip(&other_vectors) {
+                *score = (*score + cosine_similarity(&vector, other).max(0.0)) / 2.0;
+            }
+        }
+
+        let mut candidates: Vec<DuplicateCandidate> = others
+            .into_iter()
+            .zip(scores)
+            .filter(|(_, similarity)| *similarity >= self.threshold)
+            .map(|(task, similarity)| DuplicateCandidate {
+                task: task.clone(),
+                similarity,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        candidates.truncate(self.max_candidates);
+        Ok(candidates)
+    }
+
+    /// The vector of `task` and one per task in `others`, in order. Stored
+    /// vectors are used where the text is unchanged; corpus-relative
+    /// vectors are always computed together.
+    async fn vectors(
+        &self,
+        embeddings: &dyn EmbeddingProvider,
+        task: &Task,
+        others: &[&Task],
+    ) -> Result<(Vec<f32>, Vec<Vec<f32>>)> {
+        let texts: Vec<String> = std::iter::once(task)
+            .chain(others.iter().copied())
+            .map(task_summary_source)
+            .collect();
+        let store = match &self.store {
+            Some(store) if !embeddings.is_corpus_relative() => store,
+            _ => {
+                let mut vectors = embed(embeddings, &texts).await?;
+                let vector = vectors.remove(0);
+                return Ok((vector, vectors));
+            }
+        };
+
+        let model = embeddings.model();
+        let keys: Vec<(Uuid, String)> = others
+            .iter()
+            .zip(&texts[1..])
+            .map(|(other, text)| (other.id, content_hash(text)))
+            .collect();
+        let mut stored = store.get_many(model, &keys).await?;
+        let missing: Vec<usize> = (0..others.len())
+            .filter(|i| !stored.contains_key(&others[*i].id))
+            .collect();
+
+        let batch: Vec<String> = std::iter::once(texts[0].clone())
+            .chain(missing.iter().map(|i| texts[i + 1].clone()))
+            .collect();
+        let mut embedded = embed(embeddings, &batch).await?.into_iter();
+        let vector = embedded.next().unwrap_or_default();
+        // The checked task may not be saved yet, so only the others are stored
+        for (i, other_vector) in missing.iter().zip(embedded) {
+            let (task_id, hash) = &keys[*i];
+            store.save(*task_id, model, hash, &other_vector).await?;
+            stored.insert(*task_id, other_vector);
+        }
+        let other_vectors = others
+            .iter()
+            .map(|other| stored.remove(&other.id).unwrap_or_default())
+            .collect();
+        Ok((vector, other_vectors))
+    }
+}
+
+/// One vector per text, failing if the provider returns a different number.
+async fn embed(embeddings: &dyn EmbeddingProvider, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let vectors = embeddings.embed(texts).await?;
+    if vectors.len() != texts.len() {
+        bail!(
+            "{} returned {} vectors for {} texts",
+            embeddings.model(),
+            vectors.len(),
+            texts.len()
+        );
+    }
+    Ok(vectors)
+}
+
+/// Lowercase words separated by single spaces, without punctuation.
+pub fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Title similarity. Titles that differ only in their numbers ("Sprint 3"
+/// and "Sprint 4") are usually separate tasks, so that halves the score.
+fn title_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (normalize(a), normalize(b));
+    let numbers = |s: &str| -> HashSet<String> {
+        s.split(' ')
+            .filter(|w| w.chars().all(|c| c.is_ascii_digit()))
+            .map(String::from)
+            .collect()
+    };
+    let similarity = dice_similarity(&a, &b);
+    if numbers(&a) != numbers(&b) {
+        similarity / 2.0
+    } else {
+        similarity
+    }
+}
+
+/// Sørensen–Dice coefficient over character bigrams: 1.0 for equal
+/// strings, tolerant of typos and reordered words.
+pub fn dice_similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a, mut b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let total = (a.len() + b.len()) as f32;
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(pos) = b.iter().position(|p| p == pair) {
+            b.swap_remove(pos);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f32 / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Repository;
+    use crate::repository::database::init_test_database;
+    use crate::services::embeddings::TfIdfEmbedder;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    fn task(title: &str, description: &str, tags: &[&str]) -> Task {
+        let mut task = Task::new(title.to_string(), description.to_string());
+        task.tags = tags.iter().map(|t| t.to_string()).collect();
+        task
+    }
+
+    #[test]
+    fn test_similarity_measures() {
+        assert_eq!(normalize("  Export: CSV!! "), "export csv");
+        assert_eq!(dice_similarity("night", "night"), 1.0);
+        assert!(dice_similarity("export csv", "exprot csv") > 0.6);
+        assert!(dice_similarity("export csv", "dark mode") < 0.2);
+        assert!(title_similarity("Sprint 3 review", "Sprint 4 review") < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicates() {
+        let detector = DuplicateDetector::new();
+        let new = task(
+            "Export tasks to CSV",
+            "Let users download tasks as CSV",
+            &["export"],
+        );
+        let mut archived = task("Export tasks to CSV", "", &[]);
+        archived.is_archived = true;
+        let existing = vec![
+            task(
+                "export tasks to csv.",
+                "Users can download their tasks as a CSV file",
+                &["export", "csv"],
+            ),
+            task("Dark mode", "Theme support", &[]),
+            task("Task 1", "", &[]),
+            archived,
+            new.clone(),
+        ];
+
+        let found = detector.find_duplicates(&new, &existing).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].task.id, existing[0].id);
+        assert!(found[0].similarity > 0.75);
+
+        let numbered = detector
+            .find_duplicates(&task("Task 2", "", &[]), &existing)
+            .await
+            .unwrap();
+        assert!(numbered.is_empty());
+
+        // Embeddings catch rewordings the fuzzy match scores lower
+        let reworded = task("CSV download of the task list", "", &[]);
+        let fuzzy = detector.score(&reworded, &existing[0]);
+        let with_embeddings = DuplicateDetector::new()
+            .with_threshold(0.0)
+            .with_embeddings(Arc::new(TfIdfEmbedder::default()))
+            .find_duplicates(&reworded, &existing)
+            .await
+            .unwrap();
+        assert_eq!(with_embeddings[0].task.id, existing[0].id);
+        assert!(with_embeddings[0].similarity > fuzzy);
+    }
+
+    /// One dimension, whether the text mentions CSV. Records what it embeds
+    /// and, when `short`, drops the last vector.
+    #[derive(Default)]
+    struct CsvEmbeddings {
+        embedded: Mutex<Vec<String>>,
+        short: bool,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for CsvEmbeddings {
+        fn model(&self) -> &str {
+            "csv"
+        }
+
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.embedded.lock().unwrap().extend(texts.iter().cloned());
+            let mut vectors: Vec<Vec<f32>> = texts
+                .iter()
+                .map(|t| vec![t.to_lowercase().contains("csv") as u8 as f32])
+                .collect();
+            if self.short {
+                vectors.pop();
+            }
+            Ok(vectors)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stored_vectors_are_reused() {
+        let pool = init_test_database().await.unwrap();
+        let repository = Repository::new(pool);
+        let existing = vec![
+            task("Export tasks to CSV", "", &[]),
+            task("Dark mode", "Theme support", &[]),
+        ];
+        for t in &existing {
+            repository.tasks.create(t).await.unwrap();
+        }
+        let embeddings = Arc::new(CsvEmbeddings::default());
+        let detector = DuplicateDetector::new()
+            .with_threshold(0.0)
+            .with_embeddings(embeddings.clone())
+            .with_store(repository.embeddings.clone());
+
+        let first = task("CSV download", "", &[]);
+        detector.find_duplicates(&first, &existing).await.unwrap();
+        assert_eq!(embeddings.embedded.lock().unwrap().len(), 3);
+        assert_eq!(repository.embeddings.count().await.unwrap(), 2);
+
+        // Only the new task is embedded once the board's vectors are stored
+        let second = task("Theme picker", "", &[]);
+        let found = detector.find_duplicates(&second, &existing).await.unwrap();
+        assert_eq!(found.len(), 2);
+        let embedded = embeddings.embedded.lock().unwrap().clone();
+        assert_eq!(embedded.len(), 4);
+        assert_eq!(embedded[3], task_summary_source(&second));
+
+        // A provider returning too few vectors is an error, not a panic
+        let short = DuplicateDetector::new().with_embeddings(Arc::new(CsvEmbeddings {
+            short: true,
+            ..Default::default()
+        }));
+        assert!(short.find_duplicates(&second, &existing).await.is_err());
+    }
+}
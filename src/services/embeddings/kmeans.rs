@@ -0,0 +1,184 @@
+use super::{cosine_similarity, normalize};
+
+/// Which cluster each vector belongs to, and the cluster centres.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clustering {
+    pub assignments: Vec<usize>,
+    pub centroids: Vec<Vec<f32>>,
+}
+
+impl Clustering {
+    pub fn len(&self) -> usize {
+        self.centroids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty()
+    }
+
+    /// Indices of the vectors in each cluster.
+    pub fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.centroids.len()];
+        for (index, cluster) in self.assignments.iter().enumerate() {
+            members[*cluster].push(index);
+        }
+        members
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - cosine_similarity(a, b)
+}
+
+fn nearest(vector: &[f32], centroids: &[Vec<f32>]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, distance(vector, c)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Spherical k-means: clusters by cosine distance, so vectors of any
+/// length can be mixed. Seeding is deterministic (the first vector, then
+/// repeatedly the one farthest from every seed), so the same input always
+/// gives the same clusters.
+pub fn kmeans(vectors: &[Vec<f32>], k: usize, max_iterations: usize) -> Clustering {
+    let k = k.clamp(1, vectors.len().max(1));
+    if vectors.is_empty() {
+        return Clustering { assignments: Vec::new(), centroids: Vec::new() };
+    }
+
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let closest = centroids
+                    .iter()
+                    .map(|c| distance(v, c))
+                    .fold(f32::INFINITY, f32::min);
+                (i, closest)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i);
+        centroids.push(vectors[farthest].clone());
+    }
+
+    let dimensions = vectors[0].len();
+    let mut assignments = vec![usize::MAX; vectors.len()];
+    for _ in 0..max_iterations.max(1) {
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0; dimensions];
+            let mut size = 0;
+            for (vector, _) in vectors.iter().zip(&assignments).filter(|(_, a)| **a == cluster) {
+                for (s, x) in sum.iter_mut().zip(vector) {
+                    *s += x;
+                }
+                size += 1;
+            }
+            // An emptied cluster keeps its old centre
+            if size > 0 {
+                normalize(&mut sum);
+                *centroid = sum;
+            }
+        }
+    }
+
+    Clustering { assignments, centroids }
+}
+
+/// Mean silhouette of a clustering, from -1 (wrong) through 0 (no
+/// structure) to 1 (tight, well separated clusters).
+pub fn silhouette(vectors: &[Vec<f32>], clustering: &Clustering) -> f32 {
+    let members = clustering.members();
+    if members.iter().filter(|m| !m.is_empty()).count() < 2 {
+        return 0.0;
+    }
+
+    let mean_distance = |i: usize, others: &[usize]| -> Option<f32> {
+        let others: Vec<usize> = others.iter().copied().filter(|j| *j != i).collect();
+        if others.is_empty() {
+            return None;
+        }
+        Some(others.iter().map(|j| distance(&vectors[i], &vectors[*j])).sum::<f32>() / others.len() as f32)
+    };
+
+    let total: f32 = (0..vectors.len())
+        .map(|i| {
+            let own = clustering.assignments[i];
+            // A vector alone in its cluster scores 0
+            let Some(a) = mean_distance(i, &members[own]) else {
+                return 0.0;
+            };
+            let b = members
+                .iter()
+                .enumerate()
+                .filter(|(c, m)| *c != own && !m.is_empty())
+                .filter_map(|(_, m)| mean_distance(i, m))
+                .fold(f32::INFINITY, f32::min);
+            if a.max(b) == 0.0 { 0.0 } else { (b - a) / a.max(b) }
+        })
+        .sum();
+    total / vectors.len() as f32
+}
+
+/// Group vectors into topics, trying every cluster count up to
+/// `max_clusters` and keeping the one with the best silhouette. Returns a
+/// single cluster when no split beats a silhouette of 0.05.
+pub fn topic_clusters(vectors: &[Vec<f32>], max_clusters: usize) -> Clustering {
+    let mut best = kmeans(vectors, 1, 1);
+    let mut best_score = 0.05;
+    for k in 2..=max_clusters.min(vectors.len().saturating_sub(1)) {
+        let clustering = kmeans(vectors, k, 50);
+        let score = silhouette(vectors, &clustering);
+        if score > best_score {
+            best = clustering;
+            best_score = score;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups() -> Vec<Vec<f32>> {
+        vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.9, 0.0, 0.1],
+            vec![0.0, 1.0, 0.1],
+            vec![0.1, 0.9, 0.0],
+            vec![1.0, 0.0, 0.0],
+        ]
+    }
+
+    #[test]
+    fn test_kmeans_separates_groups() {
+        let vectors = groups();
+        let clustering = kmeans(&vectors, 2, 20);
+        assert_eq!(clustering.len(), 2);
+        let a = clustering.assignments[0];
+        assert_eq!(clustering.assignments, vec![a, a, 1 - a, 1 - a, a]);
+        assert_eq!(kmeans(&vectors, 2, 20), clustering);
+        assert!(silhouette(&vectors, &clustering) > 0.5);
+    }
+
+    #[test]
+    fn test_topic_clusters_picks_count() {
+        let vectors = groups();
+        assert_eq!(topic_clusters(&vectors, 4).len(), 2);
+
+        let same = vec![vec![1.0, 0.0]; 4];
+        assert_eq!(topic_clusters(&same, 3).len(), 1);
+        assert!(topic_clusters(&[], 3).is_empty());
+    }
+}
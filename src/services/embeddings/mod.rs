@@ -0,0 +1,126 @@
+//! Text embeddings for comparing and grouping tasks by topic: remote
+//! embedding models (Ollama, OpenAI-compatible), a local TF-IDF fallback
+//! that needs no model, and the clustering built on them.
+
+mod kmeans;
+mod remote;
+mod tfidf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::domain::app_settings::{AppSettings, LlmProviderKind};
+use crate::services::llm::LlmConfig;
+
+pub use kmeans::{Clustering, kmeans, silhouette, topic_clusters};
+pub use remote::{OllamaEmbeddings, OpenAiEmbeddings};
+pub use tfidf::{TfIdfEmbedder, terms};
+
+/// Turns texts into vectors whose cosine similarity reflects how related
+/// the texts are.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Names the vector space; vectors from different models can't be compared.
+    fn model(&self) -> &str;
+
+    /// True when a text's vector depends on the other texts in the batch,
+    /// as with TF-IDF. Such vectors are recomputed rather than stored.
+    fn is_corpus_relative(&self) -> bool {
+        false
+    }
+
+    /// One vector per text, in order.
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Embeddings for the app settings: the configured provider's embedding
+/// model when one is set and the provider serves embeddings, otherwise
+/// local TF-IDF.
+pub fn embedding_provider_from_settings(settings: &AppSettings) -> Arc<dyn EmbeddingProvider> {
+    let model = settings.llm_embedding_model.trim();
+    if model.is_empty() {
+        return Arc::new(TfIdfEmbedder::default());
+    }
+    let config = LlmConfig::from_settings(settings);
+    match settings.llm_provider {
+        LlmProviderKind::Ollama => Arc::new(OllamaEmbeddings::new(
+            &embeddings_endpoint(settings.llm_provider, &config.endpoint),
+            model,
+        )),
+        LlmProviderKind::OpenAiCompatible => Arc::new(OpenAiEmbeddings::new(
+            &embeddings_endpoint(settings.llm_provider, &config.endpoint),
+            model,
+            config.api_key,
+        )),
+        LlmProviderKind::Anthropic | LlmProviderKind::Replay => Arc::new(TfIdfEmbedder::default()),
+    }
+}
+
+/// The embeddings URL next to a provider's text generation endpoint.
+pub fn embeddings_endpoint(provider: LlmProviderKind, endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    match provider {
+        LlmProviderKind::Ollama => match endpoint.find("/api/") {
+            Some(pos) => format!("{}/api/embed", &endpoint[..pos]),
+            None => format!("{}/api/embed", endpoint),
+        },
+        _ => match endpoint.strip_suffix("/chat/completions") {
+            Some(base) => format!("{}/embeddings", base),
+            None => format!("{}/embeddings", endpoint),
+        },
+    }
+}
+
+/// Cosine similarity of two vectors; 0 when either is all zeros.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Scale `vector` to unit length in place; zero vectors are left alone.
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embeddings_endpoint() {
+        assert_eq!(
+            embeddings_endpoint(LlmProviderKind::Ollama, "http://localhost:11434/api/generate"),
+            "http://localhost:11434/api/embed"
+        );
+        assert_eq!(
+            embeddings_endpoint(
+                LlmProviderKind::OpenAiCompatible,
+                "https://api.openai.com/v1/chat/completions"
+            ),
+            "https://api.openai.com/v1/embeddings"
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+
+        let mut v = vec![3.0, 4.0];
+        normalize(&mut v);
+        assert_eq!(v, vec![0.6, 0.8]);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::EmbeddingProvider;
+use crate::domain::app_settings::LlmProviderKind;
+use crate::services::llm::{LlmError, check_response};
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+/// Ollama's `/api/embed` endpoint.
+pub struct OllamaEmbeddings {
+    endpoint: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl OllamaEmbeddings {
+    pub fn new(endpoint: &str, model: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddings {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(&EmbedRequest { model: &self.model, input: texts })
+            .send()
+            .await?;
+        let body: OllamaResponse = check_response(response).await?.json().await?;
+        Ok(body.embeddings)
+    }
+}
+
+/// Any API that speaks OpenAI's `/embeddings` format.
+pub struct OpenAiEmbeddings {
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiEmbeddings {
+    pub fn new(endpoint: &str, model: &str, api_key: Option<String>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbeddings {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or(LlmError::MissingApiKey(LlmProviderKind::OpenAiCompatible))?;
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .bearer_auth(api_key)
+            .json(&EmbedRequest { model: &self.model, input: texts })
+            .send()
+            .await?;
+        let mut body: OpenAiResponse = check_response(response).await?.json().await?;
+        // Entries carry their input's index and need not arrive in order
+        body.data.sort_by_key(|e| e.index);
+        Ok(body.data.into_iter().map(|e| e.embedding).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::test_server::serve_once;
+
+    #[tokio::test]
+    async fn test_ollama_embeddings() {
+        let (url, request) = serve_once(
+            "application/json",
+            r#"{"model":"nomic-embed-text","embeddings":[[0.1,0.2],[0.3,0.4]]}"#,
+        )
+        .await;
+        let provider = OllamaEmbeddings::new(&url, "nomic-embed-text");
+        let vectors = provider
+            .embed(&["csv export".to_string(), "dark mode".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+        let request = request.await.unwrap();
+        assert!(request.contains(r#""input":["csv export","dark mode"]"#));
+    }
+
+    #[tokio::test]
+    async fn test_openai_embeddings_in_input_order() {
+        let (url, request) = serve_once(
+            "application/json",
+            r#"{"data":[{"index":1,"embedding":[0.3]},{"index":0,"embedding":[0.1]}]}"#,
+        )
+        .await;
+        let provider = OpenAiEmbeddings::new(&url, "text-embedding-3-small", Some("key".to_string()));
+        let vectors = provider
+            .embed(&["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![0.1], vec![0.3]]);
+        assert!(request.await.unwrap().to_lowercase().contains("authorization: bearer key"));
+    }
+}
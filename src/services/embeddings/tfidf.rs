@@ -0,0 +1,143 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use super::{EmbeddingProvider, normalize};
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "add", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "do", "does", "for", "from", "get", "has", "have", "how", "if", "in",
+    "into", "is", "it", "its", "make", "more", "need", "new", "not", "of", "on", "or", "our",
+    "out", "should", "so", "some", "than", "that", "the", "their", "them", "then", "there",
+    "these", "they", "this", "to", "up", "use", "was", "we", "when", "which", "will", "with",
+    "would", "you", "your",
+];
+
+/// The words of `text` that carry meaning: lowercased, without stop words,
+/// numbers or one and two letter words, and with plural `s` dropped.
+pub fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() > 2)
+        .filter(|w| !w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .map(|w| match w.strip_suffix('s') {
+            Some(stem) if stem.len() > 3 && !stem.ends_with('s') => stem.to_string(),
+            _ => w,
+        })
+        .collect()
+}
+
+/// Local embeddings that need no model: TF-IDF weights over the batch,
+/// hashed into a fixed number of dimensions. Texts sharing rare words end
+/// up close together; it knows nothing about synonyms.
+#[derive(Debug, Clone)]
+pub struct TfIdfEmbedder {
+    dimensions: usize,
+}
+
+impl Default for TfIdfEmbedder {
+    fn default() -> Self {
+        Self { dimensions: 512 }
+    }
+}
+
+impl TfIdfEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self { dimensions: dimensions.max(1) }
+    }
+
+    fn bucket(&self, term: &str) -> usize {
+        // FNV-1a, so buckets are stable across runs
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in term.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash % self.dimensions as u64) as usize
+    }
+
+    pub fn vectors(&self, texts: &[String]) -> Vec<Vec<f32>> {
+        let counts: Vec<HashMap<String, usize>> = texts
+            .iter()
+            .map(|text| {
+                let mut counts = HashMap::new();
+                for term in terms(text) {
+                    *counts.entry(term).or_insert(0) += 1;
+                }
+                counts
+            })
+            .collect();
+
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for doc in &counts {
+            for term in doc.keys() {
+                *document_frequency.entry(term.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let documents = texts.len() as f32;
+        counts
+            .iter()
+            .map(|doc| {
+                let mut vector = vec![0.0; self.dimensions];
+                for (term, count) in doc {
+                    let df = document_frequency[term.as_str()] as f32;
+                    let tf = 1.0 + (*count as f32).ln();
+                    let idf = ((1.0 + documents) / (1.0 + df)).ln() + 1.0;
+                    vector[self.bucket(term)] += tf * idf;
+                }
+                normalize(&mut vector);
+                vector
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for TfIdfEmbedder {
+    fn model(&self) -> &str {
+        "tfidf"
+    }
+
+    fn is_corpus_relative(&self) -> bool {
+        true
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(self.vectors(texts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::embeddings::cosine_similarity;
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(
+            terms("Add CSV exports for the 2 reports!"),
+            vec!["csv", "export", "report"]
+        );
+        assert_eq!(terms("Fix class access"), vec!["fix", "class", "access"]);
+    }
+
+    #[test]
+    fn test_shared_words_are_closer() {
+        let texts: Vec<String> = [
+            "Export tasks to CSV",
+            "CSV export of goals",
+            "Dark mode theme colors",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let vectors = TfIdfEmbedder::default().vectors(&texts);
+        assert_eq!(vectors.len(), 3);
+        let related = cosine_similarity(&vectors[0], &vectors[1]);
+        let unrelated = cosine_similarity(&vectors[0], &vectors[2]);
+        assert!(related > 0.3, "related = {}", related);
+        assert!(related > unrelated);
+    }
+}
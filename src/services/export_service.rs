@@ -1,12 +1,17 @@
 use crate::repository::Repository;
 use crate::repository::task_repository::TaskFilters;
-use crate::domain::task::{Task, TaskStatus};
+use crate::domain::task::{Priority, Task, TaskStatus};
+use crate::domain::time_entry::Timesheet;
+use crate::services::calendar_service::CalendarService;
+use crate::services::task_service::{CreatedTask, TaskService};
+use crate::services::time_tracking_service::TimeTrackingService;
 use std::sync::Arc;
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde_json;
 use csv::Writer;
 use std::io::Write;
+use uuid::Uuid;
 
 pub struct ExportService {
     repository: Arc<Repository>,
@@ -47,11 +52,49 @@ impl From<Task> for ExportedTask {
     }
 }
 
+impl ExportedTask {
+    /// A task with this one's fields. It gets a fresh id, so importing
+    /// into the board it was exported from doesn't clash.
+    pub fn into_task(self) -> Task {
+        let mut task = Task::new(self.title, self.description);
+        task.status = match self.status.as_str() {
+            "InProgress" => TaskStatus::InProgress,
+            "Blocked" => TaskStatus::Blocked,
+            "Review" => TaskStatus::Review,
+            "Done" => TaskStatus::Done,
+            "Cancelled" => TaskStatus::Cancelled,
+            _ => TaskStatus::Todo,
+        };
+        task.priority = match self.priority.as_str() {
+            "Low" => Priority::Low,
+            "High" => Priority::High,
+            "Critical" => Priority::Critical,
+            _ => Priority::Medium,
+        };
+        task.created_at = self.created_at;
+        task.updated_at = self.updated_at;
+        task.due_date = self.due_date;
+        task.estimated_hours = self.estimated_hours;
+        task.actual_hours = self.actual_hours;
+        task.tags = self.tags.into_iter().collect();
+        task.assignee = self.assignee;
+        task
+    }
+}
+
 impl ExportService {
     pub fn new(repository: Arc<Repository>) -> Self {
         Self { repository }
     }
     
+    /// Import tasks from the JSON `export_to_json` writes, reporting likely
+    /// duplicates of each imported task.
+    pub async fn import_from_json(&self, json: &str) -> Result<Vec<CreatedTask>> {
+        let exported: Vec<ExportedTask> = serde_json::from_str(json)?;
+        let tasks = exported.into_iter().map(ExportedTask::into_task).collect();
+        TaskService::new(self.repository.clone()).import(tasks).await
+    }
+    
     /// Export tasks to JSON format
     pub async fn export_to_json(&self, filters: TaskFilters) -> Result<String> {
         let tasks = self.repository.tasks.list(filters).await?;
@@ -161,12 +204,27 @@ impl ExportService {
         Ok(output)
     }
     
+    /// Export dated tasks and recurring templates as an iCalendar file
+    pub async fn export_to_ics(&self, filters: TaskFilters) -> Result<String> {
+        CalendarService::new(self.repository.clone()).export_ics(filters).await
+    }
+    
+    /// Export the week containing `date` as a CSV timesheet, for one
+    /// resource or for everyone when `resource_id` is `None`
+    pub async fn export_timesheet_to_csv(&self, resource_id: Option<Uuid>, date: NaiveDate) -> Result<String> {
+        let timesheet = TimeTrackingService::new(self.repository.clone())
+            .weekly_timesheet(resource_id, date)
+            .await?;
+        timesheet_to_csv(&timesheet)
+    }
+    
     /// Save export to file
     pub async fn export_to_file(&self, filters: TaskFilters, format: ExportFormat, path: &str) -> Result<()> {
         let content = match format {
             ExportFormat::Json => self.export_to_json(filters).await?,
             ExportFormat::Csv => self.export_to_csv(filters).await?,
             ExportFormat::Markdown => self.export_to_markdown(filters).await?,
+            ExportFormat::ICalendar => self.export_to_ics(filters).await?,
         };
         
         let mut file = std::fs::File::create(path)?;
@@ -181,6 +239,33 @@ pub enum ExportFormat {
     Json,
     Csv,
     Markdown,
+    ICalendar,
+}
+
+/// One row per task with its hours on each day, then a row of day totals.
+pub fn timesheet_to_csv(timesheet: &Timesheet) -> Result<String> {
+    let mut wtr = Writer::from_writer(vec![]);
+    let hours = |h: f64| format!("{:.2}", h);
+    
+    let mut header = vec!["Task".to_string()];
+    header.extend(timesheet.days().iter().map(|d| d.format("%a %Y-%m-%d").to_string()));
+    header.push("Total".to_string());
+    wtr.write_record(&header)?;
+    
+    for row in &timesheet.rows {
+        let mut record = vec![row.task_title.clone()];
+        record.extend(row.hours.iter().map(|h| hours(*h)));
+        record.push(hours(row.total()));
+        wtr.write_record(&record)?;
+    }
+    
+    let mut totals = vec!["Total".to_string()];
+    totals.extend(timesheet.day_totals().iter().map(|h| hours(*h)));
+    totals.push(hours(timesheet.total()));
+    wtr.write_record(&totals)?;
+    
+    let data = wtr.into_inner()?;
+    Ok(String::from_utf8(data)?)
 }
 
 fn status_to_string(status: TaskStatus) -> &'static str {
@@ -208,36 +293,25 @@ mod tests {
         
         // Create test tasks
         for i in 0..3 {
-            let task = Task {
-                id: Uuid::new_v4(),
-                title: format!("Task {}", i + 1),
-                description: format!("Description for task {}", i + 1),
-                status: match i {
-                    0 => TaskStatus::Todo,
-                    1 => TaskStatus::InProgress,
-                    _ => TaskStatus::Done,
-                },
-                priority: match i {
-                    0 => Priority::High,
-                    1 => Priority::Medium,
-                    _ => Priority::Low,
-                },
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
-                due_date: if i == 0 { Some(Utc::now() + chrono::Duration::days(7)) } else { None },
-                estimated_hours: Some((i + 1) as f32 * 2.0),
-                actual_hours: if i == 2 { Some(5.0) } else { None },
-                assigned_resource_id: None,
-                goal_id: None,
-                parent_task_id: None,
-                tags: {
-                    let mut set = std::collections::HashSet::new();
-                    set.insert(format!("tag{}", i));
-                    set
-                },
-                assignee: if i == 0 { Some("Alice".to_string()) } else { None },
-                position: crate::domain::task::Position { x: 0.0, y: 0.0 },
+            let mut task = Task::new(
+                format!("Task {}", i + 1),
+                format!("Description for task {}", i + 1),
+            );
+            task.status = match i {
+                0 => TaskStatus::Todo,
+                1 => TaskStatus::InProgress,
+                _ => TaskStatus::Done,
             };
+            task.priority = match i {
+                0 => Priority::High,
+                1 => Priority::Medium,
+                _ => Priority::Low,
+            };
+            task.due_date = if i == 0 { Some(Utc::now() + chrono::Duration::days(7)) } else { None };
+            task.estimated_hours = Some((i + 1) as f32 * 2.0);
+            task.actual_hours = if i == 2 { Some(5.0) } else { None };
+            task.tags.insert(format!("tag{}", i));
+            task.assignee = if i == 0 { Some("Alice".to_string()) } else { None };
             repo.tasks.create(&task).await.unwrap();
         }
         
@@ -333,4 +407,42 @@ mod tests {
         assert_eq!(parsed.len(), 1);
         assert_eq!(parsed[0].title, "Task 1");
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_import_from_json_reports_duplicates() {
+        let service = setup_test_service().await;
+        let json = service.export_to_json(TaskFilters::default()).await.unwrap();
+        
+        let imported = service.import_from_json(&json).await.unwrap();
+        assert_eq!(imported.len(), 3);
+        let task_1 = imported.iter().find(|c| c.task.title == "Task 1").unwrap();
+        assert_eq!(task_1.task.priority, Priority::High);
+        assert!(task_1.task.tags.contains("tag0"));
+        assert_eq!(task_1.duplicates[0].task.title, "Task 1");
+        assert_ne!(task_1.duplicates[0].task.id, task_1.task.id);
+        
+        let all = service.repository.tasks.list(TaskFilters::default()).await.unwrap();
+        assert_eq!(all.len(), 6);
+    }
+    
+    #[test]
+    fn test_timesheet_to_csv() {
+        use crate::domain::time_entry::TimesheetRow;
+        
+        let timesheet = Timesheet {
+            resource_id: None,
+            week_start: NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            rows: vec![TimesheetRow {
+                task_id: Uuid::new_v4(),
+                task_title: "Deploy, then verify".to_string(),
+                hours: [1.5, 0.0, 2.25, 0.0, 0.0, 0.0, 0.0],
+            }],
+        };
+        
+        let csv = timesheet_to_csv(&timesheet).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Task,Mon 2025-03-03,Tue 2025-03-04,Wed 2025-03-05,Thu 2025-03-06,Fri 2025-03-07,Sat 2025-03-08,Sun 2025-03-09,Total");
+        assert_eq!(lines[1], "\"Deploy, then verify\",1.50,0.00,2.25,0.00,0.00,0.00,0.00,3.75");
+        assert_eq!(lines[2], "Total,1.50,0.00,2.25,0.00,0.00,0.00,0.00,3.75");
+    }
+}
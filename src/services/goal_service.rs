@@ -1,5 +1,6 @@
 use crate::domain::goal::Goal;
 use crate::repository::Repository;
+use crate::services::task_service::TaskService;
 use anyhow::Result;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -18,8 +19,12 @@ impl GoalService {
         Ok(goal)
     }
 
+    /// Save `goal` and recompute formulas of tasks related to it.
     pub async fn update(&self, goal: Goal) -> Result<Goal> {
         self.repository.goals.update(&goal).await?;
+        TaskService::new(self.repository.clone())
+            .recompute_related(goal.id)
+            .await?;
         Ok(goal)
     }
 
@@ -27,8 +32,15 @@ impl GoalService {
         self.repository.goals.get(id).await
     }
 
+    /// Delete the goal and recompute formulas of tasks related to it.
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        self.repository.goals.delete(id).await
+        let deleted = self.repository.goals.delete(id).await?;
+        if deleted {
+            TaskService::new(self.repository.clone())
+                .recompute_related(id)
+                .await?;
+        }
+        Ok(deleted)
     }
 
     pub async fn list_all(&self) -> Result<Vec<Goal>> {
@@ -0,0 +1,751 @@
+//! In-process stand-in for a hosting service, used by tests and offline demos.
+//!
+//! Serves the subset of the GitHub API used by `GitHubClient` at the root and
+//! the Gitea equivalent under `/api/v1`, backed by in-memory state that tests
+//! can inspect and drive (set check results, add reviews, merge or close PRs).
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::{
+    CheckRun, CheckState, GitHubClient, GiteaClient, NewPullRequest, PrComment, PullRequest,
+    PullRequestRef, PullRequestState, RepoRef, Review, ReviewState,
+};
+
+/// Author recorded for pull requests, reviews and comments made through the API.
+const API_USER: &str = "plon";
+
+/// A request received by the fake server.
+#[derive(Debug, Clone)]
+pub struct FakeRequest {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: Value,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dialect {
+    GitHub,
+    Gitea,
+}
+
+#[derive(Default)]
+struct RepoState {
+    pulls: Vec<PullRequest>,
+    checks: HashMap<String, Vec<CheckRun>>,
+    reviews: HashMap<u64, Vec<Review>>,
+    comments: HashMap<u64, Vec<PrComment>>,
+}
+
+type MergeHook = Arc<dyn Fn(&RepoRef, &PullRequest) + Send + Sync>;
+
+#[derive(Default)]
+struct ServerState {
+    repos: HashMap<RepoRef, RepoState>,
+    next_id: u64,
+    requests: Vec<FakeRequest>,
+    on_merge: Option<MergeHook>,
+}
+
+pub struct FakeHostingServer {
+    base_url: String,
+    state: Arc<Mutex<ServerState>>,
+    handle: JoinHandle<()>,
+}
+
+impl FakeHostingServer {
+    /// Bind to a free local port and start serving.
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let server_state = state.clone();
+        let server_url = base_url.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                let base_url = server_url.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, &state, &base_url).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            base_url,
+            state,
+            handle,
+        })
+    }
+
+    /// Address to use as a GitHub API URL or Gitea base URL.
+    pub fn url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn github_client(&self) -> GitHubClient {
+        GitHubClient::with_api_url(&self.base_url, Some("fake-token".to_string()))
+    }
+
+    pub fn gitea_client(&self) -> GiteaClient {
+        GiteaClient::new(&self.base_url, Some("fake-token".to_string()))
+    }
+
+    /// Seed an open pull request, as if `author` had opened it on the host.
+    pub fn open_pull_request(
+        &self,
+        repo: &RepoRef,
+        pr: &NewPullRequest,
+        author: &str,
+    ) -> PullRequest {
+        let mut state = self.state.lock().unwrap();
+        create_pull(
+            &mut state,
+            &self.base_url,
+            Dialect::GitHub,
+            repo,
+            pr,
+            author,
+        )
+    }
+
+    /// Current state of a pull request, with its GitHub-style web URL.
+    pub fn pull_request(&self, pr: &PullRequestRef) -> Option<PullRequest> {
+        let state = self.state.lock().unwrap();
+        find_pull(&state, pr).cloned()
+    }
+
+    pub fn pull_requests(&self, repo: &RepoRef) -> Vec<PullRequest> {
+        let state = self.state.lock().unwrap();
+        state
+            .repos
+            .get(repo)
+            .map(|r| r.pulls.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace the check results reported for the pull request's head commit.
+    pub fn set_checks(&self, pr: &PullRequestRef, checks: Vec<CheckRun>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(sha) = find_pull(&state, pr).map(|p| p.head_sha.clone()) {
+            repo_state(&mut state, &pr.repo_ref())
+                .checks
+                .insert(sha, checks);
+        }
+    }
+
+    pub fn add_review(&self, pr: &PullRequestRef, author: &str, review: ReviewState, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        push_review(&mut state, pr, author, review, body);
+    }
+
+    pub fn add_comment(&self, pr: &PullRequestRef, author: &str, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        push_comment(&mut state, pr, author, body);
+    }
+
+    pub fn set_mergeable(&self, pr: &PullRequestRef, mergeable: Option<bool>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(pull) = find_pull_mut(&mut state, pr) {
+            pull.mergeable = mergeable;
+        }
+    }
+
+    /// Merge or close a pull request from the host's side.
+    pub fn set_state(&self, pr: &PullRequestRef, pr_state: PullRequestState) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(pull) = find_pull_mut(&mut state, pr) {
+            pull.state = pr_state;
+        }
+    }
+
+    /// Run `hook` whenever a pull request is merged through the API, e.g. to
+    /// land the branch in a local bare repository standing in for the remote.
+    pub fn on_merge(&self, hook: impl Fn(&RepoRef, &PullRequest) + Send + Sync + 'static) {
+        self.state.lock().unwrap().on_merge = Some(Arc::new(hook));
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<FakeRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for FakeHostingServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn repo_state<'a>(state: &'a mut ServerState, repo: &RepoRef) -> &'a mut RepoState {
+    state.repos.entry(repo.clone()).or_default()
+}
+
+fn find_pull<'a>(state: &'a ServerState, pr: &PullRequestRef) -> Option<&'a PullRequest> {
+    state
+        .repos
+        .get(&pr.repo_ref())?
+        .pulls
+        .iter()
+        .find(|p| p.number == pr.number)
+}
+
+fn find_pull_mut<'a>(
+    state: &'a mut ServerState,
+    pr: &PullRequestRef,
+) -> Option<&'a mut PullRequest> {
+    state
+        .repos
+        .get_mut(&pr.repo_ref())?
+        .pulls
+        .iter_mut()
+        .find(|p| p.number == pr.number)
+}
+
+fn next_id(state: &mut ServerState) -> u64 {
+    state.next_id += 1;
+    state.next_id
+}
+
+fn pull_url(base_url: &str, dialect: Dialect, repo: &RepoRef, number: u64) -> String {
+    let segment = match dialect {
+        Dialect::GitHub => "pull",
+        Dialect::Gitea => "pulls",
+    };
+    format!(
+        "{}/{}/{}/{}/{}",
+        base_url, repo.owner, repo.repo, segment, number
+    )
+}
+
+fn create_pull(
+    state: &mut ServerState,
+    base_url: &str,
+    dialect: Dialect,
+    repo: &RepoRef,
+    pr: &NewPullRequest,
+    author: &str,
+) -> PullRequest {
+    let id = next_id(state);
+    let repo_state = repo_state(state, repo);
+    let number = repo_state.pulls.len() as u64 + 1;
+    let pull = PullRequest {
+        number,
+        url: pull_url(base_url, dialect, repo, number),
+        title: pr.title.clone(),
+        body: pr.body.clone(),
+        head: pr.head.clone(),
+        head_sha: format!("{:040x}", id),
+        base: pr.base.clone(),
+        author: author.to_string(),
+        state: PullRequestState::Open,
+        mergeable: Some(true),
+    };
+    repo_state.pulls.push(pull.clone());
+    pull
+}
+
+fn push_review(
+    state: &mut ServerState,
+    pr: &PullRequestRef,
+    author: &str,
+    review: ReviewState,
+    body: &str,
+) {
+    let id = next_id(state);
+    repo_state(state, &pr.repo_ref())
+        .reviews
+        .entry(pr.number)
+        .or_default()
+        .push(Review {
+            id,
+            author: author.to_string(),
+            state: review,
+            body: body.to_string(),
+            submitted_at: Some(Utc::now()),
+        });
+}
+
+fn push_comment(
+    state: &mut ServerState,
+    pr: &PullRequestRef,
+    author: &str,
+    body: &str,
+) -> PrComment {
+    let id = next_id(state);
+    let comment = PrComment {
+        id,
+        author: author.to_string(),
+        body: body.to_string(),
+        created_at: Utc::now(),
+    };
+    repo_state(state, &pr.repo_ref())
+        .comments
+        .entry(pr.number)
+        .or_default()
+        .push(comment.clone());
+    comment
+}
+
+fn pull_json(pull: &PullRequest, base_url: &str, dialect: Dialect, repo: &RepoRef) -> Value {
+    let merged = pull.state == PullRequestState::Merged;
+    json!({
+        "number": pull.number,
+        "html_url": pull_url(base_url, dialect, repo, pull.number),
+        "title": pull.title,
+        "body": pull.body,
+        "head": { "ref": pull.head, "sha": pull.head_sha },
+        "base": { "ref": pull.base, "sha": "" },
+        "user": { "login": pull.author },
+        "state": if pull.state == PullRequestState::Open { "open" } else { "closed" },
+        "merged": merged,
+        "merged_at": if merged { Some(Utc::now().to_rfc3339()) } else { None },
+        "mergeable": pull.mergeable,
+    })
+}
+
+fn review_json(review: &Review, dialect: Dialect) -> Value {
+    let state = match (review.state, dialect) {
+        (ReviewState::Approved, _) => "APPROVED",
+        (ReviewState::ChangesRequested, Dialect::GitHub) => "CHANGES_REQUESTED",
+        (ReviewState::ChangesRequested, Dialect::Gitea) => "REQUEST_CHANGES",
+        (ReviewState::Commented, Dialect::GitHub) => "COMMENTED",
+        (ReviewState::Commented, Dialect::Gitea) => "COMMENT",
+        (ReviewState::Pending, _) => "PENDING",
+        (ReviewState::Dismissed, _) => "DISMISSED",
+    };
+    json!({
+        "id": review.id,
+        "user": { "login": review.author },
+        "state": state,
+        "body": review.body,
+        "submitted_at": review.submitted_at.map(|t| t.to_rfc3339()),
+    })
+}
+
+fn comment_json(comment: &PrComment) -> Value {
+    json!({
+        "id": comment.id,
+        "user": { "login": comment.author },
+        "body": comment.body,
+        "created_at": comment.created_at.to_rfc3339(),
+    })
+}
+
+fn check_json(check: &CheckRun, dialect: Dialect) -> Value {
+    match dialect {
+        Dialect::GitHub => {
+            let (status, conclusion) = match check.state {
+                CheckState::Pending => ("in_progress", None),
+                CheckState::Success => ("completed", Some("success")),
+                CheckState::Failure => ("completed", Some("failure")),
+            };
+            json!({
+                "name": check.name,
+                "status": status,
+                "conclusion": conclusion,
+                "details_url": check.details_url,
+            })
+        }
+        Dialect::Gitea => {
+            let status = match check.state {
+                CheckState::Pending => "pending",
+                CheckState::Success => "success",
+                CheckState::Failure => "failure",
+            };
+            json!({
+                "context": check.name,
+                "status": status,
+                "target_url": check.details_url,
+            })
+        }
+    }
+}
+
+fn review_from_event(event: &str) -> ReviewState {
+    match event {
+        "APPROVE" | "APPROVED" => ReviewState::Approved,
+        "REQUEST_CHANGES" => ReviewState::ChangesRequested,
+        _ => ReviewState::Commented,
+    }
+}
+
+fn not_found() -> (u16, Value) {
+    (404, json!({ "message": "Not Found" }))
+}
+
+/// Route one request against the in-memory state.
+fn route(
+    state: &mut ServerState,
+    base_url: &str,
+    method: &str,
+    path: &str,
+    body: &Value,
+) -> (u16, Value) {
+    let (dialect, path) = match path.strip_prefix("/api/v1") {
+        Some(rest) => (Dialect::Gitea, rest),
+        None => (Dialect::GitHub, path),
+    };
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let ["repos", owner, repo, rest @ ..] = segments.as_slice() else {
+        return not_found();
+    };
+    let repo = RepoRef::new(owner, repo);
+    let text = |key: &str| {
+        body.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    match (method, rest) {
+        ("POST", ["pulls"]) => {
+            let new_pr = NewPullRequest {
+                title: text("title"),
+                body: text("body"),
+                head: text("head"),
+                base: text("base"),
+            };
+            let open_duplicate = state.repos.get(&repo).is_some_and(|r| {
+                r.pulls
+                    .iter()
+                    .any(|p| p.head == new_pr.head && p.state == PullRequestState::Open)
+            });
+            if open_duplicate {
+                return (
+                    422,
+                    json!({ "message": format!("A pull request already exists for {}", new_pr.head) }),
+                );
+            }
+            let pull = create_pull(state, base_url, dialect, &repo, &new_pr, API_USER);
+            (201, pull_json(&pull, base_url, dialect, &repo))
+        }
+        ("GET", ["pulls", number]) => {
+            let Ok(number) = number.parse() else {
+                return not_found();
+            };
+            match find_pull(state, &repo.pull_request(number)) {
+                Some(pull) => (200, pull_json(pull, base_url, dialect, &repo)),
+                None => not_found(),
+            }
+        }
+        ("GET", ["commits", sha, "check-runs"]) if dialect == Dialect::GitHub => {
+            let runs: Vec<Value> = state
+                .repos
+                .get(&repo)
+                .and_then(|r| r.checks.get(*sha))
+                .map(|checks| checks.iter().map(|c| check_json(c, dialect)).collect())
+                .unwrap_or_default();
+            (
+                200,
+                json!({ "total_count": runs.len(), "check_runs": runs }),
+            )
+        }
+        ("GET", ["commits", sha, "status"]) if dialect == Dialect::Gitea => {
+            let statuses: Vec<Value> = state
+                .repos
+                .get(&repo)
+                .and_then(|r| r.checks.get(*sha))
+                .map(|checks| checks.iter().map(|c| check_json(c, dialect)).collect())
+                .unwrap_or_default();
+            (200, json!({ "statuses": statuses }))
+        }
+        (_, ["pulls", number, action]) | (_, ["issues", number, action]) => {
+            let Ok(number) = number.parse() else {
+                return not_found();
+            };
+            let pr = repo.pull_request(number);
+            if find_pull(state, &pr).is_none() {
+                return not_found();
+            }
+            pull_action(state, dialect, method, rest[0], action, &pr, body)
+        }
+        _ => not_found(),
+    }
+}
+
+fn pull_action(
+    state: &mut ServerState,
+    dialect: Dialect,
+    method: &str,
+    kind: &str,
+    action: &str,
+    pr: &PullRequestRef,
+    body: &Value,
+) -> (u16, Value) {
+    let text = |key: &str| {
+        body.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let merge_method = match dialect {
+        Dialect::GitHub => "PUT",
+        Dialect::Gitea => "POST",
+    };
+
+    match (method, kind, action) {
+        ("GET", "pulls", "reviews") => {
+            let reviews: Vec<Value> = state
+                .repos
+                .get(&pr.repo_ref())
+                .and_then(|r| r.reviews.get(&pr.number))
+                .map(|reviews| reviews.iter().map(|r| review_json(r, dialect)).collect())
+                .unwrap_or_default();
+            (200, Value::Array(reviews))
+        }
+        ("POST", "pulls", "reviews") => {
+            push_review(
+                state,
+                pr,
+                API_USER,
+                review_from_event(&text("event")),
+                &text("body"),
+            );
+            (200, json!({}))
+        }
+        (m, "pulls", "merge") if m == merge_method => {
+            let pull = find_pull_mut(state, pr).expect("checked by caller");
+            if pull.state != PullRequestState::Open || pull.mergeable == Some(false) {
+                return (405, json!({ "message": "Pull Request is not mergeable" }));
+            }
+            pull.state = PullRequestState::Merged;
+            let merged = pull.clone();
+            if let Some(hook) = state.on_merge.clone() {
+                hook(&pr.repo_ref(), &merged);
+            }
+            (
+                200,
+                json!({ "merged": true, "message": "Pull Request successfully merged" }),
+            )
+        }
+        ("GET", "issues", "comments") => {
+            let comments: Vec<Value> = state
+                .repos
+                .get(&pr.repo_ref())
+                .and_then(|r| r.comments.get(&pr.number))
+                .map(|comments| comments.iter().map(comment_json).collect())
+                .unwrap_or_default();
+            (200, Value::Array(comments))
+        }
+        ("POST", "issues", "comments") => {
+            let comment = push_comment(state, pr, API_USER, &text("body"));
+            (201, comment_json(&comment))
+        }
+        _ => not_found(),
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    state: &Mutex<ServerState>,
+    base_url: &str,
+) -> Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "authorization" => authorization = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    while buffer.len() < header_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body: Value = serde_json::from_slice(&buffer[header_end..]).unwrap_or(Value::Null);
+
+    let (status, response) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(FakeRequest {
+            method: method.clone(),
+            path: path.clone(),
+            authorization,
+            body: body.clone(),
+        });
+        route(&mut state, base_url, &method, &path, &body)
+    };
+
+    let payload = response.to_string();
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Unprocessable Entity",
+    };
+    let reply = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        payload.len(),
+        payload
+    );
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::hosting::{HostingClient, MergeMethod, combined_check_state};
+
+    fn new_pr(head: &str) -> NewPullRequest {
+        NewPullRequest {
+            title: "Add feature".to_string(),
+            body: "Implements the feature".to_string(),
+            head: head.to_string(),
+            base: "main".to_string(),
+        }
+    }
+
+    async fn exercise_client(server: &FakeHostingServer, client: &dyn HostingClient) {
+        let repo = RepoRef::new("owner", "repo");
+        let created = client
+            .create_pull_request(&repo, &new_pr("feature/a"))
+            .await
+            .unwrap();
+        assert_eq!(created.state, PullRequestState::Open);
+        assert_eq!(created.head, "feature/a");
+
+        let pr = PullRequestRef::parse_url(&created.url).unwrap();
+        assert_eq!(pr, repo.pull_request(created.number));
+
+        // A second open PR for the same branch is rejected with the host's message
+        let duplicate = client
+            .create_pull_request(&repo, &new_pr("feature/a"))
+            .await
+            .unwrap_err();
+        assert!(duplicate.to_string().contains("already exists"));
+
+        server.set_checks(
+            &pr,
+            vec![
+                CheckRun {
+                    name: "build".to_string(),
+                    state: CheckState::Success,
+                    details_url: None,
+                },
+                CheckRun {
+                    name: "test".to_string(),
+                    state: CheckState::Failure,
+                    details_url: Some("https://ci.example/1".to_string()),
+                },
+            ],
+        );
+        let checks = client.checks(&pr).await.unwrap();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(combined_check_state(&checks), CheckState::Failure);
+
+        server.add_review(&pr, "alice", ReviewState::ChangesRequested, "Needs tests");
+        client
+            .submit_review(&pr, ReviewState::Approved, "LGTM")
+            .await
+            .unwrap();
+        let reviews = client.reviews(&pr).await.unwrap();
+        let states: Vec<_> = reviews
+            .iter()
+            .map(|r| (r.author.as_str(), r.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("alice", ReviewState::ChangesRequested),
+                (API_USER, ReviewState::Approved)
+            ]
+        );
+
+        client.add_comment(&pr, "Rebased on main").await.unwrap();
+        let comments = client.comments(&pr).await.unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].body, "Rebased on main");
+
+        server.set_mergeable(&pr, Some(false));
+        assert!(client.merge(&pr, MergeMethod::Squash).await.is_err());
+        server.set_mergeable(&pr, Some(true));
+        client.merge(&pr, MergeMethod::Squash).await.unwrap();
+        assert_eq!(
+            client.get_pull_request(&pr).await.unwrap().state,
+            PullRequestState::Merged
+        );
+
+        let missing = client
+            .get_pull_request(&repo.pull_request(99))
+            .await
+            .unwrap_err();
+        assert!(missing.to_string().contains("404"));
+    }
+
+    #[tokio::test]
+    async fn test_github_client_against_fake_server() {
+        let server = FakeHostingServer::start().await.unwrap();
+        exercise_client(&server, &server.github_client()).await;
+
+        let requests = server.requests();
+        assert!(requests.iter().all(|r| !r.path.starts_with("/api/v1")));
+        assert!(
+            requests
+                .iter()
+                .any(|r| r.method == "PUT" && r.path.ends_with("/merge"))
+        );
+        assert_eq!(
+            requests[0].authorization.as_deref(),
+            Some("Bearer fake-token")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gitea_client_against_fake_server() {
+        let server = FakeHostingServer::start().await.unwrap();
+        exercise_client(&server, &server.gitea_client()).await;
+
+        let requests = server.requests();
+        assert!(requests.iter().all(|r| r.path.starts_with("/api/v1/")));
+        let merge = requests
+            .iter()
+            .find(|r| r.path.ends_with("/merge"))
+            .unwrap();
+        assert_eq!(merge.method, "POST");
+        assert_eq!(merge.body["Do"], "squash");
+        assert_eq!(
+            requests[0].authorization.as_deref(),
+            Some("token fake-token")
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::wire::{ApiComment, ApiPullRequest, ApiReview};
+use super::{
+    CheckRun, CheckState, HostingClient, MergeMethod, NewPullRequest, PrComment, PullRequest,
+    PullRequestRef, RepoRef, Review, ReviewState, check_response,
+};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// GitHub REST v3 client; also works against GitHub Enterprise via `with_api_url`.
+pub struct GitHubClient {
+    http: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CheckRunsResponse {
+    check_runs: Vec<ApiCheckRun>,
+}
+
+#[derive(Deserialize)]
+struct ApiCheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+    details_url: Option<String>,
+}
+
+impl GitHubClient {
+    pub fn new(token: Option<String>) -> Self {
+        Self::with_api_url(GITHUB_API_URL, token)
+    }
+
+    pub fn with_api_url(api_url: &str, token: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut builder = self
+            .http
+            .request(method, format!("{}{}", self.api_url, path))
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "plon");
+        if let Some(token) = &self.token {
+            builder = builder.bearer_auth(token);
+        }
+        builder
+    }
+
+    fn repo_path(owner: &str, repo: &str) -> String {
+        format!("/repos/{}/{}", owner, repo)
+    }
+
+    fn pr_path(pr: &PullRequestRef) -> String {
+        format!(
+            "{}/pulls/{}",
+            Self::repo_path(&pr.owner, &pr.repo),
+            pr.number
+        )
+    }
+}
+
+fn check_state(run: &ApiCheckRun) -> CheckState {
+    if run.status != "completed" {
+        return CheckState::Pending;
+    }
+    match run.conclusion.as_deref() {
+        Some("success" | "neutral" | "skipped") => CheckState::Success,
+        _ => CheckState::Failure,
+    }
+}
+
+fn review_state(state: &str) -> ReviewState {
+    match state {
+        "APPROVED" => ReviewState::Approved,
+        "CHANGES_REQUESTED" => ReviewState::ChangesRequested,
+        "PENDING" => ReviewState::Pending,
+        "DISMISSED" => ReviewState::Dismissed,
+        _ => ReviewState::Commented,
+    }
+}
+
+fn review_event(state: ReviewState) -> &'static str {
+    match state {
+        ReviewState::Approved => "APPROVE",
+        ReviewState::ChangesRequested => "REQUEST_CHANGES",
+        _ => "COMMENT",
+    }
+}
+
+#[async_trait]
+impl HostingClient for GitHubClient {
+    async fn create_pull_request(
+        &self,
+        repo: &RepoRef,
+        pr: &NewPullRequest,
+    ) -> Result<PullRequest> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!("{}/pulls", Self::repo_path(&repo.owner, &repo.repo)),
+            )
+            .json(&json!({
+                "title": pr.title,
+                "body": pr.body,
+                "head": pr.head,
+                "base": pr.base,
+            }))
+            .send()
+            .await?;
+        let created: ApiPullRequest = check_response(response).await?.json().await?;
+        Ok(created.into())
+    }
+
+    async fn get_pull_request(&self, pr: &PullRequestRef) -> Result<PullRequest> {
+        let response = self
+            .request(reqwest::Method::GET, &Self::pr_path(pr))
+            .send()
+            .await?;
+        let pull: ApiPullRequest = check_response(response).await?.json().await?;
+        Ok(pull.into())
+    }
+
+    async fn checks(&self, pr: &PullRequestRef) -> Result<Vec<CheckRun>> {
+        let head_sha = self.get_pull_request(pr).await?.head_sha;
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!(
+                    "{}/commits/{}/check-runs",
+                    Self::repo_path(&pr.owner, &pr.repo),
+                    head_sha
+                ),
+            )
+            .send()
+            .await?;
+        let runs: CheckRunsResponse = check_response(response).await?.json().await?;
+        Ok(runs
+            .check_runs
+            .iter()
+            .map(|run| CheckRun {
+                name: run.name.clone(),
+                state: check_state(run),
+                details_url: run.details_url.clone(),
+            })
+            .collect())
+    }
+
+    async fn reviews(&self, pr: &PullRequestRef) -> Result<Vec<Review>> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!("{}/reviews", Self::pr_path(pr)),
+            )
+            .send()
+            .await?;
+        let reviews: Vec<ApiReview> = check_response(response).await?.json().await?;
+        Ok(reviews
+            .into_iter()
+            .map(|r| r.into_review(review_state))
+            .collect())
+    }
+
+    async fn submit_review(
+        &self,
+        pr: &PullRequestRef,
+        state: ReviewState,
+        body: &str,
+    ) -> Result<()> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!("{}/reviews", Self::pr_path(pr)),
+            )
+            .json(&json!({ "event": review_event(state), "body": body }))
+            .send()
+            .await?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    async fn merge(&self, pr: &PullRequestRef, method: MergeMethod) -> Result<()> {
+        let merge_method = match method {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+        let response = self
+            .request(
+                reqwest::Method::PUT,
+                &format!("{}/merge", Self::pr_path(pr)),
+            )
+            .json(&json!({ "merge_method": merge_method }))
+            .send()
+            .await?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    async fn comments(&self, pr: &PullRequestRef) -> Result<Vec<PrComment>> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!(
+                    "{}/issues/{}/comments",
+                    Self::repo_path(&pr.owner, &pr.repo),
+                    pr.number
+                ),
+            )
+            .send()
+            .await?;
+        let comments: Vec<ApiComment> = check_response(response).await?.json().await?;
+        Ok(comments.into_iter().map(Into::into).collect())
+    }
+
+    async fn add_comment(&self, pr: &PullRequestRef, body: &str) -> Result<PrComment> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!(
+                    "{}/issues/{}/comments",
+                    Self::repo_path(&pr.owner, &pr.repo),
+                    pr.number
+                ),
+            )
+            .json(&json!({ "body": body }))
+            .send()
+            .await?;
+        let comment: ApiComment = check_response(response).await?.json().await?;
+        Ok(comment.into())
+    }
+}
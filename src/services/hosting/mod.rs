@@ -0,0 +1,312 @@
+//! Typed clients for Git hosting APIs (pull requests, checks, reviews, comments).
+
+mod fake_server;
+mod gitea;
+mod github;
+mod wire;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::domain::claude_code::{ClaudeCodeConfig, HostingProvider};
+
+pub use fake_server::{FakeHostingServer, FakeRequest};
+pub use gitea::GiteaClient;
+pub use github::GitHubClient;
+
+/// A repository on the hosting service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepoRef {
+    pub owner: String,
+    pub repo: String,
+}
+
+/// A pull request, identified by repository and number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PullRequestRef {
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewPullRequest {
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PullRequestState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PullRequest {
+    pub number: u64,
+    pub url: String,
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub head_sha: String,
+    pub base: String,
+    pub author: String,
+    pub state: PullRequestState,
+    /// `None` while the host is still computing it
+    pub mergeable: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckRun {
+    pub name: String,
+    pub state: CheckState,
+    pub details_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    Commented,
+    Pending,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub id: u64,
+    pub author: String,
+    pub state: ReviewState,
+    pub body: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrComment {
+    pub id: u64,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeMethod {
+    Merge,
+    #[default]
+    Squash,
+    Rebase,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostingError {
+    InvalidPullRequestUrl(String),
+    /// The host answered with a non-success status
+    Api {
+        status: u16,
+        message: String,
+    },
+}
+
+impl fmt::Display for HostingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPullRequestUrl(url) => write!(f, "Not a pull request URL: {}", url),
+            Self::Api { status, message } => write!(f, "Hosting API error {}: {}", status, message),
+        }
+    }
+}
+
+impl std::error::Error for HostingError {}
+
+impl RepoRef {
+    pub fn new(owner: &str, repo: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        }
+    }
+
+    pub fn from_config(config: &ClaudeCodeConfig) -> Self {
+        Self::new(&config.github_owner, &config.github_repo)
+    }
+
+    pub fn pull_request(&self, number: u64) -> PullRequestRef {
+        PullRequestRef {
+            owner: self.owner.clone(),
+            repo: self.repo.clone(),
+            number,
+        }
+    }
+}
+
+impl PullRequestRef {
+    /// Parse a pull request web URL from any host, e.g.
+    /// `https://github.example.com/owner/repo/pull/12` or
+    /// `https://git.example.com/forgejo/owner/repo/pulls/12`.
+    pub fn parse_url(url: &str) -> Result<Self, HostingError> {
+        let invalid = || HostingError::InvalidPullRequestUrl(url.to_string());
+        let parsed = reqwest::Url::parse(url).map_err(|_| invalid())?;
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .ok_or_else(invalid)?
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let marker = segments
+            .iter()
+            .rposition(|s| *s == "pull" || *s == "pulls")
+            .filter(|&i| i >= 2)
+            .ok_or_else(invalid)?;
+        let number = segments
+            .get(marker + 1)
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            owner: segments[marker - 2].to_string(),
+            repo: segments[marker - 1].to_string(),
+            number,
+        })
+    }
+
+    pub fn repo_ref(&self) -> RepoRef {
+        RepoRef::new(&self.owner, &self.repo)
+    }
+}
+
+impl fmt::Display for PullRequestRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}#{}", self.owner, self.repo, self.number)
+    }
+}
+
+/// Overall state of a set of checks: failed if any failed, pending if any
+/// are still running, otherwise successful.
+pub fn combined_check_state(checks: &[CheckRun]) -> CheckState {
+    if checks.iter().any(|c| c.state == CheckState::Failure) {
+        CheckState::Failure
+    } else if checks.iter().any(|c| c.state == CheckState::Pending) {
+        CheckState::Pending
+    } else {
+        CheckState::Success
+    }
+}
+
+/// Pull request operations on a Git hosting service.
+#[async_trait]
+pub trait HostingClient: Send + Sync {
+    async fn create_pull_request(&self, repo: &RepoRef, pr: &NewPullRequest)
+    -> Result<PullRequest>;
+
+    async fn get_pull_request(&self, pr: &PullRequestRef) -> Result<PullRequest>;
+
+    /// CI results for the pull request's head commit.
+    async fn checks(&self, pr: &PullRequestRef) -> Result<Vec<CheckRun>>;
+
+    async fn reviews(&self, pr: &PullRequestRef) -> Result<Vec<Review>>;
+
+    /// Submit a review; `state` is one of `Approved`, `ChangesRequested` or `Commented`.
+    async fn submit_review(
+        &self,
+        pr: &PullRequestRef,
+        state: ReviewState,
+        body: &str,
+    ) -> Result<()>;
+
+    async fn merge(&self, pr: &PullRequestRef, method: MergeMethod) -> Result<()>;
+
+    async fn comments(&self, pr: &PullRequestRef) -> Result<Vec<PrComment>>;
+
+    async fn add_comment(&self, pr: &PullRequestRef, body: &str) -> Result<PrComment>;
+}
+
+/// Client for the host configured in `config`, authenticated with its token.
+pub fn client_from_config(config: &ClaudeCodeConfig) -> Arc<dyn HostingClient> {
+    let token = config.github_token.clone();
+    match &config.hosting_provider {
+        HostingProvider::GitHub { api_url } => match api_url {
+            Some(api_url) => Arc::new(GitHubClient::with_api_url(api_url, token)),
+            None => Arc::new(GitHubClient::new(token)),
+        },
+        HostingProvider::Gitea { base_url } => Arc::new(GiteaClient::new(base_url, token)),
+    }
+}
+
+/// Turn a non-success response into a `HostingError::Api`.
+async fn check_response(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(String::from))
+        .unwrap_or(body);
+    Err(HostingError::Api {
+        status: status.as_u16(),
+        message,
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pull_request_urls() {
+        let github = PullRequestRef::parse_url("https://github.com/owner/repo/pull/42").unwrap();
+        assert_eq!(github, RepoRef::new("owner", "repo").pull_request(42));
+
+        let enterprise =
+            PullRequestRef::parse_url("https://git.corp.example/team/service/pull/7/files")
+                .unwrap();
+        assert_eq!(enterprise, RepoRef::new("team", "service").pull_request(7));
+
+        let gitea_subpath =
+            PullRequestRef::parse_url("https://example.com/gitea/owner/repo/pulls/3").unwrap();
+        assert_eq!(gitea_subpath, RepoRef::new("owner", "repo").pull_request(3));
+
+        for bad in [
+            "not a url",
+            "https://github.com/owner/repo",
+            "https://github.com/pull/1",
+            "https://github.com/owner/repo/pull/abc",
+        ] {
+            assert!(PullRequestRef::parse_url(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_combined_check_state() {
+        let check = |state| CheckRun {
+            name: "ci".to_string(),
+            state,
+            details_url: None,
+        };
+        assert_eq!(combined_check_state(&[]), CheckState::Success);
+        assert_eq!(
+            combined_check_state(&[check(CheckState::Success), check(CheckState::Pending)]),
+            CheckState::Pending
+        );
+        assert_eq!(
+            combined_check_state(&[check(CheckState::Pending), check(CheckState::Failure)]),
+            CheckState::Failure
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! JSON shapes shared by the GitHub and Gitea APIs.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{PrComment, PullRequest, PullRequestState, Review, ReviewState};
+
+#[derive(Deserialize)]
+pub(super) struct ApiUser {
+    pub login: String,
+}
+
+#[derive(Deserialize)]
+pub(super) struct ApiBranch {
+    #[serde(rename = "ref")]
+    pub name: String,
+    pub sha: String,
+}
+
+#[derive(Deserialize)]
+pub(super) struct ApiPullRequest {
+    pub number: u64,
+    pub html_url: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub head: ApiBranch,
+    pub base: ApiBranch,
+    pub user: Option<ApiUser>,
+    pub state: String,
+    #[serde(default)]
+    pub merged: Option<bool>,
+    #[serde(default)]
+    pub merged_at: Option<String>,
+    #[serde(default)]
+    pub mergeable: Option<bool>,
+}
+
+impl From<ApiPullRequest> for PullRequest {
+    fn from(pr: ApiPullRequest) -> Self {
+        // List endpoints omit `merged`, so fall back to `merged_at`
+        let state = if pr.merged == Some(true) || pr.merged_at.is_some() {
+            PullRequestState::Merged
+        } else if pr.state == "closed" {
+            PullRequestState::Closed
+        } else {
+            PullRequestState::Open
+        };
+        Self {
+            number: pr.number,
+            url: pr.html_url,
+            title: pr.title,
+            body: pr.body.unwrap_or_default(),
+            head: pr.head.name,
+            head_sha: pr.head.sha,
+            base: pr.base.name,
+            author: pr.user.map(|u| u.login).unwrap_or_default(),
+            state,
+            mergeable: pr.mergeable,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct ApiReview {
+    pub id: u64,
+    pub user: Option<ApiUser>,
+    pub state: String,
+    pub body: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ApiReview {
+    pub fn into_review(self, state: fn(&str) -> ReviewState) -> Review {
+        Review {
+            id: self.id,
+            author: self.user.map(|u| u.login).unwrap_or_default(),
+            state: state(&self.state),
+            body: self.body.unwrap_or_default(),
+            submitted_at: self.submitted_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct ApiComment {
+    pub id: u64,
+    pub user: Option<ApiUser>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ApiComment> for PrComment {
+    fn from(comment: ApiComment) -> Self {
+        Self {
+            id: comment.id,
+            author: comment.user.map(|u| u.login).unwrap_or_default(),
+            body: comment.body,
+            created_at: comment.created_at,
+        }
+    }
+}
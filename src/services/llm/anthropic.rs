@@ -0,0 +1,176 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    LlmConfig, LlmError, LlmProvider, LlmRequest, OnChunk, check_response, for_each_line, sse_data,
+};
+
+const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic's Messages API, streamed as server-sent events.
+pub struct AnthropicProvider {
+    config: LlmConfig,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: i32,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    system: &'a str,
+    messages: Vec<Message<'a>>,
+    temperature: f32,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Event {
+    ContentBlockDelta { delta: Delta },
+    Error { error: ErrorBody },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Delta {
+    TextDelta { text: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: LlmConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+/// The text in one line of the event stream. Only text deltas carry text;
+/// `event:` lines are redundant with the `type` in each payload.
+fn parse_line(line: &str) -> Result<String> {
+    let Some(data) = sse_data(line) else {
+        return Ok(String::new());
+    };
+    match serde_json::from_str(data)? {
+        Event::ContentBlockDelta {
+            delta: Delta::TextDelta { text },
+        } => Ok(text),
+        Event::Error { error } => Err(LlmError::Stream(error.message).into()),
+        _ => Ok(String::new()),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "Anthropic"
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        let api_key = self.config.require_api_key()?;
+        let body = MessagesRequest {
+            model: &self.config.model,
+            max_tokens: self.config.max_tokens_for(request),
+            system: &request.system,
+            messages: vec![Message {
+                role: "user",
+                content: &request.prompt,
+            }],
+            temperature: self.config.temperature_for(request),
+            stream: true,
+        };
+        let response = self
+            .client
+            .post(&self.config.endpoint)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_response(response).await?;
+
+        let mut text = String::new();
+        for_each_line(response, |line| {
+            let chunk = parse_line(line)?;
+            if !chunk.is_empty() {
+                on_chunk(&chunk);
+                text.push_str(&chunk);
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::app_settings::LlmProviderKind;
+    use crate::services::llm::test_server::serve_once;
+
+    #[tokio::test]
+    async fn test_streams_message_events() {
+        let (url, request) = serve_once(
+            "text/event-stream",
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n\
+             event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n\
+             event: ping\ndata: {\"type\":\"ping\"}\n\n\
+             event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"On \"}}\n\n\
+             event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"track.\"}}\n\n\
+             event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        )
+        .await;
+        let provider = AnthropicProvider::new(LlmConfig {
+            provider: LlmProviderKind::Anthropic,
+            endpoint: url,
+            model: "claude-3-5-haiku-latest".to_string(),
+            temperature: 0.3,
+            max_tokens: 1024,
+            api_key: Some("key-test".to_string()),
+            fixture_directory: None,
+        });
+
+        let mut chunks = Vec::new();
+        let text = provider
+            .stream(&LlmRequest::new("Be brief.", "Status?"), &mut |c| {
+                chunks.push(c.to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(text, "On track.");
+        assert_eq!(chunks, vec!["On ", "track."]);
+
+        let request = request.await.unwrap();
+        assert!(request.contains("x-api-key: key-test"));
+        assert!(request.contains("anthropic-version: 2023-06-01"));
+        assert!(request.contains("\"system\":\"Be brief.\""));
+
+        let error = parse_line(
+            "data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}",
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Model stream failed: Overloaded");
+    }
+}
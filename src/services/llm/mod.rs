@@ -0,0 +1,337 @@
+//! Language model providers (Ollama, OpenAI-compatible, Anthropic Messages)
+//! behind one streaming interface, plus a record/replay provider for tests
+//! and offline use.
+
+mod anthropic;
+mod ollama;
+mod openai;
+mod replay;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::domain::app_settings::{AppSettings, LlmProviderKind};
+
+pub use anthropic::AnthropicProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiCompatibleProvider;
+pub use replay::{Fixture, ReplayProvider};
+
+/// Receives each piece of an answer as it streams in.
+pub type OnChunk<'a> = dyn FnMut(&str) + Send + 'a;
+
+/// One prompt for a model. Unset limits fall back to the provider's config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LlmRequest {
+    #[serde(default)]
+    pub system: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub max_tokens: Option<i32>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl LlmRequest {
+    pub fn new(system: &str, prompt: &str) -> Self {
+        Self {
+            system: system.to_string(),
+            prompt: prompt.to_string(),
+            max_tokens: None,
+            temperature: None,
+        }
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: i32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+}
+
+/// Where and how to reach a model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmConfig {
+    pub provider: LlmProviderKind,
+    pub endpoint: String,
+    pub model: String,
+    pub temperature: f32,
+    /// Upper limit per response; requests may ask for less
+    pub max_tokens: i32,
+    pub api_key: Option<String>,
+    pub fixture_directory: Option<PathBuf>,
+}
+
+impl LlmConfig {
+    /// Settings from the app, with the API key from `LLM_API_KEY` (or
+    /// `ANTHROPIC_API_KEY` / `OPENAI_API_KEY` for those providers).
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let provider_key = match settings.llm_provider {
+            LlmProviderKind::Anthropic => std::env::var("ANTHROPIC_API_KEY").ok(),
+            LlmProviderKind::OpenAiCompatible => std::env::var("OPENAI_API_KEY").ok(),
+            _ => None,
+        };
+        Self {
+            provider: settings.llm_provider,
+            endpoint: settings.llm_endpoint.clone(),
+            model: settings.llm_model.clone(),
+            temperature: settings.llm_temperature,
+            max_tokens: settings.llm_max_tokens,
+            api_key: std::env::var("LLM_API_KEY").ok().or(provider_key),
+            fixture_directory: settings.llm_fixture_directory.as_ref().map(PathBuf::from),
+        }
+    }
+
+    /// The older environment-only setup: `LLM_ENDPOINT` is Ollama when it
+    /// mentions Ollama's port, otherwise OpenAI-compatible if `LLM_API_KEY`
+    /// is set. `None` means no model is available.
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("LLM_ENDPOINT")
+            .unwrap_or_else(|_| LlmProviderKind::Ollama.default_endpoint().to_string());
+        let api_key = std::env::var("LLM_API_KEY").ok();
+        let provider = if endpoint.contains("ollama") || endpoint.contains("11434") {
+            LlmProviderKind::Ollama
+        } else if api_key.is_some() {
+            LlmProviderKind::OpenAiCompatible
+        } else {
+            return None;
+        };
+        let defaults = AppSettings::default();
+        Some(Self {
+            provider,
+            endpoint,
+            model: std::env::var("LLM_MODEL")
+                .unwrap_or_else(|_| provider.default_model().to_string()),
+            temperature: defaults.llm_temperature,
+            max_tokens: defaults.llm_max_tokens,
+            api_key,
+            fixture_directory: None,
+        })
+    }
+
+    /// Token limit for `request`, never above the configured maximum.
+    pub fn max_tokens_for(&self, request: &LlmRequest) -> i32 {
+        request
+            .max_tokens
+            .map_or(self.max_tokens, |m| m.min(self.max_tokens))
+    }
+
+    pub fn temperature_for(&self, request: &LlmRequest) -> f32 {
+        request.temperature.unwrap_or(self.temperature)
+    }
+
+    fn require_api_key(&self) -> Result<&str, LlmError> {
+        self.api_key
+            .as_deref()
+            .ok_or(LlmError::MissingApiKey(self.provider))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmError {
+    MissingApiKey(LlmProviderKind),
+    MissingFixtureDirectory,
+    /// The provider answered with a non-success status
+    Api {
+        status: u16,
+        message: String,
+    },
+    /// The provider reported an error part way through a streamed response
+    Stream(String),
+    NoFixture(String),
+}
+
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingApiKey(provider) => {
+                write!(f, "No API key for {}; set LLM_API_KEY", provider)
+            }
+            Self::MissingFixtureDirectory => {
+                write!(f, "The replay provider needs a fixture directory")
+            }
+            Self::Api { status, message } => write!(f, "Model API error {}: {}", status, message),
+            Self::Stream(message) => write!(f, "Model stream failed: {}", message),
+            Self::NoFixture(prompt) => write!(f, "No recorded response for prompt: {}", prompt),
+        }
+    }
+}
+
+impl std::error::Error for LlmError {}
+
+/// A language model that streams its answer as text chunks.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Send `request`, passing each chunk of the answer to `on_chunk` as it
+    /// arrives, and return the whole answer.
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String>;
+
+    async fn complete(&self, request: &LlmRequest) -> Result<String> {
+        self.stream(request, &mut |_| {}).await
+    }
+}
+
+/// Provider for `config`. A replay provider without a fixture directory
+/// answers every request with `LlmError::MissingFixtureDirectory`.
+pub fn provider_from_config(config: &LlmConfig) -> Arc<dyn LlmProvider> {
+    match config.provider {
+        LlmProviderKind::Ollama => Arc::new(OllamaProvider::new(config.clone())),
+        LlmProviderKind::OpenAiCompatible => Arc::new(OpenAiCompatibleProvider::new(config.clone())),
+        LlmProviderKind::Anthropic => Arc::new(AnthropicProvider::new(config.clone())),
+        LlmProviderKind::Replay => Arc::new(ReplayProvider::new(
+            config.fixture_directory.clone().unwrap_or_default(),
+        )),
+    }
+}
+
+/// Turn a non-success response into an `LlmError::Api`.
+pub(crate) async fn check_response(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| {
+            let error = v.get("error")?;
+            error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .or_else(|| error.as_str())
+                .map(String::from)
+        })
+        .unwrap_or(body);
+    Err(LlmError::Api {
+        status: status.as_u16(),
+        message,
+    }
+    .into())
+}
+
+/// Read a streamed response body line by line. Chunks can end mid-line, so
+/// partial lines are held back until the rest arrives.
+async fn for_each_line(
+    mut response: reqwest::Response,
+    mut on_line: impl FnMut(&str) -> Result<()> + Send,
+) -> Result<()> {
+    let mut pending = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            on_line(String::from_utf8_lossy(&line).trim_end())?;
+        }
+    }
+    if !pending.is_empty() {
+        on_line(String::from_utf8_lossy(&pending).trim_end())?;
+    }
+    Ok(())
+}
+
+/// The payload of a server-sent event `data:` line, if `line` is one.
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim_start)
+}
+
+#[cfg(test)]
+pub(crate) mod test_server {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one request with `body`, split into several writes so clients
+    /// see it arrive in pieces. Returns the URL and the received request.
+    pub async fn serve_once(
+        content_type: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let header_end = loop {
+                let read = stream.read(&mut chunk).await.unwrap();
+                buffer.extend_from_slice(&chunk[..read]);
+                if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let head = String::from_utf8_lossy(&buffer[..header_end]).to_lowercase();
+            let content_length: usize = head
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0);
+            while buffer.len() < header_end + content_length {
+                let read = stream.read(&mut chunk).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                buffer.extend_from_slice(&chunk[..read]);
+            }
+
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nConnection: close\r\n\r\n",
+                content_type
+            );
+            stream.write_all(header.as_bytes()).await.unwrap();
+            for piece in body.as_bytes().chunks(7) {
+                stream.write_all(piece).await.unwrap();
+                stream.flush().await.unwrap();
+            }
+            String::from_utf8_lossy(&buffer).to_string()
+        });
+        (url, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_limits_respect_config() {
+        let config = LlmConfig {
+            provider: LlmProviderKind::Ollama,
+            endpoint: String::new(),
+            model: "llama3.2:1b".to_string(),
+            temperature: 0.3,
+            max_tokens: 1000,
+            api_key: None,
+            fixture_directory: None,
+        };
+        let request = LlmRequest::new("", "hi");
+        assert_eq!(config.max_tokens_for(&request), 1000);
+        assert_eq!(config.max_tokens_for(&request.clone().with_max_tokens(150)), 150);
+        assert_eq!(config.max_tokens_for(&request.clone().with_max_tokens(4000)), 1000);
+        assert_eq!(config.temperature_for(&request), 0.3);
+        assert_eq!(config.temperature_for(&request.with_temperature(0.9)), 0.9);
+        assert_eq!(
+            config.require_api_key(),
+            Err(LlmError::MissingApiKey(LlmProviderKind::Ollama))
+        );
+    }
+
+    #[test]
+    fn test_sse_data() {
+        assert_eq!(sse_data("data: {\"a\":1}"), Some("{\"a\":1}"));
+        assert_eq!(sse_data("data:[DONE]"), Some("[DONE]"));
+        assert_eq!(sse_data("event: ping"), None);
+    }
+}
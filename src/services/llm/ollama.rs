@@ -0,0 +1,144 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    LlmConfig, LlmError, LlmProvider, LlmRequest, OnChunk, check_response, for_each_line,
+};
+
+/// Ollama's `/api/generate`, which streams one JSON object per line.
+pub struct OllamaProvider {
+    config: LlmConfig,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    system: &'a str,
+    stream: bool,
+    options: Options,
+}
+
+#[derive(Serialize)]
+struct Options {
+    temperature: f32,
+    num_predict: i32,
+}
+
+#[derive(Deserialize)]
+struct GenerateChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl OllamaProvider {
+    pub fn new(config: LlmConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+/// The text in one line of a streamed response.
+fn parse_line(line: &str) -> Result<String> {
+    if line.trim().is_empty() {
+        return Ok(String::new());
+    }
+    let chunk: GenerateChunk = serde_json::from_str(line)?;
+    match chunk.error {
+        Some(error) => Err(LlmError::Stream(error).into()),
+        None => Ok(chunk.response),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        let body = GenerateRequest {
+            model: &self.config.model,
+            prompt: &request.prompt,
+            system: &request.system,
+            stream: true,
+            options: Options {
+                temperature: self.config.temperature_for(request),
+                num_predict: self.config.max_tokens_for(request),
+            },
+        };
+        let response = self
+            .client
+            .post(&self.config.endpoint)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_response(response).await?;
+
+        let mut text = String::new();
+        for_each_line(response, |line| {
+            let chunk = parse_line(line)?;
+            if !chunk.is_empty() {
+                on_chunk(&chunk);
+                text.push_str(&chunk);
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::app_settings::LlmProviderKind;
+    use crate::services::llm::test_server::serve_once;
+
+    #[tokio::test]
+    async fn test_streams_ndjson() {
+        let (url, request) = serve_once(
+            "application/x-ndjson",
+            "{\"response\":\"Ship \",\"done\":false}\n{\"response\":\"the export.\",\"done\":false}\n{\"response\":\"\",\"done\":true}\n",
+        )
+        .await;
+        let provider = OllamaProvider::new(LlmConfig {
+            provider: LlmProviderKind::Ollama,
+            endpoint: url,
+            model: "llama3.2:1b".to_string(),
+            temperature: 0.3,
+            max_tokens: 2048,
+            api_key: None,
+            fixture_directory: None,
+        });
+
+        let mut chunks = Vec::new();
+        let text = provider
+            .stream(
+                &LlmRequest::new("Be brief.", "Summarize").with_max_tokens(150),
+                &mut |c| chunks.push(c.to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(text, "Ship the export.");
+        assert_eq!(chunks, vec!["Ship ", "the export."]);
+
+        let request = request.await.unwrap();
+        assert!(request.contains("\"model\":\"llama3.2:1b\""));
+        assert!(request.contains("\"system\":\"Be brief.\""));
+        assert!(request.contains("\"num_predict\":150"));
+
+        assert!(parse_line("{\"error\":\"model not found\"}").is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    LlmConfig, LlmError, LlmProvider, LlmRequest, OnChunk, check_response, for_each_line, sse_data,
+};
+
+/// Any API that speaks OpenAI's chat completions format, streamed as
+/// server-sent events.
+pub struct OpenAiCompatibleProvider {
+    config: LlmConfig,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<Message<'a>>,
+    temperature: f32,
+    max_tokens: i32,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(default)]
+    error: Option<ErrorBody>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    #[serde(default)]
+    delta: Delta,
+}
+
+#[derive(Deserialize, Default)]
+struct Delta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(config: LlmConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+/// The text in one line of the event stream; `[DONE]` and non-data lines
+/// carry none.
+fn parse_line(line: &str) -> Result<String> {
+    let Some(data) = sse_data(line) else {
+        return Ok(String::new());
+    };
+    if data == "[DONE]" {
+        return Ok(String::new());
+    }
+    let chunk: ChatChunk = serde_json::from_str(data)?;
+    if let Some(error) = chunk.error {
+        return Err(LlmError::Stream(error.message).into());
+    }
+    Ok(chunk
+        .choices
+        .into_iter()
+        .filter_map(|c| c.delta.content)
+        .collect())
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        "OpenAI-compatible"
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        let api_key = self.config.require_api_key()?;
+        let mut messages = Vec::new();
+        if !request.system.is_empty() {
+            messages.push(Message {
+                role: "system",
+                content: &request.system,
+            });
+        }
+        messages.push(Message {
+            role: "user",
+            content: &request.prompt,
+        });
+        let body = ChatRequest {
+            model: &self.config.model,
+            messages,
+            temperature: self.config.temperature_for(request),
+            max_tokens: self.config.max_tokens_for(request),
+            stream: true,
+        };
+        let response = self
+            .client
+            .post(&self.config.endpoint)
+            .bearer_auth(api_key)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_response(response).await?;
+
+        let mut text = String::new();
+        for_each_line(response, |line| {
+            let chunk = parse_line(line)?;
+            if !chunk.is_empty() {
+                on_chunk(&chunk);
+                text.push_str(&chunk);
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::app_settings::LlmProviderKind;
+    use crate::services::llm::test_server::serve_once;
+
+    #[tokio::test]
+    async fn test_streams_server_sent_events() {
+        let (url, request) = serve_once(
+            "text/event-stream",
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\"Three \"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\"tasks left.\"}}]}\n\n\
+             data: [DONE]\n\n",
+        )
+        .await;
+        let provider = OpenAiCompatibleProvider::new(LlmConfig {
+            provider: LlmProviderKind::OpenAiCompatible,
+            endpoint: url,
+            model: "gpt-4o-mini".to_string(),
+            temperature: 0.2,
+            max_tokens: 512,
+            api_key: Some("sk-test".to_string()),
+            fixture_directory: None,
+        });
+
+        let mut chunks = Vec::new();
+        let text = provider
+            .stream(&LlmRequest::new("", "Status?"), &mut |c| {
+                chunks.push(c.to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(text, "Three tasks left.");
+        assert_eq!(chunks.len(), 2);
+
+        let request = request.await.unwrap();
+        assert!(request.contains("authorization: Bearer sk-test"));
+        assert!(request.contains("\"max_tokens\":512"));
+        assert!(!request.contains("\"role\":\"system\""));
+
+        assert!(parse_line("data: {\"error\":{\"message\":\"overloaded\"}}").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::{LlmError, LlmProvider, LlmRequest, OnChunk};
+
+/// A recorded answer and the requests it answers. A fixture with `prompt`
+/// set matches that exact prompt; otherwise every `prompt_contains` entry
+/// must appear in the prompt. `system`, when set, must match exactly.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompt_contains: Vec<String>,
+    /// The answer, in the pieces it streamed in
+    pub chunks: Vec<String>,
+}
+
+impl Fixture {
+    pub fn recorded(request: &LlmRequest, chunks: Vec<String>) -> Self {
+        Self {
+            system: Some(request.system.clone()),
+            prompt: Some(request.prompt.clone()),
+            prompt_contains: Vec::new(),
+            chunks,
+        }
+    }
+
+    pub fn matches(&self, request: &LlmRequest) -> bool {
+        if self.system.as_ref().is_some_and(|s| *s != request.system) {
+            return false;
+        }
+        match &self.prompt {
+            Some(prompt) => *prompt == request.prompt,
+            None => self
+                .prompt_contains
+                .iter()
+                .all(|part| request.prompt.contains(part.as_str())),
+        }
+    }
+
+    /// File name for a recording, stable across runs and Rust versions.
+    pub fn file_name(request: &LlmRequest) -> String {
+        // FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in request.system.bytes().chain([0]).chain(request.prompt.bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}.json", hash)
+    }
+}
+
+/// Answers from fixture files in a directory instead of a model, so
+/// features built on models can be tested offline. In recording mode it
+/// forwards requests to a real provider and saves each answer as a fixture.
+pub struct ReplayProvider {
+    directory: PathBuf,
+    record_from: Option<Arc<dyn LlmProvider>>,
+}
+
+impl ReplayProvider {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            record_from: None,
+        }
+    }
+
+    pub fn recording(directory: impl Into<PathBuf>, provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            directory: directory.into(),
+            record_from: Some(provider),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// All fixtures in the directory, by file name.
+    pub fn fixtures(&self) -> Result<Vec<Fixture>> {
+        if self.directory.as_os_str().is_empty() {
+            return Err(LlmError::MissingFixtureDirectory.into());
+        }
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.directory)
+            .with_context(|| format!("Reading fixtures from {}", self.directory.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let content = fs::read_to_string(path)?;
+                serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid fixture {}", path.display()))
+            })
+            .collect()
+    }
+
+    /// The fixture answering `request`; exact prompt matches win over
+    /// `prompt_contains` ones.
+    pub fn find(&self, request: &LlmRequest) -> Result<Fixture> {
+        let mut fixtures: Vec<Fixture> = self
+            .fixtures()?
+            .into_iter()
+            .filter(|f| f.matches(request))
+            .collect();
+        fixtures.sort_by_key(|f| f.prompt.is_none());
+        fixtures.into_iter().next().ok_or_else(|| {
+            let prompt: String = request.prompt.chars().take(80).collect();
+            LlmError::NoFixture(prompt).into()
+        })
+    }
+
+    fn save(&self, fixture: &Fixture, request: &LlmRequest) -> Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        let path = self.directory.join(Fixture::file_name(request));
+        fs::write(path, serde_json::to_string_pretty(fixture)?)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        "Replay"
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        if let Some(provider) = &self.record_from {
+            let mut chunks = Vec::new();
+            let text = provider
+                .stream(request, &mut |chunk| {
+                    chunks.push(chunk.to_string());
+                    on_chunk(chunk);
+                })
+                .await?;
+            self.save(&Fixture::recorded(request, chunks), request)?;
+            return Ok(text);
+        }
+
+        let fixture = self.find(request)?;
+        for chunk in &fixture.chunks {
+            on_chunk(chunk);
+        }
+        Ok(fixture.chunks.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = TempDir::new().unwrap();
+        let canned = TempDir::new().unwrap();
+        fs::write(
+            canned.path().join("status.json"),
+            r#"{"prompt_contains": ["status"], "chunks": ["All ", "green."]}"#,
+        )
+        .unwrap();
+
+        // Record from one replay provider into another directory
+        let recorder = ReplayProvider::recording(
+            dir.path(),
+            Arc::new(ReplayProvider::new(canned.path())),
+        );
+        let request = LlmRequest::new("Be brief.", "What is the status?");
+        assert_eq!(recorder.complete(&request).await.unwrap(), "All green.");
+        assert!(dir.path().join(Fixture::file_name(&request)).exists());
+
+        let replay = ReplayProvider::new(dir.path());
+        let mut chunks = Vec::new();
+        let text = replay
+            .stream(&request, &mut |c| chunks.push(c.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(text, "All green.");
+        assert_eq!(chunks, vec!["All ", "green."]);
+
+        // The recording is exact: another system prompt is not answered
+        let other = LlmRequest::new("Be verbose.", "What is the status?");
+        let error = replay.complete(&other).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<LlmError>(),
+            Some(LlmError::NoFixture(_))
+        ));
+        assert!(ReplayProvider::new("").complete(&other).await.is_err());
+    }
+}
@@ -0,0 +1,752 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::claude_code::{ClaudeCodeConfig, ClaudeCodeSession, ClaudePromptTemplate};
+use crate::domain::verification::VerificationReport;
+use crate::repository::Repository;
+use crate::services::claude_code_service::ClaudeCodeService;
+use crate::services::command_executor::{CommandExecutor, CommandOutput};
+use crate::services::dependency_service::DependencyService;
+use crate::services::hosting::{HostingClient, MergeMethod, RepoRef};
+use crate::services::verification_service::VerificationService;
+
+/// An agent-created PR waiting to be merged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeQueueEntry {
+    pub task_id: Uuid,
+    pub session_id: Uuid,
+    pub branch: String,
+    pub pr_number: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeOutcome {
+    Merged,
+    /// The branch no longer rebases cleanly; `retry_session_id` is the agent
+    /// session asked to resolve it, if one could be started
+    Conflicted {
+        files: Vec<String>,
+        retry_session_id: Option<Uuid>,
+    },
+    /// The rebased branch failed the verification pipeline and was left
+    /// unmerged; `retry_session_id` is the agent session asked to fix it
+    VerificationFailed {
+        report: VerificationReport,
+        retry_session_id: Option<Uuid>,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeResult {
+    pub entry: MergeQueueEntry,
+    pub outcome: MergeOutcome,
+}
+
+impl MergeQueueEntry {
+    /// Sessions without a branch or PR have nothing to merge.
+    pub fn from_session(session: &ClaudeCodeSession) -> Option<Self> {
+        Some(Self {
+            task_id: session.task_id,
+            session_id: session.id,
+            branch: session.branch_name.clone()?,
+            pr_number: session.pr_number?,
+        })
+    }
+}
+
+/// Lands agent PRs on the base branch one at a time.
+///
+/// PRs are merged in dependency order. Before each merge the branch is rebased
+/// onto the current base and the verification pipeline is run again, so every
+/// PR is checked against everything merged ahead of it. Branches that no longer
+/// rebase cleanly, or fail verification once rebased, are handed back to the
+/// agent with the conflicting files or failing checks once the rest of the
+/// queue has been processed. The queue works in a temporary worktree of its
+/// own, leaving the repository's checkout to the agents.
+pub struct MergeQueueService {
+    repository: Arc<Repository>,
+    command_executor: Arc<dyn CommandExecutor>,
+    hosting: Arc<dyn HostingClient>,
+}
+
+impl MergeQueueService {
+    pub fn new(
+        repository: Arc<Repository>,
+        command_executor: Arc<dyn CommandExecutor>,
+        hosting: Arc<dyn HostingClient>,
+    ) -> Self {
+        Self {
+            repository,
+            command_executor,
+            hosting,
+        }
+    }
+
+    /// Sort entries so that a task's PR lands after the PRs of the tasks it
+    /// depends on, read the way auto-run reads them. Otherwise entries keep
+    /// their queue order.
+    pub async fn order(&self, entries: Vec<MergeQueueEntry>) -> Result<Vec<MergeQueueEntry>> {
+        let dependency_service = DependencyService::new(self.repository.clone());
+        let queued: HashSet<Uuid> = entries.iter().map(|e| e.task_id).collect();
+        let mut waiting = Vec::new();
+        for entry in entries {
+            let prerequisites: HashSet<Uuid> = dependency_service
+                .get_dependencies(entry.task_id)
+                .await?
+                .into_iter()
+                .filter(|id| queued.contains(id) && *id != entry.task_id)
+                .collect();
+            waiting.push((entry, prerequisites));
+        }
+
+        let mut ordered: Vec<MergeQueueEntry> = Vec::new();
+        while !waiting.is_empty() {
+            let landed: HashSet<Uuid> = ordered.iter().map(|e| e.task_id).collect();
+            let Some(next) = waiting
+                .iter()
+                .position(|(_, prerequisites)| prerequisites.is_subset(&landed))
+            else {
+                anyhow::bail!("Circular dependency between queued PRs");
+            };
+            ordered.push(waiting.remove(next).0);
+        }
+        Ok(ordered)
+    }
+
+    /// Land `entries` in dependency order. A PR that can't be landed gets a
+    /// `Failed` outcome and the rest of the queue carries on, so PRs merged
+    /// earlier in the run are always reported. Conflicted and failing PRs
+    /// only go back to the agent where `can_retry` allows it.
+    pub async fn process(
+        &self,
+        entries: Vec<MergeQueueEntry>,
+        agent: &ClaudeCodeService,
+        config: &ClaudeCodeConfig,
+        template: &ClaudePromptTemplate,
+        can_retry: impl Fn(&MergeQueueEntry) -> bool,
+    ) -> Result<Vec<MergeResult>> {
+        let repo_dir = match &config.working_directory {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::current_dir()?,
+        };
+        let base = config.default_base_branch.as_str();
+        let verifier = VerificationService::new(self.command_executor.clone());
+        let entries = self.order(entries).await?;
+
+        // Rebase in a worktree of our own, so agents still working in the
+        // repository's checkout aren't switched to another branch under them
+        let queue_dir = std::env::temp_dir().join(format!("plon-merge-queue-{}", Uuid::new_v4()));
+        let queue_path = queue_dir.to_string_lossy().to_string();
+        let add = self
+            .git(&repo_dir, &["worktree", "add", "--detach", &queue_path])
+            .await?;
+        if !add.success {
+            anyhow::bail!("Could not create the merge queue worktree: {}", add.stderr);
+        }
+
+        let mut results = Vec::new();
+        for entry in entries {
+            let outcome = match self.land(&entry, &queue_dir, config, &verifier).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    // Don't leave a rebase in progress for the next PR
+                    let _ = self.git(&queue_dir, &["rebase", "--abort"]).await;
+                    MergeOutcome::Failed(format!("{:#}", e))
+                }
+            };
+            results.push(MergeResult { entry, outcome });
+        }
+
+        match self
+            .git(&repo_dir, &["worktree", "remove", "--force", &queue_path])
+            .await
+        {
+            Ok(output) if output.success => {}
+            Ok(output) => tracing::warn!("Could not remove {}: {}", queue_path, output.stderr),
+            Err(e) => tracing::warn!("Could not remove {}: {}", queue_path, e),
+        }
+
+        for result in &mut results {
+            let entry = &result.entry;
+            let (error, verification, retry_session_id) = match &mut result.outcome {
+                MergeOutcome::Conflicted {
+                    files,
+                    retry_session_id,
+                } => {
+                    let error = format!(
+                        "Merge conflict: {} no longer rebases onto {}. Rebase it onto origin/{} \
+                         and resolve the conflicts in: {}",
+                        entry.branch,
+                        base,
+                        base,
+                        files.join(", ")
+                    );
+                    (error, None, retry_session_id)
+                }
+                MergeOutcome::VerificationFailed {
+                    report,
+                    retry_session_id,
+                } => {
+                    let error = format!(
+                        "Verification failed after rebasing {} onto {}",
+                        entry.branch, base
+                    );
+                    (error, Some(report.clone()), retry_session_id)
+                }
+                _ => continue,
+            };
+            if !can_retry(entry) {
+                continue;
+            }
+            match self
+                .send_back(entry, error, verification, agent, config, template)
+                .await
+            {
+                Ok(retry) => *retry_session_id = retry,
+                Err(e) => tracing::warn!("Could not send PR #{} back: {}", entry.pr_number, e),
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Rebase, verify, push and merge one PR, working in `work_dir` with a
+    /// detached HEAD so branches checked out elsewhere can be landed too.
+    async fn land(
+        &self,
+        entry: &MergeQueueEntry,
+        work_dir: &Path,
+        config: &ClaudeCodeConfig,
+        verifier: &VerificationService,
+    ) -> Result<MergeOutcome> {
+        let base = format!("origin/{}", config.default_base_branch);
+        let remote_branch = format!("origin/{}", entry.branch);
+
+        let fetch = self.git(work_dir, &["fetch", "origin"]).await?;
+        if !fetch.success {
+            return Ok(MergeOutcome::Failed(format!(
+                "git fetch failed: {}",
+                fetch.stderr
+            )));
+        }
+
+        let checkout = self
+            .git(work_dir, &["checkout", "--detach", &remote_branch])
+            .await?;
+        if !checkout.success {
+            return Ok(MergeOutcome::Failed(format!(
+                "Could not check out {}: {}",
+                entry.branch, checkout.stderr
+            )));
+        }
+
+        let rebase = self.git(work_dir, &["rebase", &base]).await?;
+        if !rebase.success {
+            let unmerged = self
+                .git(work_dir, &["diff", "--name-only", "--diff-filter=U"])
+                .await?;
+            self.git(work_dir, &["rebase", "--abort"]).await?;
+            let files = unmerged
+                .stdout
+                .lines()
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(String::from)
+                .collect();
+            return Ok(MergeOutcome::Conflicted {
+                files,
+                retry_session_id: None,
+            });
+        }
+
+        if !config.verification_pipeline.is_empty() {
+            let report = verifier.run(&config.verification_pipeline, work_dir).await;
+            if !report.passed() {
+                return Ok(MergeOutcome::VerificationFailed {
+                    report,
+                    retry_session_id: None,
+                });
+            }
+        }
+
+        let refspec = format!("HEAD:refs/heads/{}", entry.branch);
+        let push = self
+            .git(work_dir, &["push", "--force-with-lease", "origin", &refspec])
+            .await?;
+        if !push.success {
+            return Ok(MergeOutcome::Failed(format!(
+                "Could not push {}: {}",
+                entry.branch, push.stderr
+            )));
+        }
+
+        let pr = RepoRef::from_config(config).pull_request(entry.pr_number as u64);
+        if let Err(e) = self.hosting.merge(&pr, MergeMethod::Squash).await {
+            return Ok(MergeOutcome::Failed(format!(
+                "Could not merge PR #{}: {}",
+                entry.pr_number, e
+            )));
+        }
+
+        Ok(MergeOutcome::Merged)
+    }
+
+    /// Start a retry of the PR's session with `error`, and the failing
+    /// checks if there are any, as its failure.
+    async fn send_back(
+        &self,
+        entry: &MergeQueueEntry,
+        error: String,
+        verification: Option<VerificationReport>,
+        agent: &ClaudeCodeService,
+        config: &ClaudeCodeConfig,
+        template: &ClaudePromptTemplate,
+    ) -> Result<Option<Uuid>> {
+        let Some(mut previous) = self
+            .repository
+            .claude_code
+            .get_session(entry.session_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(task) = self.repository.tasks.get(entry.task_id).await? else {
+            return Ok(None);
+        };
+
+        previous.set_error(error);
+        if verification.is_some() {
+            previous.verification = verification;
+        }
+        let retry = agent
+            .retry_session(&previous, &task, config, template)
+            .await?;
+        Ok(Some(retry.id))
+    }
+
+    async fn git(&self, work_dir: &Path, args: &[&str]) -> Result<CommandOutput> {
+        self.command_executor
+            .execute("git", args, Some(work_dir), None)
+            .await
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::dependency::{Dependency, DependencyType};
+    use crate::domain::task::Task;
+    use crate::domain::verification::VerificationStep;
+    use crate::repository::database::init_test_database;
+    use crate::services::command_executor::SystemCommandExecutor;
+    use crate::services::command_executor::mock::MockCommandExecutor;
+    use crate::services::hosting::{FakeHostingServer, NewPullRequest, PullRequestState};
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    /// Real git against a local bare "origin", with `claude` mocked.
+    #[derive(Clone)]
+    struct LocalRemoteExecutor {
+        mock: MockCommandExecutor,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for LocalRemoteExecutor {
+        async fn execute(
+            &self,
+            program: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+            env_vars: Option<HashMap<String, String>>,
+        ) -> Result<CommandOutput> {
+            match program {
+                "claude" => {
+                    self.mock
+                        .execute(program, args, working_dir, env_vars)
+                        .await
+                }
+                _ => {
+                    SystemCommandExecutor
+                        .execute(program, args, working_dir, env_vars)
+                        .await
+                }
+            }
+        }
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn commit_branch(seed: &Path, branch: &str, file: &str, contents: &str) {
+        git(seed, &["checkout", "-b", branch, "main"]);
+        std::fs::write(seed.join(file), contents).unwrap();
+        git(seed, &["add", "."]);
+        git(seed, &["commit", "-m", branch]);
+        git(seed, &["push", "origin", branch]);
+    }
+
+    #[tokio::test]
+    async fn test_order_follows_dependencies() {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let mut ids = Vec::new();
+        for title in ["first", "second", "unrelated"] {
+            let task = Task::new(title.to_string(), String::new());
+            repository.tasks.create(&task).await.unwrap();
+            ids.push(task.id);
+        }
+        let (first, second, unrelated) = (ids[0], ids[1], ids[2]);
+        repository
+            .dependencies
+            .create(&Dependency::new(
+                second,
+                first,
+                DependencyType::FinishToStart,
+            ))
+            .await
+            .unwrap();
+
+        let entry = |task_id, pr_number| MergeQueueEntry {
+            task_id,
+            session_id: Uuid::new_v4(),
+            branch: format!("branch-{}", pr_number),
+            pr_number,
+        };
+        let server = FakeHostingServer::start().await.unwrap();
+        let service = MergeQueueService::new(
+            repository,
+            Arc::new(MockCommandExecutor::new()),
+            Arc::new(server.github_client()),
+        );
+        let ordered = service
+            .order(vec![entry(second, 2), entry(unrelated, 3), entry(first, 1)])
+            .await
+            .unwrap();
+
+        let numbers: Vec<i32> = ordered.iter().map(|e| e.pr_number).collect();
+        assert_eq!(numbers, vec![3, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_queue_rebases_and_sends_conflicts_back() {
+        let temp = TempDir::new().unwrap();
+        let origin = temp.path().join("origin.git");
+        let seed = temp.path().join("seed");
+        let work = temp.path().join("work");
+        std::fs::create_dir_all(&origin).unwrap();
+        std::fs::create_dir_all(&seed).unwrap();
+
+        git(&origin, &["init", "--bare", "--initial-branch=main"]);
+        git(&seed, &["init", "--initial-branch=main"]);
+        std::fs::write(seed.join("a.txt"), "base\n").unwrap();
+        git(&seed, &["add", "."]);
+        git(&seed, &["commit", "-m", "base"]);
+        git(
+            &seed,
+            &["remote", "add", "origin", origin.to_str().unwrap()],
+        );
+        git(&seed, &["push", "origin", "main"]);
+        commit_branch(&seed, "feature-a", "a.txt", "from A\n");
+        commit_branch(&seed, "feature-b", "b.txt", "from B\n");
+        commit_branch(&seed, "feature-c", "a.txt", "from C\n");
+        git(temp.path(), &["clone", origin.to_str().unwrap(), "work"]);
+        git(&work, &["config", "user.name", "Test"]);
+        git(&work, &["config", "user.email", "test@example.com"]);
+
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let tasks: Vec<Task> = ["A", "B", "C"]
+            .iter()
+            .map(|t| Task::new(t.to_string(), String::new()))
+            .collect();
+        let mut entries = Vec::new();
+        for (i, task) in tasks.iter().enumerate() {
+            repository.tasks.create(task).await.unwrap();
+            let mut session = ClaudeCodeSession::new(task.id);
+            session.branch_name = Some(format!("feature-{}", task.title.to_lowercase()));
+            session.pr_number = Some(i as i32 + 1);
+            repository
+                .claude_code
+                .create_session(&session)
+                .await
+                .unwrap();
+            entries.push(MergeQueueEntry::from_session(&session).unwrap());
+        }
+        // B and C both build on A
+        for dependent in [&tasks[1], &tasks[2]] {
+            repository
+                .dependencies
+                .create(&Dependency::new(
+                    dependent.id,
+                    tasks[0].id,
+                    DependencyType::FinishToStart,
+                ))
+                .await
+                .unwrap();
+        }
+        entries.reverse();
+
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_claude_success();
+        let executor: Arc<dyn CommandExecutor> =
+            Arc::new(LocalRemoteExecutor { mock: mock.clone() });
+
+        // Merging fast-forwards the bare repo's `main` to the PR branch, which
+        // the queue has just rebased, like a squash merge would
+        let server = FakeHostingServer::start().await.unwrap();
+        let repo = RepoRef::new("owner", "repo");
+        for branch in ["feature-a", "feature-b", "feature-c"] {
+            server.open_pull_request(
+                &repo,
+                &NewPullRequest {
+                    title: branch.to_string(),
+                    body: String::new(),
+                    head: branch.to_string(),
+                    base: "main".to_string(),
+                },
+                "claude",
+            );
+        }
+        let bare = origin.clone();
+        server.on_merge(move |_, pr| {
+            git(&bare, &["update-ref", "refs/heads/main", &format!("refs/heads/{}", pr.head)]);
+        });
+
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.working_directory = Some(work.to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        // B only passes once it has been rebased onto A
+        config.verification_pipeline.steps =
+            vec![VerificationStep::new("has-a", "grep -q 'from A' a.txt")];
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+        let agent =
+            ClaudeCodeService::with_executor(repository.claude_code.clone(), executor.clone());
+
+        let service = MergeQueueService::new(
+            repository.clone(),
+            executor,
+            Arc::new(server.github_client()),
+        );
+        let results = service
+            .process(entries, &agent, &config, &template, |_| true)
+            .await
+            .unwrap();
+
+        let outcome = |pr| {
+            &results
+                .iter()
+                .find(|r| r.entry.pr_number == pr)
+                .unwrap()
+                .outcome
+        };
+        assert_eq!(results[0].entry.pr_number, 1);
+        assert_eq!(outcome(1), &MergeOutcome::Merged);
+        assert_eq!(outcome(2), &MergeOutcome::Merged);
+        let MergeOutcome::Conflicted {
+            files,
+            retry_session_id: Some(retry_id),
+        } = outcome(3)
+        else {
+            panic!("expected a conflict, got {:?}", outcome(3));
+        };
+        assert_eq!(files, &vec!["a.txt".to_string()]);
+
+        let main = |file: &str| {
+            std::process::Command::new("git")
+                .args(["show", &format!("main:{}", file)])
+                .current_dir(&origin)
+                .output()
+                .unwrap()
+        };
+        assert_eq!(String::from_utf8_lossy(&main("a.txt").stdout), "from A\n");
+        assert_eq!(String::from_utf8_lossy(&main("b.txt").stdout), "from B\n");
+        let state = |number| server.pull_request(&repo.pull_request(number)).unwrap().state;
+        assert_eq!(state(2), PullRequestState::Merged);
+        assert_eq!(state(3), PullRequestState::Open);
+
+        // The conflicting PR went back to an agent with the conflict as context
+        let retry = repository
+            .claude_code
+            .get_session(*retry_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let conflicted = results.iter().find(|r| r.entry.pr_number == 3).unwrap();
+        assert_eq!(retry.retry_of, Some(conflicted.entry.session_id));
+        assert_eq!(retry.branch_name.as_deref(), Some("feature-c"));
+
+        // The feedback joins the prompt once the branch is checked out
+        for _ in 0..100 {
+            let session = repository
+                .claude_code
+                .get_session(*retry_id)
+                .await
+                .unwrap()
+                .unwrap();
+            if session.status.is_terminal() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let prompt = std::fs::read_to_string(work.join("claude_task.md")).unwrap();
+        assert!(prompt.contains("Merge conflict"));
+        assert!(prompt.contains("a.txt"));
+    }
+
+    /// The mock, except that pushing the flaky branch fails to run at all.
+    struct PushFailsExecutor {
+        mock: MockCommandExecutor,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for PushFailsExecutor {
+        async fn execute(
+            &self,
+            program: &str,
+            args: &[&str],
+            working_dir: Option<&Path>,
+            env_vars: Option<HashMap<String, String>>,
+        ) -> Result<CommandOutput> {
+            if args.contains(&"HEAD:refs/heads/feature-flaky") {
+                anyhow::bail!("git is gone");
+            }
+            self.mock
+                .execute(program, args, working_dir, env_vars)
+                .await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_reported_per_pr_and_retries_follow_the_policy() {
+        let temp = TempDir::new().unwrap();
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let server = FakeHostingServer::start().await.unwrap();
+        let repo = RepoRef::new("owner", "repo");
+        let mut entries = Vec::new();
+        for (title, branch) in [("Flaky", "feature-flaky"), ("Fine", "feature-fine")] {
+            let task = Task::new(title.to_string(), String::new());
+            repository.tasks.create(&task).await.unwrap();
+            let pull = server.open_pull_request(
+                &repo,
+                &NewPullRequest {
+                    title: title.to_string(),
+                    body: String::new(),
+                    head: branch.to_string(),
+                    base: "main".to_string(),
+                },
+                "claude",
+            );
+            let mut session = ClaudeCodeSession::new(task.id);
+            session.branch_name = Some(branch.to_string());
+            session.pr_number = Some(pull.number as i32);
+            repository.claude_code.create_session(&session).await.unwrap();
+            entries.push(MergeQueueEntry::from_session(&session).unwrap());
+        }
+        let flaky = entries[0].clone();
+
+        let mut config = ClaudeCodeConfig::new("repo".to_string(), "owner".to_string());
+        config.working_directory = Some(temp.path().to_string_lossy().to_string());
+        config.auto_create_pr = false;
+        config.verification_pipeline.steps = vec![VerificationStep::new("test", "cargo test")];
+        let template =
+            ClaudePromptTemplate::new("test".to_string(), "Task: {{task_title}}".to_string());
+
+        // A git call that can't run fails that PR only; the next one lands
+        let mock = MockCommandExecutor::new().with_delay(0);
+        let executor: Arc<dyn CommandExecutor> = Arc::new(PushFailsExecutor { mock: mock.clone() });
+        let agent =
+            ClaudeCodeService::with_executor(repository.claude_code.clone(), executor.clone());
+        let service = MergeQueueService::new(
+            repository.clone(),
+            executor,
+            Arc::new(server.github_client()),
+        );
+        let results = service
+            .process(entries, &agent, &config, &template, |_| true)
+            .await
+            .unwrap();
+        let MergeOutcome::Failed(error) = &results[0].outcome else {
+            panic!("expected a failure, got {:?}", results[0].outcome);
+        };
+        assert!(error.contains("git push"), "{}", error);
+        assert_eq!(results[1].outcome, MergeOutcome::Merged);
+
+        // Everything but adding and removing the queue's worktree runs in it
+        let repo_dir = Some(temp.path().to_string_lossy().to_string());
+        let git_calls: Vec<_> = mock
+            .get_call_history()
+            .into_iter()
+            .filter(|call| call.program == "git")
+            .collect();
+        let in_repo: Vec<&str> = git_calls
+            .iter()
+            .filter(|call| call.working_dir == repo_dir)
+            .map(|call| call.args[1].as_str())
+            .collect();
+        assert_eq!(in_repo, ["add", "remove"]);
+        assert!(git_calls.iter().any(|call| call.args == ["rebase", "--abort"]));
+
+        // A rebased branch failing its checks goes back to the agent with
+        // them, unless it has no retries left
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.mock_claude_success();
+        mock.add_response("sh", vec!["cargo test"], "", "1 test failed", false);
+        let executor: Arc<dyn CommandExecutor> = Arc::new(mock);
+        let agent =
+            ClaudeCodeService::with_executor(repository.claude_code.clone(), executor.clone());
+        let service = MergeQueueService::new(
+            repository.clone(),
+            executor,
+            Arc::new(server.github_client()),
+        );
+        let results = service
+            .process(vec![flaky.clone()], &agent, &config, &template, |_| false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            results[0].outcome,
+            MergeOutcome::VerificationFailed { retry_session_id: None, .. }
+        ));
+
+        let results = service
+            .process(vec![flaky.clone()], &agent, &config, &template, |_| true)
+            .await
+            .unwrap();
+        let MergeOutcome::VerificationFailed {
+            report,
+            retry_session_id: Some(retry_id),
+        } = &results[0].outcome
+        else {
+            panic!("expected failed checks, got {:?}", results[0].outcome);
+        };
+        assert!(!report.passed());
+        let retry = repository
+            .claude_code
+            .get_session(*retry_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retry.retry_of, Some(flaky.session_id));
+        assert_eq!(retry.branch_name.as_deref(), Some("feature-flaky"));
+    }
+}
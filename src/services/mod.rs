@@ -15,20 +15,38 @@ mod task_service;
 mod auto_run_e2e_tests;
 #[cfg(test)]
 mod claude_automation_e2e_tests;
+mod pr_monitor;
 mod pr_review_service;
 // #[cfg(test)]
 // mod error_recovery_tests;  // Temporarily disabled - needs fixes
 // #[cfg(test)]
 // mod stress_tests;  // Temporarily disabled - needs fixes
+pub mod agent_backend;
 pub mod command_executor;
 pub mod summarization;
+pub mod prompt_context;
 pub mod timeline_scheduler;
 pub mod claude_automation;
 pub mod workspace_service;
 pub mod task_dependency_service;
 pub mod time_tracking_service;
 pub mod export_service;
+pub mod verification_service;
+pub mod merge_queue_service;
+pub mod planning_service;
+pub mod hosting;
+pub mod llm;
+pub mod embeddings;
+pub mod topic_service;
+pub mod duplicate_detector;
+pub mod status_report;
+pub mod calendar_service;
+pub mod approval_service;
 
+pub use agent_backend::{
+    AgentBackend, AgentCapabilities, AgentInvocation, AgentOutcome, ClaudeCliBackend,
+    ScriptedAgentBackend, ScriptedRun, ShellAgentBackend,
+};
 pub use auto_run_orchestrator::{
     AutoRunConfig, AutoRunOrchestrator, AutoRunStatus, AutoRunProgress, TaskExecution,
     TaskExecutionStatus,
@@ -36,13 +54,37 @@ pub use auto_run_orchestrator::{
 pub use claude_code_service::ClaudeCodeService;
 pub use dependency_service::DependencyService;
 pub use goal_service::GoalService;
+pub use pr_monitor::{PrMonitor, PrSyncUpdate, start_pr_monitor_background};
 pub use pr_review_service::PRReviewService;
+pub use prompt_context::PromptContextBuilder;
 pub use recurring_service::RecurringService;
 pub use resource_service::ResourceService;
 pub use task_config_service::TaskConfigService;
-pub use task_service::TaskService;
+pub use task_service::{CreatedTask, TaskService, TransitionOutcome};
 pub use claude_automation::ClaudeAutomation;
 pub use workspace_service::{WorkspaceService, WorkspaceType};
 pub use task_dependency_service::TaskDependencyService;
 pub use time_tracking_service::{TimeTrackingService, TimeEntry};
 pub use export_service::{ExportService, ExportFormat};
+pub use verification_service::VerificationService;
+pub use approval_service::ApprovalService;
+pub use merge_queue_service::{MergeOutcome, MergeQueueEntry, MergeQueueService, MergeResult};
+pub use planning_service::{AgentPlanGenerator, CommittedPlan, PlanGenerator, PlanningService};
+pub use hosting::{
+    FakeHostingServer, GitHubClient, GiteaClient, HostingClient, HostingError, PullRequestRef,
+    RepoRef,
+};
+pub use llm::{
+    AnthropicProvider, LlmConfig, LlmError, LlmProvider, LlmRequest, OllamaProvider,
+    OnChunk, OpenAiCompatibleProvider, ReplayProvider,
+};
+pub use embeddings::{EmbeddingProvider, OllamaEmbeddings, OpenAiEmbeddings, TfIdfEmbedder};
+pub use topic_service::{SimilarTask, TopicCluster, TopicService};
+pub use duplicate_detector::{DuplicateCandidate, DuplicateDetector};
+pub use calendar_service::{
+    CalendarFeed, CalendarService, IcsImport, serve_calendar_feed, start_calendar_feed_from_settings,
+};
+pub use status_report::{
+    ReportPeriod, ReportScope, StatusReport, StatusReportScheduler, StatusReportService,
+    start_status_reports_background,
+};
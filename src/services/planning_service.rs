@@ -0,0 +1,518 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::claude_code::AgentBudget;
+use crate::domain::dependency::{Dependency, DependencyType};
+use crate::domain::goal::Goal;
+use crate::domain::plan::TaskPlan;
+use crate::domain::task::{Position, Task};
+use crate::repository::Repository;
+use crate::services::agent_backend::{AgentBackend, AgentInvocation};
+use crate::services::dependency_service::DependencyService;
+use crate::services::llm::OnChunk;
+use crate::services::summarization::SummarizationService;
+use crate::services::task_service::TaskService;
+
+const PLANNER_SYSTEM: &str =
+    "You are a project planner. You break software tasks into small, concrete steps \
+     and answer with JSON only.";
+
+/// Sibling tasks listed in the prompt as context, at most.
+const MAX_CONTEXT_TASKS: usize = 20;
+
+/// Turns a planning prompt into model output containing a JSON plan.
+#[async_trait]
+pub trait PlanGenerator: Send + Sync {
+    async fn generate(&self, prompt: &str) -> Result<String>;
+
+    /// Like `generate`, passing output to `on_chunk` as it arrives.
+    /// Generators that cannot stream pass everything at the end.
+    async fn generate_streaming(
+        &self,
+        prompt: &str,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        let output = self.generate(prompt).await?;
+        on_chunk(&output);
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl PlanGenerator for SummarizationService {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        self.complete(PLANNER_SYSTEM, prompt, 2000).await
+    }
+
+    async fn generate_streaming(
+        &self,
+        prompt: &str,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        self.stream(PLANNER_SYSTEM, prompt, 2000, on_chunk).await
+    }
+}
+
+/// Plans with a coding agent run in the project directory, so the plan can
+/// take the code into account. The agent is asked not to change anything.
+pub struct AgentPlanGenerator {
+    backend: Arc<dyn AgentBackend>,
+    work_dir: PathBuf,
+}
+
+impl AgentPlanGenerator {
+    pub fn new(backend: Arc<dyn AgentBackend>, work_dir: PathBuf) -> Self {
+        Self { backend, work_dir }
+    }
+}
+
+#[async_trait]
+impl PlanGenerator for AgentPlanGenerator {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        let prompt = format!(
+            "{}\n\nDo not modify any files; only print the plan.",
+            prompt
+        );
+        let prompt_file = self.work_dir.join("claude_plan.md");
+        fs::write(&prompt_file, &prompt)?;
+        let invocation = AgentInvocation {
+            work_dir: self.work_dir.clone(),
+            prompt,
+            prompt_file: prompt_file.clone(),
+            instructions_file: None,
+            branch: String::new(),
+            model: None,
+            env_vars: HashMap::new(),
+            budget: AgentBudget::default(),
+        };
+
+        self.backend.start(&invocation).await?;
+        let outcome = self.backend.run(&invocation).await;
+        let finished = self.backend.finish(&invocation).await;
+        let _ = fs::remove_file(&prompt_file);
+        let outcome = outcome?;
+        finished?;
+
+        if !outcome.success {
+            anyhow::bail!("{} failed to plan: {}", self.backend.name(), outcome.stderr);
+        }
+        Ok(outcome.stdout)
+    }
+}
+
+/// What committing a plan created.
+#[derive(Debug, Clone)]
+pub struct CommittedPlan {
+    /// The planned task with its new subtasks
+    pub task: Task,
+    /// New child tasks, in the order they were created
+    pub tasks: Vec<Task>,
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Breaks a task down with a model and saves the reviewed plan.
+pub struct PlanningService {
+    repository: Arc<Repository>,
+    generator: Arc<dyn PlanGenerator>,
+    task_service: TaskService,
+    dependency_service: DependencyService,
+}
+
+impl PlanningService {
+    pub fn new(repository: Arc<Repository>, generator: Arc<dyn PlanGenerator>) -> Self {
+        Self {
+            task_service: TaskService::new(repository.clone()),
+            dependency_service: DependencyService::new(repository.clone()),
+            repository,
+            generator,
+        }
+    }
+
+    /// Ask the model for a plan for `task`. Nothing is saved.
+    pub async fn propose(&self, task: &Task) -> Result<TaskPlan> {
+        self.propose_streaming(task, &mut |_| {}).await
+    }
+
+    /// Like `propose`, passing the model's output to `on_chunk` as it streams.
+    pub async fn propose_streaming(
+        &self,
+        task: &Task,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<TaskPlan> {
+        let goal = match task.goal_id {
+            Some(goal_id) => self.repository.goals.get(goal_id).await?,
+            None => None,
+        };
+        let mut siblings = Vec::new();
+        if let Some(goal) = &goal {
+            for task_id in goal.task_ids.iter().filter(|id| **id != task.id) {
+                if siblings.len() == MAX_CONTEXT_TASKS {
+                    break;
+                }
+                if let Some(sibling) = self.task_service.get(*task_id).await? {
+                    siblings.push(sibling);
+                }
+            }
+        }
+
+        let prompt = Self::build_prompt(task, goal.as_ref(), &siblings);
+        let output = self.generator.generate_streaming(&prompt, on_chunk).await?;
+        TaskPlan::parse(&output).context("Could not read the plan")
+    }
+
+    pub fn build_prompt(task: &Task, goal: Option<&Goal>, siblings: &[Task]) -> String {
+        let mut prompt = format!(
+            "Break the following task into a plan.\n\n## Task\n\nTitle: {}\n",
+            task.title
+        );
+        if !task.description.trim().is_empty() {
+            prompt.push_str(&format!("Description:\n{}\n", task.description.trim()));
+        }
+        if let Some(hours) = task.estimated_hours {
+            prompt.push_str(&format!("Estimated hours: {}\n", hours));
+        }
+        if !task.subtasks.is_empty() {
+            prompt.push_str("Existing subtasks:\n");
+            for subtask in &task.subtasks {
+                prompt.push_str(&format!("- {}\n", subtask.description));
+            }
+        }
+
+        if let Some(goal) = goal {
+            prompt.push_str(&format!("\n## Goal\n\n{}\n", goal.title));
+            if !goal.description.trim().is_empty() {
+                prompt.push_str(&format!("{}\n", goal.description.trim()));
+            }
+            if !siblings.is_empty() {
+                prompt.push_str("\nOther tasks for this goal:\n");
+                for sibling in siblings {
+                    prompt.push_str(&format!("- {} ({:?})\n", sibling.title, sibling.status));
+                }
+            }
+        }
+
+        prompt.push_str(
+            "\n## Answer format\n\n\
+             Reply with a single JSON object and nothing else:\n\
+             {\n  \"subtasks\": [\"short checklist item for this task\"],\n  \
+             \"tasks\": [\n    {\"key\": \"short-id\", \"title\": \"...\", \"description\": \"...\", \
+             \"estimated_hours\": 2.0, \"depends_on\": [\"key of a task that must finish first\"]}\n  ]\n}\n\n\
+             Use `subtasks` for small steps that belong to this task and `tasks` for pieces of work \
+             big enough to track and schedule on their own. Leave either list empty if it is not needed. \
+             Do not repeat existing subtasks or tasks.\n",
+        );
+        prompt
+    }
+
+    /// Save a reviewed plan: subtasks are added to the task, planned tasks become
+    /// its children in the same goal, and `depends_on` becomes finish-to-start
+    /// dependencies between them. If anything fails, the children saved so far
+    /// are deleted and the task is put back as it was.
+    pub async fn commit(&self, task_id: Uuid, plan: &TaskPlan) -> Result<CommittedPlan> {
+        let mut plan = plan.clone();
+        plan.normalize();
+        plan.validate()?;
+
+        let original = self
+            .task_service
+            .get(task_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", task_id))?;
+
+        let mut created = Vec::new();
+        match self.save_plan(original.clone(), &plan, &mut created).await {
+            Ok((task, dependencies)) => Ok(CommittedPlan {
+                task,
+                tasks: created,
+                dependencies,
+            }),
+            Err(e) => {
+                // Deleting the children drops their goal links and dependencies
+                for child in &created {
+                    self.repository.tasks.delete(child.id).await?;
+                }
+                self.repository.tasks.update(&original).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// The steps of `commit`, pushing each child onto `created` once saved.
+    async fn save_plan(
+        &self,
+        mut task: Task,
+        plan: &TaskPlan,
+        created: &mut Vec<Task>,
+    ) -> Result<(Task, Vec<Dependency>)> {
+        if !plan.subtasks.is_empty() {
+            for subtask in &plan.subtasks {
+                task.add_subtask(subtask.clone());
+            }
+            task = self.task_service.update(task).await?;
+        }
+
+        let mut ids: HashMap<&str, Uuid> = HashMap::new();
+        for (column, i) in plan.creation_order()?.into_iter().enumerate() {
+            let planned = &plan.tasks[i];
+            let mut child = Task::new(planned.title.clone(), planned.description.clone());
+            child.estimated_hours = planned.estimated_hours;
+            child.priority = task.priority;
+            child.goal_id = task.goal_id;
+            child.parent_task_id = Some(task.id);
+            // Lay children out in a row below the task on the map
+            child.position = Position {
+                x: task.position.x + column as f64 * 220.0,
+                y: task.position.y + 150.0,
+            };
+            let child = self.task_service.create(child).await?.task;
+            ids.insert(planned.key.as_str(), child.id);
+            created.push(child);
+        }
+
+        if let Some(goal_id) = task.goal_id
+            && !created.is_empty()
+            && let Some(mut goal) = self.repository.goals.get(goal_id).await?
+        {
+            for child in created.iter() {
+                goal.add_task(child.id);
+            }
+            self.repository.goals.update(&goal).await?;
+        }
+
+        let mut dependencies = Vec::new();
+        for planned in &plan.tasks {
+            for prerequisite in &planned.depends_on {
+                dependencies.push(
+                    self.dependency_service
+                        .create_dependency(
+                            ids[planned.key.as_str()],
+                            ids[prerequisite.as_str()],
+                            DependencyType::FinishToStart,
+                        )
+                        .await?,
+                );
+            }
+        }
+
+        Ok((task, dependencies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::plan::PlannedTask;
+    use crate::repository::database::init_test_database;
+    use crate::services::agent_backend::{ScriptedAgentBackend, ScriptedRun};
+    use crate::services::llm::ReplayProvider;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Returns a fixed answer and remembers the prompts it was given.
+    struct CannedGenerator {
+        output: String,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl PlanGenerator for CannedGenerator {
+        async fn generate(&self, prompt: &str) -> Result<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.output.clone())
+        }
+    }
+
+    const PLAN: &str = r#"{
+        "subtasks": ["Check the current exporter"],
+        "tasks": [
+            {"key": "api", "title": "Build the API", "estimated_hours": 5, "depends_on": ["schema"]},
+            {"key": "schema", "title": "Design the schema", "estimated_hours": 2},
+            {"key": "docs", "title": "Write docs", "depends_on": ["api", "schema"]}
+        ]
+    }"#;
+
+    async fn setup() -> (Arc<Repository>, Task, Goal) {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+
+        let mut goal = Goal::new("Reporting".to_string(), "Better reports".to_string());
+        repository.goals.create(&goal).await.unwrap();
+        let mut task = Task::new("Export to CSV".to_string(), "Users want CSV".to_string());
+        task.goal_id = Some(goal.id);
+        let sibling = Task::new("Add PDF export".to_string(), String::new());
+        repository.tasks.create(&task).await.unwrap();
+        repository.tasks.create(&sibling).await.unwrap();
+        goal.add_task(task.id);
+        goal.add_task(sibling.id);
+        repository.goals.update(&goal).await.unwrap();
+
+        (repository, task, goal)
+    }
+
+    #[tokio::test]
+    async fn test_propose_sends_goal_context() {
+        let (repository, task, _goal) = setup().await;
+        let generator = Arc::new(CannedGenerator {
+            output: format!("Sure!\n```json\n{}\n```", PLAN),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let service = PlanningService::new(repository.clone(), generator.clone());
+
+        let plan = service.propose(&task).await.unwrap();
+        assert_eq!(plan.subtasks.len(), 1);
+        assert_eq!(plan.tasks.len(), 3);
+
+        let prompts = generator.prompts.lock().unwrap();
+        assert!(prompts[0].contains("Title: Export to CSV"));
+        assert!(prompts[0].contains("Reporting"));
+        assert!(prompts[0].contains("Add PDF export"));
+    }
+
+    #[tokio::test]
+    async fn test_commit_creates_children_and_dependencies() {
+        let (repository, task, goal) = setup().await;
+        let generator = Arc::new(CannedGenerator {
+            output: PLAN.to_string(),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let service = PlanningService::new(repository.clone(), generator);
+
+        // The preview edits the plan before it is committed
+        let mut plan = service.propose(&task).await.unwrap();
+        plan.tasks[0].title = "Build the REST API".to_string();
+        plan.tasks.push(PlannedTask {
+            key: "release".to_string(),
+            title: "Release".to_string(),
+            depends_on: vec!["docs".to_string()],
+            ..Default::default()
+        });
+
+        let committed = service.commit(task.id, &plan).await.unwrap();
+        let titles: Vec<&str> = committed.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec!["Design the schema", "Build the REST API", "Write docs", "Release"]
+        );
+        assert_eq!(committed.dependencies.len(), 4);
+        assert!(committed
+            .dependencies
+            .iter()
+            .all(|d| d.dependency_type == DependencyType::FinishToStart));
+
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.subtasks.len(), 1);
+        assert_eq!(stored.subtasks[0].description, "Check the current exporter");
+
+        let schema = &committed.tasks[0];
+        let api = &committed.tasks[1];
+        let stored_api = repository.tasks.get(api.id).await.unwrap().unwrap();
+        assert_eq!(stored_api.parent_task_id, Some(task.id));
+        assert_eq!(stored_api.goal_id, Some(goal.id));
+        assert_eq!(stored_api.estimated_hours, Some(5.0));
+
+        let api_prerequisites = DependencyService::new(repository.clone())
+            .get_dependencies(api.id)
+            .await
+            .unwrap();
+        assert_eq!(api_prerequisites, vec![schema.id]);
+
+        let goal = repository.goals.get(goal.id).await.unwrap().unwrap();
+        assert!(committed.tasks.iter().all(|t| goal.task_ids.contains(&t.id)));
+    }
+
+    #[tokio::test]
+    async fn test_propose_with_recorded_model() {
+        let (repository, task, _goal) = setup().await;
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/llm");
+        let generator = Arc::new(SummarizationService::with_provider(Arc::new(
+            ReplayProvider::new(fixtures),
+        )));
+        let service = PlanningService::new(repository, generator);
+
+        let mut streamed = String::new();
+        let plan = service
+            .propose_streaming(&task, &mut |chunk| streamed.push_str(chunk))
+            .await
+            .unwrap();
+        assert!(streamed.contains("\"key\": \"writer\""));
+        assert_eq!(plan.subtasks, vec!["List the columns users need"]);
+        assert_eq!(plan.tasks[1].depends_on, vec!["writer"]);
+        assert_eq!(plan.total_estimated_hours(), 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_commit_rejects_invalid_plan() {
+        let (repository, task, _goal) = setup().await;
+        let generator = Arc::new(CannedGenerator {
+            output: String::new(),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let service = PlanningService::new(repository.clone(), generator);
+
+        let plan = TaskPlan {
+            subtasks: vec!["Step".to_string()],
+            tasks: vec![PlannedTask {
+                key: "a".to_string(),
+                title: "A".to_string(),
+                depends_on: vec!["a".to_string()],
+                ..Default::default()
+            }],
+        };
+        assert!(service.commit(task.id, &plan).await.is_err());
+
+        // Nothing was saved
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert!(stored.subtasks.is_empty());
+        assert_eq!(repository.tasks.list(Default::default()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_is_rolled_back() {
+        let (repository, task, goal) = setup().await;
+        let generator = Arc::new(CannedGenerator {
+            output: PLAN.to_string(),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let service = PlanningService::new(repository.clone(), generator);
+        let plan = service.propose(&task).await.unwrap();
+
+        // Saving fails after the children, the goal and a dependency are saved
+        sqlx::query(
+            "CREATE TRIGGER fail_second_dependency BEFORE INSERT ON dependencies
+             WHEN (SELECT COUNT(*) FROM dependencies) >= 1
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END",
+        )
+        .execute(&*repository.pool)
+        .await
+        .unwrap();
+        assert!(service.commit(task.id, &plan).await.is_err());
+
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert!(stored.subtasks.is_empty());
+        assert_eq!(repository.tasks.list(Default::default()).await.unwrap().len(), 2);
+        assert!(repository.dependencies.list_all().await.unwrap().is_empty());
+        let goal = repository.goals.get(goal.id).await.unwrap().unwrap();
+        assert_eq!(goal.task_ids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_agent_plan_generator() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = ScriptedAgentBackend::new(vec![ScriptedRun::success(PLAN)]);
+        let generator = AgentPlanGenerator::new(Arc::new(backend.clone()), temp_dir.path().into());
+
+        let plan = TaskPlan::parse(&generator.generate("Plan it").await.unwrap()).unwrap();
+        assert_eq!(plan.tasks.len(), 3);
+
+        let invocations = backend.invocations();
+        assert!(invocations[0].prompt.starts_with("Plan it"));
+        assert!(invocations[0].prompt.contains("Do not modify any files"));
+        assert_eq!(backend.finished_sessions(), 1);
+        assert!(!temp_dir.path().join("claude_plan.md").exists());
+    }
+}
@@ -1,136 +1,480 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::domain::claude_code::ClaudeCodeSession;
+use crate::domain::comment::{Comment, EntityType};
+use crate::domain::task::TaskStatus;
 use crate::repository::Repository;
-use crate::services::ClaudeAutomation;
-use std::path::PathBuf;
-use anyhow::Result;
+use crate::services::task_service::TaskService;
+use crate::services::hosting::{
+    CheckState, HostingClient, PullRequest, PullRequestRef, PullRequestState, Review, ReviewState,
+};
+
+/// Author of comments about the PR itself (merged, closed).
+const MONITOR_AUTHOR: &str = "PR monitor";
+/// Author of comments mirroring check results.
+const CI_AUTHOR: &str = "CI";
 
+/// What one sync of a session's PR changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrSyncUpdate {
+    pub session_id: Uuid,
+    pub task_id: Uuid,
+    pub pr_state: PullRequestState,
+    /// The task's new status, if the PR was merged or closed in this sync
+    pub task_status: Option<TaskStatus>,
+    pub comments_added: usize,
+}
+
+/// Mirrors the state of agent PRs back into their tasks.
+///
+/// Each sync looks at every Claude Code session with a PR that has not been
+/// resolved yet. Merged PRs move the task to Done; PRs closed without merging
+/// move it back to Todo. A move the task's workflow refuses is reported and
+/// retried on later syncs. New reviews, PR comments and finished check runs are
+/// copied into the task's comment thread. What has been mirrored is recorded on
+/// the session so nothing is posted twice.
 pub struct PrMonitor {
-    repository: Repository,
-    automation: ClaudeAutomation,
+    repository: Arc<Repository>,
+    hosting: Arc<dyn HostingClient>,
     check_interval: Duration,
 }
 
 impl PrMonitor {
-    pub fn new(repository: Repository, workspace_dir: PathBuf) -> Self {
-        let automation = ClaudeAutomation::with_repository(workspace_dir, repository.clone());
+    pub fn new(repository: Arc<Repository>, hosting: Arc<dyn HostingClient>) -> Self {
         Self {
             repository,
-            automation,
+            hosting,
             check_interval: Duration::from_secs(60), // Check every minute
         }
     }
-    
-    /// Start monitoring PR status for active task executions
+
+    pub fn with_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Sync PR state until the task is aborted.
     pub async fn start_monitoring(&self) {
         let mut interval = interval(self.check_interval);
-        
+
         loop {
             interval.tick().await;
-            
-            if let Err(e) = self.check_all_active_executions().await {
-                eprintln!("Error checking PR status: {}", e);
+
+            if let Err(e) = self.sync_all().await {
+                tracing::error!("Error checking PR status: {}", e);
             }
         }
     }
-    
-    /// Check all active task executions for PR status updates
-    async fn check_all_active_executions(&self) -> Result<()> {
-        use crate::domain::task_execution::ExecutionStatus;
-        
-        // Get all active executions (Running or PendingReview status)
-        let running_status = serde_json::to_string(&ExecutionStatus::Running)?;
-        let pending_status = serde_json::to_string(&ExecutionStatus::PendingReview)?;
-        
-        let active_executions = sqlx::query!(
-            r#"
-            SELECT id 
-            FROM task_executions 
-            WHERE status = ? OR status = ?
-            "#,
-            running_status,
-            pending_status
-        )
-        .fetch_all(&*self.repository.pool)
-        .await?;
-        
-        for row in active_executions {
-            // row.id is Option<String> from sqlx::query!
-            if let Some(ref id_str) = row.id {
-                if let Ok(id) = uuid::Uuid::parse_str(id_str) {
-                    // Update each execution's status
-                    if let Err(e) = self.automation.update_execution_status(id).await {
-                        eprintln!("Failed to update execution {}: {}", id, e);
+
+    /// Sync every session with an unresolved PR. A failure on one PR is logged
+    /// and does not stop the others.
+    pub async fn sync_all(&self) -> Result<Vec<PrSyncUpdate>> {
+        let sessions = self
+            .repository
+            .claude_code
+            .get_sessions_with_open_prs()
+            .await?;
+
+        let mut updates = Vec::new();
+        for session in sessions {
+            let session_id = session.id;
+            match self.sync_session(session).await {
+                Ok(update) => updates.push(update),
+                Err(e) => tracing::warn!("Failed to sync PR for session {}: {}", session_id, e),
+            }
+        }
+        Ok(updates)
+    }
+
+    pub async fn sync_session(&self, mut session: ClaudeCodeSession) -> Result<PrSyncUpdate> {
+        let pr_url = session
+            .pr_url
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Session {} has no pull request", session.id))?;
+        let pr = PullRequestRef::parse_url(&pr_url)?;
+        let pull = self
+            .hosting
+            .get_pull_request(&pr)
+            .await
+            .with_context(|| format!("Failed to fetch {}", pr))?;
+
+        let mut comments = Vec::new();
+        let sync = &mut session.pr_sync;
+
+        for review in self.hosting.reviews(&pr).await? {
+            if review.state == ReviewState::Pending || sync.review_ids.contains(&review.id) {
+                continue;
+            }
+            sync.review_ids.push(review.id);
+            // Reviews that only carry line comments have no summary worth posting
+            if review.state == ReviewState::Commented && review.body.trim().is_empty() {
+                continue;
+            }
+            comments.push((review.author.clone(), review_comment(&pull, &review)));
+        }
+
+        for comment in self.hosting.comments(&pr).await? {
+            if sync.comment_ids.contains(&comment.id) {
+                continue;
+            }
+            sync.comment_ids.push(comment.id);
+            comments.push((
+                comment.author,
+                format!("On [PR #{}]({}):\n\n{}", pull.number, pull.url, comment.body),
+            ));
+        }
+
+        for check in self.hosting.checks(&pr).await? {
+            let passed = match check.state {
+                CheckState::Pending => continue,
+                CheckState::Success => true,
+                CheckState::Failure => false,
+            };
+            if sync.checks.get(&check.name) == Some(&passed) {
+                continue;
+            }
+            sync.checks.insert(check.name.clone(), passed);
+            let mut content = if passed {
+                format!("✅ Check `{}` passed on PR #{}", check.name, pull.number)
+            } else {
+                format!("❌ Check `{}` failed on PR #{}", check.name, pull.number)
+            };
+            if let Some(url) = &check.details_url {
+                content.push_str(&format!(" ([details]({}))", url));
+            }
+            comments.push((CI_AUTHOR.to_string(), content));
+        }
+
+        let target = match pull.state {
+            PullRequestState::Open => None,
+            PullRequestState::Merged => Some(TaskStatus::Done),
+            PullRequestState::Closed => Some(TaskStatus::Todo),
+        };
+        let mut task_status = None;
+        if let Some(status) = target {
+            let link = format!("[PR #{}]({})", pull.number, pull.url);
+            let resolution = if status == TaskStatus::Done {
+                "merged"
+            } else {
+                "closed without merging"
+            };
+            match self.move_task(session.task_id, status).await? {
+                None => {
+                    let content = if status == TaskStatus::Done {
+                        format!("{} was merged.", link)
+                    } else {
+                        format!("{} was {}, so the task is back in Todo.", link, resolution)
+                    };
+                    comments.push((MONITOR_AUTHOR.to_string(), content));
+                    task_status = Some(status);
+                }
+                Some(reason) => {
+                    tracing::warn!(
+                        "Could not move task {} to {:?}: {}",
+                        session.task_id,
+                        status,
+                        reason
+                    );
+                    // The move is tried again on every sync but reported once
+                    if !sync.move_refused {
+                        sync.move_refused = true;
+                        comments.push((
+                            MONITOR_AUTHOR.to_string(),
+                            format!(
+                                "{} was {}, but the task's workflow refused the move to {:?}: {}",
+                                link, resolution, status, reason
+                            ),
+                        ));
                     }
                 }
             }
         }
-        
-        Ok(())
+        sync.closed = task_status.is_some();
+        sync.synced_at = Some(Utc::now());
+
+        // The mirrored comments and the record of them are saved together
+        let comments: Vec<Comment> = comments
+            .into_iter()
+            .map(|(author, content)| {
+                Comment::new(session.task_id, EntityType::Task, author, content)
+            })
+            .collect();
+        let comments_added = comments.len();
+        session.updated_at = Utc::now();
+        self.repository
+            .claude_code
+            .save_pr_sync(&session, &comments)
+            .await?;
+
+        Ok(PrSyncUpdate {
+            session_id: session.id,
+            task_id: session.task_id,
+            pr_state: pull.state,
+            task_status,
+            comments_added,
+        })
     }
-    
-    /// Get recent PR activity for reporting
-    pub async fn get_recent_pr_activity(&self, hours: i64) -> Result<Vec<PrActivity>> {
-        use chrono::{Utc, Duration as ChronoDuration};
-        use crate::domain::task_execution::ExecutionStatus;
-        
-        let since = Utc::now() - ChronoDuration::hours(hours);
-        let pending_status = serde_json::to_string(&ExecutionStatus::PendingReview)?;
-        
-        let rows = sqlx::query!(
-            r#"
-            SELECT te.id, te.task_id, te.pr_url, te.status, te.completed_at, t.title
-            FROM task_executions te
-            JOIN tasks t ON te.task_id = t.id
-            WHERE te.pr_url IS NOT NULL 
-                AND (te.completed_at > ? OR te.status = ?)
-            ORDER BY te.completed_at DESC
-            "#,
-            since,
-            pending_status
-        )
-        .fetch_all(&*self.repository.pool)
-        .await?;
-        
-        let mut activities = Vec::new();
-        for row in rows {
-            if let Some(ref id_str) = row.id {
-                let status: ExecutionStatus = serde_json::from_str(&row.status)?;
-                let execution_id = uuid::Uuid::parse_str(id_str)?;
-                let task_id = uuid::Uuid::parse_str(&row.task_id)?;
-                
-                // Convert NaiveDateTime to DateTime<Utc> if present
-                let completed_at = row.completed_at.map(|dt| chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(dt, chrono::Utc));
-                
-                activities.push(PrActivity {
-                    execution_id,
-                    task_id,
-                    task_title: row.title,
-                    pr_url: row.pr_url.unwrap_or_default(),
-                    status,
-                    completed_at,
-                });
-            }
+
+    /// Move a task to `status` through its workflow. Returns why the move
+    /// was refused; a task already there, or deleted, needs no move.
+    async fn move_task(&self, task_id: Uuid, status: TaskStatus) -> Result<Option<String>> {
+        let Some(mut task) = self.repository.tasks.get(task_id).await? else {
+            return Ok(None);
+        };
+        if task.status == status {
+            return Ok(None);
+        }
+        task.update_status(status);
+        let task_service = TaskService::new(self.repository.clone());
+        match task_service.update(task).await {
+            Ok(task) if task.status == status => Ok(None),
+            Ok(_) => Ok(Some("the move awaits approval".to_string())),
+            Err(e) => Ok(Some(e.to_string())),
         }
-        
-        Ok(activities)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct PrActivity {
-    pub execution_id: uuid::Uuid,
-    pub task_id: uuid::Uuid,
-    pub task_title: String,
-    pub pr_url: String,
-    pub status: crate::domain::task_execution::ExecutionStatus,
-    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+fn review_comment(pull: &PullRequest, review: &Review) -> String {
+    let verdict = match review.state {
+        ReviewState::Approved => "approved",
+        ReviewState::ChangesRequested => "requested changes on",
+        ReviewState::Dismissed => "had a review dismissed on",
+        _ => "commented on",
+    };
+    let mut content = format!(
+        "{} {} [PR #{}]({})",
+        review.author, verdict, pull.number, pull.url
+    );
+    if !review.body.trim().is_empty() {
+        content.push_str(&format!(":\n\n{}", review.body.trim()));
+    }
+    content
 }
 
 /// Start the PR monitor in the background
-pub async fn start_pr_monitor_background(repository: Repository, workspace_dir: PathBuf) {
+pub fn start_pr_monitor_background(
+    repository: Arc<Repository>,
+    hosting: Arc<dyn HostingClient>,
+) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        let monitor = PrMonitor::new(repository, workspace_dir);
+        let monitor = PrMonitor::new(repository, hosting);
         monitor.start_monitoring().await;
-    });
-}
\ No newline at end of file
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::Task;
+    use crate::repository::database::init_test_database;
+    use crate::services::hosting::{CheckRun, FakeHostingServer, NewPullRequest, RepoRef};
+
+    async fn setup() -> (PrMonitor, Arc<Repository>, FakeHostingServer) {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let server = FakeHostingServer::start().await.unwrap();
+        let monitor = PrMonitor::new(repository.clone(), Arc::new(server.github_client()));
+        (monitor, repository, server)
+    }
+
+    /// A task in Review with a completed session whose PR is open on the fake host.
+    async fn task_with_pr(
+        repository: &Repository,
+        server: &FakeHostingServer,
+        title: &str,
+    ) -> (Task, PullRequestRef) {
+        let mut task = Task::new(title.to_string(), String::new());
+        task.update_status(TaskStatus::Review);
+        repository.tasks.create(&task).await.unwrap();
+
+        let branch = format!("claude/{}", title.to_lowercase());
+        let pull = server.open_pull_request(
+            &RepoRef::new("owner", "repo"),
+            &NewPullRequest {
+                title: title.to_string(),
+                body: String::new(),
+                head: branch.clone(),
+                base: "main".to_string(),
+            },
+            "claude",
+        );
+        let mut session = ClaudeCodeSession::new(task.id);
+        session.branch_name = Some(branch);
+        session.set_pr_info(pull.url.clone(), pull.number as i32);
+        repository
+            .claude_code
+            .create_session(&session)
+            .await
+            .unwrap();
+
+        (task, PullRequestRef::parse_url(&pull.url).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_merged_pr_completes_task_and_mirrors_activity() {
+        let (monitor, repository, server) = setup().await;
+        let (task, pr) = task_with_pr(&repository, &server, "Feature").await;
+
+        server.add_review(&pr, "alice", ReviewState::ChangesRequested, "Please add tests");
+        server.add_comment(&pr, "bob", "Is this behind a flag?");
+        server.set_checks(
+            &pr,
+            vec![
+                CheckRun {
+                    name: "test".to_string(),
+                    state: CheckState::Failure,
+                    details_url: Some("https://ci.example/runs/1".to_string()),
+                },
+                CheckRun {
+                    name: "lint".to_string(),
+                    state: CheckState::Pending,
+                    details_url: None,
+                },
+            ],
+        );
+
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].pr_state, PullRequestState::Open);
+        assert_eq!(updates[0].task_status, None);
+        assert_eq!(updates[0].comments_added, 3);
+
+        let thread = repository.comments.list_for_entity(task.id).await.unwrap();
+        let by = |author: &str| thread.iter().find(|c| c.author_name == author).unwrap();
+        assert!(by("alice").content.contains("requested changes"));
+        assert!(by("alice").content.contains("Please add tests"));
+        assert!(by("bob").content.contains("Is this behind a flag?"));
+        assert!(by(CI_AUTHOR).content.contains("`test` failed"));
+        assert!(by(CI_AUTHOR).content.contains("https://ci.example/runs/1"));
+
+        // Nothing new: nothing is posted twice
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates[0].comments_added, 0);
+
+        // The check is fixed, the reviewer approves and the PR is merged
+        server.set_checks(
+            &pr,
+            vec![CheckRun {
+                name: "test".to_string(),
+                state: CheckState::Success,
+                details_url: None,
+            }],
+        );
+        server.add_review(&pr, "alice", ReviewState::Approved, "");
+        server.set_state(&pr, PullRequestState::Merged);
+
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates[0].task_status, Some(TaskStatus::Done));
+        assert_eq!(updates[0].comments_added, 3);
+
+        let task = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.completed_at.is_some());
+        let thread = repository.comments.list_for_entity(task.id).await.unwrap();
+        assert!(thread.iter().any(|c| c.content.contains("`test` passed")));
+        assert!(thread.iter().any(|c| c.content.contains("alice approved")));
+        assert!(thread.iter().any(|c| c.content.contains("was merged")));
+
+        // Resolved PRs are no longer polled
+        assert!(monitor.sync_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_closed_pr_moves_task_back_to_todo() {
+        let (monitor, repository, server) = setup().await;
+        let (closed_task, closed_pr) = task_with_pr(&repository, &server, "Rejected").await;
+        let (open_task, _) = task_with_pr(&repository, &server, "Pending").await;
+
+        server.set_state(&closed_pr, PullRequestState::Closed);
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates.len(), 2);
+
+        let closed_task = repository.tasks.get(closed_task.id).await.unwrap().unwrap();
+        assert_eq!(closed_task.status, TaskStatus::Todo);
+        let thread = repository
+            .comments
+            .list_for_entity(closed_task.id)
+            .await
+            .unwrap();
+        assert_eq!(thread.len(), 1);
+        assert_eq!(thread[0].author_name, MONITOR_AUTHOR);
+        assert!(thread[0].content.contains("closed without merging"));
+
+        let open_task = repository.tasks.get(open_task.id).await.unwrap().unwrap();
+        assert_eq!(open_task.status, TaskStatus::Review);
+        assert_eq!(monitor.sync_all().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refused_move_is_reported_and_retried() {
+        use crate::domain::task_config::TaskConfiguration;
+
+        let (monitor, repository, server) = setup().await;
+        let (task, pr) = task_with_pr(&repository, &server, "Started").await;
+        // No way back to To Do once started
+        let mut config = TaskConfiguration::new("No way back".to_string());
+        config.state_machine.transitions.retain(|t| t.to_state != "todo");
+        repository.task_configs.create(&config).await.unwrap();
+        let mut task = repository.tasks.get(task.id).await.unwrap().unwrap();
+        task.configuration_id = Some(config.id);
+        task.workflow_state = Some("in_progress".to_string());
+        task.status = TaskStatus::InProgress;
+        repository.tasks.update(&task).await.unwrap();
+
+        server.set_state(&pr, PullRequestState::Closed);
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates[0].task_status, None);
+        assert_eq!(updates[0].comments_added, 1);
+        let thread = repository.comments.list_for_entity(task.id).await.unwrap();
+        assert!(thread[0].content.contains("refused the move to Todo"), "{}", thread[0].content);
+        assert!(!thread[0].content.contains("back in Todo"));
+        let stored = repository.tasks.get(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::InProgress);
+
+        // Still watched, without repeating the refusal
+        let updates = monitor.sync_all().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].comments_added, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sync_state_is_not_saved_without_its_comments() {
+        let (_, repository, server) = setup().await;
+        let (task, _) = task_with_pr(&repository, &server, "Atomic").await;
+        let mut session = repository
+            .claude_code
+            .get_sessions_with_open_prs()
+            .await
+            .unwrap()
+            .remove(0);
+
+        // The second insert fails, so neither the comment nor its id is kept
+        session.pr_sync.comment_ids.push(1);
+        let comment = Comment::new(
+            task.id,
+            EntityType::Task,
+            "bob".to_string(),
+            "Hi".to_string(),
+        );
+        let result = repository
+            .claude_code
+            .save_pr_sync(&session, &[comment.clone(), comment])
+            .await;
+        assert!(result.is_err());
+
+        let thread = repository.comments.list_for_entity(task.id).await.unwrap();
+        assert!(thread.is_empty());
+        let stored = repository
+            .claude_code
+            .get_session(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.pr_sync.comment_ids.is_empty());
+    }
+}
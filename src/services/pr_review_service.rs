@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 use crate::repository::Repository;
 use crate::services::command_executor::CommandExecutor;
+use crate::services::hosting::{
+    HostingClient, MergeMethod, PullRequestRef, PullRequestState, ReviewState,
+};
 
 #[derive(Debug, Clone)]
 pub struct PullRequestInfo {
@@ -41,13 +44,19 @@ pub struct PRReviewService {
     #[allow(dead_code)]
     repository: Arc<Repository>,
     command_executor: Arc<dyn CommandExecutor>,
+    hosting: Arc<dyn HostingClient>,
 }
 
 impl PRReviewService {
-    pub fn new(repository: Arc<Repository>, command_executor: Arc<dyn CommandExecutor>) -> Self {
+    pub fn new(
+        repository: Arc<Repository>,
+        command_executor: Arc<dyn CommandExecutor>,
+        hosting: Arc<dyn HostingClient>,
+    ) -> Self {
         Self {
             repository,
             command_executor,
+            hosting,
         }
     }
 
@@ -98,50 +107,22 @@ impl PRReviewService {
     }
 
     async fn get_pr_info(&self, pr_url: &str) -> Result<PullRequestInfo> {
-        // Parse PR URL to extract owner/repo/number
-        let parts: Vec<&str> = pr_url.split('/').collect();
-        if parts.len() < 7 {
-            return Err(anyhow::anyhow!("Invalid PR URL"));
-        }
-
-        let owner = parts[3];
-        let repo = parts[4];
-        let pr_number = parts[6].parse::<i32>()?;
-
-        // Use gh CLI to get PR info
-        let pr_number_str = pr_number.to_string();
-        let repo_str = format!("{}/{}", owner, repo);
-        let args = vec![
-            "pr",
-            "view",
-            &pr_number_str,
-            "--repo",
-            &repo_str,
-            "--json",
-            "title,body,headRefName,baseRefName,author,createdAt,state",
-        ];
-
-        let output = self
-            .command_executor
-            .execute("gh", &args, None, None)
-            .await?;
-
-        // Parse JSON output
-        let json: serde_json::Value = serde_json::from_str(&output.stdout)?;
+        let pr = PullRequestRef::parse_url(pr_url)?;
+        let pull = self.hosting.get_pull_request(&pr).await?;
 
         Ok(PullRequestInfo {
-            pr_url: pr_url.to_string(),
-            pr_number,
-            title: json["title"].as_str().unwrap_or("").to_string(),
-            description: json["body"].as_str().unwrap_or("").to_string(),
-            branch: json["headRefName"].as_str().unwrap_or("").to_string(),
-            base_branch: json["baseRefName"].as_str().unwrap_or("main").to_string(),
-            author: json["author"]["login"].as_str().unwrap_or("").to_string(),
+            pr_url: pull.url,
+            pr_number: pull.number as i32,
+            title: pull.title,
+            description: pull.body,
+            branch: pull.head,
+            base_branch: pull.base,
+            author: pull.author,
             created_at: Utc::now(), // Simplified for now
-            status: match json["state"].as_str().unwrap_or("OPEN") {
-                "MERGED" => PRStatus::Merged,
-                "CLOSED" => PRStatus::Closed,
-                _ => PRStatus::Open,
+            status: match pull.state {
+                PullRequestState::Merged => PRStatus::Merged,
+                PullRequestState::Closed => PRStatus::Closed,
+                PullRequestState::Open => PRStatus::Open,
             },
         })
     }
@@ -222,48 +203,19 @@ impl PRReviewService {
     }
 
     async fn approve_pr(&self, pr_url: &str) -> Result<()> {
-        // Parse PR URL
-        let parts: Vec<&str> = pr_url.split('/').collect();
-        let owner = parts[3];
-        let repo = parts[4];
-        let pr_number = parts[6];
-
-        // Approve using gh CLI
-        let repo_str = format!("{}/{}", owner, repo);
-        let args = vec![
-            "pr",
-            "review",
-            pr_number,
-            "--repo",
-            &repo_str,
-            "--approve",
-            "--body",
-            "Automated approval by Claude PR reviewer",
-        ];
-
-        self.command_executor
-            .execute("gh", &args, None, None)
-            .await?;
-        Ok(())
+        let pr = PullRequestRef::parse_url(pr_url)?;
+        self.hosting
+            .submit_review(
+                &pr,
+                ReviewState::Approved,
+                "Automated approval by Claude PR reviewer",
+            )
+            .await
     }
 
     async fn merge_pr(&self, pr_url: &str) -> Result<()> {
-        // Parse PR URL
-        let parts: Vec<&str> = pr_url.split('/').collect();
-        let owner = parts[3];
-        let repo = parts[4];
-        let pr_number = parts[6];
-
-        // Merge using gh CLI
-        let repo_str = format!("{}/{}", owner, repo);
-        let args = vec![
-            "pr", "merge", pr_number, "--repo", &repo_str, "--squash", "--auto",
-        ];
-
-        self.command_executor
-            .execute("gh", &args, None, None)
-            .await?;
-        Ok(())
+        let pr = PullRequestRef::parse_url(pr_url)?;
+        self.hosting.merge(&pr, MergeMethod::Squash).await
     }
 }
 
@@ -272,42 +224,54 @@ mod tests {
     use super::*;
     use crate::repository::database::init_test_database;
     use crate::services::command_executor::mock::MockCommandExecutor;
+    use crate::services::hosting::{FakeHostingServer, NewPullRequest, PullRequest, RepoRef};
 
-    async fn setup() -> (PRReviewService, Arc<MockCommandExecutor>) {
+    async fn setup() -> (PRReviewService, Arc<MockCommandExecutor>, FakeHostingServer) {
         let pool = init_test_database().await.unwrap();
         let repository = Arc::new(Repository::new(pool));
         let mock_executor = Arc::new(MockCommandExecutor::new());
+        let server = FakeHostingServer::start().await.unwrap();
 
         let service = PRReviewService::new(
             repository,
             mock_executor.clone() as Arc<dyn CommandExecutor>,
+            Arc::new(server.github_client()),
         );
 
-        (service, mock_executor)
+        (service, mock_executor, server)
+    }
+
+    fn open_pr(
+        server: &FakeHostingServer,
+        title: &str,
+        body: &str,
+        branch: &str,
+        author: &str,
+    ) -> PullRequest {
+        server.open_pull_request(
+            &RepoRef::new("owner", "repo"),
+            &NewPullRequest {
+                title: title.to_string(),
+                body: body.to_string(),
+                head: branch.to_string(),
+                base: "main".to_string(),
+            },
+            author,
+        )
     }
 
     #[tokio::test]
     async fn test_get_pr_info() {
-        let (service, mock_executor) = setup().await;
+        let (service, _mock_executor, server) = setup().await;
 
-        // Set up mock response
-        let pr_json = r#"{
-            "title": "Add new feature",
-            "body": "This PR adds a new feature",
-            "headRefName": "feature-branch",
-            "baseRefName": "main",
-            "author": {"login": "user123"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "123"], pr_json, "", true);
+        let pr = open_pr(&server, "Add new feature", "This PR adds a new feature", "feature-branch", "user123");
 
         let pr_info = service
-            .get_pr_info("https://github.com/owner/repo/pull/123")
+            .get_pr_info(&pr.url)
             .await
             .unwrap();
 
-        assert_eq!(pr_info.pr_number, 123);
+        assert_eq!(pr_info.pr_number, pr.number as i32);
         assert_eq!(pr_info.title, "Add new feature");
         assert_eq!(pr_info.branch, "feature-branch");
         assert_eq!(pr_info.base_branch, "main");
@@ -317,19 +281,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_review_pr_all_passing() {
-        let (service, mock_executor) = setup().await;
-
-        // Set up mock responses
-        let pr_json = r#"{
-            "title": "Fix bug",
-            "body": "Fixes issue #42",
-            "headRefName": "bugfix",
-            "baseRefName": "main",
-            "author": {"login": "developer"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "456"], pr_json, "", true);
+        let (service, mock_executor, server) = setup().await;
+
+        let pr = open_pr(&server, "Fix bug", "Fixes issue #42", "bugfix", "developer");
         mock_executor.add_response(
             "git",
             vec!["checkout", "bugfix"],
@@ -361,7 +315,7 @@ mod tests {
         mock_executor.add_response("git", vec!["merge", "--abort"], "", "", true);
 
         let review = service
-            .review_pr("https://github.com/owner/repo/pull/456".to_string())
+            .review_pr(pr.url.clone())
             .await
             .unwrap();
 
@@ -379,19 +333,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_review_pr_tests_fail() {
-        let (service, mock_executor) = setup().await;
-
-        // Set up mock responses
-        let pr_json = r#"{
-            "title": "New feature",
-            "body": "Adds feature X",
-            "headRefName": "feature-x",
-            "baseRefName": "main",
-            "author": {"login": "dev"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "789"], pr_json, "", true);
+        let (service, mock_executor, server) = setup().await;
+
+        let pr = open_pr(&server, "New feature", "Adds feature X", "feature-x", "dev");
         mock_executor.add_response(
             "git",
             vec!["checkout", "feature-x"],
@@ -423,7 +367,7 @@ mod tests {
         mock_executor.add_response("git", vec!["merge", "--abort"], "", "", true);
 
         let review = service
-            .review_pr("https://github.com/owner/repo/pull/789".to_string())
+            .review_pr(pr.url.clone())
             .await
             .unwrap();
 
@@ -435,19 +379,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_review_pr_merge_conflicts() {
-        let (service, mock_executor) = setup().await;
-
-        // Set up mock responses
-        let pr_json = r#"{
-            "title": "Conflicting change",
-            "body": "This might conflict",
-            "headRefName": "conflict-branch",
-            "baseRefName": "main",
-            "author": {"login": "dev"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "999"], pr_json, "", true);
+        let (service, mock_executor, server) = setup().await;
+
+        let pr = open_pr(&server, "Conflicting change", "This might conflict", "conflict-branch", "dev");
         mock_executor.add_response(
             "git",
             vec!["checkout", "conflict-branch"],
@@ -473,7 +407,7 @@ mod tests {
         mock_executor.add_response("git", vec!["merge", "--abort"], "", "", true);
 
         let review = service
-            .review_pr("https://github.com/owner/repo/pull/999".to_string())
+            .review_pr(pr.url.clone())
             .await
             .unwrap();
 
@@ -490,19 +424,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_approve_and_merge_success() {
-        let (service, mock_executor) = setup().await;
-
-        // Set up mock responses for successful review
-        let pr_json = r#"{
-            "title": "Ready to merge",
-            "body": "All good",
-            "headRefName": "ready",
-            "baseRefName": "main",
-            "author": {"login": "dev"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "111"], pr_json, "", true);
+        let (service, mock_executor, server) = setup().await;
+
+        let pr = open_pr(&server, "Ready to merge", "All good", "ready", "dev");
         mock_executor.add_response("git", vec!["checkout", "ready"], "Switched", "", true);
         mock_executor.add_response("cargo", vec!["test"], "test result: ok", "", true);
         mock_executor.add_response("git", vec!["checkout", "-"], "Switched", "", true);
@@ -514,42 +438,25 @@ mod tests {
             true,
         );
         mock_executor.add_response("git", vec!["merge", "--abort"], "", "", true);
-        mock_executor.add_response(
-            "gh",
-            vec!["pr", "review", "111", "--approve"],
-            "Approved",
-            "",
-            true,
-        );
-        mock_executor.add_response(
-            "gh",
-            vec!["pr", "merge", "111", "--squash"],
-            "Merged",
-            "",
-            true,
-        );
 
-        service
-            .approve_and_merge("https://github.com/owner/repo/pull/111".to_string())
-            .await
-            .unwrap();
+        service.approve_and_merge(pr.url.clone()).await.unwrap();
+
+        let pr_ref = PullRequestRef::parse_url(&pr.url).unwrap();
+        let merged = server.pull_request(&pr_ref).unwrap();
+        assert_eq!(merged.state, PullRequestState::Merged);
+        assert!(
+            server
+                .requests()
+                .iter()
+                .any(|r| r.path.ends_with("/reviews") && r.body["event"] == "APPROVE")
+        );
     }
 
     #[tokio::test]
     async fn test_approve_and_merge_fails_when_tests_fail() {
-        let (service, mock_executor) = setup().await;
-
-        // Set up mock responses with failing tests
-        let pr_json = r#"{
-            "title": "Broken PR",
-            "body": "Has issues",
-            "headRefName": "broken",
-            "baseRefName": "main",
-            "author": {"login": "dev"},
-            "state": "OPEN"
-        }"#;
-
-        mock_executor.add_response("gh", vec!["pr", "view", "222"], pr_json, "", true);
+        let (service, mock_executor, server) = setup().await;
+
+        let pr = open_pr(&server, "Broken PR", "Has issues", "broken", "dev");
         mock_executor.add_response("git", vec!["checkout", "broken"], "Switched", "", true);
         mock_executor.add_response("cargo", vec!["test"], "", "test FAILED", false);
         mock_executor.add_response("git", vec!["checkout", "-"], "Switched", "", true);
@@ -562,9 +469,7 @@ mod tests {
         );
         mock_executor.add_response("git", vec!["merge", "--abort"], "", "", true);
 
-        let result = service
-            .approve_and_merge("https://github.com/owner/repo/pull/222".to_string())
-            .await;
+        let result = service.approve_and_merge(pr.url.clone()).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be merged"));
     }
@@ -0,0 +1,283 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::domain::comment::Comment;
+use crate::domain::goal::Goal;
+use crate::domain::prompt_template::{TemplateContext, TemplateValue, slugify};
+use crate::domain::task::Task;
+use crate::repository::Repository;
+
+/// How many of the most recent comments are exposed to prompt templates.
+const RECENT_COMMENT_LIMIT: usize = 5;
+
+/// A task this task depends on, as exposed to prompt templates.
+#[derive(Debug, Clone)]
+pub struct DependencyContext {
+    pub task: Task,
+    pub pr_url: Option<String>,
+}
+
+/// Builds the standard template context used when rendering Claude prompts.
+///
+/// Templates can reference `task`, `goal`, `dependencies`, `comments`,
+/// `subtasks` and `metadata`, plus the flat legacy variables
+/// (`task_title`, `goal_title`, ...) used by the original default template.
+#[derive(Clone)]
+pub struct PromptContextBuilder {
+    repository: Arc<Repository>,
+}
+
+impl PromptContextBuilder {
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn build(&self, task: &Task) -> Result<TemplateContext> {
+        let goal = match task.goal_id {
+            Some(goal_id) => self.repository.goals.get(goal_id).await?,
+            None => None,
+        };
+
+        // Dependencies where this task is the "from" side are the tasks it waits on
+        let mut dependencies = Vec::new();
+        for dep in self
+            .repository
+            .dependencies
+            .get_dependents_for_task(task.id)
+            .await?
+        {
+            if let Some(dep_task) = self.repository.tasks.get(dep.to_task_id).await? {
+                let pr_url = self
+                    .repository
+                    .claude_code
+                    .get_sessions_by_task(dep_task.id)
+                    .await?
+                    .into_iter()
+                    .find_map(|s| s.pr_url);
+                dependencies.push(DependencyContext {
+                    task: dep_task,
+                    pr_url,
+                });
+            }
+        }
+
+        let comments = self.repository.comments.list_for_entity(task.id).await?;
+
+        Ok(Self::from_parts(task, goal.as_ref(), &dependencies, &comments))
+    }
+
+    pub fn from_parts(
+        task: &Task,
+        goal: Option<&Goal>,
+        dependencies: &[DependencyContext],
+        comments: &[Comment],
+    ) -> TemplateContext {
+        let mut context = TemplateContext::new();
+
+        let subtasks: Vec<TemplateValue> = task
+            .subtasks
+            .iter()
+            .map(|s| {
+                object([
+                    ("title", s.title.clone().into()),
+                    ("description", s.description.clone().into()),
+                    ("completed", s.completed.into()),
+                ])
+            })
+            .collect();
+
+        let mut tags: Vec<String> = task.tags.iter().cloned().collect();
+        tags.sort();
+
+        let (done, total) = task.subtask_progress();
+        let task_value = object([
+            ("id", task.id.to_string().into()),
+            ("id_short", short_id(task).into()),
+            ("title", task.title.clone().into()),
+            ("title_slug", slugify(&task.title).into()),
+            ("description", task.description.clone().into()),
+            ("status", format!("{:?}", task.status).into()),
+            ("priority", format!("{:?}", task.priority).into()),
+            ("tags", tags.clone().into()),
+            ("metadata", (&task.metadata).into()),
+            ("estimated_hours", task.estimated_hours.into()),
+            ("actual_hours", task.actual_hours.into()),
+            ("due_date", task.due_date.into()),
+            ("scheduled_date", task.scheduled_date.into()),
+            ("assignee", task.assignee.clone().into()),
+            ("subtasks", TemplateValue::List(subtasks.clone())),
+            ("subtasks_completed", done.into()),
+            ("subtasks_total", total.into()),
+        ]);
+
+        let goal_value = goal
+            .map(|g| {
+                object([
+                    ("id", g.id.to_string().into()),
+                    ("title", g.title.clone().into()),
+                    ("description", g.description.clone().into()),
+                    ("status", format!("{:?}", g.status).into()),
+                    ("target_date", g.target_date.into()),
+                    ("progress", g.progress.into()),
+                ])
+            })
+            .unwrap_or(TemplateValue::Null);
+
+        let dependency_values: Vec<TemplateValue> = dependencies
+            .iter()
+            .map(|d| {
+                object([
+                    ("id", d.task.id.to_string().into()),
+                    ("title", d.task.title.clone().into()),
+                    ("status", format!("{:?}", d.task.status).into()),
+                    ("pr_url", d.pr_url.clone().into()),
+                ])
+            })
+            .collect();
+
+        let mut recent: Vec<&Comment> = comments.iter().collect();
+        recent.sort_by_key(|c| c.created_at);
+        let skip = recent.len().saturating_sub(RECENT_COMMENT_LIMIT);
+        let comment_values: Vec<TemplateValue> = recent
+            .into_iter()
+            .skip(skip)
+            .map(|c| {
+                object([
+                    ("author", c.author_name.clone().into()),
+                    ("content", c.content.clone().into()),
+                    ("created_at", c.created_at.into()),
+                ])
+            })
+            .collect();
+
+        context.insert("task", task_value);
+        context.insert("goal", goal_value);
+        context.insert("dependencies", TemplateValue::List(dependency_values));
+        context.insert("comments", TemplateValue::List(comment_values));
+        context.insert("subtasks", TemplateValue::List(subtasks));
+        context.insert("metadata", &task.metadata);
+
+        // Flat variables understood by templates written before the structured context
+        context.insert("task_title", task.title.clone());
+        context.insert("task_description", task.description.clone());
+        context.insert("task_id", task.id.to_string());
+        context.insert("task_id_short", short_id(task));
+        context.insert("task_title_slug", slugify(&task.title));
+        context.insert("priority", format!("{:?}", task.priority));
+        context.insert("status", format!("{:?}", task.status));
+        context.insert("tags", tags.join(", "));
+        context.insert(
+            "estimated_hours",
+            task.estimated_hours
+                .map(|h| h.to_string())
+                .unwrap_or_else(|| "Not estimated".to_string()),
+        );
+        context.insert(
+            "goal_title",
+            goal.map(|g| g.title.clone())
+                .unwrap_or_else(|| "N/A".to_string()),
+        );
+
+        context
+    }
+}
+
+fn short_id(task: &Task) -> String {
+    task.id.to_string().chars().take(8).collect()
+}
+
+fn object<const N: usize>(fields: [(&str, TemplateValue); N]) -> TemplateValue {
+    TemplateValue::Object(
+        fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::claude_code::{ClaudeCodeSession, ClaudePromptTemplate};
+    use crate::domain::comment::EntityType;
+    use crate::domain::dependency::{Dependency, DependencyType};
+    use crate::repository::database::init_test_database;
+
+    #[tokio::test]
+    async fn test_build_context_from_repository() {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+
+        let goal = Goal::new("Launch v2".to_string(), "Ship it".to_string());
+        repository.goals.create(&goal).await.unwrap();
+
+        let mut task = Task::new("Implement API".to_string(), "Build endpoints".to_string());
+        task.goal_id = Some(goal.id);
+        task.add_subtask("Write handlers".to_string());
+        task.add_metadata("team".to_string(), "backend".to_string());
+        repository.tasks.create(&task).await.unwrap();
+
+        let dep_task = Task::new("Design API".to_string(), "".to_string());
+        repository.tasks.create(&dep_task).await.unwrap();
+        let mut session = ClaudeCodeSession::new(dep_task.id);
+        session.set_pr_info("https://git.example.com/acme/api/pull/7".to_string(), 7);
+        repository.claude_code.create_session(&session).await.unwrap();
+
+        repository
+            .dependencies
+            .create(&Dependency::new(task.id, dep_task.id, DependencyType::FinishToStart))
+            .await
+            .unwrap();
+
+        for i in 0..7 {
+            let comment = Comment::new(
+                task.id,
+                EntityType::Task,
+                "alice".to_string(),
+                format!("comment {}", i),
+            );
+            repository.comments.create(&comment).await.unwrap();
+        }
+
+        let context = PromptContextBuilder::new(repository.clone())
+            .build(&task)
+            .await
+            .unwrap();
+
+        let template = ClaudePromptTemplate::new(
+            "preview".to_string(),
+            "{{task.title}} ({{goal.title}}) team={{metadata.team}}\n\
+             {{#each dependencies}}after {{title}} {{pr_url}}\n{{/each}}\
+             {{#each subtasks}}- {{title}}\n{{/each}}\
+             {{comments | length}} comments, last: {{#each comments}}{{#if @last}}{{content}}{{/if}}{{/each}}"
+                .to_string(),
+        );
+        let rendered = template.render_with(&context, true).unwrap();
+
+        assert_eq!(
+            rendered,
+            "Implement API (Launch v2) team=backend\n\
+             after Design API https://git.example.com/acme/api/pull/7\n\
+             - Write handlers\n\
+             5 comments, last: comment 6"
+        );
+    }
+
+    #[test]
+    fn test_legacy_variables_present() {
+        let task = Task::new("Fix Bug!".to_string(), "desc".to_string());
+        let context = PromptContextBuilder::from_parts(&task, None, &[], &[]);
+
+        assert_eq!(
+            context.get("task_title_slug"),
+            Some(&TemplateValue::from("fix-bug"))
+        );
+        assert_eq!(context.get("goal_title"), Some(&TemplateValue::from("N/A")));
+        assert_eq!(
+            context.get("estimated_hours"),
+            Some(&TemplateValue::from("Not estimated"))
+        );
+        assert_eq!(context.get("goal"), Some(&TemplateValue::Null));
+    }
+}
@@ -1,9 +1,12 @@
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveTime, Weekday};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::domain::recurring::{RecurrencePattern, RecurrenceRule, RecurringTaskTemplate};
+use crate::domain::recurring::{
+    GeneratedChildren, RecurrencePattern, RecurrenceRule, RecurringTaskTemplate,
+};
+use crate::domain::rrule::RRule;
 use crate::domain::task::Task;
 use crate::repository::Repository;
 
@@ -49,6 +52,43 @@ impl RecurringService {
         Ok(template)
     }
 
+    /// Create a template scheduled by an RFC 5545 rule, e.g.
+    /// `FREQ=MONTHLY;BYDAY=-1FR` or `DTSTART`, `RRULE` and `EXDATE` lines.
+    pub async fn create_rrule_template(
+        &self,
+        title: String,
+        description: String,
+        rrule: &str,
+    ) -> Result<RecurringTaskTemplate> {
+        let template = RecurringTaskTemplate::from_rrule(title, description, RRule::parse(rrule)?);
+
+        self.repository.recurring.create(&template).await?;
+
+        Ok(template)
+    }
+
+    /// The next `count` dates the template will generate tasks for.
+    pub async fn preview_occurrences(
+        &self,
+        template_id: Uuid,
+        count: usize,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        let template = self
+            .repository
+            .recurring
+            .get(template_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Recurring template {} not found", template_id))?;
+        Ok(template.preview_occurrences(count))
+    }
+
+    /// The next `count` occurrences of a rule that isn't saved yet, starting today.
+    pub fn preview_rrule(&self, rrule: &str, count: usize) -> Result<Vec<DateTime<Utc>>> {
+        let template =
+            RecurringTaskTemplate::from_rrule(String::new(), String::new(), RRule::parse(rrule)?);
+        Ok(template.preview_occurrences(count))
+    }
+
     pub async fn update_template(&self, template: &RecurringTaskTemplate) -> Result<()> {
         self.repository.recurring.update(template).await
     }
@@ -81,43 +121,124 @@ impl RecurringService {
         Ok(())
     }
 
+    /// Tasks for every template occurrence that is due, caught up as each
+    /// template's policy says, followed by their child tasks. Occurrences
+    /// generated before are left alone.
     pub async fn generate_due_tasks(&self) -> Result<Vec<Task>> {
         let active_templates = self.repository.recurring.list_active().await?;
         let mut generated_tasks = Vec::new();
+        let now = Utc::now();
 
         for mut template in active_templates {
-            if template.should_generate_now() {
-                if let Some(task) = template.generate_task() {
-                    // Save the generated task to the database
-                    self.repository.tasks.create(&task).await?;
+            if !template.should_generate_at(now) {
+                continue;
+            }
+            for at in template.take_due_occurrences(now) {
+                // Count the occurrence only once its task is saved
+                let mut pending = template.clone();
+                let task = pending.task_for(at);
+                if let Some(children) = self.save_occurrence(&template, at, &task).await? {
+                    template = pending;
                     generated_tasks.push(task);
-
-                    // Update the template with new occurrence count and next occurrence
-                    self.repository.recurring.update(&template).await?;
-                } else {
-                    // Template returned None, which means it was deactivated
-                    // We still need to update it in the database
-                    self.repository.recurring.update(&template).await?;
+                    generated_tasks.extend(children);
                 }
             }
+
+            // Save the new occurrence count and next occurrence, or deactivation
+            self.repository.recurring.update(&template).await?;
         }
 
         Ok(generated_tasks)
     }
 
     pub async fn generate_tasks_for_template(&self, template_id: Uuid) -> Result<Option<Task>> {
-        if let Some(mut template) = self.repository.recurring.get(template_id).await?
-            && let Some(task) = template.generate_task()
+        let Some(mut template) = self.repository.recurring.get(template_id).await? else {
+            return Ok(None);
+        };
+        // Count the occurrence only once its task is saved
+        let mut pending = template.clone();
+        let Some(task) = pending.generate_task() else {
+            return Ok(None);
+        };
+        let at = task.scheduled_date.unwrap_or(template.updated_at);
+        let saved = self.save_occurrence(&template, at, &task).await?;
+
+        if saved.is_some() {
+            template = pending;
+        } else {
+            // Generated before; only move on to the next occurrence
+            template.next_occurrence = pending.next_occurrence;
+            template.active = pending.active;
+        }
+        self.repository.recurring.update(&template).await?;
+
+        Ok(saved.map(|_| task))
+    }
+
+    /// Save `task` as the template's occurrence at `at`, with the blueprint's
+    /// child tasks, unless a task was already generated for it. Returns the
+    /// children saved. If anything fails, the tasks saved so far are deleted
+    /// and the claim is given back, so the occurrence is generated in full
+    /// next time.
+    async fn save_occurrence(
+        &self,
+        template: &RecurringTaskTemplate,
+        at: DateTime<Utc>,
+        task: &Task,
+    ) -> Result<Option<Vec<Task>>> {
+        let key = template.occurrence_key(at);
+        let recurring = &self.repository.recurring;
+        if !recurring
+            .claim_occurrence(&key, template.id, at, task.id)
+            .await?
         {
-            // Save the generated task to the database
-            self.repository.tasks.create(&task).await?;
+            tracing::debug!("Occurrence {} was already generated", key);
+            return Ok(None);
+        }
 
-            // Update the template
-            self.repository.recurring.update(&template).await?;
+        let children = template.child_tasks(task, at);
+        match self.create_occurrence(template, task, &children).await {
+            Ok(()) => Ok(Some(children.tasks)),
+            Err(e) => {
+                for id in children.tasks.iter().chain([task]).map(|t| t.id) {
+                    self.repository.tasks.delete(id).await?;
+                }
+                recurring.release_occurrence(&key).await?;
+                Err(e)
+            }
+        }
+    }
 
-            return Ok(Some(task));
+    async fn create_occurrence(
+        &self,
+        template: &RecurringTaskTemplate,
+        task: &Task,
+        children: &GeneratedChildren,
+    ) -> Result<()> {
+        self.repository.tasks.create(task).await?;
+        for child in &children.tasks {
+            self.repository.tasks.create(child).await?;
+        }
+        for dependency in &children.dependencies {
+            self.repository.dependencies.create(dependency).await?;
         }
-        Ok(None)
+        if let Some(goal_id) = task.goal_id {
+            match self.repository.goals.get(goal_id).await? {
+                Some(mut goal) => {
+                    goal.add_task(task.id);
+                    for child in &children.tasks {
+                        goal.add_task(child.id);
+                    }
+                    self.repository.goals.update(&goal).await?;
+                }
+                None => tracing::warn!(
+                    "Recurring template {} assigns missing goal {}",
+                    template.id,
+                    goal_id
+                ),
+            }
+        }
+        Ok(())
     }
 
     pub async fn get_upcoming_occurrences(
@@ -145,8 +266,11 @@ impl RecurringService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::recurring::CatchUpPolicy;
     use crate::repository::database::init_test_database;
-    use chrono::{Duration, Utc};
+    use crate::repository::task_repository::TaskFilters;
+    use crate::services::dependency_service::DependencyService;
+    use chrono::{Datelike, Duration, Utc};
 
     async fn setup() -> RecurringService {
         let pool = init_test_database().await.unwrap();
@@ -357,4 +481,310 @@ mod tests {
         let active = service.list_active_templates().await.unwrap();
         assert_eq!(active.len(), 2); // Only 2 should be active
     }
+
+    #[tokio::test]
+    async fn test_rrule_template_round_trip_and_preview() {
+        let service = setup().await;
+
+        let template = service
+            .create_rrule_template(
+                "Release notes".to_string(),
+                "Publish on the last Friday of the month".to_string(),
+                "DTSTART:20250103T160000Z\nRRULE:FREQ=MONTHLY;BYDAY=-1FR\nEXDATE;VALUE=DATE:20991225",
+            )
+            .await
+            .unwrap();
+        assert_eq!(template.recurrence_rule.pattern, RecurrencePattern::Monthly);
+        assert_eq!(
+            template.recurrence_rule.time_of_day,
+            NaiveTime::from_hms_opt(16, 0, 0).unwrap()
+        );
+
+        let retrieved = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.rrule, template.rrule);
+
+        let dates = service.preview_occurrences(template.id, 3).await.unwrap();
+        assert_eq!(dates.len(), 3);
+        assert!(dates[0] > Utc::now());
+        for date in &dates {
+            assert_eq!(date.weekday(), Weekday::Fri);
+            assert!((date.date_naive() + Duration::days(7)).month() != date.month());
+        }
+
+        // Generating moves on to the following occurrence
+        let task = service
+            .generate_tasks_for_template(template.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.scheduled_date, Some(dates[0]));
+        let retrieved = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.next_occurrence, Some(dates[1]));
+
+        assert!(service.preview_rrule("FREQ=FORTNIGHTLY", 3).is_err());
+        assert!(service.preview_rrule("FREQ=DAILY;BYWEEKNO=3", 3).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_generates_each_missed_occurrence_once() {
+        let service = setup().await;
+
+        // Closed for three days, with the occurrence three days ago pending
+        let midnight = Utc::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        let rule = RecurrenceRule {
+            pattern: RecurrencePattern::Daily,
+            interval: 1,
+            days_of_week: vec![],
+            day_of_month: None,
+            month_of_year: None,
+            time_of_day: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            end_date: None,
+            max_occurrences: None,
+            occurrences_count: 0,
+        };
+        let mut template =
+            RecurringTaskTemplate::new("Rotate logs".to_string(), String::new(), rule);
+        template.created_at = midnight - Duration::days(3);
+        template.next_occurrence = Some(midnight - Duration::days(3));
+        service
+            .repository
+            .recurring
+            .create(&template)
+            .await
+            .unwrap();
+
+        let tasks = service.generate_due_tasks().await.unwrap();
+        let mut dates: Vec<_> = tasks.iter().filter_map(|t| t.scheduled_date).collect();
+        dates.sort();
+        assert_eq!(
+            dates,
+            (0..4)
+                .rev()
+                .map(|d| midnight - Duration::days(d))
+                .collect::<Vec<_>>()
+        );
+
+        let retrieved = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.recurrence_rule.occurrences_count, 4);
+        assert_eq!(
+            retrieved.next_occurrence,
+            Some(midnight + Duration::days(1))
+        );
+        assert!(service.generate_due_tasks().await.unwrap().is_empty());
+
+        // A template saved before its run finished doesn't generate them again
+        service.update_template(&template).await.unwrap();
+        assert!(service.generate_due_tasks().await.unwrap().is_empty());
+        let all = service
+            .repository
+            .tasks
+            .list(TaskFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        let retrieved = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(
+            retrieved.next_occurrence,
+            Some(midnight + Duration::days(1))
+        );
+
+        // Only the latest missed occurrence for templates that catch up on it
+        let mut latest = RecurringTaskTemplate::from_rrule(
+            "Digest".to_string(),
+            String::new(),
+            RRule::parse("DTSTART:20200101T000000Z\nRRULE:FREQ=DAILY").unwrap(),
+        );
+        latest.catch_up = CatchUpPolicy::Latest;
+        latest.next_occurrence = Some(midnight - Duration::days(5));
+        service.repository.recurring.create(&latest).await.unwrap();
+        let retrieved = service.get_template(latest.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.catch_up, CatchUpPolicy::Latest);
+
+        let tasks = service.generate_due_tasks().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].scheduled_date, Some(midnight));
+    }
+
+    #[tokio::test]
+    async fn test_blueprint_occurrence_saves_children_and_goal_links() {
+        use crate::domain::goal::Goal;
+        use crate::domain::recurring::{ChildTaskBlueprint, TaskBlueprint};
+
+        let service = setup().await;
+        let goal = Goal::new("Ship 2.0".to_string(), String::new());
+        service.repository.goals.create(&goal).await.unwrap();
+
+        let mut template = service
+            .create_rrule_template("Release".to_string(), String::new(), "FREQ=WEEKLY")
+            .await
+            .unwrap();
+        template.blueprint = TaskBlueprint {
+            goal_id: Some(goal.id),
+            children: vec![
+                ChildTaskBlueprint::new("Build".to_string()),
+                ChildTaskBlueprint {
+                    depends_on: vec![0],
+                    ..ChildTaskBlueprint::new("Deploy".to_string())
+                },
+            ],
+            ..Default::default()
+        };
+        service.update_template(&template).await.unwrap();
+        assert_eq!(
+            service
+                .get_template(template.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .blueprint,
+            template.blueprint
+        );
+
+        let task = service
+            .generate_tasks_for_template(template.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let tasks = service
+            .repository
+            .tasks
+            .list(TaskFilters::default())
+            .await
+            .unwrap();
+        let children: Vec<_> = tasks
+            .iter()
+            .filter(|t| t.parent_task_id == Some(task.id))
+            .collect();
+        assert_eq!(children.len(), 2);
+
+        let build = children.iter().find(|t| t.title == "Build").unwrap();
+        let deploy = children.iter().find(|t| t.title == "Deploy").unwrap();
+        let prerequisites = DependencyService::new(service.repository.clone())
+            .get_dependencies(deploy.id)
+            .await
+            .unwrap();
+        assert_eq!(prerequisites, vec![build.id]);
+
+        let goal = service
+            .repository
+            .goals
+            .get(goal.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(goal.task_ids.len(), 3);
+        assert!(goal.task_ids.contains(&task.id));
+    }
+
+    #[tokio::test]
+    async fn test_failed_occurrence_is_rolled_back_and_released() {
+        use crate::domain::recurring::{ChildTaskBlueprint, TaskBlueprint};
+
+        let service = setup().await;
+        let mut template = service
+            .create_rrule_template("Release".to_string(), String::new(), "FREQ=WEEKLY")
+            .await
+            .unwrap();
+        template.blueprint = TaskBlueprint {
+            children: vec![
+                ChildTaskBlueprint::new("Build".to_string()),
+                ChildTaskBlueprint {
+                    depends_on: vec![0],
+                    ..ChildTaskBlueprint::new("Deploy".to_string())
+                },
+            ],
+            ..Default::default()
+        };
+        service.update_template(&template).await.unwrap();
+
+        let pool = service.repository.pool.clone();
+        sqlx::raw_sql(
+            "CREATE TRIGGER no_dependencies BEFORE INSERT ON dependencies
+             BEGIN SELECT RAISE(ABORT, 'no dependencies'); END",
+        )
+        .execute(&*pool)
+        .await
+        .unwrap();
+        assert!(
+            service
+                .generate_tasks_for_template(template.id)
+                .await
+                .is_err()
+        );
+        let tasks = service
+            .repository
+            .tasks
+            .list(TaskFilters::default())
+            .await
+            .unwrap();
+        assert!(tasks.is_empty());
+
+        sqlx::raw_sql("DROP TRIGGER no_dependencies")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        assert!(
+            service
+                .generate_tasks_for_template(template.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let tasks = service
+            .repository
+            .tasks
+            .list(TaskFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 3);
+        let stored = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(stored.recurrence_rule.occurrences_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_occurrence_generated_before_is_not_counted_again() {
+        let service = setup().await;
+        let template = service
+            .create_rrule_template("Standup".to_string(), String::new(), "FREQ=DAILY")
+            .await
+            .unwrap();
+        let dates = service.preview_occurrences(template.id, 2).await.unwrap();
+        let claimed = service
+            .repository
+            .recurring
+            .claim_occurrence(
+                &template.occurrence_key(dates[0]),
+                template.id,
+                dates[0],
+                Uuid::new_v4(),
+            )
+            .await
+            .unwrap();
+        assert!(claimed);
+
+        assert!(
+            service
+                .generate_tasks_for_template(template.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let stored = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(stored.recurrence_rule.occurrences_count, 0);
+        assert_eq!(stored.next_occurrence, Some(dates[1]));
+
+        assert!(
+            service
+                .generate_tasks_for_template(template.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let stored = service.get_template(template.id).await.unwrap().unwrap();
+        assert_eq!(stored.recurrence_rule.occurrences_count, 1);
+    }
 }
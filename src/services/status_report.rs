@@ -0,0 +1,597 @@
+use crate::domain::app_settings::AppSettings;
+use crate::domain::claude_code::{ClaudeCodeSession, SessionStatus};
+use crate::domain::goal::{Goal, GoalStatus};
+use crate::domain::prompt_template::slugify;
+use crate::domain::task::{Task, TaskStatus};
+use crate::domain::time_entry;
+use crate::repository::Repository;
+use crate::repository::task_repository::TaskFilters;
+use crate::services::summarization::SummarizationService;
+use crate::services::time_tracking_service::TimeTrackingService;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+const REPORT_SYSTEM: &str = "You write brief, factual project status updates.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    pub fn duration(&self) -> Duration {
+        match self {
+            ReportPeriod::Daily => Duration::days(1),
+            ReportPeriod::Weekly => Duration::weeks(1),
+        }
+    }
+
+    /// The period that ends at `end`.
+    pub fn range_ending(&self, end: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        (end - self.duration(), end)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "Daily",
+            ReportPeriod::Weekly => "Weekly",
+        }
+    }
+}
+
+/// What a report covers: every task, or the tasks of one goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportScope {
+    Workspace,
+    Goal(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionActivity {
+    pub task_title: String,
+    pub status: SessionStatus,
+    pub pr_number: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergedPr {
+    pub task_title: String,
+    pub pr_number: Option<i32>,
+    pub pr_url: String,
+}
+
+/// Everything that changed in a scope during a period.
+#[derive(Debug, Clone)]
+pub struct StatusReportData {
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub completed: Vec<Task>,
+    pub created: Vec<Task>,
+    /// Tasks blocked at the time of the report
+    pub blocked: Vec<Task>,
+    /// Tasks that came due in the period and are still open
+    pub slipped: Vec<Task>,
+    pub sessions: Vec<SessionActivity>,
+    pub merged_prs: Vec<MergedPr>,
+    /// Tracked hours per task title, largest first
+    pub hours: Vec<(String, f64)>,
+}
+
+impl StatusReportData {
+    pub fn total_hours(&self) -> f64 {
+        self.hours.iter().fold(0.0, |total, (_, hours)| total + hours)
+    }
+
+    /// One sentence with the period's counts; used when no model is configured.
+    pub fn overview(&self) -> String {
+        format!(
+            "{} completed, {} created, {} blocked and {} slipped; {}, {} merged and {:.1} hours logged.",
+            plural(self.completed.len(), "task"),
+            self.created.len(),
+            self.blocked.len(),
+            self.slipped.len(),
+            plural(self.sessions.len(), "agent session"),
+            plural(self.merged_prs.len(), "PR"),
+            self.total_hours()
+        )
+    }
+
+    /// The report's sections in Markdown, without the heading and summary.
+    pub fn sections_markdown(&self) -> String {
+        let mut out = String::new();
+        task_section(&mut out, "Completed", &self.completed, |_| String::new());
+        task_section(&mut out, "Created", &self.created, |_| String::new());
+        task_section(&mut out, "Blocked", &self.blocked, |_| String::new());
+        task_section(&mut out, "Slipped", &self.slipped, |task| {
+            task.due_date
+                .map(|due| format!(" (due {})", due.format("%Y-%m-%d")))
+                .unwrap_or_default()
+        });
+
+        let _ = writeln!(out, "## Agent sessions ({})\n", self.sessions.len());
+        if self.sessions.is_empty() {
+            out.push_str("- None\n");
+        }
+        for session in &self.sessions {
+            let pr = session
+                .pr_number
+                .map(|n| format!(" · PR #{}", n))
+                .unwrap_or_default();
+            let _ = writeln!(out, "- {} — {}{}", session.task_title, session.status.as_str(), pr);
+        }
+        out.push('\n');
+
+        let _ = writeln!(out, "## PRs merged ({})\n", self.merged_prs.len());
+        if self.merged_prs.is_empty() {
+            out.push_str("- None\n");
+        }
+        for pr in &self.merged_prs {
+            let number = pr.pr_number.map(|n| format!("#{} ", n)).unwrap_or_default();
+            let _ = writeln!(out, "- [{}{}]({})", number, pr.task_title, pr.pr_url);
+        }
+        out.push('\n');
+
+        let _ = writeln!(out, "## Hours logged ({:.1}h)\n", self.total_hours());
+        if self.hours.is_empty() {
+            out.push_str("- None\n");
+        }
+        for (title, hours) in &self.hours {
+            let _ = writeln!(out, "- {}: {:.1}h", title, hours);
+        }
+        out
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn task_section(out: &mut String, heading: &str, tasks: &[Task], suffix: impl Fn(&Task) -> String) {
+    let _ = writeln!(out, "## {} ({})\n", heading, tasks.len());
+    if tasks.is_empty() {
+        out.push_str("- None\n");
+    }
+    for task in tasks {
+        let _ = writeln!(out, "- {}{}", task.title, suffix(task));
+    }
+    out.push('\n');
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusReport {
+    pub scope: ReportScope,
+    pub period: ReportPeriod,
+    pub data: StatusReportData,
+    /// Written by the model, or the counts sentence without one
+    pub summary: String,
+    pub markdown: String,
+}
+
+impl StatusReport {
+    /// `status-<scope>-<period>-<end date>.md`. Goal scopes end in the
+    /// start of the goal id, so goals with the same title don't collide.
+    pub fn file_name(&self) -> String {
+        let scope = match self.scope {
+            ReportScope::Workspace => slugify(&self.data.title),
+            ReportScope::Goal(id) => {
+                let id = id.to_string();
+                format!("{}-{}", slugify(&self.data.title), &id[..8])
+            }
+        };
+        format!(
+            "status-{}-{}-{}.md",
+            scope,
+            self.period.label().to_lowercase(),
+            self.data.end.format("%Y-%m-%d")
+        )
+    }
+}
+
+/// Builds Markdown status reports of what changed in a workspace or goal.
+pub struct StatusReportService {
+    repository: Arc<Repository>,
+    summarizer: SummarizationService,
+    time_tracking: Option<Arc<TimeTrackingService>>,
+}
+
+impl StatusReportService {
+    pub fn new(repository: Arc<Repository>, summarizer: SummarizationService) -> Self {
+        Self {
+            repository,
+            summarizer,
+            time_tracking: None,
+        }
+    }
+
+    /// Summaries from the model chosen in the app settings.
+    pub fn from_settings(repository: Arc<Repository>, settings: &AppSettings) -> Self {
+        Self::new(repository, SummarizationService::from_settings(settings))
+    }
+
+    /// Include hours tracked by `time_tracking` in reports.
+    pub fn with_time_tracking(mut self, time_tracking: Arc<TimeTrackingService>) -> Self {
+        self.time_tracking = Some(time_tracking);
+        self
+    }
+
+    /// Gather the activity in `scope` between `start` and `end`.
+    pub async fn collect(
+        &self,
+        scope: ReportScope,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<StatusReportData> {
+        let all_tasks = self.repository.tasks.list(TaskFilters::default()).await?;
+        let (title, tasks) = match scope {
+            ReportScope::Workspace => ("Workspace".to_string(), all_tasks),
+            ReportScope::Goal(goal_id) => {
+                let goal = self
+                    .repository
+                    .goals
+                    .get(goal_id)
+                    .await?
+                    .ok_or_else(|| anyhow!("Goal {} not found", goal_id))?;
+                let tasks = all_tasks
+                    .into_iter()
+                    .filter(|t| t.goal_id == Some(goal.id) || goal.task_ids.contains(&t.id))
+                    .collect();
+                (goal.title, tasks)
+            }
+        };
+        let in_period = |at: DateTime<Utc>| at >= start && at < end;
+        let by_id: HashMap<Uuid, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+
+        let completed = tasks
+            .iter()
+            .filter(|t| t.completed_at.is_some_and(in_period))
+            .cloned()
+            .collect();
+        let created = tasks
+            .iter()
+            .filter(|t| in_period(t.created_at))
+            .cloned()
+            .collect();
+        let blocked = tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Blocked)
+            .cloned()
+            .collect();
+        let slipped = tasks
+            .iter()
+            .filter(|t| {
+                t.due_date.is_some_and(in_period)
+                    && !matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled)
+            })
+            .cloned()
+            .collect();
+
+        let mut sessions = Vec::new();
+        let mut merged_prs = Vec::new();
+        let recent: Vec<ClaudeCodeSession> = self
+            .repository
+            .claude_code
+            .get_sessions_updated_since(start)
+            .await?;
+        for session in recent {
+            let Some(task) = by_id.get(&session.task_id) else {
+                continue;
+            };
+            if in_period(session.started_at) || session.completed_at.is_some_and(in_period) {
+                sessions.push(SessionActivity {
+                    task_title: task.title.clone(),
+                    status: session.status,
+                    pr_number: session.pr_number,
+                });
+            }
+            // The PR monitor closes a session's PR and marks its task done on merge
+            if let Some(pr_url) = &session.pr_url
+                && session.pr_sync.closed
+                && session.pr_sync.synced_at.is_some_and(in_period)
+                && task.status == TaskStatus::Done
+            {
+                merged_prs.push(MergedPr {
+                    task_title: task.title.clone(),
+                    pr_number: session.pr_number,
+                    pr_url: pr_url.clone(),
+                });
+            }
+        }
+
+        let mut hours: Vec<(String, f64)> = match &self.time_tracking {
+            Some(time_tracking) => time_tracking
+                .get_time_summary(start, end)
+                .await?
+                .into_iter()
+                .filter_map(|(task_id, duration)| {
+                    by_id
+                        .get(&task_id)
+                        .map(|t| (t.title.clone(), time_entry::hours(duration)))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        hours.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(StatusReportData {
+            title,
+            start,
+            end,
+            completed,
+            created,
+            blocked,
+            slipped,
+            sessions,
+            merged_prs,
+            hours,
+        })
+    }
+
+    /// The report for `scope` over the `period` ending at `end`. The summary
+    /// comes from the model when one answers; otherwise the report is built
+    /// from the template alone.
+    pub async fn generate(
+        &self,
+        scope: ReportScope,
+        period: ReportPeriod,
+        end: DateTime<Utc>,
+    ) -> Result<StatusReport> {
+        let (start, end) = period.range_ending(end);
+        let data = self.collect(scope, start, end).await?;
+        let sections = data.sections_markdown();
+
+        let prompt = format!(
+            "Write a 2 to 4 sentence status summary of this {} report for \"{}\". \
+             Lead with progress, then name risks such as blocked or slipped tasks. \
+             Reply with the summary only.\n\n{}",
+            period.label().to_lowercase(),
+            data.title,
+            sections
+        );
+        let summary = match self.summarizer.complete(REPORT_SYSTEM, &prompt, 200).await {
+            Ok(summary) if !summary.trim().is_empty() => summary.trim().to_string(),
+            Ok(_) => data.overview(),
+            Err(e) => {
+                debug!("Status report without model summary: {}", e);
+                data.overview()
+            }
+        };
+
+        let markdown = format!(
+            "# {} status: {}\n\n_{} – {}_\n\n## Summary\n\n{}\n\n{}",
+            period.label(),
+            data.title,
+            data.start.format("%Y-%m-%d %H:%M UTC"),
+            data.end.format("%Y-%m-%d %H:%M UTC"),
+            summary,
+            sections
+        );
+        Ok(StatusReport {
+            scope,
+            period,
+            data,
+            summary,
+            markdown,
+        })
+    }
+
+    /// Write `report` into `dir` under its file name; returns the path.
+    pub async fn export(&self, report: &StatusReport, dir: &Path) -> Result<PathBuf> {
+        tokio::fs::create_dir_all(dir).await?;
+        let path = dir.join(report.file_name());
+        tokio::fs::write(&path, &report.markdown).await?;
+        Ok(path)
+    }
+
+    /// Goals that still get reports: neither completed nor cancelled.
+    pub async fn active_goals(&self) -> Result<Vec<Goal>> {
+        Ok(self
+            .repository
+            .goals
+            .list_all()
+            .await?
+            .into_iter()
+            .filter(|g| !matches!(g.status, GoalStatus::Completed | GoalStatus::Cancelled))
+            .collect())
+    }
+}
+
+/// Writes a workspace report and one per active goal every period.
+pub struct StatusReportScheduler {
+    service: StatusReportService,
+    period: ReportPeriod,
+    output_dir: PathBuf,
+}
+
+impl StatusReportScheduler {
+    pub fn new(service: StatusReportService, period: ReportPeriod, output_dir: PathBuf) -> Self {
+        Self {
+            service,
+            period,
+            output_dir,
+        }
+    }
+
+    /// Start the scheduler that runs in the background
+    pub async fn start(self) {
+        let period = self.period.duration().to_std().unwrap_or_default();
+        let mut interval = tokio::time::interval(period);
+
+        info!(
+            "Starting {} status reports into {}",
+            self.period.label().to_lowercase(),
+            self.output_dir.display()
+        );
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.run_once().await {
+                error!("Error writing status reports: {}", e);
+            }
+        }
+    }
+
+    /// Write the reports for the period ending now; returns their paths.
+    pub async fn run_once(&self) -> Result<Vec<PathBuf>> {
+        let end = Utc::now();
+        let mut scopes = vec![ReportScope::Workspace];
+        scopes.extend(
+            self.service
+                .active_goals()
+                .await?
+                .into_iter()
+                .map(|g| ReportScope::Goal(g.id)),
+        );
+
+        let mut written = Vec::new();
+        for scope in scopes {
+            let report = self.service.generate(scope, self.period, end).await?;
+            written.push(self.service.export(&report, &self.output_dir).await?);
+        }
+        info!("Wrote {} status reports", written.len());
+        Ok(written)
+    }
+}
+
+/// Start writing status reports every `period` into `output_dir`.
+pub fn start_status_reports_background(
+    service: StatusReportService,
+    period: ReportPeriod,
+    output_dir: PathBuf,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        StatusReportScheduler::new(service, period, output_dir).start().await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::database::init_test_database;
+    use crate::services::llm::ReplayProvider;
+    use tempfile::TempDir;
+
+    async fn setup() -> (Arc<Repository>, Goal) {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let now = Utc::now();
+
+        let goal = Goal::new("Reporting".to_string(), "Better reports".to_string());
+        repository.goals.create(&goal).await.unwrap();
+
+        let mut export = Task::new("Export tasks to CSV".to_string(), String::new());
+        export.goal_id = Some(goal.id);
+        export.update_status(TaskStatus::Done);
+        repository.tasks.create(&export).await.unwrap();
+
+        let mut pdf = Task::new("Export to PDF".to_string(), String::new());
+        pdf.goal_id = Some(goal.id);
+        pdf.status = TaskStatus::Blocked;
+        repository.tasks.create(&pdf).await.unwrap();
+
+        let mut onboarding = Task::new("Write onboarding guide".to_string(), String::new());
+        onboarding.due_date = Some(now - Duration::hours(3));
+        repository.tasks.create(&onboarding).await.unwrap();
+
+        let mut old = Task::new("Old cleanup".to_string(), String::new());
+        old.created_at = now - Duration::days(30);
+        repository.tasks.create(&old).await.unwrap();
+
+        let mut session = ClaudeCodeSession::new(export.id);
+        session.status = SessionStatus::Completed;
+        session.pr_url = Some("https://github.com/acme/plon/pull/12".to_string());
+        session.pr_number = Some(12);
+        session.pr_sync.closed = true;
+        session.pr_sync.synced_at = Some(now - Duration::hours(1));
+        repository.claude_code.create_session(&session).await.unwrap();
+
+        (repository, goal)
+    }
+
+    #[tokio::test]
+    async fn test_report_template_without_model() {
+        let (repository, goal) = setup().await;
+        let offline = SummarizationService::with_provider(Arc::new(ReplayProvider::new("")));
+        let service = StatusReportService::new(repository, offline);
+
+        let report = service
+            .generate(ReportScope::Workspace, ReportPeriod::Daily, Utc::now())
+            .await
+            .unwrap();
+        let data = &report.data;
+        assert_eq!(data.completed.len(), 1);
+        assert_eq!(data.created.len(), 3);
+        assert_eq!(data.blocked[0].title, "Export to PDF");
+        assert_eq!(data.slipped[0].title, "Write onboarding guide");
+        assert_eq!(data.sessions.len(), 1);
+        assert_eq!(data.merged_prs[0].pr_number, Some(12));
+        assert_eq!(
+            report.summary,
+            "1 task completed, 3 created, 1 blocked and 1 slipped; 1 agent session, 1 PR merged and 0.0 hours logged."
+        );
+        assert!(report.markdown.starts_with("# Daily status: Workspace"));
+        assert!(report.markdown.contains("- [#12 Export tasks to CSV](https://github.com/acme/plon/pull/12)"));
+
+        let goal_report = service
+            .generate(ReportScope::Goal(goal.id), ReportPeriod::Weekly, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(goal_report.data.title, "Reporting");
+        assert_eq!(goal_report.data.created.len(), 2);
+        assert!(goal_report.data.slipped.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_report_with_recorded_model() {
+        let (repository, goal) = setup().await;
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/llm");
+        let summarizer = SummarizationService::with_provider(Arc::new(ReplayProvider::new(fixtures)));
+        let service = StatusReportService::new(repository, summarizer);
+
+        let report = service
+            .generate(ReportScope::Goal(goal.id), ReportPeriod::Weekly, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            report.summary,
+            "CSV export shipped and its PR merged. PDF export is blocked."
+        );
+        assert!(report.markdown.contains("## Summary\n\nCSV export shipped"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_writes_reports() {
+        let (repository, goal) = setup().await;
+        let namesake = Goal::new(goal.title.clone(), String::new());
+        repository.goals.create(&namesake).await.unwrap();
+        let offline = SummarizationService::with_provider(Arc::new(ReplayProvider::new("")));
+        let service = StatusReportService::new(repository, offline);
+        let dir = TempDir::new().unwrap();
+
+        let scheduler = StatusReportScheduler::new(service, ReportPeriod::Daily, dir.path().to_path_buf());
+        let written = scheduler.run_once().await.unwrap();
+        assert_eq!(written.len(), 3);
+
+        // Goals sharing a title each get their own report
+        let date = Utc::now().format("%Y-%m-%d");
+        let goal_report = dir
+            .path()
+            .join(format!("status-reporting-{}-daily-{}.md", &goal.id.to_string()[..8], date));
+        assert!(written.contains(&goal_report));
+        assert!(written.iter().any(|p| p.to_string_lossy().contains(&namesake.id.to_string()[..8])));
+        let content = std::fs::read_to_string(goal_report).unwrap();
+        assert!(content.contains("## Completed (1)\n\n- Export tasks to CSV"));
+    }
+}
@@ -1,7 +1,12 @@
+use crate::domain::app_settings::AppSettings;
 use crate::domain::goal::Goal;
 use crate::domain::task::Task;
+use crate::repository::Repository;
+use crate::repository::summary_repository::{SummaryRepository, content_hash};
+use crate::repository::task_repository::TaskFilters;
+use crate::services::embeddings::terms;
+use crate::services::llm::{LlmConfig, LlmProvider, LlmRequest, OnChunk, provider_from_config};
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,6 +21,39 @@ pub enum SummarizationLevel {
     Detailed,  // Full information
 }
 
+impl SummarizationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SummarizationLevel::HighLevel => "HighLevel",
+            SummarizationLevel::MidLevel => "MidLevel",
+            SummarizationLevel::LowLevel => "LowLevel",
+            SummarizationLevel::Detailed => "Detailed",
+        }
+    }
+
+    /// The level shown on the map at `zoom`; `None` when cards are large
+    /// enough for the full description.
+    pub fn for_zoom(zoom: f32) -> Option<Self> {
+        if zoom >= 0.8 {
+            None
+        } else if zoom >= 0.55 {
+            Some(SummarizationLevel::MidLevel)
+        } else {
+            Some(SummarizationLevel::HighLevel)
+        }
+    }
+}
+
+/// The text a task's summaries are made from.
+pub fn task_summary_source(task: &Task) -> String {
+    format!("{}: {}", task.title, task.description)
+}
+
+/// The text a goal's summaries are made from.
+pub fn goal_summary_source(goal: &Goal) -> String {
+    format!("Goal: {}\n{}", goal.title, goal.description)
+}
+
 #[derive(Debug, Clone)]
 pub struct SummaryRequest {
     pub content: String,
@@ -30,11 +68,15 @@ pub struct SummaryResponse {
     pub processing_time_ms: u64,
 }
 
+const SUMMARY_SYSTEM: &str = "You are a concise summarization assistant.";
+
 #[derive(Clone)]
 pub struct SummarizationService {
     cache: Arc<RwLock<SummaryCache>>,
-    model_endpoint: String,
-    api_key: Option<String>,
+    /// `None` when no model is configured; summaries then use the fallback
+    provider: Option<Arc<dyn LlmProvider>>,
+    /// Keeps model summaries across restarts when set
+    store: Option<SummaryRepository>,
     max_retries: usize,
     timeout: Duration,
 }
@@ -65,23 +107,34 @@ impl Default for SummarizationService {
 }
 
 impl SummarizationService {
+    /// Uses the model set up through `LLM_ENDPOINT` and `LLM_API_KEY`.
     pub fn new() -> Self {
+        Self::with_optional_provider(LlmConfig::from_env().map(|c| provider_from_config(&c)))
+    }
+
+    /// Uses the model chosen in the app settings.
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self::with_provider(provider_from_config(&LlmConfig::from_settings(settings)))
+    }
+
+    pub fn with_provider(provider: Arc<dyn LlmProvider>) -> Self {
+        Self::with_optional_provider(Some(provider))
+    }
+
+    fn with_optional_provider(provider: Option<Arc<dyn LlmProvider>>) -> Self {
         Self {
             cache: Arc::new(RwLock::new(SummaryCache::new(500))),
-            model_endpoint: Self::get_model_endpoint(),
-            api_key: Self::get_api_key(),
+            provider,
+            store: None,
             max_retries: 3,
             timeout: Duration::from_secs(5),
         }
     }
 
-    fn get_model_endpoint() -> String {
-        std::env::var("LLM_ENDPOINT")
-            .unwrap_or_else(|_| "http://localhost:11434/api/generate".to_string())
-    }
-
-    fn get_api_key() -> Option<String> {
-        std::env::var("LLM_API_KEY").ok()
+    /// Persist model summaries in `store` and read them back from it.
+    pub fn with_store(mut self, store: SummaryRepository) -> Self {
+        self.store = Some(store);
+        self
     }
 
     pub async fn summarize(&self, content: &str, level: SummarizationLevel) -> String {
@@ -138,6 +191,126 @@ impl SummarizationService {
         summary
     }
 
+    /// Summary of one entity's text, from memory, the persistent store or
+    /// the model, in that order. Only model summaries are persisted, so the
+    /// rule-based fallback never hides a model that comes back later.
+    pub async fn summarize_entity(
+        &self,
+        entity_id: Uuid,
+        content: &str,
+        level: SummarizationLevel,
+    ) -> String {
+        let key = CacheKey {
+            content_hash: Self::hash_content(content),
+            level,
+        };
+        if let Some(summary) = self.cache.write().await.get(&key) {
+            return summary;
+        }
+
+        let hash = content_hash(content);
+        if let Some(store) = &self.store
+            && let Ok(Some(summary)) = store.get(entity_id, level.as_str(), &hash).await
+        {
+            self.cache.write().await.insert(key, summary.clone());
+            return summary;
+        }
+
+        let summary = match self.generate_and_store(entity_id, content, level).await {
+            Some(summary) => summary,
+            None => self.fallback_summary(content, level),
+        };
+        self.cache.write().await.insert(key, summary.clone());
+        summary
+    }
+
+    /// Persisted summaries at `level` for entities whose text is unchanged.
+    /// Nothing is generated, so this is fast enough to call on every zoom.
+    pub async fn stored_summaries(
+        &self,
+        entities: &[(Uuid, String)],
+        level: SummarizationLevel,
+    ) -> Result<HashMap<Uuid, String>> {
+        let Some(store) = &self.store else {
+            return Ok(HashMap::new());
+        };
+        let hashes: Vec<(Uuid, String)> = entities
+            .iter()
+            .map(|(id, content)| (*id, content_hash(content)))
+            .collect();
+        store.get_many(&hashes, level.as_str()).await
+    }
+
+    /// Generate and persist the summaries missing from the store, running at
+    /// most `max_concurrent` model calls at once. Each new summary is passed
+    /// to `on_summary`; returns how many were generated.
+    pub async fn warm(
+        &self,
+        entities: Vec<(Uuid, String)>,
+        levels: &[SummarizationLevel],
+        max_concurrent: usize,
+        mut on_summary: impl FnMut(Uuid, SummarizationLevel, String),
+    ) -> Result<usize> {
+        if self.provider.is_none() || self.store.is_none() {
+            return Ok(0);
+        }
+
+        let mut missing = Vec::new();
+        for level in levels {
+            let stored = self.stored_summaries(&entities, *level).await?;
+            missing.extend(
+                entities
+                    .iter()
+                    .filter(|(id, _)| !stored.contains_key(id))
+                    .map(|(id, content)| (*id, content.clone(), *level)),
+            );
+        }
+
+        let mut running = tokio::task::JoinSet::new();
+        let mut generated = 0;
+        let mut missing = missing.into_iter();
+        loop {
+            while running.len() < max_concurrent.max(1) {
+                let Some((entity_id, content, level)) = missing.next() else {
+                    break;
+                };
+                let service = self.clone();
+                running.spawn(async move {
+                    let summary = service.generate_and_store(entity_id, &content, level).await;
+                    (entity_id, level, summary)
+                });
+            }
+            let Some(finished) = running.join_next().await else {
+                break;
+            };
+            if let (entity_id, level, Some(summary)) = finished? {
+                generated += 1;
+                on_summary(entity_id, level, summary);
+            }
+        }
+        Ok(generated)
+    }
+
+    /// A model summary, saved to the store when there is one. `None` when
+    /// the model is unavailable.
+    async fn generate_and_store(
+        &self,
+        entity_id: Uuid,
+        content: &str,
+        level: SummarizationLevel,
+    ) -> Option<String> {
+        self.provider.as_ref()?;
+        let summary = self.generate_summary(content, level).await.ok()?;
+        if let Some(store) = &self.store
+            && let Err(e) = store
+                .save(entity_id, level.as_str(), &content_hash(content), &summary)
+                .await
+        {
+            tracing::warn!("Failed to persist summary for {}: {}", entity_id, e);
+        }
+        Some(summary)
+    }
+
     pub async fn summarize_cluster(&self, tasks: &[Task], level: SummarizationLevel) -> String {
         if tasks.is_empty() {
             return String::new();
@@ -156,6 +329,71 @@ impl SummarizationService {
             .await
     }
 
+    /// A name of a few words for the topic a group of tasks share. Asks the
+    /// model when one is configured, otherwise uses the words the tasks
+    /// have most in common.
+    pub async fn label_cluster(&self, tasks: &[Task]) -> String {
+        if tasks.is_empty() {
+            return String::new();
+        }
+        let listing = tasks
+            .iter()
+            .map(|t| format!("- {}", task_summary_source(t)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = format!(
+            "Name the common topic of these tasks in 2 to 4 words. Reply with the name only.\n\nTasks:\n{}",
+            listing
+        );
+
+        if let Some(provider) = &self.provider {
+            let request = LlmRequest::new(SUMMARY_SYSTEM, &prompt).with_max_tokens(16);
+            if let Ok(Ok(label)) =
+                tokio::time::timeout(self.timeout, provider.complete(&request)).await
+            {
+                let label = label
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .unwrap_or_default()
+                    .trim()
+                    .trim_matches(|c: char| c == '"' || c == '.' || c == '*');
+                if !label.is_empty() {
+                    return label.chars().take(40).collect();
+                }
+            }
+        }
+        Self::fallback_label(tasks)
+    }
+
+    fn fallback_label(tasks: &[Task]) -> String {
+        // Title words count double; ties go to the word seen first
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for task in tasks {
+            let words = terms(&task.title)
+                .into_iter()
+                .flat_map(|w| [w.clone(), w])
+                .chain(terms(&task.description));
+            for word in words {
+                match counts.iter_mut().find(|(w, _)| *w == word) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((word, 1)),
+                }
+            }
+        }
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let label = counts
+            .into_iter()
+            .take(3)
+            .map(|(w, _)| w)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut chars = label.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => tasks[0].title.chars().take(40).collect(),
+        }
+    }
+
     pub async fn summarize_goal(
         &self,
         goal: &Goal,
@@ -223,122 +461,42 @@ impl SummarizationService {
         )
     }
 
-    async fn call_llm(&self, prompt: &str) -> Result<String> {
-        // For Ollama integration
-        if self.model_endpoint.contains("ollama") || self.model_endpoint.contains("11434") {
-            return self.call_ollama(prompt).await;
-        }
-
-        // For OpenAI-compatible APIs
-        if let Some(api_key) = &self.api_key {
-            return self.call_openai_compatible(prompt, api_key).await;
-        }
-
-        // Fallback to local processing
-        Ok(self.fallback_summary(prompt, SummarizationLevel::MidLevel))
+    /// Send a prompt to the configured model without the summarization
+    /// fallback; fails when no model is configured. Longer outputs than
+    /// summaries get a longer timeout.
+    pub async fn complete(&self, system: &str, prompt: &str, max_tokens: i32) -> Result<String> {
+        self.stream(system, prompt, max_tokens, &mut |_| {}).await
     }
 
-    async fn call_ollama(&self, prompt: &str) -> Result<String> {
-        #[derive(Serialize)]
-        struct OllamaRequest {
-            model: String,
-            prompt: String,
-            stream: bool,
-            options: OllamaOptions,
-        }
-
-        #[derive(Serialize)]
-        struct OllamaOptions {
-            temperature: f32,
-            top_p: f32,
-            max_tokens: i32,
-        }
-
-        #[derive(Deserialize)]
-        struct OllamaResponse {
-            response: String,
-        }
-
-        let request = OllamaRequest {
-            model: "llama3.2:1b".to_string(), // Fast small model
-            prompt: prompt.to_string(),
-            stream: false,
-            options: OllamaOptions {
-                temperature: 0.3,
-                top_p: 0.9,
-                max_tokens: 150,
-            },
-        };
-
-        let client = reqwest::Client::new();
-        let response = client
-            .post(&self.model_endpoint)
-            .json(&request)
-            .timeout(self.timeout)
-            .send()
-            .await?;
-
-        let ollama_response: OllamaResponse = response.json().await?;
-        Ok(ollama_response.response)
+    /// Like `complete`, passing the answer to `on_chunk` as it streams in.
+    pub async fn stream(
+        &self,
+        system: &str,
+        prompt: &str,
+        max_tokens: i32,
+        on_chunk: &mut OnChunk<'_>,
+    ) -> Result<String> {
+        let provider = self.provider.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("No language model configured; choose a provider in Settings")
+        })?;
+        let request = LlmRequest::new(system, prompt).with_max_tokens(max_tokens);
+        tokio::time::timeout(
+            Duration::from_secs(120),
+            provider.stream(&request, on_chunk),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("{} did not answer in time", provider.name()))?
     }
 
-    async fn call_openai_compatible(&self, prompt: &str, api_key: &str) -> Result<String> {
-        #[derive(Serialize)]
-        struct OpenAIRequest {
-            model: String,
-            messages: Vec<Message>,
-            temperature: f32,
-            max_tokens: i32,
-        }
-
-        #[derive(Serialize)]
-        struct Message {
-            role: String,
-            content: String,
-        }
-
-        #[derive(Deserialize)]
-        struct OpenAIResponse {
-            choices: Vec<Choice>,
-        }
-
-        #[derive(Deserialize)]
-        struct Choice {
-            message: ResponseMessage,
-        }
-
-        #[derive(Deserialize)]
-        struct ResponseMessage {
-            content: String,
-        }
-
-        let request = OpenAIRequest {
-            model: "gpt-4o-mini".to_string(), // Fast model
-            messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: "You are a concise summarization assistant.".to_string(),
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                },
-            ],
-            temperature: 0.3,
-            max_tokens: 150,
+    async fn call_llm(&self, prompt: &str) -> Result<String> {
+        let Some(provider) = &self.provider else {
+            // Fallback to local processing
+            return Ok(self.fallback_summary(prompt, SummarizationLevel::MidLevel));
         };
-
-        let client = reqwest::Client::new();
-        let response = client
-            .post(&self.model_endpoint)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(&request)
-            .timeout(self.timeout)
-            .send()
-            .await?;
-
-        let openai_response: OpenAIResponse = response.json().await?;
-        Ok(openai_response.choices[0].message.content.clone())
+        let request = LlmRequest::new(SUMMARY_SYSTEM, prompt).with_max_tokens(150);
+        tokio::time::timeout(self.timeout, provider.complete(&request))
+            .await
+            .map_err(|_| anyhow::anyhow!("{} did not answer in time", provider.name()))?
     }
 
     fn fallback_summary(&self, content: &str, level: SummarizationLevel) -> String {
@@ -385,14 +543,45 @@ impl SummarizationService {
     pub fn clone(&self) -> Self {
         Self {
             cache: Arc::clone(&self.cache),
-            model_endpoint: self.model_endpoint.clone(),
-            api_key: self.api_key.clone(),
+            provider: self.provider.clone(),
+            store: self.store.clone(),
             max_retries: self.max_retries,
             timeout: self.timeout,
         }
     }
 }
 
+/// Summary levels the map shows when zoomed out.
+pub const MAP_LEVELS: [SummarizationLevel; 2] =
+    [SummarizationLevel::MidLevel, SummarizationLevel::HighLevel];
+
+/// Warm the persistent store for every task and goal at the map's levels,
+/// so semantic zoom does not wait on the model after a restart.
+pub fn start_summary_warming_background(
+    repository: Arc<Repository>,
+    service: SummarizationService,
+    max_concurrent: usize,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let service = service.with_store(repository.summaries.clone());
+        let mut entities: Vec<(Uuid, String)> = Vec::new();
+        match repository.tasks.list(TaskFilters::default()).await {
+            Ok(tasks) => entities.extend(tasks.iter().map(|t| (t.id, task_summary_source(t)))),
+            Err(e) => tracing::warn!("Summary warming could not load tasks: {}", e),
+        }
+        match repository.goals.list_all().await {
+            Ok(goals) => entities.extend(goals.iter().map(|g| (g.id, goal_summary_source(g)))),
+            Err(e) => tracing::warn!("Summary warming could not load goals: {}", e),
+        }
+        if let Err(e) = service
+            .warm(entities, &MAP_LEVELS, max_concurrent, |_, _, _| {})
+            .await
+        {
+            tracing::warn!("Summary warming failed: {}", e);
+        }
+    })
+}
+
 impl SummaryCache {
     pub fn new(max_size: usize) -> Self {
         Self {
@@ -455,6 +644,7 @@ impl SummaryCache {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::llm::ReplayProvider;
 
     #[tokio::test]
     async fn test_summarize_caching() {
@@ -540,6 +730,176 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_summarize_with_recorded_model() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/llm");
+        let service = SummarizationService::with_provider(Arc::new(ReplayProvider::new(fixtures)));
+
+        let summary = service
+            .summarize(
+                "Users need to export their task list as CSV. Columns should match the list view.",
+                SummarizationLevel::HighLevel,
+            )
+            .await;
+        assert_eq!(summary, "CSV export for the task list.");
+
+        // Without a recording the rule-based fallback answers
+        let fallback = service
+            .summarize(
+                "Nothing recorded. Second sentence.",
+                SummarizationLevel::HighLevel,
+            )
+            .await;
+        assert_eq!(fallback, "Nothing recorded");
+        assert!(service.complete("", "Nothing recorded", 100).await.is_err());
+    }
+
+    /// Answers after a short delay and records how many calls overlapped.
+    #[derive(Default)]
+    struct SlowProvider {
+        calls: std::sync::atomic::AtomicUsize,
+        active: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmProvider for SlowProvider {
+        fn name(&self) -> &str {
+            "Slow"
+        }
+
+        async fn stream(&self, request: &LlmRequest, on_chunk: &mut OnChunk<'_>) -> Result<String> {
+            use std::sync::atomic::Ordering;
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            let content = request
+                .prompt
+                .split("Content:\n")
+                .nth(1)
+                .unwrap_or_default();
+            let summary = format!("Summary of {}", content.lines().next().unwrap_or_default());
+            on_chunk(&summary);
+            Ok(summary)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persisted_summaries_survive_restart() {
+        let pool = crate::repository::database::init_test_database()
+            .await
+            .unwrap();
+        let repository = Repository::new(pool);
+        let task = Task::new("Export".to_string(), "CSV for the list view".to_string());
+        repository.tasks.create(&task).await.unwrap();
+        let source = task_summary_source(&task);
+
+        let provider = Arc::new(SlowProvider::default());
+        let service = SummarizationService::with_provider(provider.clone())
+            .with_store(repository.summaries.clone());
+        let summary = service
+            .summarize_entity(task.id, &source, SummarizationLevel::HighLevel)
+            .await;
+        assert_eq!(summary, "Summary of Export: CSV for the list view");
+
+        // A fresh service, as after a restart, reads it back without the model
+        let offline = SummarizationService::with_provider(Arc::new(ReplayProvider::new("")))
+            .with_store(repository.summaries.clone());
+        let again = offline
+            .summarize_entity(task.id, &source, SummarizationLevel::HighLevel)
+            .await;
+        assert_eq!(again, summary);
+        let stored = offline
+            .stored_summaries(&[(task.id, source.clone())], SummarizationLevel::HighLevel)
+            .await
+            .unwrap();
+        assert_eq!(stored[&task.id], summary);
+        assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Fallback summaries are not persisted
+        let other = Task::new("Import".to_string(), "From CSV. Later.".to_string());
+        offline
+            .summarize_entity(
+                other.id,
+                &task_summary_source(&other),
+                SummarizationLevel::HighLevel,
+            )
+            .await;
+        assert_eq!(repository.summaries.count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_warm_is_bounded_and_skips_stored() {
+        use std::sync::atomic::Ordering;
+        let pool = crate::repository::database::init_test_database()
+            .await
+            .unwrap();
+        let repository = Repository::new(pool);
+        let mut tasks = Vec::new();
+        for i in 0..5 {
+            let task = Task::new(format!("Task {}", i), format!("Details {}", i));
+            repository.tasks.create(&task).await.unwrap();
+            tasks.push(task);
+        }
+        let entities: Vec<(Uuid, String)> = tasks
+            .iter()
+            .map(|t| (t.id, task_summary_source(t)))
+            .collect();
+
+        let provider = Arc::new(SlowProvider::default());
+        let service = SummarizationService::with_provider(provider.clone())
+            .with_store(repository.summaries.clone());
+        let mut seen = Vec::new();
+        let generated = service
+            .warm(entities.clone(), &MAP_LEVELS, 2, |id, level, _| {
+                seen.push((id, level))
+            })
+            .await
+            .unwrap();
+        assert_eq!(generated, 10);
+        assert_eq!(seen.len(), 10);
+        assert!(provider.peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(
+            service
+                .warm(entities, &MAP_LEVELS, 2, |_, _, _| {})
+                .await
+                .unwrap(),
+            0
+        );
+
+        // Editing one task's text leaves only that task to re-summarize
+        let mut edited = tasks[0].clone();
+        edited.description = "Rewritten".to_string();
+        repository.tasks.update(&edited).await.unwrap();
+        let entities: Vec<(Uuid, String)> = std::iter::once(&edited)
+            .chain(&tasks[1..])
+            .map(|t| (t.id, task_summary_source(t)))
+            .collect();
+        assert_eq!(
+            service
+                .warm(entities, &MAP_LEVELS, 2, |_, _, _| {})
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 12);
+    }
+
+    #[test]
+    fn test_level_for_zoom() {
+        assert_eq!(SummarizationLevel::for_zoom(1.0), None);
+        assert_eq!(
+            SummarizationLevel::for_zoom(0.6),
+            Some(SummarizationLevel::MidLevel)
+        );
+        assert_eq!(
+            SummarizationLevel::for_zoom(0.3),
+            Some(SummarizationLevel::HighLevel)
+        );
+    }
+
     #[test]
     fn test_summary_cache() {
         let mut cache = SummaryCache::new(2);
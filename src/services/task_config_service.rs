@@ -215,6 +215,9 @@ mod tests {
             required: false,
             options: vec![],
             default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
             validation_rules: vec![],
             help_text: String::new(),
             show_in_list: true,
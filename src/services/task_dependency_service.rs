@@ -1,6 +1,7 @@
 use crate::domain::task::{Task, TaskStatus};
 use crate::repository::Repository;
 use crate::repository::task_repository::TaskFilters;
+use crate::services::task_service::TaskService;
 use std::sync::Arc;
 use uuid::Uuid;
 use anyhow::Result;
@@ -13,6 +14,29 @@ impl TaskDependencyService {
     pub fn new(repository: Arc<Repository>) -> Self {
         Self { repository }
     }
+
+    /// Status changes go through the task's workflow, if it has one
+    fn task_service(&self) -> TaskService {
+        TaskService::new(self.repository.clone())
+    }
+
+    /// Move `task` to `status`. A task whose workflow refuses the move is
+    /// logged and left as it is, so one task can't hold up the others.
+    /// Returns whether it moved.
+    async fn set_status(&self, mut task: Task, status: TaskStatus) -> Result<bool> {
+        let task_id = task.id;
+        let configured = task.configuration_id.is_some();
+        task.status = status;
+        task.updated_at = chrono::Utc::now();
+        match self.task_service().update(task).await {
+            Ok(task) => Ok(task.status == status),
+            Err(e) if configured => {
+                tracing::warn!("Leaving task {} out of {:?}: {}", task_id, status, e);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
     
     /// Check if a task can be completed based on its dependencies
     pub async fn can_complete_task(&self, task_id: Uuid) -> Result<bool> {
@@ -56,14 +80,14 @@ impl TaskDependencyService {
         let dependent_tasks = self.get_dependent_tasks(completed_task_id).await?;
         let mut unblocked_ids = Vec::new();
         
-        for mut task in dependent_tasks {
+        for task in dependent_tasks {
             if task.status == TaskStatus::Blocked {
                 // Check if all other dependencies are also complete
-                if self.can_complete_task(task.id).await? {
-                    task.status = TaskStatus::Todo;
-                    task.updated_at = chrono::Utc::now();
-                    self.repository.tasks.update(&task).await?;
-                    unblocked_ids.push(task.id);
+                let task_id = task.id;
+                if self.can_complete_task(task_id).await?
+                    && self.set_status(task, TaskStatus::Todo).await?
+                {
+                    unblocked_ids.push(task_id);
                 }
             }
         }
@@ -83,11 +107,7 @@ impl TaskDependencyService {
             // If parent is not complete, task should be blocked
             if parent.status != TaskStatus::Done && parent.status != TaskStatus::Cancelled {
                 if task.status != TaskStatus::Blocked {
-                    let mut updated_task = task;
-                    updated_task.status = TaskStatus::Blocked;
-                    updated_task.updated_at = chrono::Utc::now();
-                    self.repository.tasks.update(&updated_task).await?;
-                    return Ok(true); // Task was blocked
+                    return self.set_status(task, TaskStatus::Blocked).await;
                 }
             }
         }
@@ -105,17 +125,17 @@ impl TaskDependencyService {
             return Err(anyhow::anyhow!("Cannot create circular dependency"));
         }
         
+        let parent = self.repository.tasks.get(parent_task_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Parent task not found"))?;
+        
         dependent_task.parent_task_id = Some(parent_task_id);
         dependent_task.updated_at = chrono::Utc::now();
+        let dependent_task = self.task_service().update(dependent_task).await?;
         
         // Check if task should be blocked
-        let parent = self.repository.tasks.get(parent_task_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Parent task not found"))?;
         if parent.status != TaskStatus::Done && parent.status != TaskStatus::Cancelled {
-            dependent_task.status = TaskStatus::Blocked;
+            self.set_status(dependent_task, TaskStatus::Blocked).await?;
         }
-        
-        self.repository.tasks.update(&dependent_task).await?;
         Ok(())
     }
     
@@ -148,14 +168,13 @@ impl TaskDependencyService {
         let mut task = self.repository.tasks.get(task_id).await?
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
         task.parent_task_id = None;
+        task.updated_at = chrono::Utc::now();
+        let task = self.task_service().update(task).await?;
         
         // Unblock task if it was blocked only due to dependency
         if task.status == TaskStatus::Blocked {
-            task.status = TaskStatus::Todo;
+            self.set_status(task, TaskStatus::Todo).await?;
         }
-        
-        task.updated_at = chrono::Utc::now();
-        self.repository.tasks.update(&task).await?;
         Ok(())
     }
 }
@@ -163,8 +182,6 @@ impl TaskDependencyService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::task::{Task, Priority};
-    use std::collections::HashSet;
     use sqlx::SqlitePool;
     
     async fn setup_test_service() -> TaskDependencyService {
@@ -179,24 +196,7 @@ mod tests {
         let service = setup_test_service().await;
         
         // Create three tasks
-        let task_a = Task {
-            id: Uuid::new_v4(),
-            title: "Task A".to_string(),
-            description: "".to_string(),
-            status: TaskStatus::Todo,
-            priority: Priority::Medium,
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
-            due_date: None,
-            estimated_hours: None,
-            actual_hours: None,
-            assigned_resource_id: None,
-            goal_id: None,
-            parent_task_id: None,
-            tags: HashSet::new(),
-            assignee: None,
-            position: crate::domain::task::Position { x: 0.0, y: 0.0 },
-        };
+        let task_a = Task::new("Task A".to_string(), String::new());
         
         let task_b = Task {
             id: Uuid::new_v4(),
@@ -213,4 +213,52 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("circular"));
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_workflow_refusing_a_status_skips_the_task() {
+        use crate::domain::task_config::TaskConfiguration;
+        
+        let service = setup_test_service().await;
+        let repository = service.repository.clone();
+        // No Blocked state, and no way back to To Do once started
+        let mut config = TaskConfiguration::new("No blocking".to_string());
+        config.state_machine.transitions.retain(|t| t.to_state != "todo");
+        repository.task_configs.create(&config).await.unwrap();
+        
+        let parent = Task::new("Parent".to_string(), String::new());
+        repository.tasks.create(&parent).await.unwrap();
+        let mut configured = Task::new("Configured".to_string(), String::new());
+        configured.configuration_id = Some(config.id);
+        let configured = service.task_service().create(configured).await.unwrap().task;
+        let plain = Task::new("Plain".to_string(), String::new());
+        repository.tasks.create(&plain).await.unwrap();
+        
+        // The dependency is kept even though the task can't be blocked
+        service.create_dependency(configured.id, parent.id).await.unwrap();
+        let stored = repository.tasks.get(configured.id).await.unwrap().unwrap();
+        assert_eq!(stored.parent_task_id, Some(parent.id));
+        assert_eq!(stored.status, TaskStatus::Todo);
+        assert!(!service.update_task_blocked_status(configured.id).await.unwrap());
+        
+        // A configured task stuck in Blocked doesn't stop the others unblocking
+        let mut stuck = stored;
+        stuck.status = TaskStatus::Blocked;
+        stuck.workflow_state = Some("in_progress".to_string());
+        repository.tasks.update(&stuck).await.unwrap();
+        service.create_dependency(plain.id, parent.id).await.unwrap();
+        assert_eq!(
+            repository.tasks.get(plain.id).await.unwrap().unwrap().status,
+            TaskStatus::Blocked
+        );
+        
+        let mut done = repository.tasks.get(parent.id).await.unwrap().unwrap();
+        done.status = TaskStatus::Done;
+        repository.tasks.update(&done).await.unwrap();
+        let unblocked = service.unblock_dependent_tasks(parent.id).await.unwrap();
+        assert_eq!(unblocked, vec![plain.id]);
+        assert_eq!(
+            repository.tasks.get(configured.id).await.unwrap().unwrap().status,
+            TaskStatus::Blocked
+        );
+    }
+}
@@ -1,35 +1,704 @@
-use crate::domain::task::Task;
+use crate::domain::approval::{ApprovalRequest, ApprovalStatus};
+use crate::domain::dependency::{Dependency, DependencyGraph};
+use crate::domain::formula::RelatedItem;
+use crate::domain::notification::Notification;
+use crate::domain::script::ScriptContext;
+use crate::domain::task::{Task, TaskStatus};
+use crate::domain::task_config::{
+    FieldType, RelationTarget, StateEntry, TaskConfiguration, TransitionContext,
+};
+use crate::domain::time_entry::actual_hours;
 use crate::repository::Repository;
-use anyhow::Result;
+use crate::services::duplicate_detector::{DuplicateCandidate, DuplicateDetector, normalize};
+use anyhow::{Result, anyhow, bail};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct TaskService {
     pub repository: Arc<Repository>,
+    duplicates: DuplicateDetector,
+}
+
+/// How a requested transition went.
+#[derive(Debug, Clone)]
+pub enum TransitionOutcome {
+    Moved(Box<Task>),
+    /// The transition needs sign-off and goes ahead once these requests
+    /// are approved
+    AwaitingApproval(Vec<ApprovalRequest>),
+}
+
+impl TransitionOutcome {
+    /// The moved task, or `None` while the transition awaits approval.
+    pub fn moved(self) -> Option<Task> {
+        match self {
+            TransitionOutcome::Moved(task) => Some(*task),
+            TransitionOutcome::AwaitingApproval(_) => None,
+        }
+    }
+}
+
+/// A newly saved task and the existing tasks it may duplicate.
+#[derive(Debug, Clone)]
+pub struct CreatedTask {
+    pub task: Task,
+    pub duplicates: Vec<DuplicateCandidate>,
 }
 
 impl TaskService {
     pub fn new(repository: Arc<Repository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            duplicates: DuplicateDetector::new(),
+        }
     }
 
-    pub async fn create(&self, task: Task) -> Result<Task> {
-        self.repository.tasks.create(&task).await?;
+    /// Check new tasks with `detector`, keeping its task vectors in this
+    /// service's repository.
+    pub fn with_duplicate_detector(mut self, detector: DuplicateDetector) -> Self {
+        self.duplicates = detector.with_store(self.repository.embeddings.clone());
+        self
+    }
+
+    /// Save `task`, reporting existing tasks it looks like. The task is
+    /// saved either way; callers can offer to `merge` it into a candidate.
+    /// Tasks with a configuration start in its initial state.
+    pub async fn create(&self, task: Task) -> Result<CreatedTask> {
+        let existing = self.repository.tasks.list(Default::default()).await?;
+        let duplicates = self.duplicates.find_duplicates(&task, &existing).await?;
+        let task = self.save_new(task).await?;
+        Ok(CreatedTask { task, duplicates })
+    }
+
+    /// Save many tasks, checking each against the board and against the
+    /// tasks imported before it.
+    pub async fn import(&self, tasks: Vec<Task>) -> Result<Vec<CreatedTask>> {
+        let mut existing = self.repository.tasks.list(Default::default()).await?;
+        let mut imported = Vec::new();
+        for task in tasks {
+            let duplicates = self.duplicates.find_duplicates(&task, &existing).await?;
+            let task = self.save_new(task).await?;
+            existing.push(task.clone());
+            imported.push(CreatedTask { task, duplicates });
+        }
+        Ok(imported)
+    }
+
+    /// Fold `duplicate_id` into `keep_id` and delete it. The kept task
+    /// gains the duplicate's subtasks, tags, metadata it lacks, comments,
+    /// logged time, approval requests, dependencies, goal links, child
+    /// tasks and the relation fields linking to it. A running timer on the
+    /// duplicate is paused.
+    pub async fn merge(&self, keep_id: Uuid, duplicate_id: Uuid) -> Result<Task> {
+        if keep_id == duplicate_id {
+            anyhow::bail!("Cannot merge a task into itself");
+        }
+        let mut keep = self
+            .get(keep_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", keep_id))?;
+        let duplicate = self
+            .get(duplicate_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", duplicate_id))?;
+
+        for mut subtask in duplicate.subtasks {
+            let text = normalize(&subtask.description);
+            if !keep
+                .subtasks
+                .iter()
+                .any(|s| normalize(&s.description) == text)
+            {
+                subtask.id = Uuid::new_v4();
+                keep.subtasks.push(subtask);
+            }
+        }
+        keep.tags.extend(duplicate.tags);
+        for (key, value) in duplicate.metadata {
+            keep.metadata.entry(key).or_insert(value);
+        }
+        if keep.description.trim().is_empty() {
+            keep.description = duplicate.description;
+        }
+        keep.goal_id = keep.goal_id.or(duplicate.goal_id);
+
+        self.repository
+            .comments
+            .reassign(duplicate_id, keep_id)
+            .await?;
+        if let Some(mut running) = self
+            .repository
+            .time_entries
+            .get_running(duplicate_id)
+            .await?
+        {
+            running.end_time = Some(chrono::Utc::now());
+            running.updated_at = chrono::Utc::now();
+            self.repository.time_entries.update(&running).await?;
+        }
+        self.repository
+            .time_entries
+            .reassign(duplicate_id, keep_id)
+            .await?;
+        keep.actual_hours =
+            actual_hours(&self.repository.time_entries.list_for_task(keep_id).await?);
+        self.repository
+            .approvals
+            .reassign(duplicate_id, keep_id)
+            .await?;
+        self.merge_dependencies(keep_id, duplicate_id).await?;
+
+        for mut goal in self.repository.goals.list_all().await? {
+            if goal.remove_task(&duplicate_id) {
+                goal.add_task(keep_id);
+                self.repository.goals.update(&goal).await?;
+            }
+        }
+        for mut child in self.list_all().await? {
+            if child.parent_task_id == Some(duplicate_id) {
+                child.parent_task_id = Some(keep_id);
+                self.repository.tasks.update(&child).await?;
+            }
+        }
+
+        self.relink_relations(keep_id, duplicate_id).await?;
+
+        // The duplicate goes last, together with saving the kept task: the
+        // steps above only move things onto the kept task, so a merge that
+        // fails part way loses nothing. Deleting first in that transaction
+        // frees the unique metadata values the kept task took over.
+        keep.updated_at = chrono::Utc::now();
+        self.repository.tasks.replace(&keep, duplicate_id).await?;
+        self.recompute_related(keep_id).await?;
+        Ok(keep)
+    }
+
+    /// Point the relation fields that link to the duplicate at the kept
+    /// task instead.
+    async fn relink_relations(&self, keep_id: Uuid, duplicate_id: Uuid) -> Result<()> {
+        let linking = self
+            .repository
+            .task_metadata
+            .tasks_listing(&duplicate_id.to_string())
+            .await?;
+        for task_id in linking {
+            let Some(mut task) = self.repository.tasks.get(task_id).await? else {
+                continue;
+            };
+            let Some(config) = self.configuration(&task).await? else {
+                continue;
+            };
+            for (field, _, ids) in config.relations(&task) {
+                if !ids.contains(&duplicate_id) {
+                    continue;
+                }
+                let mut relinked: Vec<Uuid> = Vec::new();
+                for id in ids {
+                    let id = if id == duplicate_id { keep_id } else { id };
+                    if !relinked.contains(&id) {
+                        relinked.push(id);
+                    }
+                }
+                let value = relinked
+                    .iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                task.metadata.insert(field, value);
+            }
+            self.repository.tasks.update(&task).await?;
+        }
+        Ok(())
+    }
+
+    /// Point the duplicate's dependencies at the kept task, skipping ones
+    /// it already has and ones that would become a cycle.
+    async fn merge_dependencies(&self, keep_id: Uuid, duplicate_id: Uuid) -> Result<()> {
+        let all = self.repository.dependencies.list_all().await?;
+        let (moving, staying): (Vec<Dependency>, Vec<Dependency>) = all
+            .into_iter()
+            .partition(|d| d.from_task_id == duplicate_id || d.to_task_id == duplicate_id);
+
+        let mut graph = DependencyGraph::new();
+        for dependency in &staying {
+            let _ = graph.add_dependency(dependency);
+        }
+        for old in moving {
+            self.repository
+                .dependencies
+                .delete(old.from_task_id, old.to_task_id)
+                .await?;
+            let swap = |id: Uuid| if id == duplicate_id { keep_id } else { id };
+            let (from, to) = (swap(old.from_task_id), swap(old.to_task_id));
+            let exists = staying
+                .iter()
+                .any(|d| d.from_task_id == from && d.to_task_id == to);
+            if from == to || exists {
+                continue;
+            }
+            let rewired = Dependency::new(from, to, old.dependency_type);
+            if graph.add_dependency(&rewired).is_ok() {
+                self.repository.dependencies.create(&rewired).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save changes to `task`. For a task with a configuration, a changed
+    /// `workflow_state`, or a changed `status` the state machine can reach
+    /// in one step, goes through `transition`'s checks and effects. If the
+    /// transition awaits approval, the other changes are saved and the task
+    /// stays where it was. Only the metadata fields that changed are
+    /// validated. Formulas are recomputed, here and in the tasks related to
+    /// this one.
+    pub async fn update(&self, mut task: Task) -> Result<Task> {
+        if let Some(config) = self.configuration(&task).await?
+            && let Some(stored) = self.repository.tasks.get(task.id).await?
+        {
+            self.compute_formulas(&config, &mut task).await?;
+            let context = self.script_context(&config, &task).await?;
+            config
+                .validate_changes(&stored.metadata, &task, &context)
+                .map_err(|errors| anyhow!("{}", errors.join("; ")))?;
+            let from = config.current_state(&stored);
+            let to = match &task.workflow_state {
+                Some(state) if *state != from => Some(state.clone()),
+                _ if task.status != stored.status => Some(
+                    config
+                        .state_machine
+                        .state_for_status(&from, task.status)
+                        .ok_or_else(|| anyhow!("No transition from {} to {:?}", from, task.status))?
+                        .to_string(),
+                ),
+                _ => None,
+            };
+            task.workflow_state = Some(from);
+            if let Some(to) = to {
+                match self
+                    .run_transition(&config, task.clone(), &to, None)
+                    .await?
+                {
+                    TransitionOutcome::Moved(task) => return Ok(*task),
+                    TransitionOutcome::AwaitingApproval(_) => task.status = stored.status,
+                }
+            }
+        }
+        self.repository.tasks.update(&task).await?;
+        self.recompute_related(task.id).await?;
         Ok(task)
     }
 
-    pub async fn update(&self, task: Task) -> Result<Task> {
+    /// Move a task with a configuration into `to_state` on behalf of the
+    /// resource `actor`. Fails if the state machine has no such transition
+    /// or its conditions aren't met. Transitions that need approval open
+    /// requests for the approvers instead; otherwise the transition's
+    /// effects and the new state's auto actions run.
+    pub async fn transition(
+        &self,
+        task_id: Uuid,
+        to_state: &str,
+        actor: Option<Uuid>,
+    ) -> Result<TransitionOutcome> {
+        let task = self
+            .repository
+            .tasks
+            .get(task_id)
+            .await?
+            .ok_or_else(|| anyhow!("Task not found"))?;
+        let config = self
+            .configuration(&task)
+            .await?
+            .ok_or_else(|| anyhow!("Task '{}' has no workflow configuration", task.title))?;
+        self.run_transition(&config, task, to_state, actor).await
+    }
+
+    async fn run_transition(
+        &self,
+        config: &TaskConfiguration,
+        mut task: Task,
+        to_state: &str,
+        actor: Option<Uuid>,
+    ) -> Result<TransitionOutcome> {
+        let from_state = config.current_state(&task);
+        let requests: Vec<ApprovalRequest> = self
+            .repository
+            .approvals
+            .list_for_task(task.id)
+            .await?
+            .into_iter()
+            .filter(|r| r.from_state == from_state && r.to_state == to_state)
+            .collect();
+        let user_role = match actor {
+            Some(id) => self.repository.resources.get(id).await?.map(|r| r.role),
+            None => None,
+        };
+        let context = TransitionContext {
+            metadata: task.metadata.clone(),
+            all_subtasks_complete: self.all_subtasks_complete(&task).await?,
+            user_role,
+            script: self.script_context(config, &task).await?,
+            approved_roles: requests
+                .iter()
+                .filter(|r| r.status == ApprovalStatus::Approved)
+                .map(|r| r.role.clone())
+                .collect(),
+        };
+
+        let missing: Vec<(String, usize)> = config
+            .required_approvals(&from_state, to_state)
+            .into_iter()
+            .filter(|(role, _)| !context.approved_roles.contains(role))
+            .collect();
+        if !missing.is_empty() {
+            // Everything else must hold before anyone is asked to approve
+            let mut assumed = context.clone();
+            assumed
+                .approved_roles
+                .extend(missing.iter().map(|(role, _)| role.clone()));
+            config
+                .can_transition(&from_state, to_state, &assumed)
+                .map_err(|e| anyhow!(e))?;
+
+            let mut pending = Vec::new();
+            for (role, quorum) in missing {
+                let open = requests
+                    .iter()
+                    .find(|r| r.role == role && r.status == ApprovalStatus::Pending);
+                pending.push(match open {
+                    Some(request) => request.clone(),
+                    None => {
+                        self.request_approval(config, &task, to_state, role, quorum, actor)
+                            .await?
+                    }
+                });
+            }
+            return Ok(TransitionOutcome::AwaitingApproval(pending));
+        }
+
+        let entry = config
+            .apply_transition(&mut task, to_state, &context)
+            .map_err(|e| anyhow!(e))?;
+        self.assign_by_role(&mut task, &entry).await?;
+        self.compute_formulas(config, &mut task).await?;
         self.repository.tasks.update(&task).await?;
+        self.save_entry(&task, entry).await?;
+        self.settle_approvals(task.id, &from_state, to_state)
+            .await?;
+        self.recompute_related(task.id).await?;
+        Ok(TransitionOutcome::Moved(Box::new(task)))
+    }
+
+    /// Open a request for the resources holding `role` to approve moving
+    /// `task` into `to_state`, and let them know.
+    async fn request_approval(
+        &self,
+        config: &TaskConfiguration,
+        task: &Task,
+        to_state: &str,
+        role: String,
+        quorum: usize,
+        actor: Option<Uuid>,
+    ) -> Result<ApprovalRequest> {
+        let approvers: Vec<Uuid> = self
+            .repository
+            .resources
+            .list_all()
+            .await?
+            .into_iter()
+            .filter(|r| r.role.eq_ignore_ascii_case(&role))
+            .map(|r| r.id)
+            .collect();
+        let (request, event) = ApprovalRequest::new(
+            task.id,
+            config.current_state(task),
+            to_state.to_string(),
+            role,
+            quorum,
+            approvers,
+            actor,
+        )?;
+        self.repository.approvals.create(&request).await?;
+        self.repository.approvals.add_event(&event).await?;
+
+        let state = config
+            .state_machine
+            .states
+            .get(to_state)
+            .map_or(to_state, |s| s.display_name.as_str());
+        for approver in &request.approvers {
+            let message = format!(
+                "'{}' is awaiting your approval to move to {}",
+                task.title, state
+            );
+            let notification = Notification::new(Some(*approver), Some(task.id), message);
+            self.repository.notifications.create(&notification).await?;
+        }
+        Ok(request)
+    }
+
+    /// Close the requests a task leaving `from_state` settles: approvals
+    /// for the transition taken are used up, and those still pending
+    /// there are withdrawn.
+    async fn settle_approvals(
+        &self,
+        task_id: Uuid,
+        from_state: &str,
+        to_state: &str,
+    ) -> Result<()> {
+        for mut request in self.repository.approvals.list_for_task(task_id).await? {
+            if request.from_state != from_state {
+                continue;
+            }
+            let event = match request.status {
+                ApprovalStatus::Approved if request.to_state == to_state => request.complete(),
+                ApprovalStatus::Pending | ApprovalStatus::Approved => {
+                    request.cancel(format!("The task moved to {}", to_state))
+                }
+                _ => continue,
+            };
+            self.repository.approvals.update(&request).await?;
+            self.repository.approvals.add_event(&event).await?;
+        }
+        Ok(())
+    }
+
+    /// Save a new task, entering its configuration's initial state first.
+    async fn save_new(&self, mut task: Task) -> Result<Task> {
+        let mut entry = StateEntry::default();
+        if let Some(config) = self.configuration(&task).await? {
+            let context = self.script_context(&config, &task).await?;
+            let errors = config.apply_defaults(&mut task, &context);
+            if !errors.is_empty() {
+                bail!("{}", errors.join("; "));
+            }
+            self.compute_formulas(&config, &mut task).await?;
+            self.validate(&config, &task).await?;
+            if task.workflow_state.is_none() {
+                entry = config.enter_initial_state(&mut task);
+                self.assign_by_role(&mut task, &entry).await?;
+            }
+        }
+        self.repository.tasks.create(&task).await?;
+        self.save_entry(&task, entry).await?;
         Ok(task)
     }
 
+    /// Check a configured task's metadata against its configuration,
+    /// custom validation scripts included.
+    async fn validate(&self, config: &TaskConfiguration, task: &Task) -> Result<()> {
+        let context = self.script_context(config, task).await?;
+        config
+            .validate_task(task, &context)
+            .map_err(|errors| anyhow!("{}", errors.join("; ")))
+    }
+
+    /// Recompute a configured task's formula fields.
+    async fn compute_formulas(&self, config: &TaskConfiguration, task: &mut Task) -> Result<()> {
+        let mut context = self.script_context(config, task).await?;
+        let errors = config.compute_formulas(task, &mut context);
+        if !errors.is_empty() {
+            bail!("{}", errors.join("; "));
+        }
+        Ok(())
+    }
+
+    /// Recompute the formulas of tasks whose relation fields link to the
+    /// task or goal `id`, then of the tasks related to those, and so on.
+    /// Call after `id` changes or is deleted.
+    pub async fn recompute_related(&self, id: Uuid) -> Result<()> {
+        let configs: HashMap<Uuid, TaskConfiguration> = self
+            .repository
+            .task_configs
+            .list_all()
+            .await?
+            .into_iter()
+            .filter(|config| {
+                let mut fields = config.metadata_schema.fields.values();
+                fields.any(|field| field.field_type == FieldType::Relation)
+            })
+            .map(|config| (config.id, config))
+            .collect();
+        if configs.is_empty() {
+            return Ok(());
+        }
+
+        let mut changed = VecDeque::from([id]);
+        let mut visited = HashSet::from([id]);
+        while let Some(id) = changed.pop_front() {
+            let linking = self
+                .repository
+                .task_metadata
+                .tasks_listing(&id.to_string())
+                .await?;
+            for task_id in linking {
+                let Some(task) = self.repository.tasks.get(task_id).await? else {
+                    continue;
+                };
+                let Some(config) = task.configuration_id.and_then(|c| configs.get(&c)) else {
+                    continue;
+                };
+                if !config
+                    .relations(&task)
+                    .iter()
+                    .any(|(_, _, ids)| ids.contains(&id))
+                {
+                    continue;
+                }
+
+                let mut updated = task.clone();
+                self.compute_formulas(config, &mut updated).await?;
+                if updated.metadata != task.metadata {
+                    self.repository.tasks.update(&updated).await?;
+                    if visited.insert(updated.id) {
+                        changed.push_back(updated.id);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The tasks and goals each of the task's relation fields links to.
+    /// Ids that no longer exist are left out.
+    async fn related_items(
+        &self,
+        config: &TaskConfiguration,
+        task: &Task,
+    ) -> Result<HashMap<String, Vec<RelatedItem>>> {
+        let mut relations = HashMap::new();
+        for (field, target, ids) in config.relations(task) {
+            let mut items = Vec::new();
+            for id in ids {
+                let item = match target {
+                    RelationTarget::Tasks => self
+                        .repository
+                        .tasks
+                        .get(id)
+                        .await?
+                        .as_ref()
+                        .map(RelatedItem::from),
+                    RelationTarget::Goals => self
+                        .repository
+                        .goals
+                        .get(id)
+                        .await?
+                        .as_ref()
+                        .map(RelatedItem::from),
+                };
+                items.extend(item);
+            }
+            relations.insert(field, items);
+        }
+        Ok(relations)
+    }
+
+    /// The task, the tasks it depends on and its related items, for scripts.
+    async fn script_context(
+        &self,
+        config: &TaskConfiguration,
+        task: &Task,
+    ) -> Result<ScriptContext> {
+        let mut dependencies = Vec::new();
+        let prerequisites = self
+            .repository
+            .dependencies
+            .get_dependents_for_task(task.id)
+            .await?;
+        for dependency in prerequisites {
+            if let Some(other) = self.repository.tasks.get(dependency.to_task_id).await? {
+                dependencies.push((dependency.dependency_type, other));
+            }
+        }
+        let dependencies: Vec<_> = dependencies
+            .iter()
+            .map(|(kind, other)| (*kind, other))
+            .collect();
+        let relations = self.related_items(config, task).await?;
+        Ok(ScriptContext::for_task(task, &dependencies).with_relations(&relations))
+    }
+
+    async fn configuration(&self, task: &Task) -> Result<Option<TaskConfiguration>> {
+        match task.configuration_id {
+            Some(id) => self.repository.task_configs.get(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the task's checklist is ticked off and its child tasks are
+    /// done or cancelled.
+    async fn all_subtasks_complete(&self, task: &Task) -> Result<bool> {
+        if !task.subtasks.iter().all(|s| s.completed) {
+            return Ok(false);
+        }
+        let tasks = self.repository.tasks.list(Default::default()).await?;
+        Ok(tasks
+            .iter()
+            .filter(|t| t.parent_task_id == Some(task.id))
+            .all(|t| matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled)))
+    }
+
+    /// Assign the first resource with the role an auto action asked for.
+    async fn assign_by_role(&self, task: &mut Task, entry: &StateEntry) -> Result<()> {
+        let Some(role) = &entry.assign_role else {
+            return Ok(());
+        };
+        let resources = self.repository.resources.list_all().await?;
+        if let Some(resource) = resources.iter().find(|r| r.role.eq_ignore_ascii_case(role)) {
+            task.assigned_resource_id = Some(resource.id);
+            task.assignee = Some(resource.name.clone());
+        }
+        Ok(())
+    }
+
+    /// Save the child tasks and notifications entering a state produced.
+    async fn save_entry(&self, task: &Task, entry: StateEntry) -> Result<()> {
+        for child in entry.subtasks {
+            self.repository.tasks.create(&child).await?;
+        }
+        for message in entry.notifications {
+            let notification = Notification::new(task.assigned_resource_id, Some(task.id), message);
+            self.repository.notifications.create(&notification).await?;
+        }
+        for url in entry.webhooks {
+            // A receiver being down doesn't undo the transition
+            if let Err(e) = Self::trigger_webhook(&url, task).await {
+                tracing::warn!("Webhook {} for task {} failed: {}", url, task.id, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// POST the task's new state to `url` as JSON.
+    async fn trigger_webhook(url: &str, task: &Task) -> Result<()> {
+        let payload = serde_json::json!({
+            "event": "task.transition",
+            "task_id": task.id,
+            "title": task.title,
+            "state": task.workflow_state,
+            "status": format!("{:?}", task.status),
+        });
+        reqwest::Client::new()
+            .post(url)
+            .timeout(std::time::Duration::from_secs(10))
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
     pub async fn get(&self, id: Uuid) -> Result<Option<Task>> {
         self.repository.tasks.get(id).await
     }
 
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        self.repository.tasks.delete(id).await
+        let deleted = self.repository.tasks.delete(id).await?;
+        if deleted {
+            self.recompute_related(id).await?;
+        }
+        Ok(deleted)
     }
 
     pub async fn list_all(&self) -> Result<Vec<Task>> {
@@ -54,7 +723,7 @@ mod tests {
         let service = setup().await;
         let task = Task::new("Test Task".to_string(), "Description".to_string());
 
-        let created = service.create(task.clone()).await.unwrap();
+        let created = service.create(task.clone()).await.unwrap().task;
         assert_eq!(created.title, task.title);
         assert_eq!(created.description, task.description);
     }
@@ -63,7 +732,7 @@ mod tests {
     async fn test_get_task() {
         let service = setup().await;
         let task = Task::new("Test Task".to_string(), "Description".to_string());
-        let created = service.create(task.clone()).await.unwrap();
+        let created = service.create(task.clone()).await.unwrap().task;
 
         let retrieved = service.get(created.id).await.unwrap();
         assert!(retrieved.is_some());
@@ -74,7 +743,7 @@ mod tests {
     async fn test_update_task() {
         let service = setup().await;
         let mut task = Task::new("Original".to_string(), "Original desc".to_string());
-        let created = service.create(task.clone()).await.unwrap();
+        let created = service.create(task.clone()).await.unwrap().task;
 
         task = created;
         task.title = "Updated".to_string();
@@ -94,7 +763,7 @@ mod tests {
     async fn test_delete_task() {
         let service = setup().await;
         let task = Task::new("To Delete".to_string(), "".to_string());
-        let created = service.create(task).await.unwrap();
+        let created = service.create(task).await.unwrap().task;
 
         let deleted = service.delete(created.id).await.unwrap();
         assert!(deleted);
@@ -116,4 +785,623 @@ mod tests {
         let tasks = service.list_all().await.unwrap();
         assert_eq!(tasks.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_create_and_import_report_duplicates() {
+        let service = setup().await;
+        let first = service
+            .create(Task::new("Export tasks to CSV".to_string(), String::new()))
+            .await
+            .unwrap();
+        assert!(first.duplicates.is_empty());
+
+        let second = service
+            .create(Task::new("Export tasks to CSV.".to_string(), String::new()))
+            .await
+            .unwrap();
+        assert_eq!(second.duplicates.len(), 1);
+        assert_eq!(second.duplicates[0].task.id, first.task.id);
+        // Both are saved; merging is up to the caller
+        assert_eq!(service.list_all().await.unwrap().len(), 2);
+
+        let imported = service
+            .import(vec![
+                Task::new("Dark mode".to_string(), String::new()),
+                Task::new("Dark Mode!".to_string(), String::new()),
+            ])
+            .await
+            .unwrap();
+        assert!(imported[0].duplicates.is_empty());
+        assert_eq!(imported[1].duplicates[0].task.id, imported[0].task.id);
+    }
+
+    #[tokio::test]
+    async fn test_merge_combines_tasks() {
+        use crate::domain::comment::{Comment, EntityType};
+        use crate::domain::dependency::{Dependency, DependencyType};
+        use crate::domain::goal::Goal;
+
+        let service = setup().await;
+        let repository = service.repository.clone();
+
+        let mut keep = Task::new("Export to CSV".to_string(), String::new());
+        keep.tags.insert("export".to_string());
+        keep.metadata.insert("owner".to_string(), "ana".to_string());
+        keep.add_subtask("Write exporter".to_string());
+        let mut duplicate = Task::new("Export CSV".to_string(), "Download as CSV".to_string());
+        duplicate.tags.insert("csv".to_string());
+        duplicate
+            .metadata
+            .insert("owner".to_string(), "bo".to_string());
+        duplicate
+            .metadata
+            .insert("source".to_string(), "list view".to_string());
+        duplicate.add_subtask("write exporter".to_string());
+        duplicate.add_subtask("Add button".to_string());
+        let before = Task::new("Pick CSV library".to_string(), String::new());
+        let mut child = Task::new("Escape quotes".to_string(), String::new());
+        child.parent_task_id = Some(duplicate.id);
+
+        let mut goal = Goal::new("Reporting".to_string(), String::new());
+        repository.goals.create(&goal).await.unwrap();
+        for task in [&keep, &duplicate, &before, &child] {
+            repository.tasks.create(task).await.unwrap();
+        }
+        goal.add_task(duplicate.id);
+        repository.goals.update(&goal).await.unwrap();
+        let comment = Comment::new(
+            duplicate.id,
+            EntityType::Task,
+            "ana".to_string(),
+            "Needs UTF-8".to_string(),
+        );
+        repository.comments.create(&comment).await.unwrap();
+        for (from, to) in [(before.id, duplicate.id), (keep.id, duplicate.id)] {
+            repository
+                .dependencies
+                .create(&Dependency::new(from, to, DependencyType::FinishToStart))
+                .await
+                .unwrap();
+        }
+
+        let merged = service.merge(keep.id, duplicate.id).await.unwrap();
+        assert!(service.get(duplicate.id).await.unwrap().is_none());
+        let merged_stored = service.get(keep.id).await.unwrap().unwrap();
+        assert_eq!(merged_stored.subtasks.len(), 2);
+        assert_eq!(merged.tags.len(), 2);
+        assert_eq!(merged.metadata["owner"], "ana");
+        assert_eq!(merged.metadata["source"], "list view");
+        assert_eq!(merged.description, "Download as CSV");
+
+        let comments = repository.comments.list_for_entity(keep.id).await.unwrap();
+        assert_eq!(comments.len(), 1);
+        let dependencies = repository.dependencies.list_all().await.unwrap();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(
+            (dependencies[0].from_task_id, dependencies[0].to_task_id),
+            (before.id, keep.id)
+        );
+        let goal = repository.goals.get(goal.id).await.unwrap().unwrap();
+        assert!(goal.task_ids.contains(&keep.id) && !goal.task_ids.contains(&duplicate.id));
+        let child = service.get(child.id).await.unwrap().unwrap();
+        assert_eq!(child.parent_task_id, Some(keep.id));
+
+        assert!(service.merge(keep.id, keep.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_keeps_logged_time_approvals_and_links() {
+        use crate::domain::approval::ApprovalRequest;
+        use crate::domain::metadata_value::MetadataValue;
+        use crate::domain::task_config::{FieldType, MetadataFieldConfig, ValidationRule};
+        use crate::services::time_tracking_service::TimeTrackingService;
+
+        let service = setup().await;
+        let repository = service.repository.clone();
+        let field = |name: &str, field_type: FieldType| MetadataFieldConfig {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            field_type,
+            required: false,
+            options: vec![],
+            default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
+            validation_rules: vec![],
+            help_text: String::new(),
+            show_in_list: true,
+            show_in_card: false,
+            sortable: true,
+            searchable: false,
+        };
+        let mut config = TaskConfiguration::new("Tracked".to_string());
+        // The kept task takes over a unique value while the duplicate still exists
+        let mut points = field("points", FieldType::Number);
+        points.validation_rules.push(ValidationRule::UniqueValue);
+        config.add_metadata_field(points);
+        config.add_metadata_field(field("parts", FieldType::Relation));
+        repository.task_configs.create(&config).await.unwrap();
+
+        let mut keep = Task::new("Export to CSV".to_string(), String::new());
+        keep.configuration_id = Some(config.id);
+        let keep = service.create(keep).await.unwrap().task;
+        let mut duplicate = Task::new("Export CSV".to_string(), String::new());
+        duplicate.configuration_id = Some(config.id);
+        duplicate
+            .metadata
+            .insert("points".to_string(), "5".to_string());
+        let duplicate = service.create(duplicate).await.unwrap().task;
+        let mut epic = Task::new("Reporting".to_string(), String::new());
+        epic.configuration_id = Some(config.id);
+        epic.metadata.insert(
+            "parts".to_string(),
+            format!("{}, {}", keep.id, duplicate.id),
+        );
+        let epic = service.create(epic).await.unwrap().task;
+
+        let timer = TimeTrackingService::new(repository.clone());
+        let start = chrono::Utc::now() - chrono::Duration::hours(6);
+        timer
+            .log_time(
+                keep.id,
+                None,
+                start,
+                start + chrono::Duration::hours(1),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        timer
+            .log_time(
+                duplicate.id,
+                None,
+                start,
+                start + chrono::Duration::hours(2),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        timer
+            .start_tracking(duplicate.id, "Still going".to_string())
+            .await
+            .unwrap();
+        let (request, event) = ApprovalRequest::new(
+            duplicate.id,
+            "in_progress".to_string(),
+            "done".to_string(),
+            "Reviewer".to_string(),
+            1,
+            vec![Uuid::new_v4()],
+            None,
+        )
+        .unwrap();
+        repository.approvals.create(&request).await.unwrap();
+        repository.approvals.add_event(&event).await.unwrap();
+
+        let merged = service.merge(keep.id, duplicate.id).await.unwrap();
+        let entries = repository
+            .time_entries
+            .list_for_task(keep.id)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| !e.is_running()));
+        assert!((merged.actual_hours.unwrap() - 3.0).abs() < 0.01);
+        let stored = service.get(keep.id).await.unwrap().unwrap();
+        assert_eq!(stored.actual_hours, merged.actual_hours);
+
+        let requests = repository.approvals.list_for_task(keep.id).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            repository
+                .approvals
+                .list_events(request.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let typed = repository.task_metadata.get(keep.id).await.unwrap();
+        assert_eq!(typed["points"], MetadataValue::Number(5.0));
+        let epic = service.get(epic.id).await.unwrap().unwrap();
+        assert_eq!(epic.metadata["parts"], keep.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_configured_tasks_follow_their_state_machine() {
+        use crate::domain::resource::Resource;
+        use crate::domain::task_config::{
+            AutoAction, SubtaskTemplate, TransitionEffect, create_software_development_config,
+        };
+
+        let service = setup().await;
+        let hooks = crate::services::hosting::FakeHostingServer::start()
+            .await
+            .unwrap();
+        let reviewer = Resource::new("Rosa".to_string(), "Reviewer".to_string(), 40.0);
+        service
+            .repository
+            .resources
+            .create(&reviewer)
+            .await
+            .unwrap();
+
+        let mut config = create_software_development_config();
+        let review = config.state_machine.states.get_mut("review").unwrap();
+        review.auto_actions.push(AutoAction::AssignToResource {
+            resource_role: "reviewer".to_string(),
+        });
+        let submit = config
+            .state_machine
+            .transitions
+            .iter_mut()
+            .find(|t| t.to_state == "review")
+            .unwrap();
+        submit.effects.push(TransitionEffect::SetMetadataField {
+            field: "sprint".to_string(),
+            value: "review-queue".to_string(),
+        });
+        submit.effects.push(TransitionEffect::CreateSubtask {
+            template: SubtaskTemplate {
+                title: "Review '{title}'".to_string(),
+                description: String::new(),
+                assigned_to: Some("Rosa".to_string()),
+            },
+        });
+        submit.effects.push(TransitionEffect::TriggerWebhook {
+            url: format!("{}/hooks/review", hooks.url()),
+        });
+        service
+            .repository
+            .task_configs
+            .create(&config)
+            .await
+            .unwrap();
+
+        let mut task = Task::new("Login form".to_string(), String::new());
+        task.configuration_id = Some(config.id);
+        let task = service.create(task).await.unwrap().task;
+        assert_eq!(task.workflow_state.as_deref(), Some("todo"));
+
+        // Status edits must follow a transition
+        let mut skipping = task.clone();
+        skipping.status = TaskStatus::Done;
+        assert!(service.update(skipping).await.is_err());
+        let mut started = task.clone();
+        started.status = TaskStatus::InProgress;
+        let started = service.update(started).await.unwrap();
+        assert_eq!(started.workflow_state.as_deref(), Some("in_progress"));
+
+        // Submitting needs a pull request
+        assert!(service.transition(task.id, "review", None).await.is_err());
+        let mut with_pr = started.clone();
+        with_pr
+            .metadata
+            .insert("pr_url".to_string(), "https://example.com/pr/1".to_string());
+        service.update(with_pr).await.unwrap();
+
+        let reviewed = service
+            .transition(task.id, "review", None)
+            .await
+            .unwrap()
+            .moved()
+            .unwrap();
+        assert_eq!(reviewed.status, TaskStatus::Review);
+        assert_eq!(reviewed.metadata["sprint"], "review-queue");
+        assert!(reviewed.tags.contains("needs-review"));
+        assert_eq!(reviewed.assigned_resource_id, Some(reviewer.id));
+        assert_eq!(service.get(task.id).await.unwrap().unwrap(), reviewed);
+
+        let children: Vec<Task> = service
+            .list_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.parent_task_id == Some(task.id))
+            .collect();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].title, "Review 'Login form'");
+
+        let inbox = service
+            .repository
+            .notifications
+            .list_for_resource(reviewer.id, true)
+            .await
+            .unwrap();
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].message, "Task 'Login form' is ready for review");
+
+        let delivered: Vec<_> = hooks
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == "POST" && r.path == "/hooks/review")
+            .collect();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].body["task_id"], task.id.to_string());
+        assert_eq!(delivered[0].body["state"], "review");
+
+        let done = service
+            .transition(task.id, "done", None)
+            .await
+            .unwrap()
+            .moved()
+            .unwrap();
+        assert_eq!(done.status, TaskStatus::Done);
+        assert!(done.completed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_validates_only_changed_fields() {
+        use crate::domain::task_config::{FieldType, MetadataFieldConfig, TaskConfiguration};
+
+        let service = setup().await;
+        let mut config = TaskConfiguration::new("Estimated".to_string());
+        service
+            .repository
+            .task_configs
+            .create(&config)
+            .await
+            .unwrap();
+        let mut task = Task::new("Old task".to_string(), String::new());
+        task.configuration_id = Some(config.id);
+        let task = service.create(task).await.unwrap().task;
+
+        // A required field added after the task was created
+        config.add_metadata_field(MetadataFieldConfig {
+            name: "points".to_string(),
+            display_name: "Points".to_string(),
+            field_type: FieldType::Number,
+            required: true,
+            options: vec![],
+            default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
+            validation_rules: vec![],
+            help_text: String::new(),
+            show_in_list: false,
+            show_in_card: false,
+            sortable: true,
+            searchable: false,
+        });
+        service
+            .repository
+            .task_configs
+            .update(&config)
+            .await
+            .unwrap();
+
+        let mut started = task.clone();
+        started.status = TaskStatus::InProgress;
+        let started = service.update(started).await.unwrap();
+        assert_eq!(started.status, TaskStatus::InProgress);
+
+        let mut edited = started.clone();
+        edited
+            .metadata
+            .insert("points".to_string(), "lots".to_string());
+        let error = service
+            .update(edited.clone())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Points must be a number"), "{}", error);
+        edited
+            .metadata
+            .insert("points".to_string(), "3".to_string());
+        assert!(service.update(edited).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_scripts_validate_fields_guard_transitions_and_compute_defaults() {
+        use crate::domain::dependency::DependencyType;
+        use crate::domain::task_config::{
+            FieldType, MetadataFieldConfig, TaskConfiguration, TransitionCondition, ValidationRule,
+        };
+
+        let service = setup().await;
+        let mut config = TaskConfiguration::new("Scripted".to_string());
+        config.add_metadata_field(MetadataFieldConfig {
+            name: "budget".to_string(),
+            display_name: "Budget".to_string(),
+            field_type: FieldType::Currency,
+            required: true,
+            options: vec![],
+            default_value: None,
+            default_script: Some("task.estimated_hours * 100.0".to_string()),
+            formula: None,
+            relation: None,
+            validation_rules: vec![ValidationRule::CustomValidation(
+                r#"if parse_float(value) > 1000.0 { "Budget over 1000 needs a purchase order" }"#
+                    .to_string(),
+            )],
+            help_text: String::new(),
+            show_in_list: true,
+            show_in_card: true,
+            sortable: true,
+            searchable: false,
+        });
+        config
+            .state_machine
+            .transitions
+            .iter_mut()
+            .find(|t| t.to_state == "in_progress")
+            .unwrap()
+            .conditions
+            .push(TransitionCondition::CustomValidation {
+                script: r#"dependencies.all(|d| d.status == "Done")"#.to_string(),
+            });
+        service
+            .repository
+            .task_configs
+            .create(&config)
+            .await
+            .unwrap();
+
+        let mut too_big = Task::new("Migrate".to_string(), String::new());
+        too_big.configuration_id = Some(config.id);
+        too_big.estimated_hours = Some(12.0);
+        let error = service.create(too_big).await.unwrap_err().to_string();
+        assert!(
+            error.contains("Budget over 1000 needs a purchase order"),
+            "{}",
+            error
+        );
+
+        let mut task = Task::new("Migrate".to_string(), String::new());
+        task.configuration_id = Some(config.id);
+        task.estimated_hours = Some(4.0);
+        let task = service.create(task).await.unwrap().task;
+        assert_eq!(task.metadata["budget"], "400.0");
+
+        let blocker = service
+            .create(Task::new("Back up".to_string(), String::new()))
+            .await
+            .unwrap()
+            .task;
+        service
+            .repository
+            .dependencies
+            .create(&Dependency::new(
+                task.id,
+                blocker.id,
+                DependencyType::FinishToStart,
+            ))
+            .await
+            .unwrap();
+        let blocked = service.transition(task.id, "in_progress", None).await;
+        assert!(
+            blocked
+                .unwrap_err()
+                .to_string()
+                .contains("Custom validation failed")
+        );
+
+        let mut done = blocker.clone();
+        done.status = TaskStatus::Done;
+        service.update(done).await.unwrap();
+        assert!(
+            service
+                .transition(task.id, "in_progress", None)
+                .await
+                .unwrap()
+                .moved()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_formulas_roll_up_related_tasks_and_goals() {
+        use crate::domain::goal::Goal;
+        use crate::domain::task_config::{FieldType, MetadataFieldConfig, RelationTarget};
+        use crate::services::GoalService;
+
+        let service = setup().await;
+        let field = |name: &str, field_type: FieldType| MetadataFieldConfig {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            field_type,
+            required: false,
+            options: vec![],
+            default_value: None,
+            default_script: None,
+            formula: None,
+            relation: None,
+            validation_rules: vec![],
+            help_text: String::new(),
+            show_in_list: true,
+            show_in_card: false,
+            sortable: true,
+            searchable: false,
+        };
+        let mut config = TaskConfiguration::new("Epic".to_string());
+        config.add_metadata_field(field("parts", FieldType::Relation));
+        config.add_metadata_field(MetadataFieldConfig {
+            relation: Some(RelationTarget::Goals),
+            ..field("goal", FieldType::Relation)
+        });
+        config.add_metadata_field(MetadataFieldConfig {
+            formula: Some("sum_estimates(relations.parts)".to_string()),
+            ..field("estimate", FieldType::Formula)
+        });
+        config.add_metadata_field(MetadataFieldConfig {
+            formula: Some("count_done(relations.parts)".to_string()),
+            ..field("done", FieldType::Formula)
+        });
+        config.add_metadata_field(MetadataFieldConfig {
+            formula: Some("max_due_date(relations.goal)".to_string()),
+            ..field("deadline", FieldType::Formula)
+        });
+        service
+            .repository
+            .task_configs
+            .create(&config)
+            .await
+            .unwrap();
+
+        let mut part = Task::new("Schema".to_string(), String::new());
+        part.estimated_hours = Some(2.0);
+        let part = service.create(part).await.unwrap().task;
+        let goal = GoalService::new(service.repository.clone())
+            .create(Goal::new("Launch".to_string(), String::new()))
+            .await
+            .unwrap();
+
+        let mut epic = Task::new("Storage".to_string(), String::new());
+        epic.configuration_id = Some(config.id);
+        epic.metadata
+            .insert("parts".to_string(), part.id.to_string());
+        epic.metadata
+            .insert("goal".to_string(), goal.id.to_string());
+        let epic = service.create(epic).await.unwrap().task;
+        assert_eq!(epic.metadata["estimate"], "2.0");
+        assert_eq!(epic.metadata["done"], "0");
+        assert!(!epic.metadata.contains_key("deadline"));
+
+        // An epic of epics rolls up the inner epic's estimate once it changes
+        let mut program = Task::new("Platform".to_string(), String::new());
+        program.configuration_id = Some(config.id);
+        program
+            .metadata
+            .insert("parts".to_string(), epic.id.to_string());
+        let program = service.create(program).await.unwrap().task;
+        assert_eq!(program.metadata["estimate"], "0.0");
+
+        let mut part = part;
+        part.estimated_hours = Some(6.0);
+        part.status = TaskStatus::Done;
+        service.update(part.clone()).await.unwrap();
+        let epic = service.get(epic.id).await.unwrap().unwrap();
+        assert_eq!(epic.metadata["estimate"], "6.0");
+        assert_eq!(epic.metadata["done"], "1");
+
+        let mut with_estimate = epic.clone();
+        with_estimate.estimated_hours = Some(10.0);
+        service.update(with_estimate).await.unwrap();
+        let program = service.get(program.id).await.unwrap().unwrap();
+        assert_eq!(program.metadata["estimate"], "10.0");
+
+        let (mut goal, goal_id) = (goal.clone(), goal.id);
+        let target = chrono::Utc::now() + chrono::Duration::days(30);
+        goal.target_date = Some(target);
+        GoalService::new(service.repository.clone())
+            .update(goal)
+            .await
+            .unwrap();
+        let epic = service.get(epic.id).await.unwrap().unwrap();
+        assert_eq!(epic.metadata["deadline"], target.to_rfc3339());
+
+        service.delete(part.id).await.unwrap();
+        let epic = service.get(epic.id).await.unwrap().unwrap();
+        assert_eq!(epic.metadata["estimate"], "0.0");
+
+        GoalService::new(service.repository.clone())
+            .delete(goal_id)
+            .await
+            .unwrap();
+        let epic = service.get(epic.id).await.unwrap().unwrap();
+        assert!(!epic.metadata.contains_key("deadline"));
+    }
 }
@@ -1,36 +1,45 @@
 use crate::repository::Repository;
 use crate::domain::task::TaskStatus;
+use crate::domain::time_entry::{Timesheet, actual_hours};
+use crate::services::task_service::TaskService;
+pub use crate::domain::time_entry::TimeEntry;
 use std::sync::Arc;
 use uuid::Uuid;
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, NaiveDate, Utc, Duration};
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::task::JoinHandle;
 
-#[derive(Debug, Clone)]
-pub struct TimeEntry {
-    pub id: Uuid,
-    pub task_id: Uuid,
-    pub start_time: DateTime<Utc>,
-    pub end_time: Option<DateTime<Utc>>,
-    pub description: String,
-    pub created_at: DateTime<Utc>,
-}
+/// How long without activity before running timers are stopped
+pub const DEFAULT_IDLE_TIMEOUT_MINUTES: i64 = 15;
 
 pub struct TimeTrackingService {
     repository: Arc<Repository>,
-    // In-memory storage for time entries (in production, this would be in the database)
-    time_entries: std::sync::Mutex<HashMap<Uuid, TimeEntry>>,
-    // Track active time entries per task
-    active_entries: std::sync::Mutex<HashMap<Uuid, Uuid>>, // task_id -> entry_id
+    // Running segments by task, mirrored from the database so views can
+    // check them while rendering
+    active_entries: Mutex<HashMap<Uuid, TimeEntry>>,
+    // Last sign of the user at the keyboard, for idle detection
+    last_activity: Mutex<DateTime<Utc>>,
 }
 
 impl TimeTrackingService {
     pub fn new(repository: Arc<Repository>) -> Self {
         Self {
             repository,
-            time_entries: std::sync::Mutex::new(HashMap::new()),
-            active_entries: std::sync::Mutex::new(HashMap::new()),
+            active_entries: Mutex::new(HashMap::new()),
+            last_activity: Mutex::new(Utc::now()),
+        }
+    }
+    
+    /// Pick up timers left running by an earlier session. Returns how many.
+    pub async fn restore_active(&self) -> Result<usize> {
+        let running = self.repository.time_entries.list_running().await?;
+        let mut active = self.active_entries.lock().unwrap();
+        for entry in running {
+            active.insert(entry.task_id, entry);
         }
+        Ok(active.len())
     }
     
     /// Start tracking time for a task
@@ -39,111 +48,102 @@ impl TimeTrackingService {
         let task = self.repository.tasks.get(task_id).await?
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
         
-        // Check if already tracking this task
-        let active = self.active_entries.lock().unwrap();
-        if active.contains_key(&task_id) {
-            return Err(anyhow::anyhow!("Already tracking time for this task"));
-        }
-        drop(active);
-        
-        // Create new time entry
-        let entry = TimeEntry {
-            id: Uuid::new_v4(),
-            task_id,
-            start_time: Utc::now(),
-            end_time: None,
-            description,
-            created_at: Utc::now(),
-        };
-        
-        let entry_id = entry.id;
-        
-        // Store entry
-        self.time_entries.lock().unwrap().insert(entry_id, entry);
-        self.active_entries.lock().unwrap().insert(task_id, entry_id);
+        let entry_id = self.start_segment(task_id, task.assigned_resource_id, description).await?;
         
-        // Update task status to InProgress if it was Todo
+        // Update task status to InProgress if it was Todo and its workflow allows it
         if task.status == TaskStatus::Todo {
             let mut updated_task = task;
             updated_task.status = TaskStatus::InProgress;
             updated_task.updated_at = Utc::now();
-            self.repository.tasks.update(&updated_task).await?;
+            if let Err(e) = TaskService::new(self.repository.clone()).update(updated_task).await {
+                tracing::warn!("Tracking {} without starting it: {}", task_id, e);
+            }
         }
         
         Ok(entry_id)
     }
     
-    /// Stop tracking time for a task
+    /// Stop tracking time for a task, rolling its segments into its actual hours
     pub async fn stop_tracking(&self, task_id: Uuid) -> Result<Duration> {
-        // Find active entry
-        let entry_id = {
-            let mut active = self.active_entries.lock().unwrap();
-            active.remove(&task_id).ok_or_else(|| {
-                anyhow::anyhow!("No active time tracking for this task")
-            })?
-        };
-        
-        // Update entry with end time
-        let mut entries = self.time_entries.lock().unwrap();
-        let entry = entries.get_mut(&entry_id).ok_or_else(|| {
-            anyhow::anyhow!("Time entry not found")
-        })?;
-        
-        entry.end_time = Some(Utc::now());
-        let duration = entry.end_time.unwrap() - entry.start_time;
-        
-        // Update task's actual hours
-        let mut task = self.repository.tasks.get(task_id).await?
+        let entry = self.end_segment(task_id, Utc::now()).await?;
+        self.roll_up_segments(task_id).await?;
+        Ok(entry.duration())
+    }
+    
+    /// Pause tracking: end the current segment without updating task hours
+    pub async fn pause_tracking(&self, task_id: Uuid) -> Result<()> {
+        self.end_segment(task_id, Utc::now()).await?;
+        Ok(())
+    }
+    
+    /// Resume a paused task in a new segment, carrying over the last description
+    pub async fn resume_tracking(&self, task_id: Uuid) -> Result<Uuid> {
+        let entries = self.repository.time_entries.list_for_task(task_id).await?;
+        let last = entries.iter().rev().find(|e| !e.manual && !e.rolled_up)
+            .ok_or_else(|| anyhow::anyhow!("No paused time tracking for this task"))?;
+        self.start_segment(task_id, last.resource_id, last.description.clone()).await
+    }
+    
+    /// Log time worked without the timer
+    pub async fn log_time(
+        &self,
+        task_id: Uuid,
+        resource_id: Option<Uuid>,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        description: String,
+    ) -> Result<TimeEntry> {
+        self.repository.tasks.get(task_id).await?
             .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
-        let hours_tracked = duration.num_seconds() as f32 / 3600.0;
-        task.actual_hours = Some(task.actual_hours.unwrap_or(0.0) + hours_tracked);
-        task.updated_at = Utc::now();
-        self.repository.tasks.update(&task).await?;
-        
-        Ok(duration)
+        let mut entry = TimeEntry::manual(task_id, resource_id, start_time, end_time, description);
+        entry.rolled_up = true;
+        validate(&entry)?;
+        self.repository.time_entries.create(&entry).await?;
+        self.roll_up(task_id).await?;
+        Ok(entry)
     }
     
-    /// Pause tracking (stop without updating task hours)
-    pub fn pause_tracking(&self, task_id: Uuid) -> Result<()> {
-        let entry_id = {
-            let mut active = self.active_entries.lock().unwrap();
-            active.remove(&task_id).ok_or_else(|| {
-                anyhow::anyhow!("No active time tracking for this task")
-            })?
-        };
-        
-        let mut entries = self.time_entries.lock().unwrap();
-        let entry = entries.get_mut(&entry_id).ok_or_else(|| {
-            anyhow::anyhow!("Time entry not found")
-        })?;
+    /// Save changes to a finished entry, e.g. corrected times or another task
+    pub async fn edit_entry(&self, entry: &TimeEntry) -> Result<()> {
+        let previous = self.repository.time_entries.get(entry.id).await?
+            .ok_or_else(|| anyhow::anyhow!("Time entry not found"))?;
+        if previous.is_running() || entry.is_running() {
+            return Err(anyhow::anyhow!("Stop the timer before editing this entry"));
+        }
+        validate(entry)?;
         
-        entry.end_time = Some(Utc::now());
+        let mut entry = entry.clone();
+        entry.rolled_up = previous.rolled_up;
+        entry.updated_at = Utc::now();
+        self.repository.time_entries.update(&entry).await?;
+        self.roll_up(entry.task_id).await?;
+        if previous.task_id != entry.task_id {
+            self.roll_up(previous.task_id).await?;
+        }
         Ok(())
     }
     
-    /// Get total time tracked for a task
-    pub fn get_total_time(&self, task_id: Uuid) -> Duration {
-        let entries = self.time_entries.lock().unwrap();
-        let mut total = Duration::zero();
-        
-        for entry in entries.values() {
-            if entry.task_id == task_id {
-                let end = entry.end_time.unwrap_or_else(Utc::now);
-                total = total + (end - entry.start_time);
-            }
+    pub async fn delete_entry(&self, entry_id: Uuid) -> Result<bool> {
+        let Some(entry) = self.repository.time_entries.get(entry_id).await? else {
+            return Ok(false);
+        };
+        self.repository.time_entries.delete(entry_id).await?;
+        if entry.is_running() {
+            self.active_entries.lock().unwrap().remove(&entry.task_id);
         }
-        
-        total
+        self.roll_up(entry.task_id).await?;
+        Ok(true)
+    }
+    
+    /// Get total time tracked for a task, including a running timer
+    pub async fn get_total_time(&self, task_id: Uuid) -> Result<Duration> {
+        let entries = self.repository.time_entries.list_for_task(task_id).await?;
+        Ok(entries.iter().fold(Duration::zero(), |total, e| total + e.duration()))
     }
     
     /// Get all time entries for a task
-    pub fn get_task_entries(&self, task_id: Uuid) -> Vec<TimeEntry> {
-        let entries = self.time_entries.lock().unwrap();
-        entries
-            .values()
-            .filter(|e| e.task_id == task_id)
-            .cloned()
-            .collect()
+    pub async fn get_task_entries(&self, task_id: Uuid) -> Result<Vec<TimeEntry>> {
+        self.repository.time_entries.list_for_task(task_id).await
     }
     
     /// Check if a task is currently being tracked
@@ -153,38 +153,145 @@ impl TimeTrackingService {
     
     /// Get the active time entry for a task
     pub fn get_active_entry(&self, task_id: Uuid) -> Option<TimeEntry> {
-        let active = self.active_entries.lock().unwrap();
-        if let Some(&entry_id) = active.get(&task_id) {
-            let entries = self.time_entries.lock().unwrap();
-            entries.get(&entry_id).cloned()
-        } else {
-            None
-        }
+        self.active_entries.lock().unwrap().get(&task_id).cloned()
     }
     
-    /// Get a summary of time tracked in a date range
-    pub fn get_time_summary(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> HashMap<Uuid, Duration> {
-        let entries = self.time_entries.lock().unwrap();
+    /// Get a summary of time tracked in a date range, counting only the
+    /// part of each entry inside it
+    pub async fn get_time_summary(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<HashMap<Uuid, Duration>> {
+        let entries = self.repository.time_entries.list_between(start, end).await?;
         let mut summary: HashMap<Uuid, Duration> = HashMap::new();
         
-        for entry in entries.values() {
-            if entry.start_time >= start && entry.start_time <= end {
-                let end_time = entry.end_time.unwrap_or_else(Utc::now);
-                let duration = end_time - entry.start_time;
-                
-                *summary.entry(entry.task_id).or_insert(Duration::zero()) += duration;
+        for entry in entries {
+            *summary.entry(entry.task_id).or_insert(Duration::zero()) += entry.overlap(start, end);
+        }
+        
+        Ok(summary)
+    }
+    
+    /// The week containing `date`, by task and day, for one resource or
+    /// for everyone when `resource_id` is `None`
+    pub async fn weekly_timesheet(&self, resource_id: Option<Uuid>, date: NaiveDate) -> Result<Timesheet> {
+        let (start, end) = Timesheet::week_range(date);
+        let entries = self.repository.time_entries.list_between(start, end).await?;
+        
+        let mut titles = HashMap::new();
+        for entry in &entries {
+            if !titles.contains_key(&entry.task_id)
+                && let Some(task) = self.repository.tasks.get(entry.task_id).await?
+            {
+                titles.insert(task.id, task.title);
             }
         }
         
-        summary
+        Ok(Timesheet::build(resource_id, date, &entries, &titles))
+    }
+    
+    /// Note that the user is active, holding off idle detection
+    pub fn record_activity(&self) {
+        *self.last_activity.lock().unwrap() = Utc::now();
+    }
+    
+    /// Stop timers left running while the user was away for longer than
+    /// `idle_timeout`. Their segments end at the last activity, so the idle
+    /// time isn't counted.
+    pub async fn stop_idle_timers(&self, idle_timeout: Duration) -> Result<Vec<TimeEntry>> {
+        let last_activity = *self.last_activity.lock().unwrap();
+        if Utc::now() - last_activity < idle_timeout {
+            return Ok(Vec::new());
+        }
+        
+        let running: Vec<Uuid> = self.active_entries.lock().unwrap().keys().copied().collect();
+        let mut stopped = Vec::new();
+        for task_id in running {
+            let entry = self.end_segment(task_id, last_activity).await?;
+            self.roll_up_segments(task_id).await?;
+            tracing::info!("Stopped idle timer on task {}", task_id);
+            stopped.push(entry);
+        }
+        Ok(stopped)
+    }
+    
+    async fn start_segment(&self, task_id: Uuid, resource_id: Option<Uuid>, description: String) -> Result<Uuid> {
+        // Check if already tracking this task
+        if self.is_tracking(task_id) || self.repository.time_entries.get_running(task_id).await?.is_some() {
+            return Err(anyhow::anyhow!("Already tracking time for this task"));
+        }
+        
+        let entry = TimeEntry::start(task_id, resource_id, description);
+        self.repository.time_entries.create(&entry).await?;
+        let entry_id = entry.id;
+        self.active_entries.lock().unwrap().insert(task_id, entry);
+        Ok(entry_id)
+    }
+    
+    async fn end_segment(&self, task_id: Uuid, end_time: DateTime<Utc>) -> Result<TimeEntry> {
+        let cached = self.active_entries.lock().unwrap().remove(&task_id);
+        let mut entry = match cached {
+            Some(entry) => entry,
+            None => self.repository.time_entries.get_running(task_id).await?
+                .ok_or_else(|| anyhow::anyhow!("No active time tracking for this task"))?,
+        };
+        
+        entry.end_time = Some(end_time.max(entry.start_time));
+        entry.updated_at = Utc::now();
+        self.repository.time_entries.update(&entry).await?;
+        Ok(entry)
+    }
+    
+    /// Mark the task's finished timer segments, paused ones included, as
+    /// counted and roll them up
+    async fn roll_up_segments(&self, task_id: Uuid) -> Result<()> {
+        let entries = self.repository.time_entries.list_for_task(task_id).await?;
+        for mut entry in entries {
+            if !entry.rolled_up && !entry.is_running() {
+                entry.rolled_up = true;
+                entry.updated_at = Utc::now();
+                self.repository.time_entries.update(&entry).await?;
+            }
+        }
+        self.roll_up(task_id).await
+    }
+    
+    /// Set the task's actual hours to the sum of its rolled-up entries
+    async fn roll_up(&self, task_id: Uuid) -> Result<()> {
+        let entries = self.repository.time_entries.list_for_task(task_id).await?;
+        let Some(mut task) = self.repository.tasks.get(task_id).await? else {
+            return Ok(());
+        };
+        
+        task.actual_hours = actual_hours(&entries);
+        task.updated_at = Utc::now();
+        self.repository.tasks.update(&task).await
     }
 }
 
+fn validate(entry: &TimeEntry) -> Result<()> {
+    if let Some(end_time) = entry.end_time
+        && end_time <= entry.start_time
+    {
+        return Err(anyhow::anyhow!("A time entry must end after it starts"));
+    }
+    Ok(())
+}
+
+/// Check for idle timers every minute, stopping them after `idle_timeout`.
+pub fn start_idle_monitor(service: Arc<TimeTrackingService>, idle_timeout: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.stop_idle_timers(idle_timeout).await {
+                tracing::error!("Failed to stop idle timers: {}", e);
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::task::{Task, Priority};
-    use std::collections::HashSet;
+    use crate::domain::task::Task;
     use sqlx::SqlitePool;
     
     async fn setup_test_service() -> (TimeTrackingService, Uuid) {
@@ -193,24 +300,8 @@ mod tests {
         let repo = Arc::new(Repository::new(pool));
         
         // Create a test task
-        let task = Task {
-            id: Uuid::new_v4(),
-            title: "Test Task".to_string(),
-            description: "".to_string(),
-            status: TaskStatus::Todo,
-            priority: Priority::Medium,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            due_date: None,
-            estimated_hours: Some(5.0),
-            actual_hours: None,
-            assigned_resource_id: None,
-            goal_id: None,
-            parent_task_id: None,
-            tags: HashSet::new(),
-            assignee: None,
-            position: crate::domain::task::Position { x: 0.0, y: 0.0 },
-        };
+        let mut task = Task::new("Test Task".to_string(), String::new());
+        task.estimated_hours = Some(5.0);
         
         repo.tasks.create(&task).await.unwrap();
         
@@ -224,6 +315,7 @@ mod tests {
         // Start tracking
         let entry_id = service.start_tracking(task_id, "Working on feature".to_string()).await.unwrap();
         assert!(service.is_tracking(task_id));
+        assert_eq!(service.get_active_entry(task_id).unwrap().id, entry_id);
         
         // Wait a bit
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -260,12 +352,23 @@ mod tests {
         service.start_tracking(task_id, "Working".to_string()).await.unwrap();
         
         // Pause
-        service.pause_tracking(task_id).unwrap();
+        service.pause_tracking(task_id).await.unwrap();
         assert!(!service.is_tracking(task_id));
         
-        // Task hours should not be updated yet
+        // Task hours should not be updated yet, even by logging other time
         let task = service.repository.tasks.get(task_id).await.unwrap().unwrap();
         assert_eq!(task.actual_hours, None);
+        let start = Utc::now() - Duration::hours(3);
+        service.log_time(task_id, None, start, start + Duration::hours(1), String::new()).await.unwrap();
+        let task = service.repository.tasks.get(task_id).await.unwrap().unwrap();
+        assert_eq!(task.actual_hours, Some(1.0));
+        
+        // Stopping after resuming counts the paused segment too
+        service.resume_tracking(task_id).await.unwrap();
+        service.stop_tracking(task_id).await.unwrap();
+        let entries = service.get_task_entries(task_id).await.unwrap();
+        assert!(entries.iter().all(|e| e.rolled_up));
+        assert!(service.resume_tracking(task_id).await.is_err());
     }
     
     #[tokio::test]
@@ -279,7 +382,90 @@ mod tests {
             service.stop_tracking(task_id).await.unwrap();
         }
         
-        let total = service.get_total_time(task_id);
+        let total = service.get_total_time(task_id).await.unwrap();
         assert!(total.num_milliseconds() >= 150);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_running_timer_survives_restart() {
+        let (service, task_id) = setup_test_service().await;
+        service.start_tracking(task_id, "Before restart".to_string()).await.unwrap();
+        
+        let restarted = TimeTrackingService::new(service.repository.clone());
+        assert!(!restarted.is_tracking(task_id));
+        assert!(restarted.start_tracking(task_id, "Again".to_string()).await.is_err());
+        assert_eq!(restarted.restore_active().await.unwrap(), 1);
+        assert_eq!(restarted.get_active_entry(task_id).unwrap().description, "Before restart");
+        
+        restarted.stop_tracking(task_id).await.unwrap();
+        assert!(!restarted.is_tracking(task_id));
+        assert_eq!(restarted.get_task_entries(task_id).await.unwrap().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_segments_and_manual_entries_roll_up() {
+        let (service, task_id) = setup_test_service().await;
+        
+        // Pausing and resuming makes separate segments
+        service.start_tracking(task_id, "Coding".to_string()).await.unwrap();
+        service.pause_tracking(task_id).await.unwrap();
+        service.resume_tracking(task_id).await.unwrap();
+        assert!(service.resume_tracking(task_id).await.is_err());
+        service.stop_tracking(task_id).await.unwrap();
+        let segments = service.get_task_entries(task_id).await.unwrap();
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|e| e.description == "Coding" && !e.is_running()));
+        
+        let start = Utc::now() - Duration::hours(5);
+        let mut logged = service
+            .log_time(task_id, None, start, start + Duration::hours(2), "Meeting".to_string())
+            .await
+            .unwrap();
+        let actual = |task: crate::domain::task::Task| task.actual_hours.unwrap();
+        let task = service.repository.tasks.get(task_id).await.unwrap().unwrap();
+        assert!((actual(task) - 2.0).abs() < 0.01);
+        
+        logged.end_time = Some(start + Duration::hours(3));
+        service.edit_entry(&logged).await.unwrap();
+        let task = service.repository.tasks.get(task_id).await.unwrap().unwrap();
+        assert!((actual(task) - 3.0).abs() < 0.01);
+        
+        logged.end_time = Some(start - Duration::hours(1));
+        assert!(service.edit_entry(&logged).await.is_err());
+        assert!(service.log_time(task_id, None, start, start, String::new()).await.is_err());
+        
+        assert!(service.delete_entry(logged.id).await.unwrap());
+        let task = service.repository.tasks.get(task_id).await.unwrap().unwrap();
+        assert!(actual(task) < 0.01);
+    }
+    
+    #[tokio::test]
+    async fn test_weekly_timesheet_and_idle_timers() {
+        let (service, task_id) = setup_test_service().await;
+        let resource_id = Uuid::new_v4();
+        let monday = Timesheet::week_of(Utc::now().date_naive());
+        let start = monday.and_hms_opt(9, 0, 0).unwrap().and_utc();
+        service.log_time(task_id, Some(resource_id), start, start + Duration::hours(4), String::new()).await.unwrap();
+        service.log_time(task_id, None, start + Duration::hours(5), start + Duration::hours(6), String::new()).await.unwrap();
+        
+        let sheet = service.weekly_timesheet(Some(resource_id), monday).await.unwrap();
+        assert_eq!(sheet.rows.len(), 1);
+        assert_eq!(sheet.rows[0].task_title, "Test Task");
+        assert_eq!(sheet.rows[0].hours[0], 4.0);
+        assert_eq!(service.weekly_timesheet(None, monday).await.unwrap().total(), 5.0);
+        
+        service.start_tracking(task_id, "Working".to_string()).await.unwrap();
+        assert!(service.stop_idle_timers(Duration::minutes(15)).await.unwrap().is_empty());
+        
+        // Away for twenty minutes: the timer stops where activity did
+        let away_since = Utc::now() - Duration::minutes(20);
+        *service.last_activity.lock().unwrap() = away_since;
+        let stopped = service.stop_idle_timers(Duration::minutes(15)).await.unwrap();
+        assert_eq!(stopped.len(), 1);
+        assert!(!service.is_tracking(task_id));
+        assert_eq!(stopped[0].duration(), Duration::zero());
+        
+        service.record_activity();
+        assert!(service.stop_idle_timers(Duration::minutes(15)).await.unwrap().is_empty());
+    }
+}
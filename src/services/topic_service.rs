@@ -0,0 +1,385 @@
+use crate::domain::app_settings::AppSettings;
+use crate::domain::task::{Position, Task};
+use crate::repository::Repository;
+use crate::repository::summary_repository::content_hash;
+use crate::repository::task_repository::TaskFilters;
+use crate::services::embeddings::{
+    EmbeddingProvider, TfIdfEmbedder, cosine_similarity, embedding_provider_from_settings,
+    topic_clusters,
+};
+use crate::services::summarization::{SummarizationService, task_summary_source};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const CARD_COLUMN_WIDTH: f64 = 240.0;
+const CARD_ROW_HEIGHT: f64 = 120.0;
+const CARDS_PER_COLUMN: usize = 6;
+const CLUSTER_GAP: f64 = 100.0;
+/// Texts per request to a remote embedding model
+const EMBED_BATCH_SIZE: usize = 64;
+
+/// Tasks that share a topic, named by the summarizer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicCluster {
+    pub label: String,
+    pub task_ids: Vec<Uuid>,
+    /// Top-left corner of the cluster's cards once arranged
+    pub origin: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimilarTask {
+    pub task: Task,
+    /// Cosine similarity to the task asked about, up to 1.0
+    pub similarity: f32,
+}
+
+/// Compares and groups tasks by what they are about, using embeddings of
+/// their title and description.
+pub struct TopicService {
+    repository: Arc<Repository>,
+    embeddings: Arc<dyn EmbeddingProvider>,
+    summarizer: SummarizationService,
+}
+
+impl TopicService {
+    pub fn new(
+        repository: Arc<Repository>,
+        embeddings: Arc<dyn EmbeddingProvider>,
+        summarizer: SummarizationService,
+    ) -> Self {
+        Self {
+            repository,
+            embeddings,
+            summarizer,
+        }
+    }
+
+    /// Embeddings and cluster labels from the models chosen in the app settings.
+    pub fn from_settings(repository: Arc<Repository>, settings: &AppSettings) -> Self {
+        let summarizer =
+            SummarizationService::from_settings(settings).with_store(repository.summaries.clone());
+        Self::new(
+            repository,
+            embedding_provider_from_settings(settings),
+            summarizer,
+        )
+    }
+
+    /// One vector per task, in order. Model vectors are stored and reused
+    /// until the task's text changes; if the model can't be reached the
+    /// whole batch uses local TF-IDF instead.
+    pub async fn vectors(&self, tasks: &[Task]) -> Result<Vec<Vec<f32>>> {
+        let texts: Vec<String> = tasks.iter().map(task_summary_source).collect();
+        if self.embeddings.is_corpus_relative() {
+            return self.embeddings.embed(&texts).await;
+        }
+        match self.model_vectors(tasks, &texts).await {
+            Ok(vectors) => Ok(vectors),
+            Err(e) => {
+                tracing::warn!(
+                    "Embedding model {} unavailable, using TF-IDF: {}",
+                    self.embeddings.model(),
+                    e
+                );
+                TfIdfEmbedder::default().embed(&texts).await
+            }
+        }
+    }
+
+    async fn model_vectors(&self, tasks: &[Task], texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let model = self.embeddings.model();
+        let keys: Vec<(Uuid, String)> = tasks
+            .iter()
+            .zip(texts)
+            .map(|(task, text)| (task.id, content_hash(text)))
+            .collect();
+        let mut vectors = self.repository.embeddings.get_many(model, &keys).await?;
+
+        let missing: Vec<usize> = (0..tasks.len())
+            .filter(|i| !vectors.contains_key(&tasks[*i].id))
+            .collect();
+        for batch in missing.chunks(EMBED_BATCH_SIZE) {
+            let batch_texts: Vec<String> = batch.iter().map(|i| texts[*i].clone()).collect();
+            let embedded = self.embeddings.embed(&batch_texts).await?;
+            if embedded.len() != batch.len() {
+                anyhow::bail!(
+                    "{} returned {} vectors for {} texts",
+                    model,
+                    embedded.len(),
+                    batch.len()
+                );
+            }
+            for (i, vector) in batch.iter().zip(embedded) {
+                let (task_id, hash) = &keys[*i];
+                self.repository
+                    .embeddings
+                    .save(*task_id, model, hash, &vector)
+                    .await?;
+                vectors.insert(*task_id, vector);
+            }
+        }
+
+        Ok(tasks
+            .iter()
+            .map(|task| vectors.get(&task.id).cloned().unwrap_or_default())
+            .collect())
+    }
+
+    /// The tasks most like `task_id`, most similar first. Tasks with
+    /// nothing in common are left out.
+    pub async fn find_similar(&self, task_id: Uuid, limit: usize) -> Result<Vec<SimilarTask>> {
+        let tasks = self.repository.tasks.list(TaskFilters::default()).await?;
+        let index = tasks
+            .iter()
+            .position(|t| t.id == task_id)
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", task_id))?;
+        let vectors = self.vectors(&tasks).await?;
+
+        let mut similar: Vec<SimilarTask> = tasks
+            .into_iter()
+            .zip(&vectors)
+            .filter(|(task, _)| task.id != task_id)
+            .map(|(task, vector)| SimilarTask {
+                similarity: cosine_similarity(&vectors[index], vector),
+                task,
+            })
+            .filter(|s| s.similarity > 0.0)
+            .collect();
+        similar.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+
+    /// Group `tasks` into at most `max_clusters` topics, largest first.
+    pub async fn clusters(&self, tasks: &[Task], max_clusters: usize) -> Result<Vec<TopicCluster>> {
+        let vectors = self.vectors(tasks).await?;
+        let mut members = topic_clusters(&vectors, max_clusters).members();
+        members.retain(|m| !m.is_empty());
+        members.sort_by_key(|m| std::cmp::Reverse(m.len()));
+
+        let mut clusters = Vec::new();
+        for indices in members {
+            let cluster_tasks: Vec<Task> = indices.iter().map(|i| tasks[*i].clone()).collect();
+            clusters.push(TopicCluster {
+                label: self.summarizer.label_cluster(&cluster_tasks).await,
+                task_ids: cluster_tasks.iter().map(|t| t.id).collect(),
+                origin: Position { x: 0.0, y: 0.0 },
+            });
+        }
+        Ok(clusters)
+    }
+
+    /// Cluster every task by topic and move each cluster into its own
+    /// block on the map, starting where the top-left task is now.
+    pub async fn arrange_by_topic(&self, max_clusters: usize) -> Result<Vec<TopicCluster>> {
+        let tasks = self.repository.tasks.list(TaskFilters::default()).await?;
+        if tasks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let origin = Position {
+            x: tasks
+                .iter()
+                .map(|t| t.position.x)
+                .fold(f64::INFINITY, f64::min),
+            y: tasks
+                .iter()
+                .map(|t| t.position.y)
+                .fold(f64::INFINITY, f64::min),
+        };
+
+        let mut clusters = self.clusters(&tasks, max_clusters).await?;
+        let positions = layout_topics(&mut clusters, origin);
+        for mut task in tasks {
+            if let Some(position) = positions.get(&task.id) {
+                task.position = *position;
+                self.repository.tasks.update(&task).await?;
+            }
+        }
+        Ok(clusters)
+    }
+}
+
+/// Place clusters side by side from `origin`, each in columns of up to
+/// six cards, and set each cluster's origin. Returns every task's position.
+pub fn layout_topics(clusters: &mut [TopicCluster], origin: Position) -> HashMap<Uuid, Position> {
+    let mut positions = HashMap::new();
+    let mut x = origin.x;
+    for cluster in clusters.iter_mut() {
+        cluster.origin = Position { x, y: origin.y };
+        for (i, task_id) in cluster.task_ids.iter().enumerate() {
+            positions.insert(
+                *task_id,
+                Position {
+                    x: x + (i / CARDS_PER_COLUMN) as f64 * CARD_COLUMN_WIDTH,
+                    y: origin.y + (i % CARDS_PER_COLUMN) as f64 * CARD_ROW_HEIGHT,
+                },
+            );
+        }
+        let columns = cluster.task_ids.len().div_ceil(CARDS_PER_COLUMN).max(1);
+        x += columns as f64 * CARD_COLUMN_WIDTH + CLUSTER_GAP;
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::database::init_test_database;
+    use crate::services::llm::ReplayProvider;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    async fn setup(titles: &[(&str, &str)]) -> (Arc<Repository>, Vec<Task>) {
+        let pool = init_test_database().await.unwrap();
+        let repository = Arc::new(Repository::new(pool));
+        let mut tasks = Vec::new();
+        for (i, (title, description)) in titles.iter().enumerate() {
+            let mut task = Task::new(title.to_string(), description.to_string());
+            task.position = Position {
+                x: 100.0 + i as f64 * 50.0,
+                y: 300.0 - i as f64 * 20.0,
+            };
+            repository.tasks.create(&task).await.unwrap();
+            tasks.push(task);
+        }
+        (repository, tasks)
+    }
+
+    const TASKS: &[(&str, &str)] = &[
+        ("Export tasks to CSV", "Download the task list as CSV"),
+        ("Dark mode theme", "Dark colors for the theme"),
+        ("CSV export for goals", "Goals as CSV download"),
+        ("Theme picker", "Choose dark or light theme colors"),
+    ];
+
+    fn summarizer(fixtures: &str) -> SummarizationService {
+        SummarizationService::with_provider(Arc::new(ReplayProvider::new(fixtures)))
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_with_tfidf() {
+        let (repository, tasks) = setup(TASKS).await;
+        let empty = TempDir::new().unwrap();
+        let service = TopicService::new(
+            repository,
+            Arc::new(TfIdfEmbedder::default()),
+            summarizer(empty.path().to_str().unwrap()),
+        );
+
+        let similar = service.find_similar(tasks[0].id, 2).await.unwrap();
+        assert_eq!(similar[0].task.id, tasks[2].id);
+        assert!(similar.iter().all(|s| s.task.id != tasks[0].id));
+        assert!(similar.len() <= 2);
+        assert!(service.find_similar(Uuid::new_v4(), 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_arrange_by_topic() {
+        let (repository, tasks) = setup(TASKS).await;
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/llm");
+        let service = TopicService::new(
+            repository.clone(),
+            Arc::new(TfIdfEmbedder::default()),
+            summarizer(fixtures),
+        );
+
+        let clusters = service.arrange_by_topic(3).await.unwrap();
+        assert_eq!(clusters.len(), 2);
+        let export = clusters
+            .iter()
+            .find(|c| c.task_ids.contains(&tasks[0].id))
+            .unwrap();
+        assert!(export.task_ids.contains(&tasks[2].id));
+        // Labelled by the recorded model, or by shared words without a recording
+        assert_eq!(export.label, "Data export");
+        let theme = clusters
+            .iter()
+            .find(|c| c.task_ids.contains(&tasks[1].id))
+            .unwrap();
+        assert!(
+            theme.label.to_lowercase().contains("theme"),
+            "{}",
+            theme.label
+        );
+
+        // Clusters sit side by side from the top-left of the old layout
+        assert_eq!(clusters[0].origin, Position { x: 100.0, y: 240.0 });
+        let moved = repository.tasks.get(tasks[2].id).await.unwrap().unwrap();
+        assert_eq!(moved.position.x, export.origin.x);
+        assert!(clusters[1].origin.x > clusters[0].origin.x);
+    }
+
+    /// Two dimensions: mentions CSV, mentions theme. Counts texts embedded.
+    #[derive(Default)]
+    struct KeywordEmbeddings {
+        embedded: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for KeywordEmbeddings {
+        fn model(&self) -> &str {
+            "keywords"
+        }
+
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            if self.fail {
+                anyhow::bail!("connection refused");
+            }
+            self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(texts
+                .iter()
+                .map(|t| {
+                    let t = t.to_lowercase();
+                    vec![
+                        t.contains("csv") as u8 as f32,
+                        t.contains("theme") as u8 as f32,
+                    ]
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_model_vectors_are_stored_until_text_changes() {
+        let (repository, mut tasks) = setup(TASKS).await;
+        let embeddings = Arc::new(KeywordEmbeddings::default());
+        let empty = TempDir::new().unwrap();
+        let service = TopicService::new(
+            repository.clone(),
+            embeddings.clone(),
+            summarizer(empty.path().to_str().unwrap()),
+        );
+
+        let vectors = service.vectors(&tasks).await.unwrap();
+        assert_eq!(vectors[0], vec![1.0, 0.0]);
+        assert_eq!(embeddings.embedded.load(Ordering::SeqCst), 4);
+        assert_eq!(repository.embeddings.count().await.unwrap(), 4);
+
+        service.vectors(&tasks).await.unwrap();
+        assert_eq!(embeddings.embedded.load(Ordering::SeqCst), 4);
+
+        tasks[0].description = "Pick a theme".to_string();
+        repository.tasks.update(&tasks[0]).await.unwrap();
+        let vectors = service.vectors(&tasks).await.unwrap();
+        assert_eq!(vectors[0], vec![1.0, 1.0]);
+        assert_eq!(embeddings.embedded.load(Ordering::SeqCst), 5);
+
+        // An unreachable model falls back to TF-IDF for text it hasn't seen
+        tasks[1].title = "Light mode".to_string();
+        let offline = TopicService::new(
+            repository,
+            Arc::new(KeywordEmbeddings {
+                fail: true,
+                ..Default::default()
+            }),
+            summarizer(empty.path().to_str().unwrap()),
+        );
+        let vectors = offline.vectors(&tasks).await.unwrap();
+        assert_eq!(vectors[0].len(), 512);
+    }
+}
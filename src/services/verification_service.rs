@@ -0,0 +1,120 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::domain::verification::{
+    VerificationPipeline, VerificationReport, VerificationStepResult, truncate_output,
+};
+use crate::services::command_executor::CommandExecutor;
+
+/// Runs a `VerificationPipeline` in an agent's worktree.
+///
+/// Steps run in order and stop at the first failure; a step that exceeds its
+/// timeout is killed and reported as failed.
+pub struct VerificationService {
+    command_executor: Arc<dyn CommandExecutor>,
+}
+
+impl VerificationService {
+    pub fn new(command_executor: Arc<dyn CommandExecutor>) -> Self {
+        Self { command_executor }
+    }
+
+    pub async fn run(&self, pipeline: &VerificationPipeline, work_dir: &Path) -> VerificationReport {
+        let mut report = VerificationReport::default();
+
+        for step in &pipeline.steps {
+            let started = Instant::now();
+            let args = ["-c", step.command.as_str()];
+            let execution = self
+                .command_executor
+                .execute("sh", &args, Some(work_dir), None);
+            let outcome =
+                tokio::time::timeout(Duration::from_secs(step.timeout_secs), execution).await;
+
+            let mut result = VerificationStepResult {
+                name: step.name.clone(),
+                command: step.command.clone(),
+                success: false,
+                timed_out: false,
+                exit_code: None,
+                stdout: String::new(),
+                stderr: String::new(),
+                duration_ms: 0,
+            };
+            match outcome {
+                Ok(Ok(output)) => {
+                    result.success = output.success;
+                    result.exit_code = output.exit_code;
+                    result.stdout = truncate_output(&output.stdout);
+                    result.stderr = truncate_output(&output.stderr);
+                }
+                Ok(Err(e)) => {
+                    result.stderr = format!("Failed to run command: {}", e);
+                }
+                Err(_) => {
+                    result.timed_out = true;
+                    result.stderr = format!("Timed out after {}s", step.timeout_secs);
+                }
+            }
+            result.duration_ms = started.elapsed().as_millis() as u64;
+
+            let passed = result.success;
+            report.steps.push(result);
+            if !passed {
+                break;
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::verification::VerificationStep;
+    use crate::services::command_executor::mock::MockCommandExecutor;
+
+    #[tokio::test]
+    async fn test_pipeline_stops_at_first_failure() {
+        let mock = MockCommandExecutor::new().with_delay(0);
+        mock.add_response("sh", vec!["cargo build"], "Finished", "", true);
+        mock.add_response("sh", vec!["cargo test"], "", "test result: FAILED", false);
+
+        let pipeline = VerificationPipeline {
+            steps: vec![
+                VerificationStep::new("build", "cargo build"),
+                VerificationStep::new("test", "cargo test"),
+                VerificationStep::new("lint", "cargo clippy"),
+            ],
+            ..Default::default()
+        };
+
+        let service = VerificationService::new(Arc::new(mock.clone()));
+        let report = service.run(&pipeline, Path::new("/tmp")).await;
+
+        assert!(!report.passed());
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[1].stderr, "test result: FAILED");
+        assert!(!mock.assert_called_with("sh", &["cargo clippy"]));
+    }
+
+    #[tokio::test]
+    async fn test_step_timeout() {
+        let mock = MockCommandExecutor::new();
+        mock.add_response_with_delay("sh", vec!["sleep"], "", "", true, 3_000);
+
+        let pipeline = VerificationPipeline {
+            steps: vec![VerificationStep::new("slow", "sleep 10").with_timeout(1)],
+            ..Default::default()
+        };
+
+        let report = VerificationService::new(Arc::new(mock))
+            .run(&pipeline, Path::new("/tmp"))
+            .await;
+
+        assert!(!report.passed());
+        assert!(report.steps[0].timed_out);
+    }
+}
@@ -1,7 +1,13 @@
 use dioxus::prelude::*;
 use crate::ui_dioxus::views::*;
 use crate::repository::Repository;
-use crate::services::TimeTrackingService;
+use crate::repository::task_metadata_repository::backfill_task_metadata;
+use crate::services::{
+    CalendarFeed, TimeTrackingService, start_calendar_feed_from_settings, start_pr_monitor_background,
+};
+use crate::services::hosting::client_from_config;
+use crate::services::time_tracking_service::{start_idle_monitor, DEFAULT_IDLE_TIMEOUT_MINUTES};
+use crate::ui_dioxus::components::{ApprovalInbox, TimesheetView};
 use std::sync::Arc;
 use sqlx::SqlitePool;
 use std::path::Path;
@@ -27,6 +33,9 @@ pub fn App() -> Element {
             .run(&pool)
             .await
             .expect("Failed to run migrations");
+        backfill_task_metadata(&pool)
+            .await
+            .expect("Failed to backfill task metadata");
         
         Arc::new(Repository::new(pool))
     });
@@ -40,12 +49,68 @@ pub fn App() -> Element {
             use_context_provider(|| repo.clone());
             
             // Provide the TimeTrackingService as context
-            let time_tracking_service = Arc::new(TimeTrackingService::new(repo.clone()));
-            use_context_provider(|| time_tracking_service);
+            let time_tracking_service =
+                use_context_provider(|| Arc::new(TimeTrackingService::new(repo.clone())));
+            
+            // The running calendar feed, shown in the integration settings
+            let mut calendar_feed =
+                use_context_provider(|| Signal::new(None::<Arc<CalendarFeed>>));
+            
+            // Pick up timers from the last session and stop ones left running idle
+            use_hook(|| {
+                let service = time_tracking_service.clone();
+                spawn(async move {
+                    if let Err(e) = service.restore_active().await {
+                        tracing::error!("Failed to restore running timers: {}", e);
+                    }
+                });
+                start_idle_monitor(
+                    time_tracking_service.clone(),
+                    chrono::Duration::minutes(DEFAULT_IDLE_TIMEOUT_MINUTES),
+                );
+                
+                // Mirror agent PRs into their tasks, once a host is configured
+                let repo = repo.clone();
+                spawn(async move {
+                    match repo.claude_code.get_config().await {
+                        Ok(Some(config)) => {
+                            start_pr_monitor_background(repo.clone(), client_from_config(&config));
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::error!("Failed to load Claude Code config: {}", e),
+                    }
+                });
+                
+                // Serve the calendar feed when calendar sync is enabled
+                let repo = repo.clone();
+                spawn(async move {
+                    let settings = match repo.app_settings.get().await {
+                        Ok(Some(settings)) => settings,
+                        Ok(None) => return,
+                        Err(e) => {
+                            tracing::error!("Failed to load app settings: {}", e);
+                            return;
+                        }
+                    };
+                    match start_calendar_feed_from_settings(repo.clone(), &settings).await {
+                        Ok(Some(feed)) => calendar_feed.set(Some(Arc::new(feed))),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("Failed to start the calendar feed: {}", e),
+                    }
+                });
+            });
             
             rsx! {
                 div {
                     class: "app-container",
+                    onmousemove: {
+                        let service = time_tracking_service.clone();
+                        move |_| service.record_activity()
+                    },
+                    onkeydown: {
+                        let service = time_tracking_service.clone();
+                        move |_| service.record_activity()
+                    },
             
             // Navigation bar
             nav {
@@ -92,6 +157,18 @@ pub fn App() -> Element {
                         "📊 Gantt"
                     }
                     
+                    button {
+                        class: if *current_view.read() == "timesheet" { "nav-item active" } else { "nav-item" },
+                        onclick: move |_| current_view.set("timesheet"),
+                        "⏱ Timesheet"
+                    }
+                    
+                    button {
+                        class: if *current_view.read() == "approvals" { "nav-item active" } else { "nav-item" },
+                        onclick: move |_| current_view.set("approvals"),
+                        "✅ Approvals"
+                    }
+                    
                     button {
                         class: if *current_view.read() == "settings" { "nav-item active" } else { "nav-item" },
                         onclick: move |_| current_view.set("settings"),
@@ -111,6 +188,8 @@ pub fn App() -> Element {
                     "kanban" => rsx! { KanbanView {} },
                     "timeline" => rsx! { TimelineView {} },
                     "gantt" => rsx! { GanttView {} },
+                    "timesheet" => rsx! { TimesheetView {} },
+                    "approvals" => rsx! { ApprovalInbox {} },
                     "settings" => rsx! { SettingsView {} },
                     _ => rsx! { Dashboard {} },
                 }
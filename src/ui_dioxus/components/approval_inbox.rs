@@ -0,0 +1,205 @@
+use dioxus::prelude::*;
+use crate::domain::approval::{ApprovalAction, ApprovalEvent, ApprovalRequest};
+use crate::domain::resource::Resource;
+use crate::repository::Repository;
+use crate::services::ApprovalService;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// A request in the inbox with its task title and audit trail.
+#[derive(Debug, Clone, PartialEq)]
+struct InboxItem {
+    request: ApprovalRequest,
+    task_title: String,
+    history: Vec<ApprovalEvent>,
+}
+
+#[component]
+pub fn ApprovalInbox() -> Element {
+    let repository = use_context::<Arc<Repository>>();
+
+    let mut resource_id = use_signal(|| None::<Uuid>);
+    let mut refresh = use_signal(|| 0u32);
+    let mut message = use_signal(|| None::<String>);
+
+    let resources = use_resource({
+        let repository = repository.clone();
+        move || {
+            let repository = repository.clone();
+            async move { repository.resources.list_all().await.unwrap_or_default() }
+        }
+    });
+
+    let inbox = use_resource({
+        let repository = repository.clone();
+        move || {
+            let repository = repository.clone();
+            let (resource_id, _) = (resource_id(), refresh());
+            async move {
+                let Some(resource_id) = resource_id else {
+                    return Ok(Vec::new());
+                };
+                let service = ApprovalService::new(repository.clone());
+                let mut items = Vec::new();
+                for request in service.inbox(resource_id).await? {
+                    let task_title = match repository.tasks.get(request.task_id).await? {
+                        Some(task) => task.title,
+                        None => request.task_id.to_string(),
+                    };
+                    let history = service.history(request.id).await?;
+                    items.push(InboxItem { request, task_title, history });
+                }
+                anyhow::Ok(items)
+            }
+        }
+    });
+
+    let decide = {
+        let repository = repository.clone();
+        move |(request_id, approve, comment): (Uuid, bool, String)| {
+            let Some(me) = resource_id() else { return };
+            let service = ApprovalService::new(repository.clone());
+            spawn(async move {
+                let result = service.decide(request_id, me, approve, comment).await;
+                message.set(Some(match result {
+                    Ok(request) => format!("Request is now {:?}", request.status),
+                    Err(e) => format!("{:#}", e),
+                }));
+                refresh.set(refresh() + 1);
+            });
+        }
+    };
+
+    let resource_list: Vec<Resource> = resources.read().clone().unwrap_or_default();
+    let names: HashMap<Uuid, String> = resource_list.iter().map(|r| (r.id, r.name.clone())).collect();
+
+    rsx! {
+        div {
+            style: "padding: 20px; display: flex; flex-direction: column; gap: 16px;",
+
+            div {
+                style: "display: flex; align-items: center; gap: 10px;",
+                h2 { style: "margin: 0; font-size: 18px;", "Awaiting my approval" }
+                select {
+                    style: "margin-left: auto; padding: 6px; border-radius: 4px;",
+                    onchange: move |e| {
+                        resource_id.set(Uuid::parse_str(&e.value()).ok());
+                        message.set(None);
+                    },
+                    option { value: "", "Approving as…" }
+                    for resource in resource_list.iter() {
+                        option {
+                            value: "{resource.id}",
+                            selected: resource_id() == Some(resource.id),
+                            "{resource.name} ({resource.role})"
+                        }
+                    }
+                }
+            }
+
+            if let Some(message) = message() {
+                div { style: "color: #374151; font-size: 14px;", "{message}" }
+            }
+
+            match &*inbox.read() {
+                _ if resource_id().is_none() => rsx! {
+                    div { style: "color: #6b7280;", "Choose who you are approving as." }
+                },
+                Some(Ok(items)) if items.is_empty() => rsx! {
+                    div { style: "color: #6b7280;", "Nothing is waiting for your approval." }
+                },
+                Some(Ok(items)) => rsx! {
+                    for item in items.iter() {
+                        ApprovalCard {
+                            key: "{item.request.id}",
+                            item: item.clone(),
+                            names: names.clone(),
+                            on_decide: decide.clone(),
+                        }
+                    }
+                },
+                Some(Err(e)) => rsx! { div { style: "color: #ef4444;", "Failed to load approvals: {e}" } },
+                None => rsx! { div { "Loading..." } },
+            }
+        }
+    }
+}
+
+#[component]
+fn ApprovalCard(
+    item: InboxItem,
+    names: HashMap<Uuid, String>,
+    on_decide: EventHandler<(Uuid, bool, String)>,
+) -> Element {
+    let mut comment = use_signal(String::new);
+    let request = &item.request;
+    let request_id = request.id;
+    let name = |id: Option<Uuid>| {
+        id.and_then(|id| names.get(&id).cloned())
+            .unwrap_or_else(|| "System".to_string())
+    };
+    let approvals = request.approvals(&item.history);
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #e5e7eb; border-radius: 8px; padding: 16px;",
+
+            div {
+                style: "display: flex; justify-content: space-between; align-items: baseline;",
+                h3 { style: "margin: 0; font-size: 16px;", "{item.task_title}" }
+                span {
+                    style: "font-size: 13px; color: #6b7280;",
+                    "{approvals} of {request.quorum} approvals"
+                }
+            }
+            p {
+                style: "margin: 6px 0; font-size: 14px; color: #374151;",
+                "{request.from_state} → {request.to_state}, needs {request.role} approval. Requested by {name(request.requested_by)}."
+            }
+
+            textarea {
+                style: "width: 100%; min-height: 48px; padding: 6px; border: 1px solid #d1d5db; border-radius: 4px;",
+                placeholder: "Comment",
+                value: "{comment}",
+                oninput: move |e| comment.set(e.value()),
+            }
+            div {
+                style: "display: flex; gap: 8px; margin-top: 8px;",
+                button {
+                    style: "padding: 6px 12px; background: #10b981; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                    onclick: move |_| on_decide.call((request_id, true, comment())),
+                    "Approve"
+                }
+                button {
+                    style: "padding: 6px 12px; background: #ef4444; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                    onclick: move |_| on_decide.call((request_id, false, comment())),
+                    "Reject"
+                }
+            }
+
+            // Audit trail
+            ul {
+                style: "margin: 12px 0 0 0; padding-left: 18px; font-size: 12px; color: #6b7280;",
+                for event in item.history.iter() {
+                    li {
+                        key: "{event.id}",
+                        {format!(
+                            "{} · {} {}{}",
+                            event.created_at.format("%b %-d %H:%M"),
+                            name(event.resource_id),
+                            match event.action {
+                                ApprovalAction::Requested => "requested approval",
+                                ApprovalAction::Approved => "approved",
+                                ApprovalAction::Rejected => "rejected",
+                                ApprovalAction::Completed => "completed the transition",
+                                ApprovalAction::Cancelled => "cancelled the request",
+                            },
+                            if event.comment.is_empty() { String::new() } else { format!(": {}", event.comment) },
+                        )}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,13 @@
 use dioxus::prelude::*;
-use crate::domain::claude_code::ClaudeCodeConfig;
+use crate::domain::claude_code::{
+    AgentBackendConfig, ClaudeCodeConfig, ClaudePromptTemplate, HostingProvider,
+};
+use crate::domain::task::Task;
+use crate::domain::verification::{VerificationFailurePolicy, VerificationPipeline};
 use crate::repository::Repository;
+use crate::services::PromptContextBuilder;
 use std::sync::Arc;
+use uuid::Uuid;
 
 #[component]
 pub fn ClaudeConfigAdmin() -> Element {
@@ -22,7 +28,19 @@ pub fn ClaudeConfigAdmin() -> Element {
     let mut auto_create_pr = use_signal(|| true);
     let mut working_directory = use_signal(String::new);
     let mut claude_model = use_signal(String::new);
+    let mut agent_shell_command = use_signal(String::new);
+    let mut use_shell_agent = use_signal(|| false);
+    // "github" or "gitea"; the URL is the GitHub Enterprise API or Gitea instance address
+    let mut hosting_kind = use_signal(|| "github".to_string());
+    let mut hosting_url = use_signal(String::new);
     let mut max_session_duration = use_signal(|| 60);
+    // Budget limits; empty means unlimited
+    let mut max_tokens = use_signal(String::new);
+    let mut max_cost = use_signal(String::new);
+    let mut max_files = use_signal(String::new);
+    // Verification pipeline: one `name [timeout]: command` per line
+    let mut verification_steps = use_signal(String::new);
+    let mut feedback_attempts = use_signal(|| 0usize);
 
     // Load current config on mount
     use_effect({
@@ -37,7 +55,16 @@ pub fn ClaudeConfigAdmin() -> Element {
         let mut auto_create_pr_signal = auto_create_pr.clone();
         let mut working_directory_signal = working_directory.clone();
         let mut claude_model_signal = claude_model.clone();
+        let mut agent_shell_command_signal = agent_shell_command.clone();
+        let mut use_shell_agent_signal = use_shell_agent.clone();
+        let mut hosting_kind_signal = hosting_kind.clone();
+        let mut hosting_url_signal = hosting_url.clone();
         let mut max_session_duration_signal = max_session_duration.clone();
+        let mut max_tokens_signal = max_tokens.clone();
+        let mut max_cost_signal = max_cost.clone();
+        let mut max_files_signal = max_files.clone();
+        let mut verification_steps_signal = verification_steps.clone();
+        let mut feedback_attempts_signal = feedback_attempts.clone();
         let mut save_status_signal = save_status.clone();
         move || {
             let repo = repo.clone();
@@ -52,7 +79,28 @@ pub fn ClaudeConfigAdmin() -> Element {
                     auto_create_pr_signal.set(cfg.auto_create_pr);
                     working_directory_signal.set(cfg.working_directory.clone().unwrap_or_default());
                     claude_model_signal.set(cfg.claude_model.clone());
+                    if let AgentBackendConfig::Shell { command } = &cfg.agent_backend {
+                        use_shell_agent_signal.set(true);
+                        agent_shell_command_signal.set(command.clone());
+                    }
+                    match &cfg.hosting_provider {
+                        HostingProvider::GitHub { api_url } => {
+                            hosting_url_signal.set(api_url.clone().unwrap_or_default());
+                        }
+                        HostingProvider::Gitea { base_url } => {
+                            hosting_kind_signal.set("gitea".to_string());
+                            hosting_url_signal.set(base_url.clone());
+                        }
+                    }
                     max_session_duration_signal.set(cfg.max_session_duration_minutes);
+                    max_tokens_signal.set(cfg.max_tokens_per_session.map(|v| v.to_string()).unwrap_or_default());
+                    max_cost_signal.set(cfg.max_cost_per_session_usd.map(|v| v.to_string()).unwrap_or_default());
+                    max_files_signal.set(cfg.max_files_changed.map(|v| v.to_string()).unwrap_or_default());
+                    verification_steps_signal.set(cfg.verification_pipeline.steps_text());
+                    feedback_attempts_signal.set(match cfg.verification_pipeline.on_failure {
+                        VerificationFailurePolicy::FailSession => 0,
+                        VerificationFailurePolicy::FeedBackToAgent { max_attempts } => max_attempts,
+                    });
                     config_signal.set(Some(cfg));
                     loading_signal.set(false);
                 }
@@ -99,6 +147,33 @@ pub fn ClaudeConfigAdmin() -> Element {
                     h2 { class: "text-xl font-semibold mb-4 text-gray-700", "GitHub Settings" }
                     
                     div { class: "grid grid-cols-1 md:grid-cols-2 gap-4",
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1", "Git Host" }
+                            select {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{hosting_kind}",
+                                onchange: move |e| hosting_kind.set(e.value()),
+                                option { value: "github", "GitHub / GitHub Enterprise" }
+                                option { value: "gitea", "Gitea / Forgejo" }
+                            }
+                        }
+
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                if hosting_kind() == "gitea" { "Instance URL" } else { "API URL " }
+                                if hosting_kind() != "gitea" {
+                                    span { class: "text-xs text-gray-500", "(Optional - for GitHub Enterprise)" }
+                                }
+                            }
+                            input {
+                                r#type: "text",
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{hosting_url}",
+                                placeholder: if hosting_kind() == "gitea" { "https://codeberg.org" } else { "https://ghe.example.com/api/v3" },
+                                oninput: move |e| hosting_url.set(e.value())
+                            }
+                        }
+
                         div {
                             label { class: "block text-sm font-medium text-gray-700 mb-1", "Repository Owner" }
                             input {
@@ -224,6 +299,33 @@ pub fn ClaudeConfigAdmin() -> Element {
                             }
                         }
                         
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1", "Agent Backend" }
+                            select {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: if use_shell_agent() { "shell" } else { "claude_cli" },
+                                onchange: move |e| use_shell_agent.set(e.value() == "shell"),
+                                option { value: "claude_cli", "Claude Code CLI" }
+                                option { value: "shell", "Shell command (prompt on stdin)" }
+                            }
+                        }
+                        
+                        if use_shell_agent() {
+                            div {
+                                label { class: "block text-sm font-medium text-gray-700 mb-1", "Agent Command" }
+                                input {
+                                    r#type: "text",
+                                    class: "w-full px-3 py-2 border border-gray-300 rounded-md font-mono text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                    value: "{agent_shell_command}",
+                                    placeholder: "aider --yes --message-file $PLON_PROMPT_FILE",
+                                    oninput: move |e| agent_shell_command.set(e.value())
+                                }
+                                p { class: "text-xs text-gray-500 mt-1",
+                                    "Runs via sh -c in the worktree. PLON_PROMPT_FILE, PLON_INSTRUCTIONS_FILE, PLON_BRANCH and PLON_MODEL are set."
+                                }
+                            }
+                        }
+                        
                         div {
                             label { class: "block text-sm font-medium text-gray-700 mb-1", "Claude Model" }
                             select {
@@ -255,6 +357,54 @@ pub fn ClaudeConfigAdmin() -> Element {
                         }
                     }
                     
+                    div { class: "grid grid-cols-1 md:grid-cols-3 gap-4 mt-4",
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                "Max Tokens per Session ",
+                                span { class: "text-xs text-gray-500", "(Optional)" }
+                            }
+                            input {
+                                r#type: "number",
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{max_tokens}",
+                                placeholder: "Unlimited",
+                                min: "1",
+                                oninput: move |e| max_tokens.set(e.value())
+                            }
+                        }
+
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                "Max Cost per Session (USD) ",
+                                span { class: "text-xs text-gray-500", "(Optional)" }
+                            }
+                            input {
+                                r#type: "number",
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{max_cost}",
+                                placeholder: "Unlimited",
+                                min: "0",
+                                step: "0.01",
+                                oninput: move |e| max_cost.set(e.value())
+                            }
+                        }
+
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                "Max Files Changed ",
+                                span { class: "text-xs text-gray-500", "(Optional)" }
+                            }
+                            input {
+                                r#type: "number",
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{max_files}",
+                                placeholder: "Unlimited",
+                                min: "1",
+                                oninput: move |e| max_files.set(e.value())
+                            }
+                        }
+                    }
+
                     div { class: "mt-4",
                         label { class: "flex items-center",
                             input {
@@ -268,6 +418,50 @@ pub fn ClaudeConfigAdmin() -> Element {
                     }
                 }
 
+                // Verification Settings
+                div { class: "mb-6",
+                    h2 { class: "text-xl font-semibold mb-4 text-gray-700", "Verification Pipeline" }
+
+                    div { class: "space-y-4",
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                "Commands ",
+                                span { class: "text-xs text-gray-500", "(Run in order before pushing; one per line)" }
+                            }
+                            textarea {
+                                class: "w-full h-32 px-3 py-2 border border-gray-300 rounded-md font-mono text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{verification_steps}",
+                                placeholder: "build: cargo build\ntest [900]: cargo test\nlint: cargo clippy -- -D warnings",
+                                oninput: move |e| verification_steps.set(e.value())
+                            }
+                            p { class: "text-xs text-gray-500 mt-1",
+                                "Format: name [timeout seconds]: shell command. Timeout defaults to 600 seconds."
+                            }
+                        }
+
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 mb-1",
+                                "Fix-up Attempts on Failure"
+                            }
+                            input {
+                                r#type: "number",
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: "{feedback_attempts}",
+                                min: "0",
+                                max: "5",
+                                oninput: move |e| {
+                                    if let Ok(val) = e.value().parse::<usize>() {
+                                        feedback_attempts.set(val);
+                                    }
+                                }
+                            }
+                            p { class: "text-xs text-gray-500 mt-1",
+                                "0 fails the session immediately; otherwise Claude Code is re-run with the failing output."
+                            }
+                        }
+                    }
+                }
+
                 // Save Button and Status
                 div { class: "flex items-center justify-between mt-6 pt-6 border-t border-gray-200",
                     div { class: "text-sm",
@@ -302,7 +496,34 @@ pub fn ClaudeConfigAdmin() -> Element {
                                 cfg.auto_create_pr = auto_create_pr();
                                 cfg.working_directory = if working_directory().is_empty() { None } else { Some(working_directory()) };
                                 cfg.claude_model = claude_model();
+                                cfg.agent_backend = if use_shell_agent() {
+                                    AgentBackendConfig::Shell { command: agent_shell_command().trim().to_string() }
+                                } else {
+                                    AgentBackendConfig::ClaudeCli
+                                };
+                                let hosting_address = hosting_url().trim().to_string();
+                                cfg.hosting_provider = if hosting_kind() == "gitea" {
+                                    HostingProvider::Gitea { base_url: hosting_address }
+                                } else {
+                                    HostingProvider::GitHub {
+                                        api_url: Some(hosting_address).filter(|url| !url.is_empty()),
+                                    }
+                                };
                                 cfg.max_session_duration_minutes = max_session_duration();
+                                cfg.max_tokens_per_session = max_tokens().trim().parse().ok();
+                                cfg.max_cost_per_session_usd = max_cost().trim().parse().ok();
+                                cfg.max_files_changed = max_files().trim().parse().ok();
+                                cfg.verification_pipeline = VerificationPipeline {
+                                    steps: VerificationPipeline::parse_steps(&verification_steps()),
+                                    on_failure: match feedback_attempts() {
+                                        0 => VerificationFailurePolicy::FailSession,
+                                        max_attempts => VerificationFailurePolicy::FeedBackToAgent { max_attempts },
+                                    },
+                                };
+                                if let Err(e) = cfg.validate() {
+                                    save_status.set(format!("❌ {}", e));
+                                    return;
+                                }
                                 cfg.updated_at = chrono::Utc::now();
 
                                 // Save to database
@@ -328,6 +549,8 @@ pub fn ClaudeConfigAdmin() -> Element {
                 }
             }
 
+            PromptTemplatePreview {}
+
             // Info Box
             div { class: "mt-6 bg-blue-50 border border-blue-200 rounded-lg p-4",
                 h3 { class: "text-sm font-semibold text-blue-900 mb-2", "Configuration Info" }
@@ -337,8 +560,173 @@ pub fn ClaudeConfigAdmin() -> Element {
                     li { "• GitHub token is only needed for private repositories" }
                     li { "• Claude API key is required to launch Claude Code sessions" }
                     li { "• Workspace folders are named: task-[id_short]-[title_slug]" }
+                    li { "• Sessions that exceed their time, token, cost or file budget are stopped and marked failed" }
+                }
+            }
+        }
+    }
+}
+/// Editor for the default prompt template with a live preview rendered
+/// against a real task, so template errors show up before a session runs.
+#[component]
+fn PromptTemplatePreview() -> Element {
+    let repository = use_context::<Arc<Repository>>();
+    let mut template = use_signal(|| None::<ClaudePromptTemplate>);
+    let mut template_text = use_signal(String::new);
+    let mut tasks = use_signal(Vec::<Task>::new);
+    let mut selected_task_id = use_signal(|| None::<Uuid>);
+    let mut strict = use_signal(|| true);
+    let mut preview = use_signal(|| Ok::<String, String>(String::new()));
+    let mut save_status = use_signal(String::new);
+
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let repo = repo.clone();
+            spawn(async move {
+                match repo.claude_code.get_default_template().await {
+                    Ok(Some(t)) => {
+                        template_text.set(t.template.clone());
+                        template.set(Some(t));
+                    }
+                    Ok(None) => {}
+                    Err(e) => save_status.set(format!("❌ Error loading template: {}", e)),
+                }
+                if let Ok(list) = repo.tasks.list(Default::default()).await {
+                    if selected_task_id().is_none() {
+                        selected_task_id.set(list.first().map(|t| t.id));
+                    }
+                    tasks.set(list);
+                }
+            });
+        }
+    });
+
+    // Re-render whenever the template text, task or strictness changes
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let text = template_text();
+            let strict = strict();
+            let task = selected_task_id()
+                .and_then(|id| tasks.read().iter().find(|t| t.id == id).cloned());
+            let repo = repo.clone();
+            spawn(async move {
+                let Some(task) = task else {
+                    preview.set(Err("Select a task to preview the prompt".to_string()));
+                    return;
+                };
+                let result = match PromptContextBuilder::new(repo).build(&task).await {
+                    Ok(context) => ClaudePromptTemplate::new("preview".to_string(), text)
+                        .render_with(&context, strict)
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(format!("Error building context: {}", e)),
+                };
+                preview.set(result);
+            });
+        }
+    });
+
+    rsx! {
+        div { class: "mt-6 bg-white rounded-lg shadow-md p-6",
+            h2 { class: "text-xl font-semibold mb-4 text-gray-700", "Prompt Template" }
+
+            div { class: "grid grid-cols-1 md:grid-cols-2 gap-4",
+                div {
+                    label { class: "block text-sm font-medium text-gray-700 mb-1", "Template" }
+                    textarea {
+                        class: "w-full h-96 px-3 py-2 border border-gray-300 rounded-md font-mono text-xs focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        value: "{template_text}",
+                        oninput: move |e| template_text.set(e.value())
+                    }
+                    p { class: "text-xs text-gray-500 mt-1",
+                        "Supports {{{{#if}}}}, {{{{#each subtasks}}}}, {{{{else}}}} and filters like {{{{task.title | upper}}}}, {{{{task.description | truncate:200}}}}, {{{{task.due_date | date:short}}}}"
+                    }
+                }
+
+                div {
+                    div { class: "flex items-center gap-4 mb-1",
+                        label { class: "text-sm font-medium text-gray-700", "Preview with task" }
+                        select {
+                            class: "flex-1 px-2 py-1 border border-gray-300 rounded-md text-sm",
+                            onchange: move |e| selected_task_id.set(Uuid::parse_str(&e.value()).ok()),
+                            for task in tasks() {
+                                option {
+                                    value: "{task.id}",
+                                    selected: selected_task_id() == Some(task.id),
+                                    "{task.title}"
+                                }
+                            }
+                        }
+                        label { class: "flex items-center text-sm text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                class: "mr-1",
+                                checked: strict(),
+                                onchange: move |e| strict.set(e.checked())
+                            }
+                            "Strict"
+                        }
+                    }
+                    match preview() {
+                        Ok(rendered) => rsx! {
+                            pre { class: "w-full h-96 overflow-auto px-3 py-2 bg-gray-50 border border-gray-200 rounded-md text-xs whitespace-pre-wrap",
+                                "{rendered}"
+                            }
+                        },
+                        Err(error) => rsx! {
+                            div { class: "w-full px-3 py-2 bg-red-50 border border-red-200 rounded-md text-sm text-red-700",
+                                "{error}"
+                            }
+                        },
+                    }
+                }
+            }
+
+            div { class: "flex items-center justify-between mt-4",
+                div { class: "text-sm",
+                    if !save_status().is_empty() {
+                        span { class: if save_status().starts_with("✅") { "text-green-600" } else { "text-red-600" },
+                            "{save_status}"
+                        }
+                    }
+                }
+
+                button {
+                    r#type: "button",
+                    class: "px-6 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500",
+                    onclick: move |_| {
+                        let repo = repository.clone();
+                        spawn(async move {
+                            let mut t = template().unwrap_or_else(|| {
+                                let mut t = ClaudePromptTemplate::new("default".to_string(), String::new());
+                                t.is_default = true;
+                                t
+                            });
+                            t.set_template(template_text());
+                            if let Err(e) = t.compile() {
+                                save_status.set(format!("❌ Template error: {}", e));
+                                return;
+                            }
+
+                            let result = if template().is_some() {
+                                repo.claude_code.update_template(&t).await
+                            } else {
+                                repo.claude_code.create_template(&t).await
+                            };
+
+                            match result {
+                                Ok(_) => {
+                                    template.set(Some(t));
+                                    save_status.set("✅ Template saved".to_string());
+                                }
+                                Err(e) => save_status.set(format!("❌ Error saving: {}", e)),
+                            }
+                        });
+                    },
+                    "Save Template"
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
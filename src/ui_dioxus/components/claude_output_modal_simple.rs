@@ -58,6 +58,8 @@ pub fn ClaudeOutputModal(
                 } else if sessions.read().is_empty() {
                     div { "No Claude Code sessions found for this task." }
                 } else {
+                    RetryHistory { sessions: sessions.read().clone() }
+                    
                     div {
                         style: "font-family: monospace; white-space: pre-wrap; background: #f5f5f5; 
                                padding: 15px; border-radius: 8px; max-height: 500px; overflow-y: auto;",
@@ -68,7 +70,7 @@ pub fn ClaudeOutputModal(
                                 
                                 div {
                                     style: "font-weight: bold; margin-bottom: 10px;",
-                                    "Session: {session.id}"
+                                    "Attempt {session.attempt} · Session: {session.id}"
                                 }
                                 
                                 div {
@@ -76,6 +78,20 @@ pub fn ClaudeOutputModal(
                                     "Status: {session.status:?}"
                                 }
                                 
+                                if let Some(previous) = session.retry_of {
+                                    div {
+                                        style: "margin-bottom: 10px; color: #666;",
+                                        "Retry of {previous}"
+                                    }
+                                }
+                                
+                                if let Some(error) = &session.error_message {
+                                    div {
+                                        style: "margin-bottom: 10px; color: #c62828;",
+                                        "Error: {error}"
+                                    }
+                                }
+                                
                                 if !session.session_log.is_empty() {
                                     div {
                                         style: "background: #1e1e1e; color: #d4d4d4; padding: 10px; 
@@ -92,4 +108,39 @@ pub fn ClaudeOutputModal(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// One line per attempt, oldest first, for tasks that have been retried.
+#[component]
+fn RetryHistory(sessions: Vec<ClaudeCodeSession>) -> Element {
+    if sessions.len() < 2 {
+        return rsx! {};
+    }
+    let mut attempts = sessions;
+    attempts.sort_by_key(|s| (s.attempt, s.created_at));
+    let rows: Vec<(String, &'static str, String)> = attempts
+        .iter()
+        .map(|s| {
+            let color = if s.status == SessionStatus::Failed { "#c62828" } else { "#2e7d32" };
+            let mut detail = s.started_at.format("%Y-%m-%d %H:%M").to_string();
+            if let Some(error) = &s.error_message {
+                detail.push_str(&format!(" · {}", error));
+            }
+            (format!("#{} {:?}", s.attempt, s.status), color, detail)
+        })
+        .collect();
+
+    rsx! {
+        div {
+            style: "margin-bottom: 16px; padding: 12px; background: #fff8e1; border-radius: 8px;",
+            div { style: "font-weight: bold; margin-bottom: 8px;", "Retry history" }
+            for (label, color, detail) in rows {
+                div {
+                    style: "font-size: 13px; margin-bottom: 4px;",
+                    span { style: "color: {color};", "{label}" }
+                    span { style: "color: #666;", " · {detail}" }
+                }
+            }
+        }
+    }
+}
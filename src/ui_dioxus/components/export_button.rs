@@ -40,6 +40,7 @@ pub fn ExportButton() -> Element {
                     ExportFormat::Json => (service.export_to_json(filters).await, "json"),
                     ExportFormat::Csv => (service.export_to_csv(filters).await, "csv"),
                     ExportFormat::Markdown => (service.export_to_markdown(filters).await, "md"),
+                    ExportFormat::ICalendar => (service.export_to_ics(filters).await, "ics"),
                 };
                 
                 let result = match content_result {
@@ -136,6 +137,18 @@ pub fn ExportButton() -> Element {
                         },
                         "📝 Export as Markdown"
                     }
+                    
+                    button {
+                        style: "display: block; width: 100%; padding: 8px 12px; \
+                               text-align: left; background: none; border: none; \
+                               cursor: pointer; font-size: 14px; \
+                               hover: background: #f3f4f6;",
+                        onclick: {
+                            let export = do_export.clone();
+                            move |_| export(ExportFormat::ICalendar)
+                        },
+                        "📅 Export as iCalendar"
+                    }
                 }
             }
             
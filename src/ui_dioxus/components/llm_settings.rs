@@ -0,0 +1,326 @@
+use dioxus::prelude::*;
+use crate::domain::app_settings::{AppSettings, LlmProviderKind};
+use crate::repository::Repository;
+use crate::services::llm::{LlmConfig, LlmRequest, provider_from_config};
+use std::sync::Arc;
+
+#[component]
+pub fn LlmSettings() -> Element {
+    let repository = use_context::<Arc<Repository>>();
+    let mut settings = use_signal(|| None::<AppSettings>);
+    let mut save_status = use_signal(String::new);
+    let mut test_output = use_signal(String::new);
+    let mut testing = use_signal(|| false);
+
+    // Form fields
+    let mut provider = use_signal(|| LlmProviderKind::Ollama);
+    let mut endpoint = use_signal(String::new);
+    let mut model = use_signal(String::new);
+    let mut temperature = use_signal(|| 0.3f32);
+    let mut max_tokens = use_signal(|| 2048);
+    let mut fixture_directory = use_signal(String::new);
+    let mut embedding_model = use_signal(String::new);
+
+    // Load settings on mount
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let repo = repo.clone();
+            spawn(async move {
+                match repo.app_settings.get_or_create_default().await {
+                    Ok(loaded_settings) => {
+                        provider.set(loaded_settings.llm_provider);
+                        endpoint.set(loaded_settings.llm_endpoint.clone());
+                        model.set(loaded_settings.llm_model.clone());
+                        temperature.set(loaded_settings.llm_temperature);
+                        max_tokens.set(loaded_settings.llm_max_tokens);
+                        fixture_directory.set(loaded_settings.llm_fixture_directory.clone().unwrap_or_default());
+                        embedding_model.set(loaded_settings.llm_embedding_model.clone());
+                        settings.set(Some(loaded_settings));
+                    }
+                    Err(e) => {
+                        save_status.set(format!("Error loading settings: {}", e));
+                    }
+                }
+            });
+        }
+    });
+
+    // The form as settings, whether saved or not
+    let current = move || {
+        settings().map(|mut s| {
+            s.llm_provider = provider();
+            s.llm_endpoint = endpoint();
+            s.llm_model = model();
+            s.llm_temperature = temperature();
+            s.llm_max_tokens = max_tokens();
+            s.llm_fixture_directory = if fixture_directory().is_empty() {
+                None
+            } else {
+                Some(fixture_directory())
+            };
+            s.llm_embedding_model = embedding_model();
+            s
+        })
+    };
+
+    let save_settings = move || {
+        let repo = repository.clone();
+        spawn(async move {
+            if let Some(mut current_settings) = current() {
+                current_settings.update_timestamp();
+
+                match repo.app_settings.update(&current_settings).await {
+                    Ok(_) => {
+                        save_status.set("Language model settings saved successfully!".to_string());
+                        settings.set(Some(current_settings));
+                    }
+                    Err(e) => {
+                        save_status.set(format!("Error saving settings: {}", e));
+                    }
+                }
+            }
+        });
+    };
+
+    // Stream a short answer from the form's settings
+    let test_connection = move || {
+        let Some(form_settings) = current() else {
+            return;
+        };
+        spawn(async move {
+            testing.set(true);
+            test_output.set(String::new());
+            let llm = provider_from_config(&LlmConfig::from_settings(&form_settings));
+            let request = LlmRequest::new("", "Reply with one short sentence confirming you are working.")
+                .with_max_tokens(40);
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+            let run = async move {
+                llm.stream(&request, &mut |chunk| {
+                    let _ = tx.send(chunk.to_string());
+                })
+                .await
+            };
+            let show = async {
+                while let Some(chunk) = rx.recv().await {
+                    test_output.write().push_str(&chunk);
+                }
+            };
+            let (result, _) = tokio::join!(run, show);
+            if let Err(e) = result {
+                test_output.set(format!("Error: {:#}", e));
+            }
+            testing.set(false);
+        });
+    };
+
+    let show_fixture_directory = provider() == LlmProviderKind::Replay;
+
+    rsx! {
+        div { class: "settings-panel",
+            style: "background: white; padding: 30px; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,0.1);",
+
+            h2 {
+                style: "font-size: 1.5rem; font-weight: 600; margin-bottom: 20px;",
+                "Language Model Settings"
+            }
+
+            div { style: "space-y: 20px;",
+                // Provider
+                div { style: "margin-bottom: 24px;",
+                    h3 {
+                        style: "font-size: 1.1rem; font-weight: 600; margin-bottom: 16px; color: #374151;",
+                        "🧠 Provider"
+                    }
+
+                    div { style: "space-y: 16px;",
+                        div {
+                            label {
+                                style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                "Provider"
+                            }
+                            select {
+                                style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                onchange: move |e| {
+                                    let kind = LlmProviderKind::all()
+                                        .into_iter()
+                                        .find(|k| format!("{:?}", k) == e.value())
+                                        .unwrap_or(LlmProviderKind::Ollama);
+                                    // Switching provider resets the provider-specific fields
+                                    endpoint.set(kind.default_endpoint().to_string());
+                                    model.set(kind.default_model().to_string());
+                                    provider.set(kind);
+                                },
+                                for kind in LlmProviderKind::all() {
+                                    option {
+                                        value: "{kind:?}",
+                                        selected: provider() == kind,
+                                        "{kind}"
+                                    }
+                                }
+                            }
+                            p { style: "text-xs text-gray-500 mt-2;",
+                                "API keys are read from LLM_API_KEY, or ANTHROPIC_API_KEY / OPENAI_API_KEY"
+                            }
+                        }
+
+                        if show_fixture_directory {
+                            div {
+                                label {
+                                    style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                    "Fixture Directory"
+                                }
+                                input {
+                                    r#type: "text",
+                                    style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                    value: "{fixture_directory}",
+                                    placeholder: "tests/fixtures/llm",
+                                    oninput: move |e| fixture_directory.set(e.value())
+                                }
+                                p { style: "text-xs text-gray-500 mt-2;",
+                                    "Directory of recorded responses to answer from"
+                                }
+                            }
+                        } else {
+                            div {
+                                label {
+                                    style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                    "Endpoint"
+                                }
+                                input {
+                                    r#type: "text",
+                                    style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                    value: "{endpoint}",
+                                    oninput: move |e| endpoint.set(e.value())
+                                }
+                            }
+
+                            div {
+                                label {
+                                    style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                    "Model"
+                                }
+                                input {
+                                    r#type: "text",
+                                    style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                    value: "{model}",
+                                    oninput: move |e| model.set(e.value())
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Embeddings
+                div { style: "margin-bottom: 24px;",
+                    h3 {
+                        style: "font-size: 1.1rem; font-weight: 600; margin-bottom: 16px; color: #374151;",
+                        "🧩 Embeddings"
+                    }
+
+                    div {
+                        label {
+                            style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                            "Embedding Model"
+                        }
+                        input {
+                            r#type: "text",
+                            style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                            value: "{embedding_model}",
+                            placeholder: "{provider().default_embedding_model()}",
+                            oninput: move |e| embedding_model.set(e.value())
+                        }
+                        p { style: "text-xs text-gray-500 mt-2;",
+                            "Used to group tasks by topic and find similar tasks; leave empty for local TF-IDF"
+                        }
+                    }
+                }
+
+                // Generation
+                div { style: "margin-bottom: 24px;",
+                    h3 {
+                        style: "font-size: 1.1rem; font-weight: 600; margin-bottom: 16px; color: #374151;",
+                        "⚙️ Generation"
+                    }
+
+                    div { style: "display: grid; grid-template-columns: 1fr 1fr; gap: 16px;",
+                        div {
+                            label {
+                                style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                "Temperature"
+                            }
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                max: "2",
+                                step: "0.1",
+                                style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                value: "{temperature}",
+                                oninput: move |e| {
+                                    if let Ok(val) = e.value().parse::<f32>() {
+                                        temperature.set(val);
+                                    }
+                                }
+                            }
+                        }
+
+                        div {
+                            label {
+                                style: "display: block; font-weight: 500; margin-bottom: 8px;",
+                                "Max Tokens"
+                            }
+                            input {
+                                r#type: "number",
+                                min: "16",
+                                max: "32000",
+                                style: "width: 100%; padding: 8px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                                value: "{max_tokens}",
+                                oninput: move |e| {
+                                    if let Ok(val) = e.value().parse::<i32>() {
+                                        max_tokens.set(val);
+                                    }
+                                }
+                            }
+                            p { style: "text-xs text-gray-500 mt-2;",
+                                "Upper limit per response; summaries use less"
+                            }
+                        }
+                    }
+                }
+
+                if !test_output().is_empty() {
+                    div {
+                        style: "padding: 12px; background: #f9fafb; border-radius: 6px; font-family: monospace; font-size: 13px; white-space: pre-wrap; margin-bottom: 16px;",
+                        "{test_output}"
+                    }
+                }
+
+                // Save Button
+                div { style: "display: flex; align-items: center; gap: 12px; margin-top: 24px; padding-top: 24px; border-top: 1px solid #e5e7eb;",
+                    button {
+                        style: "padding: 10px 20px; background: #3b82f6; color: white; border: none; border-radius: 6px; cursor: pointer; font-weight: 500;",
+                        onclick: move |_| save_settings(),
+                        "Save Language Model Settings"
+                    }
+
+                    button {
+                        style: "padding: 10px 20px; background: white; color: #374151; border: 1px solid #e5e7eb; border-radius: 6px; cursor: pointer; font-weight: 500;",
+                        disabled: testing(),
+                        onclick: move |_| test_connection(),
+                        if testing() { "Testing..." } else { "Test" }
+                    }
+
+                    if !save_status().is_empty() {
+                        span {
+                            style: format!(
+                                "color: {}; font-size: 14px;",
+                                if save_status().contains("Error") { "#ef4444" } else { "#10b981" }
+                            ),
+                            "{save_status}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,14 +2,19 @@ pub mod appearance_settings;
 pub mod claude_config_admin;
 pub mod claude_output_modal_simple;
 pub mod general_settings;
+pub mod llm_settings;
+pub mod session_diff_viewer;
 pub mod workspace_settings;
 // pub mod execution_monitor;  // Uses non-existent task_execution module
 // pub mod execution_modal;     // Uses non-existent task_execution module
 pub mod task_editor;
 pub mod task_edit_modal;
 pub mod task_create_modal;
+pub mod task_plan_modal;
 pub mod confirmation_dialog;
 pub mod time_tracker;
+pub mod timesheet;
+pub mod approval_inbox;
 pub mod export_button;
 
 // Tests disabled - need dioxus_ssr crate
@@ -26,10 +31,15 @@ pub use appearance_settings::AppearanceSettings;
 pub use claude_config_admin::ClaudeConfigAdmin;
 pub use claude_output_modal_simple::ClaudeOutputModal;
 pub use general_settings::GeneralSettings;
+pub use llm_settings::LlmSettings;
+pub use session_diff_viewer::SessionDiffViewer;
 pub use task_editor::TaskEditor;
 pub use task_edit_modal::TaskEditModal;
 pub use task_create_modal::TaskCreateModal;
+pub use task_plan_modal::TaskPlanModal;
 pub use confirmation_dialog::ConfirmationDialog;
 pub use workspace_settings::WorkspaceSettings;
 pub use time_tracker::TimeTracker;
+pub use timesheet::TimesheetView;
+pub use approval_inbox::ApprovalInbox;
 pub use export_button::ExportButton;
\ No newline at end of file
@@ -0,0 +1,389 @@
+use dioxus::prelude::*;
+use crate::domain::claude_code::ClaudeCodeSession;
+use crate::domain::session_diff::{DiffLine, DiffLineKind, SessionDiff};
+use crate::repository::Repository;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Changed files and a side-by-side diff for each Claude Code session of a task.
+#[component]
+pub fn SessionDiffViewer(task_id: Uuid) -> Element {
+    let repository = use_context::<Arc<Repository>>();
+    let mut diffs = use_signal(|| Vec::<(ClaudeCodeSession, SessionDiff)>::new());
+    let mut loading = use_signal(|| true);
+    let mut selected_session = use_signal(|| 0usize);
+    let mut selected_file = use_signal(|| None::<String>);
+
+    // Load the captured diff of every session, newest first
+    let _ = use_resource(move || {
+        let repository = repository.clone();
+        async move {
+            loading.set(true);
+            let mut loaded = Vec::new();
+            if let Ok(sessions) = repository.claude_code.get_sessions_by_task(task_id).await {
+                for session in sessions {
+                    if let Ok(Some(diff)) = repository.claude_code.get_session_diff(session.id).await {
+                        loaded.push((session, diff));
+                    }
+                }
+            }
+            diffs.set(loaded);
+            loading.set(false);
+        }
+    });
+
+    if *loading.read() {
+        return rsx! { div { style: "color: #666;", "Loading changes..." } };
+    }
+    if diffs.read().is_empty() {
+        return rsx! { div { style: "color: #999;", "No agent changes captured for this task." } };
+    }
+
+    let all = diffs.read().clone();
+    let index = (*selected_session.read()).min(all.len() - 1);
+    let (session, diff) = all[index].clone();
+    let session_options: Vec<(usize, String)> = all
+        .iter()
+        .enumerate()
+        .map(|(i, (s, d))| {
+            (i, format!(
+                "Attempt {} · {:?} · {}",
+                s.attempt,
+                s.status,
+                d.captured_at.format("%Y-%m-%d %H:%M")
+            ))
+        })
+        .collect();
+    let summary = format!(
+        "{} files changed against {} · +{} −{}",
+        diff.files.len(),
+        diff.base_branch,
+        diff.total_additions(),
+        diff.total_deletions()
+    );
+    let branch = session.branch_name.clone().unwrap_or_default();
+
+    let tree: Vec<TreeRow> = diff
+        .file_tree()
+        .into_iter()
+        .map(|entry| {
+            let indent = format!("{}px", 8 + entry.depth * 14);
+            match entry.file {
+                Some(file) => TreeRow {
+                    indent,
+                    label: entry.name,
+                    stats: if file.binary {
+                        "binary".to_string()
+                    } else {
+                        format!("+{} −{}", file.additions, file.deletions)
+                    },
+                    selected: selected_file.read().as_deref() == Some(file.path.as_str()),
+                    path: Some(file.path),
+                },
+                None => TreeRow {
+                    indent,
+                    label: format!("{}/", entry.name),
+                    stats: String::new(),
+                    selected: false,
+                    path: None,
+                },
+            }
+        })
+        .collect();
+
+    let file_path = selected_file
+        .read()
+        .clone()
+        .or_else(|| diff.files.first().map(|f| f.path.clone()));
+    let file_view = file_path.as_ref().map(|path| {
+        let language = language_for(path);
+        match diff.file_patch(path) {
+            Some(patch) if patch.binary => FileView::Message(format!("{} is a binary file", path)),
+            Some(patch) => FileView::Hunks(
+                patch
+                    .hunks
+                    .iter()
+                    .map(|hunk| {
+                        let rows = hunk
+                            .side_by_side()
+                            .iter()
+                            .map(|row| {
+                                (
+                                    DiffCell::new(row.left.as_ref(), language),
+                                    DiffCell::new(row.right.as_ref(), language),
+                                )
+                            })
+                            .collect();
+                        (hunk.header.clone(), rows)
+                    })
+                    .collect(),
+            ),
+            None if diff.truncated => FileView::Message(format!(
+                "The diff of {} was cut off because the session's patch is too large",
+                path
+            )),
+            None => FileView::Message(format!("No textual changes in {}", path)),
+        }
+    });
+
+    rsx! {
+        div {
+            style: "border: 1px solid #ddd; border-radius: 8px; overflow: hidden;",
+
+            // Session picker and totals
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; gap: 12px;
+                       padding: 10px 12px; background: #f8f9fa; border-bottom: 1px solid #ddd;",
+                select {
+                    style: "padding: 4px 8px; border: 1px solid #ddd; border-radius: 4px; font-size: 13px;",
+                    onchange: move |e| {
+                        if let Ok(i) = e.value().parse::<usize>() {
+                            selected_session.set(i);
+                            selected_file.set(None);
+                        }
+                    },
+                    for (i, label) in session_options {
+                        option { value: "{i}", selected: i == index, "{label}" }
+                    }
+                }
+                div {
+                    style: "font-size: 13px; color: #555;",
+                    if !branch.is_empty() {
+                        code { style: "margin-right: 8px;", "{branch}" }
+                    }
+                    "{summary}"
+                }
+            }
+
+            div {
+                style: "display: grid; grid-template-columns: 240px 1fr; min-height: 200px; max-height: 60vh;",
+
+                // Changed-files tree
+                div {
+                    style: "border-right: 1px solid #ddd; overflow-y: auto; font-size: 13px; padding: 6px 0;",
+                    for row in tree {
+                        if let Some(path) = row.path.clone() {
+                            div {
+                                style: "display: flex; justify-content: space-between; gap: 8px; cursor: pointer;
+                                       padding: 3px 8px 3px {row.indent};
+                                       background: {selected_background(row.selected)};",
+                                onclick: move |_| selected_file.set(Some(path.clone())),
+                                span { style: "overflow: hidden; text-overflow: ellipsis; white-space: nowrap;", "{row.label}" }
+                                span { style: "color: #666; font-family: monospace; white-space: nowrap;", "{row.stats}" }
+                            }
+                        } else {
+                            div {
+                                style: "padding: 3px 8px 3px {row.indent}; color: #666;",
+                                "{row.label}"
+                            }
+                        }
+                    }
+                }
+
+                // Side-by-side diff of the selected file
+                div {
+                    style: "overflow: auto; font-family: monospace; font-size: 12px;",
+                    match file_view {
+                        Some(FileView::Hunks(hunks)) => rsx! {
+                            for (header, rows) in hunks {
+                                div {
+                                    style: "background: #f1f8ff; color: #57606a; padding: 4px 8px;",
+                                    "{header}"
+                                }
+                                table {
+                                    style: "width: 100%; border-collapse: collapse; table-layout: fixed;",
+                                    for (left, right) in rows {
+                                        tr {
+                                            DiffCellView { cell: left }
+                                            DiffCellView { cell: right }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        Some(FileView::Message(message)) => rsx! {
+                            div { style: "padding: 12px; color: #666;", "{message}" }
+                        },
+                        None => rsx! {
+                            div { style: "padding: 12px; color: #666;", "Select a file" }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct TreeRow {
+    indent: String,
+    label: String,
+    stats: String,
+    selected: bool,
+    path: Option<String>,
+}
+
+enum FileView {
+    Hunks(Vec<(String, Vec<(DiffCell, DiffCell)>)>),
+    Message(String),
+}
+
+/// One side of a side-by-side row; an empty cell pads the shorter side.
+#[derive(Clone, PartialEq)]
+struct DiffCell {
+    number: String,
+    background: &'static str,
+    tokens: Vec<(String, &'static str)>,
+}
+
+impl DiffCell {
+    fn new(line: Option<&DiffLine>, language: Language) -> Self {
+        match line {
+            Some(line) => {
+                let (number, background) = match line.kind {
+                    DiffLineKind::Removed => (line.old_line, "#ffebe9"),
+                    DiffLineKind::Added => (line.new_line, "#e6ffec"),
+                    DiffLineKind::Context => (line.new_line.or(line.old_line), "white"),
+                };
+                Self {
+                    number: number.map(|n| n.to_string()).unwrap_or_default(),
+                    background,
+                    tokens: highlight(&line.text, language),
+                }
+            }
+            None => Self {
+                number: String::new(),
+                background: "#f6f8fa",
+                tokens: Vec::new(),
+            },
+        }
+    }
+}
+
+#[component]
+fn DiffCellView(cell: DiffCell) -> Element {
+    rsx! {
+        td {
+            style: "width: 40px; padding: 0 6px; text-align: right; color: #8c959f;
+                   background: {cell.background}; user-select: none; vertical-align: top;",
+            "{cell.number}"
+        }
+        td {
+            style: "padding: 0 8px; white-space: pre-wrap; word-break: break-all;
+                   background: {cell.background}; vertical-align: top;",
+            for (text, color) in cell.tokens {
+                span { style: "color: {color};", "{text}" }
+            }
+        }
+    }
+}
+
+fn selected_background(selected: bool) -> &'static str {
+    if selected { "#e3f2fd" } else { "transparent" }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    CLike,
+    Python,
+    Shell,
+    Plain,
+}
+
+fn language_for(path: &str) -> Language {
+    match path.rsplit('.').next().unwrap_or_default() {
+        "rs" => Language::Rust,
+        "js" | "jsx" | "ts" | "tsx" | "go" | "java" | "c" | "h" | "cpp" | "cs" | "swift" | "kt" => {
+            Language::CLike
+        }
+        "py" => Language::Python,
+        "sh" | "bash" | "toml" | "yml" | "yaml" => Language::Shell,
+        _ => Language::Plain,
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "use",
+    "where", "while",
+];
+const C_LIKE_KEYWORDS: &[&str] = &[
+    "break", "case", "class", "const", "continue", "default", "else", "export", "extends",
+    "false", "for", "func", "function", "if", "import", "interface", "let", "new", "null",
+    "package", "private", "public", "return", "static", "struct", "switch", "this", "true",
+    "type", "var", "void", "while",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "async", "await", "class", "def", "elif", "else", "except", "False", "for",
+    "from", "if", "import", "in", "is", "lambda", "None", "not", "or", "pass", "raise",
+    "return", "True", "try", "while", "with", "yield",
+];
+
+const PLAIN: &str = "#24292f";
+const KEYWORD: &str = "#cf222e";
+const STRING: &str = "#0a3069";
+const COMMENT: &str = "#6e7781";
+const NUMBER: &str = "#0550ae";
+
+/// Split a line into coloured tokens: keywords, strings, numbers and comments.
+fn highlight(text: &str, language: Language) -> Vec<(String, &'static str)> {
+    if language == Language::Plain {
+        return vec![(text.to_string(), PLAIN)];
+    }
+    let (keywords, comment): (&[&str], &str) = match language {
+        Language::Rust => (RUST_KEYWORDS, "//"),
+        Language::CLike => (C_LIKE_KEYWORDS, "//"),
+        Language::Python => (PYTHON_KEYWORDS, "#"),
+        _ => (&[], "#"),
+    };
+
+    let mut tokens: Vec<(String, &'static str)> = Vec::new();
+    let mut push = |token: &str, color: &'static str| match tokens.last_mut() {
+        Some((last, last_color)) if *last_color == color => last.push_str(token),
+        _ => tokens.push((token.to_string(), color)),
+    };
+
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(comment) {
+            push(rest, COMMENT);
+            break;
+        }
+        let len = if c == '"' || (c == '\'' && language != Language::Rust) {
+            // Up to the closing quote, skipping escaped characters
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|&(_, ch)| {
+                    let closes = ch == c && !escaped;
+                    escaped = ch == '\\' && !escaped;
+                    closes
+                })
+                .map(|(i, _)| i + 2)
+                .unwrap_or(rest.len());
+            push(&rest[..end], STRING);
+            end
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let color = if c.is_ascii_digit() {
+                NUMBER
+            } else if keywords.contains(&word) {
+                KEYWORD
+            } else {
+                PLAIN
+            };
+            push(word, color);
+            end
+        } else {
+            push(&rest[..c.len_utf8()], PLAIN);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    tokens
+}
@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 use crate::domain::task::{Task, TaskStatus, Priority};
 use crate::repository::Repository;
+use crate::services::{DuplicateCandidate, TaskService};
 use std::sync::Arc;
 
 #[component]
@@ -22,6 +23,9 @@ pub fn TaskCreateModal(
     // UI state
     let mut saving = use_signal(|| false);
     let mut error = use_signal(|| None::<String>);
+    // The saved task and the existing tasks it looks like, until the user decides
+    let mut possible_duplicates = use_signal(|| None::<(Task, Vec<DuplicateCandidate>)>);
+    let merge_repository = repository.clone();
     
     // Handle save
     let handle_save = move |_| {
@@ -70,9 +74,12 @@ pub fn TaskCreateModal(
                 }
                 
                 // Save to database
-                match repository.tasks.create(&task).await {
-                    Ok(_) => {
-                        on_create.call(task);
+                match TaskService::new(repository.clone()).create(task).await {
+                    Ok(created) if created.duplicates.is_empty() => {
+                        on_create.call(created.task);
+                    }
+                    Ok(created) => {
+                        possible_duplicates.set(Some((created.task, created.duplicates)));
                     }
                     Err(e) => {
                         error.set(Some(format!("Failed to create task: {}", e)));
@@ -281,6 +288,56 @@ pub fn TaskCreateModal(
                         }
                     }
                     
+                    // Likely duplicates of the task just saved
+                    if let Some((new_task, candidates)) = possible_duplicates.read().clone() {
+                        div {
+                            style: "background: #fff8e1; border: 1px solid #ffe082; border-radius: 6px; padding: 12px; margin-top: 20px;",
+                            div {
+                                style: "font-weight: 500; margin-bottom: 8px;",
+                                "This looks like existing tasks"
+                            }
+                            for candidate in candidates {
+                                div {
+                                    key: "{candidate.task.id}",
+                                    style: "display: flex; align-items: center; gap: 10px; padding: 6px 0; font-size: 14px;",
+                                    span { style: "flex: 1;", "{candidate.task.title}" }
+                                    span { style: "color: #666;", "{(candidate.similarity * 100.0).round()}%" }
+                                    button {
+                                        r#type: "button",
+                                        style: "padding: 4px 10px; border: 1px solid #f9a825; background: white;
+                                               border-radius: 4px; cursor: pointer; font-size: 13px;",
+                                        disabled: *saving.read(),
+                                        onclick: {
+                                            let repository = merge_repository.clone();
+                                            let keep_id = candidate.task.id;
+                                            let duplicate_id = new_task.id;
+                                            move |_| {
+                                                let repository = repository.clone();
+                                                spawn(async move {
+                                                    saving.set(true);
+                                                    match TaskService::new(repository).merge(keep_id, duplicate_id).await {
+                                                        Ok(_) => on_cancel.call(()),
+                                                        Err(e) => error.set(Some(format!("Failed to merge tasks: {}", e))),
+                                                    }
+                                                    saving.set(false);
+                                                });
+                                            }
+                                        },
+                                        "Merge into"
+                                    }
+                                }
+                            }
+                            button {
+                                r#type: "button",
+                                style: "margin-top: 8px; padding: 6px 14px; border: 1px solid #ddd; background: white;
+                                       border-radius: 4px; cursor: pointer; font-size: 13px;",
+                                disabled: *saving.read(),
+                                onclick: move |_| on_create.call(new_task.clone()),
+                                "Keep both"
+                            }
+                        }
+                    }
+                    
                     // Buttons
                     div {
                         style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 30px;",
@@ -299,7 +356,7 @@ pub fn TaskCreateModal(
                                    background: #4CAF50; color: white; border-radius: 4px; 
                                    cursor: pointer; font-size: 14px;",
                             onclick: handle_save,
-                            disabled: *saving.read(),
+                            disabled: *saving.read() || possible_duplicates.read().is_some(),
                             if *saving.read() {
                                 "Creating..."
                             } else {
@@ -1,6 +1,9 @@
 use dioxus::prelude::*;
 use crate::domain::task::{Task, TaskStatus, Priority};
 use crate::repository::Repository;
+use crate::services::{SimilarTask, TaskService, TopicService};
+use crate::ui_dioxus::components::session_diff_viewer::SessionDiffViewer;
+use crate::ui_dioxus::components::task_plan_modal::TaskPlanModal;
 use std::sync::Arc;
 
 #[component]
@@ -21,6 +24,32 @@ pub fn TaskEditModal(
     let mut assignee = use_signal(|| task.assignee.clone().unwrap_or_default());
     let mut saving = use_signal(|| false);
     let mut error = use_signal(|| None::<String>);
+    let mut show_changes = use_signal(|| false);
+    let mut show_plan = use_signal(|| false);
+    let mut show_similar = use_signal(|| false);
+    let mut similar_tasks = use_signal(|| None::<Result<Vec<SimilarTask>, String>>);
+    let task_id = task.id;
+    let plan_task = task.clone();
+    let modal_width = if *show_changes.read() { "1200px" } else { "600px" };
+    
+    // Find tasks on the same topic the first time the section is opened
+    let mut find_similar = {
+        let repository = repository.clone();
+        move || {
+            let open = *show_similar.read();
+            show_similar.set(!open);
+            if open || similar_tasks.read().is_some() {
+                return;
+            }
+            let repository = repository.clone();
+            spawn(async move {
+                let settings = repository.app_settings.get_or_create_default().await.unwrap_or_default();
+                let service = TopicService::from_settings(repository, &settings);
+                let result = service.find_similar(task_id, 5).await.map_err(|e| e.to_string());
+                similar_tasks.set(Some(result));
+            });
+        }
+    };
     
     // Handle save
     let handle_save = move |_| {
@@ -68,10 +97,11 @@ pub fn TaskEditModal(
                 
                 task.updated_at = chrono::Utc::now();
                 
-                // Save to database
-                match repository.tasks.update(&task).await {
-                    Ok(_) => {
-                        on_save.call(task);
+                // Save through the service so configured tasks are validated
+                // and follow their workflow
+                match TaskService::new(repository.clone()).update(task).await {
+                    Ok(saved) => {
+                        on_save.call(saved);
                     }
                     Err(e) => {
                         error.set(Some(format!("Failed to save: {}", e)));
@@ -94,7 +124,7 @@ pub fn TaskEditModal(
             // Modal content
             div {
                 style: "background: white; border-radius: 12px; padding: 24px;
-                       width: 90%; max-width: 600px; max-height: 80vh; overflow-y: auto;
+                       width: 90%; max-width: {modal_width}; max-height: 80vh; overflow-y: auto;
                        box-shadow: 0 10px 40px rgba(0, 0, 0, 0.2);",
                 onclick: move |e| e.stop_propagation(),
                 
@@ -116,7 +146,9 @@ pub fn TaskEditModal(
                 if let Some(err) = error.read().as_ref() {
                     div {
                         style: "background: #fee; color: #c00; padding: 10px; border-radius: 4px; margin-bottom: 15px;",
-                        "{err}"
+                        for line in err.split("; ") {
+                            div { "{line}" }
+                        }
                     }
                 }
                 
@@ -279,10 +311,83 @@ pub fn TaskEditModal(
                         }
                     }
                     
+                    // Changes made by Claude Code sessions
+                    div {
+                        style: "margin-bottom: 20px;",
+                        button {
+                            r#type: "button",
+                            style: "background: none; border: none; padding: 0; cursor: pointer;
+                                   font-weight: 500; font-size: 14px; color: #333;",
+                            onclick: move |_| {
+                                let open = *show_changes.read();
+                                show_changes.set(!open);
+                            },
+                            if *show_changes.read() { "▾ Agent changes" } else { "▸ Agent changes" }
+                        }
+                        if *show_changes.read() {
+                            div {
+                                style: "margin-top: 10px;",
+                                SessionDiffViewer { task_id }
+                            }
+                        }
+                    }
+                    
+                    // Tasks on the same topic
+                    div {
+                        style: "margin-bottom: 20px;",
+                        button {
+                            r#type: "button",
+                            style: "background: none; border: none; padding: 0; cursor: pointer;
+                                   font-weight: 500; font-size: 14px; color: #333;",
+                            onclick: move |_| find_similar(),
+                            if *show_similar.read() { "▾ Similar tasks" } else { "▸ Similar tasks" }
+                        }
+                        if *show_similar.read() {
+                            div {
+                                style: "margin-top: 10px; font-size: 14px;",
+                                match similar_tasks.read().as_ref() {
+                                    None => rsx! {
+                                        div { style: "color: #666;", "Finding similar tasks..." }
+                                    },
+                                    Some(Err(e)) => rsx! {
+                                        div { style: "color: #dc3545;", "Error: {e}" }
+                                    },
+                                    Some(Ok(similar)) if similar.is_empty() => rsx! {
+                                        div { style: "color: #666;", "No similar tasks" }
+                                    },
+                                    Some(Ok(similar)) => rsx! {
+                                        for similar_task in similar.clone() {
+                                            div {
+                                                key: "{similar_task.task.id}",
+                                                style: "display: flex; justify-content: space-between; padding: 6px 0;
+                                                       border-bottom: 1px solid #eee;",
+                                                span { "{similar_task.task.title}" }
+                                                span {
+                                                    style: "color: #666;",
+                                                    "{(similar_task.similarity * 100.0).round()}%"
+                                                }
+                                            }
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                    
                     // Buttons
                     div {
                         style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 30px;",
                         
+                        button {
+                            r#type: "button",
+                            style: "margin-right: auto; padding: 8px 16px; border: 1px solid #6f42c1;
+                                   background: white; color: #6f42c1; border-radius: 4px;
+                                   cursor: pointer; font-size: 14px;",
+                            onclick: move |_| show_plan.set(true),
+                            disabled: *saving.read(),
+                            "✨ Plan"
+                        }
+                        
                         button {
                             r#type: "button",
                             style: "padding: 8px 20px; border: 1px solid #ddd; 
@@ -309,5 +414,17 @@ pub fn TaskEditModal(
                 }
             }
         }
+        
+        // Planning saves straight to the database, so the editor closes with the result
+        if *show_plan.read() {
+            TaskPlanModal {
+                task: plan_task,
+                on_committed: move |planned: Task| {
+                    show_plan.set(false);
+                    on_save.call(planned);
+                },
+                on_close: move |_| show_plan.set(false),
+            }
+        }
     }
 }
\ No newline at end of file
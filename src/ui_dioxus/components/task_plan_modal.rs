@@ -0,0 +1,367 @@
+use dioxus::prelude::*;
+use crate::domain::plan::{PlannedTask, TaskPlan};
+use crate::domain::task::Task;
+use crate::repository::Repository;
+use crate::services::agent_backend::backend_from_config;
+use crate::services::command_executor::SystemCommandExecutor;
+use crate::services::planning_service::{AgentPlanGenerator, PlanGenerator, PlanningService};
+use crate::services::summarization::SummarizationService;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Ask a model to break a task down, edit the proposal, then save it.
+#[component]
+pub fn TaskPlanModal(
+    task: Task,
+    on_committed: EventHandler<Task>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let repository = use_context::<Arc<Repository>>();
+    let mut planner = use_signal(|| "llm".to_string());
+    let mut plan = use_signal(|| None::<TaskPlan>);
+    let mut generating = use_signal(|| false);
+    let mut streamed = use_signal(String::new);
+    let mut committing = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let generate = {
+        let repository = repository.clone();
+        let task = task.clone();
+        move |_| {
+            let repository = repository.clone();
+            let task = task.clone();
+            spawn(async move {
+                generating.set(true);
+                error.set(None);
+                streamed.set(String::new());
+                // Show the model's output as it streams in
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+                let propose = async move {
+                    let generator = plan_generator(&repository, &task, &planner.read()).await?;
+                    PlanningService::new(repository.clone(), generator)
+                        .propose_streaming(&task, &mut |chunk| {
+                            let _ = tx.send(chunk.to_string());
+                        })
+                        .await
+                };
+                let show = async {
+                    while let Some(chunk) = rx.recv().await {
+                        streamed.write().push_str(&chunk);
+                    }
+                };
+                let (result, _) = tokio::join!(propose, show);
+                match result {
+                    Ok(proposed) => plan.set(Some(proposed)),
+                    Err(e) => error.set(Some(format!("Planning failed: {:#}", e))),
+                }
+                generating.set(false);
+            });
+        }
+    };
+
+    let commit = {
+        let repository = repository.clone();
+        let task_id = task.id;
+        move |_| {
+            let repository = repository.clone();
+            let Some(reviewed) = plan.read().clone() else {
+                return;
+            };
+            spawn(async move {
+                committing.set(true);
+                error.set(None);
+                let service =
+                    PlanningService::new(repository, Arc::new(SummarizationService::new()));
+                match service.commit(task_id, &reviewed).await {
+                    Ok(committed) => on_committed.call(committed.task),
+                    Err(e) => error.set(Some(format!("Failed to save plan: {:#}", e))),
+                }
+                committing.set(false);
+            });
+        }
+    };
+
+    // Problems with the edited plan block saving
+    let problem = plan.read().as_ref().and_then(|p| {
+        let mut p = p.clone();
+        p.normalize();
+        p.validate().err().map(|e| e.to_string())
+    });
+    let subtasks: Vec<(usize, String)> = plan
+        .read()
+        .as_ref()
+        .map(|p| p.subtasks.iter().cloned().enumerate().collect())
+        .unwrap_or_default();
+    let tasks: Vec<(usize, PlannedTask)> = plan
+        .read()
+        .as_ref()
+        .map(|p| p.tasks.iter().cloned().enumerate().collect())
+        .unwrap_or_default();
+    let total_hours = plan
+        .read()
+        .as_ref()
+        .map(|p| p.total_estimated_hours())
+        .unwrap_or_default();
+    let has_plan = plan.read().is_some();
+    let can_commit = has_plan && problem.is_none() && !*committing.read();
+
+    rsx! {
+        div {
+            style: "position: fixed; top: 0; left: 0; right: 0; bottom: 0;
+                   background: rgba(0, 0, 0, 0.5); z-index: 1001;
+                   display: flex; align-items: center; justify-content: center;",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                style: "background: white; border-radius: 12px; padding: 24px;
+                       width: 90%; max-width: 800px; max-height: 85vh; overflow-y: auto;
+                       box-shadow: 0 10px 40px rgba(0, 0, 0, 0.2);",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 16px;",
+                    h2 { style: "margin: 0; font-size: 22px; font-weight: 600;", "Plan: {task.title}" }
+                    button {
+                        style: "background: none; border: none; font-size: 24px; cursor: pointer;",
+                        onclick: move |_| on_close.call(()),
+                        "×"
+                    }
+                }
+
+                div {
+                    style: "display: flex; gap: 10px; align-items: center; margin-bottom: 16px;",
+                    select {
+                        style: "padding: 8px 12px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
+                        value: "{planner}",
+                        onchange: move |e| planner.set(e.value()),
+                        option { value: "llm", "Language model" }
+                        option { value: "agent", "Agent backend (reads the repository)" }
+                    }
+                    button {
+                        style: "padding: 8px 16px; border: none; background: #6f42c1; color: white;
+                               border-radius: 4px; cursor: pointer; font-size: 14px;",
+                        disabled: *generating.read(),
+                        onclick: generate,
+                        if *generating.read() {
+                            "Planning..."
+                        } else if has_plan {
+                            "Plan again"
+                        } else {
+                            "Generate plan"
+                        }
+                    }
+                }
+
+                if let Some(err) = error.read().as_ref() {
+                    div {
+                        style: "background: #fee; color: #c00; padding: 10px; border-radius: 4px; margin-bottom: 15px;",
+                        "{err}"
+                    }
+                }
+
+                if *generating.read() && !streamed.read().is_empty() {
+                    pre {
+                        style: "background: #f8f9fa; border-radius: 4px; padding: 10px; font-size: 12px;
+                               max-height: 240px; overflow-y: auto; white-space: pre-wrap;",
+                        "{streamed}"
+                    }
+                }
+
+                if has_plan {
+                    // Checklist items for this task
+                    h3 { style: "font-size: 16px; margin: 8px 0;", "Subtasks" }
+                    for (i, subtask) in subtasks {
+                        div {
+                            key: "subtask-{i}",
+                            style: "display: flex; gap: 8px; margin-bottom: 6px;",
+                            input {
+                                r#type: "text",
+                                style: "flex: 1; padding: 6px 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
+                                value: "{subtask}",
+                                oninput: move |e| {
+                                    if let Some(p) = plan.write().as_mut() {
+                                        p.subtasks[i] = e.value();
+                                    }
+                                },
+                            }
+                            button {
+                                style: "padding: 4px 10px; border: 1px solid #ddd; background: white; border-radius: 4px; cursor: pointer;",
+                                onclick: move |_| {
+                                    if let Some(p) = plan.write().as_mut() {
+                                        p.subtasks.remove(i);
+                                    }
+                                },
+                                "Remove"
+                            }
+                        }
+                    }
+                    button {
+                        style: "padding: 4px 10px; border: 1px dashed #bbb; background: white; border-radius: 4px; cursor: pointer; margin-bottom: 16px;",
+                        onclick: move |_| {
+                            if let Some(p) = plan.write().as_mut() {
+                                p.subtasks.push(String::new());
+                            }
+                        },
+                        "+ Subtask"
+                    }
+
+                    // New child tasks
+                    h3 { style: "font-size: 16px; margin: 8px 0;", "New tasks · {total_hours}h estimated" }
+                    for (i, planned) in tasks {
+                        PlannedTaskEditor { key: "task-{i}", index: i, planned, plan }
+                    }
+                    button {
+                        style: "padding: 4px 10px; border: 1px dashed #bbb; background: white; border-radius: 4px; cursor: pointer;",
+                        onclick: move |_| {
+                            if let Some(p) = plan.write().as_mut() {
+                                let mut n = p.tasks.len() + 1;
+                                while p.tasks.iter().any(|t| t.key == format!("task-{}", n)) {
+                                    n += 1;
+                                }
+                                p.tasks.push(PlannedTask {
+                                    key: format!("task-{}", n),
+                                    ..Default::default()
+                                });
+                            }
+                        },
+                        "+ Task"
+                    }
+
+                    if let Some(problem) = problem {
+                        div {
+                            style: "margin-top: 16px; color: #c00; font-size: 14px;",
+                            "{problem}"
+                        }
+                    }
+
+                    div {
+                        style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 24px;",
+                        button {
+                            style: "padding: 8px 20px; border: 1px solid #ddd; background: white; color: #333;
+                                   border-radius: 4px; cursor: pointer; font-size: 14px;",
+                            onclick: move |_| on_close.call(()),
+                            "Discard"
+                        }
+                        button {
+                            style: "padding: 8px 20px; border: none; background: #007bff; color: white;
+                                   border-radius: 4px; cursor: pointer; font-size: 14px;",
+                            disabled: !can_commit,
+                            onclick: commit,
+                            if *committing.read() { "Saving..." } else { "Create tasks" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One editable planned task. Dependencies are entered as comma-separated keys.
+#[component]
+fn PlannedTaskEditor(index: usize, planned: PlannedTask, plan: Signal<Option<TaskPlan>>) -> Element {
+    let mut plan = plan;
+    let hours = planned.estimated_hours.map(|h| h.to_string()).unwrap_or_default();
+    let depends_on = planned.depends_on.join(", ");
+    let key = planned.key.clone();
+
+    rsx! {
+        div {
+            style: "border: 1px solid #e0e0e0; border-radius: 8px; padding: 12px; margin-bottom: 10px;",
+            div {
+                style: "display: grid; grid-template-columns: 1fr 90px auto; gap: 8px; align-items: center;",
+                input {
+                    r#type: "text",
+                    style: "padding: 6px 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px; font-weight: 500;",
+                    value: "{planned.title}",
+                    placeholder: "Title",
+                    oninput: move |e| {
+                        if let Some(p) = plan.write().as_mut() {
+                            p.tasks[index].title = e.value();
+                        }
+                    },
+                }
+                input {
+                    r#type: "number",
+                    style: "padding: 6px 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
+                    value: "{hours}",
+                    placeholder: "Hours",
+                    min: "0",
+                    step: "0.5",
+                    oninput: move |e| {
+                        if let Some(p) = plan.write().as_mut() {
+                            p.tasks[index].estimated_hours = e.value().parse::<f32>().ok();
+                        }
+                    },
+                }
+                button {
+                    style: "padding: 4px 10px; border: 1px solid #ddd; background: white; border-radius: 4px; cursor: pointer;",
+                    onclick: move |_| {
+                        if let Some(p) = plan.write().as_mut() {
+                            p.remove_task(&key);
+                        }
+                    },
+                    "Remove"
+                }
+            }
+            textarea {
+                style: "width: 100%; margin-top: 8px; padding: 6px 10px; border: 1px solid #ddd; border-radius: 4px;
+                       font-size: 13px; min-height: 50px; resize: vertical; font-family: inherit;",
+                value: "{planned.description}",
+                placeholder: "Description",
+                oninput: move |e| {
+                    if let Some(p) = plan.write().as_mut() {
+                        p.tasks[index].description = e.value();
+                    }
+                },
+            }
+            div {
+                style: "display: flex; gap: 8px; align-items: center; margin-top: 8px; font-size: 13px; color: #555;",
+                code { "{planned.key}" }
+                span { "after" }
+                input {
+                    r#type: "text",
+                    style: "flex: 1; padding: 4px 8px; border: 1px solid #ddd; border-radius: 4px; font-size: 13px;",
+                    value: "{depends_on}",
+                    placeholder: "keys of tasks that must finish first",
+                    oninput: move |e| {
+                        if let Some(p) = plan.write().as_mut() {
+                            p.tasks[index].depends_on = e
+                                .value()
+                                .split(',')
+                                .map(|k| k.trim().to_string())
+                                .filter(|k| !k.is_empty())
+                                .collect();
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// The language model chosen in the settings, or the agent backend
+/// configured for the task, run in the project's working directory.
+async fn plan_generator(
+    repository: &Arc<Repository>,
+    task: &Task,
+    planner: &str,
+) -> anyhow::Result<Arc<dyn PlanGenerator>> {
+    if planner != "agent" {
+        let settings = repository.app_settings.get_or_create_default().await?;
+        return Ok(Arc::new(SummarizationService::from_settings(&settings)));
+    }
+    let config = repository
+        .claude_code
+        .get_config()
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Claude Code is not configured"))?;
+    let work_dir = config
+        .working_directory
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("No working directory configured"))?;
+    let backend = backend_from_config(
+        config.backend_for_task(task.id),
+        Arc::new(SystemCommandExecutor),
+    );
+    Ok(Arc::new(AgentPlanGenerator::new(backend, PathBuf::from(work_dir))))
+}
@@ -85,18 +85,31 @@ pub fn TimeTracker(
     let handle_pause = {
         let service = service.clone();
         move |_| {
-            match service.pause_tracking(task_id) {
-                Ok(_) => {
-                    is_tracking.set(false);
-                    timer_active.set(false);
-                }
-                Err(e) => {
-                    eprintln!("Failed to pause tracking: {}", e);
+            let service = service.clone();
+            spawn(async move {
+                match service.pause_tracking(task_id).await {
+                    Ok(_) => {
+                        is_tracking.set(false);
+                        timer_active.set(false);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to pause tracking: {}", e);
+                    }
                 }
-            }
+            });
         }
     };
     
+    // Total time for the task, reloaded whenever the timer starts or stops
+    let total_time = use_resource({
+        let service = service.clone();
+        move || {
+            let service = service.clone();
+            let _ = is_tracking();
+            async move { service.get_total_time(task_id).await.unwrap_or_else(|_| Duration::zero()) }
+        }
+    });
+    
     // Format duration as HH:MM:SS
     let format_duration = |d: Duration| -> String {
         let total_seconds = d.num_seconds();
@@ -141,7 +154,7 @@ pub fn TimeTracker(
             div {
                 style: "margin-left: auto; color: #6b7280; font-size: 14px;",
                 {
-                    let total = service.get_total_time(task_id);
+                    let total = total_time.read().unwrap_or_else(Duration::zero);
                     format!("Total: {}", format_duration(total))
                 }
             }
@@ -184,6 +197,7 @@ mod tests {
             position: crate::domain::task::Position { x: 0.0, y: 0.0 },
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             metadata: HashMap::new(),
             subtasks: Vec::new(),
             sort_order: 0,
@@ -0,0 +1,150 @@
+use dioxus::prelude::*;
+use crate::domain::resource::Resource;
+use crate::domain::time_entry::Timesheet;
+use crate::repository::Repository;
+use crate::services::{ExportService, TimeTrackingService};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[component]
+pub fn TimesheetView() -> Element {
+    let repository = use_context::<Arc<Repository>>();
+    let service = use_context::<Arc<TimeTrackingService>>();
+
+    let mut week = use_signal(|| Timesheet::week_of(Utc::now().date_naive()));
+    let mut resource_id = use_signal(|| None::<Uuid>);
+    let mut export_message = use_signal(|| None::<String>);
+
+    let resources = use_resource({
+        let repository = repository.clone();
+        move || {
+            let repository = repository.clone();
+            async move { repository.resources.list_all().await.unwrap_or_default() }
+        }
+    });
+
+    let timesheet = use_resource({
+        let service = service.clone();
+        move || {
+            let service = service.clone();
+            let (resource_id, week) = (resource_id(), week());
+            async move { service.weekly_timesheet(resource_id, week).await }
+        }
+    });
+
+    let export_csv = {
+        let repository = repository.clone();
+        move |_| {
+            let service = ExportService::new(repository.clone());
+            let (resource_id, week) = (resource_id(), week());
+            spawn(async move {
+                let message = match service.export_timesheet_to_csv(resource_id, week).await {
+                    Ok(content) => {
+                        let filename = format!("timesheet_{}.csv", week.format("%Y%m%d"));
+                        match std::fs::write(&filename, content) {
+                            Ok(_) => format!("✅ Exported to {}", filename),
+                            Err(e) => format!("Failed to save file: {}", e),
+                        }
+                    }
+                    Err(e) => format!("Export failed: {}", e),
+                };
+                export_message.set(Some(message));
+            });
+        }
+    };
+
+    let resource_list: Vec<Resource> = resources.read().clone().unwrap_or_default();
+
+    rsx! {
+        div {
+            style: "padding: 20px; display: flex; flex-direction: column; gap: 16px;",
+
+            // Week navigation and filters
+            div {
+                style: "display: flex; align-items: center; gap: 10px;",
+                button {
+                    style: "padding: 6px 12px; border: 1px solid #d1d5db; border-radius: 4px; background: white; cursor: pointer;",
+                    onclick: move |_| week.set(week() - Duration::days(7)),
+                    "◀"
+                }
+                h2 {
+                    style: "margin: 0; font-size: 18px;",
+                    {format!("Week of {}", week().format("%b %-d, %Y"))}
+                }
+                button {
+                    style: "padding: 6px 12px; border: 1px solid #d1d5db; border-radius: 4px; background: white; cursor: pointer;",
+                    onclick: move |_| week.set(week() + Duration::days(7)),
+                    "▶"
+                }
+                select {
+                    style: "margin-left: auto; padding: 6px; border-radius: 4px;",
+                    onchange: move |e| resource_id.set(Uuid::parse_str(&e.value()).ok()),
+                    option { value: "", "Everyone" }
+                    for resource in resource_list {
+                        option {
+                            value: "{resource.id}",
+                            selected: resource_id() == Some(resource.id),
+                            "{resource.name}"
+                        }
+                    }
+                }
+                button {
+                    style: "padding: 6px 12px; background: #6366f1; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                    onclick: export_csv,
+                    "📊 Export CSV"
+                }
+            }
+
+            if let Some(message) = export_message() {
+                div { style: "color: #374151; font-size: 14px;", "{message}" }
+            }
+
+            match &*timesheet.read() {
+                Some(Ok(sheet)) => rsx! { TimesheetTable { sheet: sheet.clone() } },
+                Some(Err(e)) => rsx! { div { style: "color: #ef4444;", "Failed to load timesheet: {e}" } },
+                None => rsx! { div { "Loading..." } },
+            }
+        }
+    }
+}
+
+#[component]
+fn TimesheetTable(sheet: Timesheet) -> Element {
+    let hours = |h: f64| if h == 0.0 { String::new() } else { format!("{:.2}", h) };
+    let cell = "padding: 8px; border-bottom: 1px solid #e5e7eb; text-align: right;";
+
+    rsx! {
+        table {
+            style: "width: 100%; border-collapse: collapse; font-size: 14px;",
+            thead {
+                tr {
+                    th { style: "padding: 8px; text-align: left; border-bottom: 2px solid #d1d5db;", "Task" }
+                    for day in sheet.days() {
+                        th { style: "padding: 8px; text-align: right; border-bottom: 2px solid #d1d5db;", {day.format("%a %-d").to_string()} }
+                    }
+                    th { style: "padding: 8px; text-align: right; border-bottom: 2px solid #d1d5db;", "Total" }
+                }
+            }
+            tbody {
+                for row in sheet.rows.iter() {
+                    tr {
+                        key: "{row.task_id}",
+                        td { style: "padding: 8px; border-bottom: 1px solid #e5e7eb;", "{row.task_title}" }
+                        for h in row.hours {
+                            td { style: cell, {hours(h)} }
+                        }
+                        td { style: "{cell} font-weight: 600;", {hours(row.total())} }
+                    }
+                }
+                tr {
+                    td { style: "padding: 8px; font-weight: 600;", "Total" }
+                    for h in sheet.day_totals() {
+                        td { style: "padding: 8px; text-align: right; font-weight: 600;", {hours(h)} }
+                    }
+                    td { style: "padding: 8px; text-align: right; font-weight: 600;", {hours(sheet.total())} }
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,10 @@ use dioxus::prelude::*;
 use crate::repository::Repository;
 use crate::repository::task_repository::TaskFilters;
 use crate::domain::task::{Task, TaskStatus, Priority};
+use crate::domain::claude_code::AgentUsage;
+use std::collections::HashMap;
 use std::sync::Arc;
+use uuid::Uuid;
 
 #[component]
 pub fn Dashboard() -> Element {
@@ -24,6 +27,13 @@ pub fn Dashboard() -> Element {
     let mut medium_count = use_signal(|| 0);
     let mut low_count = use_signal(|| 0);
     
+    // Agent spend, highest cost first: (label, usage). Goals are kept by
+    // id, so two goals with the same title stay apart
+    let mut task_spend = use_signal(|| Vec::<(String, AgentUsage)>::new());
+    let mut goal_spend = use_signal(|| Vec::<(Option<Uuid>, AgentUsage)>::new());
+    let mut goal_titles = use_signal(HashMap::<Uuid, String>::new);
+    let mut total_spend = use_signal(AgentUsage::default);
+    
     // Load tasks and calculate statistics
     use_effect({
         let repo = repository.clone();
@@ -74,6 +84,36 @@ pub fn Dashboard() -> Element {
                             }
                         }
                         
+                        // Roll agent usage up per task and per goal
+                        if let Ok(usage_by_task) = repo.claude_code.get_usage_by_task().await {
+                            let titles: HashMap<_, _> = repo.goals.list_all().await
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|g| (g.id, g.title))
+                                .collect();
+                            
+                            let mut total = AgentUsage::default();
+                            let mut per_task = Vec::new();
+                            let mut per_goal: HashMap<Option<Uuid>, AgentUsage> = HashMap::new();
+                            for task in &task_list {
+                                if let Some(usage) = usage_by_task.get(&task.id) {
+                                    total.add(usage);
+                                    per_task.push((task.title.clone(), *usage));
+                                    per_goal.entry(task.goal_id).or_default().add(usage);
+                                }
+                            }
+                            
+                            per_task.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+                            per_task.truncate(10);
+                            let mut per_goal: Vec<_> = per_goal.into_iter().collect();
+                            per_goal.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+                            
+                            task_spend.set(per_task);
+                            goal_spend.set(per_goal);
+                            goal_titles.set(titles);
+                            total_spend.set(total);
+                        }
+                        
                         tasks.set(task_list);
                         total_count.set(total);
                         todo_count.set(todo);
@@ -273,6 +313,46 @@ pub fn Dashboard() -> Element {
                 }
             }
             
+            // Agent spend per goal and per task
+            div {
+                style: "background: white; border-radius: 8px; padding: 20px; margin-top: 20px;",
+                
+                h3 { style: "margin: 0 0 20px 0; display: flex; align-items: center; gap: 8px;",
+                    "🤖 Agent Spend"
+                    span {
+                        style: "font-size: 14px; font-weight: normal; color: #666; margin-left: auto;",
+                        {format!("${:.2} · {} tokens · {} files changed",
+                            total_spend().cost_usd, total_spend().total_tokens(), total_spend().files_changed)}
+                    }
+                }
+                
+                if task_spend().is_empty() {
+                    div {
+                        style: "text-align: center; padding: 20px; color: #9ca3af;",
+                        "No agent sessions yet"
+                    }
+                } else {
+                    div {
+                        style: "display: grid; grid-template-columns: 1fr 1fr; gap: 20px;",
+                        SpendTable {
+                            title: "By Goal",
+                            rows: goal_spend()
+                                .into_iter()
+                                .map(|(goal_id, usage)| {
+                                    let label = match goal_id {
+                                        Some(id) => goal_titles.read().get(&id).cloned()
+                                            .unwrap_or_else(|| "Unknown goal".to_string()),
+                                        None => "No goal".to_string(),
+                                    };
+                                    (label, usage)
+                                })
+                                .collect(),
+                        }
+                        SpendTable { title: "Top Tasks", rows: task_spend() }
+                    }
+                }
+            }
+            
             // Recent tasks and upcoming due dates
             div {
                 style: "display: grid; grid-template-columns: 1fr 1fr; gap: 20px; margin-top: 20px;",
@@ -307,4 +387,23 @@ pub fn Dashboard() -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[component]
+fn SpendTable(title: &'static str, rows: Vec<(String, AgentUsage)>) -> Element {
+    rsx! {
+        div {
+            h4 { style: "margin: 0 0 10px 0; font-size: 14px; color: #666;", "{title}" }
+            for (label, usage) in rows {
+                div {
+                    style: "display: flex; justify-content: space-between; padding: 6px 0; border-bottom: 1px solid #f0f0f0; font-size: 14px;",
+                    span { style: "overflow: hidden; text-overflow: ellipsis; white-space: nowrap;", "{label}" }
+                    span {
+                        style: "color: #666; white-space: nowrap; margin-left: 10px;",
+                        {format!("${:.2} · {} tok", usage.cost_usd, usage.total_tokens())}
+                    }
+                }
+            }
+        }
+    }
+}
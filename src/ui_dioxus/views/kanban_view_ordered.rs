@@ -1,12 +1,66 @@
 use dioxus::prelude::*;
 use crate::domain::task::{Task, TaskStatus};
+use crate::domain::task_config::TaskConfiguration;
 use crate::ui_dioxus::state_simple::sample_tasks;
 use crate::ui_dioxus::components::TaskEditModal;
 use crate::repository::Repository;
 use crate::repository::task_repository::TaskFilters;
+use crate::services::TaskService;
 use uuid::Uuid;
 use std::sync::Arc;
 
+/// A board column: a fixed status, or a state of the selected configuration.
+#[derive(Debug, Clone, PartialEq)]
+struct BoardColumn {
+    key: String,
+    title: String,
+    color: String,
+}
+
+const STATUS_COLUMNS: [(TaskStatus, &str, &str, &str); 5] = [
+    (TaskStatus::Todo, "todo", "Todo", "#808080"),
+    (TaskStatus::InProgress, "in_progress", "In Progress", "#2196F3"),
+    (TaskStatus::Review, "review", "Review", "#FF9800"),
+    (TaskStatus::Done, "done", "Done", "#4CAF50"),
+    (TaskStatus::Blocked, "blocked", "Blocked", "#f44336"),
+];
+
+fn board_columns(config: Option<&TaskConfiguration>) -> Vec<BoardColumn> {
+    match config {
+        Some(config) => config
+            .state_machine
+            .ordered_states()
+            .into_iter()
+            .map(|state| BoardColumn {
+                key: state.name.clone(),
+                title: state.display_name.clone(),
+                color: state.color.clone(),
+            })
+            .collect(),
+        None => STATUS_COLUMNS
+            .iter()
+            .map(|(_, key, title, color)| BoardColumn {
+                key: key.to_string(),
+                title: title.to_string(),
+                color: color.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// The column `task` sits in: its workflow state on a configuration's
+/// board, its status otherwise.
+fn column_of(task: &Task, config: Option<&TaskConfiguration>) -> String {
+    match config {
+        Some(config) => config.current_state(task),
+        None => STATUS_COLUMNS
+            .iter()
+            .find(|(status, ..)| *status == task.status)
+            .map(|(_, key, ..)| key.to_string())
+            .unwrap_or_default(),
+    }
+}
+
 #[component]
 pub fn KanbanViewOrdered() -> Element {
     // Initialize repository once using use_resource
@@ -15,10 +69,13 @@ pub fn KanbanViewOrdered() -> Element {
     
     // Track dragging state
     let mut dragging_task = use_signal(|| None::<Uuid>);
-    let mut drag_over_status = use_signal(|| None::<TaskStatus>);
+    let mut drag_over_status = use_signal(|| None::<String>);
     let mut drag_over_position = use_signal(|| None::<usize>); // Position in the column where we're hovering
     let mut mouse_position = use_signal(|| (0.0, 0.0));
     let mut editing_task = use_signal(|| None::<Task>);
+    let mut configurations = use_signal(Vec::<TaskConfiguration>::new);
+    let mut board_config = use_signal(|| None::<TaskConfiguration>);
+    let mut move_error = use_signal(|| None::<String>);
     
     // Load repository and tasks asynchronously
     let _ = use_resource(move || async move {
@@ -69,15 +126,18 @@ pub fn KanbanViewOrdered() -> Element {
             }
         };
         
+        configurations.set(repo.task_configs.list_all().await.unwrap_or_default());
         repository.set(Some(repo));
         tasks.set(loaded_tasks);
     });
     
     // Helper function to get tasks for a specific column, sorted by sort_order
-    let get_column_tasks = move |status: TaskStatus| -> Vec<Task> {
+    let get_column_tasks = move |key: &str| -> Vec<Task> {
+        let config = board_config.read();
         let mut column_tasks: Vec<Task> = tasks.read()
             .iter()
-            .filter(|t| t.status == status)
+            .filter(|t| config.as_ref().is_none_or(|c| t.configuration_id == Some(c.id)))
+            .filter(|t| column_of(t, config.as_ref()) == key)
             .cloned()
             .collect();
         column_tasks.sort_by_key(|t| t.sort_order);
@@ -85,12 +145,13 @@ pub fn KanbanViewOrdered() -> Element {
     };
     
     // Helper to recalculate sort_order values when reordering
-    let mut recalculate_sort_orders = move |status: TaskStatus, moved_task_id: Uuid, new_position: usize| {
+    let mut recalculate_sort_orders = move |key: String, moved_task_id: Uuid, new_position: usize| {
+        let config = board_config();
         tasks.with_mut(|tasks| {
             // Get all tasks in this column
             let mut column_tasks: Vec<&mut Task> = tasks
                 .iter_mut()
-                .filter(|t| t.status == status)
+                .filter(|t| column_of(t, config.as_ref()) == key)
                 .collect();
             
             // Sort by current sort_order
@@ -134,24 +195,43 @@ pub fn KanbanViewOrdered() -> Element {
             // Global mouse up handler
             onmouseup: move |_| {
                 // If we were dragging and have a target position, reorder the cards
-                if let (Some(task_id), Some(status), Some(position)) = 
-                    (*dragging_task.read(), *drag_over_status.read(), *drag_over_position.read()) {
+                if let (Some(task_id), Some(key), Some(position)) = 
+                    (dragging_task(), drag_over_status(), drag_over_position()) {
                     
-                    // First update the task's status if it changed
-                    let old_status = tasks.read().iter()
-                        .find(|t| t.id == task_id)
-                        .map(|t| t.status);
+                    let config = board_config();
+                    let moved = tasks.read().iter()
+                        .find(|t| t.id == task_id && column_of(t, config.as_ref()) != key)
+                        .cloned();
                     
-                    if old_status != Some(status) {
-                        tasks.with_mut(|tasks| {
-                            if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-                                task.status = status;
+                    match (moved, repository()) {
+                        // Moving between columns goes through the task's
+                        // state machine, which may refuse it
+                        (Some(mut task), Some(repo)) => {
+                            match &config {
+                                Some(_) => task.workflow_state = Some(key.clone()),
+                                None => if let Some((status, ..)) = STATUS_COLUMNS.iter().find(|(_, k, ..)| *k == key) {
+                                    task.status = *status;
+                                },
                             }
-                        });
+                            spawn(async move {
+                                match TaskService::new(repo.clone()).update(task).await {
+                                    // A move that needs sign-off leaves the card where it was
+                                    Ok(saved) if column_of(&saved, config.as_ref()) != key => {
+                                        move_error.set(Some(format!("'{}' is awaiting approval to move", saved.title)));
+                                    }
+                                    Ok(_) => {
+                                        move_error.set(None);
+                                        if let Ok(loaded) = repo.tasks.list(TaskFilters::default()).await {
+                                            tasks.set(loaded);
+                                        }
+                                        recalculate_sort_orders(key, task_id, position);
+                                    }
+                                    Err(e) => move_error.set(Some(format!("Can't move task: {}", e))),
+                                }
+                            });
+                        }
+                        _ => recalculate_sort_orders(key, task_id, position),
                     }
-                    
-                    // Then recalculate sort orders
-                    recalculate_sort_orders(status, task_id, position);
                 }
                 
                 // Clear drag state
@@ -161,7 +241,33 @@ pub fn KanbanViewOrdered() -> Element {
             },
             
             h2 { "Kanban Board with Ordering" }
-            p { "Drag cards to reorder them within columns or move between columns" }
+            div {
+                style: "display: flex; align-items: center; gap: 12px; margin-bottom: 12px;",
+                p { style: "margin: 0;", "Drag cards to reorder them within columns or move between columns" }
+                select {
+                    style: "margin-left: auto; padding: 6px; border-radius: 4px;",
+                    onchange: move |e| {
+                        let id = Uuid::parse_str(&e.value()).ok();
+                        board_config.set(configurations.read().iter().find(|c| Some(c.id) == id).cloned());
+                        move_error.set(None);
+                    },
+                    option { value: "", "All tasks by status" }
+                    for config in configurations() {
+                        option {
+                            value: "{config.id}",
+                            selected: board_config.read().as_ref().map(|c| c.id) == Some(config.id),
+                            "{config.name} workflow"
+                        }
+                    }
+                }
+            }
+            
+            if let Some(error) = move_error() {
+                div {
+                    style: "margin-bottom: 12px; padding: 8px 12px; background: #fee2e2; color: #b91c1c; border-radius: 4px;",
+                    "{error}"
+                }
+            }
             
             // Show loading if repository not ready
             if repository().is_none() {
@@ -175,54 +281,17 @@ pub fn KanbanViewOrdered() -> Element {
                     style: "display: flex; gap: 15px; height: calc(100vh - 100px); overflow-x: auto;",
                     
                     // Render each column
-                    KanbanColumnOrdered {
-                        status: TaskStatus::Todo,
-                        tasks: get_column_tasks(TaskStatus::Todo),
-                        dragging_task: dragging_task,
-                        drag_over_status: drag_over_status,
-                        drag_over_position: drag_over_position,
-                        mouse_position: mouse_position,
-                        editing_task: editing_task,
-                    }
-                    
-                    KanbanColumnOrdered {
-                        status: TaskStatus::InProgress,
-                        tasks: get_column_tasks(TaskStatus::InProgress),
-                        dragging_task: dragging_task,
-                        drag_over_status: drag_over_status,
-                        drag_over_position: drag_over_position,
-                        mouse_position: mouse_position,
-                        editing_task: editing_task,
-                    }
-                    
-                    KanbanColumnOrdered {
-                        status: TaskStatus::Review,
-                        tasks: get_column_tasks(TaskStatus::Review),
-                        dragging_task: dragging_task,
-                        drag_over_status: drag_over_status,
-                        drag_over_position: drag_over_position,
-                        mouse_position: mouse_position,
-                        editing_task: editing_task,
-                    }
-                    
-                    KanbanColumnOrdered {
-                        status: TaskStatus::Done,
-                        tasks: get_column_tasks(TaskStatus::Done),
-                        dragging_task: dragging_task,
-                        drag_over_status: drag_over_status,
-                        drag_over_position: drag_over_position,
-                        mouse_position: mouse_position,
-                        editing_task: editing_task,
-                    }
-                    
-                    KanbanColumnOrdered {
-                        status: TaskStatus::Blocked,
-                        tasks: get_column_tasks(TaskStatus::Blocked),
-                        dragging_task: dragging_task,
-                        drag_over_status: drag_over_status,
-                        drag_over_position: drag_over_position,
-                        mouse_position: mouse_position,
-                        editing_task: editing_task,
+                    for column in board_columns(board_config.read().as_ref()) {
+                        KanbanColumnOrdered {
+                            key: "{column.key}",
+                            tasks: get_column_tasks(&column.key),
+                            column: column,
+                            dragging_task: dragging_task,
+                            drag_over_status: drag_over_status,
+                            drag_over_position: drag_over_position,
+                            mouse_position: mouse_position,
+                            editing_task: editing_task,
+                        }
                     }
                 }
             }
@@ -284,35 +353,19 @@ pub fn KanbanViewOrdered() -> Element {
 
 #[component]
 fn KanbanColumnOrdered(
-    status: TaskStatus,
+    column: BoardColumn,
     tasks: Vec<Task>,
     dragging_task: Signal<Option<Uuid>>,
-    drag_over_status: Signal<Option<TaskStatus>>,
+    drag_over_status: Signal<Option<String>>,
     drag_over_position: Signal<Option<usize>>,
     mouse_position: Signal<(f64, f64)>,
     editing_task: Signal<Option<Task>>,
 ) -> Element {
-    let column_name = match status {
-        TaskStatus::Todo => "Todo",
-        TaskStatus::InProgress => "In Progress",
-        TaskStatus::Review => "Review",
-        TaskStatus::Done => "Done",
-        TaskStatus::Blocked => "Blocked",
-        _ => "Unknown",
-    };
-    
-    let column_color = match status {
-        TaskStatus::Todo => "#808080",
-        TaskStatus::InProgress => "#2196F3",
-        TaskStatus::Review => "#FF9800",
-        TaskStatus::Done => "#4CAF50",
-        TaskStatus::Blocked => "#f44336",
-        _ => "#999999",
-    };
+    let BoardColumn { key: status, title: column_name, color: column_color } = column;
     
     let is_drag_over = drag_over_status.read().as_ref() == Some(&status);
     let background = if is_drag_over { "#e8f5e9" } else { "white" };
-    let border_color = if is_drag_over { "#4CAF50" } else { column_color };
+    let border_color = if is_drag_over { "#4CAF50" } else { column_color.as_str() };
     
     rsx! {
         div {
@@ -342,14 +395,17 @@ fn KanbanColumnOrdered(
                 style: "overflow-y: auto; max-height: calc(100vh - 200px); 
                        min-height: 100px; position: relative;",
                 
-                onmouseenter: move |_| {
-                    if dragging_task.read().is_some() {
-                        drag_over_status.set(Some(status));
+                onmouseenter: {
+                    let status = status.clone();
+                    move |_| {
+                        if dragging_task.read().is_some() {
+                            drag_over_status.set(Some(status.clone()));
+                        }
                     }
                 },
                 
                 onmouseleave: move |_| {
-                    if *drag_over_status.read() == Some(status) {
+                    if is_drag_over {
                         drag_over_status.set(None);
                         drag_over_position.set(None);
                     }
@@ -375,7 +431,7 @@ fn KanbanColumnOrdered(
                 {tasks.iter().enumerate().map(|(index, task)| {
                     rsx! {
                         // Drop zone before this card
-                        {if dragging_task.read().is_some() && *drag_over_status.read() == Some(status) {
+                        {if dragging_task.read().is_some() && is_drag_over {
                             rsx! {
                                 DropZone {
                                     index: index,
@@ -401,7 +457,7 @@ fn KanbanColumnOrdered(
                 })}
                 
                 // Drop zone after the last card
-                {if dragging_task.read().is_some() && *drag_over_status.read() == Some(status) {
+                {if dragging_task.read().is_some() && is_drag_over {
                     rsx! {
                         DropZone {
                             index: tasks.len(),
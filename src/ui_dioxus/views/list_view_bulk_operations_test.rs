@@ -44,6 +44,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        };
         
         let task2 = task1.clone();
@@ -96,6 +97,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,            };
             repo.tasks.create(&task).await.unwrap();
             tasks.push(task);
@@ -153,6 +155,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,            };
             repo.tasks.create(&task).await.unwrap();
         }
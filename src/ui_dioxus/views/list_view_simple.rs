@@ -3,10 +3,15 @@ use dioxus::events::Key;
 use crate::repository::Repository;
 use crate::repository::task_repository::TaskFilters;
 use crate::domain::task::{Task, TaskStatus, Priority};
+use crate::domain::metadata_value::MetadataValue;
+use crate::domain::task_config::MetadataFieldConfig;
+use crate::repository::task_metadata_repository::MetadataQuery;
 use crate::ui_dioxus::components::{TaskEditModal, TaskCreateModal, ExportButton};
-use crate::services::TimeTrackingService;
+use crate::services::{TaskService, TimeTrackingService};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use chrono::Local;
+use uuid::Uuid;
 
 #[component]
 pub fn ListView() -> Element {
@@ -23,6 +28,16 @@ pub fn ListView() -> Element {
     let mut sort_by = use_signal(|| "created_desc".to_string());
     let mut selected_tasks = use_signal(|| std::collections::HashSet::<uuid::Uuid>::new());
     let mut bulk_mode = use_signal(|| false);
+    let mut metadata_fields = use_signal(Vec::<MetadataFieldConfig>::new);
+    let mut metadata_filter_field = use_signal(String::new);
+    let mut metadata_filter = use_signal(String::new);
+    // Typed results from task_metadata: the order of a metadata sort and
+    // the tasks passing a comparison filter
+    let mut metadata_order = use_signal(Vec::<Uuid>::new);
+    let mut metadata_matches = use_signal(|| None::<HashSet<Uuid>>);
+    // The listed tasks' metadata typed by their fields, for display and
+    // text filters
+    let mut typed_metadata = use_signal(HashMap::<Uuid, HashMap<String, MetadataValue>>::new);
     
     // Keyboard shortcuts
     let handle_keydown = move |e: KeyboardEvent| {
@@ -107,6 +122,69 @@ pub fn ListView() -> Element {
         }
     };
     
+    // Load the configured metadata fields shown in the list, formulas and
+    // relations included
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let repo = repo.clone();
+            spawn(async move {
+                let mut fields: Vec<MetadataFieldConfig> = Vec::new();
+                for config in repo.task_configs.list_all().await.unwrap_or_default() {
+                    for field in config.metadata_schema.fields.into_values() {
+                        if (field.show_in_list || field.sortable)
+                            && !fields.iter().any(|f| f.name == field.name)
+                        {
+                            fields.push(field);
+                        }
+                    }
+                }
+                fields.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+                metadata_fields.set(fields);
+            });
+        }
+    });
+
+    // Sort and filter by metadata through the typed index, so "$90" sorts
+    // below "$1,200" and "8" compares as a number only in number fields
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let repo = repo.clone();
+            let sort_value = sort_by();
+            let (field_name, filter) = (metadata_filter_field(), metadata_filter());
+            let fields = metadata_fields();
+            let task_ids: Vec<Uuid> = tasks().iter().map(|task| task.id).collect();
+            spawn(async move {
+                let sort_field = match sort_value.split_once(':') {
+                    Some(("meta_asc", name)) => Some((name.to_string(), false)),
+                    Some(("meta_desc", name)) => Some((name.to_string(), true)),
+                    _ => None,
+                };
+                let order = match sort_field {
+                    Some((field, descending)) => {
+                        let query = MetadataQuery { field, descending, ..Default::default() };
+                        repo.task_metadata.find_tasks(&query).await
+                    }
+                    None => Ok(Vec::new()),
+                };
+                let matches = match fields.iter().find(|f| f.name == field_name) {
+                    Some(field) => find_metadata_matches(&repo, field, &filter, &task_ids).await,
+                    None => Ok(None),
+                };
+                match (order, matches) {
+                    (Ok(order), Ok(matches)) => {
+                        metadata_order.set(order);
+                        metadata_matches.set(matches);
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        error_message.set(format!("Failed to filter by metadata: {}", e));
+                    }
+                }
+            });
+        }
+    });
+
     // Load tasks on mount and when filter changes
     use_effect({
         let repo = repository.clone();
@@ -147,6 +225,21 @@ pub fn ListView() -> Element {
         }
     });
     
+    // Type the metadata of the tasks listed
+    use_effect({
+        let repo = repository.clone();
+        move || {
+            let repo = repo.clone();
+            let task_ids: Vec<Uuid> = tasks().iter().map(|task| task.id).collect();
+            spawn(async move {
+                match repo.task_metadata.get_for_tasks(&task_ids).await {
+                    Ok(metadata) => typed_metadata.set(metadata),
+                    Err(e) => error_message.set(format!("Failed to load metadata: {}", e)),
+                }
+            });
+        }
+    });
+    
     // Handle task status update
     let update_task_status = {
         let repo = repository.clone();
@@ -159,8 +252,8 @@ pub fn ListView() -> Element {
                 updated_task.status = new_status;
                 updated_task.updated_at = chrono::Utc::now();
                 
-                match repo.tasks.update(&updated_task).await {
-                    Ok(_) => {
+                match TaskService::new(repo.clone()).update(updated_task).await {
+                    Ok(updated_task) => {
                         // Update local state
                         let mut task_list = current_tasks();
                         if let Some(task) = task_list.iter_mut().find(|t| t.id == task_id) {
@@ -246,7 +339,9 @@ pub fn ListView() -> Element {
                                                     let mut updated_task = task.clone();
                                                     updated_task.status = TaskStatus::Done;
                                                     updated_task.updated_at = chrono::Utc::now();
-                                                    let _ = repo.tasks.update(&updated_task).await;
+                                                    if let Err(e) = TaskService::new(repo.clone()).update(updated_task).await {
+                                                        error_message.set(format!("Failed to update task: {}", e));
+                                                    }
                                                 }
                                             }
                                         });
@@ -308,6 +403,10 @@ pub fn ListView() -> Element {
                         option { value: "title_asc", "Title (A-Z)" }
                         option { value: "title_desc", "Title (Z-A)" }
                         option { value: "status", "Status" }
+                        for field in metadata_fields().into_iter().filter(|f| f.sortable) {
+                            option { value: "meta_asc:{field.name}", "{field.display_name} (Ascending)" }
+                            option { value: "meta_desc:{field.name}", "{field.display_name} (Descending)" }
+                        }
                     }
                     
                     // Status filter
@@ -321,6 +420,28 @@ pub fn ListView() -> Element {
                         option { value: "done", "Done" }
                         option { value: "blocked", "Blocked" }
                     }
+
+                    // Metadata filter, e.g. "Estimate" with ">= 8"
+                    if !metadata_fields().is_empty() {
+                        select {
+                            style: "padding: 8px 12px; border: 1px solid #e5e7eb; border-radius: 6px;",
+                            value: "{metadata_filter_field}",
+                            onchange: move |e| metadata_filter_field.set(e.value()),
+                            option { value: "", "Any Field" }
+                            for field in metadata_fields() {
+                                option { value: "{field.name}", "{field.display_name}" }
+                            }
+                        }
+                        if !metadata_filter_field().is_empty() {
+                            input {
+                                r#type: "text",
+                                style: "padding: 8px 12px; border: 1px solid #e5e7eb; border-radius: 6px; width: 120px; font-size: 14px;",
+                                placeholder: "e.g. >= 8",
+                                value: "{metadata_filter}",
+                                oninput: move |e| metadata_filter.set(e.value()),
+                            }
+                        }
+                    }
                     
                     // Task count - use memoized count
                     div {
@@ -329,15 +450,18 @@ pub fn ListView() -> Element {
                             // Use memoized task count to avoid recalculating
                             let count = use_memo(move || {
                                 let query = search_query.read().to_lowercase();
-                                if query.is_empty() {
-                                    tasks().len()
-                                } else {
-                                    tasks().into_iter().filter(|task| {
-                                        task.title.to_lowercase().contains(&query) ||
-                                        task.description.to_lowercase().contains(&query) ||
-                                        task.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
-                                    }).count()
-                                }
+                                let (field, filter) = (metadata_filter_field(), metadata_filter());
+                                let matches = metadata_matches();
+                                let typed = typed_metadata.read();
+                                tasks().into_iter().filter(|task| {
+                                    let found = matches.as_ref().map(|m| m.contains(&task.id));
+                                    matches_metadata_filter(typed.get(&task.id), &field, &filter, found)
+                                }).filter(|task| {
+                                    query.is_empty() ||
+                                    task.title.to_lowercase().contains(&query) ||
+                                    task.description.to_lowercase().contains(&query) ||
+                                    task.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+                                }).count()
                             });
                             format!("{} tasks", count())
                         }
@@ -375,7 +499,16 @@ pub fn ListView() -> Element {
                         let filtered_sorted_tasks = use_memo(move || {
                             let query = search_query.read().to_lowercase();
                             let sort_value = sort_by.read().clone();
-                            let all_tasks = tasks();
+                            let (field, filter) = (metadata_filter_field(), metadata_filter());
+                            let matches = metadata_matches();
+                            let typed = typed_metadata.read();
+                            let all_tasks: Vec<Task> = tasks()
+                                .into_iter()
+                                .filter(|task| {
+                                    let found = matches.as_ref().map(|m| m.contains(&task.id));
+                                    matches_metadata_filter(typed.get(&task.id), &field, &filter, found)
+                                })
+                                .collect();
                             
                             // Filter tasks
                             let mut filtered_tasks = if query.is_empty() {
@@ -459,6 +592,18 @@ pub fn ListView() -> Element {
                                     };
                                     a_status.cmp(&b_status)
                                 }),
+                                other if other.starts_with("meta_") => {
+                                    // Metadata fields, in the order task_metadata
+                                    // returns; tasks without a value go last
+                                    let positions: HashMap<Uuid, usize> = metadata_order()
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(position, id)| (id, position))
+                                        .collect();
+                                    filtered_tasks.sort_by_key(|task| {
+                                        positions.get(&task.id).copied().unwrap_or(usize::MAX)
+                                    });
+                                }
                                 _ => {}
                             }
                             
@@ -494,6 +639,8 @@ pub fn ListView() -> Element {
                                         }
                                     },
                                     bulk_mode: *bulk_mode.read(),
+                                    fields: metadata_fields(),
+                                    metadata: typed_metadata.read().get(&task.id).cloned().unwrap_or_default(),
                                 }
                             }
                         }
@@ -583,6 +730,8 @@ fn TaskCard(
     on_status_change: EventHandler<(uuid::Uuid, TaskStatus)>,
     on_edit: EventHandler<Task>,
     bulk_mode: bool,
+    fields: Vec<MetadataFieldConfig>,
+    metadata: HashMap<String, MetadataValue>,
 ) -> Element {
     let time_tracking_service = use_context::<Arc<TimeTrackingService>>();
     let is_tracking = time_tracking_service.is_tracking(task.id);
@@ -693,6 +842,12 @@ fn TaskCard(
                                 "Due: {due.with_timezone(&Local).format(\"%b %d, %Y\")}"
                             }
                         }
+
+                        for field in fields.iter().filter(|f| f.show_in_list) {
+                            if let Some(value) = metadata.get(&field.name) {
+                                div { "{field.display_name}: {value}" }
+                            }
+                        }
                     }
                 }
                 
@@ -771,4 +926,64 @@ fn TaskCard(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Whether a task passes the list's metadata filter; no field chosen
+/// passes everything. `found` says whether task_metadata found the task
+/// for a comparison filter; other filters search the typed `values`.
+fn matches_metadata_filter(
+    values: Option<&HashMap<String, MetadataValue>>,
+    field: &str,
+    filter: &str,
+    found: Option<bool>,
+) -> bool {
+    match found {
+        _ if field.is_empty() => true,
+        Some(found) => found,
+        None => {
+            let value = values.and_then(|values| values.get(field)).map(|v| v.to_string());
+            MetadataFieldConfig::matches_filter(value.as_deref(), filter)
+        }
+    }
+}
+
+/// The tasks passing a comparison filter such as ">= 8", compared as the
+/// field's type. `None` for filters left to `matches_filter`: text
+/// searches, list fields and operands the field can't read.
+async fn find_metadata_matches(
+    repository: &Repository,
+    field: &MetadataFieldConfig,
+    filter: &str,
+    task_ids: &[Uuid],
+) -> anyhow::Result<Option<HashSet<Uuid>>> {
+    let filter = filter.trim();
+    let Some((operator, operand)) = [">=", "<=", "!=", ">", "<", "="]
+        .into_iter()
+        .find_map(|operator| Some((operator, filter.strip_prefix(operator)?.trim())))
+    else {
+        return Ok(None);
+    };
+    let value = match MetadataValue::parse(field.field_type, operand) {
+        Ok(MetadataValue::List(_)) | Err(_) => return Ok(None),
+        Ok(value) => value,
+    };
+
+    let find = |min: Option<MetadataValue>, max: Option<MetadataValue>| {
+        let query = MetadataQuery { field: field.name.clone(), min, max, descending: false };
+        async move {
+            let ids = repository.task_metadata.find_tasks(&query).await?;
+            anyhow::Ok(ids.into_iter().collect::<HashSet<Uuid>>())
+        }
+    };
+    let equal = find(Some(value.clone()), Some(value.clone())).await?;
+    let matches = match operator {
+        ">=" => find(Some(value), None).await?,
+        "<=" => find(None, Some(value)).await?,
+        ">" => &find(Some(value), None).await? - &equal,
+        "<" => &find(None, Some(value)).await? - &equal,
+        "=" => equal,
+        // Tasks without a value pass, as with matches_filter
+        _ => task_ids.iter().copied().filter(|id| !equal.contains(id)).collect(),
+    };
+    Ok(Some(matches))
+}
@@ -33,6 +33,7 @@ mod tests {
             subtasks: vec![],
             is_archived: false,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,        }
     }
     
@@ -198,6 +198,7 @@ mod tests {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 0,
         };
         
@@ -224,6 +225,7 @@ mod tests {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 1,
         };
         
@@ -250,6 +252,7 @@ mod tests {
             is_archived: false,
             assignee: None,
             configuration_id: None,
+            workflow_state: None,
             sort_order: 2,
         };
         
@@ -5,12 +5,13 @@ use crate::repository::{Repository, database::init_database};
 use crate::ui_dioxus::components::{TaskEditModal, ConfirmationDialog, ClaudeOutputModal};
 use crate::services::{
     AutoRunOrchestrator, AutoRunStatus, AutoRunConfig, TaskExecutionStatus,
-    ClaudeCodeService, DependencyService, TaskService,
+    ClaudeCodeService, DependencyService, TaskService, TopicCluster, TopicService,
 };
+use crate::services::summarization::{SummarizationLevel, SummarizationService, task_summary_source};
 use uuid::Uuid;
 use std::env::current_dir;
 use std::sync::Arc;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug, PartialEq)]
 struct DragState {
@@ -36,6 +37,17 @@ pub fn MapView() -> Element {
     let mut deleting_task = use_signal(|| None::<(Uuid, String)>);
     let mut showing_claude_output = use_signal(|| None::<Uuid>);
     
+    // Semantic zoom: zoomed-out cards show a summary instead of the description
+    let mut card_summaries = use_signal(HashMap::<Uuid, String>::new);
+    let summary_level = use_memo(move || SummarizationLevel::for_zoom(*zoom.read()));
+    let summary_sources = use_memo(move || {
+        tasks.read().iter().map(|t| (t.id, task_summary_source(t))).collect::<Vec<_>>()
+    });
+    
+    // Topic clusters from the last "Arrange by topic", labelled on the map
+    let mut topic_clusters = use_signal(Vec::<TopicCluster>::new);
+    let mut arranging = use_signal(|| false);
+    
     // Autoplay state
     let mut autoplay_status = use_signal(|| AutoRunStatus::Idle);
     let mut autoplay_orchestrator: Signal<Option<Arc<AutoRunOrchestrator>>> = use_signal(|| None);
@@ -104,6 +116,34 @@ pub fn MapView() -> Element {
         });
     });
     
+    // Show stored summaries for the zoom level at once, then fill in the
+    // missing ones in the background
+    use_effect(move || {
+        let level = summary_level();
+        let sources = summary_sources();
+        card_summaries.write().clear();
+        let Some(level) = level else {
+            return;
+        };
+        let Some(Some(repo)) = repository.read().as_ref().cloned() else {
+            return;
+        };
+        spawn(async move {
+            let settings = repo.app_settings.get_or_create_default().await.unwrap_or_default();
+            let service = SummarizationService::from_settings(&settings).with_store(repo.summaries.clone());
+            if let Ok(stored) = service.stored_summaries(&sources, level).await {
+                card_summaries.set(stored);
+            }
+            let _ = service
+                .warm(sources, &[level], 4, |task_id, warmed_level, summary| {
+                    if *summary_level.peek() == Some(warmed_level) {
+                        card_summaries.write().insert(task_id, summary);
+                    }
+                })
+                .await;
+        });
+    });
+    
     // Helper to get task connection points
     let get_task_left_point = |task: &Task| -> (f64, f64) {
         (task.position.x, task.position.y + 30.0)
@@ -183,6 +223,36 @@ pub fn MapView() -> Element {
                 
                 span { style: "margin-left: 20px;", "Zoom: {(*zoom.read() * 100.0) as i32}%" }
                 
+                button {
+                    disabled: *arranging.read(),
+                    onclick: move |_| {
+                        let Some(Some(repo)) = repository.read().as_ref().cloned() else {
+                            return;
+                        };
+                        arranging.set(true);
+                        spawn(async move {
+                            let settings = repo.app_settings.get_or_create_default().await.unwrap_or_default();
+                            let repo = Arc::new(repo);
+                            let service = TopicService::from_settings(repo.clone(), &settings);
+                            match service.arrange_by_topic(6).await {
+                                Ok(clusters) => {
+                                    use crate::repository::task_repository::TaskFilters;
+                                    if let Ok(loaded_tasks) = repo.tasks.list(TaskFilters::default()).await {
+                                        tasks.set(loaded_tasks);
+                                    }
+                                    topic_clusters.set(clusters);
+                                }
+                                Err(e) => {
+                                    error_message.set(Some(format!("Failed to arrange by topic: {}", e)));
+                                }
+                            }
+                            arranging.set(false);
+                        });
+                    },
+                    style: "padding: 8px 12px; background: #607D8B; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                    if *arranging.read() { "Arranging..." } else { "🧩 Arrange by topic" }
+                }
+                
                 // Autoplay controls
                 div {
                     style: "margin-left: auto; display: flex; align-items: center; gap: 10px;",
@@ -641,6 +711,15 @@ pub fn MapView() -> Element {
                         hover_left_node.set(None);
                     },
                     
+                    // Topic labels above each arranged cluster
+                    for cluster in topic_clusters.read().clone() {
+                        div {
+                            key: "{cluster.origin.x}-{cluster.origin.y}",
+                            style: "position: absolute; left: {cluster.origin.x}px; top: {cluster.origin.y - 36.0}px; max-width: 220px; padding: 4px 10px; background: #ECEFF1; color: #37474F; border-radius: 12px; font-size: 13px; font-weight: 600; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; pointer-events: none;",
+                            "{cluster.label} ({cluster.task_ids.len()})"
+                        }
+                    }
+                    
                     // Render tasks
                     for task in tasks.read().clone() {
                         {
@@ -653,6 +732,7 @@ pub fn MapView() -> Element {
                             rsx! {
                                 TaskCard {
                                     task: task.clone(),
+                                    summary: card_summaries.read().get(&task.id).cloned(),
                                     selected: selected_task.read().as_ref() == Some(&task.id),
                                     dragging: dragging_task.read().as_ref() == Some(&task.id),
                                     is_highlighted: is_highlighted,
@@ -791,6 +871,7 @@ pub fn MapView() -> Element {
 #[component]
 fn TaskCard(
     task: Task,
+    summary: Option<String>,
     selected: bool,
     dragging: bool,
     is_highlighted: bool,
@@ -808,6 +889,7 @@ fn TaskCard(
     on_edit: EventHandler<MouseEvent>,
     ondelete: EventHandler<MouseEvent>
 ) -> Element {
+    let repository = use_context::<Arc<Repository>>();
     let status_color = match task.status {
         TaskStatus::Todo => "#808080",
         TaskStatus::InProgress => "#2196F3",
@@ -963,7 +1045,12 @@ fn TaskCard(
                     }
                 }
                 
-                if !task.description.is_empty() {
+                if let Some(summary) = summary {
+                    p {
+                        style: "margin: 2px 0 0 0; font-size: 11px; color: #666; font-style: italic;",
+                        "{summary}"
+                    }
+                } else if !task.description.is_empty() {
                     p {
                         style: "margin: 2px 0 0 0; font-size: 11px; color: #666;",
                         "{task.description}"
@@ -998,12 +1085,25 @@ fn TaskCard(
                                         // Otherwise, launch Claude Code
                                         let task_clone = task.clone();
                                         let task_id = task.id;
+                                        let repository = repository.clone();
                                         spawn(async move {
                                             use crate::services::ClaudeAutomation;
                                             use std::env::current_dir;
                                             
+                                            // The configuration picks the agent backend for the task
+                                            let config = match repository.claude_code.get_config().await {
+                                                Ok(Some(config)) => config,
+                                                Ok(None) => {
+                                                    eprintln!("❌ Claude Code is not configured");
+                                                    return;
+                                                }
+                                                Err(e) => {
+                                                    eprintln!("❌ Failed to load Claude Code config: {}", e);
+                                                    return;
+                                                }
+                                            };
                                             let workspace_dir = current_dir().unwrap_or_default();
-                                            let automation = ClaudeAutomation::new(workspace_dir);
+                                            let automation = ClaudeAutomation::new(workspace_dir, config);
                                             let repo_url = "https://github.com/user/repo.git";
                                             
                                             match automation.execute_task(&task_clone, repo_url).await {
@@ -1,8 +1,11 @@
 use dioxus::prelude::*;
+use crate::services::CalendarFeed;
+use std::sync::Arc;
 use crate::ui_dioxus::components::{
     AppearanceSettings, 
     ClaudeConfigAdmin, 
     GeneralSettings, 
+    LlmSettings,
     WorkspaceSettings
 };
 
@@ -52,6 +55,13 @@ pub fn SettingsView() -> Element {
                     onclick: move |_| active_tab.set("workspace".to_string())
                 }
                 
+                TabButton {
+                    label: "Language Model",
+                    icon: "🧠",
+                    active: active_tab() == "llm",
+                    onclick: move |_| active_tab.set("llm".to_string())
+                }
+                
                 TabButton {
                     label: "Integrations",
                     icon: "🔗",
@@ -75,6 +85,8 @@ pub fn SettingsView() -> Element {
                     GeneralSettings {}
                 } else if active_tab() == "workspace" {
                     WorkspaceSettings {}
+                } else if active_tab() == "llm" {
+                    LlmSettings {}
                 } else if active_tab() == "integrations" {
                     IntegrationsSettings {}
                 } else if active_tab() == "appearance" {
@@ -88,6 +100,9 @@ pub fn SettingsView() -> Element {
 // Keep IntegrationsSettings component here since it's still a placeholder
 #[component]
 fn IntegrationsSettings() -> Element {
+    let calendar_feed = try_use_context::<Signal<Option<Arc<CalendarFeed>>>>()
+        .and_then(|feed| feed.read().as_ref().map(|feed| feed.webcal_url()));
+    
     rsx! {
         div { class: "settings-panel",
             style: "background: white; padding: 30px; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,0.1);",
@@ -118,6 +133,29 @@ fn IntegrationsSettings() -> Element {
                         description: "AI-powered code generation"
                     }
                     
+                    div {
+                        style: "padding: 16px; background: #f9fafb; border-radius: 6px; display: flex; align-items: center; gap: 16px;",
+                        
+                        div { 
+                            style: "font-size: 2rem;",
+                            "📅" 
+                        }
+                        
+                        div { style: "flex: 1;",
+                            div { style: "font-weight: 600; margin-bottom: 4px;", "Calendar feed" }
+                            if let Some(url) = &calendar_feed {
+                                div { style: "font-size: 0.875rem; color: #6b7280;",
+                                    "Subscribe in your calendar app: "
+                                    a { href: "{url}", style: "font-family: monospace;", "{url}" }
+                                }
+                            } else {
+                                div { style: "font-size: 0.875rem; color: #6b7280;",
+                                    "Enable calendar sync with the 'ics' provider to serve a feed"
+                                }
+                            }
+                        }
+                    }
+                    
                     IntegrationCard {
                         name: "Slack",
                         icon: "💬",